@@ -0,0 +1,369 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use crate::types::{EventDetail, Family, Gedcom, Individual, Restriction};
+
+// Filter a Gedcom by the restrictions (RESN) recorded in it, so that a server
+// can hand out only what the people who kept the tree meant to be shared.
+//
+// A record whose restriction hides it is treated according to the strongest
+// restriction: a confidential record is removed, along with every pointer to
+// it, while a private one is kept as a placeholder, with its links to other
+// records but nothing else, so the shape of the tree is kept. An event that's
+// hidden is removed, along with its notes, sources and media. Locked records
+// and events are always shown: locking stops changes, not viewing.
+
+/// Who's looking at a Gedcom, and so which restrictions apply to them. See
+/// `Gedcom::filtered`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AccessLevel {
+    /// Anyone: confidential and private records and events are hidden
+    #[default]
+    Public,
+    /// Someone trusted with private details, i.e. a relative, but not with
+    /// what's confidential
+    Privileged,
+    /// The people who keep the tree, who see everything
+    Owner,
+}
+
+impl AccessLevel {
+    /// Whether a restriction hides what it's on from this level
+    pub fn hides(&self, restriction: Restriction) -> bool {
+        match self {
+            AccessLevel::Public => restriction != Restriction::Locked,
+            AccessLevel::Privileged => restriction == Restriction::Confidential,
+            AccessLevel::Owner => false,
+        }
+    }
+
+    /// How much of a record with these restrictions can be seen
+    fn access(&self, restrictions: &[Restriction]) -> Access {
+        let hidden: Vec<Restriction> = restrictions
+            .iter()
+            .copied()
+            .filter(|r| self.hides(*r))
+            .collect();
+        if hidden.contains(&Restriction::Confidential) {
+            Access::None
+        } else if hidden.is_empty() {
+            Access::Full
+        } else {
+            Access::Placeholder
+        }
+    }
+
+    /// Whether an event can be seen
+    fn shows(&self, detail: Option<&EventDetail>) -> bool {
+        detail.is_none_or(|d| self.access(&d.restrictions()) == Access::Full)
+    }
+}
+
+impl FromStr for AccessLevel {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<AccessLevel, Self::Err> {
+        match input.to_lowercase().as_str() {
+            "public" => Ok(AccessLevel::Public),
+            "privileged" => Ok(AccessLevel::Privileged),
+            "owner" => Ok(AccessLevel::Owner),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Access {
+    Full,
+    /// Only the record's links to other records
+    Placeholder,
+    None,
+}
+
+/// A copy of the Gedcom with whatever its restrictions hide from `view`
+/// taken out.
+///
+/// The original text of the records (`Gedcom::raw_records`) and the warnings
+/// from parsing aren't copied, since they may quote what's been hidden, and
+/// neither is any precomputed kinship.
+pub fn filtered(gedcom: &Gedcom, view: AccessLevel) -> Gedcom {
+    let mut removed: HashSet<String> = HashSet::new();
+    let mut filtered = Gedcom {
+        header: gedcom.header.clone(),
+        sources: gedcom.sources.clone(),
+        multimedia: gedcom.multimedia.clone(),
+        repositories: gedcom.repositories.clone(),
+        decoding: gedcom.decoding.clone(),
+        archive: gedcom.archive.clone(),
+        ..Default::default()
+    };
+
+    for individual in &gedcom.individuals {
+        match view.access(&individual.restrictions()) {
+            Access::Full => {
+                let mut individual = individual.clone();
+                retain_individual_events(&mut individual, view);
+                filtered.individuals.push(individual);
+            }
+            Access::Placeholder => filtered.individuals.push(Individual {
+                xref: individual.xref.clone(),
+                famc: individual.famc.iter().map(family_link).collect(),
+                fams: individual.fams.iter().map(family_link).collect(),
+                restriction_notice: individual.restriction_notice.clone(),
+                ..Default::default()
+            }),
+            Access::None => removed.extend(individual.xref.clone()),
+        }
+    }
+    for family in &gedcom.families {
+        match view.access(&family.restrictions()) {
+            Access::Full => {
+                let mut family = family.clone();
+                retain_family_events(&mut family, view);
+                filtered.families.push(family);
+            }
+            Access::Placeholder => filtered.families.push(Family {
+                xref: family.xref.clone(),
+                husband: family.husband.clone(),
+                wife: family.wife.clone(),
+                children: family.children.clone(),
+                restriction_notice: family.restriction_notice.clone(),
+                ..Default::default()
+            }),
+            Access::None => {
+                removed.insert(family.xref.clone());
+            }
+        }
+    }
+
+    // Nothing may point to what's been removed
+    let kept = |xref: &str| !removed.contains(xref);
+    for individual in &mut filtered.individuals {
+        individual.famc.retain(|f| kept(&f.xref));
+        individual.fams.retain(|f| kept(&f.xref));
+        individual.associations.retain(|a| kept(&a.xref));
+        individual.aliases.retain(|a| kept(a));
+        let families = individual
+            .birth
+            .iter_mut()
+            .map(|b| &mut b.family)
+            .chain(individual.adoption.iter_mut().map(|a| &mut a.family))
+            .chain(individual.christening.iter_mut().map(|c| &mut c.family))
+            .chain(
+                individual
+                    .christening_adult
+                    .iter_mut()
+                    .map(|c| &mut c.family),
+            )
+            .chain(individual.death.iter_mut().map(|d| &mut d.family));
+        for family in families {
+            if family.as_ref().is_some_and(|f| !kept(&f.xref)) {
+                *family = None;
+            }
+        }
+    }
+    for family in &mut filtered.families {
+        for spouse in [&mut family.husband, &mut family.wife] {
+            let xref = spouse
+                .as_ref()
+                .and_then(|s| s.xref.as_ref())
+                .and_then(|x| x.xref.as_deref());
+            if xref.is_some_and(|x| !kept(x)) {
+                *spouse = None;
+            }
+        }
+        family.children.retain(|c| kept(c));
+    }
+    if filtered
+        .header
+        .home_person
+        .as_deref()
+        .is_some_and(|x| !kept(x))
+    {
+        filtered.header.home_person = None;
+    }
+
+    filtered.record_positions = gedcom
+        .record_positions
+        .iter()
+        .filter(|(xref, _)| kept(xref))
+        .map(|(xref, position)| (xref.clone(), *position))
+        .collect();
+    filtered.skipped_text = gedcom
+        .skipped_text
+        .iter()
+        .filter(|s| s.xref.as_deref().is_none_or(kept))
+        .cloned()
+        .collect();
+    filtered
+        .decoding
+        .records
+        .retain(|r| r.xref.as_deref().is_none_or(kept));
+
+    filtered
+}
+
+/// A FAMC or FAMS link, without its notes
+fn family_link(family: &Family) -> Family {
+    Family {
+        xref: family.xref.clone(),
+        pedigree: family.pedigree.clone(),
+        ..Default::default()
+    }
+}
+
+fn retain_individual_events(individual: &mut Individual, view: AccessLevel) {
+    individual
+        .birth
+        .retain(|b| view.shows(Some(&b.event.detail)));
+    individual
+        .adoption
+        .retain(|a| view.shows(Some(&a.event.detail)));
+    individual
+        .christening
+        .retain(|c| view.shows(Some(&c.event.detail)));
+    individual
+        .christening_adult
+        .retain(|c| view.shows(Some(&c.event.detail)));
+    individual.death.retain(|d| view.shows(d.event.as_ref()));
+    individual
+        .residences
+        .retain(|r| view.shows(r.detail.as_ref().and_then(|d| d.detail.as_ref())));
+    if !view.shows(individual.first_communion.as_ref().map(|e| &e.detail)) {
+        individual.first_communion = None;
+    }
+    for list in [
+        &mut individual.baptism,
+        &mut individual.barmitzvah,
+        &mut individual.basmitzvah,
+        &mut individual.blessing,
+        &mut individual.burial,
+        &mut individual.census,
+        &mut individual.confirmation,
+        &mut individual.cremation,
+        &mut individual.emigration,
+        &mut individual.events,
+        &mut individual.graduation,
+        &mut individual.immigration,
+        &mut individual.naturalization,
+        &mut individual.occupations,
+        &mut individual.probate,
+        &mut individual.retirement,
+        &mut individual.will,
+        &mut individual.caste,
+        &mut individual.physical_description,
+        &mut individual.education,
+        &mut individual.id_numbers,
+        &mut individual.nationality,
+        &mut individual.children_count,
+        &mut individual.marriage_count,
+        &mut individual.property,
+        &mut individual.religion,
+        &mut individual.titles,
+    ] {
+        list.retain(|e| view.shows(Some(&e.detail)));
+    }
+}
+
+fn retain_family_events(family: &mut Family, view: AccessLevel) {
+    for list in [
+        &mut family.annulment,
+        &mut family.census,
+        &mut family.divorce,
+        &mut family.divorce_filed,
+        &mut family.engagement,
+        &mut family.marriage,
+        &mut family.marriage_banns,
+        &mut family.marriage_contract,
+        &mut family.marriage_license,
+        &mut family.marriage_settlement,
+        &mut family.residences,
+        &mut family.events,
+    ] {
+        list.retain(|e| view.shows(e.detail.as_ref()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_gedcom_from_str;
+
+    const DATA: &str = "0 HEAD
+1 _HOME @I3@
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 FAMS @F1@
+1 BIRT
+2 DATE 1900
+1 OCCU Spy
+2 RESN confidential
+2 NOTE Never to be spoken of
+1 RESI
+2 DATE 1950
+2 RESN PRIVACY
+0 @I2@ INDI
+1 RESN privacy
+1 NAME Ann /Bloggs/
+1 FAMS @F1@
+1 NOTE Still living
+0 @I3@ INDI
+1 RESN confidential, locked
+1 NAME Sam /Bloggs/
+1 FAMC @F1@
+0 @I4@ INDI
+1 RESN locked
+1 NAME Tom /Bloggs/
+1 FAMC @F1@
+2 PEDI birth
+0 @F1@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 CHIL @I3@
+1 CHIL @I4@
+1 MARR
+2 DATE 1925
+2 RESN privacy
+0 TRLR
+";
+
+    #[test]
+    fn filter() {
+        let gedcom = parse_gedcom_from_str(DATA).unwrap();
+        assert!(gedcom.individuals[2].restrictions().len() == 2);
+
+        let public = filtered(&gedcom, AccessLevel::Public);
+        let xrefs: Vec<&str> = public
+            .individuals
+            .iter()
+            .filter_map(|i| i.xref.as_deref())
+            .collect();
+        assert!(xrefs == ["@I1@", "@I2@", "@I4@"]);
+        let joe = &public.individuals[0];
+        assert!(joe.birth.len() == 1 && joe.occupations.is_empty() && joe.residences.is_empty());
+        let ann = &public.individuals[1];
+        assert!(ann.names.is_empty() && ann.notes.is_empty());
+        assert!(ann.fams[0].xref == "@F1@");
+        assert!(public.individuals[2].famc[0].pedigree.is_some());
+        let family = &public.families[0];
+        assert!(family.children == ["@I4@"] && family.marriage.is_empty());
+        assert!(family.wife_xref() == Some("@I2@"));
+        assert!(public.header.home_person.is_none());
+        assert!(!public.record_positions.contains_key("@I3@"));
+
+        let privileged = gedcom.filtered(AccessLevel::Privileged);
+        assert!(privileged.individuals.len() == 3);
+        assert!(privileged.individuals[1].names.len() == 1);
+        assert!(privileged.individuals[0].residences.len() == 1);
+        assert!(privileged.individuals[0].occupations.is_empty());
+        assert!(privileged.families[0].marriage.len() == 1);
+
+        let owner = gedcom.filtered(AccessLevel::Owner);
+        assert!(owner.individuals.len() == 4);
+        assert!(owner.individuals[0].occupations[0].detail.note.is_some());
+        assert!(owner.header.home_person.as_deref() == Some("@I3@"));
+
+        assert!("privileged".parse() == Ok(AccessLevel::Privileged));
+        assert!("admin".parse::<AccessLevel>().is_err());
+    }
+}
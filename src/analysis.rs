@@ -0,0 +1,465 @@
+use std::collections::{BTreeMap, HashSet};
+use std::str::FromStr;
+
+use crate::names;
+use crate::statistics::decade;
+use crate::types::{EventDetail, EventType, Gedcom, GedcomDate, Individual, Note, SourceCitation};
+
+/// The markers `research_tasks` looks for, unless told otherwise
+pub const RESEARCH_MARKERS: [&str; 5] = ["TODO", "FIXME", "check", "verify", "research"];
+
+/// The longest snippet kept of a line with a marker, in characters
+const SNIPPET_LENGTH: usize = 200;
+
+/// A research task found in a note. See `research_tasks`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResearchTask<'a> {
+    /// The record the note is in, or "HEAD" for the header
+    pub xref: &'a str,
+    /// What in the record the note is on, i.e. "BIRT" or "NAME", or None
+    /// for the record itself
+    pub tag: Option<&'static str>,
+    /// The marker found, as it was given
+    pub marker: String,
+    /// The line of the note the marker is on, shortened if it's long
+    pub snippet: String,
+}
+
+/// Every line of a note, anywhere in the Gedcom, with one of the `markers`
+/// on it, i.e. "TODO: find the baptism", as a to-do list, in file order.
+/// Markers are matched as whole words, ignoring case, so "check" finds
+/// "Check the census" but not "checked". A line is listed once, for the
+/// first marker found on it.
+pub fn research_tasks<'a>(gedcom: &'a Gedcom, markers: &[&str]) -> Vec<ResearchTask<'a>> {
+    let mut finder = Finder {
+        markers,
+        tasks: vec![],
+    };
+
+    if let Some(note) = &gedcom.header.note {
+        finder.text("HEAD", None, note);
+    }
+    for individual in &gedcom.individuals {
+        let xref = individual.xref.as_deref().unwrap_or_default();
+        finder.notes(xref, None, &individual.notes);
+        finder.citations(xref, Some("SOUR"), &individual.sources);
+        for name in &individual.names {
+            finder.note(xref, Some("NAME"), name.name.note.as_ref());
+        }
+        for event in individual.all_events() {
+            if let Some(detail) = event.detail {
+                finder.event(xref, event.event_type.tag(), detail);
+            }
+        }
+        for family in &individual.famc {
+            finder.notes(xref, Some("FAMC"), &family.notes);
+        }
+        for family in &individual.fams {
+            finder.notes(xref, Some("FAMS"), &family.notes);
+        }
+    }
+    for family in &gedcom.families {
+        finder.notes(&family.xref, None, &family.notes);
+        finder.citations(&family.xref, Some("SOUR"), &family.sources);
+        for (tag, detail) in family.tagged_events() {
+            if let Some(detail) = detail {
+                finder.event(&family.xref, tag, detail);
+            }
+        }
+    }
+    for source in &gedcom.sources {
+        finder.notes(&source.xref, None, &source.notes);
+    }
+    for record in &gedcom.multimedia {
+        finder.notes(&record.xref, None, &record.notes);
+    }
+
+    finder.tasks
+}
+
+struct Finder<'a, 'm> {
+    markers: &'m [&'m str],
+    tasks: Vec<ResearchTask<'a>>,
+}
+
+impl<'a> Finder<'a, '_> {
+    fn event(&mut self, xref: &'a str, tag: &'static str, detail: &EventDetail) {
+        if let Some(note) = &detail.note {
+            self.text(xref, Some(tag), note);
+        }
+        self.note(
+            xref,
+            Some(tag),
+            detail.place.as_ref().and_then(|p| p.note.as_ref()),
+        );
+        self.citations(xref, Some(tag), &detail.sources);
+    }
+
+    fn citations(&mut self, xref: &'a str, tag: Option<&'static str>, sources: &[SourceCitation]) {
+        for source in sources {
+            self.note(xref, tag, source.note.as_ref());
+        }
+    }
+
+    fn notes(&mut self, xref: &'a str, tag: Option<&'static str>, notes: &[Note]) {
+        for note in notes {
+            self.note(xref, tag, Some(note));
+        }
+    }
+
+    fn note(&mut self, xref: &'a str, tag: Option<&'static str>, note: Option<&Note>) {
+        if let Some(text) = note.and_then(|n| n.note.as_deref()) {
+            self.text(xref, tag, text);
+        }
+    }
+
+    fn text(&mut self, xref: &'a str, tag: Option<&'static str>, text: &str) {
+        for line in text.lines() {
+            if let Some(marker) = self.markers.iter().find(|m| has_word(line, m)) {
+                let line = line.trim();
+                let mut snippet: String = line.chars().take(SNIPPET_LENGTH).collect();
+                if snippet.len() < line.len() {
+                    snippet.push('…');
+                }
+                self.tasks.push(ResearchTask {
+                    xref,
+                    tag,
+                    marker: marker.to_string(),
+                    snippet,
+                });
+            }
+        }
+    }
+}
+
+/// Whether `word` is in `text` on its own, rather than as part of a longer
+/// word, ignoring case
+fn has_word(text: &str, word: &str) -> bool {
+    if word.is_empty() {
+        return false;
+    }
+    let text = text.to_lowercase();
+    let word = word.to_lowercase();
+    text.match_indices(&word).any(|(i, _)| {
+        let before = text[..i].chars().next_back();
+        let after = text[i + word.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+/// Where carriers of a surname were recorded in one decade. See
+/// `surname_geography`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SurnamePlace<'a> {
+    /// The place, as named in the events
+    pub place: &'a str,
+    /// The parts of the place name, most specific first, for grouping by
+    /// county, country, etc. See `Place::jurisdictions`.
+    pub jurisdictions: Vec<&'a str>,
+    /// The latitude and longitude of the place, if any of its events has a
+    /// MAP
+    pub coordinates: Option<(f64, f64)>,
+    /// The first year of the decade, i.e. 1900 for the 1900s
+    pub decade: i32,
+    /// How many events happened there
+    pub events: usize,
+    /// How many carriers of the surname those events were for
+    pub individuals: usize,
+}
+
+/// Where and when the carriers of `surname` were recorded: the places of
+/// their events, and of the events of families they're a spouse in, counted
+/// by decade, for drawing a map of how a surname spread. Anyone with the
+/// surname in any of their names is a carrier, ignoring case. Events
+/// without both a place and a date with a year are left out.
+///
+/// Sorted by decade, then the most events first, then by place.
+pub fn surname_geography<'a>(gedcom: &'a Gedcom, surname: &str) -> Vec<SurnamePlace<'a>> {
+    let surname = surname.trim();
+    let carriers: Vec<&Individual> = gedcom
+        .individuals
+        .iter()
+        .filter(|i| has_surname(i, surname))
+        .collect();
+
+    let mut places: BTreeMap<(i32, &str), (SurnamePlace, HashSet<&str>)> = BTreeMap::new();
+    let mut add = |detail: &'a EventDetail, individuals: &[&'a str]| {
+        let place = match &detail.place {
+            Some(place) => place,
+            None => return,
+        };
+        let name = place.name.as_deref().map(str::trim).unwrap_or_default();
+        let year = detail
+            .date
+            .as_deref()
+            .and_then(|d| GedcomDate::from_str(d).ok())
+            .and_then(|d| d.year());
+        let year = match year {
+            Some(year) if !name.is_empty() => year,
+            _ => return,
+        };
+        let (entry, carriers) = places.entry((decade(year), name)).or_insert_with(|| {
+            (
+                SurnamePlace {
+                    place: name,
+                    jurisdictions: place.jurisdictions(),
+                    coordinates: None,
+                    decade: decade(year),
+                    events: 0,
+                    individuals: 0,
+                },
+                HashSet::new(),
+            )
+        });
+        entry.events += 1;
+        if entry.coordinates.is_none() {
+            entry.coordinates = place.map.as_ref().map(|m| (m.latitude, m.longitude));
+        }
+        carriers.extend(individuals);
+    };
+
+    let mut families = HashSet::new();
+    for individual in &carriers {
+        let xref = individual.xref.as_deref().unwrap_or_default();
+        for event in individual.all_events() {
+            if let Some(detail) = event.detail {
+                add(detail, &[xref]);
+            }
+        }
+        for family in gedcom.spouse_families(individual) {
+            if !families.insert(&family.xref) {
+                continue;
+            }
+            // Count the event once, for each spouse who's a carrier
+            let spouses: Vec<&str> = [family.husband_xref(), family.wife_xref()]
+                .iter()
+                .flatten()
+                .copied()
+                .filter(|x| carriers.iter().any(|c| c.xref.as_deref() == Some(x)))
+                .collect();
+            for (_, detail) in family.tagged_events() {
+                if let Some(detail) = detail {
+                    add(detail, &spouses);
+                }
+            }
+        }
+    }
+
+    let mut places: Vec<SurnamePlace> = places
+        .into_values()
+        .map(|(mut place, carriers)| {
+            place.individuals = carriers.len();
+            place
+        })
+        .collect();
+    places.sort_by(|a, b| {
+        a.decade
+            .cmp(&b.decade)
+            .then_with(|| b.events.cmp(&a.events))
+            .then_with(|| a.place.cmp(b.place))
+    });
+    places
+}
+
+/// How many events of a kind happened in each year, e.g. births. Each
+/// individual or family counts once, in the year of the first of their
+/// events of that kind with a date. Approximate dates count in their year,
+/// and ranges in their first year.
+pub fn events_per_year(gedcom: &Gedcom, kind: EventType) -> BTreeMap<i32, usize> {
+    let year = |date: Option<&str>| GedcomDate::from_str(date?).ok()?.year();
+    let individuals = gedcom.individuals.iter().map(|i| {
+        i.all_events()
+            .filter(|e| e.event_type == kind)
+            .find_map(|e| year(e.detail?.date.as_deref()))
+    });
+    let families = gedcom.families.iter().map(|f| {
+        f.tagged_events()
+            .into_iter()
+            .filter(|(tag, _)| *tag == kind.tag())
+            .find_map(|(_, detail)| year(detail?.date.as_deref()))
+    });
+
+    let mut years = BTreeMap::new();
+    for year in individuals.chain(families).flatten() {
+        *years.entry(year).or_insert(0) += 1;
+    }
+    years
+}
+
+/// Whether any of an individual's names has `surname`, ignoring case
+fn has_surname(individual: &Individual, surname: &str) -> bool {
+    individual
+        .names
+        .iter()
+        .any(|name| names::surname(&name.name).is_some_and(|s| s.eq_ignore_ascii_case(surname)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_gedcom_from_str;
+
+    #[test]
+    fn tasks() {
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+1 NOTE Exported for cousin Ann
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+2 NOTE Verify the spelling
+1 BIRT
+2 DATE 1900
+2 NOTE Born at home.
+3 CONT TODO: find the birth certificate
+2 SOUR @S1@
+3 NOTE Checked, and it's right
+1 NOTE @N1@
+1 NOTE todo - ask Aunt Mary about his brothers
+0 @F1@ FAM
+1 HUSB @I1@
+1 MARR
+2 PLAC York
+3 NOTE Which church? check the banns
+1 NOTE Nothing to do here
+0 @S1@ SOUR
+1 NOTE To Research: the rest of the register
+0 TRLR
+",
+        )
+        .unwrap();
+
+        let tasks = research_tasks(&gedcom, &RESEARCH_MARKERS);
+        let found: Vec<(&str, Option<&str>, &str)> = tasks
+            .iter()
+            .map(|t| (t.xref, t.tag, t.marker.as_str()))
+            .collect();
+        assert!(
+            found
+                == [
+                    ("@I1@", None, "TODO"),
+                    ("@I1@", Some("NAME"), "verify"),
+                    ("@I1@", Some("BIRT"), "TODO"),
+                    ("@F1@", Some("MARR"), "check"),
+                    ("@S1@", None, "research"),
+                ]
+        );
+        assert!(tasks[0].snippet == "todo - ask Aunt Mary about his brothers");
+        assert!(tasks[2].snippet == "TODO: find the birth certificate");
+
+        let tasks = research_tasks(&gedcom, &["cousin"]);
+        assert!(tasks.len() == 1 && tasks[0].xref == "HEAD");
+        assert!(research_tasks(&gedcom, &[""]).is_empty());
+    }
+
+    #[test]
+    fn geography() {
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 BIRT
+2 DATE 1901
+2 PLAC York, Yorkshire, England
+3 MAP
+4 LATI N53.96
+4 LONG W1.08
+1 DEAT
+2 DATE 1975
+2 PLAC Leeds, Yorkshire, England
+0 @I2@ INDI
+1 NAME Jane /Doe/
+1 NAME Jane /bloggs/
+2 TYPE married
+1 BIRT
+2 DATE ABT 1905
+2 PLAC York, Yorkshire, England
+1 RESI
+2 PLAC Nowhere
+0 @I3@ INDI
+1 NAME Ann /Smith/
+1 BIRT
+2 DATE 1903
+2 PLAC York, Yorkshire, England
+0 @F1@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 MARR
+2 DATE 1925
+2 PLAC York, Yorkshire, England
+0 TRLR
+",
+        )
+        .unwrap();
+
+        let places = surname_geography(&gedcom, "Bloggs");
+        let found: Vec<(i32, &str, usize, usize)> = places
+            .iter()
+            .map(|p| (p.decade, p.place, p.events, p.individuals))
+            .collect();
+        assert!(
+            found
+                == [
+                    (1900, "York, Yorkshire, England", 2, 2),
+                    (1920, "York, Yorkshire, England", 1, 2),
+                    (1970, "Leeds, Yorkshire, England", 1, 1),
+                ]
+        );
+        assert!(places[0].jurisdictions == ["York", "Yorkshire", "England"]);
+        assert!(places[0].coordinates == Some((53.96, -1.08)));
+        assert!(places[1].coordinates.is_none());
+        assert!(gedcom.surname_geography("Jones").is_empty());
+    }
+
+    #[test]
+    fn events_per_year() {
+        // Only the first dated birth counts: 1905 for @I2@, and 1900 for @I3@
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @I1@ INDI
+1 BIRT
+2 DATE ABT 1900
+1 DEAT
+2 DATE BET 1970 AND 1975
+0 @I2@ INDI
+1 BIRT
+2 DATE 12 MAR 1905
+1 BIRT
+2 DATE 1906
+0 @I3@ INDI
+1 BIRT
+2 PLAC York
+1 BIRT
+2 DATE 1900
+0 @F1@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 MARR
+2 DATE 1929
+0 TRLR
+",
+        )
+        .unwrap();
+
+        let births = super::events_per_year(&gedcom, EventType::Birth);
+        assert!(births.into_iter().collect::<Vec<_>>() == [(1900, 2), (1905, 1)]);
+        assert!(
+            gedcom
+                .events_per_year(EventType::Death)
+                .into_iter()
+                .collect::<Vec<_>>()
+                == [(1970, 1)]
+        );
+        assert!(gedcom.events_per_year(EventType::Marriage).get(&1929) == Some(&1));
+        assert!(gedcom.events_per_year(EventType::Burial).is_empty());
+    }
+
+    #[test]
+    fn words() {
+        assert!(has_word("TODO", "todo"));
+        assert!(has_word("(check)", "check"));
+        assert!(!has_word("rechecked", "check"));
+        assert!(has_word("double-check", "check"));
+        assert!(has_word("ok ?? maybe", "??"));
+        assert!(!has_word("Ärger", "rger"));
+    }
+}
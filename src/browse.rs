@@ -0,0 +1,404 @@
+use std::io;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListState, Paragraph};
+use ratatui::Frame;
+
+use crate::names;
+use crate::search::score_individual_name;
+use crate::types::{Gedcom, Gender, Individual};
+
+// An interactive, terminal-based browser for a Gedcom.
+//
+// The list of individuals is on the left, and the selected individual's
+// events and relatives are on the right. Tab moves between the list and the
+// relatives, Enter jumps to the highlighted relative, and Left goes back.
+// Typing after `/` narrows the list to matching names.
+
+/// Which pane the arrow keys move through
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Focus {
+    Individuals,
+    Relatives,
+}
+
+/// The state of the browser, separate from drawing it, so it can be driven
+/// by key presses without a terminal
+pub struct Browser<'a> {
+    gedcom: &'a Gedcom,
+    /// Indexes into `gedcom.individuals` of those matching the search
+    visible: Vec<usize>,
+    /// The position in `visible` of the selected individual
+    selected: usize,
+    /// The position in `relatives()` of the highlighted relative
+    relative: usize,
+    focus: Focus,
+    search: String,
+    searching: bool,
+    /// The individuals we jumped away from, most recent last
+    history: Vec<usize>,
+}
+
+impl<'a> Browser<'a> {
+    pub fn new(gedcom: &'a Gedcom) -> Browser<'a> {
+        Browser {
+            gedcom,
+            visible: (0..gedcom.individuals.len()).collect(),
+            selected: 0,
+            relative: 0,
+            focus: Focus::Individuals,
+            search: String::new(),
+            searching: false,
+            history: vec![],
+        }
+    }
+
+    /// The individual whose details are shown
+    pub fn selected(&self) -> Option<&'a Individual> {
+        let index = *self.visible.get(self.selected)?;
+        self.gedcom.individuals.get(index)
+    }
+
+    /// The individuals in the list, which narrows as you search
+    pub fn visible(&self) -> Vec<&'a Individual> {
+        self.visible
+            .iter()
+            .map(|i| &self.gedcom.individuals[*i])
+            .collect()
+    }
+
+    /// The selected individual's parents, spouses, and children, with how
+    /// they're related
+    pub fn relatives(&self) -> Vec<(&'static str, &'a Individual)> {
+        let individual = match self.selected() {
+            Some(individual) => individual,
+            None => return vec![],
+        };
+        let parents = self.gedcom.parents(individual).into_iter();
+        let spouses = self.gedcom.spouses(individual).into_iter();
+        let children = self.gedcom.children(individual).into_iter();
+        parents
+            .map(|p| ("Parent", p))
+            .chain(spouses.map(|s| ("Spouse", s)))
+            .chain(children.map(|c| ("Child", c)))
+            .collect()
+    }
+
+    pub fn focus(&self) -> Focus {
+        self.focus
+    }
+
+    /// What's been typed into the search, and whether we're still typing
+    pub fn search(&self) -> (&str, bool) {
+        (&self.search, self.searching)
+    }
+
+    /// Handle a key press. Returns false once the user wants to quit.
+    pub fn key(&mut self, code: KeyCode) -> bool {
+        if self.searching {
+            match code {
+                KeyCode::Char(c) => self.search.push(c),
+                KeyCode::Backspace => {
+                    self.search.pop();
+                }
+                KeyCode::Enter => self.searching = false,
+                KeyCode::Esc => {
+                    self.search.clear();
+                    self.searching = false;
+                }
+                _ => return true,
+            }
+            self.filter();
+            return true;
+        }
+
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return false,
+            KeyCode::Char('/') => {
+                self.searching = true;
+                self.focus = Focus::Individuals;
+            }
+            KeyCode::Tab => {
+                self.focus = match self.focus {
+                    Focus::Individuals if !self.relatives().is_empty() => Focus::Relatives,
+                    _ => Focus::Individuals,
+                };
+            }
+            KeyCode::Up => match self.focus {
+                Focus::Individuals => self.select(self.selected.saturating_sub(1)),
+                Focus::Relatives => self.relative = self.relative.saturating_sub(1),
+            },
+            KeyCode::Down => match self.focus {
+                Focus::Individuals => self.select(self.selected + 1),
+                Focus::Relatives => {
+                    self.relative = (self.relative + 1).min(self.relatives().len() - 1)
+                }
+            },
+            KeyCode::Enter | KeyCode::Right if self.focus == Focus::Relatives => {
+                if let Some((_, relative)) = self.relatives().get(self.relative) {
+                    if let Some(current) = self.visible.get(self.selected) {
+                        self.history.push(*current);
+                    }
+                    self.jump(relative);
+                }
+            }
+            KeyCode::Left | KeyCode::Backspace => {
+                if let Some(index) = self.history.pop() {
+                    self.jump(&self.gedcom.individuals[index]);
+                }
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn select(&mut self, position: usize) {
+        if position < self.visible.len() {
+            self.selected = position;
+            self.relative = 0;
+            self.focus = Focus::Individuals;
+        }
+    }
+
+    /// Select an individual, clearing the search if it hides them
+    fn jump(&mut self, individual: &Individual) {
+        let index = match self
+            .gedcom
+            .individuals
+            .iter()
+            .position(|i| std::ptr::eq(i, individual))
+        {
+            Some(index) => index,
+            None => return,
+        };
+        if !self.visible.contains(&index) {
+            self.search.clear();
+            self.filter();
+        }
+        if let Some(position) = self.visible.iter().position(|i| *i == index) {
+            self.select(position);
+        }
+    }
+
+    /// Narrow the list to the individuals whose names match the search,
+    /// keeping the selected individual selected if they still match
+    fn filter(&mut self) {
+        let current = self.visible.get(self.selected).copied();
+        self.visible = self
+            .gedcom
+            .individuals
+            .iter()
+            .enumerate()
+            .filter(|(_, i)| {
+                self.search.trim().is_empty() || score_individual_name(i, &self.search).is_some()
+            })
+            .map(|(index, _)| index)
+            .collect();
+        self.selected = current
+            .and_then(|c| self.visible.iter().position(|i| *i == c))
+            .unwrap_or(0);
+        self.relative = 0;
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let [main, status] =
+            Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).areas(frame.area());
+        let [list, detail] =
+            Layout::horizontal([Constraint::Percentage(40), Constraint::Percentage(60)])
+                .areas(main);
+
+        let highlight = Style::default().add_modifier(Modifier::REVERSED);
+
+        let names: Vec<String> = self.visible().iter().map(|i| label(i)).collect();
+        let title = format!("Individuals ({})", names.len());
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(
+            List::new(names)
+                .block(Block::bordered().title(title))
+                .highlight_style(highlight),
+            list,
+            &mut state,
+        );
+
+        let relatives: Vec<String> = self
+            .relatives()
+            .iter()
+            .map(|(relationship, individual)| format!("{}: {}", relationship, label(individual)))
+            .collect();
+        let [events, family] = Layout::vertical([
+            Constraint::Min(1),
+            Constraint::Length(relatives.len() as u16 + 2),
+        ])
+        .areas(detail);
+
+        let mut lines = vec![];
+        if let Some(individual) = self.selected() {
+            lines.push(Line::from(label(individual)));
+            lines.push(Line::from(format!("Sex: {}", gender(&individual.gender))));
+            lines.push(Line::from(""));
+            for (tag, detail) in individual.tagged_events() {
+                let mut line = tag.to_string();
+                if let Some(detail) = detail {
+                    let parts: Vec<&str> = detail
+                        .date
+                        .as_deref()
+                        .into_iter()
+                        .chain(detail.place.as_ref().and_then(|p| p.name.as_deref()))
+                        .collect();
+                    if !parts.is_empty() {
+                        line += &format!("  {}", parts.join(", "));
+                    }
+                }
+                lines.push(Line::from(line));
+            }
+        }
+        frame.render_widget(
+            Paragraph::new(lines).block(Block::bordered().title("Details")),
+            events,
+        );
+
+        let mut state = ListState::default();
+        if self.focus == Focus::Relatives {
+            state.select(Some(self.relative));
+        }
+        frame.render_stateful_widget(
+            List::new(relatives)
+                .block(Block::bordered().title("Relatives"))
+                .highlight_style(highlight),
+            family,
+            &mut state,
+        );
+
+        let help = if self.searching {
+            format!("/{}", self.search)
+        } else {
+            "↑↓ move  Tab relatives  Enter go  ← back  / search  q quit".to_string()
+        };
+        frame.render_widget(Paragraph::new(help), status);
+    }
+}
+
+/// Browse the Gedcom in the terminal until the user quits
+pub fn browse(gedcom: &Gedcom) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    let mut browser = Browser::new(gedcom);
+    let result = loop {
+        if let Err(err) = terminal.draw(|frame| browser.draw(frame)) {
+            break Err(err);
+        }
+        match event::read() {
+            Ok(Event::Key(key)) if key.kind == KeyEventKind::Press => {
+                if !browser.key(key.code) {
+                    break Ok(());
+                }
+            }
+            Ok(_) => {}
+            Err(err) => break Err(err),
+        }
+    };
+    ratatui::restore();
+    result
+}
+
+/// An individual's name, without the slashes around the surname, and xref
+fn label(individual: &Individual) -> String {
+    let name = individual
+        .names
+        .first()
+        .and_then(|n| n.name.value.as_deref())
+        .map(names::strip_slashes)
+        .unwrap_or_else(|| "(no name)".to_string());
+    format!(
+        "{} {}",
+        name,
+        individual.xref.as_deref().unwrap_or_default()
+    )
+}
+
+fn gender(gender: &Gender) -> &str {
+    match gender {
+        Gender::Male => "male",
+        Gender::Female => "female",
+        Gender::Nonbinary => "nonbinary",
+        Gender::Unknown => "unknown",
+        Gender::Other(value) => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_gedcom_from_str;
+
+    #[test]
+    fn navigate() {
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 FAMS @F1@
+0 @I2@ INDI
+1 NAME Jane /Doe/
+1 FAMS @F1@
+0 @I3@ INDI
+1 NAME Jim /Bloggs/
+1 FAMC @F1@
+0 @F1@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 CHIL @I3@
+0 TRLR
+",
+        )
+        .unwrap();
+        let mut browser = Browser::new(&gedcom);
+        assert!(label(browser.selected().unwrap()) == "Joe Bloggs @I1@");
+
+        let relatives: Vec<(&str, String)> = browser
+            .relatives()
+            .iter()
+            .map(|(r, i)| (*r, label(i)))
+            .collect();
+        assert!(
+            relatives
+                == [
+                    ("Spouse", "Jane Doe @I2@".to_string()),
+                    ("Child", "Jim Bloggs @I3@".to_string())
+                ]
+        );
+
+        // Jump to the child, then back
+        browser.key(KeyCode::Tab);
+        assert!(browser.focus() == Focus::Relatives);
+        browser.key(KeyCode::Down);
+        browser.key(KeyCode::Enter);
+        assert!(label(browser.selected().unwrap()) == "Jim Bloggs @I3@");
+        assert!(browser.focus() == Focus::Individuals);
+        browser.key(KeyCode::Left);
+        assert!(label(browser.selected().unwrap()) == "Joe Bloggs @I1@");
+
+        // Search as you type
+        for c in "/bloggs".chars() {
+            browser.key(KeyCode::Char(c));
+        }
+        assert!(browser.search() == ("bloggs", true));
+        assert!(browser.visible().len() == 2);
+        browser.key(KeyCode::Enter);
+        browser.key(KeyCode::Down);
+        assert!(label(browser.selected().unwrap()) == "Jim Bloggs @I3@");
+        browser.key(KeyCode::Down);
+        assert!(label(browser.selected().unwrap()) == "Jim Bloggs @I3@");
+
+        // Jumping to someone the search hides clears it
+        browser.key(KeyCode::Tab);
+        browser.key(KeyCode::Down);
+        browser.key(KeyCode::Enter);
+        assert!(label(browser.selected().unwrap()) == "Jane Doe @I2@");
+        assert!(browser.visible().len() == 3);
+
+        assert!(!browser.key(KeyCode::Char('q')));
+    }
+}
@@ -0,0 +1,362 @@
+use std::collections::HashMap;
+
+use crate::types::{Family, Gedcom, Gender, Header, Individual};
+
+// Build a tree in code, for applications that start from an empty tree
+// rather than a file. Xrefs are handed out as records are added, and the
+// FAMC and FAMS links on individuals are filled in from the families that
+// name them, so the two sides of every link agree.
+//
+// As with the CSV import, each record is written out as GEDCOM and run
+// through the parser, so the result is exactly what we'd get from reading
+// the same tree from a file.
+
+/// Builds a new Gedcom, declared as GEDCOM 5.5.1 in UTF-8. Use
+/// `writer::write_gedcom_as` to write it as another version.
+///
+/// ```
+/// use gedcom_rs::builder::{FamilyBuilder, GedcomBuilder, IndividualBuilder};
+/// use gedcom_rs::types::Gender;
+///
+/// let mut builder = GedcomBuilder::new();
+/// let joe = builder.individual(IndividualBuilder::new().name("Joe /Bloggs/").sex(Gender::Male));
+/// let ann = builder.individual(IndividualBuilder::new().name("Ann /Smith/"));
+/// let sam = builder.individual(IndividualBuilder::new().name("Sam /Bloggs/").birth("1925", ""));
+/// builder.family(FamilyBuilder::new().husband(&joe).wife(&ann).child(&sam));
+///
+/// let gedcom = builder.build();
+/// assert!(gedcom.individuals[2].famc[0].xref == "@F1@");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct GedcomBuilder {
+    individuals: Vec<(String, IndividualBuilder)>,
+    families: Vec<(String, FamilyBuilder)>,
+    home_person: Option<String>,
+}
+
+impl GedcomBuilder {
+    pub fn new() -> GedcomBuilder {
+        GedcomBuilder::default()
+    }
+
+    /// Add an individual, and return the xref they've been given, i.e.
+    /// "@I1@", to link them into families with
+    pub fn individual(&mut self, individual: IndividualBuilder) -> String {
+        let xref = format!("@I{}@", self.individuals.len() + 1);
+        self.individuals.push((xref.clone(), individual));
+        xref
+    }
+
+    /// Add a family, and return the xref it's been given, i.e. "@F1@". The
+    /// spouses and children are given FAMS and FAMC links to it.
+    pub fn family(&mut self, family: FamilyBuilder) -> String {
+        let xref = format!("@F{}@", self.families.len() + 1);
+        self.families.push((xref.clone(), family));
+        xref
+    }
+
+    /// Make the individual with this xref the home person. See
+    /// `Gedcom::home_person`.
+    pub fn home_person(&mut self, xref: &str) {
+        self.home_person = Some(xref.to_string());
+    }
+
+    pub fn build(self) -> Gedcom {
+        let mut header = vec![
+            "0 HEAD".to_string(),
+            "1 GEDC".to_string(),
+            "2 VERS 5.5.1".to_string(),
+            "2 FORM LINEAGE-LINKED".to_string(),
+            "1 CHAR UTF-8".to_string(),
+        ];
+        if let Some(xref) = &self.home_person {
+            header.push(format!("1 _HOME {}", xref));
+        }
+        let mut gedcom = Gedcom {
+            header: Header::parse(header.join("\n") + "\n"),
+            ..Default::default()
+        };
+
+        // Work out which families each individual belongs to
+        let mut fams: HashMap<&str, Vec<&str>> = HashMap::new();
+        let mut famc: HashMap<&str, Vec<&str>> = HashMap::new();
+        for (xref, family) in &self.families {
+            for spouse in family.husband.iter().chain(family.wife.iter()) {
+                fams.entry(spouse).or_default().push(xref);
+            }
+            for child in &family.children {
+                famc.entry(child).or_default().push(xref);
+            }
+        }
+
+        for (xref, individual) in &self.individuals {
+            let mut record = vec![format!("0 {} INDI", xref)];
+            for name in &individual.names {
+                record.push(format!("1 NAME {}", line_value(name)));
+            }
+            if let Some(sex) = &individual.sex {
+                record.push(format!("1 SEX {}", sex));
+            }
+            for event in &individual.events {
+                event.push(&mut record);
+            }
+            for family in famc.get(xref.as_str()).into_iter().flatten() {
+                record.push(format!("1 FAMC {}", family));
+            }
+            for family in fams.get(xref.as_str()).into_iter().flatten() {
+                record.push(format!("1 FAMS {}", family));
+            }
+            for note in &individual.notes {
+                push_note(&mut record, note);
+            }
+
+            let input = record.join("\n") + "\n";
+            gedcom
+                .individuals
+                .push(Individual::parse(&mut input.as_str()));
+        }
+
+        for (xref, family) in &self.families {
+            let mut record = vec![format!("0 {} FAM", xref)];
+            if let Some(husband) = &family.husband {
+                record.push(format!("1 HUSB {}", husband));
+            }
+            if let Some(wife) = &family.wife {
+                record.push(format!("1 WIFE {}", wife));
+            }
+            for child in &family.children {
+                record.push(format!("1 CHIL {}", child));
+            }
+            for event in &family.events {
+                event.push(&mut record);
+            }
+            for note in &family.notes {
+                push_note(&mut record, note);
+            }
+
+            let input = record.join("\n") + "\n";
+            gedcom.families.push(Family::parse(&mut input.as_str()));
+        }
+
+        gedcom
+    }
+}
+
+/// An individual to add with `GedcomBuilder::individual`
+#[derive(Clone, Debug, Default)]
+pub struct IndividualBuilder {
+    names: Vec<String>,
+    sex: Option<String>,
+    events: Vec<Event>,
+    notes: Vec<String>,
+}
+
+impl IndividualBuilder {
+    pub fn new() -> IndividualBuilder {
+        IndividualBuilder::default()
+    }
+
+    /// A name, with slashes around the surname, i.e. "Joe /Bloggs/". The
+    /// first name given is the individual's main name.
+    pub fn name(mut self, name: &str) -> IndividualBuilder {
+        self.names.push(name.to_string());
+        self
+    }
+
+    /// The individual's sex. GEDCOM 5.5.1 has no nonbinary value, so
+    /// `Gender::Nonbinary` is written as GEDCOM 7's X.
+    pub fn sex(mut self, sex: Gender) -> IndividualBuilder {
+        let sex = match &sex {
+            Gender::Male => "M",
+            Gender::Female => "F",
+            Gender::Nonbinary => "X",
+            Gender::Unknown => "U",
+            Gender::Other(value) => value,
+        };
+        self.sex = Some(line_value(sex));
+        self
+    }
+
+    /// A birth, with its date, i.e. "12 MAR 1900", and place. Either may be
+    /// empty if it isn't known.
+    pub fn birth(self, date: &str, place: &str) -> IndividualBuilder {
+        self.event("BIRT", date, place)
+    }
+
+    /// A death, with its date and place. Either may be empty if it isn't
+    /// known.
+    pub fn death(self, date: &str, place: &str) -> IndividualBuilder {
+        self.event("DEAT", date, place)
+    }
+
+    /// Any other event, by its tag, i.e. "BURI" or "CENS"
+    pub fn event(mut self, tag: &str, date: &str, place: &str) -> IndividualBuilder {
+        self.events.push(Event::new(tag, date, place));
+        self
+    }
+
+    pub fn note(mut self, note: &str) -> IndividualBuilder {
+        self.notes.push(note.to_string());
+        self
+    }
+}
+
+/// A family to add with `GedcomBuilder::family`. The husband, wife and
+/// children are xrefs returned by `GedcomBuilder::individual`.
+#[derive(Clone, Debug, Default)]
+pub struct FamilyBuilder {
+    husband: Option<String>,
+    wife: Option<String>,
+    children: Vec<String>,
+    events: Vec<Event>,
+    notes: Vec<String>,
+}
+
+impl FamilyBuilder {
+    pub fn new() -> FamilyBuilder {
+        FamilyBuilder::default()
+    }
+
+    pub fn husband(mut self, xref: &str) -> FamilyBuilder {
+        self.husband = Some(xref.to_string());
+        self
+    }
+
+    pub fn wife(mut self, xref: &str) -> FamilyBuilder {
+        self.wife = Some(xref.to_string());
+        self
+    }
+
+    /// A child, after any already added
+    pub fn child(mut self, xref: &str) -> FamilyBuilder {
+        self.children.push(xref.to_string());
+        self
+    }
+
+    /// A marriage, with its date and place. Either may be empty if it isn't
+    /// known.
+    pub fn marriage(self, date: &str, place: &str) -> FamilyBuilder {
+        self.event("MARR", date, place)
+    }
+
+    /// Any other event, by its tag, i.e. "DIV"
+    pub fn event(mut self, tag: &str, date: &str, place: &str) -> FamilyBuilder {
+        self.events.push(Event::new(tag, date, place));
+        self
+    }
+
+    pub fn note(mut self, note: &str) -> FamilyBuilder {
+        self.notes.push(note.to_string());
+        self
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Event {
+    tag: String,
+    date: String,
+    place: String,
+}
+
+impl Event {
+    fn new(tag: &str, date: &str, place: &str) -> Event {
+        Event {
+            tag: tag.trim().to_uppercase(),
+            date: line_value(date),
+            place: line_value(place),
+        }
+    }
+
+    fn push(&self, record: &mut Vec<String>) {
+        record.push(format!("1 {}", self.tag));
+        if !self.date.is_empty() {
+            record.push(format!("2 DATE {}", self.date));
+        }
+        if !self.place.is_empty() {
+            record.push(format!("2 PLAC {}", self.place));
+        }
+    }
+}
+
+/// A value that must fit on one line, with any line breaks made spaces
+fn line_value(value: &str) -> String {
+    value.split_whitespace().collect::<Vec<&str>>().join(" ")
+}
+
+/// A NOTE, with a CONT line for each line break in it
+fn push_note(record: &mut Vec<String>, note: &str) {
+    let mut lines = note.lines();
+    record.push(format!("1 NOTE {}", lines.next().unwrap_or_default()));
+    for line in lines {
+        record.push(format!("2 CONT {}", line));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_gedcom_from_str;
+    use crate::writer::write_gedcom;
+
+    #[test]
+    fn build() {
+        let mut builder = GedcomBuilder::new();
+        let joe = builder.individual(
+            IndividualBuilder::new()
+                .name("Joe /Bloggs/")
+                .sex(Gender::Male)
+                .birth("12 MAR 1900", "London")
+                .death("1970", ""),
+        );
+        let ann = builder.individual(
+            IndividualBuilder::new()
+                .name("Ann /Smith/")
+                .sex(Gender::Female)
+                .note("Kept a diary\nfor sixty years"),
+        );
+        let sam = builder.individual(IndividualBuilder::new().name("Sam\n/Bloggs/"));
+        let family = builder.family(
+            FamilyBuilder::new()
+                .husband(&joe)
+                .wife(&ann)
+                .child(&sam)
+                .marriage("1925", "Leeds")
+                .event("div", "", ""),
+        );
+        builder.home_person(&sam);
+        assert!(joe == "@I1@" && sam == "@I3@" && family == "@F1@");
+
+        let gedcom = builder.build();
+        assert!(gedcom.header.gedcom_version.is_some());
+        assert!(gedcom.home_person().unwrap().xref.as_deref() == Some("@I3@"));
+
+        let joe = &gedcom.individuals[0];
+        assert!(joe.gender == Gender::Male);
+        assert!(joe.birth_date() == Some("12 MAR 1900") && joe.death_date() == Some("1970"));
+        assert!(joe.fams[0].xref == "@F1@" && joe.famc.is_empty());
+        let ann = &gedcom.individuals[1];
+        assert!(ann.notes[0].note.as_deref() == Some("Kept a diary\nfor sixty years"));
+        let sam = &gedcom.individuals[2];
+        assert!(sam.primary_name().as_deref() == Some("Sam Bloggs"));
+        assert!(sam.famc[0].xref == "@F1@");
+
+        let family = &gedcom.families[0];
+        assert!(family.husband_xref() == Some("@I1@") && family.wife_xref() == Some("@I2@"));
+        assert!(family.children == ["@I3@"]);
+        assert!(family.marriage_date() == Some("1925") && family.divorce.len() == 1);
+        assert!(gedcom.parents(sam).len() == 2);
+
+        // Round trip through the writer
+        let reread = parse_gedcom_from_str(&write_gedcom(&gedcom)).unwrap();
+        assert!(reread.warnings.is_empty());
+        assert!(reread.individuals.len() == 3 && reread.families.len() == 1);
+        assert!(reread.individuals[2].famc[0].xref == "@F1@");
+    }
+
+    #[test]
+    fn build_empty() {
+        let gedcom = GedcomBuilder::new().build();
+        assert!(gedcom.individuals.is_empty() && gedcom.families.is_empty());
+        assert!(gedcom.header.character_set.is_some());
+    }
+}
@@ -0,0 +1,162 @@
+use crate::types::{DateTime, Gedcom, Timestamp};
+
+// Every record keeps its CHAN as a DateTime, read the same way, so they can
+// be compared across record types. Records the structured model doesn't
+// keep, i.e. SUBN, are read from their raw text when parsing with
+// `GedcomConfig::preserve_unknown`.
+
+/// A record and when it was last changed. See `records_changed_since`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordChange<'a> {
+    pub xref: &'a str,
+    /// The record's tag, i.e. "INDI"
+    pub tag: &'a str,
+    pub changed: Timestamp,
+}
+
+/// Every record changed on or after `since`, by its CHAN, the most recently
+/// changed first, i.e. those to send in an incremental sync. Records
+/// without a CHAN date we can read are left out.
+pub fn records_changed_since<'a>(gedcom: &'a Gedcom, since: &Timestamp) -> Vec<RecordChange<'a>> {
+    let mut changes: Vec<RecordChange> = record_changes(gedcom)
+        .into_iter()
+        .filter(|c| c.changed >= *since)
+        .collect();
+    // Sorting is stable, so records changed together stay in file order
+    changes.sort_by_key(|c| std::cmp::Reverse(c.changed));
+    changes
+}
+
+/// Every record with a CHAN date we can read, in the order of the model:
+/// individuals, families, sources, repositories, multimedia, notes,
+/// submitters, then
+/// anything else kept raw
+pub(crate) fn record_changes(gedcom: &Gedcom) -> Vec<RecordChange<'_>> {
+    let mut records: Vec<(&str, &str, Option<DateTime>)> = vec![];
+    for individual in &gedcom.individuals {
+        if let Some(xref) = &individual.xref {
+            records.push((xref, "INDI", individual.change_date.clone()));
+        }
+    }
+    for family in &gedcom.families {
+        records.push((&family.xref, "FAM", family.change_date.clone()));
+    }
+    for source in &gedcom.sources {
+        records.push((&source.xref, "SOUR", source.change_date.clone()));
+    }
+    for repository in &gedcom.repositories {
+        records.push((&repository.xref, "REPO", repository.change_date.clone()));
+    }
+    for multimedia in &gedcom.multimedia {
+        records.push((&multimedia.xref, "OBJE", multimedia.change_date.clone()));
+    }
+    for note in &gedcom.notes {
+        records.push((&note.xref, "NOTE", note.change_date.clone()));
+    }
+    for submitter in &gedcom.submitters {
+        if let Some(xref) = &submitter.xref {
+            records.push((xref, "SUBM", submitter.change_date.clone()));
+        }
+    }
+    for raw in &gedcom.raw_records {
+        let xref = match &raw.xref {
+            Some(xref) if !records.iter().any(|(x, _, _)| x == xref) => xref,
+            _ => continue,
+        };
+        records.push((xref, &raw.tag, raw.change_date()));
+    }
+
+    records
+        .into_iter()
+        .filter_map(|(xref, tag, date)| {
+            Some(RecordChange {
+                xref,
+                tag,
+                changed: date?.timestamp()?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GedcomConfig;
+    use crate::parse::parse_gedcom_from_reader_with_config;
+
+    #[test]
+    fn changed_since() {
+        let text = "0 HEAD
+1 SUBM @U1@
+0 @U1@ SUBM
+1 NAME Jo Researcher
+1 CHAN
+2 DATE 5 MAY 2024
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 CHAN
+2 DATE 1 FEB 2024
+3 TIME 10:00
+0 @I2@ INDI
+1 NAME Ann /Bloggs/
+1 CHAN
+2 DATE 1 FEB 2024
+3 TIME 09:00
+0 @F1@ FAM
+1 HUSB @I1@
+1 CHAN
+2 DATE 14 JAN 2001
+0 @S1@ SOUR
+1 TITL Parish register
+1 CHAN
+2 DATE 1 FEB 2024
+3 TIME 10:00
+0 @R1@ REPO
+1 NAME County archive
+1 CHAN
+2 DATE 3 MAR 2024
+0 @N1@ NOTE Unchanged
+0 @M1@ OBJE
+1 FILE photo.jpeg
+1 CHAN
+2 DATE 2 FEB 2024
+0 TRLR
+";
+        let config = GedcomConfig::new().preserve_unknown();
+        let gedcom = parse_gedcom_from_reader_with_config(text.as_bytes(), &config).unwrap();
+
+        let since = |date: &str| -> Vec<(&str, &str)> {
+            let since = Timestamp {
+                date: date.parse().unwrap(),
+                time: None,
+            };
+            records_changed_since(&gedcom, &since)
+                .iter()
+                .map(|c| (c.xref, c.tag))
+                .collect()
+        };
+        assert!(
+            since("1 FEB 2024")
+                == [
+                    ("@U1@", "SUBM"),
+                    ("@R1@", "REPO"),
+                    ("@M1@", "OBJE"),
+                    ("@I1@", "INDI"),
+                    ("@S1@", "SOUR"),
+                    ("@I2@", "INDI"),
+                ]
+        );
+        assert!(since("3 MAR 2024") == [("@U1@", "SUBM"), ("@R1@", "REPO")]);
+        assert!(since("2000").len() == 7);
+        assert!(since("2025").is_empty());
+
+        // The same without the raw records
+        let gedcom = crate::parse::parse_gedcom_from_str(text).unwrap();
+        let since = Timestamp {
+            date: "2000".parse().unwrap(),
+            time: None,
+        };
+        let changes = gedcom.records_changed_since(&since);
+        assert!(changes.len() == 7 && changes[1].tag == "REPO");
+    }
+}
@@ -0,0 +1,449 @@
+use std::str::FromStr;
+
+use crate::types::{Gedcom, Individual};
+
+// Text charts of a family tree, for a quick look in the terminal.
+//
+// A pedigree chart draws an individual's ancestors as a tree, father above
+// mother:
+//
+//     Joe Bloggs (@I1@) 1900-1970
+//     ├── Fred Bloggs (@I2@) b. 1870
+//     └── Ann Smith (@I3@)
+//
+// A descendant outline lists an individual's descendants by generation,
+// with each spouse, marked `+`, followed by the children they had together:
+//
+//     1 Joe Bloggs (@I1@) 1900-1970
+//       + Jane Doe (@I4@)
+//         2 Jim Bloggs (@I5@) b. 1925
+//
+// Descendants can be numbered by generation, as above, or with one of the
+// systems genealogical journals use; see `Numbering`.
+
+/// Draw `individual` and `generations` generations of their ancestors.
+/// Where a family's parents are both known, the father is drawn first.
+pub fn render_pedigree(gedcom: &Gedcom, individual: &Individual, generations: usize) -> String {
+    let mut chart = label(individual) + "\n";
+    let mut line = vec![individual];
+    pedigree(gedcom, &mut chart, &mut line, "", generations);
+    chart
+}
+
+fn pedigree<'a>(
+    gedcom: &'a Gedcom,
+    chart: &mut String,
+    line: &mut Vec<&'a Individual>,
+    prefix: &str,
+    generations: usize,
+) {
+    if generations == 0 {
+        return;
+    }
+    let child = line[line.len() - 1];
+    let family = match gedcom.child_families(child).into_iter().next() {
+        Some(family) => family,
+        None => return,
+    };
+    let parents: Vec<&Individual> = family
+        .husband_xref()
+        .into_iter()
+        .chain(family.wife_xref())
+        .filter_map(|xref| gedcom.find_individual(xref))
+        // Someone who is their own ancestor would go on forever
+        .filter(|p| !line.iter().any(|i| i.xref == p.xref))
+        .collect();
+
+    for (i, parent) in parents.iter().enumerate() {
+        let last = i == parents.len() - 1;
+        *chart += &format!(
+            "{}{}{}\n",
+            prefix,
+            if last { "└── " } else { "├── " },
+            label(parent)
+        );
+        line.push(parent);
+        let prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+        pedigree(gedcom, chart, line, &prefix, generations - 1);
+        line.pop();
+    }
+}
+
+/// How the individuals in a descendant outline are numbered. Journals and
+/// societies each expect their own system.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Numbering {
+    /// Each individual's generation, counting the first as 1
+    #[default]
+    Generation,
+    /// d'Aboville: the parent's number, then the child's place among their
+    /// siblings, separated by dots, i.e. 1.2.1
+    DAboville,
+    /// Henry: the parent's number followed by the child's place among their
+    /// siblings, i.e. 121. From the tenth child on, the place is bracketed,
+    /// i.e. 1(10)
+    Henry,
+    /// Register (NEHGS): each child's place among their siblings in Roman
+    /// numerals, after a running number for those whose own children are
+    /// listed, i.e. "2 ii"
+    Register,
+    /// NGSQ, or Modified Register: a running number for everyone, then their
+    /// place among their siblings in Roman numerals. Those whose own
+    /// children are listed are marked "+", i.e. "+2 ii"
+    Ngsq,
+}
+
+impl FromStr for Numbering {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Numbering, Self::Err> {
+        match input.to_lowercase().as_str() {
+            "generation" => Ok(Numbering::Generation),
+            "daboville" | "d'aboville" => Ok(Numbering::DAboville),
+            "henry" => Ok(Numbering::Henry),
+            "register" | "nehgs" => Ok(Numbering::Register),
+            "ngsq" | "modified-register" => Ok(Numbering::Ngsq),
+            _ => Err(()),
+        }
+    }
+}
+
+/// List `individual` and `generations` generations of their descendants,
+/// numbered by generation and indented under their parents.
+pub fn render_descendants(gedcom: &Gedcom, individual: &Individual, generations: usize) -> String {
+    render_numbered_descendants(gedcom, individual, generations, Numbering::Generation)
+}
+
+/// List `individual` and `generations` generations of their descendants,
+/// indented under their parents and numbered with the given system.
+pub fn render_numbered_descendants(
+    gedcom: &Gedcom,
+    individual: &Individual,
+    generations: usize,
+    numbering: Numbering,
+) -> String {
+    let mut entries = vec![];
+    let mut line = vec![];
+    descendants(
+        gedcom,
+        &mut entries,
+        &mut line,
+        (individual, None, 1),
+        1,
+        generations,
+    );
+
+    let numbers = numbers(&entries, numbering);
+    let mut chart = String::new();
+    for (entry, number) in entries.iter().zip(numbers) {
+        let indent = "  ".repeat(2 * (entry.generation - 1));
+        match entry.descent {
+            Some(_) => chart += &format!("{}{} {}\n", indent, number, label(entry.individual)),
+            None => chart += &format!("{}  + {}\n", indent, label(entry.individual)),
+        }
+    }
+    chart
+}
+
+/// A line of a descendant outline
+struct Entry<'a> {
+    individual: &'a Individual,
+    generation: usize,
+    /// For a descendant, rather than a spouse, how they descend
+    descent: Option<Descent>,
+}
+
+struct Descent {
+    /// The index of their parent's entry, or None for the first individual
+    parent: Option<usize>,
+    /// Their place among their parent's children, counting from 1
+    place: usize,
+    /// Whether any of their children are listed
+    has_children: bool,
+}
+
+fn descendants<'a>(
+    gedcom: &'a Gedcom,
+    entries: &mut Vec<Entry<'a>>,
+    line: &mut Vec<&'a Individual>,
+    (individual, parent, place): (&'a Individual, Option<usize>, usize),
+    generation: usize,
+    generations: usize,
+) {
+    let index = entries.len();
+    entries.push(Entry {
+        individual,
+        generation,
+        descent: Some(Descent {
+            parent,
+            place,
+            has_children: false,
+        }),
+    });
+    if generation > generations {
+        return;
+    }
+
+    line.push(individual);
+    let mut place = 0;
+    for family in gedcom.spouse_families(individual) {
+        let spouse = family
+            .husband_xref()
+            .into_iter()
+            .chain(family.wife_xref())
+            .filter(|xref| Some(*xref) != individual.xref.as_deref())
+            .find_map(|xref| gedcom.find_individual(xref));
+        if let Some(spouse) = spouse {
+            entries.push(Entry {
+                individual: spouse,
+                generation,
+                descent: None,
+            });
+        }
+
+        let children: Vec<&Individual> = family
+            .children
+            .iter()
+            .filter_map(|xref| gedcom.find_individual(xref))
+            // Someone who is their own descendant would go on forever
+            .filter(|c| !line.iter().any(|i| i.xref == c.xref))
+            .collect();
+        for child in children {
+            place += 1;
+            descendants(
+                gedcom,
+                entries,
+                line,
+                (child, Some(index), place),
+                generation + 1,
+                generations,
+            );
+        }
+    }
+    if let Some(descent) = &mut entries[index].descent {
+        descent.has_children = place > 0;
+    }
+    line.pop();
+}
+
+/// The number of each entry in the outline, empty for spouses
+fn numbers(entries: &[Entry], numbering: Numbering) -> Vec<String> {
+    let mut numbers = vec![String::new(); entries.len()];
+
+    // Register and NGSQ number a whole generation before the next
+    let mut order: Vec<usize> = (0..entries.len()).collect();
+    order.sort_by_key(|i| entries[*i].generation);
+    let mut running = 0;
+
+    for i in order {
+        let entry = &entries[i];
+        let descent = match &entry.descent {
+            Some(descent) => descent,
+            None => continue,
+        };
+        let parent = match descent.parent {
+            Some(parent) => parent,
+            None => {
+                numbers[i] = match numbering {
+                    Numbering::Generation => entry.generation.to_string(),
+                    _ => "1".to_string(),
+                };
+                running = 1;
+                continue;
+            }
+        };
+        numbers[i] = match numbering {
+            Numbering::Generation => entry.generation.to_string(),
+            Numbering::DAboville => format!("{}.{}", numbers[parent], descent.place),
+            Numbering::Henry if descent.place < 10 => {
+                format!("{}{}", numbers[parent], descent.place)
+            }
+            Numbering::Henry => format!("{}({})", numbers[parent], descent.place),
+            Numbering::Register if descent.has_children => {
+                running += 1;
+                format!("{} {}", running, roman(descent.place))
+            }
+            Numbering::Register => roman(descent.place),
+            Numbering::Ngsq => {
+                running += 1;
+                let mark = if descent.has_children { "+" } else { "" };
+                format!("{}{} {}", mark, running, roman(descent.place))
+            }
+        };
+    }
+    numbers
+}
+
+/// `n` in lower-case Roman numerals
+pub(crate) fn roman(mut n: usize) -> String {
+    let numerals = [
+        (1000, "m"),
+        (900, "cm"),
+        (500, "d"),
+        (400, "cd"),
+        (100, "c"),
+        (90, "xc"),
+        (50, "l"),
+        (40, "xl"),
+        (10, "x"),
+        (9, "ix"),
+        (5, "v"),
+        (4, "iv"),
+        (1, "i"),
+    ];
+    let mut roman = String::new();
+    for (value, numeral) in numerals {
+        while n >= value {
+            roman += numeral;
+            n -= value;
+        }
+    }
+    roman
+}
+
+/// An individual's name, xref, and years of birth and death, if known
+fn label(individual: &Individual) -> String {
+    let name = individual
+        .primary_name()
+        .unwrap_or_else(|| "(no name)".to_string());
+    let mut label = format!(
+        "{} ({})",
+        name,
+        individual.xref.as_deref().unwrap_or_default()
+    );
+    match (individual.birth_year(), individual.death_year()) {
+        (Some(birth), Some(death)) => label += &format!(" {}-{}", birth, death),
+        (Some(birth), None) => label += &format!(" b. {}", birth),
+        (None, Some(death)) => label += &format!(" d. {}", death),
+        (None, None) => {}
+    }
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_gedcom_from_str;
+
+    const DATA: &str = "0 HEAD
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 BIRT
+2 DATE 1900
+1 DEAT
+2 DATE 1970
+1 FAMC @F1@
+1 FAMS @F2@
+0 @I2@ INDI
+1 NAME Fred /Bloggs/
+1 BIRT
+2 DATE 1870
+1 FAMS @F1@
+0 @I3@ INDI
+1 NAME Ann /Smith/
+1 FAMS @F1@
+0 @I4@ INDI
+1 NAME Jane /Doe/
+1 FAMS @F2@
+0 @I5@ INDI
+1 NAME Jim /Bloggs/
+1 BIRT
+2 DATE 1925
+1 FAMC @F2@
+0 @F1@ FAM
+1 HUSB @I2@
+1 WIFE @I3@
+1 CHIL @I1@
+0 @F2@ FAM
+1 HUSB @I1@
+1 WIFE @I4@
+1 CHIL @I5@
+0 TRLR
+";
+
+    #[test]
+    fn pedigree() {
+        let gedcom = parse_gedcom_from_str(DATA).unwrap();
+        assert!(
+            gedcom.render_pedigree("@I5@", 2).unwrap()
+                == "Jim Bloggs (@I5@) b. 1925
+├── Joe Bloggs (@I1@) 1900-1970
+│   ├── Fred Bloggs (@I2@) b. 1870
+│   └── Ann Smith (@I3@)
+└── Jane Doe (@I4@)
+"
+        );
+        assert!(
+            gedcom.render_pedigree("@I5@", 1).unwrap()
+                == "Jim Bloggs (@I5@) b. 1925
+├── Joe Bloggs (@I1@) 1900-1970
+└── Jane Doe (@I4@)
+"
+        );
+        assert!(gedcom.render_pedigree("@I9@", 1).is_none());
+    }
+
+    #[test]
+    fn descendants() {
+        let gedcom = parse_gedcom_from_str(DATA).unwrap();
+        assert!(
+            gedcom.render_descendants("@I2@", 2).unwrap()
+                == "1 Fred Bloggs (@I2@) b. 1870
+  + Ann Smith (@I3@)
+    2 Joe Bloggs (@I1@) 1900-1970
+      + Jane Doe (@I4@)
+        3 Jim Bloggs (@I5@) b. 1925
+"
+        );
+        assert!(gedcom.render_descendants("@I2@", 0).unwrap() == "1 Fred Bloggs (@I2@) b. 1870\n");
+    }
+
+    #[test]
+    fn numbering() {
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @I1@ INDI
+1 NAME Fred /Bloggs/
+1 FAMS @F1@
+0 @I2@ INDI
+1 NAME Joe /Bloggs/
+1 FAMC @F1@
+1 FAMS @F2@
+0 @I3@ INDI
+1 NAME Ann /Bloggs/
+1 FAMC @F1@
+0 @I4@ INDI
+1 NAME Jim /Bloggs/
+1 FAMC @F2@
+0 @F1@ FAM
+1 HUSB @I1@
+1 CHIL @I2@
+1 CHIL @I3@
+0 @F2@ FAM
+1 HUSB @I2@
+1 CHIL @I4@
+0 TRLR
+",
+        )
+        .unwrap();
+        let numbers = |numbering: &str| -> Vec<String> {
+            let numbering = Numbering::from_str(numbering).unwrap();
+            gedcom
+                .render_numbered_descendants("@I1@", 4, numbering)
+                .unwrap()
+                .lines()
+                .map(|l| l.split(" Bloggs").next().unwrap().trim().to_string())
+                .collect()
+        };
+
+        assert!(numbers("generation") == ["1 Fred", "2 Joe", "3 Jim", "2 Ann"]);
+        assert!(numbers("d'Aboville") == ["1 Fred", "1.1 Joe", "1.1.1 Jim", "1.2 Ann"]);
+        assert!(numbers("henry") == ["1 Fred", "11 Joe", "111 Jim", "12 Ann"]);
+        assert!(numbers("register") == ["1 Fred", "2 i Joe", "i Jim", "ii Ann"]);
+        assert!(numbers("NGSQ") == ["1 Fred", "+2 i Joe", "4 i Jim", "3 ii Ann"]);
+        assert!(Numbering::from_str("dewey").is_err());
+
+        assert!(roman(14) == "xiv");
+        assert!(roman(1990) == "mcmxc");
+    }
+}
@@ -0,0 +1,453 @@
+use crate::timeline::{Anniversary, TimelineEntry};
+use crate::types::{
+    Association, EventDetail, Family, Gedcom, Individual, NoteRecord, Quay, SourceCitation,
+};
+
+/// How well the events in a Gedcom are backed by source citations, so you
+/// can see where research is needed most. See `Gedcom::citation_coverage`.
+#[derive(Debug)]
+pub struct CitationCoverage<'a> {
+    /// Births, falling back to christenings and baptisms as the genealogical
+    /// standard does
+    pub births: EventCoverage,
+    pub deaths: EventCoverage,
+    pub marriages: EventCoverage,
+    /// Every individual and family event, in file order
+    pub events: Vec<EventCitations<'a>>,
+    /// Each individual, with how many of their events are sourced
+    pub individuals: Vec<IndividualCitations<'a>>,
+}
+
+/// How many events of a kind there are, and how many cite a source
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct EventCoverage {
+    pub events: usize,
+    pub sourced: usize,
+}
+
+impl EventCoverage {
+    /// The percentage of events that are sourced, or None if there are none
+    pub fn percentage(&self) -> Option<f64> {
+        if self.events == 0 {
+            return None;
+        }
+        Some(self.sourced as f64 * 100.0 / self.events as f64)
+    }
+}
+
+/// The citations on a single event
+#[derive(Clone, Debug, PartialEq)]
+pub struct EventCitations<'a> {
+    /// The xref of the individual or family the event belongs to
+    pub xref: &'a str,
+    pub tag: &'static str,
+    /// The QUAY of each citation, where one was given
+    pub quality: Vec<Option<Quay>>,
+}
+
+impl EventCitations<'_> {
+    pub fn is_sourced(&self) -> bool {
+        !self.quality.is_empty()
+    }
+
+    /// The most credible evidence cited for the event
+    pub fn best_quality(&self) -> Option<Quay> {
+        self.quality.iter().flatten().max().cloned()
+    }
+}
+
+/// How many of an individual's own events are sourced
+#[derive(Debug)]
+pub struct IndividualCitations<'a> {
+    pub individual: &'a Individual,
+    pub events: usize,
+    pub sourced: usize,
+}
+
+impl<'a> CitationCoverage<'a> {
+    pub fn new(gedcom: &'a Gedcom) -> CitationCoverage<'a> {
+        let mut coverage = CitationCoverage {
+            births: EventCoverage::default(),
+            deaths: EventCoverage::default(),
+            marriages: EventCoverage::default(),
+            events: vec![],
+            individuals: vec![],
+        };
+
+        for individual in &gedcom.individuals {
+            let xref = individual.xref.as_deref().unwrap_or_default();
+            let events = individual.tagged_events();
+            let start = coverage.events.len();
+            let mut counts = IndividualCitations {
+                individual,
+                events: events.len(),
+                sourced: 0,
+            };
+            for (tag, detail) in events {
+                let event = citations(xref, tag, detail);
+                if event.is_sourced() {
+                    counts.sourced += 1;
+                }
+                coverage.events.push(event);
+            }
+            coverage.individuals.push(counts);
+
+            // Count each individual's birth and death once, from the best
+            // evidence we have of it
+            let own = &coverage.events[start..];
+            for (kinds, total) in [
+                (&["BIRT", "CHR", "BAPM"][..], &mut coverage.births),
+                (&["DEAT", "BURI", "CREM"][..], &mut coverage.deaths),
+            ] {
+                let found: Vec<&EventCitations> =
+                    own.iter().filter(|e| kinds.contains(&e.tag)).collect();
+                if !found.is_empty() {
+                    total.events += 1;
+                    if found.iter().any(|e| e.is_sourced()) {
+                        total.sourced += 1;
+                    }
+                }
+            }
+        }
+
+        for family in &gedcom.families {
+            for (tag, detail) in family.tagged_events() {
+                let event = citations(&family.xref, tag, detail);
+                if tag == "MARR" {
+                    coverage.marriages.events += 1;
+                    if event.is_sourced() {
+                        coverage.marriages.sourced += 1;
+                    }
+                }
+                coverage.events.push(event);
+            }
+        }
+
+        coverage
+    }
+
+    /// The individuals with no source cited for any of their events
+    pub fn unsourced(&self) -> Vec<&'a Individual> {
+        self.individuals
+            .iter()
+            .filter(|i| i.sourced == 0)
+            .map(|i| i.individual)
+            .collect()
+    }
+
+    /// The events with no source cited
+    pub fn unsourced_events(&self) -> Vec<&EventCitations<'a>> {
+        self.events.iter().filter(|e| !e.is_sourced()).collect()
+    }
+}
+
+/// A record that cites a source. See `records_citing_source`.
+#[derive(Clone, Copy, Debug)]
+pub enum CitingRecord<'a> {
+    Individual(&'a Individual),
+    Family(&'a Family),
+    Note(&'a NoteRecord),
+}
+
+impl CitingRecord<'_> {
+    /// The record's xref
+    pub fn xref(&self) -> Option<&str> {
+        match self {
+            CitingRecord::Individual(individual) => individual.xref.as_deref(),
+            CitingRecord::Family(family) => Some(&family.xref),
+            CitingRecord::Note(note) => Some(&note.xref),
+        }
+    }
+}
+
+/// A citation of a source, and where it is
+#[derive(Debug)]
+pub struct SourceReference<'a> {
+    pub record: CitingRecord<'a>,
+    /// Where in the record the citation is: the tag of the event, i.e.
+    /// "BIRT", or "ASSO" for an association, or None if it's on the record
+    /// itself
+    pub tag: Option<&'static str>,
+    /// The citation, with its PAGE and QUAY
+    pub citation: &'a SourceCitation,
+}
+
+/// Every citation of the SOUR record `xref`, on individuals, families and
+/// their events, and on NOTE records, in file order
+pub fn records_citing_source<'a>(gedcom: &'a Gedcom, xref: &str) -> Vec<SourceReference<'a>> {
+    // Everywhere there may be citations, as the record, where in it, and
+    // the citations there
+    let mut places: Vec<(CitingRecord, Option<&'static str>, &[SourceCitation])> = vec![];
+    let associations = |record, associations: &'a [Association]| {
+        associations
+            .iter()
+            .map(move |a| (record, Some("ASSO"), a.sources.as_slice()))
+    };
+    for individual in &gedcom.individuals {
+        let record = CitingRecord::Individual(individual);
+        places.push((record, None, &individual.sources));
+        places.extend(associations(record, &individual.associations));
+        for (tag, detail) in individual.tagged_events() {
+            if let Some(detail) = detail {
+                places.push((record, Some(tag), &detail.sources));
+                places.extend(associations(record, &detail.associations));
+            }
+        }
+    }
+    for family in &gedcom.families {
+        let record = CitingRecord::Family(family);
+        places.push((record, None, &family.sources));
+        for (tag, detail) in family.tagged_events() {
+            if let Some(detail) = detail {
+                places.push((record, Some(tag), &detail.sources));
+                places.extend(associations(record, &detail.associations));
+            }
+        }
+    }
+    for note in &gedcom.notes {
+        places.push((CitingRecord::Note(note), None, &note.sources));
+    }
+
+    let mut references: Vec<SourceReference> = vec![];
+    for (record, tag, sources) in places {
+        for citation in sources.iter().filter(|c| c.xref.as_deref() == Some(xref)) {
+            references.push(SourceReference {
+                record,
+                tag,
+                citation,
+            });
+        }
+    }
+    references
+}
+
+/// Keeping only what's backed by evidence of at least a given QUAY, i.e.
+/// `gedcom.timeline(individual).with_min_quay(Quay::Secondary)` for the
+/// facts from official records. Anything without a QUAY is left out, since
+/// nothing is known of its evidence.
+pub trait QuayFilter {
+    fn with_min_quay(self, min: Quay) -> Self;
+}
+
+impl QuayFilter for Vec<TimelineEntry<'_>> {
+    /// The events with a citation at least as credible as `min`
+    fn with_min_quay(mut self, min: Quay) -> Self {
+        self.retain(|e| e.quay().is_some_and(|q| q >= min));
+        self
+    }
+}
+
+impl QuayFilter for Vec<Anniversary<'_>> {
+    /// The events with a citation at least as credible as `min`
+    fn with_min_quay(mut self, min: Quay) -> Self {
+        self.retain(|a| a.detail.best_quay().is_some_and(|q| q >= min));
+        self
+    }
+}
+
+impl QuayFilter for Vec<SourceReference<'_>> {
+    /// The citations at least as credible as `min`
+    fn with_min_quay(mut self, min: Quay) -> Self {
+        self.retain(|r| r.citation.quay.as_ref().is_some_and(|q| *q >= min));
+        self
+    }
+}
+
+fn citations<'a>(
+    xref: &'a str,
+    tag: &'static str,
+    detail: Option<&EventDetail>,
+) -> EventCitations<'a> {
+    EventCitations {
+        xref,
+        tag,
+        quality: detail
+            .map(|d| d.sources.iter().map(|s| s.quay.clone()).collect())
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_gedcom_from_str;
+    use crate::types::EventType;
+
+    #[test]
+    fn coverage() {
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @S1@ SOUR
+1 TITL Parish register
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 BIRT
+2 DATE 1900
+2 SOUR @S1@
+3 QUAY 3
+2 SOUR @S1@
+3 QUAY 1
+1 DEAT
+2 DATE 1970
+0 @I2@ INDI
+1 NAME Jane /Doe/
+1 CHR
+2 DATE 1905
+2 SOUR @S1@
+0 @I3@ INDI
+1 NAME Jim /Bloggs/
+1 BIRT
+2 DATE 1930
+1 OCCU Farmer
+0 @F1@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 MARR
+2 DATE 1929
+0 TRLR
+",
+        )
+        .unwrap();
+        let coverage = gedcom.citation_coverage();
+
+        assert!(
+            coverage.births
+                == EventCoverage {
+                    events: 3,
+                    sourced: 2
+                }
+        );
+        assert!(
+            coverage.deaths
+                == EventCoverage {
+                    events: 1,
+                    sourced: 0
+                }
+        );
+        assert!(coverage.marriages.percentage() == Some(0.0));
+        assert!(coverage.births.percentage().unwrap().round() == 67.0);
+        assert!(EventCoverage::default().percentage().is_none());
+
+        let birth = &coverage.events[0];
+        assert!(birth.xref == "@I1@" && birth.tag == "BIRT");
+        assert!(birth.quality == [Some(Quay::Direct), Some(Quay::Questionable)]);
+        assert!(birth.best_quality() == Some(Quay::Direct));
+        assert!(coverage.events[2].quality == [None]);
+        assert!(coverage.events[2].best_quality().is_none());
+
+        let unsourced: Vec<&str> = coverage
+            .unsourced()
+            .iter()
+            .filter_map(|i| i.xref.as_deref())
+            .collect();
+        assert!(unsourced == ["@I3@"]);
+        assert!(coverage.unsourced_events().len() == 4);
+    }
+
+    #[test]
+    fn citing_source() {
+        let text = "0 HEAD
+0 @S1@ SOUR
+1 TITL Parish register
+0 @I1@ INDI
+1 SOUR @S1@
+2 PAGE Folio 12
+1 BIRT
+2 SOUR @S1@
+3 PAGE Entry 3
+3 QUAY 3
+2 SOUR @S2@
+1 ASSO @I2@
+2 RELA Godfather
+2 SOUR @S1@
+0 @I2@ INDI
+0 @F1@ FAM
+1 MARR
+2 SOUR @S1@
+0 @N1@ NOTE Copied from the register
+1 SOUR @S1@
+2 PAGE p. 4
+0 TRLR
+";
+        let gedcom = parse_gedcom_from_str(text).unwrap();
+
+        let references = gedcom.records_citing_source("@S1@");
+        let found: Vec<(Option<&str>, Option<&str>, Option<&str>)> = references
+            .iter()
+            .map(|r| (r.record.xref(), r.tag, r.citation.page.as_deref()))
+            .collect();
+        assert!(
+            found
+                == [
+                    (Some("@I1@"), None, Some("Folio 12")),
+                    (Some("@I1@"), Some("ASSO"), None),
+                    (Some("@I1@"), Some("BIRT"), Some("Entry 3")),
+                    (Some("@F1@"), Some("MARR"), None),
+                    (Some("@N1@"), None, Some("p. 4")),
+                ]
+        );
+        assert!(references[2].citation.quay == Some(Quay::Direct));
+        assert!(matches!(references[3].record, CitingRecord::Family(_)));
+        assert!(gedcom.records_citing_source("@S2@").len() == 1);
+        assert!(gedcom.records_citing_source("@S9@").is_empty());
+    }
+
+    #[test]
+    fn quay_filter() {
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 BIRT
+2 DATE 12 MAR 1900
+2 SOUR @S1@
+3 QUAY 1
+2 SOUR @S2@
+3 QUAY 3
+1 RESI
+2 DATE 1911
+2 SOUR @S1@
+3 QUAY 2
+1 OCCU Farmer
+2 DATE 1921
+2 SOUR @S1@
+3 QUAY 9
+1 DEAT
+2 DATE 12 MAR 1950
+2 SOUR @S1@
+0 TRLR
+",
+        )
+        .unwrap();
+        let joe = gedcom.find_individual("@I1@").unwrap();
+
+        let events = |min: Quay| -> Vec<EventType> {
+            gedcom
+                .timeline(joe)
+                .with_min_quay(min)
+                .iter()
+                .map(|e| e.event)
+                .collect()
+        };
+        assert!(events(Quay::Unreliable) == [EventType::Birth, EventType::Residence]);
+        assert!(events(Quay::Secondary) == [EventType::Birth, EventType::Residence]);
+        assert!(events(Quay::Direct) == [EventType::Birth]);
+        assert!(gedcom.timeline(joe)[0].quay() == Some(Quay::Direct));
+
+        // The OCCU's QUAY isn't one, and the DEAT has none
+        assert!(gedcom.timeline(joe)[2].quay().is_none());
+        assert!(gedcom.events_on(3, 12).len() == 2);
+        assert!(
+            gedcom
+                .events_on(3, 12)
+                .with_min_quay(Quay::Unreliable)
+                .len()
+                == 1
+        );
+
+        let pages = gedcom.records_citing_source("@S1@");
+        assert!(pages.len() == 4);
+        let secondary = pages.with_min_quay(Quay::Secondary);
+        assert!(secondary.len() == 1 && secondary[0].tag == Some("RESI"));
+    }
+}
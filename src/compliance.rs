@@ -0,0 +1,622 @@
+use std::fmt;
+use std::fs;
+
+use crate::error::GedcomError;
+use crate::repair::{characters, split_lines};
+
+// Checks that a file follows the GEDCOM 5.5.1 grammar, as opposed to
+// whether what it says makes sense, which is for the validate module. This
+// is for archives and other programs that only accept files that follow the
+// specification, to find out what's wrong with one before it's rejected.
+//
+// Like repair, this works line by line on the bytes of the file, so it sees
+// what the parser forgives: long lines, level jumps, malformed xrefs and
+// tags where the specification doesn't allow them. Which tags are allowed
+// where, and which are required, are checked for the records and the
+// structures most used in them; anything deeper isn't checked. Tags
+// starting with an underscore are extensions, which are allowed anywhere.
+
+/// The longest a line may be, in characters, including its level and tag
+const MAX_LINE_LENGTH: usize = 255;
+/// The longest an xref may be, including its @s
+const MAX_XREF_LENGTH: usize = 22;
+/// The longest a tag may be
+const MAX_TAG_LENGTH: usize = 31;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+/// The kind of a Violation
+pub enum ViolationKind {
+    /// A line isn't a level, an optional xref and a tag
+    Malformed,
+    /// A line is longer than 255 characters
+    LineTooLong,
+    /// A level has a leading zero
+    InvalidLevel,
+    /// A level is more than one greater than the line before's
+    LevelJump,
+    /// An xref or pointer isn't written as the specification says, or an
+    /// xref is where there shouldn't be one, or isn't where there should
+    InvalidXref,
+    /// A tag has characters other than letters, digits and underscores, or
+    /// is too long
+    InvalidTag,
+    /// A tag isn't allowed in the structure it's in
+    TagNotAllowed,
+    /// A structure is missing a line it must have
+    MissingSubordinate,
+    /// The file doesn't start with HEAD, or end with TRLR
+    RecordOrder,
+}
+
+/// A way a file doesn't follow the specification. See `check_compliance`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation {
+    /// The line of the file
+    pub line: usize,
+    pub kind: ViolationKind,
+    /// Where in the GEDCOM 5.5.1 specification the rule is, i.e. "Chapter
+    /// 2, INDIVIDUAL_RECORD"
+    pub section: &'static str,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}: {} (GEDCOM 5.5.1, {})",
+            self.line, self.message, self.section
+        )
+    }
+}
+
+/// Check a GEDCOM file's contents against the 5.5.1 grammar. Returns every
+/// violation found, in the order of the lines they're on.
+pub fn check_compliance(input: &[u8]) -> Vec<Violation> {
+    let input = input.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(input);
+    let (lines, _) = split_lines(input);
+    let mut checker = Checker::default();
+    for (i, text) in lines.iter().enumerate() {
+        checker.line(i + 1, text);
+    }
+    checker.finish(lines.len())
+}
+
+/// Check a GEDCOM file against the 5.5.1 grammar. See `check_compliance`.
+pub fn check_compliance_file(path: &str) -> Result<Vec<Violation>, GedcomError> {
+    Ok(check_compliance(&fs::read(path)?))
+}
+
+/// The tags a structure may have under it, and those it must
+struct Structure {
+    section: &'static str,
+    allowed: &'static [&'static str],
+    required: &'static [&'static str],
+}
+
+const INDIVIDUAL_EVENTS: [&str; 23] = [
+    "BIRT", "CHR", "DEAT", "BURI", "CREM", "ADOP", "BAPM", "BARM", "BASM", "BLES", "CHRA", "CONF",
+    "FCOM", "ORDN", "NATU", "EMIG", "IMMI", "CENS", "PROB", "WILL", "GRAD", "RETI", "EVEN",
+];
+
+const INDIVIDUAL_ATTRIBUTES: [&str; 14] = [
+    "CAST", "DSCR", "EDUC", "IDNO", "NATI", "NCHI", "NMR", "OCCU", "PROP", "RELI", "RESI", "SSN",
+    "TITL", "FACT",
+];
+
+const FAMILY_EVENTS: [&str; 11] = [
+    "ANUL", "CENS", "DIV", "DIVF", "ENGA", "MARB", "MARC", "MARR", "MARL", "MARS", "EVEN",
+];
+
+const LINEAGE_LINKED_GEDCOM: Structure = Structure {
+    section: "Chapter 2, LINEAGE_LINKED_GEDCOM",
+    allowed: &[
+        "HEAD", "SUBN", "FAM", "INDI", "OBJE", "NOTE", "REPO", "SOUR", "SUBM", "TRLR",
+    ],
+    required: &[],
+};
+
+const HEADER: Structure = Structure {
+    section: "Chapter 2, HEADER",
+    allowed: &[
+        "SOUR", "DEST", "DATE", "SUBM", "SUBN", "FILE", "COPR", "GEDC", "CHAR", "LANG", "PLAC",
+        "NOTE",
+    ],
+    required: &["SOUR", "SUBM", "GEDC", "CHAR"],
+};
+
+const HEADER_SOURCE: Structure = Structure {
+    section: "Chapter 2, HEADER",
+    allowed: &["VERS", "NAME", "CORP", "DATA"],
+    required: &[],
+};
+
+const HEADER_GEDC: Structure = Structure {
+    section: "Chapter 2, HEADER",
+    allowed: &["VERS", "FORM"],
+    required: &["VERS", "FORM"],
+};
+
+const INDIVIDUAL_RECORD: Structure = Structure {
+    section: "Chapter 2, INDIVIDUAL_RECORD",
+    allowed: &[
+        "RESN", "NAME", "SEX", "BIRT", "CHR", "DEAT", "BURI", "CREM", "ADOP", "BAPM", "BARM",
+        "BASM", "BLES", "CHRA", "CONF", "FCOM", "ORDN", "NATU", "EMIG", "IMMI", "CENS", "PROB",
+        "WILL", "GRAD", "RETI", "EVEN", "CAST", "DSCR", "EDUC", "IDNO", "NATI", "NCHI", "NMR",
+        "OCCU", "PROP", "RELI", "RESI", "SSN", "TITL", "FACT", "BAPL", "CONL", "ENDL", "SLGC",
+        "FAMC", "FAMS", "SUBM", "ASSO", "ALIA", "ANCI", "DESI", "RFN", "AFN", "REFN", "RIN",
+        "CHAN", "NOTE", "SOUR", "OBJE",
+    ],
+    required: &[],
+};
+
+const PERSONAL_NAME_STRUCTURE: Structure = Structure {
+    section: "Chapter 2, PERSONAL_NAME_STRUCTURE",
+    allowed: &[
+        "TYPE", "NPFX", "GIVN", "NICK", "SPFX", "SURN", "NSFX", "NOTE", "SOUR", "FONE", "ROMN",
+    ],
+    required: &[],
+};
+
+const INDIVIDUAL_EVENT_STRUCTURE: Structure = Structure {
+    section: "Chapter 2, INDIVIDUAL_EVENT_STRUCTURE",
+    allowed: &[
+        "TYPE", "DATE", "PLAC", "ADDR", "PHON", "EMAIL", "FAX", "WWW", "AGNC", "RELI", "CAUS",
+        "RESN", "NOTE", "SOUR", "OBJE", "AGE", "FAMC",
+    ],
+    required: &[],
+};
+
+const INDIVIDUAL_ATTRIBUTE_STRUCTURE: Structure = Structure {
+    section: "Chapter 2, INDIVIDUAL_ATTRIBUTE_STRUCTURE",
+    allowed: &[
+        "TYPE", "DATE", "PLAC", "ADDR", "PHON", "EMAIL", "FAX", "WWW", "AGNC", "RELI", "CAUS",
+        "RESN", "NOTE", "SOUR", "OBJE", "AGE",
+    ],
+    required: &[],
+};
+
+const CHILD_TO_FAMILY_LINK: Structure = Structure {
+    section: "Chapter 2, CHILD_TO_FAMILY_LINK",
+    allowed: &["PEDI", "STAT", "NOTE"],
+    required: &[],
+};
+
+const SPOUSE_TO_FAMILY_LINK: Structure = Structure {
+    section: "Chapter 2, SPOUSE_TO_FAMILY_LINK",
+    allowed: &["NOTE"],
+    required: &[],
+};
+
+const ASSOCIATION_STRUCTURE: Structure = Structure {
+    section: "Chapter 2, ASSOCIATION_STRUCTURE",
+    allowed: &["RELA", "SOUR", "NOTE"],
+    required: &["RELA"],
+};
+
+const FAM_RECORD: Structure = Structure {
+    section: "Chapter 2, FAM_RECORD",
+    allowed: &[
+        "RESN", "ANUL", "CENS", "DIV", "DIVF", "ENGA", "MARB", "MARC", "MARR", "MARL", "MARS",
+        "RESI", "EVEN", "HUSB", "WIFE", "CHIL", "NCHI", "SUBM", "SLGS", "REFN", "RIN", "CHAN",
+        "NOTE", "SOUR", "OBJE",
+    ],
+    required: &[],
+};
+
+const FAMILY_EVENT_STRUCTURE: Structure = Structure {
+    section: "Chapter 2, FAMILY_EVENT_STRUCTURE",
+    allowed: &[
+        "TYPE", "DATE", "PLAC", "ADDR", "PHON", "EMAIL", "FAX", "WWW", "AGNC", "RELI", "CAUS",
+        "RESN", "NOTE", "SOUR", "OBJE", "HUSB", "WIFE",
+    ],
+    required: &[],
+};
+
+const SOURCE_RECORD: Structure = Structure {
+    section: "Chapter 2, SOURCE_RECORD",
+    allowed: &[
+        "DATA", "AUTH", "TITL", "ABBR", "PUBL", "TEXT", "REPO", "REFN", "RIN", "CHAN", "NOTE",
+        "OBJE",
+    ],
+    required: &[],
+};
+
+const REPOSITORY_RECORD: Structure = Structure {
+    section: "Chapter 2, REPOSITORY_RECORD",
+    allowed: &[
+        "NAME", "ADDR", "PHON", "EMAIL", "FAX", "WWW", "NOTE", "REFN", "RIN", "CHAN",
+    ],
+    required: &["NAME"],
+};
+
+const MULTIMEDIA_RECORD: Structure = Structure {
+    section: "Chapter 2, MULTIMEDIA_RECORD",
+    allowed: &["FILE", "REFN", "RIN", "NOTE", "SOUR", "CHAN"],
+    required: &["FILE"],
+};
+
+const MULTIMEDIA_FILE: Structure = Structure {
+    section: "Chapter 2, MULTIMEDIA_RECORD",
+    allowed: &["FORM", "TITL"],
+    required: &["FORM"],
+};
+
+const NOTE_RECORD: Structure = Structure {
+    section: "Chapter 2, NOTE_RECORD",
+    allowed: &["CONC", "CONT", "REFN", "RIN", "SOUR", "CHAN"],
+    required: &[],
+};
+
+const SUBMITTER_RECORD: Structure = Structure {
+    section: "Chapter 2, SUBMITTER_RECORD",
+    allowed: &[
+        "NAME", "ADDR", "PHON", "EMAIL", "FAX", "WWW", "OBJE", "LANG", "RFN", "RIN", "NOTE", "CHAN",
+    ],
+    required: &["NAME"],
+};
+
+const SUBMISSION_RECORD: Structure = Structure {
+    section: "Chapter 2, SUBMISSION_RECORD",
+    allowed: &[
+        "SUBM", "FAMF", "TEMP", "ANCE", "DESC", "ORDI", "RIN", "NOTE", "CHAN",
+    ],
+    required: &[],
+};
+
+/// The structure the tags under `path` are in, if it's one we check
+fn structure(path: &[&str]) -> Option<&'static Structure> {
+    let structure = match path {
+        [] => &LINEAGE_LINKED_GEDCOM,
+        ["HEAD"] => &HEADER,
+        ["HEAD", "SOUR"] => &HEADER_SOURCE,
+        ["HEAD", "GEDC"] => &HEADER_GEDC,
+        ["INDI"] => &INDIVIDUAL_RECORD,
+        ["INDI", "NAME"] => &PERSONAL_NAME_STRUCTURE,
+        ["INDI", "FAMC"] => &CHILD_TO_FAMILY_LINK,
+        ["INDI", "FAMS"] => &SPOUSE_TO_FAMILY_LINK,
+        ["INDI", "ASSO"] => &ASSOCIATION_STRUCTURE,
+        ["INDI", tag] if INDIVIDUAL_EVENTS.contains(tag) => &INDIVIDUAL_EVENT_STRUCTURE,
+        ["INDI", tag] if INDIVIDUAL_ATTRIBUTES.contains(tag) => &INDIVIDUAL_ATTRIBUTE_STRUCTURE,
+        ["FAM"] => &FAM_RECORD,
+        ["FAM", tag] if FAMILY_EVENTS.contains(tag) || *tag == "RESI" => &FAMILY_EVENT_STRUCTURE,
+        ["SOUR"] => &SOURCE_RECORD,
+        ["REPO"] => &REPOSITORY_RECORD,
+        ["OBJE"] => &MULTIMEDIA_RECORD,
+        ["OBJE", "FILE"] => &MULTIMEDIA_FILE,
+        ["NOTE"] => &NOTE_RECORD,
+        ["SUBM"] => &SUBMITTER_RECORD,
+        ["SUBN"] => &SUBMISSION_RECORD,
+        _ => return None,
+    };
+    Some(structure)
+}
+
+/// A line whose subordinate lines we're still reading
+struct Open {
+    line: usize,
+    tag: String,
+    /// The tags of the lines under it
+    children: Vec<String>,
+}
+
+#[derive(Default)]
+struct Checker {
+    violations: Vec<Violation>,
+    /// The line each level is on, from the record down
+    open: Vec<Open>,
+    first: Option<String>,
+    last: Option<(usize, String)>,
+}
+
+impl Checker {
+    fn violation(
+        &mut self,
+        line: usize,
+        kind: ViolationKind,
+        section: &'static str,
+        message: String,
+    ) {
+        self.violations.push(Violation {
+            line,
+            kind,
+            section,
+            message,
+        });
+    }
+
+    fn line(&mut self, number: usize, text: &[u8]) {
+        const GEDCOM_LINE: &str = "Chapter 1, gedcom_line";
+
+        if characters(text) > MAX_LINE_LENGTH {
+            let message = format!(
+                "is {} characters long, more than {}",
+                characters(text),
+                MAX_LINE_LENGTH
+            );
+            self.violation(number, ViolationKind::LineTooLong, GEDCOM_LINE, message);
+        }
+
+        let text = String::from_utf8_lossy(text);
+        let mut words = text.splitn(3, ' ');
+        let level = words.next().unwrap_or_default();
+        let mut tag = words.next().unwrap_or_default();
+        let mut rest = words.next();
+        let mut xref = None;
+        if tag.starts_with('@') {
+            xref = Some(tag);
+            let mut words = rest.unwrap_or_default().splitn(2, ' ');
+            tag = words.next().unwrap_or_default();
+            rest = words.next();
+        }
+        let value = rest.unwrap_or_default();
+
+        let level = match level.parse::<usize>() {
+            Ok(level) if !tag.is_empty() && level.to_string().len() <= 2 => level,
+            _ => {
+                let message = "isn't a level, an optional xref and a tag".to_string();
+                self.violation(number, ViolationKind::Malformed, GEDCOM_LINE, message);
+                return;
+            }
+        };
+        let written = text.split(' ').next().unwrap_or_default();
+        if written != level.to_string() {
+            let message = format!("level {} has a leading zero", written);
+            self.violation(
+                number,
+                ViolationKind::InvalidLevel,
+                "Chapter 1, level",
+                message,
+            );
+        }
+        if level > self.open.len() {
+            let message = match self.open.len() {
+                0 => format!("the first line of a record is at level {}, not 0", level),
+                depth => format!("level jumps from {} to {}", depth - 1, level),
+            };
+            self.violation(
+                number,
+                ViolationKind::LevelJump,
+                "Chapter 1, level",
+                message,
+            );
+        }
+        // Carry on as if the level had followed on
+        let level = level.min(self.open.len());
+        while self.open.len() > level {
+            self.close();
+        }
+
+        let valid = tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if !valid {
+            let message = format!(
+                "tag {} has characters other than letters, digits and _",
+                tag
+            );
+            self.violation(number, ViolationKind::InvalidTag, "Chapter 1, tag", message);
+        } else if tag.len() > MAX_TAG_LENGTH {
+            let message = format!("tag {} is longer than {} characters", tag, MAX_TAG_LENGTH);
+            self.violation(number, ViolationKind::InvalidTag, "Chapter 1, tag", message);
+        }
+
+        match xref {
+            Some(xref) if level > 0 => {
+                let message = format!("xref {} is on a line that isn't a record", xref);
+                self.violation(
+                    number,
+                    ViolationKind::InvalidXref,
+                    "Chapter 1, xref_ID",
+                    message,
+                );
+            }
+            Some(xref) if !is_xref(xref) => {
+                let message = format!("xref {} isn't written as the specification says", xref);
+                self.violation(
+                    number,
+                    ViolationKind::InvalidXref,
+                    "Chapter 1, xref_ID",
+                    message,
+                );
+            }
+            None if level == 0 && !matches!(tag, "HEAD" | "TRLR") => {
+                let message = format!("{} record has no xref", tag);
+                self.violation(
+                    number,
+                    ViolationKind::InvalidXref,
+                    "Chapter 1, xref_ID",
+                    message,
+                );
+            }
+            _ => {}
+        }
+        // A value in @s is a pointer, unless it's an escape, i.e. @#DJULIAN@
+        if value.len() > 1
+            && value.starts_with('@')
+            && value.ends_with('@')
+            && !value.starts_with("@#")
+            && !value.starts_with("@@")
+            && !is_xref(value)
+        {
+            let message = format!("pointer {} isn't written as the specification says", value);
+            self.violation(
+                number,
+                ViolationKind::InvalidXref,
+                "Chapter 1, pointer",
+                message,
+            );
+        }
+
+        let path: Vec<&str> = self.open.iter().map(|o| o.tag.as_str()).collect();
+        let extension = path.iter().any(|t| t.starts_with('_'));
+        if valid && !extension && !tag.starts_with('_') {
+            if let Some(structure) = structure(&path) {
+                if !structure.allowed.contains(&tag) {
+                    let message = match path.last() {
+                        Some(parent) => format!("{} isn't allowed under {}", tag, parent),
+                        None => format!("{} isn't a record", tag),
+                    };
+                    let kind = ViolationKind::TagNotAllowed;
+                    self.violation(number, kind, structure.section, message);
+                }
+            }
+        }
+
+        if let Some(parent) = self.open.last_mut() {
+            parent.children.push(tag.to_string());
+        }
+        if level == 0 {
+            self.first.get_or_insert_with(|| tag.to_string());
+            self.last = Some((number, tag.to_string()));
+        }
+        self.open.push(Open {
+            line: number,
+            tag: tag.to_string(),
+            children: vec![],
+        });
+    }
+
+    /// Finish with the innermost open line, checking it has what it must
+    fn close(&mut self) {
+        let path: Vec<&str> = self.open.iter().map(|o| o.tag.as_str()).collect();
+        let missing: Vec<&str> = match structure(&path) {
+            Some(structure) => structure
+                .required
+                .iter()
+                .filter(|t| !self.open.last().unwrap().children.iter().any(|c| c == *t))
+                .copied()
+                .collect(),
+            None => vec![],
+        };
+        let section = structure(&path).map_or("", |s| s.section);
+        let open = self.open.pop().unwrap();
+        for tag in missing {
+            let message = format!("{} has no {}", open.tag, tag);
+            self.violation(
+                open.line,
+                ViolationKind::MissingSubordinate,
+                section,
+                message,
+            );
+        }
+    }
+
+    fn finish(mut self, lines: usize) -> Vec<Violation> {
+        while !self.open.is_empty() {
+            self.close();
+        }
+
+        const ORDER: &str = "Chapter 2, LINEAGE_LINKED_GEDCOM";
+        if self.first.as_deref() != Some("HEAD") {
+            let message = "the file doesn't start with HEAD".to_string();
+            self.violation(1, ViolationKind::RecordOrder, ORDER, message);
+        }
+        match self.last.clone() {
+            Some((_, tag)) if tag == "TRLR" => {}
+            _ => {
+                let message = "the file doesn't end with TRLR".to_string();
+                self.violation(lines.max(1), ViolationKind::RecordOrder, ORDER, message);
+            }
+        }
+
+        // Required lines are found missing when their structure ends, so
+        // put everything back in line order
+        self.violations.sort_by_key(|v| v.line);
+        self.violations
+    }
+}
+
+/// Whether `text` is an xref as the specification says: @, a letter or
+/// digit, then anything but @, then @, no more than 22 characters in all
+fn is_xref(text: &str) -> bool {
+    let inner = match text.strip_prefix('@').and_then(|t| t.strip_suffix('@')) {
+        Some(inner) => inner,
+        None => return false,
+    };
+    text.chars().count() <= MAX_XREF_LENGTH
+        && inner.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && !inner.contains('@')
+        && !inner.chars().any(|c| c.is_control())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compliant() {
+        let data = std::fs::read("./data/complete.ged").unwrap();
+        assert!(check_compliance(&data).is_empty());
+    }
+
+    #[test]
+    fn violations() {
+        let long = format!("1 NOTE {}", "x".repeat(250));
+        let data = [
+            "0 HEAD",
+            "1 GEDC",
+            "2 VERS 5.5.1",
+            "1 CHAR UTF-8",
+            "0 @I1@ INDI",
+            "1 NAME Joe /Bloggs/",
+            "3 GIVN Joe",
+            "1 FAMS @F@1@",
+            "1 BIRT",
+            "2 HUSB @I2@",
+            "1 _CUSTOM",
+            "2 ANYTHING",
+            long.as_str(),
+            "01 SEX M",
+            "1 ASSO @I1@",
+            "0 @@ FAM",
+            "1 @X1@ CHIL @I1@",
+            "0 REPO",
+            "1 NAME Library",
+            "1 MAR-R",
+        ]
+        .join("\r\n");
+        let violations = check_compliance(data.as_bytes());
+        let found: Vec<(usize, ViolationKind)> =
+            violations.iter().map(|v| (v.line, v.kind)).collect();
+        assert!(
+            found
+                == [
+                    (1, ViolationKind::MissingSubordinate),
+                    (1, ViolationKind::MissingSubordinate),
+                    (2, ViolationKind::MissingSubordinate),
+                    (7, ViolationKind::LevelJump),
+                    (8, ViolationKind::InvalidXref),
+                    (10, ViolationKind::TagNotAllowed),
+                    (13, ViolationKind::LineTooLong),
+                    (14, ViolationKind::InvalidLevel),
+                    (15, ViolationKind::MissingSubordinate),
+                    (16, ViolationKind::InvalidXref),
+                    (17, ViolationKind::InvalidXref),
+                    (18, ViolationKind::InvalidXref),
+                    (20, ViolationKind::InvalidTag),
+                    (20, ViolationKind::RecordOrder),
+                ]
+        );
+        assert!(violations[0].message == "HEAD has no SOUR");
+        assert!(violations[3].message == "level jumps from 1 to 3");
+        assert!(
+            violations[5].to_string()
+                == "line 10: HUSB isn't allowed under BIRT (GEDCOM 5.5.1, Chapter 2, INDIVIDUAL_EVENT_STRUCTURE)"
+        );
+        assert!(violations[8].message == "ASSO has no RELA");
+
+        let violations = check_compliance(b"0 @I1@ INDI\n0 TRLR\n");
+        assert!(violations.len() == 1);
+        assert!(violations[0].message == "the file doesn't start with HEAD");
+        assert!(check_compliance_file("no-such-file.ged").is_err());
+    }
+
+    #[test]
+    fn xrefs() {
+        assert!(is_xref("@I1@"));
+        assert!(is_xref("@1_a-b@"));
+        assert!(!is_xref("@_I1@"));
+        assert!(!is_xref("@@"));
+        assert!(!is_xref("@I1"));
+        assert!(!is_xref("@ABCDEFGHIJKLMNOPQRSTU@"));
+    }
+}
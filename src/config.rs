@@ -0,0 +1,274 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use crate::error::GedcomError;
+use crate::types::Encoding;
+use crate::validate::ValidationConfig;
+
+/// Options that control how a GEDCOM file is parsed.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GedcomConfig {
+    /// Keep the original text of every record, alongside the parsed model.
+    /// See `Gedcom::raw_records`.
+    pub preserve_unknown: bool,
+    /// Fail on the first malformed line or unrecognised structure, rather
+    /// than skipping it with a warning. See `GedcomConfig::strict`.
+    pub strict: bool,
+    /// Keep values' whitespace exactly as written. See
+    /// `GedcomConfig::preserve_whitespace`.
+    pub preserve_whitespace: bool,
+    /// Drop the text of notes and BLOBs longer than this many bytes. See
+    /// `GedcomConfig::max_text_length`.
+    pub max_text_length: Option<usize>,
+    /// Where to send warnings as they're found. See
+    /// `GedcomConfig::diagnostics`.
+    pub diagnostics: Option<Diagnostics>,
+    /// What to tell about how far parsing has got. See
+    /// `GedcomConfig::on_progress`.
+    pub progress: Option<Progress>,
+    /// A flag to stop parsing early. See `GedcomConfig::cancel_token`.
+    pub cancel: Option<CancelToken>,
+    /// The encoding to read the file as, whatever it looks like or declares.
+    /// See `GedcomConfig::force_encoding`.
+    pub encoding: Option<Encoding>,
+    /// The rules `Gedcom::validate` checks the file against. See
+    /// `GedcomConfig::validation`.
+    pub validation: ValidationConfig,
+}
+
+impl GedcomConfig {
+    /// The default configuration
+    pub fn new() -> GedcomConfig {
+        GedcomConfig::default()
+    }
+
+    /// Retain every line of the input, including those the structured model
+    /// doesn't understand (unknown or vendor tags, out-of-spec levels), in
+    /// their original order, so `write_gedcom` can reproduce the file
+    /// byte-for-byte, apart from the records changed since.
+    pub fn preserve_unknown(mut self) -> GedcomConfig {
+        self.preserve_unknown = true;
+        self
+    }
+
+    /// Stop parsing at the first malformed line, or the first structure
+    /// the parser would skip (an unrecognised tag, or a link without a
+    /// pointer), and return it as an error. By default, they're skipped and
+    /// recorded in `Gedcom::warnings`.
+    pub fn strict(mut self) -> GedcomConfig {
+        self.strict = true;
+        self
+    }
+
+    /// Keep the whitespace at the start of every value, as the
+    /// specification requires: only the single space after the tag is a
+    /// delimiter. This matters for text continued over CONC and CONT lines,
+    /// such as an indented transcription in a note. By default, that
+    /// whitespace is dropped, since it's more often a mistake in a
+    /// hand-edited file.
+    pub fn preserve_whitespace(mut self) -> GedcomConfig {
+        self.preserve_whitespace = true;
+        self
+    }
+
+    /// Don't keep the text of any NOTE or multimedia BLOB longer than
+    /// `bytes`, to bound memory when only the structure of the tree is
+    /// needed. The NOTE or BLOB is still there, but empty, and pointers to
+    /// NOTE records are kept as they are. What was dropped, and how long it
+    /// was, is listed in `Gedcom::skipped_text`.
+    ///
+    /// The text isn't held in memory at all while parsing, unless
+    /// `preserve_unknown` is also set.
+    pub fn max_text_length(mut self, bytes: usize) -> GedcomConfig {
+        self.max_text_length = Some(bytes);
+        self
+    }
+
+    /// Send each warning to `sink` as soon as it's found, i.e. to show
+    /// progress in a GUI or log it on a server, as well as recording it in
+    /// `Gedcom::warnings`.
+    pub fn diagnostics<S: DiagnosticsSink + 'static>(mut self, sink: S) -> GedcomConfig {
+        self.diagnostics = Some(Diagnostics(Arc::new(sink)));
+        self
+    }
+
+    /// Call `callback` every so often while parsing, and once at the end,
+    /// with how many records have been parsed, how many bytes have been
+    /// read, and how many there are in all, if that's known (as it is for a
+    /// file), i.e. to show a progress bar while a large file loads.
+    pub fn on_progress<F>(mut self, callback: F) -> GedcomConfig
+    where
+        F: Fn(usize, usize, Option<usize>) + Send + Sync + 'static,
+    {
+        self.progress = Some(Progress(Arc::new(callback)));
+        self
+    }
+
+    /// Stop parsing, with `GedcomError::Cancelled`, as soon as `token` is
+    /// cancelled, i.e. from another thread when the user closes an import
+    /// dialog.
+    pub fn cancel_token(mut self, token: CancelToken) -> GedcomConfig {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Read the file as `encoding`, rather than going by its byte order
+    /// mark or the look of its first line. Old exports often declare the
+    /// wrong CHAR, i.e. ANSI for a file that's really UTF-8, or UTF-8 for
+    /// one that's really ANSI, and the warnings about it say which to try.
+    pub fn force_encoding(mut self, encoding: Encoding) -> GedcomConfig {
+        self.encoding = Some(encoding);
+        self
+    }
+
+    /// Check the parsed Gedcom against `config` in `Gedcom::validate`, i.e.
+    /// to turn off the rules that don't matter to an application, or make
+    /// the ones that do errors, once for every file it opens.
+    pub fn validation(mut self, config: ValidationConfig) -> GedcomConfig {
+        self.validation = config;
+        self
+    }
+}
+
+/// A flag for stopping a parse that's under way. Clones share the flag, so
+/// one can be given to the GedcomConfig and another kept to cancel it with.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> CancelToken {
+        CancelToken::default()
+    }
+
+    /// Stop any parse using this token
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl PartialEq for CancelToken {
+    /// Tokens are equal if they share a flag
+    fn eq(&self, other: &CancelToken) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Something that's told about problems found while parsing, as they're
+/// found. See `GedcomConfig::diagnostics`.
+///
+/// Any function or closure that takes a `&GedcomError` is a sink, i.e.
+/// `|warning| log::warn!("{}", warning)`.
+pub trait DiagnosticsSink: Send + Sync {
+    fn warning(&self, warning: &GedcomError);
+}
+
+impl<F> DiagnosticsSink for F
+where
+    F: Fn(&GedcomError) + Send + Sync,
+{
+    fn warning(&self, warning: &GedcomError) {
+        self(warning)
+    }
+}
+
+/// A DiagnosticsSink, shared by every copy of the GedcomConfig it's in
+#[derive(Clone)]
+pub struct Diagnostics(Arc<dyn DiagnosticsSink>);
+
+impl Diagnostics {
+    pub fn warning(&self, warning: &GedcomError) {
+        self.0.warning(warning)
+    }
+}
+
+impl fmt::Debug for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Diagnostics")
+    }
+}
+
+impl PartialEq for Diagnostics {
+    /// Sinks are equal if they're the same sink
+    fn eq(&self, other: &Diagnostics) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+type ProgressCallback = dyn Fn(usize, usize, Option<usize>) + Send + Sync;
+
+/// A progress callback, shared by every copy of the GedcomConfig it's in.
+/// See `GedcomConfig::on_progress`.
+#[derive(Clone)]
+pub struct Progress(Arc<ProgressCallback>);
+
+impl Progress {
+    pub fn report(&self, records: usize, bytes: usize, total_bytes: Option<usize>) {
+        (self.0)(records, bytes, total_bytes)
+    }
+}
+
+impl fmt::Debug for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Progress")
+    }
+}
+
+impl PartialEq for Progress {
+    /// Callbacks are equal if they're the same callback
+    fn eq(&self, other: &Progress) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config() {
+        assert!(!GedcomConfig::new().preserve_unknown);
+        assert!(GedcomConfig::new().preserve_unknown().preserve_unknown);
+        assert!(!GedcomConfig::new().strict);
+        assert!(GedcomConfig::new().strict().strict);
+        assert!(!GedcomConfig::new().preserve_whitespace);
+        assert!(
+            GedcomConfig::new()
+                .preserve_whitespace()
+                .preserve_whitespace
+        );
+        assert!(GedcomConfig::new().max_text_length.is_none());
+        assert!(GedcomConfig::new().max_text_length(80).max_text_length == Some(80));
+
+        assert!(GedcomConfig::new().diagnostics.is_none());
+        let config = GedcomConfig::new().diagnostics(|_: &GedcomError| {});
+        assert!(config.diagnostics.is_some());
+        assert!(config.clone() == config);
+        assert!(config != GedcomConfig::new().diagnostics(|_: &GedcomError| {}));
+
+        assert!(GedcomConfig::new().progress.is_none());
+        let config = GedcomConfig::new().on_progress(|_, _, _| {});
+        assert!(config.progress.is_some() && config.clone() == config);
+
+        let token = CancelToken::new();
+        let config = GedcomConfig::new().cancel_token(token.clone());
+        assert!(config.cancel == Some(token.clone()));
+        assert!(!config.cancel.as_ref().unwrap().is_cancelled());
+        token.cancel();
+        assert!(config.cancel.as_ref().unwrap().is_cancelled());
+        assert!(token != CancelToken::new());
+
+        let validation = ValidationConfig::new().max_lifespan(100);
+        assert!(GedcomConfig::new().validation == ValidationConfig::default());
+        assert!(
+            GedcomConfig::new()
+                .validation(validation.clone())
+                .validation
+                == validation
+        );
+    }
+}
@@ -0,0 +1,239 @@
+use crate::relationship::{OwnedRelationship, Relationship};
+
+// How much autosomal DNA relatives are expected to share, for comparing a
+// tree against DNA matches. The averages and ranges are the Shared cM
+// Project's (Blaine Bettinger, version 4, March 2020), from tens of
+// thousands of reported matches; the ranges are its 99th percentiles. The
+// percentages are the theoretical ones: a parent shares half, and each
+// further generation halves it, doubled again where the line runs through
+// both of a couple rather than one.
+//
+// The project covers blood relatives out to fourth cousins. Closer to
+// that, the ranges overlap so much that a cM amount narrows down the
+// relationship, but rarely settles it.
+
+/// How much DNA two relatives are expected to share
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SharedDna {
+    /// The share expected in theory, as a percentage, i.e. 12.5 for first
+    /// cousins
+    pub percent: f64,
+    /// The average reported, in centimorgans
+    pub average: f64,
+    /// The least reported, in centimorgans
+    pub low: f64,
+    /// The most reported, in centimorgans
+    pub high: f64,
+}
+
+impl SharedDna {
+    /// Whether `cm` is within the range reported
+    pub fn contains(&self, cm: f64) -> bool {
+        self.low <= cm && cm <= self.high
+    }
+}
+
+/// A relationship that could account for an amount of shared DNA. See
+/// `relationships_consistent_with_cm`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Candidate {
+    /// Both sides of the relationship, i.e. "grandparent or grandchild"
+    pub description: &'static str,
+    /// The generations from each of them up to the common ancestor, the
+    /// nearer first
+    pub generations: (usize, usize),
+    pub half: bool,
+    pub expected: SharedDna,
+}
+
+// ((generations up, generations down, half), description, [average, low,
+// high] in cM), with up no more than down
+type Row = ((usize, usize, bool), &'static str, [u16; 3]);
+const TABLE: [Row; 23] = [
+    ((0, 1, false), "parent or child", [3485, 2376, 3720]),
+    ((1, 1, false), "sibling", [2613, 1613, 3488]),
+    ((1, 1, true), "half-sibling", [1759, 1160, 2436]),
+    (
+        (0, 2, false),
+        "grandparent or grandchild",
+        [1754, 984, 2462],
+    ),
+    (
+        (1, 2, false),
+        "aunt/uncle or niece/nephew",
+        [1741, 1201, 2282],
+    ),
+    (
+        (1, 2, true),
+        "half-aunt/uncle or half-niece/nephew",
+        [871, 492, 1315],
+    ),
+    (
+        (0, 3, false),
+        "great-grandparent or great-grandchild",
+        [887, 485, 1486],
+    ),
+    (
+        (1, 3, false),
+        "great-aunt/uncle or great-niece/nephew",
+        [850, 330, 1467],
+    ),
+    (
+        (1, 3, true),
+        "half great-aunt/uncle or half great-niece/nephew",
+        [431, 125, 765],
+    ),
+    ((2, 2, false), "1st cousin", [866, 396, 1397]),
+    ((2, 2, true), "half 1st cousin", [449, 156, 979]),
+    (
+        (0, 4, false),
+        "2nd great-grandparent or 2nd great-grandchild",
+        [436, 185, 694],
+    ),
+    (
+        (1, 4, false),
+        "2nd great-aunt/uncle or 2nd great-niece/nephew",
+        [420, 186, 713],
+    ),
+    ((2, 3, false), "1st cousin once removed", [433, 102, 980]),
+    ((2, 3, true), "half 1st cousin once removed", [224, 57, 530]),
+    ((2, 4, false), "1st cousin twice removed", [221, 33, 471]),
+    ((3, 3, false), "2nd cousin", [229, 41, 592]),
+    ((3, 3, true), "half 2nd cousin", [120, 10, 397]),
+    ((3, 4, false), "2nd cousin once removed", [122, 14, 353]),
+    ((4, 4, false), "3rd cousin", [73, 0, 234]),
+    ((4, 5, false), "3rd cousin once removed", [48, 0, 192]),
+    ((5, 5, false), "4th cousin", [35, 0, 139]),
+    ((5, 6, false), "4th cousin once removed", [28, 0, 126]),
+];
+
+/// The DNA expected to be shared by relatives `up` and `down` generations
+/// below their most recent common ancestor(s), as in `Relationship`. None
+/// for a relationship the Shared cM Project doesn't cover, i.e. fifth
+/// cousins, or half-relatives along a direct line, which can't be.
+pub fn expected_shared_dna(up: usize, down: usize, half: bool) -> Option<SharedDna> {
+    let (up, down) = (up.min(down), up.max(down));
+    TABLE
+        .iter()
+        .find(|row| row.0 == (up, down, half))
+        .map(|&(_, _, cm)| shared(up, down, half, cm))
+}
+
+/// The relationships whose reported range includes `cm` centimorgans,
+/// those with the nearest average first
+pub fn relationships_consistent_with_cm(cm: f64) -> Vec<Candidate> {
+    let mut candidates: Vec<Candidate> = TABLE
+        .iter()
+        .map(|&((up, down, half), description, cm)| Candidate {
+            description,
+            generations: (up, down),
+            half,
+            expected: shared(up, down, half, cm),
+        })
+        .filter(|c| c.expected.contains(cm))
+        .collect();
+    candidates.sort_by(|a, b| {
+        let distance = |c: &Candidate| (c.expected.average - cm).abs();
+        distance(a).total_cmp(&distance(b))
+    });
+    candidates
+}
+
+fn shared(up: usize, down: usize, half: bool, cm: [u16; 3]) -> SharedDna {
+    let [average, low, high] = cm.map(f64::from);
+    // A direct ancestor is one person, and so is a half-relative's common
+    // ancestor; otherwise it's a couple, doubling what's shared
+    let ancestors = if up == 0 || half { 1.0 } else { 2.0 };
+    SharedDna {
+        percent: 100.0 * ancestors / 2f64.powi((up + down) as i32),
+        average,
+        low,
+        high,
+    }
+}
+
+impl Relationship<'_> {
+    /// The DNA the two are expected to share. None if they're related by
+    /// marriage, or too distantly for the tables. See `expected_shared_dna`.
+    pub fn shared_dna(&self) -> Option<SharedDna> {
+        if self.common_ancestors.is_empty() {
+            return None;
+        }
+        expected_shared_dna(self.generations_up, self.generations_down, self.half)
+    }
+}
+
+impl OwnedRelationship {
+    /// See `Relationship::shared_dna`
+    pub fn shared_dna(&self) -> Option<SharedDna> {
+        if self.common_ancestors.is_empty() {
+            return None;
+        }
+        expected_shared_dna(self.generations_up, self.generations_down, self.half)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_gedcom_from_str;
+
+    #[test]
+    fn expected() {
+        let cousins = expected_shared_dna(2, 2, false).unwrap();
+        assert!(cousins.percent == 12.5 && cousins.average == 866.0);
+        assert!(cousins.contains(396.0) && !cousins.contains(1400.0));
+        assert!(expected_shared_dna(0, 1, false).unwrap().percent == 50.0);
+        assert!(expected_shared_dna(1, 1, true).unwrap().percent == 25.0);
+        // The same either way round
+        assert!(expected_shared_dna(3, 2, false) == expected_shared_dna(2, 3, false));
+        assert!(expected_shared_dna(6, 6, false).is_none());
+        assert!(expected_shared_dna(0, 2, true).is_none());
+    }
+
+    #[test]
+    fn consistent_with_cm() {
+        let found: Vec<&str> = relationships_consistent_with_cm(3600.0)
+            .iter()
+            .map(|c| c.description)
+            .collect();
+        assert!(found == ["parent or child"]);
+
+        let found = relationships_consistent_with_cm(870.0);
+        assert!(found[0].description == "half-aunt/uncle or half-niece/nephew");
+        assert!(found.iter().any(|c| c.generations == (2, 2) && !c.half));
+        assert!(found.iter().all(|c| c.expected.contains(870.0)));
+        assert!(relationships_consistent_with_cm(4000.0).is_empty());
+    }
+
+    #[test]
+    fn relationship_shared_dna() {
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @I1@ INDI
+1 FAMS @F1@
+0 @I2@ INDI
+1 FAMS @F1@
+0 @I3@ INDI
+1 FAMC @F1@
+0 @F1@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 CHIL @I3@
+0 TRLR
+",
+        )
+        .unwrap();
+        let individual = |xref: &str| gedcom.find_individual(xref).unwrap();
+
+        let child = gedcom
+            .find_relationship(individual("@I1@"), individual("@I3@"))
+            .unwrap();
+        assert!(child.shared_dna().unwrap().average == 3485.0);
+        let wife = gedcom
+            .find_relationship(individual("@I1@"), individual("@I2@"))
+            .unwrap();
+        assert!(wife.shared_dna().is_none());
+        assert!(child.into_owned().shared_dna().unwrap().percent == 50.0);
+    }
+}
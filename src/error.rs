@@ -0,0 +1,418 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+use crate::types::Position;
+
+// A large, messy file can have thousands of warnings that say much the same
+// thing, i.e. a dangling pointer for every child of a deleted family. Each
+// variant is a category of its own, so grouping by it, and only showing the
+// first few of each, keeps a summary readable.
+
+/// An error, or warning, encountered while reading a GEDCOM file
+#[derive(Debug)]
+pub enum GedcomError {
+    /// The file couldn't be read
+    Io(io::Error),
+    /// A line, or the structure it belongs to, couldn't be parsed
+    ParseError {
+        line: Option<usize>,
+        offset: Option<usize>,
+        tag: String,
+        message: String,
+    },
+    /// A record is missing something it's expected to have
+    MissingData {
+        line: Option<usize>,
+        offset: Option<usize>,
+        xref: Option<String>,
+        message: String,
+    },
+    /// A line isn't a GEDCOM line, or its level doesn't follow on from the
+    /// line before it, so was skipped
+    MalformedLine {
+        line: Option<usize>,
+        offset: Option<usize>,
+        /// The tag of the record the line is in, i.e. "INDI"
+        record: Option<String>,
+        /// The xref of the record the line is in
+        xref: Option<String>,
+        /// The line, as it was read
+        content: String,
+        problem: LineProblem,
+    },
+    /// A line's xref isn't an @, a name and another @
+    InvalidXref {
+        line: Option<usize>,
+        offset: Option<usize>,
+        /// The tag of the record the line is in
+        record: Option<String>,
+        xref: String,
+    },
+    /// The header's GEDC VERS isn't a version of GEDCOM we can read, i.e.
+    /// 5.5.x or 7.x. The file is read as best we can.
+    UnsupportedVersion {
+        line: Option<usize>,
+        offset: Option<usize>,
+        version: String,
+    },
+    /// A record points to a record that doesn't exist
+    DanglingReference {
+        line: Option<usize>,
+        offset: Option<usize>,
+        /// The tag of the record with the pointer, i.e. "FAM"
+        record: &'static str,
+        /// The xref of the record with the pointer
+        xref: Option<String>,
+        /// The tag of the pointer, i.e. "CHIL"
+        tag: &'static str,
+        /// Where it points
+        pointer: String,
+    },
+    /// Parsing was stopped by a CancelToken before the end of the input
+    Cancelled,
+}
+
+/// What's wrong with a line that couldn't be parsed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineProblem {
+    /// It doesn't start with a level and a space
+    NotALine,
+    /// Its tag is missing, or has characters a tag can't
+    MalformedTag,
+    /// Its level is too big to be one
+    InvalidLevel,
+    /// Its level is more than one deeper than the line before it
+    SkippedLevel { level: u8, previous: u8 },
+    /// It isn't level 0, but there's no record for it to be in
+    OutsideRecord { level: u8 },
+    /// It has a control character other than a tab, i.e. a stray carriage
+    /// return
+    ControlCharacter,
+}
+
+impl GedcomError {
+    /// The line number (starting from 1) the error was found on, if known
+    pub fn line(&self) -> Option<usize> {
+        match self {
+            GedcomError::Io(_) | GedcomError::Cancelled => None,
+            GedcomError::ParseError { line, .. }
+            | GedcomError::MissingData { line, .. }
+            | GedcomError::MalformedLine { line, .. }
+            | GedcomError::InvalidXref { line, .. }
+            | GedcomError::UnsupportedVersion { line, .. }
+            | GedcomError::DanglingReference { line, .. } => *line,
+        }
+    }
+
+    /// The byte offset of the start of the offending line, if known
+    pub fn offset(&self) -> Option<usize> {
+        match self {
+            GedcomError::Io(_) | GedcomError::Cancelled => None,
+            GedcomError::ParseError { offset, .. }
+            | GedcomError::MissingData { offset, .. }
+            | GedcomError::MalformedLine { offset, .. }
+            | GedcomError::InvalidXref { offset, .. }
+            | GedcomError::UnsupportedVersion { offset, .. }
+            | GedcomError::DanglingReference { offset, .. } => *offset,
+        }
+    }
+
+    /// The kind of error, for grouping them, i.e. "dangling-reference"
+    pub fn category(&self) -> &'static str {
+        match self {
+            GedcomError::Io(_) => "io",
+            GedcomError::ParseError { .. } => "parse",
+            GedcomError::MissingData { .. } => "missing-data",
+            GedcomError::MalformedLine { .. } => "malformed-line",
+            GedcomError::InvalidXref { .. } => "invalid-xref",
+            GedcomError::UnsupportedVersion { .. } => "unsupported-version",
+            GedcomError::DanglingReference { .. } => "dangling-reference",
+            GedcomError::Cancelled => "cancelled",
+        }
+    }
+
+    /// The tag of the record the error was found in, i.e. "INDI", if known
+    pub fn record(&self) -> Option<&str> {
+        match self {
+            GedcomError::MalformedLine { record, .. } | GedcomError::InvalidXref { record, .. } => {
+                record.as_deref()
+            }
+            GedcomError::DanglingReference { record, .. } => Some(record),
+            GedcomError::UnsupportedVersion { .. } => Some("HEAD"),
+            _ => None,
+        }
+    }
+
+    /// The xref of the record the error was found in, if known
+    pub fn xref(&self) -> Option<&str> {
+        match self {
+            GedcomError::MissingData { xref, .. }
+            | GedcomError::MalformedLine { xref, .. }
+            | GedcomError::DanglingReference { xref, .. } => xref.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// What went wrong, without the line number
+    pub fn message(&self) -> String {
+        match self {
+            GedcomError::Io(err) => err.to_string(),
+            GedcomError::MalformedLine {
+                content, problem, ..
+            } => {
+                let digits = content.chars().take_while(|c| c.is_ascii_digit()).count();
+                let mut words = content[digits..].split(' ').filter(|s| !s.is_empty());
+                let tag = match words.next() {
+                    Some(xref) if xref.starts_with('@') => words.next(),
+                    tag => tag,
+                };
+                let tag = tag.unwrap_or_default();
+                match problem {
+                    LineProblem::NotALine => format!("Not a GEDCOM line: '{}'", content),
+                    LineProblem::MalformedTag => format!("Missing or malformed tag: '{}'", content),
+                    LineProblem::InvalidLevel => {
+                        format!("{}: Invalid level: '{}'", tag, &content[..digits])
+                    }
+                    LineProblem::SkippedLevel { level, previous } => format!(
+                        "{}: Skipped level {} line following level {}",
+                        tag, level, previous
+                    ),
+                    LineProblem::OutsideRecord { level } => format!(
+                        "{}: Skipped level {} line outside of any record",
+                        tag, level
+                    ),
+                    LineProblem::ControlCharacter => {
+                        format!(
+                            "{}: Control character in line: '{}'",
+                            tag,
+                            content.escape_debug()
+                        )
+                    }
+                }
+            }
+            GedcomError::ParseError { tag, message, .. } => {
+                if tag.is_empty() {
+                    message.clone()
+                } else {
+                    format!("{}: {}", tag, message)
+                }
+            }
+            GedcomError::InvalidXref { xref, .. } => format!("Malformed xref: '{}'", xref),
+            GedcomError::UnsupportedVersion { version, .. } => {
+                format!("Unsupported GEDCOM version: {}", version)
+            }
+            GedcomError::DanglingReference {
+                xref, tag, pointer, ..
+            } => {
+                let message = format!("{} {} points to a record that doesn't exist", tag, pointer);
+                match xref {
+                    Some(xref) => format!("{}: {}", xref, message),
+                    None => message,
+                }
+            }
+            GedcomError::MissingData { xref, message, .. } => match xref {
+                Some(xref) => format!("{}: {}", xref, message),
+                None => message.clone(),
+            },
+            GedcomError::Cancelled => "Parsing was cancelled".to_string(),
+        }
+    }
+
+    /// The position of the error in the file, if known
+    pub fn position(&self) -> Option<Position> {
+        match (self.line(), self.offset()) {
+            (Some(line), Some(offset)) => Some(Position { line, offset }),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for GedcomError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line() {
+            write!(f, "line {}: ", line)?;
+        }
+        write!(f, "{}", self.message())
+    }
+}
+
+impl Error for GedcomError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GedcomError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for GedcomError {
+    fn from(err: io::Error) -> Self {
+        GedcomError::Io(err)
+    }
+}
+
+/// Warnings of one category, in the order they were found, with only the
+/// first few kept. See `summarize_warnings`.
+#[derive(Debug)]
+pub struct WarningGroup<'a> {
+    pub category: &'static str,
+    pub warnings: Vec<&'a GedcomError>,
+    /// How many more there were
+    pub more: usize,
+}
+
+impl WarningGroup<'_> {
+    /// How many there were in all
+    pub fn total(&self) -> usize {
+        self.warnings.len() + self.more
+    }
+}
+
+impl fmt::Display for WarningGroup<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} ({})", self.category, thousands(self.total()))?;
+        for warning in &self.warnings {
+            writeln!(f, "  {}", warning)?;
+        }
+        if self.more > 0 {
+            writeln!(f, "  and {} more like this", thousands(self.more))?;
+        }
+        Ok(())
+    }
+}
+
+/// The warnings by category, each in the order they were found
+pub fn warnings_by_category(warnings: &[GedcomError]) -> BTreeMap<&'static str, Vec<&GedcomError>> {
+    let mut categories: BTreeMap<&'static str, Vec<&GedcomError>> = BTreeMap::new();
+    for warning in warnings {
+        categories
+            .entry(warning.category())
+            .or_default()
+            .push(warning);
+    }
+    categories
+}
+
+/// The warnings by category, keeping only the first `limit` of each
+pub fn summarize_warnings(warnings: &[GedcomError], limit: usize) -> Vec<WarningGroup<'_>> {
+    warnings_by_category(warnings)
+        .into_iter()
+        .map(|(category, mut warnings)| {
+            let more = warnings.len().saturating_sub(limit);
+            warnings.truncate(limit);
+            WarningGroup {
+                category,
+                warnings,
+                more,
+            }
+        })
+        .collect()
+}
+
+/// A count with commas between the thousands, i.e. "4,312"
+fn thousands(n: usize) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display() {
+        let err = GedcomError::MissingData {
+            line: Some(42),
+            offset: Some(1024),
+            xref: Some("@I1@".to_string()),
+            message: "Individual has no name".to_string(),
+        };
+        assert!(err.to_string() == "line 42: @I1@: Individual has no name");
+        assert!(err.message() == "@I1@: Individual has no name");
+        assert!(
+            err.position()
+                == Some(Position {
+                    line: 42,
+                    offset: 1024
+                })
+        );
+
+        let err = GedcomError::ParseError {
+            line: None,
+            offset: None,
+            tag: String::new(),
+            message: "Not a GEDCOM line".to_string(),
+        };
+        assert!(err.to_string() == "Not a GEDCOM line");
+        assert!(err.position().is_none());
+
+        let err = GedcomError::MalformedLine {
+            line: Some(3),
+            offset: None,
+            record: Some("INDI".to_string()),
+            xref: Some("@I1@".to_string()),
+            content: "3 @N1@ NOTE Hi".to_string(),
+            problem: LineProblem::SkippedLevel {
+                level: 3,
+                previous: 1,
+            },
+        };
+        assert!(err.to_string() == "line 3: NOTE: Skipped level 3 line following level 1");
+
+        let err = GedcomError::DanglingReference {
+            line: Some(7),
+            offset: Some(90),
+            record: "FAM",
+            xref: Some("@F1@".to_string()),
+            tag: "CHIL",
+            pointer: "@I9@".to_string(),
+        };
+        assert!(err.to_string() == "line 7: @F1@: CHIL @I9@ points to a record that doesn't exist");
+        assert!(err.record() == Some("FAM") && err.xref() == Some("@F1@"));
+
+        let err = GedcomError::UnsupportedVersion {
+            line: Some(1),
+            offset: Some(0),
+            version: "4.0".to_string(),
+        };
+        assert!(err.message() == "Unsupported GEDCOM version: 4.0");
+        assert!(err.record() == Some("HEAD") && err.xref().is_none());
+    }
+
+    #[test]
+    fn summary() {
+        let dangling = |pointer: usize| GedcomError::DanglingReference {
+            line: Some(pointer),
+            offset: None,
+            record: "FAM",
+            xref: Some("@F1@".to_string()),
+            tag: "CHIL",
+            pointer: format!("@I{}@", pointer),
+        };
+        let mut warnings: Vec<GedcomError> = (1..=4315).map(dangling).collect();
+        warnings.push(GedcomError::Cancelled);
+
+        let categories = warnings_by_category(&warnings);
+        assert!(
+            categories.keys().copied().collect::<Vec<_>>() == ["cancelled", "dangling-reference"]
+        );
+        assert!(categories["dangling-reference"].len() == 4315);
+
+        let summary = summarize_warnings(&warnings, 3);
+        assert!(summary[0].total() == 1 && summary[0].more == 0);
+        assert!(summary[1].warnings.len() == 3 && summary[1].more == 4312);
+        let text = summary[1].to_string();
+        assert!(text.starts_with("dangling-reference (4,315)\n  line 1: @F1@: CHIL @I1@ "));
+        assert!(text.ends_with("\n  and 4,312 more like this\n"));
+        assert!(thousands(999) == "999" && thousands(1_234_567) == "1,234,567");
+    }
+}
@@ -0,0 +1,435 @@
+pub mod svg;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::error::GedcomError;
+use crate::names;
+use crate::types::{EventDetail, Family, Gedcom, Gender, Individual, Pedigree};
+
+/// A column of the individuals table
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IndividualColumn {
+    Xref,
+    /// The first name, without the slashes around the surname
+    Name,
+    Given,
+    Surname,
+    Sex,
+    BirthDate,
+    BirthPlace,
+    DeathDate,
+    DeathPlace,
+    /// The husband of the family the individual was born into
+    Father,
+    /// The wife of the family the individual was born into
+    Mother,
+}
+
+impl IndividualColumn {
+    pub const ALL: [IndividualColumn; 11] = [
+        IndividualColumn::Xref,
+        IndividualColumn::Name,
+        IndividualColumn::Given,
+        IndividualColumn::Surname,
+        IndividualColumn::Sex,
+        IndividualColumn::BirthDate,
+        IndividualColumn::BirthPlace,
+        IndividualColumn::DeathDate,
+        IndividualColumn::DeathPlace,
+        IndividualColumn::Father,
+        IndividualColumn::Mother,
+    ];
+
+    /// The column's heading, which is also what `from_str` takes
+    pub fn name(&self) -> &'static str {
+        match self {
+            IndividualColumn::Xref => "xref",
+            IndividualColumn::Name => "name",
+            IndividualColumn::Given => "given",
+            IndividualColumn::Surname => "surname",
+            IndividualColumn::Sex => "sex",
+            IndividualColumn::BirthDate => "birth-date",
+            IndividualColumn::BirthPlace => "birth-place",
+            IndividualColumn::DeathDate => "death-date",
+            IndividualColumn::DeathPlace => "death-place",
+            IndividualColumn::Father => "father",
+            IndividualColumn::Mother => "mother",
+        }
+    }
+
+    fn value(&self, gedcom: &Gedcom, individual: &Individual) -> String {
+        let name = individual.names.first().map(|n| &n.name);
+        let birth = individual.birth.iter().map(|b| &b.event.detail);
+        let death = individual.death.iter().filter_map(|d| d.event.as_ref());
+        let parent = |husband: bool| {
+            let family = birth_family(gedcom, individual)?;
+            let xref = if husband {
+                family.husband_xref()
+            } else {
+                family.wife_xref()
+            };
+            xref.map(|x| x.to_string())
+        };
+
+        let value = match self {
+            IndividualColumn::Xref => individual.xref.clone(),
+            IndividualColumn::Name => name
+                .and_then(|n| n.value.as_deref())
+                .map(names::strip_slashes),
+            IndividualColumn::Given => name.and_then(|n| n.given.clone()),
+            IndividualColumn::Surname => name.and_then(|n| n.surname.clone()),
+            IndividualColumn::Sex => Some(sex(&individual.gender).to_string()),
+            IndividualColumn::BirthDate => date(birth),
+            IndividualColumn::BirthPlace => place(birth),
+            IndividualColumn::DeathDate => date(death),
+            IndividualColumn::DeathPlace => place(death),
+            IndividualColumn::Father => parent(true),
+            IndividualColumn::Mother => parent(false),
+        };
+        value.unwrap_or_default()
+    }
+}
+
+impl FromStr for IndividualColumn {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<IndividualColumn, Self::Err> {
+        let input = input.trim().to_lowercase().replace('_', "-");
+        IndividualColumn::ALL
+            .iter()
+            .copied()
+            .find(|c| c.name() == input)
+            .ok_or(())
+    }
+}
+
+/// A column of the families table
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FamilyColumn {
+    Xref,
+    Husband,
+    HusbandName,
+    Wife,
+    WifeName,
+    MarriageDate,
+    MarriagePlace,
+    DivorceDate,
+    /// The children's xrefs, separated by spaces
+    Children,
+}
+
+impl FamilyColumn {
+    pub const ALL: [FamilyColumn; 9] = [
+        FamilyColumn::Xref,
+        FamilyColumn::Husband,
+        FamilyColumn::HusbandName,
+        FamilyColumn::Wife,
+        FamilyColumn::WifeName,
+        FamilyColumn::MarriageDate,
+        FamilyColumn::MarriagePlace,
+        FamilyColumn::DivorceDate,
+        FamilyColumn::Children,
+    ];
+
+    /// The column's heading, which is also what `from_str` takes
+    pub fn name(&self) -> &'static str {
+        match self {
+            FamilyColumn::Xref => "xref",
+            FamilyColumn::Husband => "husband",
+            FamilyColumn::HusbandName => "husband-name",
+            FamilyColumn::Wife => "wife",
+            FamilyColumn::WifeName => "wife-name",
+            FamilyColumn::MarriageDate => "marriage-date",
+            FamilyColumn::MarriagePlace => "marriage-place",
+            FamilyColumn::DivorceDate => "divorce-date",
+            FamilyColumn::Children => "children",
+        }
+    }
+
+    fn value(&self, gedcom: &Gedcom, family: &Family) -> String {
+        let marriage = family.marriage.iter().filter_map(|m| m.detail.as_ref());
+        let divorce = family.divorce.iter().filter_map(|d| d.detail.as_ref());
+        let spouse_name = |xref: Option<&str>| {
+            let spouse = gedcom.find_individual(xref?)?;
+            Some(IndividualColumn::Name.value(gedcom, spouse))
+        };
+
+        let value = match self {
+            FamilyColumn::Xref => Some(family.xref.clone()),
+            FamilyColumn::Husband => family.husband_xref().map(|x| x.to_string()),
+            FamilyColumn::HusbandName => spouse_name(family.husband_xref()),
+            FamilyColumn::Wife => family.wife_xref().map(|x| x.to_string()),
+            FamilyColumn::WifeName => spouse_name(family.wife_xref()),
+            FamilyColumn::MarriageDate => date(marriage),
+            FamilyColumn::MarriagePlace => place(marriage),
+            FamilyColumn::DivorceDate => date(divorce),
+            FamilyColumn::Children => Some(children(gedcom, family).join(" ")),
+        };
+        value.unwrap_or_default()
+    }
+}
+
+impl FromStr for FamilyColumn {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<FamilyColumn, Self::Err> {
+        let input = input.trim().to_lowercase().replace('_', "-");
+        FamilyColumn::ALL
+            .iter()
+            .copied()
+            .find(|c| c.name() == input)
+            .ok_or(())
+    }
+}
+
+/// How to write the individuals and families tables
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// What goes between fields: a comma, or a tab for TSV
+    pub delimiter: char,
+    pub individual_columns: Vec<IndividualColumn>,
+    pub family_columns: Vec<FamilyColumn>,
+}
+
+impl Default for CsvOptions {
+    fn default() -> CsvOptions {
+        CsvOptions {
+            delimiter: ',',
+            individual_columns: IndividualColumn::ALL.to_vec(),
+            family_columns: FamilyColumn::ALL.to_vec(),
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Comma-separated, with every column
+    pub fn new() -> CsvOptions {
+        CsvOptions::default()
+    }
+
+    /// Separate fields with tabs, rather than commas
+    pub fn tsv(mut self) -> CsvOptions {
+        self.delimiter = '\t';
+        self
+    }
+
+    /// Write these columns of the individuals table, in this order
+    pub fn individual_columns(mut self, columns: &[IndividualColumn]) -> CsvOptions {
+        self.individual_columns = columns.to_vec();
+        self
+    }
+
+    /// Write these columns of the families table, in this order
+    pub fn family_columns(mut self, columns: &[FamilyColumn]) -> CsvOptions {
+        self.family_columns = columns.to_vec();
+        self
+    }
+
+    /// The extension of the files written: csv, or tsv
+    pub fn extension(&self) -> &'static str {
+        if self.delimiter == '\t' {
+            "tsv"
+        } else {
+            "csv"
+        }
+    }
+
+    fn row<'a>(&self, fields: impl Iterator<Item = &'a str>) -> String {
+        let fields: Vec<String> = fields.map(|f| self.field(f)).collect();
+        fields.join(&self.delimiter.to_string()) + "\n"
+    }
+
+    /// Quote a field that needs it, as RFC 4180 does, or, for TSV, which
+    /// can't quote, replace tabs and line breaks with spaces
+    fn field(&self, value: &str) -> String {
+        if self.delimiter == '\t' {
+            return value.replace(['\t', '\r', '\n'], " ");
+        }
+        if value.contains([self.delimiter, '"', '\r', '\n']) {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    }
+}
+
+/// A table of the individuals, one per row in file order, with a heading
+pub fn export_individuals(gedcom: &Gedcom, options: &CsvOptions) -> String {
+    let columns = &options.individual_columns;
+    let mut text = options.row(columns.iter().map(|c| c.name()));
+    for individual in &gedcom.individuals {
+        let values: Vec<String> = columns
+            .iter()
+            .map(|c| c.value(gedcom, individual))
+            .collect();
+        text += &options.row(values.iter().map(|v| v.as_str()));
+    }
+    text
+}
+
+/// A table of the families, one per row in file order, with a heading
+pub fn export_families(gedcom: &Gedcom, options: &CsvOptions) -> String {
+    let columns = &options.family_columns;
+    let mut text = options.row(columns.iter().map(|c| c.name()));
+    for family in &gedcom.families {
+        let values: Vec<String> = columns.iter().map(|c| c.value(gedcom, family)).collect();
+        text += &options.row(values.iter().map(|v| v.as_str()));
+    }
+    text
+}
+
+/// Write individuals.csv and families.csv (or .tsv) into `directory`, which
+/// is created if need be, returning the paths written
+pub fn write_csv(
+    gedcom: &Gedcom,
+    options: &CsvOptions,
+    directory: &Path,
+) -> Result<Vec<PathBuf>, GedcomError> {
+    fs::create_dir_all(directory)?;
+    let individuals = directory.join(format!("individuals.{}", options.extension()));
+    let families = directory.join(format!("families.{}", options.extension()));
+    fs::write(&individuals, export_individuals(gedcom, options))?;
+    fs::write(&families, export_families(gedcom, options))?;
+    Ok(vec![individuals, families])
+}
+
+/// The family an individual was born into: the first they're a child of
+/// by birth, or else the first they're a child of at all
+fn birth_family<'a>(gedcom: &'a Gedcom, individual: &Individual) -> Option<&'a Family> {
+    let link = individual
+        .famc
+        .iter()
+        .find(|f| matches!(f.pedigree, None | Some(Pedigree::Birth)))
+        .or_else(|| individual.famc.first())?;
+    gedcom.families.iter().find(|f| f.xref == link.xref)
+}
+
+/// The CHIL of a family, and anyone else linked to it as a child
+fn children<'a>(gedcom: &'a Gedcom, family: &'a Family) -> Vec<&'a str> {
+    let mut children: Vec<&str> = family.children.iter().map(|c| c.as_str()).collect();
+    for individual in &gedcom.individuals {
+        if let Some(xref) = individual.xref.as_deref() {
+            if individual.famc.iter().any(|f| f.xref == family.xref) && !children.contains(&xref) {
+                children.push(xref);
+            }
+        }
+    }
+    children
+}
+
+fn date<'a>(mut events: impl Iterator<Item = &'a EventDetail>) -> Option<String> {
+    events.find_map(|e| e.date.clone())
+}
+
+fn place<'a>(mut events: impl Iterator<Item = &'a EventDetail>) -> Option<String> {
+    events.find_map(|e| e.place.as_ref().and_then(|p| p.name.clone()))
+}
+
+fn sex(gender: &Gender) -> &str {
+    match gender {
+        Gender::Male => "M",
+        Gender::Female => "F",
+        Gender::Nonbinary => "X",
+        Gender::Unknown => "U",
+        Gender::Other(value) => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_gedcom_from_str;
+    use std::env;
+
+    fn gedcom() -> Gedcom {
+        parse_gedcom_from_str(
+            "0 HEAD
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+2 GIVN Joe
+2 SURN Bloggs
+1 SEX M
+1 BIRT
+2 DATE 1 JAN 1900
+2 PLAC York, Yorkshire, England
+1 DEAT
+2 DATE 1970
+1 FAMS @F1@
+0 @I2@ INDI
+1 NAME Jane \"Janey\" /Doe/
+1 SEX F
+1 FAMS @F1@
+0 @I3@ INDI
+1 NAME Jim /Bloggs/
+1 FAMC @F2@
+2 PEDI adopted
+1 FAMC @F1@
+0 @F1@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 MARR
+2 DATE 1925
+2 PLAC Leeds
+0 @F2@ FAM
+1 WIFE @I2@
+0 TRLR
+",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn individuals() {
+        let gedcom = gedcom();
+        let csv = export_individuals(&gedcom, &CsvOptions::new());
+        let lines: Vec<&str> = csv.lines().collect();
+        assert!(lines.len() == 4);
+        assert!(
+            lines[0]
+                == "xref,name,given,surname,sex,birth-date,birth-place,death-date,death-place,father,mother"
+        );
+        assert!(
+            lines[1]
+                == "@I1@,Joe Bloggs,Joe,Bloggs,M,1 JAN 1900,\"York, Yorkshire, England\",1970,,,"
+        );
+        assert!(lines[2] == "@I2@,\"Jane \"\"Janey\"\" Doe\",,,F,,,,,,");
+        // The family he was born into, rather than adopted into
+        assert!(lines[3].ends_with(",@I1@,@I2@"));
+    }
+
+    #[test]
+    fn families() {
+        let gedcom = gedcom();
+        let options = CsvOptions::new().tsv().family_columns(&[
+            FamilyColumn::Xref,
+            FamilyColumn::HusbandName,
+            FamilyColumn::MarriagePlace,
+            FamilyColumn::Children,
+        ]);
+        let tsv = export_families(&gedcom, &options);
+        assert!(tsv == "xref\thusband-name\tmarriage-place\tchildren\n@F1@\tJoe Bloggs\tLeeds\t@I3@\n@F2@\t\t\t@I3@\n");
+    }
+
+    #[test]
+    fn columns() {
+        assert!(IndividualColumn::from_str("Birth_Date") == Ok(IndividualColumn::BirthDate));
+        assert!(IndividualColumn::from_str("birthday").is_err());
+        assert!(FamilyColumn::from_str("wife-name") == Ok(FamilyColumn::WifeName));
+        for column in IndividualColumn::ALL.iter().copied() {
+            assert!(IndividualColumn::from_str(column.name()) == Ok(column));
+        }
+    }
+
+    #[test]
+    fn files() {
+        let directory = env::temp_dir().join("gedcom-rs-test-csv");
+        let options = CsvOptions::new().individual_columns(&[IndividualColumn::Xref]);
+        let written = write_csv(&gedcom(), &options, &directory).unwrap();
+        let individuals = fs::read_to_string(&written[0]);
+        fs::remove_dir_all(&directory).unwrap();
+        assert!(written[0].ends_with("individuals.csv") && written[1].ends_with("families.csv"));
+        assert!(individuals.unwrap() == "xref\n@I1@\n@I2@\n@I3@\n");
+    }
+}
@@ -0,0 +1,462 @@
+use std::f64::consts::PI;
+
+use crate::graph::escape;
+use crate::relationship::birth_families;
+use crate::types::{Gedcom, Gender, Individual};
+
+// Charts as SVG, which any browser can show and any vector editor can
+// touch up, without a GUI toolkit.
+//
+// A fan chart is a half circle: the root in the middle, their parents in
+// the first ring around them, grandparents in the next, and so on, with
+// the father's side on the left. Each ring is divided evenly between the
+// places in that generation, so an unknown ancestor leaves a gap.
+//
+// A descendant chart is a box for each descendant, their children in a row
+// below them joined by lines, each parent centered over their children.
+
+/// How an SVG chart is drawn
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SvgOptions {
+    /// How many generations back, or down, from the root
+    pub generations: usize,
+    /// The fill of each box or segment, by the individual's sex, as any SVG
+    /// color, i.e. "#cfe2f3" or "lightblue"
+    pub male: String,
+    pub female: String,
+    /// For anyone whose sex is unknown, or neither
+    pub other: String,
+    /// The color of the outlines, the lines between boxes, and the text
+    pub stroke: String,
+    /// Whether to label each individual with their years of birth and death,
+    /// as well as their name
+    pub years: bool,
+    /// Whether to label each individual with their xref
+    pub xrefs: bool,
+}
+
+impl Default for SvgOptions {
+    fn default() -> SvgOptions {
+        SvgOptions {
+            generations: 4,
+            male: "#cfe2f3".to_string(),
+            female: "#f4cccc".to_string(),
+            other: "#eeeeee".to_string(),
+            stroke: "#444444".to_string(),
+            years: true,
+            xrefs: false,
+        }
+    }
+}
+
+impl SvgOptions {
+    /// Four generations, with names and years, in pale blue and pink
+    pub fn new() -> SvgOptions {
+        SvgOptions::default()
+    }
+
+    pub fn generations(mut self, generations: usize) -> SvgOptions {
+        self.generations = generations;
+        self
+    }
+
+    /// The fills for males, females, and everyone else
+    pub fn colors(mut self, male: &str, female: &str, other: &str) -> SvgOptions {
+        self.male = male.to_string();
+        self.female = female.to_string();
+        self.other = other.to_string();
+        self
+    }
+
+    pub fn stroke(mut self, color: &str) -> SvgOptions {
+        self.stroke = color.to_string();
+        self
+    }
+
+    pub fn years(mut self, years: bool) -> SvgOptions {
+        self.years = years;
+        self
+    }
+
+    pub fn xrefs(mut self, xrefs: bool) -> SvgOptions {
+        self.xrefs = xrefs;
+        self
+    }
+
+    fn fill(&self, individual: &Individual) -> &str {
+        match individual.gender {
+            Gender::Male => &self.male,
+            Gender::Female => &self.female,
+            _ => &self.other,
+        }
+    }
+
+    /// The lines of an individual's label
+    fn label(&self, individual: &Individual) -> Vec<String> {
+        let mut lines = vec![individual
+            .primary_name()
+            .unwrap_or_else(|| "(no name)".to_string())];
+        let lifespan = individual.lifespan_string();
+        if self.years && !lifespan.is_empty() {
+            lines.push(lifespan);
+        }
+        if self.xrefs {
+            lines.extend(individual.xref.clone());
+        }
+        lines
+    }
+}
+
+const FONT_SIZE: f64 = 12.0;
+const MARGIN: f64 = 10.0;
+
+/// The radius of the root's half circle, and the width of each ring
+const ROOT_RADIUS: f64 = 80.0;
+const RING_WIDTH: f64 = 90.0;
+
+/// A fan chart of `root` and their ancestors, through birth families
+pub fn fan_chart(gedcom: &Gedcom, root: &Individual, options: &SvgOptions) -> String {
+    let radius = ROOT_RADIUS + RING_WIDTH * options.generations as f64;
+    let (cx, cy) = (MARGIN + radius, MARGIN + radius);
+    let mut svg = header(2.0 * (radius + MARGIN), radius + 2.0 * MARGIN + FONT_SIZE);
+
+    // The root's half circle
+    svg += &format!(
+        "<path d=\"M {:.1} {:.1} A {r:.1} {r:.1} 0 0 1 {:.1} {:.1} Z\" fill=\"{}\" stroke=\"{}\"/>\n",
+        cx - ROOT_RADIUS,
+        cy,
+        cx + ROOT_RADIUS,
+        cy,
+        escape(options.fill(root)),
+        escape(&options.stroke),
+        r = ROOT_RADIUS,
+    );
+    svg += &text(
+        options,
+        &options.label(root),
+        cx,
+        cy - ROOT_RADIUS / 2.0,
+        0.0,
+    );
+
+    // Each generation's ancestors, by their ahnentafel number
+    let mut generation: Vec<(usize, &Individual)> = vec![(1, root)];
+    for g in 1..=options.generations {
+        let mut next = vec![];
+        for (number, individual) in &generation {
+            if let Some(family) = birth_families(gedcom, individual).first() {
+                let father = family
+                    .husband_xref()
+                    .and_then(|x| gedcom.find_individual(x));
+                let mother = family.wife_xref().and_then(|x| gedcom.find_individual(x));
+                next.extend(father.map(|f| (2 * number, f)));
+                next.extend(mother.map(|m| (2 * number + 1, m)));
+            }
+        }
+        if next.is_empty() {
+            break;
+        }
+
+        let places = 1usize << g;
+        let span = PI / places as f64;
+        let (inner, outer) = (
+            ROOT_RADIUS + RING_WIDTH * (g - 1) as f64,
+            ROOT_RADIUS + RING_WIDTH * g as f64,
+        );
+        for (number, individual) in &next {
+            let start = PI + span * (number - places) as f64;
+            let end = start + span;
+            let point = |r: f64, angle: f64| (cx + r * angle.cos(), cy + r * angle.sin());
+            let (x1, y1) = point(outer, start);
+            let (x2, y2) = point(outer, end);
+            let (x3, y3) = point(inner, end);
+            let (x4, y4) = point(inner, start);
+            svg += &format!(
+                "<path d=\"M {:.1} {:.1} A {o:.1} {o:.1} 0 0 1 {:.1} {:.1} L {:.1} {:.1} A {i:.1} {i:.1} 0 0 0 {:.1} {:.1} Z\" fill=\"{}\" stroke=\"{}\"/>\n",
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+                x4,
+                y4,
+                escape(options.fill(individual)),
+                escape(&options.stroke),
+                o = outer,
+                i = inner,
+            );
+
+            // Across the segment in the inner rings, where they're wide, and
+            // along it in the outer ones, where they're narrow; either way
+            // reading left to right
+            let middle = start + span / 2.0;
+            let (x, y) = point((inner + outer) / 2.0, middle);
+            let degrees = middle.to_degrees();
+            let rotation = if g <= 2 {
+                degrees + 90.0 - 360.0
+            } else if degrees < 270.0 {
+                degrees - 180.0
+            } else {
+                degrees - 360.0
+            };
+            svg += &text(options, &options.label(individual), x, y, rotation);
+        }
+        generation = next;
+    }
+
+    svg + "</svg>\n"
+}
+
+const BOX_WIDTH: f64 = 150.0;
+const BOX_GAP: f64 = 20.0;
+const ROW_GAP: f64 = 40.0;
+
+/// A box chart of `root` and their descendants
+pub fn descendant_chart(gedcom: &Gedcom, root: &Individual, options: &SvgOptions) -> String {
+    let mut boxes = vec![];
+    let mut line = vec![];
+    let mut columns = 0.0;
+    place(
+        gedcom,
+        (root, None, 0),
+        options.generations,
+        &mut boxes,
+        &mut line,
+        &mut columns,
+    );
+
+    let lines = 1 + usize::from(options.years) + usize::from(options.xrefs);
+    let height = (lines as f64 + 1.0) * FONT_SIZE * 1.2;
+    let rows = boxes.iter().map(|b| b.depth).max().unwrap_or_default() + 1;
+    let mut svg = header(
+        2.0 * MARGIN + columns * (BOX_WIDTH + BOX_GAP) - BOX_GAP,
+        2.0 * MARGIN + rows as f64 * (height + ROW_GAP) - ROW_GAP,
+    );
+
+    let left = |b: &Node| MARGIN + b.column * (BOX_WIDTH + BOX_GAP);
+    let top = |b: &Node| MARGIN + b.depth as f64 * (height + ROW_GAP);
+    for b in &boxes {
+        if let Some(parent) = b.parent.map(|p| &boxes[p]) {
+            svg += &format!(
+                "<path d=\"M {:.1} {:.1} V {:.1} H {:.1} V {:.1}\" fill=\"none\" stroke=\"{}\"/>\n",
+                left(parent) + BOX_WIDTH / 2.0,
+                top(parent) + height,
+                top(b) - ROW_GAP / 2.0,
+                left(b) + BOX_WIDTH / 2.0,
+                top(b),
+                escape(&options.stroke),
+            );
+        }
+    }
+    for b in &boxes {
+        svg += &format!(
+            "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{:.1}\" height=\"{:.1}\" rx=\"4\" fill=\"{}\" stroke=\"{}\"/>\n",
+            left(b),
+            top(b),
+            BOX_WIDTH,
+            height,
+            escape(options.fill(b.individual)),
+            escape(&options.stroke),
+        );
+        svg += &text(
+            options,
+            &options.label(b.individual),
+            left(b) + BOX_WIDTH / 2.0,
+            top(b) + height / 2.0,
+            0.0,
+        );
+    }
+
+    svg + "</svg>\n"
+}
+
+/// A descendant's box, in columns of boxes from the left and rows of
+/// generations from the top
+struct Node<'a> {
+    individual: &'a Individual,
+    depth: usize,
+    column: f64,
+    parent: Option<usize>,
+}
+
+/// Lay out `individual` and their descendants, each childless descendant in
+/// the next free column, and each parent centered over their children.
+/// `columns` is how many columns have been used.
+fn place<'a>(
+    gedcom: &'a Gedcom,
+    (individual, parent, depth): (&'a Individual, Option<usize>, usize),
+    generations: usize,
+    boxes: &mut Vec<Node<'a>>,
+    line: &mut Vec<&'a Individual>,
+    columns: &mut f64,
+) {
+    let index = boxes.len();
+    boxes.push(Node {
+        individual,
+        depth,
+        column: 0.0,
+        parent,
+    });
+
+    let children: Vec<&Individual> = if depth < generations {
+        gedcom
+            .children(individual)
+            .into_iter()
+            // Someone who is their own descendant would go on forever
+            .filter(|c| c.xref != individual.xref && !line.iter().any(|i| i.xref == c.xref))
+            .collect()
+    } else {
+        vec![]
+    };
+    if children.is_empty() {
+        boxes[index].column = *columns;
+        *columns += 1.0;
+        return;
+    }
+
+    line.push(individual);
+    let mut placed = vec![];
+    for child in children {
+        placed.push(boxes.len());
+        place(
+            gedcom,
+            (child, Some(index), depth + 1),
+            generations,
+            boxes,
+            line,
+            columns,
+        );
+    }
+    line.pop();
+    let first = boxes[placed[0]].column;
+    let last = boxes[placed[placed.len() - 1]].column;
+    boxes[index].column = (first + last) / 2.0;
+}
+
+fn header(width: f64, height: f64) -> String {
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0:.0}\" height=\"{1:.0}\" viewBox=\"0 0 {0:.0} {1:.0}\" font-family=\"sans-serif\" font-size=\"{2}\">\n",
+        width, height, FONT_SIZE
+    )
+}
+
+/// Lines of text, centered on (x, y), turned `rotation` degrees about it
+fn text(options: &SvgOptions, lines: &[String], x: f64, y: f64, rotation: f64) -> String {
+    let leading = FONT_SIZE * 1.2;
+    let first = -(lines.len() as f64 - 1.0) * leading / 2.0;
+    let mut svg = format!(
+        "<text x=\"{:.1}\" y=\"{:.1}\" text-anchor=\"middle\" dominant-baseline=\"middle\" fill=\"{}\"",
+        x,
+        y,
+        escape(&options.stroke)
+    );
+    if rotation != 0.0 {
+        svg += &format!(" transform=\"rotate({:.1} {:.1} {:.1})\"", rotation, x, y);
+    }
+    svg += ">";
+    for (i, line) in lines.iter().enumerate() {
+        let dy = if i == 0 { first } else { leading };
+        svg += &format!(
+            "<tspan x=\"{:.1}\" dy=\"{:.1}\">{}</tspan>",
+            x,
+            dy,
+            escape(line)
+        );
+    }
+    svg + "</text>\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_gedcom_from_str;
+
+    const DATA: &str = "0 HEAD
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 SEX M
+1 BIRT
+2 DATE 1900
+1 FAMC @F1@
+1 FAMS @F3@
+0 @I2@ INDI
+1 NAME Fred /Bloggs/
+1 SEX M
+1 FAMS @F1@
+1 FAMC @F2@
+0 @I3@ INDI
+1 NAME Ann <Smith>
+1 SEX F
+1 FAMS @F1@
+0 @I4@ INDI
+1 NAME Tom /Bloggs/
+1 FAMS @F2@
+0 @I5@ INDI
+1 NAME Sam /Bloggs/
+1 FAMC @F3@
+0 @I6@ INDI
+1 NAME Liz /Bloggs/
+1 FAMC @F3@
+1 FAMS @F4@
+0 @I7@ INDI
+1 NAME Kit /Brown/
+1 FAMC @F4@
+0 @F1@ FAM
+1 HUSB @I2@
+1 WIFE @I3@
+1 CHIL @I1@
+0 @F2@ FAM
+1 HUSB @I4@
+1 CHIL @I2@
+0 @F3@ FAM
+1 HUSB @I1@
+1 CHIL @I5@
+1 CHIL @I6@
+0 @F4@ FAM
+1 WIFE @I6@
+1 CHIL @I7@
+0 TRLR
+";
+
+    #[test]
+    fn fan() {
+        let gedcom = parse_gedcom_from_str(DATA).unwrap();
+        let joe = gedcom.find_individual("@I1@").unwrap();
+        let svg = fan_chart(&gedcom, joe, &SvgOptions::new());
+        assert!(svg.starts_with("<?xml") && svg.ends_with("</svg>\n"));
+        // Joe, his parents, and his father's father
+        assert!(svg.matches("<path").count() == 4);
+        assert!(svg.contains(">Joe Bloggs</tspan><tspan x=\"450.0\" dy=\"14.4\">1900–</tspan>"));
+        assert!(svg.contains(">Ann &lt;Smith&gt;</tspan>"));
+        assert!(svg.contains("fill=\"#cfe2f3\"") && svg.contains("fill=\"#f4cccc\""));
+
+        let options = SvgOptions::new()
+            .generations(1)
+            .colors("blue", "red", "grey")
+            .stroke("black")
+            .years(false)
+            .xrefs(true);
+        let svg = fan_chart(&gedcom, joe, &options);
+        assert!(svg.matches("<path").count() == 3 && !svg.contains("Tom Bloggs"));
+        assert!(svg.contains("fill=\"blue\" stroke=\"black\""));
+        assert!(svg.contains(">@I1@</tspan>") && !svg.contains("1900"));
+    }
+
+    #[test]
+    fn descendants() {
+        let gedcom = parse_gedcom_from_str(DATA).unwrap();
+        let joe = gedcom.find_individual("@I1@").unwrap();
+        let svg = descendant_chart(&gedcom, joe, &SvgOptions::new());
+        assert!(svg.matches("<rect").count() == 4);
+        // A line to each descendant
+        assert!(svg.matches("<path").count() == 3);
+        // Sam and Kit are in the first two columns, and Joe over them both
+        assert!(svg.contains("<rect x=\"10.0\" y=\"93.2\""));
+        assert!(svg.contains("<rect x=\"180.0\" y=\"176.4\""));
+        assert!(svg.contains("<rect x=\"95.0\" y=\"10.0\""));
+
+        let svg = descendant_chart(&gedcom, joe, &SvgOptions::new().generations(1));
+        assert!(svg.matches("<rect").count() == 3 && !svg.contains("Kit Brown"));
+    }
+}
@@ -0,0 +1,173 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use zip::result::ZipError;
+use zip::ZipArchive;
+
+use crate::config::GedcomConfig;
+use crate::error::GedcomError;
+use crate::media::Archive;
+use crate::parse::parse_gedcom_from_reader_with_config;
+use crate::types::Gedcom;
+
+/// The name of the GEDCOM file in a GEDZIP archive
+const GEDCOM_ENTRY: &str = "gedcom.ged";
+
+/// Whether a file looks like a GEDZIP archive, going by its extension:
+/// .gdz, or .zip
+pub fn is_gedzip(filename: &str) -> bool {
+    let extension = Path::new(filename)
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase());
+    matches!(extension.as_deref(), Some("gdz" | "zip"))
+}
+
+/// Parse the GEDCOM in a GEDZIP archive, recording the media stored with it
+/// in `Gedcom::archive`
+pub fn parse_gedzip(filename: &str) -> Result<Gedcom, GedcomError> {
+    parse_gedzip_with_config(filename, &GedcomConfig::default())
+}
+
+/// Parse the GEDCOM in a GEDZIP archive, using the given configuration.
+///
+/// The GEDCOM is gedcom.ged, at the top of the archive. For zips made by
+/// hand, a single .ged file anywhere in the archive is used instead.
+pub fn parse_gedzip_with_config(
+    filename: &str,
+    config: &GedcomConfig,
+) -> Result<Gedcom, GedcomError> {
+    let mut zip = open(Path::new(filename))?;
+    let names: Vec<String> = zip
+        .file_names()
+        .filter(|n| !n.ends_with('/'))
+        .map(|n| n.to_string())
+        .collect();
+
+    let gedcom_entry = if names.iter().any(|n| n == GEDCOM_ENTRY) {
+        GEDCOM_ENTRY.to_string()
+    } else {
+        let mut found = names.iter().filter(|n| n.to_lowercase().ends_with(".ged"));
+        match (found.next(), found.next()) {
+            (Some(name), None) => name.clone(),
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{} has no {}", filename, GEDCOM_ENTRY),
+                )
+                .into())
+            }
+        }
+    };
+
+    let entry = zip.by_name(&gedcom_entry).map_err(zip_error)?;
+    let mut gedcom = parse_gedcom_from_reader_with_config(entry, config)?;
+    gedcom.archive = Some(Archive {
+        path: PathBuf::from(filename),
+        entries: names.into_iter().filter(|n| *n != gedcom_entry).collect(),
+    });
+    Ok(gedcom)
+}
+
+/// Read one file out of an archive
+pub(crate) fn read_entry(path: &Path, name: &str) -> Result<Vec<u8>, GedcomError> {
+    let mut zip = open(path)?;
+    let mut entry = zip.by_name(name).map_err(zip_error)?;
+    let mut bytes = vec![];
+    entry.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn open(path: &Path) -> Result<ZipArchive<File>, GedcomError> {
+    let file = File::open(path)?;
+    ZipArchive::new(file).map_err(zip_error)
+}
+
+fn zip_error(err: ZipError) -> GedcomError {
+    GedcomError::Io(err.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_gedcom;
+    use std::env;
+    use std::fs;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    fn write_zip(path: &Path, entries: &[(&str, &str)]) {
+        let mut zip = ZipWriter::new(File::create(path).unwrap());
+        for (name, contents) in entries {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    #[test]
+    fn gedzip() {
+        let gedcom = "0 HEAD
+1 GEDC
+2 VERS 7.0
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 OBJE @M1@
+0 @M1@ OBJE
+1 FILE photos/joe%20smith.jpg
+2 FORM image/jpeg
+0 TRLR
+";
+        let path = env::temp_dir().join("gedcom-rs-test.gdz");
+        write_zip(
+            &path,
+            &[
+                ("gedcom.ged", gedcom),
+                ("photos/joe smith.jpg", "photo"),
+                ("photos/other.jpg", "other"),
+            ],
+        );
+        let filename = path.to_str().unwrap();
+        assert!(is_gedzip(filename) && is_gedzip("TREE.ZIP") && !is_gedzip("tree.ged"));
+
+        let parsed = parse_gedcom(filename);
+        let media = parsed.media_inventory();
+        let extracted = media[0].archive.unwrap().read(media[0].path);
+
+        let target = env::temp_dir().join("gedcom-rs-test-gdz-media");
+        let copied = crate::media::copy_media(&media, Path::new(""), &target);
+        let contents = fs::read_to_string(target.join("joe smith.jpg"));
+        fs::remove_dir_all(&target).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(parsed.warnings.is_empty());
+        assert!(parsed.individuals.len() == 1);
+        let archive = parsed.archive.as_ref().unwrap();
+        assert!(archive.entries == ["photos/joe smith.jpg", "photos/other.jpg"]);
+        assert!(media.len() == 1 && media[0].exists);
+        assert!(extracted.unwrap() == b"photo");
+        assert!(copied.unwrap().len() == 1);
+        assert!(contents.unwrap() == "photo");
+    }
+
+    #[test]
+    fn single_ged() {
+        let path = env::temp_dir().join("gedcom-rs-test-single.zip");
+        write_zip(
+            &path,
+            &[("export/tree.ged", "0 HEAD\n0 @I1@ INDI\n0 TRLR\n")],
+        );
+        let parsed = parse_gedzip(path.to_str().unwrap());
+
+        let empty = env::temp_dir().join("gedcom-rs-test-empty.gdz");
+        write_zip(&empty, &[("notes.txt", "nothing")]);
+        let missing = parse_gedzip(empty.to_str().unwrap());
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&empty).unwrap();
+
+        assert!(parsed.unwrap().individuals.len() == 1);
+        assert!(missing.is_err());
+        assert!(parse_gedzip("/nonexistent.gdz").is_err());
+    }
+}
@@ -0,0 +1,709 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::str::FromStr;
+
+use crate::names;
+use crate::types::{Gedcom, Gender, Individual, Pedigree};
+
+/// A file format for network-analysis tools, i.e. Gephi or Cytoscape
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GraphFormat {
+    #[default]
+    GraphMl,
+    Gexf,
+}
+
+impl FromStr for GraphFormat {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<GraphFormat, Self::Err> {
+        match input.to_lowercase().as_str() {
+            "graphml" => Ok(GraphFormat::GraphMl),
+            "gexf" => Ok(GraphFormat::Gexf),
+            _ => Err(()),
+        }
+    }
+}
+
+/// How two individuals are linked
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum EdgeKind {
+    /// From a parent to their child
+    Parent,
+    /// Between the spouses in a family
+    Spouse,
+}
+
+impl EdgeKind {
+    fn name(&self) -> &'static str {
+        match self {
+            EdgeKind::Parent => "parent",
+            EdgeKind::Spouse => "spouse",
+        }
+    }
+}
+
+struct Node<'a> {
+    xref: &'a str,
+    name: String,
+    birth_year: Option<i32>,
+    sex: &'a str,
+    /// Generations above the root, so parents are 1 and children -1
+    generation: Option<i32>,
+}
+
+struct Edge<'a> {
+    source: &'a str,
+    target: &'a str,
+    kind: EdgeKind,
+    /// For a parent, how the child is linked to the family, if given
+    pedigree: Option<&'static str>,
+}
+
+/// The family graph: a node for each individual, and edges from parents to
+/// their children and between spouses
+struct Graph<'a> {
+    nodes: Vec<Node<'a>>,
+    edges: Vec<Edge<'a>>,
+}
+
+impl<'a> Graph<'a> {
+    fn new(gedcom: &'a Gedcom, root: Option<&Individual>) -> Graph<'a> {
+        let mut edges: Vec<Edge> = vec![];
+        let mut seen = HashSet::new();
+        let mut add = |edges: &mut Vec<Edge<'a>>, edge: Edge<'a>| {
+            if edge.source != edge.target && seen.insert((edge.source, edge.target, edge.kind)) {
+                edges.push(edge);
+            }
+        };
+
+        for family in &gedcom.families {
+            let parents: Vec<&str> = family
+                .husband_xref()
+                .into_iter()
+                .chain(family.wife_xref())
+                .filter(|xref| gedcom.find_individual(xref).is_some())
+                .collect();
+            if let [husband, wife] = parents[..] {
+                let edge = Edge {
+                    source: husband,
+                    target: wife,
+                    kind: EdgeKind::Spouse,
+                    pedigree: None,
+                };
+                add(&mut edges, edge);
+            }
+
+            let listed = family
+                .children
+                .iter()
+                .filter_map(|xref| gedcom.find_individual(xref));
+            let linked = gedcom
+                .individuals
+                .iter()
+                .filter(|i| i.famc.iter().any(|c| c.xref == family.xref));
+            for child in listed.chain(linked) {
+                let xref = match child.xref.as_deref() {
+                    Some(xref) => xref,
+                    None => continue,
+                };
+                let pedigree = child
+                    .famc
+                    .iter()
+                    .find(|c| c.xref == family.xref)
+                    .and_then(|c| c.pedigree.as_ref())
+                    .map(pedigree_name);
+                for parent in &parents {
+                    let edge = Edge {
+                        source: parent,
+                        target: xref,
+                        kind: EdgeKind::Parent,
+                        pedigree,
+                    };
+                    add(&mut edges, edge);
+                }
+            }
+        }
+
+        let generations = root
+            .and_then(|r| r.xref.as_deref())
+            .map(|root| generations(root, &edges))
+            .unwrap_or_default();
+        let nodes = gedcom
+            .individuals
+            .iter()
+            .filter_map(|individual| {
+                let xref = individual.xref.as_deref()?;
+                Some(Node {
+                    xref,
+                    name: name(individual),
+                    birth_year: individual.birth_year(),
+                    sex: sex(&individual.gender),
+                    generation: generations.get(xref).copied(),
+                })
+            })
+            .collect();
+
+        Graph { nodes, edges }
+    }
+}
+
+/// How many generations above `root` everyone connected to them is,
+/// breadth-first, so each gets the generation of their closest link
+fn generations<'a>(root: &'a str, edges: &[Edge<'a>]) -> HashMap<&'a str, i32> {
+    let mut links: HashMap<&str, Vec<(&str, i32)>> = HashMap::new();
+    for edge in edges {
+        let step = match edge.kind {
+            EdgeKind::Parent => 1,
+            EdgeKind::Spouse => 0,
+        };
+        links
+            .entry(edge.target)
+            .or_default()
+            .push((edge.source, step));
+        links
+            .entry(edge.source)
+            .or_default()
+            .push((edge.target, -step));
+    }
+
+    let mut generations = HashMap::from([(root, 0)]);
+    let mut queue = VecDeque::from([root]);
+    while let Some(xref) = queue.pop_front() {
+        let generation = generations[xref];
+        for (other, step) in links.get(xref).into_iter().flatten() {
+            if !generations.contains_key(other) {
+                generations.insert(other, generation + step);
+                queue.push_back(other);
+            }
+        }
+    }
+    generations
+}
+
+/// How two individuals are linked in a `FamilyGraph`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Relation {
+    /// From a parent to their child
+    ParentOf,
+    /// Between the spouses in a family, in either direction
+    SpouseOf,
+    /// Between the children of a family, in either direction
+    SiblingOf,
+}
+
+/// A link in a `FamilyGraph`, between the nodes at these indexes
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GraphEdge {
+    pub source: usize,
+    pub target: usize,
+    pub relation: Relation,
+}
+
+/// The family graph as plain data, for graph libraries and algorithms:
+/// the xref of each individual, in file order, and the links between them
+/// by index. See `Gedcom::to_graph`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FamilyGraph {
+    pub nodes: Vec<String>,
+    pub edges: Vec<GraphEdge>,
+}
+
+impl FamilyGraph {
+    /// The index of the node for the individual with this xref
+    pub fn index_of(&self, xref: &str) -> Option<usize> {
+        self.nodes.iter().position(|n| n == xref)
+    }
+
+    /// Each node's links to the others, whichever way they run, as
+    /// (node, relation). A child's link to a parent is `ParentOf`, the same
+    /// as the parent's to the child.
+    pub fn adjacency(&self) -> Vec<Vec<(usize, Relation)>> {
+        let mut adjacency = vec![vec![]; self.nodes.len()];
+        for edge in &self.edges {
+            adjacency[edge.source].push((edge.target, edge.relation));
+            adjacency[edge.target].push((edge.source, edge.relation));
+        }
+        adjacency
+    }
+}
+
+/// The family graph, with an edge from each parent to their child, and
+/// edges between spouses and between the children of each family. Siblings
+/// are linked through the families they share, so half-siblings in
+/// different families aren't, except through their parent.
+pub fn to_graph(gedcom: &Gedcom) -> FamilyGraph {
+    let graph = Graph::new(gedcom, None);
+    let index: HashMap<&str, usize> = graph
+        .nodes
+        .iter()
+        .enumerate()
+        .map(|(i, node)| (node.xref, i))
+        .collect();
+
+    let mut edges: Vec<GraphEdge> = graph
+        .edges
+        .iter()
+        .filter_map(|edge| {
+            Some(GraphEdge {
+                source: *index.get(edge.source)?,
+                target: *index.get(edge.target)?,
+                relation: match edge.kind {
+                    EdgeKind::Parent => Relation::ParentOf,
+                    EdgeKind::Spouse => Relation::SpouseOf,
+                },
+            })
+        })
+        .collect();
+
+    let mut seen = HashSet::new();
+    for family in &gedcom.families {
+        let listed = family.children.iter().map(|c| c.as_str());
+        let linked = gedcom
+            .individuals
+            .iter()
+            .filter(|i| i.famc.iter().any(|c| c.xref == family.xref))
+            .filter_map(|i| i.xref.as_deref());
+        let mut children: Vec<usize> = vec![];
+        for child in listed.chain(linked).filter_map(|c| index.get(c)) {
+            if !children.contains(child) {
+                children.push(*child);
+            }
+        }
+        for (i, first) in children.iter().enumerate() {
+            for second in &children[i + 1..] {
+                let pair = (*first.min(second), *first.max(second));
+                if seen.insert(pair) {
+                    edges.push(GraphEdge {
+                        source: pair.0,
+                        target: pair.1,
+                        relation: Relation::SiblingOf,
+                    });
+                }
+            }
+        }
+    }
+
+    FamilyGraph {
+        nodes: graph.nodes.iter().map(|n| n.xref.to_string()).collect(),
+        edges,
+    }
+}
+
+/// A group of individuals linked to each other, directly or through others,
+/// but not to anyone outside it. See `Gedcom::connected_components`.
+#[derive(Debug)]
+pub struct Island<'a> {
+    /// Someone to start from: the earliest-born of those in the island with
+    /// no parents recorded
+    pub root: &'a Individual,
+    /// Everyone in the island, in file order
+    pub members: Vec<&'a Individual>,
+}
+
+impl Island<'_> {
+    pub fn size(&self) -> usize {
+        self.members.len()
+    }
+}
+
+/// Split the tree into islands of individuals connected by any chain of
+/// parents, children, spouses and siblings, largest first. Besides the main
+/// tree, there are often fragments nobody linked up, i.e. from merging or
+/// importing files, and individuals on their own.
+pub fn connected_components(gedcom: &Gedcom) -> Vec<Island<'_>> {
+    let graph = to_graph(gedcom);
+    let individuals: Vec<&Individual> = gedcom
+        .individuals
+        .iter()
+        .filter(|i| i.xref.is_some())
+        .collect();
+    let adjacency = graph.adjacency();
+    let mut has_parents = vec![false; graph.nodes.len()];
+    for edge in &graph.edges {
+        if edge.relation == Relation::ParentOf {
+            has_parents[edge.target] = true;
+        }
+    }
+
+    let mut islands: Vec<Island> = vec![];
+    let mut seen = vec![false; graph.nodes.len()];
+    for start in 0..graph.nodes.len() {
+        if seen[start] {
+            continue;
+        }
+        seen[start] = true;
+        let mut members = vec![start];
+        let mut queue = VecDeque::from([start]);
+        while let Some(node) = queue.pop_front() {
+            for (other, _) in &adjacency[node] {
+                if !seen[*other] {
+                    seen[*other] = true;
+                    members.push(*other);
+                    queue.push_back(*other);
+                }
+            }
+        }
+        members.sort_unstable();
+
+        // The earliest-born of the top ancestors, with those whose birth
+        // year isn't known last
+        let root = members
+            .iter()
+            .filter(|m| !has_parents[**m])
+            .min_by_key(|m| individuals[**m].birth_year().unwrap_or(i32::MAX))
+            .unwrap_or(&start);
+        islands.push(Island {
+            root: individuals[*root],
+            members: members.iter().map(|m| individuals[*m]).collect(),
+        });
+    }
+
+    islands.sort_by_key(|island| std::cmp::Reverse(island.size()));
+    islands
+}
+
+/// The family graph as GraphML or GEXF. Each individual is a node, with
+/// their name, birth year, sex and, if `root` is given, generation above
+/// the root (so their parents are 1 and children -1). Edges run from each
+/// parent to their child, with the child's pedigree, and between spouses.
+pub fn export_graph(gedcom: &Gedcom, format: GraphFormat, root: Option<&Individual>) -> String {
+    match format {
+        GraphFormat::GraphMl => export_graphml(gedcom, root),
+        GraphFormat::Gexf => export_gexf(gedcom, root),
+    }
+}
+
+/// The family graph as GraphML. See `export_graph`.
+pub fn export_graphml(gedcom: &Gedcom, root: Option<&Individual>) -> String {
+    let graph = Graph::new(gedcom, root);
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml += "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n";
+    for (id, domain, kind) in [
+        ("name", "node", "string"),
+        ("birth_year", "node", "int"),
+        ("sex", "node", "string"),
+        ("generation", "node", "int"),
+        ("type", "edge", "string"),
+        ("pedigree", "edge", "string"),
+    ] {
+        xml += &format!(
+            "  <key id=\"{0}\" for=\"{1}\" attr.name=\"{0}\" attr.type=\"{2}\"/>\n",
+            id, domain, kind
+        );
+    }
+    xml += "  <graph id=\"family\" edgedefault=\"directed\">\n";
+
+    for node in &graph.nodes {
+        xml += &format!("    <node id=\"{}\">\n", escape(node.xref));
+        xml += &format!("      <data key=\"name\">{}</data>\n", escape(&node.name));
+        if let Some(year) = node.birth_year {
+            xml += &format!("      <data key=\"birth_year\">{}</data>\n", year);
+        }
+        xml += &format!("      <data key=\"sex\">{}</data>\n", escape(node.sex));
+        if let Some(generation) = node.generation {
+            xml += &format!("      <data key=\"generation\">{}</data>\n", generation);
+        }
+        xml += "    </node>\n";
+    }
+    for (i, edge) in graph.edges.iter().enumerate() {
+        let directed = match edge.kind {
+            EdgeKind::Parent => "",
+            EdgeKind::Spouse => " directed=\"false\"",
+        };
+        xml += &format!(
+            "    <edge id=\"e{}\" source=\"{}\" target=\"{}\"{}>\n",
+            i,
+            escape(edge.source),
+            escape(edge.target),
+            directed
+        );
+        xml += &format!("      <data key=\"type\">{}</data>\n", edge.kind.name());
+        if let Some(pedigree) = edge.pedigree {
+            xml += &format!("      <data key=\"pedigree\">{}</data>\n", pedigree);
+        }
+        xml += "    </edge>\n";
+    }
+
+    xml += "  </graph>\n</graphml>\n";
+    xml
+}
+
+/// The family graph as GEXF 1.3. See `export_graph`.
+pub fn export_gexf(gedcom: &Gedcom, root: Option<&Individual>) -> String {
+    let graph = Graph::new(gedcom, root);
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml += "<gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">\n";
+    xml += "  <graph mode=\"static\" defaultedgetype=\"directed\">\n";
+    xml += "    <attributes class=\"node\">\n";
+    xml += "      <attribute id=\"birth_year\" title=\"birth_year\" type=\"integer\"/>\n";
+    xml += "      <attribute id=\"sex\" title=\"sex\" type=\"string\"/>\n";
+    xml += "      <attribute id=\"generation\" title=\"generation\" type=\"integer\"/>\n";
+    xml += "    </attributes>\n";
+    xml += "    <attributes class=\"edge\">\n";
+    xml += "      <attribute id=\"pedigree\" title=\"pedigree\" type=\"string\"/>\n";
+    xml += "    </attributes>\n";
+
+    xml += "    <nodes>\n";
+    for node in &graph.nodes {
+        xml += &format!(
+            "      <node id=\"{}\" label=\"{}\">\n",
+            escape(node.xref),
+            escape(&node.name)
+        );
+        xml += "        <attvalues>\n";
+        if let Some(year) = node.birth_year {
+            xml += &format!(
+                "          <attvalue for=\"birth_year\" value=\"{}\"/>\n",
+                year
+            );
+        }
+        xml += &format!(
+            "          <attvalue for=\"sex\" value=\"{}\"/>\n",
+            escape(node.sex)
+        );
+        if let Some(generation) = node.generation {
+            xml += &format!(
+                "          <attvalue for=\"generation\" value=\"{}\"/>\n",
+                generation
+            );
+        }
+        xml += "        </attvalues>\n";
+        xml += "      </node>\n";
+    }
+    xml += "    </nodes>\n";
+
+    xml += "    <edges>\n";
+    for (i, edge) in graph.edges.iter().enumerate() {
+        let directed = match edge.kind {
+            EdgeKind::Parent => "directed",
+            EdgeKind::Spouse => "undirected",
+        };
+        xml += &format!(
+            "      <edge id=\"e{}\" source=\"{}\" target=\"{}\" type=\"{}\" kind=\"{}\"",
+            i,
+            escape(edge.source),
+            escape(edge.target),
+            directed,
+            edge.kind.name()
+        );
+        match edge.pedigree {
+            Some(pedigree) => {
+                xml += ">\n        <attvalues>\n";
+                xml += &format!(
+                    "          <attvalue for=\"pedigree\" value=\"{}\"/>\n",
+                    pedigree
+                );
+                xml += "        </attvalues>\n      </edge>\n";
+            }
+            None => xml += "/>\n",
+        }
+    }
+    xml += "    </edges>\n";
+
+    xml += "  </graph>\n</gexf>\n";
+    xml
+}
+
+/// Escape text for an XML attribute or element
+pub(crate) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped += "&amp;",
+            '<' => escaped += "&lt;",
+            '>' => escaped += "&gt;",
+            '"' => escaped += "&quot;",
+            '\'' => escaped += "&apos;",
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// An individual's first name, without the slashes around the surname
+fn name(individual: &Individual) -> String {
+    individual
+        .names
+        .first()
+        .and_then(|n| n.name.value.as_deref())
+        .map(names::strip_slashes)
+        .unwrap_or_default()
+}
+
+fn sex(gender: &Gender) -> &str {
+    match gender {
+        Gender::Male => "M",
+        Gender::Female => "F",
+        Gender::Nonbinary => "X",
+        Gender::Unknown => "U",
+        Gender::Other(value) => value,
+    }
+}
+
+fn pedigree_name(pedigree: &Pedigree) -> &'static str {
+    match pedigree {
+        Pedigree::Adopted => "adopted",
+        Pedigree::Birth => "birth",
+        Pedigree::Foster => "foster",
+        Pedigree::Sealing => "sealing",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_gedcom_from_str;
+
+    fn gedcom() -> Gedcom {
+        parse_gedcom_from_str(
+            "0 HEAD
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 SEX M
+1 BIRT
+2 DATE 1900
+1 FAMS @F1@
+0 @I2@ INDI
+1 NAME Jane /Doe & Co/
+1 SEX F
+1 FAMS @F1@
+0 @I3@ INDI
+1 NAME Jim /Bloggs/
+1 FAMC @F1@
+2 PEDI adopted
+1 FAMS @F2@
+0 @I4@ INDI
+1 NAME Jill /Bloggs/
+1 FAMC @F2@
+0 @I5@ INDI
+1 NAME Unrelated /Person/
+0 @F1@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 CHIL @I3@
+0 @F2@ FAM
+1 HUSB @I3@
+1 CHIL @I4@
+0 TRLR
+",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn graph() {
+        let gedcom = gedcom();
+        let root = gedcom.find_individual("@I3@");
+        let graph = Graph::new(&gedcom, root);
+
+        let generations: Vec<Option<i32>> = graph.nodes.iter().map(|n| n.generation).collect();
+        assert!(generations == [Some(1), Some(1), Some(0), Some(-1), None]);
+        assert!(graph.nodes[1].name == "Jane Doe & Co");
+        assert!(graph.nodes[0].birth_year == Some(1900));
+
+        let edges: Vec<(&str, &str, EdgeKind, Option<&str>)> = graph
+            .edges
+            .iter()
+            .map(|e| (e.source, e.target, e.kind, e.pedigree))
+            .collect();
+        assert!(
+            edges
+                == [
+                    ("@I1@", "@I2@", EdgeKind::Spouse, None),
+                    ("@I1@", "@I3@", EdgeKind::Parent, Some("adopted")),
+                    ("@I2@", "@I3@", EdgeKind::Parent, Some("adopted")),
+                    ("@I3@", "@I4@", EdgeKind::Parent, None),
+                ]
+        );
+        assert!(Graph::new(&gedcom, None)
+            .nodes
+            .iter()
+            .all(|n| n.generation.is_none()));
+    }
+
+    #[test]
+    fn family_graph() {
+        let mut gedcom = gedcom();
+        gedcom.families[1].children.push("@I5@".to_string());
+        let graph = gedcom.to_graph();
+        assert!(graph.nodes == ["@I1@", "@I2@", "@I3@", "@I4@", "@I5@"]);
+        assert!(graph.index_of("@I4@") == Some(3) && graph.index_of("@I9@").is_none());
+
+        let edges: Vec<(usize, usize, Relation)> = graph
+            .edges
+            .iter()
+            .map(|e| (e.source, e.target, e.relation))
+            .collect();
+        assert!(
+            edges
+                == [
+                    (0, 1, Relation::SpouseOf),
+                    (0, 2, Relation::ParentOf),
+                    (1, 2, Relation::ParentOf),
+                    (2, 3, Relation::ParentOf),
+                    (2, 4, Relation::ParentOf),
+                    (3, 4, Relation::SiblingOf),
+                ]
+        );
+
+        let adjacency = graph.adjacency();
+        assert!(
+            adjacency[2]
+                == [
+                    (0, Relation::ParentOf),
+                    (1, Relation::ParentOf),
+                    (3, Relation::ParentOf),
+                    (4, Relation::ParentOf)
+                ]
+        );
+        assert!(adjacency[4] == [(2, Relation::ParentOf), (3, Relation::SiblingOf)]);
+    }
+
+    #[test]
+    fn islands() {
+        let mut gedcom = gedcom();
+        let mut data = "0 @I6@ INDI\n1 BIRT\n2 DATE 1850\n1 FAMS @F3@\n";
+        gedcom.individuals.push(Individual::parse(&mut data));
+        let mut data = "0 @I7@ INDI\n1 FAMS @F3@\n";
+        gedcom.individuals.push(Individual::parse(&mut data));
+        let mut data = "0 @F3@ FAM\n1 HUSB @I7@\n1 WIFE @I6@\n";
+        gedcom.families.push(crate::types::Family::parse(&mut data));
+
+        let islands = gedcom.connected_components();
+        let sizes: Vec<usize> = islands.iter().map(|i| i.size()).collect();
+        assert!(sizes == [4, 2, 1]);
+        let roots: Vec<&str> = islands
+            .iter()
+            .map(|i| i.root.xref.as_deref().unwrap())
+            .collect();
+        assert!(roots == ["@I1@", "@I6@", "@I5@"]);
+        assert!(islands[1].members[0].xref.as_deref() == Some("@I6@"));
+    }
+
+    #[test]
+    fn graphml() {
+        let gedcom = gedcom();
+        let xml = export_graphml(&gedcom, gedcom.find_individual("@I1@"));
+        assert!(xml.contains("<node id=\"@I1@\">"));
+        assert!(xml.contains("<data key=\"name\">Jane Doe &amp; Co</data>"));
+        assert!(xml.contains("<data key=\"generation\">-2</data>"));
+        assert!(xml.contains("source=\"@I1@\" target=\"@I2@\" directed=\"false\">"));
+        assert!(xml.contains("<data key=\"pedigree\">adopted</data>"));
+        assert!(xml.matches("<edge ").count() == 4);
+        assert!(xml.ends_with("</graphml>\n"));
+    }
+
+    #[test]
+    fn gexf() {
+        let gedcom = gedcom();
+        let xml = export_graph(&gedcom, GraphFormat::Gexf, None);
+        assert!(xml.contains("<node id=\"@I2@\" label=\"Jane Doe &amp; Co\">"));
+        assert!(xml.contains("<attvalue for=\"birth_year\" value=\"1900\"/>"));
+        assert!(!xml.contains("<attvalue for=\"generation\""));
+        assert!(xml.contains("type=\"undirected\" kind=\"spouse\"/>"));
+        assert!(xml.contains("<attvalue for=\"pedigree\" value=\"adopted\"/>"));
+        assert!(xml.ends_with("</gexf>\n"));
+        assert!(GraphFormat::from_str("GEXF") == Ok(GraphFormat::Gexf));
+        assert!(GraphFormat::from_str("dot").is_err());
+    }
+}
@@ -0,0 +1,500 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::types::{Family, Gedcom, Individual};
+
+// Build a Gedcom from a spreadsheet research log, exported as two CSV files.
+//
+// In both files, the first row is a header naming the columns, which may come
+// in any order. Column names are case-insensitive, unknown columns are ignored
+// and empty rows are skipped. Fields may be quoted ("Smith, Jr.") and quotes
+// inside a quoted field are doubled ("The ""Elder""").
+//
+// individuals.csv:
+//   id              A unique key for the individual (required). Any text will
+//                   do; it's only used to link the rows together.
+//   given           Given name(s)
+//   surname         Surname
+//   sex             M, F, X or U (male, female, etc. are also accepted)
+//   birth_date      A GEDCOM date value, i.e. 1 JAN 1900 or ABT 1900
+//   birth_place
+//   death_date
+//   death_place
+//   father          The id of the individual's father
+//   mother          The id of the individual's mother
+//   note
+//
+// families.csv:
+//   husband         The id of the husband
+//   wife            The id of the wife
+//   children        The ids of the children, separated by semicolons
+//   marriage_date
+//   marriage_place
+//
+// Xrefs are generated in row order: @I1@, @I2@, ... for individuals, and
+// @F1@, @F2@, ... for families. A father and/or mother named in
+// individuals.csv who aren't already a couple in families.csv are given a
+// family of their own.
+
+#[derive(Debug)]
+/// An error encountered while importing CSV data
+pub enum CsvError {
+    /// The file couldn't be read
+    Io(io::Error),
+    /// A required column is missing from the header row
+    MissingColumn(String),
+    /// A quoted field was opened on this line, but never closed
+    UnterminatedQuote { line: usize },
+    /// An individual on this line has no id
+    MissingId { line: usize },
+    /// More than one individual has the same id
+    DuplicateId { line: usize, id: String },
+    /// A row refers to an individual that doesn't exist
+    UnknownId { line: usize, id: String },
+}
+
+impl fmt::Display for CsvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsvError::Io(err) => write!(f, "{}", err),
+            CsvError::MissingColumn(column) => write!(f, "missing column: {}", column),
+            CsvError::UnterminatedQuote { line } => {
+                write!(f, "line {}: unterminated quoted field", line)
+            }
+            CsvError::MissingId { line } => write!(f, "line {}: individual has no id", line),
+            CsvError::DuplicateId { line, id } => write!(f, "line {}: duplicate id {}", line, id),
+            CsvError::UnknownId { line, id } => write!(f, "line {}: unknown id {}", line, id),
+        }
+    }
+}
+
+impl Error for CsvError {}
+
+impl From<io::Error> for CsvError {
+    fn from(err: io::Error) -> Self {
+        CsvError::Io(err)
+    }
+}
+
+/// Build a Gedcom from the contents of individuals.csv and families.csv.
+///
+/// `families` may be empty, in which case families are built solely from the
+/// father and mother columns of `individuals`.
+pub fn gedcom_from_csv(individuals: &str, families: &str) -> Result<Gedcom, CsvError> {
+    let people = Table::parse(individuals)?;
+    if !people.columns.contains_key("id") {
+        return Err(CsvError::MissingColumn("id".to_string()));
+    }
+
+    // Generate an xref for every individual
+    let mut xrefs: HashMap<&str, String> = HashMap::new();
+    for (i, (line, row)) in people.rows.iter().enumerate() {
+        let id = people.get(row, "id");
+        if id.is_empty() {
+            return Err(CsvError::MissingId { line: *line });
+        }
+        if xrefs.insert(id, format!("@I{}@", i + 1)).is_some() {
+            return Err(CsvError::DuplicateId {
+                line: *line,
+                id: id.to_string(),
+            });
+        }
+    }
+    let lookup = |line: usize, id: &str| -> Result<Option<String>, CsvError> {
+        if id.is_empty() {
+            return Ok(None);
+        }
+        match xrefs.get(id) {
+            Some(xref) => Ok(Some(xref.clone())),
+            None => Err(CsvError::UnknownId {
+                line,
+                id: id.to_string(),
+            }),
+        }
+    };
+
+    let mut couples: Vec<Couple> = vec![];
+    if !families.trim().is_empty() {
+        let table = Table::parse(families)?;
+        for (line, row) in &table.rows {
+            let mut couple = Couple {
+                husband: lookup(*line, table.get(row, "husband"))?,
+                wife: lookup(*line, table.get(row, "wife"))?,
+                children: vec![],
+                marriage_date: table.get(row, "marriage_date").to_string(),
+                marriage_place: table.get(row, "marriage_place").to_string(),
+            };
+            for child in table.get(row, "children").split(';') {
+                if let Some(xref) = lookup(*line, child.trim())? {
+                    couple.children.push(xref);
+                }
+            }
+            couples.push(couple);
+        }
+    }
+
+    // Link children to their parents, creating families as needed
+    for (line, row) in &people.rows {
+        let father = lookup(*line, people.get(row, "father"))?;
+        let mother = lookup(*line, people.get(row, "mother"))?;
+        if father.is_none() && mother.is_none() {
+            continue;
+        }
+        let child = xrefs[people.get(row, "id")].clone();
+
+        let index = match couples
+            .iter()
+            .position(|c| c.husband == father && c.wife == mother)
+        {
+            Some(index) => index,
+            None => {
+                couples.push(Couple {
+                    husband: father,
+                    wife: mother,
+                    children: vec![],
+                    marriage_date: String::new(),
+                    marriage_place: String::new(),
+                });
+                couples.len() - 1
+            }
+        };
+        if !couples[index].children.contains(&child) {
+            couples[index].children.push(child);
+        }
+    }
+
+    // Work out which families each individual belongs to
+    let mut fams: HashMap<&str, Vec<String>> = HashMap::new();
+    let mut famc: HashMap<&str, Vec<String>> = HashMap::new();
+    for (i, couple) in couples.iter().enumerate() {
+        let xref = format!("@F{}@", i + 1);
+        for spouse in couple.husband.iter().chain(couple.wife.iter()) {
+            fams.entry(spouse).or_default().push(xref.clone());
+        }
+        for child in &couple.children {
+            famc.entry(child).or_default().push(xref.clone());
+        }
+    }
+
+    // Now emit each record as GEDCOM and run it through the parser, so the
+    // result is exactly what we'd get from reading a file.
+    let mut gedcom = Gedcom::default();
+    for (_, row) in &people.rows {
+        let xref = xrefs[people.get(row, "id")].as_str();
+        let mut record = vec![format!("0 {} INDI", xref)];
+
+        let given = people.get(row, "given");
+        let surname = people.get(row, "surname");
+        if !given.is_empty() || !surname.is_empty() {
+            let name = match (given.is_empty(), surname.is_empty()) {
+                (false, false) => format!("{} /{}/", given, surname),
+                (false, true) => given.to_string(),
+                _ => format!("/{}/", surname),
+            };
+            record.push(format!("1 NAME {}", name));
+            if !given.is_empty() {
+                record.push(format!("2 GIVN {}", given));
+            }
+            if !surname.is_empty() {
+                record.push(format!("2 SURN {}", surname));
+            }
+        }
+        if let Some(sex) = sex(people.get(row, "sex")) {
+            record.push(format!("1 SEX {}", sex));
+        }
+        push_event(
+            &mut record,
+            "BIRT",
+            people.get(row, "birth_date"),
+            people.get(row, "birth_place"),
+        );
+        push_event(
+            &mut record,
+            "DEAT",
+            people.get(row, "death_date"),
+            people.get(row, "death_place"),
+        );
+        for family in famc.get(xref).into_iter().flatten() {
+            record.push(format!("1 FAMC {}", family));
+        }
+        for family in fams.get(xref).into_iter().flatten() {
+            record.push(format!("1 FAMS {}", family));
+        }
+        let note = people.get(row, "note");
+        if !note.is_empty() {
+            record.push(format!("1 NOTE {}", note));
+        }
+
+        let input = record.join("\n") + "\n";
+        gedcom
+            .individuals
+            .push(Individual::parse(&mut input.as_str()));
+    }
+
+    for (i, couple) in couples.iter().enumerate() {
+        let mut record = vec![format!("0 @F{}@ FAM", i + 1)];
+        if let Some(husband) = &couple.husband {
+            record.push(format!("1 HUSB {}", husband));
+        }
+        if let Some(wife) = &couple.wife {
+            record.push(format!("1 WIFE {}", wife));
+        }
+        for child in &couple.children {
+            record.push(format!("1 CHIL {}", child));
+        }
+        push_event(
+            &mut record,
+            "MARR",
+            &couple.marriage_date,
+            &couple.marriage_place,
+        );
+
+        let input = record.join("\n") + "\n";
+        gedcom.families.push(Family::parse(&mut input.as_str()));
+    }
+
+    Ok(gedcom)
+}
+
+/// Build a Gedcom from an individuals.csv and families.csv on disk.
+pub fn gedcom_from_csv_files<P: AsRef<Path>, Q: AsRef<Path>>(
+    individuals: P,
+    families: Q,
+) -> Result<Gedcom, CsvError> {
+    let individuals = fs::read_to_string(individuals)?;
+    let families = fs::read_to_string(families)?;
+    gedcom_from_csv(&individuals, &families)
+}
+
+/// A family, as gathered from the CSV files
+struct Couple {
+    husband: Option<String>,
+    wife: Option<String>,
+    children: Vec<String>,
+    marriage_date: String,
+    marriage_place: String,
+}
+
+fn push_event(record: &mut Vec<String>, tag: &str, date: &str, place: &str) {
+    if date.is_empty() && place.is_empty() {
+        return;
+    }
+    record.push(format!("1 {}", tag));
+    if !date.is_empty() {
+        record.push(format!("2 DATE {}", date));
+    }
+    if !place.is_empty() {
+        record.push(format!("2 PLAC {}", place));
+    }
+}
+
+fn sex(value: &str) -> Option<&'static str> {
+    match value.to_ascii_uppercase().as_str() {
+        "M" | "MALE" => Some("M"),
+        "F" | "FEMALE" => Some("F"),
+        "X" | "N" | "NONBINARY" => Some("X"),
+        "U" | "UNKNOWN" => Some("U"),
+        _ => None,
+    }
+}
+
+/// A parsed CSV file: the column names and the rows, with their line numbers
+struct Table {
+    columns: HashMap<String, usize>,
+    rows: Vec<(usize, Vec<String>)>,
+}
+
+impl Table {
+    fn parse(input: &str) -> Result<Table, CsvError> {
+        let input = input.strip_prefix('\u{FEFF}').unwrap_or(input);
+
+        let mut rows: Vec<(usize, Vec<String>)> = vec![];
+        let mut row: Vec<String> = vec![];
+        let mut field = String::new();
+        let mut quoted = false;
+        let mut line = 1;
+        let mut start = 1;
+
+        let mut chars = input.chars().peekable();
+        while let Some(ch) = chars.next() {
+            match ch {
+                '"' if quoted => {
+                    if chars.peek() == Some(&'"') {
+                        chars.next();
+                        field.push('"');
+                    } else {
+                        quoted = false;
+                    }
+                }
+                '"' if field.is_empty() => quoted = true,
+                '\n' if quoted => {
+                    // GEDCOM values can't span lines
+                    line += 1;
+                    field.push(' ');
+                }
+                '\r' if quoted => {}
+                ',' if !quoted => row.push(std::mem::take(&mut field)),
+                '\r' => {}
+                '\n' => {
+                    row.push(std::mem::take(&mut field));
+                    rows.push((start, std::mem::take(&mut row)));
+                    line += 1;
+                    start = line;
+                }
+                _ => field.push(ch),
+            }
+        }
+        if quoted {
+            return Err(CsvError::UnterminatedQuote { line: start });
+        }
+        if !field.is_empty() || !row.is_empty() {
+            row.push(field);
+            rows.push((start, row));
+        }
+
+        // Skip empty rows
+        rows.retain(|(_, row)| row.iter().any(|f| !f.trim().is_empty()));
+
+        let mut columns = HashMap::new();
+        if !rows.is_empty() {
+            let (_, header) = rows.remove(0);
+            for (i, name) in header.iter().enumerate() {
+                columns.insert(name.trim().to_lowercase(), i);
+            }
+        }
+
+        Ok(Table { columns, rows })
+    }
+
+    /// Get the (trimmed) value of a column in a row, or "" if it's missing.
+    fn get<'a>(&self, row: &'a [String], column: &str) -> &'a str {
+        self.columns
+            .get(column)
+            .and_then(|i| row.get(*i))
+            .map(|f| f.trim())
+            .unwrap_or("")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_table() {
+        let input = "Id,Given,Note\r\n1,\"Smith, Jr.\",\"The \"\"Elder\"\"\"\n\n2,Mary,\"two\nlines\"\n3,Ann";
+        let table = Table::parse(input).unwrap();
+
+        assert!(table.rows.len() == 3);
+        let (line, row) = &table.rows[0];
+        assert!(*line == 2);
+        assert!(table.get(row, "given") == "Smith, Jr.");
+        assert!(table.get(row, "note") == "The \"Elder\"");
+        assert!(table.get(row, "surname").is_empty());
+
+        let (line, row) = &table.rows[1];
+        assert!(*line == 4);
+        assert!(table.get(row, "note") == "two lines");
+
+        let (line, row) = &table.rows[2];
+        assert!(*line == 6);
+        assert!(table.get(row, "given") == "Ann");
+
+        assert!(matches!(
+            Table::parse("id,note\n1,\"oops\n"),
+            Err(CsvError::UnterminatedQuote { line: 2 })
+        ));
+    }
+
+    #[test]
+    fn import_csv() {
+        let individuals = [
+            "id,given,surname,sex,birth_date,birth_place,death_date,father,mother",
+            "john,John,Smith,M,1 JAN 1900,\"Springfield, IL, USA\",1970,,",
+            "jane,Jane,Doe,female,ABT 1902,,,,",
+            "bob,Bob,Smith,M,1925,,,john,jane",
+            "sue,Sue,Smith,F,1927,,,john,jane",
+            "tim,Tim,Smith,M,1950,,,bob,",
+        ]
+        .join("\n");
+        let families = [
+            "husband,wife,marriage_date,marriage_place,children",
+            "john,jane,10 JUN 1924,Springfield,bob",
+        ]
+        .join("\n");
+
+        let gedcom = gedcom_from_csv(&individuals, &families).unwrap();
+        assert!(gedcom.individuals.len() == 5);
+        assert!(gedcom.families.len() == 2);
+
+        let john = &gedcom.individuals[0];
+        assert!(john.xref == Some("@I1@".to_string()));
+        assert!(john.gender == crate::types::Gender::Male);
+        let name = &john.names[0].name;
+        assert!(name.value == Some("John /Smith/".to_string()));
+        assert!(name.given == Some("John".to_string()));
+        assert!(name.surname == Some("Smith".to_string()));
+        assert!(john.fams.len() == 1);
+        assert!(john.fams[0].xref == "@F1@");
+        assert!(john.birth.len() == 1);
+        assert!(john.death.len() == 1);
+
+        let jane = &gedcom.individuals[1];
+        assert!(jane.gender == crate::types::Gender::Female);
+
+        // Sue is linked via the father/mother columns to the existing family
+        let family = &gedcom.families[0];
+        assert!(family.xref == "@F1@");
+        assert!(family.children == ["@I3@", "@I4@"]);
+        assert!(family.marriage[0].date() == Some("10 JUN 1924"));
+        let sue = &gedcom.individuals[3];
+        assert!(sue.famc[0].xref == "@F1@");
+
+        // Bob's family was created for Tim
+        let family = &gedcom.families[1];
+        assert!(family.xref == "@F2@");
+        assert!(
+            family.husband.as_ref().unwrap().xref.as_ref().unwrap().xref
+                == Some("@I3@".to_string())
+        );
+        assert!(family.wife.is_none());
+        assert!(family.children == ["@I5@"]);
+        assert!(gedcom.individuals[2].fams[0].xref == "@F2@");
+
+        // Families are optional
+        let gedcom = gedcom_from_csv(&individuals, "").unwrap();
+        assert!(gedcom.families.len() == 2);
+    }
+
+    #[test]
+    fn import_csv_errors() {
+        assert!(matches!(
+            gedcom_from_csv("given\nJohn", ""),
+            Err(CsvError::MissingColumn(_))
+        ));
+        assert!(matches!(
+            gedcom_from_csv("id,given\n1,John\n1,Jane", ""),
+            Err(CsvError::DuplicateId { line: 3, .. })
+        ));
+        assert!(matches!(
+            gedcom_from_csv("id,father\n1,\n2,3", ""),
+            Err(CsvError::UnknownId { line: 3, .. })
+        ));
+        assert!(matches!(
+            gedcom_from_csv("id,given\n1,John", "husband,wife\n1,2"),
+            Err(CsvError::UnknownId { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn import_csv_files() {
+        let gedcom =
+            gedcom_from_csv_files("./data/csv/individuals.csv", "./data/csv/families.csv").unwrap();
+        assert!(gedcom.individuals.len() == 4);
+        assert!(gedcom.families.len() == 1);
+    }
+}
@@ -0,0 +1,905 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+use roxmltree::{Document, Node, ParsingOptions};
+
+use crate::error::GedcomError;
+use crate::parse::parse_gedcom_from_str;
+use crate::types::Gedcom;
+
+// Build a Gedcom from a Gramps XML (.gramps) file, as written by Gramps'
+// own export or backup.
+//
+// The people, families, events, places, sources, citations, media objects
+// and notes in the file are translated into the GEDCOM records they'd have
+// been exported as, which are then parsed as usual, so the result is the
+// same as if the data had been read from a GEDCOM file:
+//
+//   person       INDI, with a NAME for each name and an event for each event
+//                the person is the primary participant in
+//   family       FAM, with HUSB, WIFE, CHIL and the family's events. A
+//                child's relationship to the parents becomes a PEDI.
+//   event        BIRT, DEAT, MARR, etc., or EVEN with a TYPE for the
+//                types GEDCOM has no tag for
+//   place        PLAC, the place's name followed by the places it's in, and
+//                a MAP if it has coordinates
+//   source       SOUR
+//   citation     a SOUR citation, with its PAGE and QUAY
+//   object       OBJE
+//   note         a NOTE, wherever the note is referenced
+//
+// The Gramps id of each object (I0001, F0001, ...) becomes its xref, and
+// the home person, if there is one, the header's _HOME. Anything else, such
+// as repositories, attributes and events a person only witnessed, is left
+// out.
+
+#[derive(Debug)]
+/// An error encountered while importing a Gramps XML file
+pub enum GrampsError {
+    /// The file couldn't be read or decompressed
+    Io(io::Error),
+    /// The file isn't well-formed XML
+    Xml(String),
+    /// The file is XML, but not a Gramps database
+    NotGramps,
+    /// The GEDCOM the file was translated into couldn't be parsed
+    Gedcom(GedcomError),
+}
+
+impl fmt::Display for GrampsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GrampsError::Io(err) => write!(f, "{}", err),
+            GrampsError::Xml(err) => write!(f, "invalid XML: {}", err),
+            GrampsError::NotGramps => write!(f, "not a Gramps XML file"),
+            GrampsError::Gedcom(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl Error for GrampsError {}
+
+impl From<io::Error> for GrampsError {
+    fn from(err: io::Error) -> Self {
+        GrampsError::Io(err)
+    }
+}
+
+/// Build a Gedcom from the (uncompressed) XML of a Gramps file.
+pub fn gedcom_from_gramps(xml: &str) -> Result<Gedcom, GrampsError> {
+    let options = ParsingOptions {
+        allow_dtd: true,
+        ..ParsingOptions::default()
+    };
+    let document =
+        Document::parse_with_options(xml, options).map_err(|e| GrampsError::Xml(e.to_string()))?;
+    let database = document.root_element();
+    if database.tag_name().name() != "database" {
+        return Err(GrampsError::NotGramps);
+    }
+
+    let text = Converter::new(database).convert();
+    parse_gedcom_from_str(&text).map_err(GrampsError::Gedcom)
+}
+
+/// Build a Gedcom from the contents of a Gramps file, which may be
+/// gzip-compressed, as Gramps writes them by default, or plain XML.
+pub fn gedcom_from_gramps_bytes(bytes: &[u8]) -> Result<Gedcom, GrampsError> {
+    let mut xml = String::new();
+    if bytes.starts_with(&[0x1f, 0x8b]) {
+        GzDecoder::new(bytes).read_to_string(&mut xml)?;
+    } else {
+        xml = String::from_utf8(bytes.to_vec())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    }
+    gedcom_from_gramps(&xml)
+}
+
+/// Build a Gedcom from a Gramps file on disk.
+pub fn gedcom_from_gramps_file<P: AsRef<Path>>(path: P) -> Result<Gedcom, GrampsError> {
+    gedcom_from_gramps_bytes(&fs::read(path)?)
+}
+
+/// Translates a Gramps database into GEDCOM lines
+struct Converter<'a, 'input> {
+    database: Node<'a, 'input>,
+    /// The xref of every object with a handle
+    xrefs: HashMap<&'a str, String>,
+    /// Every object with a handle, for following references
+    objects: HashMap<&'a str, Node<'a, 'input>>,
+    /// The PEDI of each child's link to a family, by (child, family) handle
+    pedigrees: HashMap<(&'a str, &'a str), &'static str>,
+    lines: Vec<String>,
+}
+
+impl<'a, 'input> Converter<'a, 'input> {
+    fn new(database: Node<'a, 'input>) -> Self {
+        let mut converter = Converter {
+            database,
+            xrefs: HashMap::new(),
+            objects: HashMap::new(),
+            pedigrees: HashMap::new(),
+            lines: vec![],
+        };
+
+        let mut used = HashSet::new();
+        let mut count = 0;
+        for section in elements(database) {
+            for object in elements(section) {
+                let handle = match object.attribute("handle") {
+                    Some(handle) => handle,
+                    None => continue,
+                };
+                converter.objects.insert(handle, object);
+
+                // Use the Gramps id if it makes a usable xref, and isn't
+                // taken, otherwise make one up
+                let id: String = object
+                    .attribute("id")
+                    .unwrap_or_default()
+                    .chars()
+                    .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+                    .collect();
+                let xref = if !id.is_empty() && !used.contains(&id) {
+                    id
+                } else {
+                    loop {
+                        count += 1;
+                        let xref = format!("X{}", count);
+                        if !used.contains(&xref) {
+                            break xref;
+                        }
+                    }
+                };
+                used.insert(xref.clone());
+                converter.xrefs.insert(handle, format!("@{}@", xref));
+            }
+        }
+
+        for family in section(database, "families") {
+            let family_handle = family.attribute("handle").unwrap_or_default();
+            for child in children(family, "childref") {
+                let pedigree = [child.attribute("frel"), child.attribute("mrel")]
+                    .iter()
+                    .filter_map(|r| pedigree(r.unwrap_or("Birth")))
+                    .find(|p| *p != "birth")
+                    .unwrap_or("birth");
+                if let Some(handle) = child.attribute("hlink") {
+                    converter
+                        .pedigrees
+                        .insert((handle, family_handle), pedigree);
+                }
+            }
+        }
+
+        converter
+    }
+
+    fn convert(mut self) -> String {
+        self.line(0, "HEAD", "");
+        self.line(1, "SOUR", "Gramps");
+        self.line(1, "GEDC", "");
+        self.line(2, "VERS", "5.5.1");
+        self.line(2, "FORM", "LINEAGE-LINKED");
+        self.line(1, "CHAR", "UTF-8");
+        let home = child(self.database, "people")
+            .and_then(|people| people.attribute("home"))
+            .and_then(|home| self.xrefs.get(home).cloned());
+        if let Some(home) = home {
+            self.line(1, "_HOME", &home);
+        }
+
+        for person in section(self.database, "people") {
+            self.person(person);
+        }
+        for family in section(self.database, "families") {
+            self.family(family);
+        }
+        for source in section(self.database, "sources") {
+            self.source(source);
+        }
+        for object in section(self.database, "objects") {
+            self.object(object);
+        }
+
+        self.line(0, "TRLR", "");
+        self.lines.join("\n") + "\n"
+    }
+
+    fn person(&mut self, person: Node<'a, 'input>) {
+        let handle = person.attribute("handle").unwrap_or_default();
+        let xref = self.xref(handle);
+        self.line(0, &format!("{} INDI", xref), "");
+
+        for name in children(person, "name") {
+            self.name(name);
+        }
+        let sex = match child_text(person, "gender") {
+            Some("M") => "M",
+            Some("F") => "F",
+            Some("X") => "X",
+            _ => "U",
+        };
+        self.line(1, "SEX", sex);
+
+        for event in children(person, "eventref") {
+            if matches!(event.attribute("role"), None | Some("Primary")) {
+                self.event(1, event);
+            }
+        }
+        for family in children(person, "childof") {
+            let link = family.attribute("hlink").unwrap_or_default();
+            if let Some(xref) = self.xrefs.get(link).cloned() {
+                self.line(1, "FAMC", &xref);
+                if let Some(pedigree) = self.pedigrees.get(&(handle, link)) {
+                    self.line(2, "PEDI", pedigree);
+                }
+            }
+        }
+        for family in children(person, "parentin") {
+            self.pointer(1, "FAMS", family);
+        }
+        self.references(1, person);
+    }
+
+    fn name(&mut self, name: Node) {
+        let given = child_text(name, "first").unwrap_or_default();
+        let surnames: Vec<String> = children(name, "surname")
+            .filter_map(|surname| {
+                let text = surname.text().unwrap_or_default().trim();
+                let prefix = surname.attribute("prefix").unwrap_or_default();
+                let full = format!("{} {}", prefix, text);
+                let full = full.trim();
+                (!full.is_empty()).then(|| full.to_string())
+            })
+            .collect();
+        let suffix = child_text(name, "suffix").unwrap_or_default();
+
+        let mut value = given.to_string();
+        if !surnames.is_empty() {
+            value = format!("{} /{}/", value, surnames.join(" "));
+        }
+        if !suffix.is_empty() {
+            value = format!("{} {}", value, suffix);
+        }
+        self.line(1, "NAME", value.trim());
+
+        let kind = match name.attribute("type") {
+            Some("Birth Name") => Some("birth"),
+            Some("Married Name") => Some("married"),
+            Some("Also Known As") => Some("aka"),
+            _ => None,
+        };
+        if let Some(kind) = kind {
+            self.line(2, "TYPE", kind);
+        }
+        if let Some(title) = child_text(name, "title") {
+            self.line(2, "NPFX", title);
+        }
+        if !given.is_empty() {
+            self.line(2, "GIVN", given);
+        }
+        if let Some(nick) = child_text(name, "nick") {
+            self.line(2, "NICK", nick);
+        }
+        let primary = children(name, "surname")
+            .find(|s| s.attribute("prim") != Some("0"))
+            .or_else(|| child(name, "surname"));
+        if let Some(surname) = primary {
+            if let Some(prefix) = surname.attribute("prefix") {
+                self.line(2, "SPFX", prefix);
+            }
+            if let Some(text) = surname.text().filter(|t| !t.trim().is_empty()) {
+                self.line(2, "SURN", text.trim());
+            }
+        }
+        if !suffix.is_empty() {
+            self.line(2, "NSFX", suffix);
+        }
+    }
+
+    fn family(&mut self, family: Node<'a, 'input>) {
+        let xref = self.xref(family.attribute("handle").unwrap_or_default());
+        self.line(0, &format!("{} FAM", xref), "");
+        if let Some(father) = child(family, "father") {
+            self.pointer(1, "HUSB", father);
+        }
+        if let Some(mother) = child(family, "mother") {
+            self.pointer(1, "WIFE", mother);
+        }
+        for child in children(family, "childref") {
+            self.pointer(1, "CHIL", child);
+        }
+        for event in children(family, "eventref") {
+            if matches!(event.attribute("role"), None | Some("Family")) {
+                self.event(1, event);
+            }
+        }
+        self.references(1, family);
+    }
+
+    /// An event, from a reference to it
+    fn event(&mut self, level: u8, reference: Node) {
+        let event = match self.target(reference) {
+            Some(event) => event,
+            None => return,
+        };
+        let kind = child_text(event, "type").unwrap_or_default();
+        let description = child_text(event, "description").unwrap_or_default();
+        match event_tag(kind) {
+            Some(tag @ ("OCCU" | "EDUC" | "RELI" | "TITL" | "NATI" | "PROP")) => {
+                self.line(level, tag, description);
+            }
+            Some(tag) => {
+                self.line(level, tag, "");
+            }
+            None => {
+                self.line(level, "EVEN", description);
+                self.line(level + 1, "TYPE", kind);
+            }
+        }
+        if let Some(date) = date(event) {
+            self.line(level + 1, "DATE", &date);
+        }
+        if let Some(place) = child(event, "place") {
+            self.place(level + 1, place);
+        }
+        self.references(level + 1, event);
+    }
+
+    /// A place, from a reference to it
+    fn place(&mut self, level: u8, reference: Node) {
+        let place = match self.target(reference) {
+            Some(place) => place,
+            None => return,
+        };
+
+        // Older files give each place a full title; newer ones only the
+        // place's own name, and the places it's in
+        let mut names = vec![];
+        match child_text(place, "ptitle") {
+            Some(title) => names.push(title.to_string()),
+            None => {
+                let mut seen = HashSet::new();
+                let mut current = Some(place);
+                while let Some(node) = current {
+                    if !seen.insert(node.attribute("handle")) {
+                        break;
+                    }
+                    if let Some(name) = child(node, "pname").and_then(|n| n.attribute("value")) {
+                        names.push(name.to_string());
+                    }
+                    current = child(node, "placeref").and_then(|r| self.target(r));
+                }
+            }
+        }
+        if names.is_empty() {
+            return;
+        }
+        self.line(level, "PLAC", &names.join(", "));
+
+        if let Some(coord) = child(place, "coord") {
+            let latitude = coord.attribute("lat").and_then(|l| coordinate(l, 'N', 'S'));
+            let longitude = coord
+                .attribute("long")
+                .and_then(|l| coordinate(l, 'E', 'W'));
+            if let (Some(latitude), Some(longitude)) = (latitude, longitude) {
+                self.line(level + 1, "MAP", "");
+                self.line(level + 2, "LATI", &latitude);
+                self.line(level + 2, "LONG", &longitude);
+            }
+        }
+    }
+
+    fn source(&mut self, source: Node<'a, 'input>) {
+        let xref = self.xref(source.attribute("handle").unwrap_or_default());
+        self.line(0, &format!("{} SOUR", xref), "");
+        for (element, tag) in [
+            ("stitle", "TITL"),
+            ("sauthor", "AUTH"),
+            ("spubinfo", "PUBL"),
+            ("sabbrev", "ABBR"),
+        ] {
+            if let Some(text) = child_text(source, element) {
+                self.line(1, tag, text);
+            }
+        }
+        self.references(1, source);
+    }
+
+    fn object(&mut self, object: Node<'a, 'input>) {
+        let xref = self.xref(object.attribute("handle").unwrap_or_default());
+        self.line(0, &format!("{} OBJE", xref), "");
+        if let Some(file) = child(object, "file") {
+            self.line(1, "FILE", file.attribute("src").unwrap_or_default());
+            let form = file
+                .attribute("mime")
+                .and_then(|mime| mime.rsplit('/').next())
+                .filter(|form| !form.is_empty());
+            if let Some(form) = form {
+                self.line(2, "FORM", form);
+            }
+            if let Some(title) = file.attribute("description").filter(|t| !t.is_empty()) {
+                self.line(1, "TITL", title);
+            }
+        }
+        self.references(1, object);
+    }
+
+    /// The notes, citations and media an object refers to
+    fn references(&mut self, level: u8, node: Node) {
+        for note in children(node, "noteref") {
+            if let Some(text) = self.target(note).and_then(|n| child_text(n, "text")) {
+                self.line(level, "NOTE", text);
+            }
+        }
+        for citation in children(node, "citationref") {
+            self.citation(level, citation);
+        }
+        for object in children(node, "objref") {
+            self.pointer(level, "OBJE", object);
+        }
+    }
+
+    /// A source citation, from a reference to it
+    fn citation(&mut self, level: u8, reference: Node) {
+        let citation = match self.target(reference) {
+            Some(citation) => citation,
+            None => return,
+        };
+        let source = child(citation, "sourceref")
+            .and_then(|s| s.attribute("hlink"))
+            .and_then(|s| self.xrefs.get(s).cloned());
+        let source = match source {
+            Some(source) => source,
+            None => return,
+        };
+        self.line(level, "SOUR", &source);
+        if let Some(page) = child_text(citation, "page") {
+            self.line(level + 1, "PAGE", page);
+        }
+        // Gramps rates citations from 0 (very low) to 4 (very high), and
+        // GEDCOM from 0 to 3
+        let quay = child_text(citation, "confidence")
+            .and_then(|c| c.parse::<u8>().ok())
+            .map(|c| c.min(3));
+        if let Some(quay) = quay {
+            self.line(level + 1, "QUAY", &quay.to_string());
+        }
+        for note in children(citation, "noteref") {
+            if let Some(text) = self.target(note).and_then(|n| child_text(n, "text")) {
+                self.line(level + 1, "NOTE", text);
+            }
+        }
+    }
+
+    /// A pointer to another record, from a reference to it
+    fn pointer(&mut self, level: u8, tag: &str, reference: Node) {
+        let xref = reference
+            .attribute("hlink")
+            .and_then(|handle| self.xrefs.get(handle).cloned());
+        if let Some(xref) = xref {
+            self.line(level, tag, &xref);
+        }
+    }
+
+    /// The object a reference refers to
+    fn target(&self, reference: Node) -> Option<Node<'a, 'input>> {
+        reference
+            .attribute("hlink")
+            .and_then(|handle| self.objects.get(handle).copied())
+    }
+
+    fn xref(&self, handle: &str) -> String {
+        self.xrefs.get(handle).cloned().unwrap_or_default()
+    }
+
+    /// Add a line, continuing the value on CONT lines if it has more than
+    /// one line
+    fn line(&mut self, level: u8, tag: &str, value: &str) {
+        let mut lines = value.lines();
+        match lines.next().filter(|l| !l.is_empty()) {
+            Some(first) => self.lines.push(format!("{} {} {}", level, tag, first)),
+            None => self.lines.push(format!("{} {}", level, tag)),
+        }
+        for line in lines {
+            if line.is_empty() {
+                self.lines.push(format!("{} CONT", level + 1));
+            } else {
+                self.lines.push(format!("{} CONT {}", level + 1, line));
+            }
+        }
+    }
+}
+
+/// The child elements of a node
+fn elements<'a, 'input>(node: Node<'a, 'input>) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children().filter(|n| n.is_element())
+}
+
+/// The child elements of a node with the given name
+fn children<'a, 'input: 'a>(
+    node: Node<'a, 'input>,
+    name: &'a str,
+) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    elements(node).filter(move |n| n.tag_name().name() == name)
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Option<Node<'a, 'input>> {
+    elements(node).find(|n| n.tag_name().name() == name)
+}
+
+/// The text of a child element, if it has any
+fn child_text<'a>(node: Node<'a, '_>, name: &str) -> Option<&'a str> {
+    child(node, name)
+        .and_then(|n| n.text())
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+}
+
+/// The elements in one of the database's sections, i.e. "people"
+fn section<'a, 'input: 'a>(
+    database: Node<'a, 'input>,
+    name: &'a str,
+) -> impl Iterator<Item = Node<'a, 'input>> + 'a {
+    children(database, name).flat_map(elements)
+}
+
+/// The GEDCOM tag for a Gramps event type, if it has one
+fn event_tag(kind: &str) -> Option<&'static str> {
+    Some(match kind {
+        "Adopted" => "ADOP",
+        "Adult Christening" => "CHRA",
+        "Annulment" => "ANUL",
+        "Baptism" => "BAPM",
+        "Bar Mitzvah" => "BARM",
+        "Bas Mitzvah" => "BASM",
+        "Blessing" => "BLES",
+        "Birth" => "BIRT",
+        "Burial" => "BURI",
+        "Census" => "CENS",
+        "Christening" => "CHR",
+        "Confirmation" => "CONF",
+        "Cremation" => "CREM",
+        "Death" => "DEAT",
+        "Divorce" => "DIV",
+        "Divorce Filing" => "DIVF",
+        "Education" => "EDUC",
+        "Emigration" => "EMIG",
+        "Engagement" => "ENGA",
+        "First Communion" => "FCOM",
+        "Graduation" => "GRAD",
+        "Immigration" => "IMMI",
+        "Marriage" => "MARR",
+        "Marriage Banns" => "MARB",
+        "Marriage Contract" => "MARC",
+        "Marriage License" => "MARL",
+        "Marriage Settlement" => "MARS",
+        "Naturalization" => "NATU",
+        "Nobility Title" => "TITL",
+        "Occupation" => "OCCU",
+        "Probate" => "PROB",
+        "Property" => "PROP",
+        "Religion" => "RELI",
+        "Residence" => "RESI",
+        "Retirement" => "RETI",
+        "Will" => "WILL",
+        _ => return None,
+    })
+}
+
+/// The PEDI for a Gramps child reference type, if it has one
+fn pedigree(relationship: &str) -> Option<&'static str> {
+    match relationship {
+        "Birth" => Some("birth"),
+        "Adopted" => Some("adopted"),
+        "Foster" => Some("foster"),
+        _ => None,
+    }
+}
+
+/// An event's date, as a GEDCOM date value
+fn date(event: Node) -> Option<String> {
+    for node in elements(event) {
+        let calendar = node.attribute("cformat");
+        let value = match node.tag_name().name() {
+            "dateval" => {
+                let value = simple_date(node.attribute("val")?, calendar)?;
+                match (node.attribute("type"), node.attribute("quality")) {
+                    (Some("before"), _) => format!("BEF {}", value),
+                    (Some("after"), _) => format!("AFT {}", value),
+                    (Some("about"), _) => format!("ABT {}", value),
+                    (_, Some("estimated")) => format!("EST {}", value),
+                    (_, Some("calculated")) => format!("CAL {}", value),
+                    _ => value,
+                }
+            }
+            "daterange" => format!(
+                "BET {} AND {}",
+                simple_date(node.attribute("start")?, calendar)?,
+                simple_date(node.attribute("stop")?, calendar)?
+            ),
+            "datespan" => format!(
+                "FROM {} TO {}",
+                simple_date(node.attribute("start")?, calendar)?,
+                simple_date(node.attribute("stop")?, calendar)?
+            ),
+            "datestr" => format!("({})", node.attribute("val")?),
+            _ => continue,
+        };
+        return Some(value);
+    }
+    None
+}
+
+/// A Gramps date, i.e. 1900-01-31, 1900-01 or 1900-00-00, as a GEDCOM date,
+/// i.e. 31 JAN 1900. Dates in a calendar GEDCOM doesn't have are kept as a
+/// date phrase.
+fn simple_date(value: &str, calendar: Option<&str>) -> Option<String> {
+    const GREGORIAN: [&str; 12] = [
+        "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+    ];
+    const HEBREW: [&str; 13] = [
+        "TSH", "CSH", "KSL", "TVT", "SHV", "ADR", "ADS", "NSN", "IYR", "SVN", "TMZ", "AAV", "ELL",
+    ];
+    const FRENCH: [&str; 13] = [
+        "VEND", "BRUM", "FRIM", "NIVO", "PLUV", "VENT", "GERM", "FLOR", "PRAI", "MESS", "THER",
+        "FRUC", "COMP",
+    ];
+    let (escape, months): (&str, &[&str]) = match calendar {
+        None | Some("Gregorian") => ("", &GREGORIAN),
+        Some("Julian") => ("@#DJULIAN@ ", &GREGORIAN),
+        Some("Hebrew") => ("@#DHEBREW@ ", &HEBREW),
+        Some("French Republican") => ("@#DFRENCH R@ ", &FRENCH),
+        Some(calendar) => return Some(format!("({} {})", calendar, value)),
+    };
+
+    let mut parts = value.splitn(3, '-').map(|p| p.parse::<usize>().ok());
+    let year = parts.next().flatten().filter(|y| *y > 0)?;
+    let month = parts.next().flatten().unwrap_or(0);
+    let day = parts.next().flatten().unwrap_or(0);
+
+    let mut date = escape.to_string();
+    if month > 0 {
+        let month = months.get(month - 1)?;
+        if day > 0 {
+            date += &format!("{} ", day);
+        }
+        date += &format!("{} ", month);
+    }
+    Some(format!("{}{}", date, year))
+}
+
+/// A coordinate as GEDCOM writes it, i.e. N51.5 or W0.12, from the decimal
+/// degrees Gramps usually stores, i.e. 51.5 or -0.12. Coordinates in other
+/// forms, i.e. 51°30'N, are left out.
+fn coordinate(value: &str, positive: char, negative: char) -> Option<String> {
+    let value = value.trim();
+    let (sign, degrees) = match value.chars().next()? {
+        c if c == positive => (1.0, &value[1..]),
+        c if c == negative => (-1.0, &value[1..]),
+        _ => (1.0, value),
+    };
+    let degrees = sign * degrees.parse::<f64>().ok().filter(|d| d.is_finite())?;
+    let direction = if degrees < 0.0 { negative } else { positive };
+    Some(format!("{}{}", direction, degrees.abs()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Gender, Pedigree};
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    const XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!DOCTYPE database PUBLIC "-//Gramps//DTD Gramps XML 1.7.1//EN"
+"http://gramps-project.org/xml/1.7.1/grampsxml.dtd">
+<database xmlns="http://gramps-project.org/xml/1.7.1/">
+  <header>
+    <created date="2024-01-01" version="5.2.0"/>
+  </header>
+  <events>
+    <event handle="_e1" id="E0000">
+      <type>Birth</type>
+      <dateval val="1900-01-31"/>
+      <place hlink="_p1"/>
+      <citationref hlink="_c1"/>
+    </event>
+    <event handle="_e2" id="E0001">
+      <type>Death</type>
+      <dateval val="1970" type="about"/>
+    </event>
+    <event handle="_e3" id="E0002">
+      <type>Marriage</type>
+      <daterange start="1924-06" stop="1925" cformat="Julian"/>
+      <noteref hlink="_n1"/>
+    </event>
+    <event handle="_e4" id="E0003">
+      <type>Occupation</type>
+      <description>Weaver</description>
+      <datestr val="in his youth"/>
+    </event>
+    <event handle="_e5" id="E0004">
+      <type>Custom thing</type>
+    </event>
+  </events>
+  <people home="_i1">
+    <person handle="_i1" id="I0001">
+      <gender>M</gender>
+      <name type="Birth Name">
+        <first>John</first>
+        <surname prefix="van">Smith</surname>
+        <suffix>Jr</suffix>
+      </name>
+      <name type="Also Known As" alt="1">
+        <first>Jack</first>
+      </name>
+      <eventref hlink="_e1" role="Primary"/>
+      <eventref hlink="_e2" role="Primary"/>
+      <eventref hlink="_e4" role="Primary"/>
+      <eventref hlink="_e5" role="Primary"/>
+      <parentin hlink="_f1"/>
+      <noteref hlink="_n2"/>
+      <objref hlink="_o1"/>
+    </person>
+    <person handle="_i2" id="I0002">
+      <gender>F</gender>
+      <name type="Birth Name"><first>Jane</first><surname>Doe</surname></name>
+      <eventref hlink="_e1" role="Witness"/>
+      <parentin hlink="_f1"/>
+    </person>
+    <person handle="_i3" id="I0001">
+      <gender>U</gender>
+      <name type="Birth Name"><first>Bob</first><surname>Smith</surname></name>
+      <childof hlink="_f1"/>
+    </person>
+  </people>
+  <families>
+    <family handle="_f1" id="F0001">
+      <rel type="Married"/>
+      <father hlink="_i1"/>
+      <mother hlink="_i2"/>
+      <eventref hlink="_e3" role="Family"/>
+      <childref hlink="_i3" frel="Adopted"/>
+    </family>
+  </families>
+  <citations>
+    <citation handle="_c1" id="C0000">
+      <page>12</page>
+      <confidence>4</confidence>
+      <sourceref hlink="_s1"/>
+    </citation>
+  </citations>
+  <sources>
+    <source handle="_s1" id="S0001">
+      <stitle>Parish register</stitle>
+      <sauthor>St Mary's</sauthor>
+    </source>
+  </sources>
+  <places>
+    <placeobj handle="_p1" id="P0001" type="City">
+      <pname value="York"/>
+      <coord long="-1.08" lat="53.96"/>
+      <placeref hlink="_p2"/>
+    </placeobj>
+    <placeobj handle="_p2" id="P0002" type="Country">
+      <pname value="England"/>
+    </placeobj>
+  </places>
+  <objects>
+    <object handle="_o1" id="O0001">
+      <file src="photos/john.jpg" mime="image/jpeg" description="John"/>
+    </object>
+  </objects>
+  <notes>
+    <note handle="_n1" id="N0001" type="Event Note">
+      <text>At St Mary's</text>
+    </note>
+    <note handle="_n2" id="N0002" type="Person Note">
+      <text>First line
+Second line</text>
+    </note>
+  </notes>
+</database>
+"#;
+
+    #[test]
+    fn import_gramps() {
+        let gedcom = gedcom_from_gramps(XML).unwrap();
+        assert!(gedcom.warnings.is_empty());
+        assert!(gedcom.individuals.len() == 3);
+        assert!(gedcom.families.len() == 1);
+        assert!(gedcom.sources.len() == 1);
+        assert!(gedcom.multimedia.len() == 1);
+
+        let john = &gedcom.individuals[0];
+        assert!(john.xref == Some("@I0001@".to_string()));
+        assert!(john.gender == Gender::Male);
+        assert!(john.names.len() == 2);
+        let name = &john.names[0].name;
+        assert!(name.value == Some("John /van Smith/ Jr".to_string()));
+        assert!(name.given == Some("John".to_string()));
+        assert!(name.surname == Some("Smith".to_string()));
+        assert!(john.names[1].name.value == Some("Jack".to_string()));
+
+        let birth = &john.birth[0].event.detail;
+        assert!(birth.date == Some("31 JAN 1900".to_string()));
+        let place = birth.place.as_ref().unwrap();
+        assert!(place.name == Some("York, England".to_string()));
+        let map = place.map.as_ref().unwrap();
+        assert!(map.latitude == 53.96);
+        assert!(map.longitude == -1.08);
+        assert!(birth.sources[0].xref == Some("@S0001@".to_string()));
+        assert!(birth.sources[0].page.as_deref() == Some("12"));
+        let death = john.death[0].event.as_ref().unwrap();
+        assert!(death.date == Some("ABT 1970".to_string()));
+        let occupation = &john.occupations[0];
+        assert!(occupation.value == Some("Weaver".to_string()));
+        assert!(occupation.detail.date == Some("(in his youth)".to_string()));
+        assert!(john.events.len() == 1);
+        assert!(john.notes[0].note == Some("First line\nSecond line".to_string()));
+        assert!(john.fams[0].xref == "@F0001@");
+
+        // A witness isn't given the event, and a duplicate id is replaced
+        let jane = &gedcom.individuals[1];
+        assert!(jane.birth.is_empty());
+        let bob = &gedcom.individuals[2];
+        assert!(bob.xref == Some("@X1@".to_string()));
+        assert!(bob.famc[0].xref == "@F0001@");
+        assert!(bob.famc[0].pedigree == Some(Pedigree::Adopted));
+
+        let family = &gedcom.families[0];
+        assert!(family.husband_xref() == Some("@I0001@"));
+        assert!(family.wife_xref() == Some("@I0002@"));
+        assert!(family.children.len() == 1);
+        let marriage = family.marriage[0].detail.as_ref().unwrap();
+        assert!(marriage.date == Some("BET @#DJULIAN@ JUN 1924 AND @#DJULIAN@ 1925".to_string()));
+        assert!(marriage.note == Some("At St Mary's".to_string()));
+
+        assert!(gedcom.sources[0].title == Some("Parish register".to_string()));
+        assert!(gedcom.multimedia[0].xref == "@O0001@");
+        assert!(gedcom.header.home_person == Some("@I0001@".to_string()));
+    }
+
+    #[test]
+    fn import_compressed() {
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        encoder.write_all(XML.as_bytes()).unwrap();
+        let bytes = encoder.finish().unwrap();
+        let gedcom = gedcom_from_gramps_bytes(&bytes).unwrap();
+        assert!(gedcom.individuals.len() == 3);
+
+        assert!(matches!(
+            gedcom_from_gramps("<gedcom/>"),
+            Err(GrampsError::NotGramps)
+        ));
+        assert!(matches!(
+            gedcom_from_gramps("<database>"),
+            Err(GrampsError::Xml(_))
+        ));
+    }
+
+    #[test]
+    fn dates() {
+        assert!(simple_date("1900-00-00", None) == Some("1900".to_string()));
+        assert!(simple_date("1900-02", None) == Some("FEB 1900".to_string()));
+        assert!(
+            simple_date("5770-07-01", Some("Hebrew")) == Some("@#DHEBREW@ 1 ADS 5770".to_string())
+        );
+        assert!(
+            simple_date("1300-01-01", Some("Islamic")) == Some("(Islamic 1300-01-01)".to_string())
+        );
+        assert!(simple_date("0-00-00", None).is_none());
+        assert!(coordinate("-33.9", 'N', 'S') == Some("S33.9".to_string()));
+        assert!(coordinate("E151.2", 'E', 'W') == Some("E151.2".to_string()));
+        assert!(coordinate("S-1", 'N', 'S') == Some("N1".to_string()));
+        assert!(coordinate("51°30'N", 'N', 'S').is_none());
+    }
+}
@@ -0,0 +1,10 @@
+/// Build a Gedcom from data in other formats
+pub mod csv;
+#[cfg(feature = "gramps")]
+pub mod gramps;
+
+pub use self::csv::{gedcom_from_csv, gedcom_from_csv_files, CsvError};
+#[cfg(feature = "gramps")]
+pub use self::gramps::{
+    gedcom_from_gramps, gedcom_from_gramps_bytes, gedcom_from_gramps_file, GrampsError,
+};
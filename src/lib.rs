@@ -1,2 +1,54 @@
+// The older tests predate these lints, and are left as they were written
+#![cfg_attr(
+    test,
+    allow(
+        clippy::assertions_on_constants,
+        clippy::cmp_owned,
+        clippy::comparison_to_empty,
+        clippy::empty_line_after_doc_comments,
+        clippy::len_zero,
+        clippy::useless_vec
+    )
+)]
+
+pub mod access;
+pub mod analysis;
+#[cfg(feature = "tui")]
+pub mod browse;
+pub mod builder;
+pub mod changes;
+pub mod chart;
+pub mod citations;
+pub mod compliance;
+pub mod config;
+pub mod dna;
+pub mod error;
+pub mod export;
+#[cfg(feature = "gedzip")]
+pub mod gedzip;
+pub mod graph;
+pub mod import;
+pub mod marriage;
+pub mod media;
+pub mod names;
 pub mod parse;
+pub mod provenance;
+pub mod query;
+pub mod relationship;
+pub mod renumber;
+pub mod repair;
+pub mod report;
+#[cfg(feature = "romanize")]
+pub mod romanize;
+pub mod sample;
+pub mod search;
+#[cfg(feature = "site")]
+pub mod site;
+pub mod statistics;
+pub mod summary;
+pub mod timeline;
 pub mod types;
+pub mod validate;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod writer;
@@ -1,16 +1,39 @@
+// The older tests predate this lint, and are left as they were written
+#![cfg_attr(test, allow(clippy::cmp_owned))]
+
 extern crate gedcom_rs;
 
-use gedcom_rs::parse::parse_gedcom;
+use gedcom_rs::analysis::RESEARCH_MARKERS;
+use gedcom_rs::chart::Numbering;
+use gedcom_rs::compliance::check_compliance_file;
+use gedcom_rs::config::GedcomConfig;
+use gedcom_rs::error::GedcomError;
+use gedcom_rs::export::svg::SvgOptions;
+use gedcom_rs::export::{CsvOptions, FamilyColumn, IndividualColumn};
+use gedcom_rs::graph::GraphFormat;
+use gedcom_rs::media::{copy_media, media_inventory};
+use gedcom_rs::parse::{parse_gedcom, parse_gedcom_with_config};
+use gedcom_rs::renumber::XrefScheme;
+use gedcom_rs::repair::{fix_gedcom_file, repair_gedcom_file, set_home_person, RepairOptions};
+use gedcom_rs::report::ReportFormat;
+use gedcom_rs::search::SearchQuery;
+use gedcom_rs::types::{EventDetail, Gedcom, GedcomVersion, Individual};
+use gedcom_rs::validate::{validate_gedcom_with_config, Severity, ValidationConfig};
+use gedcom_rs::writer::{write_gedcom_file, write_gedcom_file_as};
 
+use std::cell::LazyCell;
+use std::collections::BTreeMap;
 use std::env;
+use std::path::Path;
+use std::str::FromStr;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    match args.len() {
-        1 => usage("Missing filename."),
-        s if s > 2 => usage(&format!("Found more args than expected: {:?}", &args[1..])),
-        _ => (),
-    };
+    let mut args: Vec<String> = env::args().collect();
+    let progress = args.iter().any(|a| a == "--progress");
+    args.retain(|a| a != "--progress");
+    if args.len() == 1 {
+        usage("Missing filename.");
+    }
 
     let filename = &args[1];
 
@@ -18,18 +41,970 @@ fn main() {
         usage("");
     }
 
-    let gedcom = parse_gedcom(filename);
+    // Commands that write a new file read this one themselves. See `read`.
+    let gedcom = LazyCell::new(|| {
+        if progress {
+            parse_with_progress(filename)
+        } else {
+            parse_gedcom(filename)
+        }
+    });
+
+    match args.get(2).map(|s| s.as_str()) {
+        None => print!("{}", summary(&gedcom)),
+        Some("media") => match media(&gedcom, filename, &args[3..]) {
+            Ok(report) => print!("{}", report),
+            Err(err) => usage(&err),
+        },
+        Some("relationship") => match &args[3..] {
+            [first, second] => match relationship(&gedcom, first, second) {
+                Ok(report) => print!("{}", report),
+                Err(err) => fail(&err),
+            },
+            [other] => match gedcom.header.home_person.as_deref() {
+                Some(home) => match relationship(&gedcom, home, other) {
+                    Ok(report) => print!("{}", report),
+                    Err(err) => fail(&err),
+                },
+                None => fail("No home person is set; see the home command"),
+            },
+            _ => usage("relationship takes two xrefs, i.e. @I1@ @I2@"),
+        },
+        #[cfg(feature = "tui")]
+        Some("browse") => {
+            if let Err(err) = gedcom_rs::browse::browse(&gedcom) {
+                fail(&err.to_string());
+            }
+        }
+        #[cfg(not(feature = "tui"))]
+        Some("browse") => fail("browse needs the tui feature"),
+        Some("chart") => match chart(&gedcom, &args[3..]) {
+            Ok(chart) => print!("{}", chart),
+            Err(err) => usage(&err),
+        },
+        Some("convert") => match &args[3..] {
+            [version, output] => {
+                match convert(&read(filename, &GedcomConfig::new()), version, output) {
+                    Ok(warnings) => {
+                        for warning in warnings {
+                            eprintln!("{}", warning);
+                        }
+                    }
+                    Err(err) => fail(&err),
+                }
+            }
+            _ => usage("convert takes a version and a file to write, i.e. 5.5.1 out.ged"),
+        },
+        Some("anonymize") => match &args[3..] {
+            [output, options @ ..] => {
+                if let Err(err) = anonymize(read(filename, &GedcomConfig::new()), output, options) {
+                    usage(&err);
+                }
+            }
+            _ => usage("anonymize takes a file to write, i.e. out.ged"),
+        },
+        Some("export") => match export(&gedcom, &args[3..]) {
+            Ok(written) => {
+                for path in written {
+                    println!("Wrote {}", path);
+                }
+            }
+            Err(err) => usage(&err),
+        },
+        Some("fix") => match &args[3..] {
+            [output] => match fix(filename, output) {
+                Ok(report) => print!("{}", report),
+                Err(err) => fail(&err),
+            },
+            _ => usage("fix takes a file to write, i.e. out.ged"),
+        },
+        Some("graph") => match graph(&gedcom, &args[3..]) {
+            Ok(graph) => print!("{}", graph),
+            Err(err) => usage(&err),
+        },
+        Some("home") => match &args[3..] {
+            [xref, output] => {
+                if let Err(err) = home(filename, xref, output) {
+                    fail(&err);
+                }
+            }
+            _ => usage("home takes an xref and a file to write, i.e. @I1@ out.ged"),
+        },
+        Some("renumber") => match renumber(
+            read(filename, &GedcomConfig::new().preserve_unknown()),
+            &args[3..],
+        ) {
+            Ok(report) => print!("{}", report),
+            Err(err) => usage(&err),
+        },
+        Some("repair") => match &args[3..] {
+            [output] => match repair(filename, output) {
+                Ok(report) => print!("{}", report),
+                Err(err) => fail(&err),
+            },
+            _ => usage("repair takes a file to write, i.e. out.ged"),
+        },
+        Some("report") => match report(&gedcom, &args[3..]) {
+            Ok(report) => print!("{}", report),
+            Err(err) => usage(&err),
+        },
+        #[cfg(feature = "site")]
+        Some("site") => match &args[3..] {
+            [directory] => match gedcom.export_site(Path::new(directory)) {
+                Ok(written) => println!("Wrote {} page(s) to {}", written.len(), directory),
+                Err(err) => fail(&err.to_string()),
+            },
+            _ => usage("site takes a directory to write to, i.e. ./site"),
+        },
+        #[cfg(not(feature = "site"))]
+        Some("site") => fail("site needs the site feature"),
+        Some("search") => match search(&gedcom, &args[3..]) {
+            Ok(report) => print!("{}", report),
+            Err(err) => usage(&err),
+        },
+        Some("tasks") => match tasks(&gedcom, &args[3..]) {
+            Ok(report) => print!("{}", report),
+            Err(err) => usage(&err),
+        },
+        Some("stats") => print!("{}", gedcom.statistics()),
+        Some("validate") => match validate(&gedcom, filename, &args[3..]) {
+            Ok((report, passed)) => {
+                print!("{}", report);
+                if !passed {
+                    std::process::exit(1);
+                }
+            }
+            Err(err) => usage(&err),
+        },
+        Some(command) => usage(&format!("Unknown command: {}", command)),
+    }
+}
+
+/// Parse the file to write a new one from, exiting if it can't be read,
+/// rather than writing an empty tree
+fn read(filename: &str, config: &GedcomConfig) -> Gedcom {
+    parse_gedcom_with_config(filename, config)
+        .unwrap_or_else(|err| fail(&format!("{}: {}", filename, err)))
+}
+
+/// Parse the file, drawing a progress bar as it goes
+fn parse_with_progress(filename: &str) -> Gedcom {
+    let config = GedcomConfig::new().on_progress(|records, bytes, total| {
+        eprint!("\r{}", progress_bar(records, bytes, total));
+    });
+    let gedcom = parse_gedcom_with_config(filename, &config).unwrap_or_else(|err| Gedcom {
+        warnings: vec![err],
+        ..Default::default()
+    });
+    eprintln!();
+    gedcom
+}
+
+/// A line showing how far parsing has got, i.e.
+/// "[#######             ]  35% 1200 records"
+fn progress_bar(records: usize, bytes: usize, total: Option<usize>) -> String {
+    const WIDTH: usize = 20;
+    match total.filter(|t| *t > 0) {
+        Some(total) => {
+            let done = bytes.min(total);
+            let filled = done * WIDTH / total;
+            format!(
+                "[{}{}] {:>3}% {} records",
+                "#".repeat(filled),
+                " ".repeat(WIDTH - filled),
+                done * 100 / total,
+                records
+            )
+        }
+        None => format!("{} KB, {} records", bytes / 1024, records),
+    }
+}
+
+/// Describe how two individuals are related: what the second is to the
+/// first, their most recent common ancestor(s), and the path between them
+fn relationship(gedcom: &Gedcom, first: &str, second: &str) -> Result<String, String> {
+    let find = |xref: &str| {
+        gedcom
+            .find_individual(xref)
+            .ok_or(format!("No individual {} found", xref))
+    };
+    let (individual, other) = (find(first)?, find(second)?);
+
+    let mut report = String::new();
+    match gedcom.find_relationship(individual, other) {
+        Some(relationship) => {
+            report += &format!(
+                "{} is the {} of {}\n",
+                display_name(other),
+                relationship.description,
+                display_name(individual)
+            );
+            if !relationship.common_ancestors.is_empty() {
+                let names: Vec<String> = relationship
+                    .common_ancestors
+                    .iter()
+                    .map(|a| display_name(a))
+                    .collect();
+                report += &format!("Most recent common ancestor(s): {}\n", names.join(", "));
+            }
+            if let Some(dna) = relationship.shared_dna() {
+                report += &format!(
+                    "Expected shared DNA: {}% ({} cM on average, {} to {} cM)\n",
+                    dna.percent, dna.average, dna.low, dna.high
+                );
+            }
+        }
+        None => {
+            report += &format!(
+                "{} and {} are not related by blood or marriage\n",
+                display_name(individual),
+                display_name(other)
+            );
+        }
+    }
+    if let Some(path) = gedcom.find_relationship_path(individual, other) {
+        let names: Vec<String> = path.iter().map(|i| display_name(i)).collect();
+        report += &format!("Path: {}\n", names.join(" -> "));
+    }
+
+    Ok(report)
+}
+
+/// Draw a `--pedigree` chart of an individual's ancestors, or a
+/// `--descendants` outline, going back or down `--generations` generations
+/// (4, by default). Descendants are numbered by generation, or with the
+/// `--numbering` system given. With `--format svg`, draw a fan chart or a
+/// box chart instead.
+fn chart(gedcom: &Gedcom, args: &[String]) -> Result<String, String> {
+    let mut pedigree = None;
+    let mut descendants = None;
+    let mut generations = 4;
+    let mut numbering = Numbering::default();
+    let mut svg = false;
+    for pair in args.chunks(2) {
+        match pair {
+            [flag, xref] if flag == "--pedigree" => pedigree = Some(xref),
+            [flag, xref] if flag == "--descendants" => descendants = Some(xref),
+            [flag, value] if flag == "--generations" => {
+                generations = value
+                    .parse()
+                    .map_err(|_| format!("Invalid number of generations: {}", value))?
+            }
+            [flag, value] if flag == "--numbering" => {
+                numbering = Numbering::from_str(value)
+                    .map_err(|_| format!("Unknown numbering system: {}", value))?
+            }
+            [flag, value] if flag == "--format" => {
+                svg = match value.as_str() {
+                    "text" => false,
+                    "svg" => true,
+                    _ => return Err(format!("Unknown chart format: {}", value)),
+                }
+            }
+            [flag, _] => return Err(format!("Unknown chart option: {}", flag)),
+            _ => return Err(format!("Missing value for {}", pair[0])),
+        }
+    }
+
+    let options = SvgOptions::new().generations(generations);
+    let chart = match (pedigree, descendants) {
+        (Some(xref), None) if svg => gedcom.fan_chart_svg(xref, &options),
+        (None, Some(xref)) if svg => gedcom.descendant_chart_svg(xref, &options),
+        (Some(xref), None) => gedcom.render_pedigree(xref, generations),
+        (None, Some(xref)) => gedcom.render_numbered_descendants(xref, generations, numbering),
+        _ => return Err("chart takes one of --pedigree or --descendants".to_string()),
+    };
+    chart.ok_or_else(|| format!("No individual {} found", pedigree.or(descendants).unwrap()))
+}
+
+/// Write a narrative report of an individual's `--descendants` or
+/// `--ancestors`, going down or back `--generations` generations (4, by
+/// default), in Markdown or HTML
+fn report(gedcom: &Gedcom, args: &[String]) -> Result<String, String> {
+    let mut ancestors = None;
+    let mut descendants = None;
+    let mut generations = 4;
+    let mut format = ReportFormat::default();
+    for pair in args.chunks(2) {
+        match pair {
+            [flag, xref] if flag == "--ancestors" => ancestors = Some(xref),
+            [flag, xref] if flag == "--descendants" => descendants = Some(xref),
+            [flag, value] if flag == "--generations" => {
+                generations = value
+                    .parse()
+                    .map_err(|_| format!("Invalid number of generations: {}", value))?
+            }
+            [flag, value] if flag == "--format" => {
+                format = ReportFormat::from_str(value)
+                    .map_err(|_| format!("Unknown report format: {}", value))?
+            }
+            [flag, _] => return Err(format!("Unknown report option: {}", flag)),
+            _ => return Err(format!("Missing value for {}", pair[0])),
+        }
+    }
+
+    let report = match (ancestors, descendants) {
+        (Some(xref), None) => gedcom.ahnentafel_report(xref, generations, format),
+        (None, Some(xref)) => gedcom.descendant_report(xref, generations, format),
+        _ => return Err("report takes one of --ancestors or --descendants".to_string()),
+    };
+    report.ok_or_else(|| format!("No individual {} found", ancestors.or(descendants).unwrap()))
+}
+
+/// List every file the GEDCOM refers to, looking for relative paths next
+/// to the GEDCOM file, and `--copy` those found into a directory
+fn media(gedcom: &Gedcom, filename: &str, args: &[String]) -> Result<String, String> {
+    let target = match args {
+        [] => None,
+        [flag, dir] if flag == "--copy" => Some(Path::new(dir)),
+        [flag, ..] => return Err(format!("Unknown media option: {}", flag)),
+    };
+    let base = Path::new(filename).parent().unwrap_or(Path::new(""));
+    let inventory = media_inventory(gedcom, base);
+
+    let rows: Vec<[String; 5]> = inventory
+        .iter()
+        .map(|item| {
+            let used_by: Vec<String> = item
+                .used_by
+                .iter()
+                .map(|u| match u.tag {
+                    Some(tag) => format!("{} {}", u.xref, tag),
+                    None => u.xref.to_string(),
+                })
+                .collect();
+            let status = if item.is_url() {
+                "url"
+            } else if item.archive.is_some() {
+                "archived"
+            } else if item.exists {
+                "found"
+            } else {
+                "missing"
+            };
+            [
+                item.path.to_string(),
+                item.format.unwrap_or_default().to_string(),
+                item.title.unwrap_or_default().to_string(),
+                used_by.join(", "),
+                status.to_string(),
+            ]
+        })
+        .collect();
+    let mut report = table(["File", "Format", "Title", "Used by", "Status"], &rows);
+
+    if let Some(target) = target {
+        let copied = copy_media(&inventory, base, target).map_err(|err| err.to_string())?;
+        report += &format!(
+            "\nCopied {} file(s) to {}\n",
+            copied.len(),
+            target.display()
+        );
+    }
+    Ok(report)
+}
+
+/// Write the individuals and families to `--csv` or `--tsv` files in a
+/// directory, with the `--columns` and `--family-columns` given, separated
+/// by commas, or else every column
+fn export(gedcom: &Gedcom, args: &[String]) -> Result<Vec<String>, String> {
+    let mut options = CsvOptions::new();
+    let mut directory = None;
+    for pair in args.chunks(2) {
+        match pair {
+            [flag, dir] if flag == "--csv" => directory = Some(dir),
+            [flag, dir] if flag == "--tsv" => {
+                directory = Some(dir);
+                options = options.tsv();
+            }
+            [flag, columns] if flag == "--columns" => {
+                let columns: Result<Vec<IndividualColumn>, String> = columns
+                    .split(',')
+                    .map(|c| {
+                        IndividualColumn::from_str(c).map_err(|_| format!("Unknown column: {}", c))
+                    })
+                    .collect();
+                options = options.individual_columns(&columns?);
+            }
+            [flag, columns] if flag == "--family-columns" => {
+                let columns: Result<Vec<FamilyColumn>, String> = columns
+                    .split(',')
+                    .map(|c| {
+                        FamilyColumn::from_str(c)
+                            .map_err(|_| format!("Unknown family column: {}", c))
+                    })
+                    .collect();
+                options = options.family_columns(&columns?);
+            }
+            [flag, _] => return Err(format!("Unknown export option: {}", flag)),
+            _ => return Err(format!("Missing value for {}", pair[0])),
+        }
+    }
+    let directory = directory.ok_or("export takes --csv or --tsv and a directory")?;
+    let written = gedcom
+        .export_csv(&options, Path::new(directory))
+        .map_err(|err| err.to_string())?;
+    Ok(written.iter().map(|p| p.display().to_string()).collect())
+}
+
+/// List the research tasks in the file's notes: the lines with one of the
+/// `--markers`, separated by commas, or else the usual ones, i.e. TODO
+fn tasks(gedcom: &Gedcom, args: &[String]) -> Result<String, String> {
+    let markers: Vec<&str> = match args {
+        [] => RESEARCH_MARKERS.to_vec(),
+        [flag, markers] if flag == "--markers" => markers.split(',').map(|m| m.trim()).collect(),
+        [flag, ..] => return Err(format!("Unknown tasks option: {}", flag)),
+    };
+    let rows: Vec<[String; 2]> = gedcom
+        .research_tasks(&markers)
+        .iter()
+        .map(|task| {
+            let record = match task.tag {
+                Some(tag) => format!("{} {}", task.xref, tag),
+                None => task.xref.to_string(),
+            };
+            [record, task.snippet.clone()]
+        })
+        .collect();
+    Ok(table(["Record", "Task"], &rows))
+}
+
+/// Export the family graph as `--format` graphml (the default) or gexf,
+/// with generations counted from `--root`, or else the home person
+fn graph(gedcom: &Gedcom, args: &[String]) -> Result<String, String> {
+    let mut format = GraphFormat::default();
+    let mut root = gedcom.header.home_person.as_deref();
+    for pair in args.chunks(2) {
+        match pair {
+            [flag, value] if flag == "--format" => {
+                format = GraphFormat::from_str(value)
+                    .map_err(|_| format!("Unknown graph format: {}", value))?
+            }
+            [flag, xref] if flag == "--root" => root = Some(xref),
+            [flag, _] => return Err(format!("Unknown graph option: {}", flag)),
+            _ => return Err(format!("Missing value for {}", pair[0])),
+        }
+    }
+    gedcom
+        .export_graph(format, root)
+        .ok_or_else(|| format!("No individual {} found", root.unwrap_or_default()))
+}
+
+/// Write the tree to `output` as another version of GEDCOM, returning what
+/// couldn't be converted
+fn convert(gedcom: &Gedcom, version: &str, output: &str) -> Result<Vec<String>, String> {
+    let version = GedcomVersion::from_str(version).map_err(|_| {
+        format!(
+            "Unknown GEDCOM version: {} (try 5.5, 5.5.1 or 7.0)",
+            version
+        )
+    })?;
+    let warnings = write_gedcom_file_as(gedcom, output, version).map_err(|err| err.to_string())?;
+    Ok(warnings
+        .iter()
+        .map(|w| match &w.xref {
+            Some(xref) => format!("{}: {}", xref, w.message),
+            None => format!("HEAD: {}", w.message),
+        })
+        .collect())
+}
+
+/// Give every record a new xref, sequential unless `--scheme uuid` is
+/// given, and write the file, renamed, to a new one. The Gedcom must be
+/// parsed with `GedcomConfig::preserve_unknown`, so the original text is
+/// written, without losing what the model doesn't keep.
+fn renumber(mut gedcom: Gedcom, args: &[String]) -> Result<String, String> {
+    let (output, scheme) = match args {
+        [output] => (output, XrefScheme::Sequential),
+        [output, flag, scheme] if flag == "--scheme" => (
+            output,
+            XrefScheme::from_str(scheme).map_err(|_| format!("Unknown scheme {}", scheme))?,
+        ),
+        _ => return Err("renumber takes a file to write, i.e. out.ged".to_string()),
+    };
+    let xrefs = gedcom.renumber_xrefs(scheme);
+    let text: String = gedcom.raw_records.iter().map(|r| r.text()).collect();
+    std::fs::write(output, text).map_err(|err| err.to_string())?;
+    let changed = xrefs.iter().filter(|(old, new)| old != new).count();
+    Ok(format!(
+        "Renamed {} of {} xrefs; wrote {}\n",
+        changed,
+        xrefs.len(),
+        output
+    ))
+}
+
+/// Fix the file's common corruptions, i.e. jumps in level or a missing TRLR,
+/// writing the result to `output`, and list the repairs made
+fn repair(filename: &str, output: &str) -> Result<String, String> {
+    let repairs = repair_gedcom_file(filename, output).map_err(|err| err.to_string())?;
+    let mut report = String::new();
+    for repair in &repairs {
+        report += &format!("{}\n", repair);
+    }
+    report += &format!("{} repair(s) made; wrote {}\n", repairs.len(), output);
+    Ok(report)
+}
+
+/// Make every repair there is, to the text of the file and its links
+/// between individuals and families, writing the result to `output`
+fn fix(filename: &str, output: &str) -> Result<String, String> {
+    let repairs = fix_gedcom_file(filename, output, &RepairOptions::default())
+        .map_err(|err| err.to_string())?;
+    let mut report = String::new();
+    for repair in &repairs {
+        report += &format!("{}\n", repair);
+    }
+    report += &format!("{} repair(s) made; wrote {}\n", repairs.len(), output);
+    Ok(report)
+}
+
+/// Write an anonymized copy of the tree to `output`, to share in a bug
+/// report, optionally of just a `--sample` of individuals and their
+/// families. `--seed` chooses a different sample and replacements.
+fn anonymize(gedcom: Gedcom, output: &str, args: &[String]) -> Result<(), String> {
+    let mut size = None;
+    let mut seed = 0;
+    for pair in args.chunks(2) {
+        match pair {
+            [flag, value] if flag == "--sample" => {
+                size = Some(
+                    value
+                        .parse::<usize>()
+                        .map_err(|_| format!("Invalid sample size: {}", value))?,
+                )
+            }
+            [flag, value] if flag == "--seed" => {
+                seed = value
+                    .parse::<u64>()
+                    .map_err(|_| format!("Invalid seed: {}", value))?
+            }
+            [flag, _] => return Err(format!("Unknown anonymize option: {}", flag)),
+            _ => return Err(format!("Missing value for {}", pair[0])),
+        }
+    }
+    let mut gedcom = match size {
+        Some(size) => gedcom.random_sample(size, seed),
+        None => gedcom,
+    };
+    gedcom.anonymize(seed);
+    write_gedcom_file(&gedcom, output).map_err(|err| err.to_string())
+}
+
+/// How many of each record the file has, and what went wrong parsing it,
+/// with the first few warnings of each category
+fn summary(gedcom: &Gedcom) -> String {
+    let summary = gedcom.summary();
+    let mut report = String::new();
+    let counts = [
+        ("Individuals", summary.individuals),
+        ("Families", summary.families),
+        ("Sources", summary.sources),
+        ("Repositories", summary.repositories),
+        ("Multimedia", summary.multimedia),
+        ("Notes", summary.notes),
+        ("Submitters", summary.submitters),
+    ];
+    for (records, count) in counts.iter() {
+        report += &format!("{}: {}\n", records, count);
+    }
+    report += &format!(
+        "Events: {} ({} dated, {} sourced)\n",
+        summary.events, summary.dated_events, summary.sourced_events
+    );
+    if !gedcom.warnings.is_empty() {
+        report += &format!("\nWarnings: {}\n", gedcom.warnings.len());
+        for group in gedcom.summarize_warnings(5) {
+            report += &group.to_string();
+        }
+    }
+    report
+}
+
+/// Make `xref` the home person and write the file, with that choice, to
+/// `output`. Only the header's `_HOME` line is changed.
+fn home(filename: &str, xref: &str, output: &str) -> Result<(), String> {
+    let gedcom = parse_gedcom_with_config(filename, &GedcomConfig::new())
+        .map_err(|err| format!("{}: {}", filename, err))?;
+    if gedcom.find_individual(xref).is_none() {
+        return Err(format!("No individual {} found", xref));
+    }
+    let input = std::fs::read(filename).map_err(|err| err.to_string())?;
+    let text =
+        set_home_person(&input, xref).ok_or_else(|| format!("{} has no header", filename))?;
+    std::fs::write(output, text).map_err(|err| err.to_string())
+}
 
-    // TODO: print a pretty summary of the gedcom. Use `tabled` crate?
-    println!("{:#?}", gedcom);
+/// Something `validate` found wrong with the file
+struct Problem {
+    severity: Severity,
+    xref: Option<String>,
+    line: Option<usize>,
+    message: String,
+    fix: Option<String>,
 }
 
+/// Check the file for parse problems and run every validation rule over it,
+/// reporting what was found by category, as `--format text` (the default)
+/// or `json`. The file passes if nothing was found at or above the
+/// `--fail-on` severity, which is `error` by default. With the
+/// `rules-config` feature, `--config` reads which rules to run, and how, from
+/// a TOML or JSON file. `--strict-spec` also checks the file follows the
+/// GEDCOM 5.5.1 grammar, with anything that doesn't an error. Parse
+/// warnings are grouped by `Gedcom::summarize_warnings`, and the text report
+/// lists only the first `--limit` problems of each category, 20 by default.
+fn validate(gedcom: &Gedcom, filename: &str, args: &[String]) -> Result<(String, bool), String> {
+    let strict = args.iter().any(|a| a == "--strict-spec");
+    let args: Vec<String> = args
+        .iter()
+        .filter(|a| *a != "--strict-spec")
+        .cloned()
+        .collect();
+    let mut json = false;
+    let mut limit = 20;
+    let mut fail_on = Severity::Error;
+    #[cfg_attr(not(feature = "rules-config"), allow(unused_mut))]
+    let mut config = ValidationConfig::default();
+    for pair in args.chunks(2) {
+        match pair {
+            [flag, format] if flag == "--format" => {
+                json = match format.as_str() {
+                    "text" => false,
+                    "json" => true,
+                    _ => return Err(format!("Unknown format: {}", format)),
+                }
+            }
+            [flag, n] if flag == "--limit" => {
+                limit = n.parse().map_err(|_| format!("Invalid limit: {}", n))?
+            }
+            [flag, severity] if flag == "--fail-on" => {
+                fail_on = severity
+                    .parse()
+                    .map_err(|_| format!("Unknown severity: {}", severity))?
+            }
+            #[cfg(feature = "rules-config")]
+            [flag, file] if flag == "--config" => {
+                config = ValidationConfig::from_file(file).map_err(|err| err.to_string())?
+            }
+            #[cfg(not(feature = "rules-config"))]
+            [flag, _] if flag == "--config" => {
+                return Err("--config needs the rules-config feature".to_string())
+            }
+            [flag, _] => return Err(format!("Unknown validate option: {}", flag)),
+            _ => return Err(format!("Missing value for {}", pair[0])),
+        }
+    }
+
+    // Parse warnings are grouped by the model, the rest here. The counts and
+    // the JSON report take both.
+    let mut categories: BTreeMap<String, Vec<Problem>> = BTreeMap::new();
+    let mut parsing: BTreeMap<String, Vec<Problem>> = BTreeMap::new();
+    for (category, warnings) in gedcom.warnings_by_category() {
+        let problems = warnings.into_iter().map(|warning| Problem {
+            // Not being able to read the file at all is more than a warning
+            severity: match warning {
+                GedcomError::Io(_) => Severity::Error,
+                _ => Severity::Warning,
+            },
+            xref: None,
+            line: warning.line(),
+            message: warning.message(),
+            fix: None,
+        });
+        parsing.insert(category.to_string(), problems.collect());
+    }
+    if strict {
+        for violation in check_compliance_file(filename).map_err(|err| err.to_string())? {
+            categories
+                .entry("spec".to_string())
+                .or_default()
+                .push(Problem {
+                    severity: Severity::Error,
+                    xref: None,
+                    line: Some(violation.line),
+                    message: format!(
+                        "{} (GEDCOM 5.5.1, {})",
+                        violation.message, violation.section
+                    ),
+                    fix: None,
+                });
+        }
+    }
+    for finding in validate_gedcom_with_config(gedcom, &config) {
+        categories
+            .entry(finding.rule.to_string())
+            .or_default()
+            .push(Problem {
+                severity: finding.severity,
+                xref: finding.xref,
+                line: finding.line,
+                message: finding.message,
+                fix: finding.fix.map(|fix| fix.to_string()),
+            });
+    }
+
+    let problems = || parsing.values().chain(categories.values()).flatten();
+    let count = |severity| problems().filter(|p| p.severity == severity).count();
+    let (errors, warnings) = (count(Severity::Error), count(Severity::Warning));
+    let info = count(Severity::Info);
+    let passed = problems().all(|p| p.severity < fail_on);
+
+    let mut report = String::new();
+    if json {
+        report += &format!(
+            "{{\"passed\": {}, \"errors\": {}, \"warnings\": {}, \"info\": {}, \"categories\": {{",
+            passed, errors, warnings, info
+        );
+        for (i, (category, problems)) in parsing.iter().chain(&categories).enumerate() {
+            if i > 0 {
+                report += ",";
+            }
+            report += &format!("\n  {}: [", json_string(category));
+            for (j, problem) in problems.iter().enumerate() {
+                if j > 0 {
+                    report += ",";
+                }
+                report += &format!(
+                    "\n    {{\"severity\": {}, \"xref\": {}, \"line\": {}, \"message\": {}, \"fix\": {}}}",
+                    json_string(&problem.severity.to_string()),
+                    problem.xref.as_deref().map_or("null".to_string(), json_string),
+                    problem.line.map_or("null".to_string(), |l| l.to_string()),
+                    json_string(&problem.message),
+                    problem.fix.as_deref().map_or("null".to_string(), json_string),
+                );
+            }
+            report += "\n  ]";
+        }
+        report += "\n}}\n";
+    } else {
+        for group in gedcom.summarize_warnings(limit) {
+            report += &group.to_string();
+        }
+        for (category, problems) in &categories {
+            report += &format!("{} ({})\n", category, problems.len());
+            for problem in problems.iter().take(limit) {
+                report += &format!("  {}", problem.severity);
+                if let Some(xref) = &problem.xref {
+                    report += &format!(" {}", xref);
+                }
+                if let Some(line) = problem.line {
+                    report += &format!(" (line {})", line);
+                }
+                report += &format!(": {}", problem.message);
+                if let Some(fix) = &problem.fix {
+                    report += &format!(" (suggested fix: {})", fix);
+                }
+                report += "\n";
+            }
+            if problems.len() > limit {
+                report += &format!("  and {} more like this\n", problems.len() - limit);
+            }
+        }
+        report += &format!("{} error(s), {} warning(s)", errors, warnings);
+        if info > 0 {
+            report += &format!(", {} info", info);
+        }
+        report += "\n";
+    }
+
+    Ok((report, passed))
+}
+
+/// Quote a string for JSON output
+fn json_string(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => quoted += "\\\"",
+            '\\' => quoted += "\\\\",
+            '\n' => quoted += "\\n",
+            '\r' => quoted += "\\r",
+            '\t' => quoted += "\\t",
+            c if c.is_control() => quoted += &format!("\\u{:04x}", c as u32),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Find the individuals matching the `--name`, `--birth-year`,
+/// `--death-year`, `--place`, and `--event` filters, best match first, as a
+/// table
+fn search(gedcom: &Gedcom, args: &[String]) -> Result<String, String> {
+    if args.is_empty() {
+        return Err("search needs at least one filter".to_string());
+    }
+
+    let individuals = match args {
+        [flag, query] if flag == "--query" => gedcom.query(query).map_err(|e| e.to_string())?,
+        _ => search_flags(gedcom, args)?,
+    };
+
+    let rows: Vec<[String; 4]> = individuals
+        .into_iter()
+        .map(|individual| {
+            let event = |tag: &str| {
+                individual
+                    .tagged_events()
+                    .into_iter()
+                    .find(|(t, _)| *t == tag)
+                    .map(|(_, detail)| event_summary(detail))
+                    .unwrap_or_default()
+            };
+            [
+                individual.xref.clone().unwrap_or_default(),
+                name(individual),
+                event("BIRT"),
+                event("DEAT"),
+            ]
+        })
+        .collect();
+
+    Ok(table(["XREF", "NAME", "BIRTH", "DEATH"], &rows))
+}
+
+/// The individuals matching search flags, i.e. --name, best match first
+fn search_flags<'a>(gedcom: &'a Gedcom, args: &[String]) -> Result<Vec<&'a Individual>, String> {
+    let mut query = SearchQuery::new();
+    for pair in args.chunks(2) {
+        let (flag, value) = match pair {
+            [flag, value] => (flag.as_str(), value.as_str()),
+            _ => return Err(format!("Missing value for {}", pair[0])),
+        };
+        let year = || {
+            value
+                .parse::<i32>()
+                .map_err(|_| format!("Invalid year for {}: {}", flag, value))
+        };
+        query = match flag {
+            "--name" => query.name(value),
+            "--birth-year" => query.birth_year(year()?),
+            "--death-year" => query.death_year(year()?),
+            "--place" => query.place(value),
+            "--event" => query.event(value),
+            _ => return Err(format!("Unknown search filter: {}", flag)),
+        };
+    }
+
+    Ok(gedcom
+        .search_individuals(&query)
+        .into_iter()
+        .map(|result| result.item)
+        .collect())
+}
+
+/// The date and place of an event, i.e. "1 JAN 1900, Salt Lake City"
+fn event_summary(detail: Option<&EventDetail>) -> String {
+    let date = detail.and_then(|d| d.date.as_deref());
+    let place = detail.and_then(|d| d.place.as_ref()?.name.as_deref());
+    match (date, place) {
+        (Some(date), Some(place)) => format!("{}, {}", date, place),
+        (Some(text), None) | (None, Some(text)) => text.to_string(),
+        (None, None) => "Y".to_string(),
+    }
+}
+
+/// Lay out rows in columns, padded to the widest value in each
+fn table<const N: usize>(headings: [&str; N], rows: &[[String; N]]) -> String {
+    let mut widths: Vec<usize> = headings.iter().map(|h| h.chars().count()).collect();
+    for row in rows {
+        for (width, value) in widths.iter_mut().zip(row) {
+            *width = (*width).max(value.chars().count());
+        }
+    }
+
+    let line = |values: Vec<&str>| {
+        let cells: Vec<String> = values
+            .iter()
+            .zip(&widths)
+            .map(|(value, width)| format!("{:width$}", value, width = width))
+            .collect();
+        cells.join("  ").trim_end().to_string() + "\n"
+    };
+
+    let mut text = line(headings.to_vec());
+    for row in rows {
+        text += &line(row.iter().map(|v| v.as_str()).collect());
+    }
+    text
+}
+
+/// The individual's first name, without the slashes around the surname
+fn name(individual: &Individual) -> String {
+    individual
+        .primary_name()
+        .unwrap_or_else(|| "(unknown)".to_string())
+}
+
+/// The individual's first name, without the slashes around the surname,
+/// and their xref
+fn display_name(individual: &Individual) -> String {
+    format!(
+        "{} ({})",
+        name(individual),
+        individual.xref.as_deref().unwrap_or_default()
+    )
+}
+
+/// Print how to use gedcom-rs, after `msg` saying what was wrong with the
+/// arguments, and exit with 2, or 0 if there's no `msg` (`--help`)
 fn usage(msg: &str) {
     if !msg.is_empty() {
         println!("{msg}");
     }
-    println!("Usage: gedcom-test ./path/to/gedcom.ged");
-    std::process::exit(0x0100);
+    println!("Usage: gedcom-rs ./path/to/gedcom.ged [--progress] [command]");
+    println!();
+    println!("Options:");
+    println!("  --progress                  Show a progress bar while the file is parsed");
+    println!();
+    println!("Commands:");
+    println!("  anonymize <output.ged> [--sample <n>] [--seed <n>]");
+    println!("                              Write a copy without names, places or dates to share");
+    println!(
+        "  browse                      Explore the tree interactively (needs the tui feature)"
+    );
+    println!("  chart (--pedigree <xref> | --descendants <xref>) [--generations <n>]");
+    println!("        [--numbering generation|daboville|henry|register|ngsq] [--format text|svg]");
+    println!("                              Draw an individual's ancestors or descendants");
+    println!("  convert <5.5|5.5.1|7.0> <output.ged>");
+    println!("                              Write the tree as another version of GEDCOM");
+    println!("  export (--csv | --tsv) <dir> [--columns <a,b,...>] [--family-columns <a,b,...>]");
+    println!("                              Write individuals and families for a spreadsheet");
+    println!("  fix <output.ged>            Repair the file and its links, writing a new file");
+    println!("  graph [--format graphml|gexf] [--root <xref>]");
+    println!("                              Export the family graph for Gephi or Cytoscape");
+    println!("  home <xref> <output.ged>    Set the home person and write the file to a new one");
+    println!("  media [--copy <dir>]        List the files the tree refers to, and copy them");
+    println!("  relationship [<xref>] <xref>");
+    println!("                              How the second individual is related to the first,");
+    println!("                              or to the home person");
+    println!("  renumber <output.ged> [--scheme sequential|uuid]");
+    println!("                              Give every record a new xref, updating pointers");
+    println!("  repair <output.ged>         Fix common corruption, i.e. level jumps or a missing");
+    println!("                              TRLR, and write the repaired file");
+    println!("  report (--ancestors <xref> | --descendants <xref>) [--generations <n>]");
+    println!("         [--format markdown|html]");
+    println!("                              Write a narrative report with source footnotes");
+    println!("  search [--name <name>] [--birth-year <year>] [--death-year <year>]");
+    println!("         [--place <place>] [--event <tag>]");
+    println!("                              Find individuals, best match first");
+    println!("  search --query <query>      Find individuals matching a query, i.e.");
+    println!("                              \"surname = 'Smith' AND birth.year < 1850\"");
+    println!("  site <dir>                  Write a static website of the tree (needs the site");
+    println!("                              feature)");
+    println!("  stats                       Names, lifespans, and births and marriages by decade");
+    println!("  tasks [--markers <TODO,check,...>]");
+    println!("                              List the research tasks marked in notes");
+    println!("  validate [--format text|json] [--fail-on info|warning|error] [--config <rules>]");
+    println!("        [--strict-spec] [--limit <n>]");
+    println!("                              Check the file, exiting with 1 if it fails");
+    std::process::exit(if msg.is_empty() { 0 } else { 2 });
+}
+
+fn fail(msg: &str) -> ! {
+    eprintln!("{msg}");
+    std::process::exit(1);
 }
 
 #[cfg(test)]
@@ -42,6 +1017,35 @@ mod tests {
 
         // Test the header
         // println!("Gedcom: {:?}", gedcom.header);
+        assert!(gedcom.header.destination == Some("ANSTFILE".to_string()));
+        assert!(gedcom.header.filename == Some("TGC55C.ged".to_string()));
+        assert!(gedcom.header.language == Some("English".to_string()));
+
+        let date = gedcom.header.date.as_ref().unwrap();
+        assert!(date.date == Some("1 JAN 1998".to_string()));
+        assert!(date.time == Some("13:57:24.80".to_string()));
+
+        let file_date = gedcom.file_date().unwrap();
+        assert!(file_date.to_string() == "1 JAN 1998 13:57:24.800");
+
+        // The records were edited after the file's date
+        let i1 = gedcom
+            .individuals
+            .iter()
+            .find(|i| i.xref == Some("@I1@".to_string()))
+            .unwrap();
+        let changed = i1.change_date.as_ref().unwrap().timestamp().unwrap();
+        assert!(changed.to_string() == "12 FEB 2001 19:16:42");
+        let findings = gedcom_rs::validate::validate_gedcom(&gedcom);
+        assert!(findings.iter().any(
+            |f| f.rule == gedcom_rs::validate::Rule::ChangedAfterFileDate
+                && f.xref == Some("@I1@".to_string())
+                && f.line == Some(182)
+        ));
+
+        let submitter = gedcom.header.submitter.as_ref().unwrap();
+        assert!(submitter.xref == Some("@U1@".to_string()));
+
         // Test the copyright header
         assert!(gedcom.header.copyright.is_some());
         let copyright = gedcom.header.copyright.unwrap();
@@ -56,6 +1060,565 @@ mod tests {
         assert!(note.ends_with("GEDCOM 5.5 specs on the Internet at <http://homepages.rootsweb.com/~pmcbride/gedcom/55gctoc.htm>."));
     }
 
+    #[test]
+    fn test_complete_preserve_unknown() {
+        use gedcom_rs::config::GedcomConfig;
+        use gedcom_rs::parse::parse_gedcom_with_config;
+
+        // By default, we don't keep the original text
+        let gedcom = parse_gedcom("./data/complete.ged");
+        assert!(gedcom.raw_records.is_empty());
+
+        let config = GedcomConfig::new().preserve_unknown();
+        let gedcom = parse_gedcom_with_config("./data/complete.ged", &config).unwrap();
+
+        // The records, put back together, are the original file
+        let original = std::fs::read_to_string("./data/complete.ged").unwrap();
+        let text: String = gedcom.raw_records.iter().map(|r| r.text()).collect();
+        assert!(text == original);
+
+        let head = gedcom.raw_records.first().unwrap();
+        assert!(head.tag == "HEAD");
+        assert!(head.unknown_lines() == ["1 _HME @I1@\r\n"]);
+
+        let trailer = gedcom.raw_records.last().unwrap();
+        assert!(trailer.tag == "TRLR");
+        assert!(gedcom
+            .raw_records
+            .iter()
+            .any(|r| r.tag == "REPO" && r.xref == Some("@R1@".to_string())));
+    }
+
+    #[test]
+    fn test_complete_positions() {
+        let gedcom = parse_gedcom("./data/complete.ged");
+
+        let position = gedcom.record_positions.get("@I1@").unwrap();
+        assert!(position.line == 182);
+        // The file starts with a byte order mark
+        assert!(position.offset == 8207);
+        assert!(gedcom.record_positions.get("@F1@").unwrap().line == 961);
+
+        // Every individual has a name
+        assert!(gedcom.warnings.is_empty());
+
+        // and every line is well-formed
+        let config = gedcom_rs::config::GedcomConfig::new().strict();
+        assert!(gedcom_rs::parse::parse_gedcom_with_config("./data/complete.ged", &config).is_ok());
+    }
+
+    #[test]
+    fn test_complete_search() {
+        let gedcom = parse_gedcom("./data/complete.ged");
+
+        // An exact name ranks above surname matches
+        let results = gedcom.find_individuals_by_name_scored("Joseph Tag Torture");
+        assert!(results[0].item.xref.as_deref() == Some("@I1@"));
+        assert!(results[0].score == 1.0);
+
+        let torture = gedcom.find_individuals_by_name("torture");
+        assert!(torture.len() >= 4);
+        assert!(torture.iter().all(|i| i.names.iter().any(|n| n
+            .name
+            .value
+            .as_deref()
+            .unwrap_or_default()
+            .contains("Torture"))));
+
+        assert!(gedcom.find_individual("@I1@").is_some());
+        assert!(gedcom.find_family("@F1@").is_some());
+        assert!(gedcom.find_individuals_by_name("Nobody Atall").is_empty());
+    }
+
+    #[test]
+    fn test_complete_couples() {
+        let gedcom = parse_gedcom("./data/complete.ged");
+
+        let family = gedcom.family_of("@I1@", "@I2@").unwrap();
+        assert!(family.xref == "@F1@");
+        // The order of the spouses doesn't matter
+        assert!(gedcom.families_of("@I2@", "@I1@").len() == 1);
+        assert!(gedcom.family_of("@I1@", "@I8@").unwrap().xref == "@F4@");
+        assert!(gedcom.family_of("@I1@", "@I1@").is_none());
+
+        let couples: Vec<_> = gedcom.couples().collect();
+        assert!(couples.len() == 4);
+        let (husband, wife, family) = couples[0];
+        assert!(husband.xref.as_deref() == Some("@I1@"));
+        assert!(wife.xref.as_deref() == Some("@I2@"));
+        assert!(family.xref == "@F1@");
+    }
+
+    #[test]
+    fn test_complete_relationship() {
+        let gedcom = parse_gedcom("./data/complete.ged");
+
+        let report = relationship(&gedcom, "@I1@", "@I2@").unwrap();
+        assert!(report
+            .starts_with("Mary First Jones (@I2@) is the wife of Joseph Tag Torture (@I1@)\n"));
+        assert!(report.ends_with("Path: Joseph Tag Torture (@I1@) -> Mary First Jones (@I2@)\n"));
+
+        assert!(relationship(&gedcom, "@I1@", "@I999@").is_err());
+    }
+
+    #[test]
+    fn test_complete_report() {
+        let gedcom = parse_gedcom("./data/complete.ged");
+        let args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+
+        let text = report(&gedcom, &args(&["--descendants", "@I1@"])).unwrap();
+        assert!(text.starts_with("# Descendants of Joseph Tag Torture\n"));
+        assert!(text.contains("**1. Joseph Tag Torture**"));
+        let text = report(&gedcom, &args(&["--ancestors", "@I1@", "--format", "html"])).unwrap();
+        assert!(text.contains("<h1>Ancestors of Joseph Tag Torture</h1>"));
+
+        assert!(report(&gedcom, &args(&["--ancestors", "@I999@"])).is_err());
+        assert!(report(&gedcom, &args(&["--format", "pdf"])).is_err());
+        assert!(report(&gedcom, &args(&[])).is_err());
+    }
+
+    #[test]
+    fn test_complete_chart() {
+        let gedcom = parse_gedcom("./data/complete.ged");
+        let args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+
+        let report = chart(
+            &gedcom,
+            &args(&["--pedigree", "@I1@", "--generations", "1"]),
+        )
+        .unwrap();
+        assert!(report.lines().count() == 2);
+        assert!(report.starts_with("Joseph Tag Torture (@I1@)"));
+
+        let report = chart(&gedcom, &args(&["--descendants", "@I1@"])).unwrap();
+        assert!(report.starts_with("1 Joseph Tag Torture (@I1@)"));
+        assert!(report.contains("\n  + Mary First Jones (@I2@)"));
+
+        assert!(chart(&gedcom, &args(&["--pedigree", "@I999@"])).is_err());
+        assert!(chart(
+            &gedcom,
+            &args(&["--pedigree", "@I1@", "--descendants", "@I1@"])
+        )
+        .is_err());
+        assert!(chart(&gedcom, &args(&["--generations", "many"])).is_err());
+        assert!(chart(
+            &gedcom,
+            &args(&["--descendants", "@I1@", "--numbering", "henry"])
+        )
+        .unwrap()
+        .starts_with("1 Joseph Tag Torture (@I1@)"));
+        assert!(chart(
+            &gedcom,
+            &args(&["--descendants", "@I1@", "--numbering", "dewey"])
+        )
+        .is_err());
+        assert!(
+            chart(&gedcom, &args(&["--pedigree", "@I1@", "--format", "svg"]))
+                .unwrap()
+                .contains("<svg xmlns")
+        );
+        assert!(chart(&gedcom, &args(&["--pedigree", "@I1@", "--format", "png"])).is_err());
+    }
+
+    #[test]
+    fn test_complete_home() {
+        let gedcom = parse_gedcom("./data/complete.ged");
+        assert!(gedcom.home_person().unwrap().xref.as_deref() == Some("@I1@"));
+
+        let output = env::temp_dir().join("gedcom-rs-test-home.ged");
+        let output = output.to_str().unwrap();
+        assert!(home("./data/complete.ged", "@I999@", output).is_err());
+        assert!(home("./data/does-not-exist.ged", "@I2@", output).is_err());
+        home("./data/complete.ged", "@I2@", output).unwrap();
+
+        let written = parse_gedcom(output);
+        let text = std::fs::read_to_string(output).unwrap();
+        std::fs::remove_file(output).unwrap();
+        assert!(written.home_person().unwrap().xref.as_deref() == Some("@I2@"));
+        assert!(gedcom.compare_summary(&written).is_same());
+        // Only the home person has changed
+        let original = std::fs::read_to_string("./data/complete.ged").unwrap();
+        assert!(text == original.replace("1 _HME @I1@", "1 _HME @I2@"));
+    }
+
+    #[test]
+    fn test_complete_renumber() {
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+        let output = env::temp_dir().join("gedcom-rs-test-renumber.ged");
+        let output = output.to_str().unwrap();
+        let gedcom = parse_gedcom("./data/complete.ged");
+        let complete = || {
+            let config = GedcomConfig::new().preserve_unknown();
+            parse_gedcom_with_config("./data/complete.ged", &config).unwrap()
+        };
+
+        assert!(renumber(complete(), &args(&[])).is_err());
+        assert!(renumber(complete(), &args(&[output, "--scheme", "random"])).is_err());
+        let report = renumber(complete(), &args(&[output, "--scheme", "uuid"])).unwrap();
+        assert!(report.starts_with("Renamed "));
+
+        let written = parse_gedcom(output);
+        std::fs::remove_file(output).unwrap();
+        assert!(written.individuals[0].xref.as_deref().unwrap().len() == 35);
+        assert!(gedcom.compare_summary(&written).is_same());
+        // The records pointed to are written, under their new xrefs
+        let note = written.repositories[0].notes[0].note.as_deref().unwrap();
+        assert!(note.len() == 35 && written.find_note(note).is_some());
+    }
+
+    #[test]
+    fn test_complete_media() {
+        let gedcom = parse_gedcom("./data/complete.ged");
+        let report = media(&gedcom, "./data/complete.ged", &[]).unwrap();
+        let lines: Vec<&str> = report.lines().collect();
+        assert!(lines.len() == 17);
+        let photo: Vec<&str> = lines[1].split_whitespace().collect();
+        assert!(photo[..2] == ["photo.jpeg", "JPEG"]);
+        assert!(lines[1].ends_with("missing"));
+        assert!(report.contains("@I1@ BIRT"));
+
+        let target = env::temp_dir().join("gedcom-rs-test-media-copy");
+        let target = target.to_str().unwrap().to_string();
+        let report = media(
+            &gedcom,
+            "./data/complete.ged",
+            &["--copy".to_string(), target.clone()],
+        );
+        std::fs::remove_dir_all(&target).unwrap();
+        assert!(report.unwrap().contains("Copied 0 file(s)"));
+        assert!(media(&gedcom, "./data/complete.ged", &["--move".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_complete_anonymize() {
+        let args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+        let output = env::temp_dir().join("gedcom-rs-test-anonymize.ged");
+        let output = output.to_str().unwrap();
+        let complete = || parse_gedcom("./data/complete.ged");
+        assert!(anonymize(complete(), output, &args(&["--sample"])).is_err());
+        anonymize(
+            complete(),
+            output,
+            &args(&["--sample", "2", "--seed", "42"]),
+        )
+        .unwrap();
+
+        let written = parse_gedcom(output);
+        let text = std::fs::read_to_string(output).unwrap();
+        std::fs::remove_file(output).unwrap();
+        assert!(written.individuals.len() >= 2);
+        assert!(written.individuals.len() < complete().individuals.len());
+        assert!(!text.contains("Torture") && !text.contains("Smith"));
+        let name = written.individuals[0].names[0].name.value.clone();
+        assert!(name.unwrap().starts_with("Given1"));
+    }
+
+    #[test]
+    fn test_complete_export() {
+        let gedcom = parse_gedcom("./data/complete.ged");
+        let args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+        let dir = env::temp_dir().join("gedcom-rs-test-export");
+        let dir = dir.to_str().unwrap();
+        let written = export(
+            &gedcom,
+            &args(&["--tsv", dir, "--columns", "xref,name,father,mother"]),
+        )
+        .unwrap();
+        let individuals = std::fs::read_to_string(&written[0]).unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+        assert!(written[1].ends_with("families.tsv"));
+        assert!(individuals.lines().count() == gedcom.individuals.len() + 1);
+        assert!(individuals.starts_with("xref\tname\tfather\tmother\n"));
+
+        assert!(export(&gedcom, &[]).is_err());
+        assert!(export(&gedcom, &args(&["--csv", dir, "--columns", "age"])).is_err());
+    }
+
+    #[test]
+    fn test_complete_tasks() {
+        let gedcom = parse_gedcom("./data/complete.ged");
+        let args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+        let report = tasks(&gedcom, &args(&["--markers", "Torture"])).unwrap();
+        assert!(report.lines().next().unwrap().starts_with("Record"));
+        assert!(report.lines().count() > 1);
+        assert!(tasks(&gedcom, &args(&["--marker"])).is_err());
+    }
+
+    #[test]
+    fn test_complete_graph() {
+        let gedcom = parse_gedcom("./data/complete.ged");
+        let args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+        let xml = graph(&gedcom, &args(&["--format", "gexf"])).unwrap();
+        assert!(xml.contains("<gexf "));
+        assert!(xml.contains("<attvalue for=\"generation\" value=\"0\"/>"));
+        assert!(graph(&gedcom, &[]).unwrap().contains("<graphml "));
+        assert!(graph(&gedcom, &args(&["--root", "@I999@"])).is_err());
+        assert!(graph(&gedcom, &args(&["--format", "dot"])).is_err());
+    }
+
+    #[test]
+    fn test_complete_convert() {
+        let gedcom = parse_gedcom("./data/complete.ged");
+        let output = env::temp_dir().join("gedcom-rs-test-convert.ged");
+        let output = output.to_str().unwrap();
+        assert!(convert(&gedcom, "6.0", output).is_err());
+        let warnings = convert(&gedcom, "7.0", output).unwrap();
+
+        let written = parse_gedcom(output);
+        std::fs::remove_file(output).unwrap();
+        assert!(written.header.version() == Some(GedcomVersion::V70));
+        assert!(warnings.iter().any(|w| w.starts_with("HEAD: FILE")));
+        assert!(written.individuals.len() == gedcom.individuals.len());
+    }
+
+    #[test]
+    fn test_summary() {
+        let gedcom = parse_gedcom("./data/complete.ged");
+        let report = summary(&gedcom);
+        assert!(report.starts_with("Individuals: 12\nFamilies: 6\n"));
+        assert!(!report.contains("Warnings"));
+
+        let mut text = String::from("0 HEAD\n0 @I1@ INDI\n1 NAME Joe /Bloggs/\n");
+        for _ in 0..7 {
+            text += "1 FOOO bar\n";
+        }
+        text += "0 TRLR\n";
+        let gedcom = gedcom_rs::parse::parse_gedcom_from_str(&text).unwrap();
+        let report = summary(&gedcom);
+        assert!(report.contains("\nWarnings: 7\nparse (7)\n  line 4: FOOO: "));
+        assert!(report.ends_with("\n  and 2 more like this\n"));
+    }
+
+    #[test]
+    fn test_progress_bar() {
+        assert!(progress_bar(12, 350, Some(1000)) == "[#######             ]  35% 12 records");
+        assert!(progress_bar(40, 1000, Some(1000)) == "[####################] 100% 40 records");
+        assert!(progress_bar(40, 4096, None) == "4 KB, 40 records");
+        assert!(progress_bar(0, 0, Some(0)) == "0 KB, 0 records");
+    }
+
+    #[test]
+    fn test_complete_repair() {
+        let output = env::temp_dir().join("gedcom-rs-test-repair.ged");
+        let output = output.to_str().unwrap();
+        let report = repair("./data/complete.ged", output).unwrap();
+        let written = parse_gedcom(output);
+        std::fs::remove_file(output).unwrap();
+        assert!(report.ends_with(&format!("wrote {}\n", output)));
+        assert!(written.individuals.len() == parse_gedcom("./data/complete.ged").individuals.len());
+        assert!(repair("./data/missing.ged", output).is_err());
+    }
+
+    #[test]
+    fn test_complete_fix() {
+        let output = env::temp_dir().join("gedcom-rs-test-fix.ged");
+        let output = output.to_str().unwrap();
+        let report = fix("./data/complete.ged", output).unwrap();
+        let mut written = parse_gedcom(output);
+        std::fs::remove_file(output).unwrap();
+        assert!(report.ends_with(&format!("wrote {}\n", output)));
+        assert!(written.individuals.len() == parse_gedcom("./data/complete.ged").individuals.len());
+        assert!(written.repair(&RepairOptions::default()).is_empty());
+        assert!(fix("./data/missing.ged", output).is_err());
+    }
+
+    #[test]
+    fn test_complete_validate_command() {
+        let gedcom = parse_gedcom("./data/complete.ged");
+        let args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+
+        let (report, passed) = validate(&gedcom, "./data/complete.ged", &[]).unwrap();
+        assert!(passed);
+        assert!(report.starts_with("changed-after-file-date (49)\n  warning @I1@ (line 182): "));
+        assert!(report.ends_with("0 error(s), 49 warning(s)\n"));
+
+        let (report, passed) = validate(
+            &gedcom,
+            "./data/complete.ged",
+            &args(&["--format", "json", "--fail-on", "warning"]),
+        )
+        .unwrap();
+        assert!(!passed);
+        assert!(report.starts_with("{\"passed\": false, \"errors\": 0, \"warnings\": 49,"));
+        assert!(report.contains("{\"severity\": \"warning\", \"xref\": \"@F6@\", \"line\": 1229,"));
+
+        let (_, passed) = validate(
+            &parse_gedcom("./data/missing.ged"),
+            "./data/missing.ged",
+            &[],
+        )
+        .unwrap();
+        assert!(!passed);
+
+        #[cfg(feature = "rules-config")]
+        {
+            let rules = env::temp_dir().join("gedcom-rs-test-rules.toml");
+            std::fs::write(
+                &rules,
+                "[rules.changed-after-file-date]\nseverity = \"error\"\n",
+            )
+            .unwrap();
+            let (report, passed) = validate(
+                &gedcom,
+                "./data/complete.ged",
+                &args(&["--config", rules.to_str().unwrap()]),
+            )
+            .unwrap();
+            std::fs::remove_file(&rules).unwrap();
+            assert!(!passed);
+            assert!(report.ends_with("49 error(s), 0 warning(s)\n"));
+        }
+        #[cfg(not(feature = "rules-config"))]
+        assert!(validate(
+            &gedcom,
+            "./data/complete.ged",
+            &args(&["--config", "rules.toml"])
+        )
+        .is_err());
+
+        assert!(validate(&gedcom, "./data/complete.ged", &args(&["--format", "xml"])).is_err());
+        assert!(validate(&gedcom, "./data/complete.ged", &args(&["--fail-on"])).is_err());
+        assert!(validate(&gedcom, "./data/complete.ged", &args(&["--limit", "x"])).is_err());
+
+        let (report, _) =
+            validate(&gedcom, "./data/complete.ged", &args(&["--limit", "2"])).unwrap();
+        assert!(report.starts_with("changed-after-file-date (49)\n  warning @I1@ "));
+        assert!(report.contains("\n  warning @I2@ (line 808): "));
+        assert!(!report.contains("@I3@"));
+        assert!(report.ends_with("\n  and 47 more like this\n0 error(s), 49 warning(s)\n"));
+
+        let (report, passed) =
+            validate(&gedcom, "./data/complete.ged", &args(&["--strict-spec"])).unwrap();
+        assert!(passed && !report.contains("spec ("));
+        let broken = env::temp_dir().join("gedcom-rs-test-spec.ged");
+        std::fs::write(
+            &broken,
+            "0 HEAD\n1 CHAR UTF-8\n0 @I1@ INDI\n2 NAME Joe\n0 TRLR\n",
+        )
+        .unwrap();
+        let broken = broken.to_str().unwrap();
+        let (report, passed) = validate(
+            &parse_gedcom(broken),
+            broken,
+            &args(&["--strict-spec", "--format", "json"]),
+        )
+        .unwrap();
+        std::fs::remove_file(broken).unwrap();
+        assert!(!passed);
+        assert!(report
+            .contains("\"message\": \"level jumps from 0 to 2 (GEDCOM 5.5.1, Chapter 1, level)\""));
+        assert!(json_string("a \"b\"\n") == "\"a \\\"b\\\"\\n\"");
+    }
+
+    #[test]
+    fn test_complete_search_command() {
+        let gedcom = parse_gedcom("./data/complete.ged");
+        let args = |a: &[&str]| a.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+
+        let report = search(&gedcom, &args(&["--name", "Joseph Tag Torture"])).unwrap();
+        let lines: Vec<&str> = report.lines().collect();
+        assert!(lines[0].starts_with("XREF  NAME"));
+        assert!(lines[1].starts_with("@I1@  Joseph Tag Torture"));
+
+        let report = search(&gedcom, &args(&["--name", "torture", "--event", "BIRT"])).unwrap();
+        assert!(report.lines().count() > 2);
+
+        assert!(search(&gedcom, &args(&[])).is_err());
+        assert!(search(&gedcom, &args(&["--name"])).is_err());
+        assert!(search(&gedcom, &args(&["--birth-year", "soon"])).is_err());
+        assert!(search(&gedcom, &args(&["--colour", "blue"])).is_err());
+
+        let report = search(
+            &gedcom,
+            &args(&["--query", "surname = 'Torture' AND sex = 'M'"]),
+        );
+        assert!(report.unwrap().lines().nth(1).unwrap().starts_with("@I1@"));
+        assert!(search(&gedcom, &args(&["--query", "surname ="])).is_err());
+    }
+
+    #[test]
+    fn test_complete_event_contacts() {
+        let gedcom = parse_gedcom("./data/complete.ged");
+
+        let individual = gedcom
+            .individuals
+            .iter()
+            .find(|i| i.xref == Some("@I1@".to_string()))
+            .unwrap();
+        let residence = individual.residences.first().unwrap();
+        let detail = residence.detail.as_ref().unwrap().detail.as_ref().unwrap();
+
+        assert!(detail.phone == ["+1-800-555-5555"]);
+        assert!(detail.address.as_ref().unwrap().phone == ["+1-800-555-5555"]);
+        assert!(detail.cause == Some("Needed housing".to_string()));
+    }
+
+    #[test]
+    fn test_complete_families() {
+        let gedcom = parse_gedcom("./data/complete.ged");
+
+        assert!(gedcom.families.len() == 6);
+
+        let family = gedcom.families.first().unwrap();
+        assert!(family.xref == "@F1@");
+        assert!(
+            family.husband.as_ref().unwrap().xref.as_ref().unwrap().xref
+                == Some("@I1@".to_string())
+        );
+        assert!(
+            family.wife.as_ref().unwrap().xref.as_ref().unwrap().xref == Some("@I2@".to_string())
+        );
+        assert!(family.children == vec!["@I3@".to_string(), "@I4@".to_string()]);
+        assert!(family.number_of_children == Some(42));
+
+        // Marriage
+        assert!(family.marriage.len() == 1);
+        let marriage = family.marriage.first().unwrap();
+        assert!(marriage.date() == Some("31 DEC 1997"));
+        assert!(marriage.place().unwrap().name == Some("The place".to_string()));
+        assert!(marriage.husband_age() == Some("42y"));
+        assert!(marriage.wife_age() == Some("42y 6m"));
+
+        let detail = marriage.detail.as_ref().unwrap();
+        assert!(detail.r#type == Some("Man and Wife".to_string()));
+        assert!(detail.cause == Some("Love".to_string()));
+        assert!(detail.agency == Some("Catholic Church".to_string()));
+        assert!(detail.address.as_ref().unwrap().addr1 == Some("A Church".to_string()));
+        assert!(detail.media.len() == 1);
+        assert!(detail.sources.len() == 1);
+        assert!(detail.sources[0].xref == Some("@S1@".to_string()));
+        assert!(detail
+            .note
+            .as_ref()
+            .unwrap()
+            .ends_with("unit of a man and a woman as husband and wife)."));
+
+        // The remaining family events
+        assert!(family.engagement.len() == 1);
+        assert!(family.engagement[0].wife_age() == Some("0y"));
+        assert!(family.marriage_banns.len() == 1);
+        assert!(family.marriage_contract.len() == 1);
+        assert!(family.marriage_contract[0].wife_age() == Some("> 42y 6m"));
+        assert!(family.marriage_license.len() == 1);
+        assert!(family.marriage_settlement.len() == 1);
+        assert!(family.divorce.len() == 1);
+        assert!(family.divorce[0].husband_age() == Some("42y 3d"));
+        assert!(family.divorce_filed.len() == 1);
+        assert!(family.annulment.len() == 1);
+        assert!(family.annulment[0].date() == Some("31 DEC 1997"));
+        assert!(family.census.len() == 1);
+        assert!(family.events.len() == 1);
+
+        // Record-level structures
+        assert!(family.sources.len() == 1);
+        assert!(family.media.len() == 1);
+        assert!(family.notes.len() == 1);
+
+        let family = gedcom.families.last().unwrap();
+        assert!(family.xref == "@F6@");
+        assert!(family.children == vec!["@I5@".to_string()]);
+    }
+
     // #[test]
     // /// Tests a possible bug in Ancestry's format, if a line break is embedded within the content of a note
     // /// As far as I can tell, it's a \n embedded into the note, at least, from a hex dump of that content.
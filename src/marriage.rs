@@ -0,0 +1,223 @@
+use std::str::FromStr;
+
+use crate::types::{EventDetail, Family, Gedcom, GedcomDate, Individual, SimpleDate};
+
+// An individual's marriages come from the families they're a spouse in, each
+// with its MARR, and ended by its DIV or ANUL, or else by the first death of
+// the two of them. A family without a MARR still counts: most files record
+// a couple as a family with nothing but children.
+
+/// One of an individual's marriages. See `Gedcom::marital_history`.
+#[derive(Debug)]
+pub struct Marriage<'a> {
+    pub family: &'a Family,
+    /// The other spouse, if the family has one in the file
+    pub spouse: Option<&'a Individual>,
+    /// The date of the (first) MARR, if it has one we can read
+    pub date: Option<GedcomDate>,
+    pub place: Option<&'a str>,
+    pub end: MarriageEnd<'a>,
+}
+
+/// How a marriage ended, as far as the file says
+#[derive(Debug)]
+pub enum MarriageEnd<'a> {
+    /// A DIV, dated if we can read it
+    Divorce(Option<GedcomDate>),
+    /// An ANUL, dated if we can read it
+    Annulment(Option<GedcomDate>),
+    /// The death of one of them, the first to die if both have
+    Death {
+        individual: &'a Individual,
+        date: Option<GedcomDate>,
+    },
+    /// Neither divorced nor dead
+    Ongoing,
+}
+
+impl MarriageEnd<'_> {
+    /// When the marriage ended, if it did and the date is known
+    pub fn date(&self) -> Option<&GedcomDate> {
+        match self {
+            MarriageEnd::Divorce(date) | MarriageEnd::Annulment(date) => date.as_ref(),
+            MarriageEnd::Death { date, .. } => date.as_ref(),
+            MarriageEnd::Ongoing => None,
+        }
+    }
+}
+
+impl Marriage<'_> {
+    /// Whether they were certainly married on `date`, which may be partial,
+    /// i.e. "1850": the marriage is dated no later, and didn't certainly
+    /// end by then. An undated marriage never counts, since it could have
+    /// been any time.
+    pub fn was_married_at(&self, date: &SimpleDate) -> bool {
+        let beginning = SimpleDate {
+            year: i32::MIN,
+            month: None,
+            day: None,
+        };
+        let started = self
+            .date
+            .as_ref()
+            .is_some_and(|d| d.within(&beginning, date));
+        let ended = match &self.end {
+            MarriageEnd::Ongoing => false,
+            end => match end.date() {
+                Some(end) => end.within(&beginning, date),
+                // Ended, but we don't know when
+                None => true,
+            },
+        };
+        started && !ended
+    }
+}
+
+/// Every marriage of `individual`, in date order. Undated marriages come
+/// last, in the order of their FAMS links.
+pub fn marital_history<'a>(gedcom: &'a Gedcom, individual: &'a Individual) -> Vec<Marriage<'a>> {
+    let mut families = gedcom.spouse_families(individual);
+    // spouse_families is in file order, but the FAMS links are the
+    // individual's own order
+    families.sort_by_key(|f| {
+        individual
+            .fams
+            .iter()
+            .position(|s| s.xref == f.xref)
+            .unwrap_or(usize::MAX)
+    });
+
+    let mut marriages: Vec<Marriage> = families
+        .into_iter()
+        .map(|family| {
+            let spouse = family
+                .husband_xref()
+                .into_iter()
+                .chain(family.wife_xref())
+                .filter(|xref| Some(*xref) != individual.xref.as_deref())
+                .find_map(|xref| gedcom.find_individual(xref));
+            let marriage = family.marriage.iter().find_map(|m| m.detail.as_ref());
+            Marriage {
+                family,
+                spouse,
+                date: marriage.and_then(date),
+                place: marriage.and_then(|m| m.place.as_ref()?.name.as_deref()),
+                end: end(family, individual, spouse),
+            }
+        })
+        .collect();
+
+    // Sorting is stable, so undated marriages keep their order
+    marriages.sort_by(|a, b| (a.date.is_none(), &a.date).cmp(&(b.date.is_none(), &b.date)));
+    marriages
+}
+
+/// Whether `individual` was certainly married on `date`. See
+/// `Marriage::was_married_at`.
+pub fn was_married_at(gedcom: &Gedcom, individual: &Individual, date: &SimpleDate) -> bool {
+    marital_history(gedcom, individual)
+        .iter()
+        .any(|m| m.was_married_at(date))
+}
+
+fn end<'a>(
+    family: &'a Family,
+    individual: &'a Individual,
+    spouse: Option<&'a Individual>,
+) -> MarriageEnd<'a> {
+    if let Some(divorce) = family.divorce.first() {
+        return MarriageEnd::Divorce(divorce.detail.as_ref().and_then(date));
+    }
+    if let Some(annulment) = family.annulment.first() {
+        return MarriageEnd::Annulment(annulment.detail.as_ref().and_then(date));
+    }
+
+    let deaths = std::iter::once(individual)
+        .chain(spouse)
+        .filter(|i| !i.death.is_empty())
+        .map(|i| (i, i.death_date().and_then(|d| GedcomDate::from_str(d).ok())));
+    // The first dated death, or an undated one if neither is dated
+    match deaths.min_by(|(_, a), (_, b)| (a.is_none(), a).cmp(&(b.is_none(), b))) {
+        Some((individual, date)) => MarriageEnd::Death { individual, date },
+        None => MarriageEnd::Ongoing,
+    }
+}
+
+fn date(detail: &EventDetail) -> Option<GedcomDate> {
+    GedcomDate::from_str(detail.date.as_deref()?).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_gedcom_from_str;
+
+    #[test]
+    fn history() {
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @I1@ INDI
+1 NAME John /Smith/
+1 FAMS @F2@
+1 FAMS @F1@
+1 FAMS @F3@
+0 @I2@ INDI
+1 NAME Mary /Jones/
+1 FAMS @F1@
+1 DEAT
+2 DATE 3 MAR 1860
+0 @I3@ INDI
+1 NAME Ann /Brown/
+1 FAMS @F2@
+0 @I4@ INDI
+1 NAME Jane /Green/
+1 FAMS @F3@
+0 @F1@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 MARR
+2 DATE 12 JUN 1850
+2 PLAC Leeds, England
+0 @F2@ FAM
+1 HUSB @I1@
+1 WIFE @I3@
+1 MARR
+2 DATE 1862
+1 DIV
+2 DATE 1870
+0 @F3@ FAM
+1 HUSB @I1@
+1 WIFE @I4@
+0 TRLR
+",
+        )
+        .unwrap();
+        let john = gedcom.find_individual("@I1@").unwrap();
+
+        let history = gedcom.marital_history(john);
+        let spouses: Vec<Option<&str>> = history
+            .iter()
+            .map(|m| m.spouse.and_then(|s| s.xref.as_deref()))
+            .collect();
+        assert!(spouses == [Some("@I2@"), Some("@I3@"), Some("@I4@")]);
+
+        assert!(history[0].place == Some("Leeds, England"));
+        assert!(history[0].date.as_ref().and_then(|d| d.year()) == Some(1850));
+        assert!(matches!(
+            history[0].end,
+            MarriageEnd::Death { individual, date: Some(ref date) }
+                if individual.xref.as_deref() == Some("@I2@") && date.year() == Some(1860)
+        ));
+        assert!(history[1].end.date().and_then(|d| d.year()) == Some(1870));
+        assert!(matches!(history[1].end, MarriageEnd::Divorce(_)));
+        assert!(history[2].date.is_none() && matches!(history[2].end, MarriageEnd::Ongoing));
+
+        let at = |date: &str| gedcom.was_married_at(john, &date.parse().unwrap());
+        assert!(at("1855"));
+        assert!(!at("1849"));
+        assert!(!at("1861"));
+        assert!(at("JUN 1865"));
+        // The third marriage is undated
+        assert!(!at("1900"));
+    }
+}
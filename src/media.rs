@@ -0,0 +1,418 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::error::GedcomError;
+use crate::types::{EventDetail, Gedcom, Object};
+
+/// A GEDZIP archive the Gedcom was read from, which holds its media
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Archive {
+    /// Where the archive is on disk
+    pub path: PathBuf,
+    /// The files in it besides the GEDCOM, in the order they're stored
+    pub entries: Vec<String>,
+}
+
+impl Archive {
+    /// The entry for a FILE path, which in a GEDZIP is a relative URL, so
+    /// may be percent-encoded, i.e. "photos/joe%20smith.jpg"
+    pub fn entry(&self, path: &str) -> Option<&str> {
+        let decoded = percent_decode(path);
+        self.entries
+            .iter()
+            .find(|e| e.as_str() == path || **e == decoded)
+            .map(|e| e.as_str())
+    }
+
+    /// Read the file at a FILE path out of the archive
+    pub fn read(&self, path: &str) -> Result<Vec<u8>, GedcomError> {
+        let entry = self.entry(path).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} isn't in {}", path, self.path.display()),
+            )
+        })?;
+        #[cfg(feature = "gedzip")]
+        return crate::gedzip::read_entry(&self.path, entry);
+        #[cfg(not(feature = "gedzip"))]
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("reading {} needs the gedzip feature", entry),
+        )
+        .into())
+    }
+}
+
+/// Decode %XX escapes, leaving anything that isn't one as it is
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        let escape = text
+            .get(i + 1..i + 3)
+            .filter(|_| bytes[i] == b'%')
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escape {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// A record that links to a file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MediaUse<'a> {
+    /// The individual, family or source
+    pub xref: &'a str,
+    /// The event the link is on, i.e. "BIRT", or None for the record itself
+    pub tag: Option<&'static str>,
+}
+
+/// A file referred to by the Gedcom, and everything that uses it. See
+/// `Gedcom::media_inventory`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MediaItem<'a> {
+    /// The path or URL, as written in the GEDCOM
+    pub path: &'a str,
+    /// The OBJE record describing the file, if there is one
+    pub object: Option<&'a str>,
+    pub format: Option<&'a str>,
+    pub media_type: Option<&'a str>,
+    pub title: Option<&'a str>,
+    /// In file order, once each
+    pub used_by: Vec<MediaUse<'a>>,
+    /// Whether the file is on disk, or in the archive. False for URLs,
+    /// which aren't checked.
+    pub exists: bool,
+    /// The GEDZIP archive the file is in, rather than on disk
+    pub archive: Option<&'a Archive>,
+}
+
+impl MediaItem<'_> {
+    /// Whether the file is on the web, rather than on disk
+    pub fn is_url(&self) -> bool {
+        let path = self.path.to_lowercase();
+        path.starts_with("http://") || path.starts_with("https://")
+    }
+
+    /// Where the file is on disk, with a relative path taken from `base`,
+    /// i.e. the directory the GEDCOM file is in. None for URLs.
+    pub fn local_path(&self, base: &Path) -> Option<PathBuf> {
+        if self.is_url() {
+            return None;
+        }
+        let path = self.path.strip_prefix("file://").unwrap_or(self.path);
+        Some(base.join(path))
+    }
+}
+
+/// Every file linked from an individual, family, source or event, or
+/// described in an OBJE record, once each, in the order first found.
+/// Relative paths are looked for in the Gedcom's GEDZIP archive, if it was
+/// read from one, and then from `base`.
+pub fn media_inventory<'a>(gedcom: &'a Gedcom, base: &Path) -> Vec<MediaItem<'a>> {
+    let mut inventory = Inventory::default();
+
+    for record in &gedcom.multimedia {
+        for file in &record.files {
+            if let Some(path) = file.path.as_deref() {
+                let item = inventory.item(path);
+                item.object = item.object.or(Some(&record.xref));
+                item.format = item.format.or(file.format.as_deref());
+                item.media_type = item.media_type.or(file.media_type.as_deref());
+                item.title = item.title.or(record.file_title(file));
+            }
+        }
+    }
+
+    for individual in &gedcom.individuals {
+        let xref = individual.xref.as_deref().unwrap_or_default();
+        inventory.links(gedcom, xref, None, &individual.media);
+        for event in individual.all_events() {
+            if let Some(detail) = event.detail {
+                inventory.event(gedcom, xref, event.event_type.tag(), detail);
+            }
+        }
+    }
+    for family in &gedcom.families {
+        inventory.links(gedcom, &family.xref, None, &family.media);
+        for source in &family.sources {
+            inventory.links(gedcom, &family.xref, None, &source.media);
+        }
+        for (tag, detail) in family.tagged_events() {
+            if let Some(detail) = detail {
+                inventory.event(gedcom, &family.xref, tag, detail);
+            }
+        }
+    }
+    for source in &gedcom.sources {
+        inventory.links(gedcom, &source.xref, None, &source.media);
+    }
+
+    for item in &mut inventory.items {
+        item.archive = gedcom
+            .archive
+            .as_ref()
+            .filter(|a| !item.is_url() && a.entry(item.path).is_some());
+        item.exists = item.archive.is_some() || item.local_path(base).is_some_and(|p| p.is_file());
+    }
+    inventory.items
+}
+
+/// Copy every file in the inventory that's on disk, or extract it from the
+/// archive, into `target`, which is created if need be, so they can be archived with the GEDCOM. Files with
+/// the same name are numbered, i.e. "photo-2.jpg". Returns where each was
+/// copied to.
+pub fn copy_media(
+    inventory: &[MediaItem],
+    base: &Path,
+    target: &Path,
+) -> Result<Vec<PathBuf>, GedcomError> {
+    fs::create_dir_all(target)?;
+    let mut copied: Vec<PathBuf> = vec![];
+    for item in inventory.iter().filter(|i| i.exists) {
+        let source = match (item.archive, item.local_path(base)) {
+            (Some(archive), _) => PathBuf::from(archive.entry(item.path).unwrap_or(item.path)),
+            (None, Some(source)) => source,
+            (None, None) => continue,
+        };
+        let name = source.file_name().unwrap_or_default();
+        let mut destination = target.join(name);
+        let mut n = 1;
+        while copied.contains(&destination) {
+            n += 1;
+            let stem = source.file_stem().unwrap_or_default().to_string_lossy();
+            destination = match source.extension() {
+                Some(extension) => {
+                    target.join(format!("{}-{}.{}", stem, n, extension.to_string_lossy()))
+                }
+                None => target.join(format!("{}-{}", stem, n)),
+            };
+        }
+        match item.archive {
+            Some(archive) => fs::write(&destination, archive.read(item.path)?)?,
+            None => {
+                fs::copy(&source, &destination)?;
+            }
+        }
+        copied.push(destination);
+    }
+    Ok(copied)
+}
+
+#[derive(Default)]
+struct Inventory<'a> {
+    items: Vec<MediaItem<'a>>,
+    /// Where each path is in `items`
+    index: HashMap<&'a str, usize>,
+}
+
+impl<'a> Inventory<'a> {
+    fn item(&mut self, path: &'a str) -> &mut MediaItem<'a> {
+        let items = &mut self.items;
+        let i = *self.index.entry(path).or_insert_with(|| {
+            items.push(MediaItem {
+                path,
+                object: None,
+                format: None,
+                media_type: None,
+                title: None,
+                used_by: vec![],
+                exists: false,
+                archive: None,
+            });
+            items.len() - 1
+        });
+        &mut self.items[i]
+    }
+
+    /// Record the files of an event's links, and of its citations' links
+    fn event(
+        &mut self,
+        gedcom: &'a Gedcom,
+        xref: &'a str,
+        tag: &'static str,
+        detail: &'a EventDetail,
+    ) {
+        self.links(gedcom, xref, Some(tag), &detail.media);
+        for source in &detail.sources {
+            self.links(gedcom, xref, Some(tag), &source.media);
+        }
+    }
+
+    /// Record the files of each link, whether in an OBJE record or given in
+    /// the link itself, as used by `xref`
+    fn links(
+        &mut self,
+        gedcom: &'a Gedcom,
+        xref: &'a str,
+        tag: Option<&'static str>,
+        links: &'a [Object],
+    ) {
+        let usage = MediaUse { xref, tag };
+        for link in links {
+            let record = link.xref.as_deref().and_then(|x| gedcom.find_multimedia(x));
+            let files = record.map_or(&link.files, |r| &r.files);
+            for file in files {
+                let path = match file.path.as_deref() {
+                    Some(path) => path,
+                    None => continue,
+                };
+                let item = self.item(path);
+                item.format = item.format.or(file.format.as_deref());
+                item.media_type = item.media_type.or(file.media_type.as_deref());
+                item.title = item
+                    .title
+                    .or(file.title.as_deref())
+                    .or(link.title.as_deref());
+                if !item.used_by.contains(&usage) {
+                    item.used_by.push(usage);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_gedcom_from_str;
+    use std::env;
+
+    fn gedcom() -> Gedcom {
+        parse_gedcom_from_str(
+            "0 HEAD
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 BIRT
+2 DATE 1900
+2 OBJE @M1@
+2 SOUR @S1@
+3 OBJE
+4 FILE register.png
+5 FORM png
+1 OBJE @M1@
+1 OBJE
+2 FILE https://example.com/joe.jpg
+2 TITL Joe online
+0 @F1@ FAM
+1 HUSB @I1@
+1 OBJE @M1@
+0 @S1@ SOUR
+1 TITL Parish register
+1 OBJE @M2@
+0 @M1@ OBJE
+1 FILE photos/joe.jpg
+2 FORM jpg
+3 TYPE photo
+2 TITL Joe in 1920
+0 @M2@ OBJE
+1 FILE register.png
+0 @M3@ OBJE
+1 FILE unused.tif
+0 TRLR
+",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn inventory() {
+        let gedcom = gedcom();
+        assert!(gedcom.multimedia.len() == 3);
+        assert!(gedcom.individuals[0].media.len() == 2);
+
+        let inventory = media_inventory(&gedcom, Path::new("/nonexistent"));
+        let paths: Vec<&str> = inventory.iter().map(|i| i.path).collect();
+        assert!(
+            paths
+                == [
+                    "photos/joe.jpg",
+                    "register.png",
+                    "unused.tif",
+                    "https://example.com/joe.jpg"
+                ]
+        );
+
+        let photo = &inventory[0];
+        assert!(photo.object == Some("@M1@"));
+        assert!(photo.format == Some("jpg") && photo.media_type == Some("photo"));
+        assert!(photo.title == Some("Joe in 1920"));
+        assert!(
+            photo.used_by
+                == [
+                    MediaUse {
+                        xref: "@I1@",
+                        tag: None
+                    },
+                    MediaUse {
+                        xref: "@I1@",
+                        tag: Some("BIRT")
+                    },
+                    MediaUse {
+                        xref: "@F1@",
+                        tag: None
+                    },
+                ]
+        );
+        assert!(!photo.exists);
+
+        // Described in a record, and in a link of its own
+        let register = &inventory[1];
+        assert!(register.object == Some("@M2@") && register.format == Some("png"));
+        let users: Vec<&str> = register.used_by.iter().map(|u| u.xref).collect();
+        assert!(users == ["@I1@", "@S1@"]);
+
+        assert!(inventory[2].used_by.is_empty());
+        let url = &inventory[3];
+        assert!(url.is_url() && url.local_path(Path::new(".")).is_none());
+        assert!(url.title == Some("Joe online"));
+    }
+
+    #[test]
+    fn archived() {
+        let mut gedcom = gedcom();
+        gedcom.archive = Some(Archive {
+            path: PathBuf::from("/nonexistent/tree.gdz"),
+            entries: vec!["photos/joe.jpg".to_string(), "joe smith.png".to_string()],
+        });
+        let archive = gedcom.archive.as_ref().unwrap();
+        assert!(archive.entry("joe%20smith.png") == Some("joe smith.png"));
+        assert!(archive.entry("100%").is_none());
+        assert!(archive.read("missing.jpg").is_err());
+
+        let inventory = media_inventory(&gedcom, Path::new("/nonexistent"));
+        assert!(inventory[0].exists && inventory[0].archive == Some(archive));
+        assert!(!inventory[1].exists && inventory[1].archive.is_none());
+    }
+
+    #[test]
+    fn copy() {
+        let gedcom = gedcom();
+        let base = env::temp_dir().join("gedcom-rs-test-media");
+        let target = base.join("archive");
+        fs::create_dir_all(base.join("photos")).unwrap();
+        fs::write(base.join("photos/joe.jpg"), "photo").unwrap();
+        fs::write(base.join("register.png"), "scan").unwrap();
+
+        let inventory = media_inventory(&gedcom, &base);
+        let found: Vec<bool> = inventory.iter().map(|i| i.exists).collect();
+        assert!(found == [true, true, false, false]);
+
+        let copied = copy_media(&inventory, &base, &target).unwrap();
+        let contents = fs::read_to_string(target.join("joe.jpg"));
+        fs::remove_dir_all(&base).unwrap();
+        assert!(copied == [target.join("joe.jpg"), target.join("register.png")]);
+        assert!(contents.unwrap() == "photo");
+    }
+}
@@ -0,0 +1,390 @@
+use crate::types::Name;
+
+// Everything that shows, sorts or compares names goes through here, so the
+// slashes around the surname, and the NPFX, SPFX, NSFX and NICK pieces, are
+// handled the same way everywhere.
+//
+// A NAME value carries the whole name as it would be written, with the
+// surname between slashes, i.e. "Lt. Joseph /von Braun/ Jr.". The pieces
+// are optional, so each is taken from its own tag where there is one, and
+// worked out from the value where there isn't.
+
+/// Surname particles, recognised at the start of a surname given without
+/// an SPFX, i.e. "von" in "/von Braun/"
+const PARTICLES: [&str; 26] = [
+    "al", "ap", "da", "dal", "de", "degli", "dei", "del", "della", "den", "der", "des", "di", "do",
+    "dos", "du", "la", "le", "les", "lo", "ten", "ter", "van", "vom", "von", "zu",
+];
+
+/// A NAME value without the slashes around the surname, and with single
+/// spaces, i.e. "Joe Bloggs" for "Joe /Bloggs/"
+pub fn strip_slashes(value: &str) -> String {
+    value
+        .replace('/', " ")
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// The part of a NAME value between slashes, i.e. "Bloggs" in "Joe /Bloggs/"
+pub fn slashed_surname(value: &str) -> Option<&str> {
+    let start = value.find('/')? + 1;
+    let end = value[start..].find('/').map_or(value.len(), |e| start + e);
+    let surname = value[start..end].trim();
+    if surname.is_empty() {
+        None
+    } else {
+        Some(surname)
+    }
+}
+
+/// The surname, from SURN, or else from between the slashes, including any
+/// particle, i.e. "von Braun"
+pub fn surname(name: &Name) -> Option<&str> {
+    name.surname
+        .as_deref()
+        .or_else(|| slashed_surname(name.value.as_deref()?))
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+}
+
+/// The given names, from GIVN, or else from before the slashes, i.e.
+/// "John Paul" for "John Paul /Jones/". A name without slashes is all
+/// given names.
+pub fn given(name: &Name) -> Option<&str> {
+    name.given
+        .as_deref()
+        .or_else(|| name.value.as_deref()?.split('/').next())
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+}
+
+/// A name broken into its pieces. Pieces with several parts, which GEDCOM
+/// separates with commas, are separated with spaces.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NamePieces {
+    /// NPFX: a title, i.e. "Lt. Cmndr."
+    pub prefix: Option<String>,
+    pub given: Option<String>,
+    /// NICK: what they were known as, i.e. "Joe"
+    pub nickname: Option<String>,
+    /// SPFX: a particle, i.e. "von", from the surname if there's no SPFX
+    pub surname_prefix: Option<String>,
+    /// The surname, without its particle
+    pub surname: Option<String>,
+    /// NSFX: i.e. "Jr.", or whatever follows the slashes
+    pub suffix: Option<String>,
+}
+
+impl NamePieces {
+    pub fn new(name: &Name) -> NamePieces {
+        let value = name.value.as_deref().unwrap_or_default();
+        let prefix = piece(name.prefix.as_deref());
+
+        // A given name worked out from the value may start with the title
+        let given = piece(given(name)).map(|given| {
+            match prefix.as_deref().and_then(|p| given.strip_prefix(p)) {
+                Some(rest) if name.given.is_none() && rest.starts_with(' ') => {
+                    rest.trim().to_string()
+                }
+                _ => given,
+            }
+        });
+
+        let mut surname_prefix = piece(name.surname_prefix.as_deref());
+        let mut surname = piece(surname(name));
+        if let Some(full) = surname.clone() {
+            match &surname_prefix {
+                Some(particle) => {
+                    if let Some(rest) = full.strip_prefix(particle.as_str()) {
+                        if rest.starts_with(' ') {
+                            surname = Some(rest.trim().to_string());
+                        }
+                    }
+                }
+                None => {
+                    let words: Vec<&str> = full.split(' ').collect();
+                    let particles = words[..words.len() - 1]
+                        .iter()
+                        .take_while(|w| PARTICLES.contains(&w.to_lowercase().as_str()))
+                        .count();
+                    if particles > 0 {
+                        surname_prefix = Some(words[..particles].join(" "));
+                        surname = Some(words[particles..].join(" "));
+                    }
+                }
+            }
+        }
+
+        let suffix = piece(name.suffix.as_deref()).or_else(|| {
+            let mut slashes = value.match_indices('/').map(|(i, _)| i);
+            slashes.next()?;
+            piece(Some(&value[slashes.next()? + 1..]))
+        });
+
+        NamePieces {
+            prefix,
+            given: given.filter(|g| !g.is_empty()),
+            nickname: piece(name.nickname.as_deref()),
+            surname_prefix,
+            surname,
+            suffix,
+        }
+    }
+}
+
+/// A piece of a name with its parts separated by single spaces, or None if
+/// it's empty
+fn piece(value: Option<&str>) -> Option<String> {
+    let value = value?
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ");
+    Some(value).filter(|v| !v.is_empty())
+}
+
+/// How to write a name out. See `given_surname` and `surname_given` for
+/// the usual forms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NameFormat {
+    /// "Bloggs, Joe" rather than "Joe Bloggs", for indexes and lists
+    pub surname_first: bool,
+    /// "Joe BLOGGS", as genealogists often write names, so the surname
+    /// stands out. The particle is left as it is.
+    pub uppercase_surname: bool,
+    /// Include the NPFX and NSFX, i.e. "Lt." and "Jr."
+    pub titles: bool,
+    /// Include the nickname, in quotes, after the given names
+    pub nickname: bool,
+}
+
+impl Default for NameFormat {
+    fn default() -> NameFormat {
+        NameFormat {
+            surname_first: false,
+            uppercase_surname: false,
+            titles: true,
+            nickname: false,
+        }
+    }
+}
+
+impl NameFormat {
+    pub fn new() -> NameFormat {
+        NameFormat::default()
+    }
+
+    pub fn surname_first(mut self, surname_first: bool) -> NameFormat {
+        self.surname_first = surname_first;
+        self
+    }
+
+    pub fn uppercase_surname(mut self, uppercase: bool) -> NameFormat {
+        self.uppercase_surname = uppercase;
+        self
+    }
+
+    pub fn titles(mut self, titles: bool) -> NameFormat {
+        self.titles = titles;
+        self
+    }
+
+    pub fn nickname(mut self, nickname: bool) -> NameFormat {
+        self.nickname = nickname;
+        self
+    }
+
+    /// Write out `name`, or an empty string if there's nothing to write
+    pub fn format(&self, name: &Name) -> String {
+        let pieces = NamePieces::new(name);
+        let title = |piece: &Option<String>| piece.clone().filter(|_| self.titles);
+
+        let surname = pieces.surname.map(|s| {
+            if self.uppercase_surname {
+                s.to_uppercase()
+            } else {
+                s
+            }
+        });
+        let surname = join(&[pieces.surname_prefix, surname]);
+        let given = join(&[
+            title(&pieces.prefix),
+            pieces.given,
+            pieces
+                .nickname
+                .filter(|_| self.nickname)
+                .map(|n| format!("\"{}\"", n)),
+        ]);
+        let suffix = title(&pieces.suffix);
+
+        match surname {
+            Some(surname) if self.surname_first => match join(&[given, suffix]) {
+                Some(rest) => format!("{}, {}", surname, rest),
+                None => surname,
+            },
+            surname => join(&[given, surname, suffix]).unwrap_or_default(),
+        }
+    }
+}
+
+/// The parts that are there, separated by spaces
+fn join(parts: &[Option<String>]) -> Option<String> {
+    let parts: Vec<&str> = parts.iter().flatten().map(|p| p.as_str()).collect();
+    Some(parts.join(" ")).filter(|p| !p.is_empty())
+}
+
+/// A name as it's usually written, i.e. "Lt. Joseph von Braun Jr."
+pub fn given_surname(name: &Name) -> String {
+    NameFormat::new().format(name)
+}
+
+/// A name surname first, as in an index, i.e. "von Braun, Lt. Joseph Jr."
+pub fn surname_given(name: &Name) -> String {
+    NameFormat::new().surname_first(true).format(name)
+}
+
+/// Where a surname with a particle sorts, i.e. "von Braun"
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Particles {
+    /// Under the rest of the surname, B, as German and Dutch indexes do
+    #[default]
+    Ignore,
+    /// Under the particle, V, as English indexes often do
+    Include,
+}
+
+/// A key to sort names by: surname, then given names, ignoring case and
+/// accents. Names without a surname sort first. Titles and nicknames are
+/// left out.
+pub fn sort_key(name: &Name, particles: Particles) -> String {
+    let pieces = NamePieces::new(name);
+    let parts = match particles {
+        Particles::Ignore => vec![pieces.surname, pieces.given, pieces.surname_prefix],
+        Particles::Include => vec![join(&[pieces.surname_prefix, pieces.surname]), pieces.given],
+    };
+    // Tabs sort before any letter, so "Smith" comes before "Smithson"
+    parts
+        .iter()
+        .map(|p| fold(p.as_deref().unwrap_or_default()))
+        .collect::<Vec<String>>()
+        .join("\t")
+        .trim_end()
+        .to_string()
+}
+
+/// Lowercase, and without accents, i.e. "é" as "e"
+fn fold(text: &str) -> String {
+    #[cfg(feature = "romanize")]
+    let text = crate::romanize::romanize(text);
+    text.to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a".to_string(),
+            'æ' => "ae".to_string(),
+            'ç' | 'ć' | 'č' => "c".to_string(),
+            'ď' | 'đ' => "d".to_string(),
+            'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => "e".to_string(),
+            'ì' | 'í' | 'î' | 'ï' | 'ī' | 'į' => "i".to_string(),
+            'ł' | 'ľ' | 'ĺ' => "l".to_string(),
+            'ñ' | 'ń' | 'ň' => "n".to_string(),
+            'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ő' => "o".to_string(),
+            'œ' => "oe".to_string(),
+            'ř' | 'ŕ' => "r".to_string(),
+            'ś' | 'š' | 'ş' => "s".to_string(),
+            'ß' => "ss".to_string(),
+            'ť' | 'ţ' => "t".to_string(),
+            'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' | 'ų' => "u".to_string(),
+            'ý' | 'ÿ' => "y".to_string(),
+            'ź' | 'ż' | 'ž' => "z".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(value: &str) -> Name {
+        Name {
+            value: Some(value.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn pieces() {
+        assert!(strip_slashes("Joe  /Bloggs/") == "Joe Bloggs");
+        assert!(slashed_surname("Joe /Bloggs/ Jr.") == Some("Bloggs"));
+        assert!(slashed_surname("Joe //").is_none());
+
+        let braun = name("Lt. Wernher /von Braun/ Jr.");
+        assert!(surname(&braun) == Some("von Braun"));
+        assert!(given(&braun) == Some("Lt. Wernher"));
+        assert!(given(&name("William Lee")) == Some("William Lee"));
+        assert!(given(&name("/Parry/")).is_none());
+
+        let pieces = NamePieces::new(&Name {
+            prefix: Some("Lt.".to_string()),
+            ..braun
+        });
+        assert!(pieces.prefix.as_deref() == Some("Lt."));
+        assert!(pieces.given.as_deref() == Some("Wernher"));
+        assert!(pieces.surname_prefix.as_deref() == Some("von"));
+        assert!(pieces.surname.as_deref() == Some("Braun"));
+        assert!(pieces.suffix.as_deref() == Some("Jr."));
+
+        // The pieces' own tags win over the value
+        let pieces = NamePieces::new(&Name {
+            value: Some("Juan /de la Cruz/".to_string()),
+            given: Some("Juan,Pablo".to_string()),
+            surname: Some("de la Cruz".to_string()),
+            surname_prefix: Some("de, la".to_string()),
+            ..Default::default()
+        });
+        assert!(pieces.given.as_deref() == Some("Juan Pablo"));
+        assert!(pieces.surname_prefix.as_deref() == Some("de la"));
+        assert!(pieces.surname.as_deref() == Some("Cruz"));
+
+        // A particle on its own is the surname
+        assert!(NamePieces::new(&name("Joe /Van/")).surname.as_deref() == Some("Van"));
+    }
+
+    #[test]
+    fn format() {
+        let joseph = Name {
+            value: Some("Joseph /Le Torture/".to_string()),
+            prefix: Some("Prof.".to_string()),
+            nickname: Some("Joe".to_string()),
+            suffix: Some("Jr.".to_string()),
+            ..Default::default()
+        };
+        assert!(given_surname(&joseph) == "Prof. Joseph Le Torture Jr.");
+        assert!(surname_given(&joseph) == "Le Torture, Prof. Joseph Jr.");
+        let format = NameFormat::new()
+            .uppercase_surname(true)
+            .titles(false)
+            .nickname(true);
+        assert!(format.format(&joseph) == "Joseph \"Joe\" Le TORTURE");
+        assert!(format.surname_first(true).format(&joseph) == "Le TORTURE, Joseph \"Joe\"");
+
+        assert!(surname_given(&name("/Parry/")) == "Parry");
+        assert!(surname_given(&name("William Lee")) == "William Lee");
+        assert!(given_surname(&Name::default()).is_empty());
+    }
+
+    #[test]
+    fn sort() {
+        let mut names = ["Wernher /von Braun/", "Ann /Vogel/", "Joe /Bloggs/", "Sam"];
+        let key = |n: &&str, p| sort_key(&name(n), p);
+        names.sort_by_key(|n| key(n, Particles::Ignore));
+        assert!(names == ["Sam", "Joe /Bloggs/", "Wernher /von Braun/", "Ann /Vogel/"]);
+        names.sort_by_key(|n| key(n, Particles::Include));
+        assert!(names == ["Sam", "Joe /Bloggs/", "Ann /Vogel/", "Wernher /von Braun/"]);
+
+        assert!(key(&"José /Núñez/", Particles::Ignore) == "nunez\tjose");
+        assert!(key(&"Sam", Particles::Include) == "\tsam");
+        assert!(key(&"Joe /Smith/", Particles::Ignore) < key(&"Al /Smithson/", Particles::Ignore));
+    }
+}
@@ -2,17 +2,21 @@
 // use super::types::Line;
 use super::types::*;
 
+use crate::config::GedcomConfig;
+use crate::error::{GedcomError, LineProblem};
+use crate::renumber::is_pointer;
+
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 
-use std::io::{self, BufRead};
-use std::path::Path;
+use std::io::{self, BufRead, Read};
 
 use winnow::prelude::*;
 
-/// This is pretty much a kludge to strip out U+FEFF, a Zero Width No-Break Space
-/// https://www.compart.com/en/unicode/U+FEFF
-///
-/// So far, I've only seen this with one GEDCOM, as the starting byte.
+// /// This is pretty much a kludge to strip out U+FEFF, a Zero Width No-Break Space
+// /// https://www.compart.com/en/unicode/U+FEFF
+// ///
+// /// So far, I've only seen this with one GEDCOM, as the starting byte.
 // pub fn zero_with_no_break_space(input: &mut &str) -> PResult<&str> {
 //     if input.starts_with('\u{FEFF}') {
 //         let parser = tag("\u{FEFF}");
@@ -25,15 +29,15 @@ use winnow::prelude::*;
 
 /// Read the next tag's value and any continuations
 pub fn get_tag_value(input: &mut &str) -> PResult<Option<String>> {
-    let mut line = Line::parse(input).unwrap();
+    let mut line = Line::parse(input)?;
 
     // Seed the value with the initial value
     let mut text: String = line.value.to_string();
 
-    line = Line::peek(input).unwrap();
+    line = Line::peek(input)?;
     while line.tag == "CONC" || line.tag == "CONT" {
         // consume
-        line = Line::parse(input).unwrap();
+        line = Line::parse(input)?;
 
         if line.tag == "CONT" {
             text += "\n";
@@ -41,13 +45,13 @@ pub fn get_tag_value(input: &mut &str) -> PResult<Option<String>> {
         text += line.value;
 
         // peek ahead
-        line = Line::peek(input).unwrap();
+        line = Line::peek(input)?;
     }
 
     Ok(Some(text))
 }
 
-/// Parse the buffer if the CONC tag is found and return the resulting string.
+// /// Parse the buffer if the CONC tag is found and return the resulting string.
 // pub fn conc(input: &mut &str) -> PResult<Option<String>> {
 //     let line = Line::parse(input).unwrap();
 
@@ -58,8 +62,8 @@ pub fn get_tag_value(input: &mut &str) -> PResult<Option<String>> {
 //     }
 // }
 
-/// Parse the buffer if the CONT tag is found and return the resulting string.
-/// TODO: Refactor this. It should handle CONT and CONC.
+// /// Parse the buffer if the CONT tag is found and return the resulting string.
+// /// TODO: Refactor this. It should handle CONT and CONC.
 // pub fn cont(input: &mut &str) -> PResult<Option<String>> {
 //     let line = Line::parse(input).unwrap();
 
@@ -71,109 +75,1035 @@ pub fn get_tag_value(input: &mut &str) -> PResult<Option<String>> {
 // }
 
 /// Parse a GEDCOM file
+///
+/// Problems are recorded in `Gedcom::warnings`, including failing to read the
+/// file at all.
 pub fn parse_gedcom(filename: &str) -> Gedcom {
-    // Initialize an empty gedcom
-    let mut gedcom = Gedcom {
-        header: Header {
-            character_set: None,
-            copyright: None,
-            date: None,
-            destination: None,
-            gedcom_version: None,
-            language: None,
-            filename: None,
-            note: None,
-            place: None,
-            source: None,
-            submitter: None,
-            submission: None,
-        },
-        individuals: vec![],
-    };
+    parse_gedcom_with_config(filename, &GedcomConfig::default()).unwrap_or_else(|err| Gedcom {
+        warnings: vec![err],
+        ..Default::default()
+    })
+}
+
+/// Parse a GEDCOM file, using the given configuration
+///
+/// Returns an error if the file can't be read or, in strict mode, at the
+/// first malformed line or structure that would be skipped. Otherwise,
+/// they're skipped and recorded in `Gedcom::warnings`.
+///
+/// With the `gedzip` feature, a .gdz or .zip file is read as a GEDZIP
+/// archive; see `gedzip::parse_gedzip`.
+pub fn parse_gedcom_with_config(
+    filename: &str,
+    config: &GedcomConfig,
+) -> Result<Gedcom, GedcomError> {
+    #[cfg(feature = "gedzip")]
+    if crate::gedzip::is_gedzip(filename) {
+        return crate::gedzip::parse_gedzip_with_config(filename, config);
+    }
+    let file = File::open(filename)?;
+    let total = file.metadata().ok().map(|m| m.len() as usize);
+    read_gedcom(file, config, total)
+}
+
+/// Parse GEDCOM data from any reader, i.e. a network stream or a file
+/// inside an archive
+pub fn parse_gedcom_from_reader<R: Read>(reader: R) -> Result<Gedcom, GedcomError> {
+    parse_gedcom_from_reader_with_config(reader, &GedcomConfig::default())
+}
+
+/// Parse GEDCOM data from any reader, using the given configuration
+pub fn parse_gedcom_from_reader_with_config<R: Read>(
+    reader: R,
+    config: &GedcomConfig,
+) -> Result<Gedcom, GedcomError> {
+    read_gedcom(reader, config, None)
+}
+
+/// Parse GEDCOM data from a reader of `total` bytes, if we know how many
+fn read_gedcom<R: Read>(
+    reader: R,
+    config: &GedcomConfig,
+    total: Option<usize>,
+) -> Result<Gedcom, GedcomError> {
+    let mut reader = io::BufReader::new(reader);
+    let mut parser = Parser::new(config);
+    parser.total = total;
+    let encoding = parser.sniff(reader.fill_buf()?);
+
+    let mut input: Vec<u8> = vec![];
+    loop {
+        input.clear();
+        if read_line(&mut reader, encoding, &mut input)? == 0 {
+            break;
+        }
+        parser.push_line(&input)?;
+    }
+
+    parser.finish()
+}
+
+/// Parse GEDCOM data from an async reader, i.e. an upload, without blocking
+/// the executor
+#[cfg(feature = "tokio")]
+pub async fn parse_gedcom_async<R>(reader: R) -> Result<Gedcom, GedcomError>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    parse_gedcom_async_with_config(reader, &GedcomConfig::default()).await
+}
+
+/// Parse GEDCOM data from an async reader, using the given configuration
+#[cfg(feature = "tokio")]
+pub async fn parse_gedcom_async_with_config<R>(
+    reader: R,
+    config: &GedcomConfig,
+) -> Result<Gedcom, GedcomError>
+where
+    R: tokio::io::AsyncRead + Unpin,
+{
+    use tokio::io::AsyncBufReadExt;
+
+    let mut reader = tokio::io::BufReader::new(reader);
+    let mut parser = Parser::new(config);
+    let encoding = parser.sniff(reader.fill_buf().await?);
+
+    let mut input: Vec<u8> = vec![];
+    loop {
+        input.clear();
+        if reader.read_until(b'\n', &mut input).await? == 0 {
+            break;
+        }
+        // See read_line
+        if let Some(newline) = utf16_newline(encoding) {
+            while !input.ends_with(&newline) {
+                if input.len() % 2 == 1 {
+                    match reader.fill_buf().await?.first() {
+                        Some(&byte) => input.push(byte),
+                        None => break,
+                    }
+                    reader.consume(1);
+                } else if reader.read_until(b'\n', &mut input).await? == 0 {
+                    break;
+                }
+            }
+        }
+        parser.push_line(&input)?;
+    }
+
+    parser.finish()
+}
 
-    if let Ok(lines) = read_lines(filename) {
-        // Consumes the iterator, returns an (Optional) String
-
-        // Read through the lines and build a buffer of <records>, each starting
-        // with a zero and ending with the last line before the next. Then feed that
-        // buffer to a nom parser to split it into Lines?
-
-        // This is kind of like a buffered read, specific to the GEDCOM format
-        // We read into the buffer until we hit a new record, and then parse that
-        // record into a struct.
-        let mut record: String = String::new();
-
-        // Use `map_while` because we could loop on an Err value
-        for mut buffer in lines.map_while(Result::ok) {
-            // Strip off any leading Zero Width No-Break Space
-            if buffer.strip_prefix('\u{FEFF}').is_some() {
-                buffer.remove(0);
-            }
-            // println!("Buffer: \n'{}'", buffer);
-            // record = buffer.clone() + "\n";
-
-            if let Some(ch) = buffer.chars().next() {
-                if ch == '0' && !record.is_empty() {
-                    let mut input: &str = record.as_str();
-
-                    // Peek at the first line in the record so we know how
-                    // to parse it.
-                    let line = Line::peek(&mut input).unwrap();
-                    // println!("Got a line: {:?}", line);
-                    match line.tag {
-                        "HEAD" => {
-                            // println!("Parsing HEAD: \n{}", input);
-                            gedcom.header = Header::parse(input.to_string());
-                        }
-                        "INDI" => {
-                            let indi = Individual::parse(&mut input);
-                            // TODO: Remove the if. This is just to clean up the output for debugging.
-                            // if indi.xref.clone().unwrap() == "@I1@" {
-                            gedcom.individuals.push(indi);
-                            // }
-                        }
-                        "SOUR" => {}
-                        "REPO" => {}
-                        "OBJE" => {
-                            // let obj = Object::parse(buff);
-                            // println!("{:?}", obj);
-                        }
-                        "FAM" => {}
-                        "SUBM" => {
-                            // // The record of the submitter of the family tree
-                            // // Not always present (it exists in complete.ged)
-                            if let Some(ref subm) = gedcom.header.submitter {
-                                if let Some(xref) = &subm.xref {
-                                    gedcom.header.submitter =
-                                        Submitter::find_by_xref(input, xref.to_string());
-                                }
-                            }
-                        }
-                        _ => {}
+/// Read a line, up to and including its newline. In UTF-16, read_until may
+/// stop at a byte of some other character, or halfway through the newline,
+/// so we keep reading until we're at the end of a whole newline.
+fn read_line<R: BufRead>(
+    reader: &mut R,
+    encoding: Encoding,
+    line: &mut Vec<u8>,
+) -> io::Result<usize> {
+    if reader.read_until(b'\n', line)? == 0 {
+        return Ok(0);
+    }
+    if let Some(newline) = utf16_newline(encoding) {
+        while !line.ends_with(&newline) {
+            if line.len() % 2 == 1 {
+                match reader.fill_buf()?.first() {
+                    Some(&byte) => line.push(byte),
+                    None => break,
+                }
+                reader.consume(1);
+            } else if reader.read_until(b'\n', line)? == 0 {
+                break;
+            }
+        }
+    }
+    Ok(line.len())
+}
+
+/// The bytes of a newline in UTF-16, if that's what we're reading
+fn utf16_newline(encoding: Encoding) -> Option<[u8; 2]> {
+    match encoding {
+        Encoding::Utf8 | Encoding::Windows1252 => None,
+        Encoding::Utf16Le => Some([b'\n', 0]),
+        Encoding::Utf16Be => Some([0, b'\n']),
+    }
+}
+
+/// Work out a file's encoding from its first bytes: its byte order mark, if
+/// it has one, or else the NULs around the 0 that starts a UTF-16 file.
+/// Returns the encoding, and whether there was a byte order mark.
+fn sniff_encoding(start: &[u8]) -> (Encoding, bool) {
+    if start.starts_with(b"\xEF\xBB\xBF") {
+        (Encoding::Utf8, true)
+    } else if start.starts_with(b"\xFF\xFE") {
+        (Encoding::Utf16Le, true)
+    } else if start.starts_with(b"\xFE\xFF") {
+        (Encoding::Utf16Be, true)
+    } else if start.starts_with(b"0\0") {
+        (Encoding::Utf16Le, false)
+    } else if start.starts_with(b"\x000") {
+        (Encoding::Utf16Be, false)
+    } else {
+        (Encoding::Utf8, false)
+    }
+}
+
+/// Parse GEDCOM data that's already in memory
+pub fn parse_gedcom_from_bytes(bytes: &[u8]) -> Result<Gedcom, GedcomError> {
+    parse_gedcom_from_reader(bytes)
+}
+
+/// Parse GEDCOM text that's already in memory
+pub fn parse_gedcom_from_str(text: &str) -> Result<Gedcom, GedcomError> {
+    parse_gedcom_from_bytes(text.as_bytes())
+}
+
+/// How many bytes to parse between reports of progress. See
+/// `GedcomConfig::on_progress`.
+const PROGRESS_INTERVAL: usize = 1 << 20;
+
+/// The tags GEDCOM 7.0 adds to those of 5.5.1
+const GEDCOM_7_TAGS: [&str; 17] = [
+    "CREA", "CROP", "EXID", "HEIGHT", "INIL", "LEFT", "MIME", "NO", "PHRASE", "SCHMA", "SDATE",
+    "SNOTE", "TAG", "TOP", "TRAN", "UID", "WIDTH",
+];
+
+/// The records `parse_record` reads, or the header reads for itself
+const RECORD_TAGS: [&str; 11] = [
+    "HEAD", "INDI", "FAM", "SOUR", "REPO", "OBJE", "NOTE", "SNOTE", "SUBM", "SUBN", "TRLR",
+];
+
+/// The links in each record that are nothing without a pointer
+const POINTER_TAGS: [(&str, &str); 10] = [
+    ("INDI", "FAMC"),
+    ("INDI", "FAMS"),
+    ("INDI", "ASSO"),
+    ("INDI", "SUBM"),
+    ("INDI", "ANCI"),
+    ("INDI", "DESI"),
+    ("FAM", "HUSB"),
+    ("FAM", "WIFE"),
+    ("FAM", "CHIL"),
+    ("FAM", "SUBM"),
+];
+
+/// Builds a Gedcom from its lines, one at a time, however they're read.
+///
+/// Lines are collected into records, each starting with a level 0 line and
+/// ending with the last line before the next, and each record is parsed once
+/// it's complete.
+pub(crate) struct Parser<'a> {
+    config: &'a GedcomConfig,
+    gedcom: Gedcom,
+
+    /// The record we're currently building
+    record: String,
+    /// A NOTE or BLOB that may be too long to keep
+    text: Option<HeldText>,
+    /// How many characters in the record were replaced or look suspect
+    /// when decoding
+    replaced: usize,
+    suspect: usize,
+    /// How many characters outside ASCII were read as valid UTF-8, in the
+    /// whole file
+    multibyte: usize,
+    /// The original lines of the record, terminators and all
+    raw: Vec<String>,
+
+    /// Where we are in the input, and where the current record started
+    position: Position,
+    start: Position,
+
+    /// The level of the last well-formed line
+    previous: Option<u8>,
+    /// The tag of the record we're in, and the level of any structure whose
+    /// lines we aren't checking, because it's unrecognised or an extension
+    record_tag: String,
+    skipping: Option<u8>,
+
+    /// How many bytes there are to parse, if we know, how many records
+    /// have been parsed, and where we were when we last reported progress
+    total: Option<usize>,
+    records: usize,
+    reported: usize,
+}
+
+/// A NOTE or BLOB, held back from the record until we know how long its
+/// text is. See `GedcomConfig::max_text_length`.
+struct HeldText {
+    level: u8,
+    tag: String,
+    line: usize,
+    /// The NOTE or BLOB line, and where its value starts
+    head: String,
+    value_start: usize,
+    /// The CONC and CONT lines, until the text gets too long
+    lines: Vec<String>,
+    length: usize,
+}
+
+impl<'a> Parser<'a> {
+    pub(crate) fn new(config: &'a GedcomConfig) -> Parser<'a> {
+        Parser {
+            config,
+            gedcom: Gedcom {
+                header: Header {
+                    character_set: None,
+                    copyright: None,
+                    date: None,
+                    destination: None,
+                    gedcom_version: None,
+                    language: None,
+                    filename: None,
+                    note: None,
+                    place: None,
+                    source: None,
+                    submitter: None,
+                    submission: None,
+                    home_person: None,
+                },
+                individuals: vec![],
+                families: vec![],
+                sources: vec![],
+                multimedia: vec![],
+                notes: vec![],
+                repositories: vec![],
+                submitters: vec![],
+                raw_records: vec![],
+                record_positions: HashMap::new(),
+                warnings: vec![],
+                skipped_text: vec![],
+                decoding: DecodingStats::default(),
+                kinship: None,
+                archive: None,
+                validation: config.validation.clone(),
+            },
+            replaced: 0,
+            suspect: 0,
+            multibyte: 0,
+            record: String::new(),
+            text: None,
+            raw: vec![],
+            position: Position { line: 1, offset: 0 },
+            start: Position { line: 1, offset: 0 },
+            previous: None,
+            record_tag: String::new(),
+            skipping: None,
+            total: None,
+            records: 0,
+            reported: 0,
+        }
+    }
+
+    /// Work out the encoding of the input from its first bytes, which takes
+    /// precedence over the CHAR the header declares, unless the encoding's
+    /// been forced
+    pub(crate) fn sniff(&mut self, start: &[u8]) -> Encoding {
+        let (sniffed, byte_order_mark) = sniff_encoding(start);
+        let encoding = self.config.encoding.unwrap_or(sniffed);
+        self.gedcom.decoding.encoding = encoding;
+        self.gedcom.decoding.byte_order_mark = byte_order_mark && sniffed == encoding;
+        encoding
+    }
+
+    /// Add the next line of input, including its line terminator. In strict
+    /// mode, a malformed or skipped line is an error, and if the parse has been
+    /// cancelled, every line is.
+    pub(crate) fn push_line(&mut self, bytes: &[u8]) -> Result<(), GedcomError> {
+        if self
+            .config
+            .cancel
+            .as_ref()
+            .is_some_and(|c| c.is_cancelled())
+        {
+            return Err(GedcomError::Cancelled);
+        }
+        let (input, replaced, suspect) = decode(bytes, self.gedcom.decoding.encoding);
+        self.replaced += replaced;
+        self.suspect += suspect;
+        if self.gedcom.decoding.encoding == Encoding::Utf8 {
+            self.multibyte += input
+                .chars()
+                .filter(|c| !c.is_ascii() && *c != char::REPLACEMENT_CHARACTER && *c != '\u{FEFF}')
+                .count();
+        }
+        let mut buffer = input.trim_end_matches(&['\r', '\n'][..]).to_string();
+
+        // Strip off any leading Zero Width No-Break Space, which is also
+        // what a byte order mark decodes to
+        if buffer.strip_prefix('\u{FEFF}').is_some() {
+            buffer.remove(0);
+        }
+
+        if let Some(ch) = buffer.chars().next() {
+            if ch == '0' && (!self.record.is_empty() || self.text.is_some()) {
+                self.flush()?;
+            }
+            if self.record.is_empty() && self.text.is_none() {
+                self.start = self.position;
+            }
+            match check_line(&buffer, self.previous) {
+                Ok(level) => {
+                    self.check_structure(&buffer, level)?;
+                    if !self.config.preserve_whitespace {
+                        trim_value(&mut buffer);
+                    }
+                    if let Some(buffer) = self.hold_text(buffer) {
+                        self.record = std::mem::take(&mut self.record) + &buffer + "\n";
+                    }
+                    self.previous = Some(level);
+                }
+                Err(error) => {
+                    // Most likely a newline embedded in a value, rather than
+                    // a CONC/CONT. We can't parse it, so skip it, but make a
+                    // note of it.
+                    let (record, xref) = match Line::peek(&mut self.record.as_str()) {
+                        Ok(l) => (
+                            Some(l.tag.to_string()),
+                            Some(l.xref.to_string()).filter(|x| !x.is_empty()),
+                        ),
+                        Err(_) => (None, None),
+                    };
+                    let (line, offset) = (Some(self.position.line), Some(self.position.offset));
+                    let err = match error {
+                        LineError::Xref(xref) => GedcomError::InvalidXref {
+                            line,
+                            offset,
+                            record,
+                            xref,
+                        },
+                        LineError::Malformed(problem) => GedcomError::MalformedLine {
+                            line,
+                            offset,
+                            record,
+                            xref,
+                            content: buffer,
+                            problem,
+                        },
                     };
+                    if self.config.strict {
+                        return Err(err);
+                    }
+                    self.warn(err);
+                }
+            }
+        }
+        self.raw.push(input);
 
-                    record.clear();
+        self.position.line += 1;
+        self.position.offset += bytes.len();
+        Ok(())
+    }
+
+    /// Parse whatever's left, which should be the trailer, and hand back
+    /// the finished Gedcom
+    pub(crate) fn finish(mut self) -> Result<Gedcom, GedcomError> {
+        if !self.record.is_empty() || !self.raw.is_empty() || self.text.is_some() {
+            self.flush()?;
+        }
+        self.check_encoding();
+        self.check_references();
+        if let Some(progress) = &self.config.progress {
+            progress.report(self.records, self.position.offset, self.total);
+        }
+        Ok(self.gedcom)
+    }
+
+    /// Warn if the header seems to declare the wrong encoding: if the file
+    /// was read in a different one, going by its byte order mark or the look
+    /// of its first line, or if its text doesn't fit the one declared. A
+    /// file that doesn't look like UTF-16 is read as UTF-8, whatever 8-bit
+    /// character set it declares, unless the encoding's been forced.
+    fn check_encoding(&mut self) {
+        if self.config.encoding.is_some() {
+            return;
+        }
+        let declared = match self.gedcom.header.character_set.as_ref() {
+            Some(CharacterSet {
+                encoding: Some(declared),
+                ..
+            }) => declared,
+            _ => return,
+        };
+        let decoding = &self.gedcom.decoding;
+        let message = if decoding.byte_order_mark
+            || decoding.encoding != Encoding::Utf8
+            || Encoding::Utf16Le.matches(declared)
+        {
+            if decoding.encoding.matches(declared) {
+                return;
+            }
+            let evidence = if decoding.byte_order_mark {
+                format!("starts with a {} byte order mark", decoding.encoding)
+            } else if decoding.encoding != Encoding::Utf8 {
+                format!("looks like {}", decoding.encoding)
+            } else {
+                "isn't UTF-16".to_string()
+            };
+            format!(
+                "The header declares {}, but the file {}, so it was read as {}",
+                declared, evidence, decoding.encoding
+            )
+        } else if declared.parse() == Ok(Encoding::Utf8) {
+            if decoding.replaced == 0 {
+                return;
+            }
+            format!(
+                "The header declares {}, but {} byte sequence(s) aren't valid UTF-8, so the file may really be ANSI; see GedcomConfig::force_encoding",
+                declared, decoding.replaced
+            )
+        } else {
+            // An 8-bit character set, whose text beyond ASCII is very
+            // unlikely to also be valid UTF-8
+            if self.multibyte == 0 || decoding.replaced > 0 {
+                return;
+            }
+            format!(
+                "The header declares {}, but the file's text is valid UTF-8, so it was read as UTF-8",
+                declared
+            )
+        };
+        let warning = GedcomError::ParseError {
+            line: None,
+            offset: None,
+            tag: "CHAR".to_string(),
+            message,
+        };
+        self.warn(warning);
+    }
+
+    /// Warn about pointers to individuals, families and sources that aren't
+    /// in the file
+    fn check_references(&mut self) {
+        let gedcom = &self.gedcom;
+        let individuals: HashSet<&str> = gedcom
+            .individuals
+            .iter()
+            .filter_map(|i| i.xref.as_deref())
+            .collect();
+        let families: HashSet<&str> = gedcom.families.iter().map(|f| f.xref.as_str()).collect();
+        let sources: HashSet<&str> = gedcom.sources.iter().map(|s| s.xref.as_str()).collect();
+        let mut dangling = vec![];
+
+        for individual in &gedcom.individuals {
+            let xref = individual.xref.as_deref().unwrap_or_default();
+            for (tag, links) in [("FAMC", &individual.famc), ("FAMS", &individual.fams)] {
+                for link in links.iter().filter(|l| !families.contains(l.xref.as_str())) {
+                    dangling.push(("INDI", xref, tag, link.xref.as_str()));
+                }
+            }
+            for source in individual.sources.iter().filter_map(|c| c.xref.as_deref()) {
+                if !sources.contains(source) {
+                    dangling.push(("INDI", xref, "SOUR", source));
+                }
+            }
+        }
+        for family in &gedcom.families {
+            let spouses = [
+                ("HUSB", family.husband_xref()),
+                ("WIFE", family.wife_xref()),
+            ];
+            let members = spouses
+                .iter()
+                .copied()
+                .chain(family.children.iter().map(|c| ("CHIL", Some(c.as_str()))));
+            for (tag, member) in members {
+                if let Some(member) = member.filter(|m| !individuals.contains(m)) {
+                    dangling.push(("FAM", &family.xref, tag, member));
                 }
-                record = record + &buffer.clone() + "\n";
             }
+            for source in family.sources.iter().filter_map(|c| c.xref.as_deref()) {
+                if !sources.contains(source) {
+                    dangling.push(("FAM", &family.xref, "SOUR", source));
+                }
+            }
+        }
+
+        let warnings: Vec<GedcomError> = dangling
+            .into_iter()
+            .map(|(record, xref, tag, pointer)| {
+                let start = gedcom.record_positions.get(xref);
+                GedcomError::DanglingReference {
+                    line: start.map(|p| p.line),
+                    offset: start.map(|p| p.offset),
+                    record,
+                    xref: Some(xref.to_string()).filter(|x| !x.is_empty()),
+                    tag,
+                    pointer: pointer.to_string(),
+                }
+            })
+            .collect();
+        for warning in warnings {
+            self.warn(warning);
         }
-        // TODO: families
-        // TODO: repositories
-        // TODO: sources
-        // TODO: multimedia
     }
-    gedcom
+
+    /// Check a well-formed line for what the record parsers would skip
+    /// without a word: a tag they don't know, with everything under it, or
+    /// a link to another record without a pointer. In strict mode, that's
+    /// an error.
+    fn check_structure(&mut self, buffer: &str, level: u8) -> Result<(), GedcomError> {
+        let line = match Line::peek(&mut &*buffer) {
+            Ok(line) => line,
+            Err(_) => return Ok(()),
+        };
+        if level == 0 {
+            self.record_tag = line.tag.to_string();
+        }
+        match self.skipping {
+            Some(skipping) if level > skipping => return Ok(()),
+            _ => self.skipping = None,
+        }
+
+        // Extensions are a program's own, and may have anything under them
+        if line.tag.starts_with('_') {
+            self.skipping = Some(level);
+            return Ok(());
+        }
+        let message = if level == 0 && !RECORD_TAGS.contains(&line.tag) {
+            self.skipping = Some(level);
+            format!("Unrecognised record {}, skipped", line.tag)
+        } else if !is_standard_tag(line.tag) && !GEDCOM_7_TAGS.contains(&line.tag) {
+            self.skipping = Some(level);
+            format!(
+                "Unrecognised tag in {}, skipped with anything under it",
+                self.record_tag
+            )
+        } else if level == 1
+            && POINTER_TAGS.contains(&(self.record_tag.as_str(), line.tag))
+            && !is_pointer(line.value.trim())
+        {
+            format!("Link in {} without a pointer, skipped", self.record_tag)
+        } else {
+            return Ok(());
+        };
+
+        let error = GedcomError::ParseError {
+            line: Some(self.position.line),
+            offset: Some(self.position.offset),
+            tag: line.tag.to_string(),
+            message,
+        };
+        if self.config.strict {
+            return Err(error);
+        }
+        self.warn(error);
+        Ok(())
+    }
+
+    /// Record a warning, and pass it on to any DiagnosticsSink
+    fn warn(&mut self, warning: GedcomError) {
+        if let Some(diagnostics) = &self.config.diagnostics {
+            diagnostics.warning(&warning);
+        }
+        self.gedcom.warnings.push(warning);
+    }
+
+    /// With `GedcomConfig::max_text_length`, hold back a NOTE or BLOB line
+    /// and its CONC and CONT lines, dropping them once the text is too long.
+    /// Returns the line if it should go straight into the record.
+    fn hold_text(&mut self, line: String) -> Option<String> {
+        let max = match self.config.max_text_length {
+            Some(max) => max,
+            None => return Some(line),
+        };
+        let (level, tag, value) = match Line::peek(&mut line.as_str()) {
+            Ok(l) => (l.level, l.tag.to_string(), l.value.len()),
+            Err(_) => return Some(line),
+        };
+
+        if let Some(text) = &mut self.text {
+            if level == text.level + 1 && (tag == "CONC" || tag == "CONT") {
+                text.length += value + usize::from(tag == "CONT");
+                if text.length <= max {
+                    text.lines.push(line);
+                } else {
+                    text.lines.clear();
+                }
+                return None;
+            }
+            self.release_text();
+        }
+
+        let pointer = line.ends_with('@') && line[line.len() - value..].starts_with('@');
+        if (tag == "NOTE" && !pointer) || tag == "BLOB" {
+            self.text = Some(HeldText {
+                level,
+                tag,
+                line: self.position.line,
+                value_start: line.len() - value,
+                head: line,
+                lines: vec![],
+                length: value,
+            });
+            return None;
+        }
+        Some(line)
+    }
+
+    /// Add the held NOTE or BLOB to the record, without its text if it was
+    /// too long
+    fn release_text(&mut self) {
+        let text = match self.text.take() {
+            Some(text) => text,
+            None => return,
+        };
+        if text.length <= self.config.max_text_length.unwrap_or(usize::MAX) {
+            self.record += &text.head;
+            self.record += "\n";
+            for line in text.lines {
+                self.record += &line;
+                self.record += "\n";
+            }
+            return;
+        }
+
+        // The xref is on the first line of the record, which may be this one
+        let first = if self.record.is_empty() {
+            &text.head
+        } else {
+            &self.record
+        };
+        let xref = Line::peek(&mut first.as_str())
+            .ok()
+            .map(|l| l.xref.to_string())
+            .filter(|x| !x.is_empty());
+        self.record += text.head[..text.value_start].trim_end();
+        self.record += "\n";
+        self.gedcom.skipped_text.push(SkippedText {
+            xref,
+            line: text.line,
+            tag: text.tag,
+            length: text.length,
+        });
+    }
+
+    /// Parse the record we've built so far. In strict mode, a record that
+    /// can't be parsed is an error.
+    fn flush(&mut self) -> Result<(), GedcomError> {
+        self.release_text();
+        if self.replaced > 0 || self.suspect > 0 {
+            let xref = Line::peek(&mut self.record.as_str())
+                .ok()
+                .map(|l| l.xref.to_string())
+                .filter(|x| !x.is_empty());
+            let decoding = &mut self.gedcom.decoding;
+            decoding.replaced += self.replaced;
+            decoding.suspect += self.suspect;
+            decoding.records.push(RecordDecoding {
+                xref,
+                line: self.start.line,
+                replaced: std::mem::take(&mut self.replaced),
+                suspect: std::mem::take(&mut self.suspect),
+            });
+        }
+        if !self.record.is_empty() {
+            let warnings = self.gedcom.warnings.len();
+            if let Err(error) = parse_record(&mut self.gedcom, &self.record, self.start) {
+                if self.config.strict {
+                    return Err(error);
+                }
+                self.gedcom.warnings.push(error);
+            }
+            if let Some(diagnostics) = &self.config.diagnostics {
+                for warning in &self.gedcom.warnings[warnings..] {
+                    diagnostics.warning(warning);
+                }
+            }
+            self.records += 1;
+        }
+        if let Some(progress) = &self.config.progress {
+            if self.position.offset - self.reported >= PROGRESS_INTERVAL {
+                progress.report(self.records, self.position.offset, self.total);
+                self.reported = self.position.offset;
+            }
+        }
+        if self.config.preserve_unknown {
+            let raw = raw_record(&self.record, &mut self.raw);
+            self.gedcom.raw_records.push(raw);
+        }
+        self.raw.clear();
+        self.record.clear();
+        Ok(())
+    }
 }
 
-// The output is wrapped in a Result to allow matching on errors
-// Returns an Iterator to the Reader of the lines of the file.
-// https://doc.rust-lang.org/rust-by-example/std_misc/file/read_lines.html
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-where
-    P: AsRef<Path>,
-{
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+/// Decode a line, replacing each invalid sequence with U+FFFD. Returns the
+/// text, and how many characters were replaced or look suspect.
+fn decode(bytes: &[u8], encoding: Encoding) -> (String, usize, usize) {
+    let mut text = String::with_capacity(bytes.len());
+    let mut replaced = 0;
+    let mut suspect = 0;
+    match encoding {
+        Encoding::Utf8 => {}
+        Encoding::Utf16Le | Encoding::Utf16Be => {
+            let units = bytes.chunks_exact(2).map(|pair| match encoding {
+                Encoding::Utf16Be => u16::from_be_bytes([pair[0], pair[1]]),
+                _ => u16::from_le_bytes([pair[0], pair[1]]),
+            });
+            for c in char::decode_utf16(units) {
+                text.push(c.unwrap_or_else(|_| {
+                    replaced += 1;
+                    char::REPLACEMENT_CHARACTER
+                }));
+            }
+            // A stray byte at the end of the file
+            if bytes.len() % 2 == 1 {
+                text.push(char::REPLACEMENT_CHARACTER);
+                replaced += 1;
+            }
+            suspect += suspect_chars(&text);
+            return (text, replaced, suspect);
+        }
+        Encoding::Windows1252 => {
+            text.extend(bytes.iter().map(|b| windows_1252(*b)));
+            suspect += suspect_chars(&text);
+            return (text, replaced, suspect);
+        }
+    }
+    for chunk in bytes.utf8_chunks() {
+        suspect += suspect_chars(chunk.valid());
+        text += chunk.valid();
+        if !chunk.invalid().is_empty() {
+            text.push(char::REPLACEMENT_CHARACTER);
+            replaced += 1;
+        }
+    }
+    (text, replaced, suspect)
+}
+
+/// The character a Windows-1252 byte stands for. The five bytes it leaves
+/// undefined are read as the C1 controls of the same number, as browsers do.
+fn windows_1252(byte: u8) -> char {
+    const HIGH: [char; 32] = [
+        '\u{20AC}', '\u{81}', '\u{201A}', '\u{192}', '\u{201E}', '\u{2026}', '\u{2020}',
+        '\u{2021}', '\u{2C6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8D}', '\u{17D}',
+        '\u{8F}', '\u{90}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}',
+        '\u{2014}', '\u{2DC}', '\u{2122}', '\u{161}', '\u{203A}', '\u{153}', '\u{9D}', '\u{17E}',
+        '\u{178}',
+    ];
+    match byte {
+        0x80..=0x9F => HIGH[(byte - 0x80) as usize],
+        _ => byte as char,
+    }
+}
+
+/// Count the characters that are likely the result of an earlier mistake:
+/// replacement characters, C1 controls (usually Windows-1252 read as
+/// Latin-1), and the second half of UTF-8 read as Latin-1, i.e. "Ã©" for "é"
+fn suspect_chars(text: &str) -> usize {
+    let mut count = 0;
+    let mut previous = None;
+    for c in text.chars() {
+        match c {
+            char::REPLACEMENT_CHARACTER | '\u{80}'..='\u{9F}' => count += 1,
+            '\u{A0}'..='\u{BF}' if matches!(previous, Some('Ã') | Some('Â')) => count += 1,
+            _ => {}
+        }
+        previous = Some(c);
+    }
+    count
+}
+
+/// Drop any whitespace between a line's delimiter and its value, so that
+/// i.e. "2 CONT   text" is read as "2 CONT text"
+fn trim_value(line: &mut String) {
+    let mut words = line.splitn(4, ' ');
+    let mut prefix = words.next().unwrap_or_default().len() + 1;
+    let tag = words.next().unwrap_or_default();
+    prefix += tag.len() + 1;
+    if tag.starts_with('@') {
+        prefix += words.next().unwrap_or_default().len() + 1;
+    }
+    if prefix < line.len() {
+        let whitespace = line[prefix..].len() - line[prefix..].trim_start().len();
+        line.replace_range(prefix..prefix + whitespace, "");
+    }
+}
+
+/// What's wrong with a line that `check_line` rejects
+#[derive(Debug, PartialEq)]
+enum LineError {
+    /// A malformed xref
+    Xref(String),
+    Malformed(LineProblem),
+}
+
+/// Check that a line is well-formed, and follows on from the level of the
+/// line before it. Returns the line's level, or what's wrong with it.
+///
+/// A line whose level jumps by more than one is skipped. Its children are
+/// reported in turn, since they can't follow on from anything either.
+fn check_line(text: &str, previous: Option<u8>) -> Result<u8, LineError> {
+    let digits = text.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits == 0 {
+        return Err(LineError::Malformed(LineProblem::NotALine));
+    }
+
+    let mut rest = text[digits..].split(' ').filter(|s| !s.is_empty());
+    let mut tag = rest.next().unwrap_or_default();
+    if tag.starts_with('@') {
+        if tag.len() < 3 || !tag.ends_with('@') {
+            return Err(LineError::Xref(tag.to_string()));
+        }
+        tag = rest.next().unwrap_or_default();
+    }
+    if tag.is_empty() || !tag.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(LineError::Malformed(LineProblem::MalformedTag));
+    }
+    if !text[digits..].starts_with(' ') {
+        return Err(LineError::Malformed(LineProblem::NotALine));
+    }
+    if text.chars().any(|c| c.is_control() && c != '\t') {
+        return Err(LineError::Malformed(LineProblem::ControlCharacter));
+    }
+
+    let level = text[..digits]
+        .parse::<u8>()
+        .map_err(|_| LineError::Malformed(LineProblem::InvalidLevel))?;
+    match previous {
+        None if level != 0 => Err(LineError::Malformed(LineProblem::OutsideRecord { level })),
+        Some(p) if level > p + 1 => Err(LineError::Malformed(LineProblem::SkippedLevel {
+            level,
+            previous: p,
+        })),
+        _ => Ok(level),
+    }
+}
+
+/// Parse a single level 0 record, which starts at `start` in the file, into
+/// the Gedcom
+fn parse_record(gedcom: &mut Gedcom, record: &str, start: Position) -> Result<(), GedcomError> {
+    let mut input: &str = record;
+
+    // Peek at the first line in the record so we know how
+    // to parse it.
+    let line = Line::peek(&mut input).map_err(|_| GedcomError::ParseError {
+        line: Some(start.line),
+        offset: Some(start.offset),
+        tag: String::new(),
+        message: "Record can't be read, skipped".to_string(),
+    })?;
+    if !line.xref.is_empty() {
+        gedcom.record_positions.insert(line.xref.to_string(), start);
+    }
+    // println!("Got a line: {:?}", line);
+    match line.tag {
+        "HEAD" => {
+            // println!("Parsing HEAD: \n{}", input);
+            gedcom.header = Header::parse(input.to_string());
+            let version = gedcom.header.gedcom_version.as_ref();
+            if let Some(version) = version.and_then(|v| v.version.as_ref()) {
+                // 5.5.5 is close enough to 5.5.1 to read as it
+                if version.parse::<GedcomVersion>().is_err() && !version.starts_with("5.5.") {
+                    gedcom.warnings.push(GedcomError::UnsupportedVersion {
+                        line: Some(start.line),
+                        offset: Some(start.offset),
+                        version: version.clone(),
+                    });
+                }
+            }
+            let date = gedcom.header.date.clone();
+            check_time(gedcom, "HEAD", date.as_ref(), start);
+        }
+        "INDI" => {
+            let indi = Individual::parse(&mut input);
+            if indi.names.is_empty() {
+                gedcom.warnings.push(GedcomError::MissingData {
+                    line: Some(start.line),
+                    offset: Some(start.offset),
+                    xref: indi.xref.clone(),
+                    message: "Individual has no name".to_string(),
+                });
+            }
+            let xref = indi.xref.clone().unwrap_or_default();
+            check_time(gedcom, &xref, indi.change_date.as_ref(), start);
+            gedcom.individuals.push(indi);
+        }
+        "SOUR" => {
+            let source = SourceRecord::parse(&mut input);
+            check_time(gedcom, &source.xref, source.change_date.as_ref(), start);
+            gedcom.sources.push(source);
+        }
+        "REPO" => {
+            let repository = RepositoryRecord::parse(&mut input);
+            check_time(
+                gedcom,
+                &repository.xref,
+                repository.change_date.as_ref(),
+                start,
+            );
+            gedcom.repositories.push(repository);
+        }
+        "OBJE" => {
+            let multimedia = MultimediaRecord::parse(&mut input);
+            check_time(
+                gedcom,
+                &multimedia.xref,
+                multimedia.change_date.as_ref(),
+                start,
+            );
+            gedcom.multimedia.push(multimedia);
+        }
+        // 7.0 calls shared notes SNOTE
+        "NOTE" | "SNOTE" => {
+            let note = NoteRecord::parse(&mut input);
+            check_time(gedcom, &note.xref, note.change_date.as_ref(), start);
+            gedcom.notes.push(note);
+        }
+        "FAM" => {
+            let family = Family::parse(&mut input);
+            check_time(gedcom, &family.xref, family.change_date.as_ref(), start);
+            gedcom.families.push(family);
+        }
+        "SUBM" => {
+            if let Some(submitter) = Submitter::find_by_xref(input, line.xref.to_string()) {
+                // The header only has the pointer, until we find the record
+                let header = gedcom.header.submitter.as_ref();
+                if header.and_then(|s| s.xref.as_ref()) == submitter.xref.as_ref() {
+                    gedcom.header.submitter = Some(submitter.clone());
+                }
+                gedcom.submitters.push(submitter);
+            }
+        }
+        _ => {}
+    };
+    Ok(())
+}
+
+/// Warn about a record's CHAN, or the header's DATE, with a TIME that can't
+/// be read, which is then ignored
+fn check_time(gedcom: &mut Gedcom, record: &str, date: Option<&DateTime>, start: Position) {
+    if let Some(time) = date.and_then(|d| d.invalid_time()) {
+        gedcom.warnings.push(GedcomError::ParseError {
+            line: Some(start.line),
+            offset: Some(start.offset),
+            tag: "TIME".to_string(),
+            message: format!(
+                "{} has an invalid time, \"{}\", which was ignored",
+                record, time
+            ),
+        });
+    }
+}
+
+/// Take the original lines of a record, for round-tripping
+fn raw_record(record: &str, raw: &mut Vec<String>) -> RawRecord {
+    let mut input: &str = record;
+    let (xref, tag) = match Line::peek(&mut input) {
+        Ok(line) if !record.is_empty() => (
+            Some(line.xref.to_string()).filter(|x| !x.is_empty()),
+            line.tag.to_string(),
+        ),
+        _ => (None, String::new()),
+    };
+
+    RawRecord {
+        xref,
+        tag,
+        lines: std::mem::take(raw),
+    }
 }
 
 #[cfg(test)]
@@ -191,4 +1121,549 @@ mod tests {
         }
         assert!(input.len() == 0);
     }
+
+    #[test]
+    fn parse_warnings() {
+        let data = [
+            "0 HEAD",
+            "1 CHAR UTF-8",
+            "0 @I1@ INDI",
+            "1 SEX M",
+            "0 @I2@ INDI",
+            "1 NAME Joe /Bloggs/",
+            "1 NOTE An embedded",
+            "newline",
+            "0 TRLR",
+        ];
+        let filename = std::env::temp_dir().join("gedcom-rs-parse-warnings.ged");
+        std::fs::write(&filename, data.join("\r\n")).unwrap();
+
+        let gedcom = parse_gedcom(filename.to_str().unwrap());
+        std::fs::remove_file(&filename).unwrap();
+
+        assert!(gedcom.individuals.len() == 2);
+        assert!(
+            gedcom.record_positions.get("@I2@")
+                == Some(&Position {
+                    line: 5,
+                    offset: 44
+                })
+        );
+
+        assert!(gedcom.warnings.len() == 2);
+        assert!(gedcom.warnings[0].to_string() == "line 3: @I1@: Individual has no name");
+        assert!(gedcom.warnings[0].offset() == Some(22));
+        assert!(gedcom.warnings[1].line() == Some(8));
+
+        // A missing file is a warning, too
+        let gedcom = parse_gedcom("./data/does-not-exist.ged");
+        assert!(matches!(gedcom.warnings[0], GedcomError::Io(_)));
+    }
+
+    #[test]
+    fn parse_diagnostics() {
+        use std::sync::{Arc, Mutex};
+
+        let data = "0 HEAD\n0 @I1@ INDI\n1 SEX M\n1 NOTE An embedded\nnewline\n0 TRLR\n";
+        let found = Arc::new(Mutex::new(vec![]));
+        let sink = found.clone();
+        let config = GedcomConfig::new().diagnostics(move |warning: &GedcomError| {
+            sink.lock().unwrap().push(warning.to_string());
+        });
+        let gedcom = parse_gedcom_from_reader_with_config(data.as_bytes(), &config).unwrap();
+
+        let warnings: Vec<String> = gedcom.warnings.iter().map(|w| w.to_string()).collect();
+        assert!(warnings.len() == 2);
+        assert!(*found.lock().unwrap() == warnings);
+    }
+
+    #[test]
+    fn parse_progress() {
+        use std::sync::{Arc, Mutex};
+
+        let record =
+            "0 @I1@ INDI\n1 NAME Joe /Bloggs/\n1 NOTE ".to_string() + &"x".repeat(100) + "\n";
+        let data = "0 HEAD\n".to_string() + &record.repeat(20_000) + "0 TRLR\n";
+        let filename = std::env::temp_dir().join("gedcom-rs-parse-progress.ged");
+        std::fs::write(&filename, &data).unwrap();
+
+        let reports = Arc::new(Mutex::new(vec![]));
+        let sink = reports.clone();
+        let config = GedcomConfig::new().on_progress(move |records, bytes, total| {
+            sink.lock().unwrap().push((records, bytes, total));
+        });
+        let gedcom = parse_gedcom_with_config(filename.to_str().unwrap(), &config).unwrap();
+        std::fs::remove_file(&filename).unwrap();
+        assert!(gedcom.individuals.len() == 20_000);
+
+        // Every megabyte or so, and at the end
+        let reports = reports.lock().unwrap();
+        assert!(reports.len() == 3);
+        assert!(reports[0].0 > 0 && reports[0].1 >= PROGRESS_INTERVAL);
+        assert!(reports
+            .windows(2)
+            .all(|r| r[0].0 < r[1].0 && r[0].1 < r[1].1));
+        assert!(*reports.last().unwrap() == (20_002, data.len(), Some(data.len())));
+
+        let config = GedcomConfig::new().on_progress(|_, _, total| assert!(total.is_none()));
+        parse_gedcom_from_reader_with_config("0 HEAD\n0 TRLR\n".as_bytes(), &config).unwrap();
+    }
+
+    #[test]
+    fn parse_cancel() {
+        use crate::config::CancelToken;
+
+        let data = "0 HEAD\n0 @I1@ INDI\n0 @I2@ INDI\n1 NAME Joe /Bloggs/\n0 TRLR\n";
+        let token = CancelToken::new();
+        let config = GedcomConfig::new().cancel_token(token.clone());
+        assert!(parse_gedcom_from_reader_with_config(data.as_bytes(), &config).is_ok());
+
+        // Cancel part way through, on the first warning
+        let cancel = token.clone();
+        let config = config.diagnostics(move |_: &GedcomError| cancel.cancel());
+        let result = parse_gedcom_from_reader_with_config(data.as_bytes(), &config);
+        assert!(matches!(result, Err(GedcomError::Cancelled)));
+        assert!(token.is_cancelled());
+
+        let result = parse_gedcom_from_reader_with_config(data.as_bytes(), &config);
+        assert!(result.unwrap_err().to_string() == "Parsing was cancelled");
+    }
+
+    #[test]
+    fn parse_strict() {
+        let data = [
+            "0 HEAD",
+            "1 CHAR UTF-8",
+            "0 @I1@ INDI",
+            "1 NAME Joe /Bloggs/",
+            "1 BIRT",
+            "3 DATE 1 JAN 1900",
+            "2 PLAC Somewhere",
+            "0 TRLR",
+        ];
+        let filename = std::env::temp_dir().join("gedcom-rs-parse-strict.ged");
+        std::fs::write(&filename, data.join("\n")).unwrap();
+        let filename = filename.to_str().unwrap().to_string();
+
+        // Leniently, the out of place DATE is skipped, with a warning
+        let gedcom = parse_gedcom_with_config(&filename, &GedcomConfig::new()).unwrap();
+        assert!(gedcom.individuals.len() == 1);
+        assert!(gedcom.warnings.len() == 1);
+        assert!(
+            gedcom.warnings[0].to_string()
+                == "line 6: DATE: Skipped level 3 line following level 1"
+        );
+
+        // Strictly, it's an error
+        let err = parse_gedcom_with_config(&filename, &GedcomConfig::new().strict());
+        std::fs::remove_file(&filename).unwrap();
+        assert!(matches!(
+            err,
+            Err(GedcomError::MalformedLine {
+                line: Some(6),
+                ref record,
+                ref xref,
+                ref content,
+                problem: LineProblem::SkippedLevel { level: 3, previous: 1 },
+                ..
+            }) if record.as_deref() == Some("INDI")
+                && xref.as_deref() == Some("@I1@")
+                && content == "3 DATE 1 JAN 1900"
+        ));
+
+        assert!(
+            parse_gedcom_with_config("./data/does-not-exist.ged", &GedcomConfig::new()).is_err()
+        );
+    }
+
+    #[test]
+    fn parse_skipped_structures() {
+        let text = [
+            "0 HEAD",
+            "0 @I1@ INDI",
+            "1 NAME Joe /Bloggs/",
+            "1 FOOO bar",
+            "2 BAZ x",
+            "1 _MILT Army",
+            "2 QUUX 1917",
+            "1 FAMS",
+            "0 @F1@ FAM",
+            "1 HUSB @I1@",
+            "1 WIFE",
+            "1 MARR",
+            "2 HUSB",
+            "3 AGE 42y",
+            "0 @X1@ FOO",
+            "1 NAME Nothing",
+            "0 TRLR",
+        ]
+        .join("\n");
+
+        // Leniently, each is skipped, with a warning
+        let gedcom = parse_gedcom_from_str(&text).unwrap();
+        let warnings: Vec<String> = gedcom.warnings.iter().map(|w| w.to_string()).collect();
+        assert!(
+            warnings
+                == [
+                    "line 4: FOOO: Unrecognised tag in INDI, skipped with anything under it",
+                    "line 8: FAMS: Link in INDI without a pointer, skipped",
+                    "line 11: WIFE: Link in FAM without a pointer, skipped",
+                    "line 15: FOO: Unrecognised record FOO, skipped",
+                ]
+        );
+        assert!(gedcom.individuals[0].names.len() == 1);
+
+        // Strictly, the first is an error
+        let err =
+            parse_gedcom_from_reader_with_config(text.as_bytes(), &GedcomConfig::new().strict());
+        assert!(matches!(
+            err,
+            Err(GedcomError::ParseError { line: Some(4), ref tag, .. }) if tag == "FOOO"
+        ));
+    }
+
+    #[test]
+    fn parse_from_memory() {
+        let text = "0 HEAD\n1 CHAR UTF-8\n0 @I1@ INDI\n1 NAME Joe /Bloggs/\n0 TRLR\n";
+
+        let gedcom = parse_gedcom_from_str(text).unwrap();
+        assert!(gedcom.individuals.len() == 1);
+        assert!(gedcom.record_positions.get("@I1@").unwrap().offset == 20);
+
+        let gedcom = parse_gedcom_from_bytes(text.as_bytes()).unwrap();
+        assert!(gedcom.individuals.len() == 1);
+
+        let gedcom = parse_gedcom_from_reader(std::io::Cursor::new(text)).unwrap();
+        assert!(gedcom.individuals[0].xref.as_deref() == Some("@I1@"));
+        assert!(gedcom.warnings.is_empty());
+
+        // Invalid UTF-8 doesn't stop us reading the rest of the file
+        let mut bytes = b"0 HEAD\n0 @I1@ INDI\n1 NAME Jos\xe9 /Bloggs/\n".to_vec();
+        bytes.extend_from_slice(b"0 @I2@ INDI\n1 NAME Joe /Bloggs/\n0 TRLR\n");
+        let gedcom = parse_gedcom_from_bytes(&bytes).unwrap();
+        assert!(gedcom.individuals.len() == 2);
+    }
+
+    fn utf16(text: &str, big_endian: bool) -> Vec<u8> {
+        text.encode_utf16()
+            .flat_map(|unit| match big_endian {
+                true => unit.to_be_bytes(),
+                false => unit.to_le_bytes(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parse_encoding() {
+        // U+0A0A has a newline byte in each half, so lines can't simply be
+        // split at newlines
+        let text = "0 HEAD\r\n1 CHAR UNICODE\r\n0 @I1@ INDI\r\n1 NAME Jos\u{E9} \u{A0A} /Bloggs/\r\n0 @I2@ INDI\r\n1 NAME Ann /Bloggs/\r\n0 TRLR\r\n";
+        for (bom, big_endian, encoding) in [
+            ("\u{FEFF}", false, Encoding::Utf16Le),
+            ("\u{FEFF}", true, Encoding::Utf16Be),
+            ("", false, Encoding::Utf16Le),
+            ("", true, Encoding::Utf16Be),
+        ] {
+            let bytes = utf16(&format!("{}{}", bom, text), big_endian);
+            let gedcom = parse_gedcom_from_bytes(&bytes).unwrap();
+            assert!(gedcom.decoding.encoding == encoding);
+            assert!(gedcom.decoding.byte_order_mark != bom.is_empty());
+            assert!(gedcom.decoding.is_clean());
+            assert!(gedcom.warnings.is_empty());
+            assert!(gedcom.individuals.len() == 2);
+            assert!(
+                gedcom.individuals[0].names[0].name.value.as_deref()
+                    == Some("Jos\u{E9} \u{A0A} /Bloggs/")
+            );
+            let offset = gedcom.record_positions.get("@I2@").unwrap().offset;
+            assert!(offset == bom.len() / 3 * 2 + 2 * 61);
+        }
+
+        // A UTF-8 byte order mark
+        let gedcom = parse_gedcom_from_str(
+            "\u{FEFF}0 HEAD\n1 CHAR UTF-8\n0 @I1@ INDI\n1 NAME Joe /Bloggs/\n0 TRLR\n",
+        )
+        .unwrap();
+        assert!(gedcom.decoding.encoding == Encoding::Utf8);
+        assert!(gedcom.decoding.byte_order_mark);
+        assert!(gedcom.warnings.is_empty() && gedcom.individuals.len() == 1);
+
+        // The byte order mark wins over the declared encoding, with a warning
+        let bytes = utf16("\u{FEFF}0 HEAD\n1 CHAR ANSEL\n0 TRLR\n", true);
+        let gedcom = parse_gedcom_from_bytes(&bytes).unwrap();
+        assert!(gedcom.header.character_set.unwrap().encoding.as_deref() == Some("ANSEL"));
+        assert!(
+            gedcom.warnings[0].to_string()
+                == "CHAR: The header declares ANSEL, but the file starts with a UTF-16BE byte order mark, so it was read as UTF-16BE"
+        );
+        let gedcom = parse_gedcom_from_str("\u{FEFF}0 HEAD\n1 CHAR UNICODE\n0 TRLR\n").unwrap();
+        assert!(gedcom.warnings.len() == 1);
+        let gedcom = parse_gedcom_from_str("0 HEAD\n1 CHAR UNICODE\n0 TRLR\n").unwrap();
+        assert!(gedcom.warnings.len() == 1);
+        let gedcom = parse_gedcom_from_str("0 HEAD\n1 CHAR ANSEL\n0 TRLR\n").unwrap();
+        assert!(gedcom.warnings.is_empty());
+    }
+
+    #[test]
+    fn parse_force_encoding() {
+        // ANSI, declared as UTF-8
+        let bytes = b"0 HEAD\n1 CHAR UTF-8\n0 @I1@ INDI\n1 NAME Jos\xe9 \x80 /Bloggs/\n0 TRLR\n";
+        let gedcom = parse_gedcom_from_bytes(bytes).unwrap();
+        assert!(gedcom.decoding.replaced == 2);
+        assert!(
+            gedcom.warnings[0].to_string()
+                == "CHAR: The header declares UTF-8, but 2 byte sequence(s) aren't valid UTF-8, so the file may really be ANSI; see GedcomConfig::force_encoding"
+        );
+
+        let config = GedcomConfig::new().force_encoding(Encoding::Windows1252);
+        let gedcom = parse_gedcom_from_reader_with_config(&bytes[..], &config).unwrap();
+        assert!(gedcom.decoding.encoding == Encoding::Windows1252);
+        assert!(gedcom.decoding.is_clean());
+        assert!(gedcom.warnings.is_empty());
+        assert!(
+            gedcom.individuals[0].names[0].name.value.as_deref()
+                == Some("Jos\u{E9} \u{20AC} /Bloggs/")
+        );
+
+        // UTF-8, declared as ANSI
+        let text = "0 HEAD\n1 CHAR ANSI\n0 @I1@ INDI\n1 NAME Jos\u{E9} /Bloggs/\n0 TRLR\n";
+        let gedcom = parse_gedcom_from_str(text).unwrap();
+        assert!(
+            gedcom.warnings[0].to_string()
+                == "CHAR: The header declares ANSI, but the file's text is valid UTF-8, so it was read as UTF-8"
+        );
+        let gedcom = parse_gedcom_from_str(&text.replace("Jos\u{E9}", "Joe")).unwrap();
+        assert!(gedcom.warnings.is_empty());
+
+        // Forcing an encoding overrides the byte order mark
+        let config = GedcomConfig::new().force_encoding("utf-8".parse().unwrap());
+        let bytes = utf16("\u{FEFF}0 HEAD\n0 TRLR\n", false);
+        let gedcom = parse_gedcom_from_reader_with_config(&bytes[..], &config).unwrap();
+        assert!(gedcom.decoding.encoding == Encoding::Utf8);
+        assert!(!gedcom.decoding.byte_order_mark);
+
+        assert!("UNICODE".parse() == Ok(Encoding::Utf16Le));
+        assert!("ANSEL".parse::<Encoding>().is_err());
+        assert!(windows_1252(b'A') == 'A' && windows_1252(0x9F) == '\u{178}');
+    }
+
+    #[cfg(feature = "tokio")]
+    #[tokio::test]
+    async fn parse_async() {
+        let file = tokio::fs::File::open("./data/complete.ged").await.unwrap();
+        let gedcom = parse_gedcom_async(file).await.unwrap();
+        let expected = parse_gedcom("./data/complete.ged");
+
+        assert!(gedcom.individuals.len() == expected.individuals.len());
+        assert!(gedcom.families.len() == expected.families.len());
+        assert!(gedcom.record_positions == expected.record_positions);
+
+        let config = GedcomConfig::new().strict();
+        let text = "0 HEAD\n2 CHAR UTF-8\n0 TRLR\n";
+        assert!(parse_gedcom_async_with_config(text.as_bytes(), &config)
+            .await
+            .is_err());
+
+        let bytes = utf16(
+            "\u{FEFF}0 HEAD\n1 CHAR UNICODE\n0 @I1@ INDI\n1 NAME \u{A0A}\n0 TRLR\n",
+            false,
+        );
+        let gedcom = parse_gedcom_async(&bytes[..]).await.unwrap();
+        assert!(gedcom.decoding.encoding == Encoding::Utf16Le);
+        assert!(gedcom.warnings.is_empty());
+        assert!(gedcom.individuals[0].names[0].name.value.as_deref() == Some("\u{A0A}"));
+    }
+
+    #[test]
+    fn parse_whitespace() {
+        let text = "0 HEAD\n1 NOTE Transcribed:\n2 CONT    Know all men\n2 CONC  by these presents\n0 TRLR\n";
+
+        let gedcom = parse_gedcom_from_str(text).unwrap();
+        assert!(
+            gedcom.header.note.as_deref() == Some("Transcribed:\nKnow all menby these presents")
+        );
+
+        let config = GedcomConfig::new().preserve_whitespace();
+        let gedcom = parse_gedcom_from_reader_with_config(text.as_bytes(), &config).unwrap();
+        assert!(
+            gedcom.header.note.as_deref()
+                == Some("Transcribed:\n   Know all men by these presents")
+        );
+
+        let mut line = "0 @I1@ INDI".to_string();
+        trim_value(&mut line);
+        assert!(line == "0 @I1@ INDI");
+        let mut line = "1 NAME  Joe /Bloggs/ ".to_string();
+        trim_value(&mut line);
+        assert!(line == "1 NAME Joe /Bloggs/ ");
+    }
+
+    #[test]
+    fn parse_decoding() {
+        let mut text = b"0 HEAD\n0 @I1@ INDI\n1 NAME Ren".to_vec();
+        // "é" in Latin-1, which isn't UTF-8
+        text.extend_from_slice(b"\xE9e /Dupr\xE9/\n");
+        text.extend_from_slice(
+            "0 @I2@ INDI\n1 NAME AndrÃ© /Dupont/\n0 @I3@ INDI\n1 NAME Joe /Bloggs/\n0 TRLR\n"
+                .as_bytes(),
+        );
+
+        let gedcom = parse_gedcom_from_reader_with_config(&text[..], &GedcomConfig::new()).unwrap();
+        let name = gedcom.individuals[0].names[0].name.value.as_deref();
+        assert!(name == Some("Ren\u{FFFD}e /Dupr\u{FFFD}/"));
+        assert!(!gedcom.decoding.is_clean());
+        assert!(gedcom.decoding.replaced == 2 && gedcom.decoding.suspect == 1);
+        assert!(
+            gedcom.decoding.records
+                == [
+                    RecordDecoding {
+                        xref: Some("@I1@".to_string()),
+                        line: 2,
+                        replaced: 2,
+                        suspect: 0,
+                    },
+                    RecordDecoding {
+                        xref: Some("@I2@".to_string()),
+                        line: 4,
+                        replaced: 0,
+                        suspect: 1,
+                    },
+                ]
+        );
+
+        let gedcom = parse_gedcom("./data/complete.ged");
+        assert!(gedcom.decoding.is_clean());
+    }
+
+    #[test]
+    fn parse_max_text_length() {
+        let text = "0 HEAD
+1 NOTE A header note that 
+2 CONC goes on
+2 CONT and on
+0 @F1@ FAM
+1 NOTE Short
+1 NOTE @N1@
+0 @M1@ OBJE
+1 BLOB
+2 CONT .HM.......k.1..F.jwA.Dzzzzw............A....1.........0U.66..E.8
+2 CONT .......A..k.a6.A.......A..k.........../6....G.......0../..U.....
+0 TRLR
+";
+        let config = GedcomConfig::new().max_text_length(10);
+        let gedcom = parse_gedcom_from_reader_with_config(text.as_bytes(), &config).unwrap();
+        assert!(gedcom.header.note.as_deref() == Some(""));
+        let notes: Vec<Option<&str>> = gedcom.families[0]
+            .notes
+            .iter()
+            .map(|n| n.note.as_deref())
+            .collect();
+        assert!(notes == [Some("Short"), Some("@N1@")]);
+        assert!(
+            gedcom.skipped_text
+                == [
+                    SkippedText {
+                        xref: None,
+                        line: 2,
+                        tag: "NOTE".to_string(),
+                        length: 33,
+                    },
+                    SkippedText {
+                        xref: Some("@M1@".to_string()),
+                        line: 9,
+                        tag: "BLOB".to_string(),
+                        length: 130,
+                    },
+                ]
+        );
+
+        // Without it, everything is kept
+        let gedcom = parse_gedcom_from_str(text).unwrap();
+        assert!(gedcom.header.note.as_deref() == Some("A header note that goes on\nand on"));
+        assert!(gedcom.skipped_text.is_empty());
+    }
+
+    #[test]
+    fn parse_structured_warnings() {
+        let text = "0 HEAD
+1 GEDC
+2 VERS 4.0
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 FAMC @F9@
+0 @F1 FAM
+0 @F1@ FAM
+1 HUSB @I1@
+1 CHIL @I2@
+0 TRLR
+";
+        let gedcom = parse_gedcom_from_str(text).unwrap();
+        assert!(matches!(
+            gedcom.warnings[0],
+            GedcomError::UnsupportedVersion { line: Some(1), ref version, .. } if version == "4.0"
+        ));
+        assert!(matches!(
+            gedcom.warnings[1],
+            GedcomError::InvalidXref { line: Some(7), ref xref, .. } if xref == "@F1"
+        ));
+        let dangling: Vec<(Option<&str>, Option<&str>, Option<usize>)> = gedcom.warnings[2..]
+            .iter()
+            .map(|w| (w.record(), w.xref(), w.line()))
+            .collect();
+        assert!(
+            dangling
+                == [
+                    (Some("INDI"), Some("@I1@"), Some(4)),
+                    (Some("FAM"), Some("@F1@"), Some(8))
+                ]
+        );
+        assert!(matches!(
+            gedcom.warnings[3],
+            GedcomError::DanglingReference { tag: "CHIL", ref pointer, .. } if pointer == "@I2@"
+        ));
+    }
+
+    #[test]
+    fn parse_check_line() {
+        assert!(check_line("0 HEAD", None) == Ok(0));
+        assert!(check_line("1 NAME Joe /Bloggs/", Some(0)) == Ok(1));
+        assert!(check_line("0 @I1@ INDI", Some(2)) == Ok(0));
+        assert!(check_line("1 _HME @I1@", Some(0)) == Ok(1));
+        assert!(check_line("newline", Some(1)).is_err());
+        assert!(check_line("1 HEAD", None).is_err());
+        assert!(check_line("0 @I1 INDI", None) == Err(LineError::Xref("@I1".to_string())));
+        assert!(check_line("1NAME Joe", Some(0)).is_err());
+        assert!(check_line("300 NAME Joe", Some(0)).is_err());
+        assert!(check_line("1 NOTE tab\tseparated", Some(0)) == Ok(1));
+    }
+
+    #[test]
+    fn parse_control_characters() {
+        let text = "0 HEAD\n0 @I1@ INDI\n1 NAME Joe /Bloggs/\n1 NOTE line one\rline two\n0 TRLR\n";
+
+        // Leniently, the line with the stray carriage return is skipped
+        let gedcom = parse_gedcom_from_str(text).unwrap();
+        assert!(gedcom.individuals[0].names.len() == 1);
+        assert!(gedcom.individuals[0].notes.is_empty());
+        assert!(gedcom.warnings.len() == 1);
+        assert!(
+            gedcom.warnings[0].to_string()
+                == "line 4: NOTE: Control character in line: '1 NOTE line one\\rline two'"
+        );
+
+        // Strictly, it's an error
+        let config = GedcomConfig::new().strict();
+        let err = parse_gedcom_from_reader_with_config(text.as_bytes(), &config);
+        assert!(matches!(
+            err,
+            Err(GedcomError::MalformedLine {
+                line: Some(4),
+                problem: LineProblem::ControlCharacter,
+                ..
+            })
+        ));
+
+        // And peeking at it is an error, not a panic
+        assert!(Line::peek(&mut "1 NOTE line one\rline two").is_err());
+    }
 }
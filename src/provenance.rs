@@ -0,0 +1,162 @@
+use crate::types::{Family, Gedcom, Individual, Submitter};
+
+// Who supplied what. The header names the submitter of the file as a whole,
+// and SUBM links on individuals and families name whoever contributed those
+// records, which matters in files merged from several researchers. ANCI and
+// DESI only say a submitter is interested in more research on the
+// individual's ancestors or descendants, so they don't count as submitting.
+
+/// A record a submitter contributed. See `records_submitted_by`.
+#[derive(Clone, Copy, Debug)]
+pub enum SubmittedRecord<'a> {
+    Individual(&'a Individual),
+    Family(&'a Family),
+}
+
+impl<'a> SubmittedRecord<'a> {
+    /// The record's xref
+    pub fn xref(&self) -> Option<&'a str> {
+        match self {
+            SubmittedRecord::Individual(individual) => individual.xref.as_deref(),
+            SubmittedRecord::Family(family) => Some(&family.xref),
+        }
+    }
+}
+
+/// How an individual is linked to a submitter
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SubmitterLink {
+    /// SUBM: The submitter contributed the record
+    Submitted,
+    /// ANCI: The submitter is interested in the individual's ancestors
+    Ancestors,
+    /// DESI: The submitter is interested in the individual's descendants
+    Descendants,
+}
+
+/// The submitter of the file, from the header's SUBM pointer. If there's no
+/// SUBM record for it, only its xref is known.
+pub fn primary_submitter(gedcom: &Gedcom) -> Option<&Submitter> {
+    let header = gedcom.header.submitter.as_ref()?;
+    let xref = header.xref.as_deref()?;
+    gedcom.find_submitter(xref).or(Some(header))
+}
+
+/// The individuals, then the families, that the submitter `xref` is linked
+/// to by SUBM, in file order
+pub fn records_submitted_by<'a>(gedcom: &'a Gedcom, xref: &str) -> Vec<SubmittedRecord<'a>> {
+    let individuals = gedcom
+        .individuals
+        .iter()
+        .filter(|i| i.submitters.iter().any(|s| s == xref))
+        .map(SubmittedRecord::Individual);
+    let families = gedcom
+        .families
+        .iter()
+        .filter(|f| f.submitters.iter().any(|s| s == xref))
+        .map(SubmittedRecord::Family);
+    individuals.chain(families).collect()
+}
+
+/// The submitters linked to `individual`, and how, in the order of the
+/// links. Links to submitters without a SUBM record are left out.
+pub fn submitters_of<'a>(
+    gedcom: &'a Gedcom,
+    individual: &Individual,
+) -> Vec<(SubmitterLink, &'a Submitter)> {
+    let links = [
+        (SubmitterLink::Submitted, &individual.submitters),
+        (SubmitterLink::Ancestors, &individual.ancestor_interest),
+        (SubmitterLink::Descendants, &individual.descendant_interest),
+    ];
+    links
+        .iter()
+        .flat_map(|&(link, xrefs)| xrefs.iter().map(move |xref| (link, xref)))
+        .filter_map(|(link, xref)| Some((link, gedcom.find_submitter(xref)?)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_gedcom_from_str;
+    use crate::writer::write_gedcom;
+
+    const DATA: &str = "0 HEAD
+1 SUBM @U2@
+0 @U1@ SUBM
+1 NAME Ann Other
+0 @U2@ SUBM
+1 NAME Jo Researcher
+1 LANG English
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 SUBM @U1@
+1 ANCI @U2@
+1 DESI @U1@
+0 @I2@ INDI
+1 NAME Mary /Bloggs/
+1 SUBM @U2@
+0 @F1@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 SUBM @U1@
+0 TRLR
+";
+
+    #[test]
+    fn submitters() {
+        let gedcom = parse_gedcom_from_str(DATA).unwrap();
+        assert!(gedcom.submitters.len() == 2);
+
+        // The header's submitter isn't the first in the file
+        let primary = gedcom.primary_submitter().unwrap();
+        assert!(primary.name.as_deref() == Some("Jo Researcher"));
+        assert!(primary.lang == ["English"]);
+        assert!(gedcom.header.submitter.as_ref() == Some(primary));
+
+        let submitted = |xref: &str| -> Vec<Option<&str>> {
+            gedcom
+                .records_submitted_by(xref)
+                .iter()
+                .map(|r| r.xref())
+                .collect()
+        };
+        assert!(submitted("@U1@") == [Some("@I1@"), Some("@F1@")]);
+        assert!(submitted("@U2@") == [Some("@I2@")]);
+        assert!(submitted("@U3@").is_empty());
+
+        let joe = gedcom.find_individual("@I1@").unwrap();
+        let links: Vec<(SubmitterLink, Option<&str>)> = gedcom
+            .submitters_of(joe)
+            .iter()
+            .map(|(link, s)| (*link, s.xref.as_deref()))
+            .collect();
+        assert!(
+            links
+                == [
+                    (SubmitterLink::Submitted, Some("@U1@")),
+                    (SubmitterLink::Ancestors, Some("@U2@")),
+                    (SubmitterLink::Descendants, Some("@U1@")),
+                ]
+        );
+
+        // Every submitter, and every link, is written back out
+        let written = parse_gedcom_from_str(&write_gedcom(&gedcom)).unwrap();
+        assert!(written.submitters == gedcom.submitters);
+        let joe = written.find_individual("@I1@").unwrap();
+        assert!(joe.ancestor_interest == ["@U2@"] && joe.descendant_interest == ["@U1@"]);
+        assert!(written.families[0].submitters == ["@U1@"]);
+    }
+
+    #[test]
+    fn missing_submitter() {
+        let gedcom = parse_gedcom_from_str("0 HEAD\n1 SUBM @U9@\n0 TRLR\n").unwrap();
+        let primary = gedcom.primary_submitter().unwrap();
+        assert!(primary.xref.as_deref() == Some("@U9@") && primary.name.is_none());
+        assert!(parse_gedcom_from_str("0 HEAD\n0 TRLR\n")
+            .unwrap()
+            .primary_submitter()
+            .is_none());
+    }
+}
@@ -0,0 +1,683 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::Not;
+use std::str::FromStr;
+
+use crate::error::GedcomError;
+use crate::names;
+use crate::types::{EventDetail, EventType, Gedcom, GedcomDate, Gender, Individual};
+
+// One way to ask for individuals, in place of a find_* function for each
+// combination of criteria. Queries are parsed into the same Filter that
+// code can build directly, so both are matched the same way.
+
+/// Something about an individual that a filter compares
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Field {
+    Xref,
+    /// Any of their names, without the slashes, i.e. "Joe Bloggs"
+    Name,
+    Surname,
+    Given,
+    Sex,
+    /// An event of this type, or of any type if None
+    Event(Option<EventType>),
+    /// The year of an event
+    Year(Option<EventType>),
+    /// Where an event took place
+    Place(Option<EventType>),
+}
+
+impl fmt::Display for Field {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let event = |event: &Option<EventType>| match event {
+            Some(event) => event.tag().to_lowercase(),
+            None => "event".to_string(),
+        };
+        match self {
+            Field::Xref => write!(f, "xref"),
+            Field::Name => write!(f, "name"),
+            Field::Surname => write!(f, "surname"),
+            Field::Given => write!(f, "given"),
+            Field::Sex => write!(f, "sex"),
+            Field::Event(e) => write!(f, "{}", event(e)),
+            Field::Year(e) => write!(f, "{}.year", event(e)),
+            Field::Place(e) => write!(f, "{}.place", event(e)),
+        }
+    }
+}
+
+impl FromStr for Field {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Field, Self::Err> {
+        let input = input.to_lowercase();
+        match input.as_str() {
+            "xref" => return Ok(Field::Xref),
+            "name" => return Ok(Field::Name),
+            "surname" => return Ok(Field::Surname),
+            "given" => return Ok(Field::Given),
+            "sex" | "gender" => return Ok(Field::Sex),
+            _ => {}
+        }
+        let (event, part) = match input.split_once('.') {
+            Some((event, part)) => (event, Some(part)),
+            None => (input.as_str(), None),
+        };
+        let event = match event {
+            "event" => None,
+            "birth" => Some(EventType::Birth),
+            "death" => Some(EventType::Death),
+            "burial" => Some(EventType::Burial),
+            "christening" => Some(EventType::Christening),
+            "baptism" => Some(EventType::Baptism),
+            "marriage" => Some(EventType::Marriage),
+            "divorce" => Some(EventType::Divorce),
+            "census" => Some(EventType::Census),
+            "residence" => Some(EventType::Residence),
+            "occupation" => Some(EventType::Occupation),
+            tag => Some(EventType::from_str(&tag.to_uppercase())?),
+        };
+        match part {
+            None => Ok(Field::Event(event)),
+            Some("year") => Ok(Field::Year(event)),
+            Some("place") => Ok(Field::Place(event)),
+            Some(_) => Err(()),
+        }
+    }
+}
+
+/// A value to compare a field with
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    Text(String),
+    Number(i32),
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Value {
+        Value::Text(value.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(value: String) -> Value {
+        Value::Text(value)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(value: i32) -> Value {
+        Value::Number(value)
+    }
+}
+
+impl Value {
+    /// Compare with another value: numbers as numbers, text ignoring case,
+    /// and text with a number only if it is one
+    fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Number(a), Value::Number(b)) => Some(a.cmp(b)),
+            (Value::Text(a), Value::Text(b)) => Some(a.to_lowercase().cmp(&b.to_lowercase())),
+            (Value::Number(a), Value::Text(b)) => Some(a.cmp(&b.trim().parse().ok()?)),
+            (Value::Text(a), Value::Number(b)) => Some(a.trim().parse::<i32>().ok()?.cmp(b)),
+        }
+    }
+}
+
+/// What a field must be for a filter to match
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Condition {
+    Eq(Value),
+    /// Not equal, so also true where the field has no value
+    Ne(Value),
+    Lt(Value),
+    Le(Value),
+    Gt(Value),
+    Ge(Value),
+    /// Contains the text, ignoring case
+    Contains(String),
+    /// From the first value to the second, inclusive
+    Between(Value, Value),
+    /// Has any value at all
+    Exists,
+}
+
+/// A test of an individual, parsed from a query with `Filter::parse`, or
+/// built up in code.
+///
+/// A query compares fields of the individual with values:
+///
+/// ```text
+/// name, surname, given    any of their names
+/// sex                     M, F, X or U
+/// xref
+/// <event>                 an event, for EXISTS, i.e. "death EXISTS"
+/// <event>.year            the year of an event
+/// <event>.place           where an event took place
+/// ```
+///
+/// where an event is birth, death, burial, christening, baptism, marriage,
+/// divorce, census, residence, occupation, or any other event tag, i.e.
+/// "cens" or "natu", and "event" is any event at all. Marriages and
+/// divorces are those of the families the individual is a spouse in.
+///
+/// The comparisons are =, !=, <, <=, >, >=, CONTAINS, BETWEEN ... AND ...,
+/// and EXISTS, combined with AND, OR, NOT and parentheses. Text is quoted,
+/// and compared ignoring case. Someone with several names or births
+/// matches if any of them does, and someone without one matches nothing
+/// but NOT and !=.
+///
+/// ```
+/// use gedcom_rs::query::{Condition, Field, Filter};
+/// use gedcom_rs::types::EventType;
+///
+/// let filter = Filter::new(Field::Surname, Condition::Eq("Smith".into())).and(Filter::new(
+///     Field::Year(Some(EventType::Birth)),
+///     Condition::Between(1800.into(), 1850.into()),
+/// ));
+/// assert!(filter == Filter::parse("surname = 'Smith' AND birth.year BETWEEN 1800 AND 1850").unwrap());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Filter {
+    Is(Field, Condition),
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    pub fn new(field: Field, condition: Condition) -> Filter {
+        Filter::Is(field, condition)
+    }
+
+    /// Match only where both filters do
+    pub fn and(self, other: Filter) -> Filter {
+        match self {
+            Filter::And(mut filters) => {
+                filters.push(other);
+                Filter::And(filters)
+            }
+            filter => Filter::And(vec![filter, other]),
+        }
+    }
+
+    /// Match where either filter does
+    pub fn or(self, other: Filter) -> Filter {
+        match self {
+            Filter::Or(mut filters) => {
+                filters.push(other);
+                Filter::Or(filters)
+            }
+            filter => Filter::Or(vec![filter, other]),
+        }
+    }
+
+    /// Parse a query, i.e. "surname = 'Smith' AND birth.year < 1850", or
+    /// say what's wrong with it, and where
+    pub fn parse(query: &str) -> Result<Filter, GedcomError> {
+        let mut parser = Parser {
+            tokens: tokenize(query)?,
+            position: 0,
+            end: query.len(),
+        };
+        let filter = parser.or()?;
+        match parser.tokens.get(parser.position) {
+            None => Ok(filter),
+            Some((offset, token)) => Err(error(*offset, format!("unexpected {}", token))),
+        }
+    }
+
+    /// Whether `individual` matches
+    pub fn matches(&self, gedcom: &Gedcom, individual: &Individual) -> bool {
+        match self {
+            Filter::Is(field, condition) => {
+                let values = values(field, gedcom, individual);
+                let value = |value: &Value| match field {
+                    // Compare the sex as it's written in the file
+                    Field::Sex => match value {
+                        Value::Text(text) => {
+                            let Ok(gender) = Gender::from_str(text);
+                            Value::Text(sex(&gender))
+                        }
+                        value => value.clone(),
+                    },
+                    _ => value.clone(),
+                };
+                let any = |test: &dyn Fn(Ordering) -> bool, wanted: &Value| {
+                    let wanted = value(wanted);
+                    values.iter().any(|v| v.compare(&wanted).is_some_and(test))
+                };
+                match condition {
+                    Condition::Eq(v) => any(&|o| o.is_eq(), v),
+                    Condition::Ne(v) => !any(&|o| o.is_eq(), v),
+                    Condition::Lt(v) => any(&|o| o.is_lt(), v),
+                    Condition::Le(v) => any(&|o| o.is_le(), v),
+                    Condition::Gt(v) => any(&|o| o.is_gt(), v),
+                    Condition::Ge(v) => any(&|o| o.is_ge(), v),
+                    Condition::Contains(text) => {
+                        let text = text.to_lowercase();
+                        values.iter().any(|v| match v {
+                            Value::Text(v) => v.to_lowercase().contains(&text),
+                            Value::Number(n) => n.to_string().contains(&text),
+                        })
+                    }
+                    Condition::Between(low, high) => {
+                        let (low, high) = (value(low), value(high));
+                        values.iter().any(|v| {
+                            v.compare(&low).is_some_and(|o| o.is_ge())
+                                && v.compare(&high).is_some_and(|o| o.is_le())
+                        })
+                    }
+                    Condition::Exists => !values.is_empty(),
+                }
+            }
+            Filter::And(filters) => filters.iter().all(|f| f.matches(gedcom, individual)),
+            Filter::Or(filters) => filters.iter().any(|f| f.matches(gedcom, individual)),
+            Filter::Not(filter) => !filter.matches(gedcom, individual),
+        }
+    }
+}
+
+impl Not for Filter {
+    type Output = Filter;
+
+    /// Match where the filter doesn't
+    fn not(self) -> Filter {
+        Filter::Not(Box::new(self))
+    }
+}
+
+/// The values of `field` for `individual`, of which there may be any number
+fn values(field: &Field, gedcom: &Gedcom, individual: &Individual) -> Vec<Value> {
+    let names = individual.names.iter().map(|n| &n.name);
+    let text = |value: &str| Value::Text(value.to_string());
+    match field {
+        Field::Xref => individual.xref.as_deref().map(text).into_iter().collect(),
+        Field::Name => names
+            .filter_map(|n| n.value.as_deref())
+            .map(|n| Value::Text(names::strip_slashes(n)))
+            .collect(),
+        Field::Surname => names.filter_map(names::surname).map(text).collect(),
+        Field::Given => names.filter_map(names::given).map(text).collect(),
+        Field::Sex => vec![Value::Text(sex(&individual.gender))],
+        Field::Event(event) => events(*event, gedcom, individual)
+            .iter()
+            .map(|(tag, _)| text(tag))
+            .collect(),
+        Field::Year(event) => events(*event, gedcom, individual)
+            .iter()
+            .filter_map(|(_, detail)| {
+                let date = GedcomDate::from_str((*detail)?.date.as_deref()?).ok()?;
+                Some(Value::Number(date.year()?))
+            })
+            .collect(),
+        Field::Place(event) => events(*event, gedcom, individual)
+            .iter()
+            .filter_map(|(_, detail)| (*detail)?.place.as_ref()?.name.as_deref())
+            .map(text)
+            .collect(),
+    }
+}
+
+/// The individual's events of a type, or all of them, including those of
+/// the families they're a spouse in
+fn events<'a>(
+    event: Option<EventType>,
+    gedcom: &'a Gedcom,
+    individual: &'a Individual,
+) -> Vec<(&'static str, Option<&'a EventDetail>)> {
+    let mut events = individual.tagged_events();
+    for family in gedcom.spouse_families(individual) {
+        events.extend(family.tagged_events());
+    }
+    events.retain(|(tag, _)| event.is_none_or(|e| e.tag() == *tag));
+    events
+}
+
+/// The SEX value for a gender, i.e. "M"
+fn sex(gender: &Gender) -> String {
+    match gender {
+        Gender::Male => "M".to_string(),
+        Gender::Female => "F".to_string(),
+        Gender::Nonbinary => "X".to_string(),
+        Gender::Unknown => "U".to_string(),
+        Gender::Other(value) => value.clone(),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Text(String),
+    Number(i32),
+    Symbol(&'static str),
+}
+
+impl Token {
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self, Token::Word(word) if word.eq_ignore_ascii_case(keyword))
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "'{}'", word),
+            Token::Text(text) => write!(f, "text '{}'", text),
+            Token::Number(number) => write!(f, "number {}", number),
+            Token::Symbol(symbol) => write!(f, "'{}'", symbol),
+        }
+    }
+}
+
+/// A problem with a query, at a byte offset into it
+fn error(offset: usize, message: String) -> GedcomError {
+    GedcomError::ParseError {
+        line: None,
+        offset: Some(offset),
+        tag: String::new(),
+        message: format!("{} (at {})", message, offset),
+    }
+}
+
+/// Split a query into its words, text, numbers and symbols, each with its
+/// offset
+fn tokenize(query: &str) -> Result<Vec<(usize, Token)>, GedcomError> {
+    const SYMBOLS: [&str; 9] = ["!=", "<>", "<=", ">=", "=", "<", ">", "(", ")"];
+    let mut tokens = vec![];
+    let mut chars = query.char_indices().peekable();
+    while let Some(&(start, c)) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '\'' || c == '"' {
+            // Quotes are escaped by doubling them
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some((_, q)) if q == c => {
+                        if chars.peek().map(|&(_, n)| n) == Some(c) {
+                            text.push(c);
+                            chars.next();
+                        } else {
+                            break;
+                        }
+                    }
+                    Some((_, ch)) => text.push(ch),
+                    None => return Err(error(start, "unterminated text".to_string())),
+                }
+            }
+            tokens.push((start, Token::Text(text)));
+        } else if c.is_ascii_digit() || c == '-' {
+            let mut end = start + c.len_utf8();
+            chars.next();
+            while let Some(&(i, d)) = chars.peek().filter(|(_, d)| d.is_ascii_digit()) {
+                end = i + d.len_utf8();
+                chars.next();
+            }
+            let number = query[start..end]
+                .parse()
+                .map_err(|_| error(start, format!("'{}' isn't a number", &query[start..end])))?;
+            tokens.push((start, Token::Number(number)));
+        } else if c.is_alphabetic() || c == '_' {
+            let mut end = start;
+            while let Some(&(i, w)) = chars
+                .peek()
+                .filter(|(_, w)| w.is_alphanumeric() || *w == '_' || *w == '.')
+            {
+                end = i + w.len_utf8();
+                chars.next();
+            }
+            tokens.push((start, Token::Word(query[start..end].to_string())));
+        } else {
+            let symbol = SYMBOLS
+                .iter()
+                .find(|s| query[start..].starts_with(*s))
+                .ok_or_else(|| error(start, format!("unexpected '{}'", c)))?;
+            for _ in 0..symbol.len() {
+                chars.next();
+            }
+            tokens.push((start, Token::Symbol(symbol)));
+        }
+    }
+    Ok(tokens)
+}
+
+/// A recursive descent parser, from OR, which binds loosest, down to
+/// single comparisons
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+    /// Where the query ends, for errors there
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, t)| t)
+    }
+
+    fn next(&mut self, wanted: &str) -> Result<(usize, Token), GedcomError> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .ok_or_else(|| error(self.end, format!("expected {}", wanted)))?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = self.peek().is_some_and(|t| t.is_keyword(keyword));
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn or(&mut self) -> Result<Filter, GedcomError> {
+        let mut filter = self.and()?;
+        while self.keyword("OR") {
+            filter = filter.or(self.and()?);
+        }
+        Ok(filter)
+    }
+
+    fn and(&mut self) -> Result<Filter, GedcomError> {
+        let mut filter = self.unary()?;
+        while self.keyword("AND") {
+            filter = filter.and(self.unary()?);
+        }
+        Ok(filter)
+    }
+
+    fn unary(&mut self) -> Result<Filter, GedcomError> {
+        if self.keyword("NOT") {
+            return Ok(!self.unary()?);
+        }
+        if self.peek() == Some(&Token::Symbol("(")) {
+            self.position += 1;
+            let filter = self.or()?;
+            match self.next("')'")? {
+                (_, Token::Symbol(")")) => return Ok(filter),
+                (offset, token) => {
+                    return Err(error(offset, format!("expected ')', not {}", token)))
+                }
+            }
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Filter, GedcomError> {
+        let field = match self.next("a field")? {
+            (offset, Token::Word(word)) => Field::from_str(&word)
+                .map_err(|_| error(offset, format!("unknown field '{}'", word)))?,
+            (offset, token) => {
+                return Err(error(offset, format!("expected a field, not {}", token)))
+            }
+        };
+
+        let condition = match self.next("a comparison")? {
+            (_, Token::Symbol(symbol)) if symbol != "(" && symbol != ")" => {
+                let value = self.value()?;
+                match symbol {
+                    "=" => Condition::Eq(value),
+                    "!=" | "<>" => Condition::Ne(value),
+                    "<" => Condition::Lt(value),
+                    "<=" => Condition::Le(value),
+                    ">" => Condition::Gt(value),
+                    _ => Condition::Ge(value),
+                }
+            }
+            (_, token) if token.is_keyword("CONTAINS") => match self.value()? {
+                Value::Text(text) => Condition::Contains(text),
+                Value::Number(number) => Condition::Contains(number.to_string()),
+            },
+            (_, token) if token.is_keyword("BETWEEN") => {
+                let low = self.value()?;
+                if !self.keyword("AND") {
+                    return Err(error(
+                        self.tokens.get(self.position).map_or(self.end, |t| t.0),
+                        "expected AND".to_string(),
+                    ));
+                }
+                Condition::Between(low, self.value()?)
+            }
+            (_, token) if token.is_keyword("EXISTS") => Condition::Exists,
+            (offset, token) => {
+                return Err(error(
+                    offset,
+                    format!("expected a comparison after {}, not {}", field, token),
+                ))
+            }
+        };
+        Ok(Filter::Is(field, condition))
+    }
+
+    fn value(&mut self) -> Result<Value, GedcomError> {
+        match self.next("a value")? {
+            (_, Token::Text(text)) => Ok(Value::Text(text)),
+            (_, Token::Number(number)) => Ok(Value::Number(number)),
+            (offset, token) => Err(error(offset, format!("expected a value, not {}", token))),
+        }
+    }
+}
+
+/// The individuals `filter` matches, in file order
+pub fn filter<'a>(gedcom: &'a Gedcom, filter: &Filter) -> Vec<&'a Individual> {
+    gedcom
+        .individuals
+        .iter()
+        .filter(|individual| filter.matches(gedcom, individual))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_gedcom_from_str;
+
+    #[test]
+    fn parse() {
+        let filter = Filter::parse(
+            "surname = 'Smith' and (birth.year between 1800 AND 1850 OR NOT death EXISTS) \
+             AND cens.place contains \"Ohio\"",
+        )
+        .unwrap();
+        assert!(
+            filter
+                == Filter::And(vec![
+                    Filter::new(Field::Surname, Condition::Eq("Smith".into())),
+                    Filter::new(
+                        Field::Year(Some(EventType::Birth)),
+                        Condition::Between(1800.into(), 1850.into())
+                    )
+                    .or(!Filter::new(
+                        Field::Event(Some(EventType::Death)),
+                        Condition::Exists
+                    )),
+                    Filter::new(
+                        Field::Place(Some(EventType::Census)),
+                        Condition::Contains("Ohio".to_string())
+                    ),
+                ])
+        );
+        assert!(
+            Filter::parse("name = 'O''Brien'").unwrap()
+                == Filter::new(Field::Name, Condition::Eq("O'Brien".into()))
+        );
+        assert!(
+            Filter::parse("event.year >= -44").unwrap()
+                == Filter::new(Field::Year(None), Condition::Ge((-44).into()))
+        );
+
+        let message = |query: &str| Filter::parse(query).unwrap_err().message();
+        assert!(message("height > 2") == "unknown field 'height' (at 0)");
+        assert!(message("surname = ") == "expected a value (at 10)");
+        assert!(message("surname = 'Smith") == "unterminated text (at 10)");
+        assert!(message("birth.year BETWEEN 1800 1850") == "expected AND (at 24)");
+        assert!(message("(sex = 'M'") == "expected ')' (at 10)");
+        assert!(message("sex = 'M' 'F'") == "unexpected text 'F' (at 10)");
+        assert!(message("sex ~ 'M'") == "unexpected '~' (at 4)");
+    }
+
+    #[test]
+    fn query() {
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @I1@ INDI
+1 NAME John /Smith/
+1 SEX M
+1 BIRT
+2 DATE ABT 1820
+2 PLAC Dayton, Ohio
+1 FAMS @F1@
+0 @I2@ INDI
+1 NAME Mary /Jones/
+1 NAME Mary /Smith/
+1 SEX F
+1 BIRT
+2 DATE 1861
+1 FAMS @F1@
+0 @I3@ INDI
+1 NAME Sam /Smith/
+1 DEAT
+2 PLAC Leeds
+0 @F1@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 MARR
+2 DATE 1880
+0 TRLR
+",
+        )
+        .unwrap();
+        let query = |query: &str| -> Vec<&str> {
+            gedcom
+                .query(query)
+                .unwrap()
+                .iter()
+                .map(|i| i.xref.as_deref().unwrap())
+                .collect()
+        };
+
+        assert!(
+            query("surname = 'SMITH' AND birth.year BETWEEN 1800 AND 1850 AND birth.place CONTAINS 'ohio'")
+                == ["@I1@"]
+        );
+        assert!(query("surname = 'Smith'") == ["@I1@", "@I2@", "@I3@"]);
+        assert!(query("surname != 'Jones'") == ["@I1@", "@I3@"]);
+        assert!(query("birth.year > 1850 OR death.place = 'Leeds'") == ["@I2@", "@I3@"]);
+        assert!(query("NOT birth.year < 1850") == ["@I2@", "@I3@"]);
+        assert!(query("sex = 'female'") == ["@I2@"]);
+        assert!(query("marriage.year = 1880") == ["@I1@", "@I2@"]);
+        assert!(query("death EXISTS AND NOT death.year EXISTS") == ["@I3@"]);
+        assert!(query("given = 'mary' AND xref = '@I2@'") == ["@I2@"]);
+        assert!(query("event.year < 1830") == ["@I1@"]);
+        assert!(gedcom.query("surname =").is_err());
+
+        let filter = Filter::new(Field::Given, Condition::Contains("a".to_string()));
+        assert!(gedcom.filter(&filter).len() == 2);
+    }
+}
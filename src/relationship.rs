@@ -0,0 +1,2206 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::str::FromStr;
+
+use crate::names;
+use crate::types::{
+    Association, EventDetail, EventType, Family, Gedcom, GedcomDate, Gender, Individual, Pedigree,
+};
+
+/// How one individual is related to another. See `Gedcom::find_relationship`.
+#[derive(Debug)]
+pub struct Relationship<'a> {
+    /// What the second individual is to the first, i.e. "father" or
+    /// "first cousin once removed"
+    pub description: String,
+    /// The most recent common ancestor(s). For full siblings and their
+    /// descendants, this is usually a couple. Empty if the relationship is
+    /// by marriage.
+    pub common_ancestors: Vec<&'a Individual>,
+    /// How many generations the first individual is below the common ancestor
+    pub generations_up: usize,
+    /// How many generations the second individual is below the common ancestor
+    pub generations_down: usize,
+    /// Whether the two lines descend from different partners of the common
+    /// ancestor, i.e. half-siblings
+    pub half: bool,
+}
+
+impl<'a> Relationship<'a> {
+    /// Work out how `other` is related to `individual`, by blood or as a
+    /// spouse, or None if they aren't.
+    pub fn find(
+        gedcom: &'a Gedcom,
+        individual: &Individual,
+        other: &Individual,
+    ) -> Option<Relationship<'a>> {
+        let (from, to) = (individual.xref.as_deref()?, other.xref.as_deref()?);
+
+        let mut common = match gedcom
+            .kinship
+            .as_ref()
+            .and_then(|k| k.common(gedcom, from, to))
+        {
+            Some(common) => common,
+            None => {
+                let up = ancestor_generations(gedcom, from);
+                let down = ancestor_generations(gedcom, to);
+                closest(
+                    up.iter()
+                        .filter_map(|(xref, &g1)| Some((*xref, g1, *down.get(xref)?))),
+                )
+            }
+        };
+
+        if common.is_empty() {
+            return gedcom
+                .spouses(individual)
+                .iter()
+                .any(|s| s.xref.as_deref() == Some(to))
+                .then(|| Relationship {
+                    description: gendered(&other.gender, "husband", "wife", "spouse").to_string(),
+                    common_ancestors: vec![],
+                    generations_up: 0,
+                    generations_down: 0,
+                    half: false,
+                });
+        }
+
+        // Keep the file's order, so the results are stable
+        common.sort_by_key(|(xref, _, _)| {
+            gedcom
+                .individuals
+                .iter()
+                .position(|i| i.xref.as_deref() == Some(xref))
+        });
+        let (_, generations_up, generations_down) = common[0];
+        common.retain(|(_, g1, g2)| (*g1, *g2) == (generations_up, generations_down));
+
+        let common_ancestors: Vec<&Individual> = common
+            .iter()
+            .filter_map(|(xref, _, _)| gedcom.find_individual(xref))
+            .collect();
+
+        let half = generations_up > 0
+            && generations_down > 0
+            && !common_ancestors.is_empty()
+            && !same_parents(
+                gedcom,
+                &line_child(gedcom, from, generations_up, &common_ancestors),
+                &line_child(gedcom, to, generations_down, &common_ancestors),
+            );
+
+        Some(Relationship {
+            description: describe(generations_up, generations_down, half, &other.gender),
+            common_ancestors,
+            generations_up,
+            generations_down,
+            half,
+        })
+    }
+}
+
+impl Relationship<'_> {
+    /// The same relationship, with the common ancestors by xref, so it can
+    /// outlive the Gedcom or be sent to another thread
+    pub fn into_owned(self) -> OwnedRelationship {
+        OwnedRelationship {
+            description: self.description,
+            common_ancestors: xrefs(&self.common_ancestors),
+            generations_up: self.generations_up,
+            generations_down: self.generations_down,
+            half: self.half,
+        }
+    }
+}
+
+/// A `Relationship` that doesn't borrow from the Gedcom. See
+/// `Gedcom::find_relationship_by_xref`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OwnedRelationship {
+    /// What the second individual is to the first
+    pub description: String,
+    /// The xrefs of the most recent common ancestor(s)
+    pub common_ancestors: Vec<String>,
+    pub generations_up: usize,
+    pub generations_down: usize,
+    pub half: bool,
+}
+
+/// The xrefs of these individuals, skipping any without one
+pub(crate) fn xrefs(individuals: &[&Individual]) -> Vec<String> {
+    individuals.iter().filter_map(|i| i.xref.clone()).collect()
+}
+
+/// Someone linked to an individual by an ASSO, i.e. a godparent. See
+/// `Gedcom::get_associates`.
+#[derive(Debug)]
+pub struct Associate<'a> {
+    pub individual: &'a Individual,
+    /// The ASSO linking them, with what the associate was, i.e. "Godfather"
+    pub association: &'a Association,
+}
+
+/// The people `individual` lists as associates, in the order listed,
+/// skipping any that aren't in the tree
+pub fn associates<'a>(gedcom: &'a Gedcom, individual: &'a Individual) -> Vec<Associate<'a>> {
+    individual
+        .associations
+        .iter()
+        .filter_map(|association| {
+            Some(Associate {
+                individual: gedcom.find_individual(&association.xref)?,
+                association,
+            })
+        })
+        .collect()
+}
+
+/// The people who list `individual` as an associate, each with the ASSO
+/// that does, in file order
+pub fn associated_by<'a>(gedcom: &'a Gedcom, individual: &Individual) -> Vec<Associate<'a>> {
+    let Some(xref) = individual.xref.as_deref() else {
+        return vec![];
+    };
+    gedcom
+        .individuals
+        .iter()
+        .flat_map(|other| {
+            other
+                .associations
+                .iter()
+                .filter(|a| a.xref == xref)
+                .map(move |association| Associate {
+                    individual: other,
+                    association,
+                })
+        })
+        .collect()
+}
+
+/// An event `individual` was at without it being theirs, i.e. as a witness
+/// or the officiant. See `Gedcom::events_witnessed_by`.
+#[derive(Debug)]
+pub struct WitnessedEvent<'a> {
+    pub event: EventType,
+    /// Whose event it was, if it was an individual's
+    pub individual: Option<&'a Individual>,
+    /// Whose event it was, if it was a family's, i.e. a marriage
+    pub family: Option<&'a Family>,
+    pub detail: &'a EventDetail,
+    /// The event's ASSO for them, with their role, i.e. "Witness"
+    pub association: &'a Association,
+}
+
+/// The events of others, and of families, that name `individual` in an
+/// ASSO, in date order. Undated events come last, in file order.
+pub fn events_witnessed_by<'a>(
+    gedcom: &'a Gedcom,
+    individual: &Individual,
+) -> Vec<WitnessedEvent<'a>> {
+    let Some(xref) = individual.xref.as_deref() else {
+        return vec![];
+    };
+    let mut events: Vec<WitnessedEvent> = vec![];
+    let mut add = |event, detail: &'a EventDetail, principal, family| {
+        for association in detail.associations.iter().filter(|a| a.xref == xref) {
+            events.push(WitnessedEvent {
+                event,
+                individual: principal,
+                family,
+                detail,
+                association,
+            });
+        }
+    };
+    for other in &gedcom.individuals {
+        for event in other.all_events() {
+            if let Some(detail) = event.detail {
+                add(event.event_type, detail, Some(other), None);
+            }
+        }
+    }
+    for family in &gedcom.families {
+        for (tag, detail) in family.tagged_events() {
+            if let (Ok(event), Some(detail)) = (EventType::from_str(tag), detail) {
+                add(event, detail, None, Some(family));
+            }
+        }
+    }
+
+    let date = |e: &WitnessedEvent| {
+        let date = GedcomDate::from_str(e.detail.date.as_deref()?).ok();
+        date.filter(|d| d.start.or(d.end).is_some())
+    };
+    // Sorting is stable, so events on the same date keep their order
+    events.sort_by_cached_key(|e| {
+        let date = date(e);
+        (date.is_none(), date)
+    });
+    events
+}
+
+/// Records for the same person, linked by ALIA in either direction,
+/// through any number of links. See `Gedcom::resolve_aliases`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Identities {
+    /// The xrefs of each person with more than one record, in file order.
+    /// The groups are in the order of their first records.
+    pub groups: Vec<Vec<String>>,
+    /// Which group each of those xrefs is in
+    group: HashMap<String, usize>,
+}
+
+impl Identities {
+    pub fn new(gedcom: &Gedcom) -> Identities {
+        let xrefs: Vec<&str> = gedcom
+            .individuals
+            .iter()
+            .filter_map(|i| i.xref.as_deref())
+            .collect();
+        let index: HashMap<&str, usize> = xrefs.iter().enumerate().map(|(i, x)| (*x, i)).collect();
+
+        // Union-find, always keeping the earliest record as the root
+        fn root(roots: &mut [usize], mut i: usize) -> usize {
+            while roots[i] != i {
+                roots[i] = roots[roots[i]];
+                i = roots[i];
+            }
+            i
+        }
+        let mut roots: Vec<usize> = (0..xrefs.len()).collect();
+        for individual in &gedcom.individuals {
+            let Some(&i) = individual.xref.as_deref().and_then(|x| index.get(x)) else {
+                continue;
+            };
+            for alias in &individual.aliases {
+                if let Some(&j) = index.get(alias.as_str()) {
+                    let (a, b) = (root(&mut roots, i), root(&mut roots, j));
+                    roots[a.max(b)] = a.min(b);
+                }
+            }
+        }
+
+        let mut members: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+        for (i, xref) in xrefs.iter().enumerate() {
+            let r = root(&mut roots, i);
+            members.entry(r).or_default().push(xref.to_string());
+        }
+        let groups: Vec<Vec<String>> = members.into_values().filter(|g| g.len() > 1).collect();
+        let group = groups
+            .iter()
+            .enumerate()
+            .flat_map(|(n, g)| g.iter().map(move |x| (x.clone(), n)))
+            .collect();
+        Identities { groups, group }
+    }
+
+    /// Every record for the person `xref` is, including it, or None if
+    /// they only have the one
+    pub fn group(&self, xref: &str) -> Option<&[String]> {
+        Some(&self.groups[*self.group.get(xref)?])
+    }
+
+    /// The first record for the person `xref` is, to stand for all of them
+    pub fn canonical<'b>(&'b self, xref: &'b str) -> &'b str {
+        self.group(xref).map_or(xref, |g| &g[0])
+    }
+
+    /// Whether the two records are for the same person
+    pub fn same_person(&self, xref: &str, other: &str) -> bool {
+        xref == other
+            || matches!((self.group.get(xref), self.group.get(other)), (Some(a), Some(b)) if a == b)
+    }
+}
+
+/// What else `find_relationship_path_with` may follow, besides parents,
+/// children, and spouses
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PathOptions {
+    /// Step between associates (ASSO), in either direction, i.e. from a
+    /// child to their godparent
+    pub associations: bool,
+    /// Treat records linked by ALIA as one person, so the relatives of
+    /// each are the relatives of all
+    pub aliases: bool,
+}
+
+/// Find the shortest chain of parents, children, and spouses connecting
+/// `individual` to `other`, including both of them.
+pub fn find_relationship_path<'a>(
+    gedcom: &'a Gedcom,
+    individual: &Individual,
+    other: &Individual,
+) -> Option<Vec<&'a Individual>> {
+    find_relationship_path_with(gedcom, individual, other, PathOptions::default())
+}
+
+/// `find_relationship_path`, also following what `options` allows. With
+/// aliases, each person along the way appears as whichever of their
+/// records was reached first, and the path ends at `other` itself.
+pub fn find_relationship_path_with<'a>(
+    gedcom: &'a Gedcom,
+    individual: &Individual,
+    other: &Individual,
+    options: PathOptions,
+) -> Option<Vec<&'a Individual>> {
+    let relatives = Relatives::new(gedcom);
+    let from = relatives.find_individual(individual.xref.as_deref()?)?;
+    let to = other.xref.as_deref()?;
+    let identities = if options.aliases {
+        Identities::new(gedcom)
+    } else {
+        Identities::default()
+    };
+
+    let mut previous: HashMap<&str, &Individual> = HashMap::new();
+    let mut queue: VecDeque<&Individual> = VecDeque::from([from]);
+    let start = from.xref.as_deref()?;
+
+    while let Some(current) = queue.pop_front() {
+        let xref = current.xref.as_deref().unwrap_or_default();
+        if identities.same_person(xref, to) {
+            let mut path = vec![current];
+            let mut step = xref;
+            while let Some(prior) = previous.get(step) {
+                path.push(prior);
+                step = prior.xref.as_deref().unwrap_or_default();
+            }
+            path.reverse();
+            if xref != to {
+                let other = relatives.find_individual(to)?;
+                match path.len() {
+                    1 => path.push(other),
+                    n => path[n - 1] = other,
+                }
+            }
+            return Some(path);
+        }
+
+        let records = match identities.group(xref) {
+            Some(group) => group
+                .iter()
+                .filter_map(|x| relatives.find_individual(x))
+                .collect(),
+            None => vec![current],
+        };
+        let mut neighbours = vec![];
+        for record in records {
+            neighbours.extend(relatives.parents(record));
+            neighbours.extend(relatives.children(record));
+            neighbours.extend(relatives.spouses(record));
+            if options.associations {
+                neighbours.extend(relatives.associates(record));
+            }
+        }
+        for next in neighbours {
+            if let Some(next_xref) = next.xref.as_deref() {
+                if next_xref != start && !previous.contains_key(next_xref) {
+                    previous.insert(next_xref, current);
+                    queue.push_back(next);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Every distinct line of descent from `ancestor` down to `individual`,
+/// each listed a generation at a time, from `individual` up to `ancestor`.
+/// There may be several, where cousins married (pedigree collapse), or none
+/// if `ancestor` isn't one.
+pub fn descent_path<'a>(
+    gedcom: &'a Gedcom,
+    individual: &Individual,
+    ancestor: &Individual,
+) -> Vec<Vec<&'a Individual>> {
+    let mut paths = vec![];
+    let (from, to) = match (individual.xref.as_deref(), ancestor.xref.as_deref()) {
+        (Some(from), Some(to)) if from != to => (from, to),
+        _ => return paths,
+    };
+    let relatives = Relatives::new(gedcom);
+    if let (Some(from), Some(top)) = (
+        relatives.find_individual(from),
+        relatives.find_individual(to),
+    ) {
+        // Only the ancestor's descendants can be on a line up to them, so
+        // the climb never strays into the rest of the pedigree
+        let mut below: HashMap<&str, Vec<&Individual>> = HashMap::new();
+        for individual in &gedcom.individuals {
+            for parent in relatives.parents(individual) {
+                if let Some(xref) = parent.xref.as_deref() {
+                    below.entry(xref).or_default().push(individual);
+                }
+            }
+        }
+        let mut descendants: HashSet<&str> = HashSet::from([to]);
+        let mut queue: VecDeque<&Individual> = VecDeque::from([top]);
+        while let Some(current) = queue.pop_front() {
+            let children = current.xref.as_deref().and_then(|x| below.get(x));
+            for child in children.into_iter().flatten() {
+                if let Some(xref) = child.xref.as_deref() {
+                    if descendants.insert(xref) {
+                        queue.push_back(child);
+                    }
+                }
+            }
+        }
+
+        let mut line = vec![from];
+        ascend(&relatives, &descendants, &mut line, to, &mut paths);
+    }
+    paths
+}
+
+/// Climb from the last individual in `line` towards `ancestor`, through
+/// their `descendants`, collecting each line that reaches them
+fn ascend<'a>(
+    relatives: &Relatives<'a>,
+    descendants: &HashSet<&str>,
+    line: &mut Vec<&'a Individual>,
+    ancestor: &str,
+    paths: &mut Vec<Vec<&'a Individual>>,
+) {
+    let current = line[line.len() - 1];
+    for parent in relatives.parents(current) {
+        if !parent
+            .xref
+            .as_deref()
+            .is_some_and(|x| descendants.contains(x))
+        {
+            continue;
+        }
+        // Someone who is their own ancestor would go on forever
+        if line.iter().any(|i| i.xref == parent.xref) {
+            continue;
+        }
+        line.push(parent);
+        if parent.xref.as_deref() == Some(ancestor) {
+            paths.push(line.clone());
+        } else {
+            ascend(relatives, descendants, line, ancestor, paths);
+        }
+        line.pop();
+    }
+}
+
+/// Who is related to whom, by index in `Gedcom::individuals`, worked out
+/// in one pass over the tree. `Gedcom::parents` and the like look each
+/// relative up afresh, which is fine for one person, but a search that
+/// visits much of a large tree would spend its time rescanning it. Each
+/// list here is the one `Gedcom` would give, in the same order.
+pub(crate) struct Relatives<'a> {
+    gedcom: &'a Gedcom,
+    /// The first individual with each xref, as `Gedcom::find_individual`
+    /// would find
+    index: HashMap<&'a str, usize>,
+    /// By individual, the families they're a child in, in file order
+    child_families: Vec<Vec<usize>>,
+    /// By individual, the families they're a spouse in, in file order
+    spouse_families: Vec<Vec<usize>>,
+    /// By family xref, the individuals whose FAMC points to it, in file order
+    linked: HashMap<&'a str, Vec<usize>>,
+    /// By xref, the individuals with an ASSO pointing to it, once for each
+    associated_by: HashMap<&'a str, Vec<usize>>,
+}
+
+impl<'a> Relatives<'a> {
+    pub(crate) fn new(gedcom: &'a Gedcom) -> Relatives<'a> {
+        let mut index: HashMap<&str, usize> = HashMap::new();
+        let mut everyone: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (i, individual) in gedcom.individuals.iter().enumerate() {
+            if let Some(xref) = individual.xref.as_deref() {
+                index.entry(xref).or_insert(i);
+                everyone.entry(xref).or_default().push(i);
+            }
+        }
+        let mut families: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (f, family) in gedcom.families.iter().enumerate() {
+            families.entry(family.xref.as_str()).or_default().push(f);
+        }
+
+        // Families are linked from either side: the family's HUSB, WIFE and
+        // CHIL, and the individual's FAMS and FAMC
+        let mut child_families: Vec<Vec<usize>> = vec![vec![]; gedcom.individuals.len()];
+        let mut spouse_families: Vec<Vec<usize>> = vec![vec![]; gedcom.individuals.len()];
+        for (f, family) in gedcom.families.iter().enumerate() {
+            for child in &family.children {
+                for &i in everyone.get(child.as_str()).into_iter().flatten() {
+                    child_families[i].push(f);
+                }
+            }
+            for spouse in family.husband_xref().into_iter().chain(family.wife_xref()) {
+                for &i in everyone.get(spouse).into_iter().flatten() {
+                    spouse_families[i].push(f);
+                }
+            }
+        }
+        let mut linked: HashMap<&str, Vec<usize>> = HashMap::new();
+        let mut associated_by: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (i, individual) in gedcom.individuals.iter().enumerate() {
+            for famc in &individual.famc {
+                let xref = famc.xref.as_str();
+                child_families[i].extend(families.get(xref).into_iter().flatten());
+                let children = linked.entry(xref).or_default();
+                if children.last() != Some(&i) {
+                    children.push(i);
+                }
+            }
+            for fams in &individual.fams {
+                spouse_families[i].extend(families.get(fams.xref.as_str()).into_iter().flatten());
+            }
+            for association in &individual.associations {
+                associated_by
+                    .entry(association.xref.as_str())
+                    .or_default()
+                    .push(i);
+            }
+        }
+        for families in child_families.iter_mut().chain(&mut spouse_families) {
+            families.sort_unstable();
+            families.dedup();
+        }
+
+        Relatives {
+            gedcom,
+            index,
+            child_families,
+            spouse_families,
+            linked,
+            associated_by,
+        }
+    }
+
+    /// `Gedcom::find_individual`
+    pub(crate) fn find_individual(&self, xref: &str) -> Option<&'a Individual> {
+        Some(&self.gedcom.individuals[*self.index.get(xref)?])
+    }
+
+    /// `Gedcom::spouse_families`
+    pub(crate) fn spouse_families(&self, individual: &Individual) -> Vec<&'a Family> {
+        match self.position(individual) {
+            Some(i) => self.spouse_families[i]
+                .iter()
+                .map(|&f| &self.gedcom.families[f])
+                .collect(),
+            None => self.gedcom.spouse_families(individual),
+        }
+    }
+
+    /// `Gedcom::parents`
+    pub(crate) fn parents(&self, individual: &Individual) -> Vec<&'a Individual> {
+        let Some(i) = self.position(individual) else {
+            return self.gedcom.parents(individual);
+        };
+        once(self.child_families[i].iter().flat_map(|&f| {
+            let family = &self.gedcom.families[f];
+            family
+                .husband_xref()
+                .into_iter()
+                .chain(family.wife_xref())
+                .filter_map(|xref| self.find_individual(xref))
+        }))
+    }
+
+    /// `Gedcom::children`
+    pub(crate) fn children(&self, individual: &Individual) -> Vec<&'a Individual> {
+        let Some(i) = self.position(individual) else {
+            return self.gedcom.children(individual);
+        };
+        once(self.spouse_families[i].iter().flat_map(|&f| {
+            let family = &self.gedcom.families[f];
+            let listed = family
+                .children
+                .iter()
+                .filter_map(|xref| self.find_individual(xref));
+            let linked = self.linked.get(family.xref.as_str()).into_iter().flatten();
+            listed.chain(linked.map(|&c| &self.gedcom.individuals[c]))
+        }))
+    }
+
+    /// `Gedcom::spouses`
+    pub(crate) fn spouses(&self, individual: &Individual) -> Vec<&'a Individual> {
+        let Some(i) = self.position(individual) else {
+            return self.gedcom.spouses(individual);
+        };
+        let xref = individual.xref.as_deref();
+        once(self.spouse_families[i].iter().flat_map(|&f| {
+            let family = &self.gedcom.families[f];
+            family
+                .husband_xref()
+                .into_iter()
+                .chain(family.wife_xref())
+                .filter(move |&other| Some(other) != xref)
+                .filter_map(|other| self.find_individual(other))
+        }))
+    }
+
+    /// The individuals `associates` and `associated_by` give, in that order
+    pub(crate) fn associates(&self, individual: &Individual) -> Vec<&'a Individual> {
+        let listed = individual
+            .associations
+            .iter()
+            .filter_map(|a| self.find_individual(&a.xref));
+        let by = individual
+            .xref
+            .as_deref()
+            .and_then(|xref| self.associated_by.get(xref))
+            .into_iter()
+            .flatten()
+            .map(|&i| &self.gedcom.individuals[i]);
+        listed.chain(by).collect()
+    }
+
+    /// Where `individual` is in `Gedcom::individuals`, unless they aren't
+    /// the record their xref finds, i.e. a copy or a duplicate
+    fn position(&self, individual: &Individual) -> Option<usize> {
+        let i = *self.index.get(individual.xref.as_deref()?)?;
+        std::ptr::eq(&self.gedcom.individuals[i], individual).then_some(i)
+    }
+}
+
+/// The people, once each by xref, in the order given
+fn once<'a>(people: impl Iterator<Item = &'a Individual>) -> Vec<&'a Individual> {
+    let mut once: Vec<&Individual> = vec![];
+    for person in people {
+        if !once.iter().any(|o| o.xref == person.xref) {
+            once.push(person);
+        }
+    }
+    once
+}
+
+/// The ancestors of everyone in a tree, worked out ahead of time, so
+/// finding how two of them are related is a matter of intersecting two
+/// lists. See `Gedcom::precompute_kinship`.
+#[derive(Clone, Debug, Default)]
+pub struct Kinship {
+    /// Each individual covered, by xref, and their index in
+    /// `Gedcom::individuals`
+    index: HashMap<String, usize>,
+    /// By index, each individual's ancestors and themselves, as (index,
+    /// generation), sorted by index. Empty for those not covered.
+    ancestors: Vec<Vec<(usize, usize)>>,
+}
+
+impl Kinship {
+    /// Work out the ancestors of everyone connected to `root` by any chain
+    /// of parents, children, and spouses, spread over the available cores
+    pub fn new(gedcom: &Gedcom, root: &str) -> Kinship {
+        let index: HashMap<&str, usize> = gedcom
+            .individuals
+            .iter()
+            .enumerate()
+            .filter_map(|(i, individual)| Some((individual.xref.as_deref()?, i)))
+            .collect();
+
+        let family_index: HashMap<&str, usize> = gedcom
+            .families
+            .iter()
+            .enumerate()
+            .map(|(f, family)| (family.xref.as_str(), f))
+            .collect();
+
+        // Each family's spouses and children, by index, from its HUSB, WIFE
+        // and CHIL and from each individual's FAMC, and anyone else who
+        // says with FAMS that they're a spouse
+        let mut spouses: Vec<Vec<usize>> = vec![];
+        let mut children: Vec<Vec<usize>> = vec![];
+        for family in &gedcom.families {
+            spouses.push(
+                family
+                    .husband_xref()
+                    .into_iter()
+                    .chain(family.wife_xref())
+                    .filter_map(|xref| index.get(xref).copied())
+                    .collect(),
+            );
+            children.push(
+                family
+                    .children
+                    .iter()
+                    .filter_map(|xref| index.get(xref.as_str()).copied())
+                    .collect(),
+            );
+        }
+        let mut linked: Vec<Vec<usize>> = vec![vec![]; gedcom.families.len()];
+        for (i, individual) in gedcom.individuals.iter().enumerate() {
+            for famc in &individual.famc {
+                if let Some(&f) = family_index.get(famc.xref.as_str()) {
+                    if !children[f].contains(&i) {
+                        children[f].push(i);
+                    }
+                }
+            }
+            for fams in &individual.fams {
+                if let Some(&f) = family_index.get(fams.xref.as_str()) {
+                    linked[f].push(i);
+                }
+            }
+        }
+
+        // Each individual's parents and families, and everyone in each family
+        let mut parents: Vec<Vec<usize>> = vec![vec![]; gedcom.individuals.len()];
+        let mut families: Vec<Vec<usize>> = vec![vec![]; gedcom.individuals.len()];
+        let mut members: Vec<Vec<usize>> = vec![];
+        for (f, (spouses, children)) in spouses.into_iter().zip(children).enumerate() {
+            for &child in &children {
+                for &parent in &spouses {
+                    if !parents[child].contains(&parent) {
+                        parents[child].push(parent);
+                    }
+                }
+            }
+            let mut family: Vec<usize> = spouses.into_iter().chain(children).collect();
+            for &i in &linked[f] {
+                if !family.contains(&i) {
+                    family.push(i);
+                }
+            }
+            for &member in &family {
+                families[member].push(f);
+            }
+            members.push(family);
+        }
+
+        // Everyone in the same tree as the root
+        let mut tree: Vec<usize> = vec![];
+        let mut seen = vec![false; gedcom.individuals.len()];
+        let mut queue: VecDeque<usize> = index.get(root).copied().into_iter().collect();
+        while let Some(i) = queue.pop_front() {
+            if std::mem::replace(&mut seen[i], true) {
+                continue;
+            }
+            tree.push(i);
+            for &f in &families[i] {
+                queue.extend(members[f].iter().filter(|m| !seen[**m]));
+            }
+        }
+
+        let mut ancestors: Vec<Vec<(usize, usize)>> = vec![vec![]; gedcom.individuals.len()];
+        let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk = tree.len().div_ceil(threads).max(1);
+        let parents = &parents;
+        if threads == 1 {
+            // No need for a thread, and some platforms, i.e. WebAssembly in
+            // the browser, can't start one
+            for &i in &tree {
+                ancestors[i] = ancestors_of(parents, i);
+            }
+        } else {
+            std::thread::scope(|scope| {
+                let workers: Vec<_> = tree
+                    .chunks(chunk)
+                    .map(|chunk| {
+                        scope.spawn(move || {
+                            chunk
+                                .iter()
+                                .map(|&i| (i, ancestors_of(parents, i)))
+                                .collect::<Vec<_>>()
+                        })
+                    })
+                    .collect();
+                for worker in workers {
+                    for (i, list) in worker.join().unwrap() {
+                        ancestors[i] = list;
+                    }
+                }
+            });
+        }
+
+        Kinship {
+            index: tree
+                .iter()
+                .filter_map(|&i| Some((gedcom.individuals[i].xref.clone()?, i)))
+                .collect(),
+            ancestors,
+        }
+    }
+
+    /// Whether the individual with this xref is covered
+    pub fn contains(&self, xref: &str) -> bool {
+        self.index.contains_key(xref)
+    }
+
+    /// Whether the individual with the `ancestor` xref is an ancestor of
+    /// the one with the `descendant` xref, or None if either isn't covered
+    pub fn is_ancestor(&self, ancestor: &str, descendant: &str) -> Option<bool> {
+        let ancestor = *self.index.get(ancestor)?;
+        let ancestors = &self.ancestors[*self.index.get(descendant)?];
+        let found = ancestors.binary_search_by_key(&ancestor, |&(i, _)| i);
+        Some(found.is_ok_and(|i| ancestors[i].1 > 0))
+    }
+
+    /// The closest ancestors two individuals share, or None if either isn't
+    /// covered
+    fn common<'a>(
+        &self,
+        gedcom: &'a Gedcom,
+        from: &str,
+        to: &str,
+    ) -> Option<Vec<(&'a str, usize, usize)>> {
+        let up = &self.ancestors[*self.index.get(from)?];
+        let down = &self.ancestors[*self.index.get(to)?];
+
+        // Both are sorted by index, so walk them together
+        let mut shared = vec![];
+        let (mut a, mut b) = (0, 0);
+        while a < up.len() && b < down.len() {
+            match up[a].0.cmp(&down[b].0) {
+                std::cmp::Ordering::Less => a += 1,
+                std::cmp::Ordering::Greater => b += 1,
+                std::cmp::Ordering::Equal => {
+                    if let Some(xref) = gedcom.individuals[up[a].0].xref.as_deref() {
+                        shared.push((xref, up[a].1, down[b].1));
+                    }
+                    a += 1;
+                    b += 1;
+                }
+            }
+        }
+        Some(closest(shared.into_iter()))
+    }
+
+    /// An individual's ancestors, by xref, with how many generations back
+    /// they are, or None if they aren't covered
+    fn generations<'a>(&self, gedcom: &'a Gedcom, xref: &str) -> Option<HashMap<&'a str, usize>> {
+        let ancestors = &self.ancestors[*self.index.get(xref)?];
+        Some(
+            ancestors
+                .iter()
+                .filter_map(|&(i, g)| Some((gedcom.individuals[i].xref.as_deref()?, g)))
+                .collect(),
+        )
+    }
+}
+
+/// The ancestors of the individual at `start`, and themselves, as (index,
+/// generation), sorted by index
+fn ancestors_of(parents: &[Vec<usize>], start: usize) -> Vec<(usize, usize)> {
+    let mut generations: HashMap<usize, usize> = HashMap::from([(start, 0)]);
+    let mut queue: VecDeque<usize> = VecDeque::from([start]);
+    while let Some(i) = queue.pop_front() {
+        let generation = generations[&i];
+        for &parent in &parents[i] {
+            if let std::collections::hash_map::Entry::Vacant(entry) = generations.entry(parent) {
+                entry.insert(generation + 1);
+                queue.push_back(parent);
+            }
+        }
+    }
+    let mut ancestors: Vec<(usize, usize)> = generations.into_iter().collect();
+    ancestors.sort();
+    ancestors
+}
+
+/// A person's relatives, grouped as a family view shows them. See
+/// `Gedcom::extended_family`.
+///
+/// Each group is in the order the relatives were found, once each, and
+/// never includes the person themselves. Half-relatives are grouped with
+/// full ones, i.e. half-siblings are siblings.
+#[derive(Debug, Default)]
+pub struct ExtendedFamily<'a> {
+    pub parents: Vec<&'a Individual>,
+    pub grandparents: Vec<&'a Individual>,
+    pub siblings: Vec<&'a Individual>,
+    /// Parents' siblings
+    pub aunts_uncles: Vec<&'a Individual>,
+    /// Aunts' and uncles' children
+    pub first_cousins: Vec<&'a Individual>,
+    /// Siblings' children
+    pub nieces_nephews: Vec<&'a Individual>,
+    pub spouses: Vec<&'a Individual>,
+    pub children: Vec<&'a Individual>,
+    pub grandchildren: Vec<&'a Individual>,
+    /// Spouses' parents
+    pub parents_in_law: Vec<&'a Individual>,
+    /// Spouses' siblings, and siblings' spouses
+    pub siblings_in_law: Vec<&'a Individual>,
+    /// Children's spouses
+    pub children_in_law: Vec<&'a Individual>,
+}
+
+/// Group the relatives of `individual` into kin classes
+pub fn extended_family<'a>(gedcom: &'a Gedcom, individual: &Individual) -> ExtendedFamily<'a> {
+    let xref = individual.xref.as_deref();
+    let each = |people: &[&'a Individual],
+                relatives: &dyn Fn(&'a Individual) -> Vec<&'a Individual>| {
+        distinct(xref, people.iter().flat_map(|p| relatives(p)))
+    };
+    let parents = |i: &'a Individual| gedcom.parents(i);
+    let children = |i: &'a Individual| gedcom.children(i);
+    let siblings = |i: &'a Individual| gedcom.siblings(i);
+    let spouses = |i: &'a Individual| gedcom.spouses(i);
+
+    let mut family = ExtendedFamily {
+        parents: distinct(xref, gedcom.parents(individual)),
+        siblings: distinct(xref, gedcom.siblings(individual)),
+        spouses: distinct(xref, gedcom.spouses(individual)),
+        children: distinct(xref, gedcom.children(individual)),
+        ..Default::default()
+    };
+    family.grandparents = each(&family.parents, &parents);
+    family.aunts_uncles = each(&family.parents, &siblings);
+    family.first_cousins = each(&family.aunts_uncles, &children);
+    family.nieces_nephews = each(&family.siblings, &children);
+    family.grandchildren = each(&family.children, &children);
+    family.parents_in_law = each(&family.spouses, &parents);
+    family.siblings_in_law = distinct(
+        xref,
+        each(&family.spouses, &siblings)
+            .into_iter()
+            .chain(each(&family.siblings, &spouses)),
+    );
+    family.children_in_law = each(&family.children, &spouses);
+    family
+}
+
+/// The people, once each, leaving out the individual with `xref`
+fn distinct<'a>(
+    xref: Option<&str>,
+    people: impl IntoIterator<Item = &'a Individual>,
+) -> Vec<&'a Individual> {
+    let mut distinct: Vec<&Individual> = vec![];
+    for person in people {
+        if person.xref.as_deref() != xref && !distinct.iter().any(|d| d.xref == person.xref) {
+            distinct.push(person);
+        }
+    }
+    distinct
+}
+
+/// The earliest known ancestor on a line: someone with no parents recorded.
+/// See `Gedcom::brick_walls`.
+#[derive(Debug)]
+pub struct BrickWall<'a> {
+    pub individual: &'a Individual,
+    /// How many generations back they are: 1 for a parent, 2 for a
+    /// grandparent, and so on
+    pub generation: usize,
+    /// The line from the root to them, i.e. "father's father's mother"
+    pub line: String,
+}
+
+/// Find the ancestors of `root` with no parents recorded, nearest first:
+/// the frontier for further research. An ancestor reached along more than
+/// one line is reported once, along the shortest. If `root` has no parents
+/// recorded, they are their own brick wall, at generation 0.
+pub fn brick_walls<'a>(gedcom: &'a Gedcom, root: &Individual) -> Vec<BrickWall<'a>> {
+    let root = match root.xref.as_deref().and_then(|x| gedcom.find_individual(x)) {
+        Some(root) => root,
+        None => return vec![],
+    };
+
+    let mut walls = vec![];
+    let mut seen: HashSet<&str> = root.xref.as_deref().into_iter().collect();
+    let mut queue: VecDeque<(&Individual, usize, String)> =
+        VecDeque::from([(root, 0, "self".to_string())]);
+
+    while let Some((individual, generation, line)) = queue.pop_front() {
+        let parents = gedcom.parents(individual);
+        if parents.is_empty() {
+            walls.push(BrickWall {
+                individual,
+                generation,
+                line: line.clone(),
+            });
+        }
+        for parent in parents {
+            if let Some(xref) = parent.xref.as_deref() {
+                if seen.insert(xref) {
+                    let relation = gendered(&parent.gender, "father", "mother", "parent");
+                    let line = if generation == 0 {
+                        relation.to_string()
+                    } else {
+                        format!("{}'s {}", line, relation)
+                    };
+                    queue.push_back((parent, generation + 1, line));
+                }
+            }
+        }
+    }
+
+    walls
+}
+
+/// Of the ancestors two individuals share, as (xref, generations above the
+/// first, generations above the second), those with the fewest generations
+/// between them
+fn closest<'a>(
+    shared: impl Iterator<Item = (&'a str, usize, usize)>,
+) -> Vec<(&'a str, usize, usize)> {
+    let mut closest: Option<usize> = None;
+    let mut common: Vec<(&str, usize, usize)> = vec![];
+    for (xref, g1, g2) in shared {
+        match closest {
+            Some(total) if total < g1 + g2 => continue,
+            Some(total) if total > g1 + g2 => common.clear(),
+            _ => {}
+        }
+        closest = Some(g1 + g2);
+        common.push((xref, g1, g2));
+    }
+    common
+}
+
+/// Which parent a single line of descent follows. See `direct_line`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Lineage {
+    /// Father to father, as a Y chromosome is passed down
+    Paternal,
+    /// Mother to mother, as mitochondrial DNA is passed down
+    Maternal,
+}
+
+/// The father, father's father, and so on, of `individual`, or their
+/// mother, mother's mother, and so on, nearest first, as far back as the
+/// tree goes. Only birth families are followed, so an adoptive or foster
+/// father doesn't continue a paternal line; a FAMC without a PEDI is taken
+/// as a birth family.
+pub fn direct_line<'a>(
+    gedcom: &'a Gedcom,
+    individual: &Individual,
+    lineage: Lineage,
+) -> Vec<&'a Individual> {
+    let mut ancestors: Vec<&Individual> = vec![];
+    let mut current = individual
+        .xref
+        .as_deref()
+        .and_then(|x| gedcom.find_individual(x));
+    while let Some(child) = current {
+        let parent = birth_families(gedcom, child)
+            .into_iter()
+            .find_map(|family| {
+                let xref = match lineage {
+                    Lineage::Paternal => family.husband_xref(),
+                    Lineage::Maternal => family.wife_xref(),
+                };
+                gedcom.find_individual(xref?)
+            });
+        // Stop at a loop, rather than going round it forever
+        current = parent
+            .filter(|p| p.xref != individual.xref && !ancestors.iter().any(|a| a.xref == p.xref));
+        ancestors.extend(current);
+    }
+    ancestors
+}
+
+/// The families `individual` was born into
+pub(crate) fn birth_families<'a>(gedcom: &'a Gedcom, individual: &Individual) -> Vec<&'a Family> {
+    gedcom
+        .child_families(individual)
+        .into_iter()
+        .filter(|family| {
+            individual
+                .famc
+                .iter()
+                .filter(|link| link.xref == family.xref)
+                .all(|link| matches!(link.pedigree, None | Some(Pedigree::Birth)))
+        })
+        .collect()
+}
+
+/// A family an individual is a child of, and how. See
+/// `Gedcom::get_parents_with_pedigree`.
+#[derive(Debug)]
+pub struct ParentFamily<'a> {
+    pub family: &'a Family,
+    /// The PEDI on the individual's FAMC link to the family, or birth if
+    /// there isn't one
+    pub pedigree: Pedigree,
+    /// The husband and wife, those who are in the file
+    pub parents: Vec<&'a Individual>,
+}
+
+/// Every family `individual` is a child of, with how they're its child
+pub fn parents_with_pedigree<'a>(
+    gedcom: &'a Gedcom,
+    individual: &Individual,
+) -> Vec<ParentFamily<'a>> {
+    gedcom
+        .child_families(individual)
+        .into_iter()
+        .map(|family| {
+            let pedigree = individual
+                .famc
+                .iter()
+                .filter(|link| link.xref == family.xref)
+                .find_map(|link| link.pedigree.clone())
+                .unwrap_or_default();
+            let parents = family
+                .husband_xref()
+                .into_iter()
+                .chain(family.wife_xref())
+                .filter_map(|xref| gedcom.find_individual(xref))
+                .collect();
+            ParentFamily {
+                family,
+                pedigree,
+                parents,
+            }
+        })
+        .collect()
+}
+
+/// Which of an individual's parents to follow. See `parents_with`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Parentage {
+    /// Every parent: birth, adoptive, foster and sealing
+    #[default]
+    All,
+    /// Birth parents only, as for a medical history. A FAMC without a PEDI
+    /// is taken as a birth family.
+    Biological,
+    /// Adoptive parents, for those who were adopted, and otherwise birth
+    /// parents: the legal line
+    Adoptive,
+}
+
+/// The parents of `individual` that `parentage` follows, from every family
+/// they're a child of
+pub fn parents_with<'a>(
+    gedcom: &'a Gedcom,
+    individual: &Individual,
+    parentage: Parentage,
+) -> Vec<&'a Individual> {
+    let families = parents_with_pedigree(gedcom, individual);
+    let adopted = families.iter().any(|f| f.pedigree == Pedigree::Adopted);
+    let followed = families.iter().filter(|f| match parentage {
+        Parentage::All => true,
+        Parentage::Adoptive if adopted => f.pedigree == Pedigree::Adopted,
+        Parentage::Biological | Parentage::Adoptive => f.pedigree == Pedigree::Birth,
+    });
+
+    let mut parents: Vec<&Individual> = vec![];
+    for parent in followed.flat_map(|f| f.parents.iter()) {
+        if !parents.iter().any(|p| p.xref == parent.xref) {
+            parents.push(parent);
+        }
+    }
+    parents
+}
+
+/// The descendants of `root` who carry `surname`, nearest generation first,
+/// following only children who have it, ignoring case, in any of their
+/// names. A daughter who carries it is included, though her children only
+/// are if they carry it too.
+pub fn descendants_carrying_surname<'a>(
+    gedcom: &'a Gedcom,
+    root: &Individual,
+    surname: &str,
+) -> Vec<&'a Individual> {
+    let surname = surname.trim().to_lowercase();
+    let carries = |individual: &Individual| {
+        individual.names.iter().any(|name| {
+            names::surname(&name.name).map(|s| s.to_lowercase()) == Some(surname.clone())
+        })
+    };
+
+    let mut found: Vec<&Individual> = vec![];
+    let mut queue: VecDeque<&Individual> = VecDeque::from(gedcom.children(root));
+    while let Some(child) = queue.pop_front() {
+        if child.xref == root.xref || found.iter().any(|f| f.xref == child.xref) {
+            continue;
+        }
+        if carries(child) {
+            found.push(child);
+            queue.extend(gedcom.children(child));
+        }
+    }
+    found
+}
+
+/// Every ancestor of `xref`, including themselves at generation 0, with
+/// how many generations back they are. Where someone is an ancestor more
+/// than once, the closest generation wins.
+fn ancestor_generations<'a>(gedcom: &'a Gedcom, xref: &'a str) -> HashMap<&'a str, usize> {
+    let mut generations: HashMap<&str, usize> = HashMap::from([(xref, 0)]);
+    let mut queue: VecDeque<(&str, usize)> = VecDeque::from([(xref, 0)]);
+
+    while let Some((xref, generation)) = queue.pop_front() {
+        if let Some(individual) = gedcom.find_individual(xref) {
+            for parent in gedcom.parents(individual) {
+                if let Some(parent) = parent.xref.as_deref() {
+                    if !generations.contains_key(parent) {
+                        generations.insert(parent, generation + 1);
+                        queue.push_back((parent, generation + 1));
+                    }
+                }
+            }
+        }
+    }
+
+    generations
+}
+
+/// The child of the common ancestor(s) that the line in `generations`
+/// descends through
+fn line_child<'a>(
+    gedcom: &'a Gedcom,
+    xref: &'a str,
+    generation: usize,
+    common_ancestors: &[&Individual],
+) -> Option<&'a Individual> {
+    let generations = match gedcom
+        .kinship
+        .as_ref()
+        .and_then(|k| k.generations(gedcom, xref))
+    {
+        Some(generations) => generations,
+        None => ancestor_generations(gedcom, xref),
+    };
+    generations
+        .iter()
+        .filter(|(_, &g)| g + 1 == generation)
+        .filter_map(|(xref, _)| gedcom.find_individual(xref))
+        .find(|child| {
+            gedcom
+                .parents(child)
+                .iter()
+                .any(|p| common_ancestors.iter().any(|a| a.xref == p.xref))
+        })
+}
+
+/// Do these two individuals share a family as children?
+fn same_parents(gedcom: &Gedcom, a: &Option<&Individual>, b: &Option<&Individual>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            let parents = gedcom.parents(a);
+            let others = gedcom.parents(b);
+            parents.len() == others.len()
+                && parents
+                    .iter()
+                    .all(|p| others.iter().any(|o| o.xref == p.xref))
+        }
+        _ => true,
+    }
+}
+
+/// Describe what someone `down` generations below a common ancestor is to
+/// someone `up` generations below it
+fn describe(up: usize, down: usize, half: bool, gender: &Gender) -> String {
+    let half = if half { "half-" } else { "" };
+    match (up, down) {
+        (0, 0) => "self".to_string(),
+        (up, 0) => generational(up, gendered(gender, "father", "mother", "parent"), "grand"),
+        (0, down) => generational(down, gendered(gender, "son", "daughter", "child"), "grand"),
+        (1, 1) => format!(
+            "{}{}",
+            half,
+            gendered(gender, "brother", "sister", "sibling")
+        ),
+        (up, 1) => format!(
+            "{}{}",
+            half,
+            generational(up - 1, gendered(gender, "uncle", "aunt", "aunt/uncle"), "")
+        ),
+        (1, down) => format!(
+            "{}{}",
+            half,
+            generational(
+                down - 1,
+                gendered(gender, "nephew", "niece", "nephew/niece"),
+                ""
+            )
+        ),
+        (up, down) => {
+            let degree = up.min(down) - 1;
+            let removed = match up.max(down) - up.min(down) {
+                0 => String::new(),
+                1 => " once removed".to_string(),
+                2 => " twice removed".to_string(),
+                n => format!(" {} times removed", n),
+            };
+            let half = if half.is_empty() { "" } else { "half " };
+            format!("{}{} cousin{}", half, ordinal(degree), removed)
+        }
+    }
+}
+
+/// "father", "grandfather", "great-grandfather", "2nd great-grandfather"...
+/// `grand` is added from the second generation on; without it, the greats
+/// start there instead (as in "great-uncle").
+fn generational(generations: usize, noun: &str, grand: &str) -> String {
+    let greats = if grand.is_empty() {
+        generations - 1
+    } else {
+        generations.saturating_sub(2)
+    };
+    let grand = if generations > 1 { grand } else { "" };
+    match greats {
+        0 => format!("{}{}", grand, noun),
+        1 => format!("great-{}{}", grand, noun),
+        n => format!("{} great-{}{}", ordinal(n), grand, noun),
+    }
+}
+
+fn gendered<'a>(gender: &Gender, male: &'a str, female: &'a str, neutral: &'a str) -> &'a str {
+    match gender {
+        Gender::Male => male,
+        Gender::Female => female,
+        Gender::Nonbinary | Gender::Unknown | Gender::Other(_) => neutral,
+    }
+}
+
+pub(crate) fn ordinal(n: usize) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+    format!("{}{}", n, suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::{parse_gedcom, parse_gedcom_from_str};
+    use crate::search::SearchQuery;
+
+    #[test]
+    fn descriptions() {
+        assert!(describe(0, 0, false, &Gender::Male) == "self");
+        assert!(describe(1, 0, false, &Gender::Male) == "father");
+        assert!(describe(2, 0, false, &Gender::Female) == "grandmother");
+        assert!(describe(3, 0, false, &Gender::Unknown) == "great-grandparent");
+        assert!(describe(1, 0, false, &Gender::Nonbinary) == "parent");
+        assert!(describe(1, 1, false, &Gender::Other("Intersex".to_string())) == "sibling");
+        assert!(describe(4, 0, false, &Gender::Male) == "2nd great-grandfather");
+        assert!(describe(0, 1, false, &Gender::Female) == "daughter");
+        assert!(describe(0, 3, false, &Gender::Male) == "great-grandson");
+        assert!(describe(1, 1, true, &Gender::Male) == "half-brother");
+        assert!(describe(2, 1, false, &Gender::Female) == "aunt");
+        assert!(describe(3, 1, false, &Gender::Male) == "great-uncle");
+        assert!(describe(1, 2, false, &Gender::Male) == "nephew");
+        assert!(describe(1, 4, false, &Gender::Female) == "2nd great-niece");
+        assert!(describe(2, 2, false, &Gender::Male) == "1st cousin");
+        assert!(describe(2, 3, false, &Gender::Male) == "1st cousin once removed");
+        assert!(describe(5, 3, true, &Gender::Male) == "half 2nd cousin twice removed");
+        assert!(ordinal(11) == "11th" && ordinal(22) == "22nd");
+    }
+
+    #[test]
+    fn find_relationship() {
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @I1@ INDI
+1 SEX M
+1 FAMS @F1@
+0 @I2@ INDI
+1 SEX F
+1 FAMS @F1@
+0 @I3@ INDI
+1 SEX M
+1 FAMC @F1@
+1 FAMS @F2@
+0 @I4@ INDI
+1 SEX F
+1 FAMC @F1@
+1 FAMS @F3@
+0 @I5@ INDI
+1 SEX M
+1 FAMC @F2@
+0 @I6@ INDI
+1 SEX F
+1 FAMC @F3@
+0 @I7@ INDI
+1 SEX F
+1 FAMS @F2@
+0 @F1@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 CHIL @I3@
+1 CHIL @I4@
+0 @F2@ FAM
+1 HUSB @I3@
+1 WIFE @I7@
+1 CHIL @I5@
+0 @F3@ FAM
+1 WIFE @I4@
+1 CHIL @I6@
+0 TRLR
+",
+        )
+        .unwrap();
+        let individual = |xref: &str| gedcom.find_individual(xref).unwrap();
+
+        let cousins = gedcom
+            .find_relationship(individual("@I5@"), individual("@I6@"))
+            .unwrap();
+        assert!(cousins.description == "1st cousin");
+        assert!(cousins.common_ancestors.len() == 2);
+        assert!(!cousins.half);
+
+        let grandfather = gedcom
+            .find_relationship(individual("@I5@"), individual("@I1@"))
+            .unwrap();
+        assert!(grandfather.description == "grandfather");
+        assert!(grandfather.generations_up == 2 && grandfather.generations_down == 0);
+
+        let aunt = gedcom
+            .find_relationship(individual("@I5@"), individual("@I4@"))
+            .unwrap();
+        assert!(aunt.description == "aunt");
+
+        let wife = gedcom
+            .find_relationship(individual("@I3@"), individual("@I7@"))
+            .unwrap();
+        assert!(wife.description == "wife");
+        assert!(wife.common_ancestors.is_empty());
+        assert!(gedcom
+            .find_relationship(individual("@I6@"), individual("@I7@"))
+            .is_none());
+
+        // From a cousin, through their mother and grandparents, to their
+        // uncle's wife
+        let path: Vec<&str> = gedcom
+            .find_relationship_path(individual("@I6@"), individual("@I7@"))
+            .unwrap()
+            .iter()
+            .map(|i| i.xref.as_deref().unwrap())
+            .collect();
+        assert!(path.len() == 5);
+        assert!(path[0] == "@I6@" && path[1] == "@I4@" && path[3] == "@I3@" && path[4] == "@I7@");
+
+        let walls = gedcom.brick_walls(individual("@I5@"));
+        let walls: Vec<(&str, usize, &str)> = walls
+            .iter()
+            .map(|w| {
+                (
+                    w.individual.xref.as_deref().unwrap(),
+                    w.generation,
+                    w.line.as_str(),
+                )
+            })
+            .collect();
+        assert!(
+            walls
+                == [
+                    ("@I7@", 1, "mother"),
+                    ("@I1@", 2, "father's father"),
+                    ("@I2@", 2, "father's mother")
+                ]
+        );
+        let walls = gedcom.brick_walls(individual("@I1@"));
+        assert!(walls.len() == 1 && walls[0].generation == 0 && walls[0].line == "self");
+    }
+
+    #[test]
+    fn by_xref() {
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @I1@ INDI
+1 SEX M
+1 FAMS @F1@
+0 @I2@ INDI
+1 SEX F
+1 FAMS @F1@
+0 @I3@ INDI
+1 SEX M
+1 FAMC @F1@
+1 FAMS @F2@
+0 @I4@ INDI
+1 SEX F
+1 FAMC @F2@
+0 @F1@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 CHIL @I3@
+0 @F2@ FAM
+1 HUSB @I3@
+1 CHIL @I4@
+0 TRLR
+",
+        )
+        .unwrap();
+
+        assert!(gedcom.get_ancestor_xrefs("@I4@") == ["@I3@", "@I1@", "@I2@"]);
+        assert!(gedcom.get_descendant_xrefs("@I1@") == ["@I3@", "@I4@"]);
+        assert!(gedcom.get_ancestor_xrefs("@I9@").is_empty());
+        assert!(gedcom.descent_path_by_xref("@I4@", "@I2@") == [["@I4@", "@I3@", "@I2@"]]);
+        assert!(
+            gedcom.find_relationship_path_by_xref("@I2@", "@I4@")
+                == Some(vec!["@I2@".into(), "@I3@".into(), "@I4@".into()])
+        );
+
+        let relationship = gedcom.find_relationship_by_xref("@I4@", "@I1@").unwrap();
+        assert!(relationship.common_ancestors == ["@I1@"]);
+        assert!(gedcom.find_relationship_by_xref("@I4@", "@I9@").is_none());
+
+        // Owned results outlive the Gedcom, and can go to another thread
+        drop(gedcom);
+        let description = std::thread::spawn(move || relationship.description)
+            .join()
+            .unwrap();
+        assert!(description == "grandfather");
+    }
+
+    #[test]
+    fn associates() {
+        // @I3@ is godfather to @I1@ and witnessed the marriage of @I2@, who
+        // is no relation of @I1@
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @I1@ INDI
+1 ASSO @I3@
+2 RELA Godfather
+1 ASSO @I9@
+2 RELA Godmother
+0 @I2@ INDI
+1 ASSO @I3@
+2 RELA Witness
+0 @I3@ INDI
+0 TRLR
+",
+        )
+        .unwrap();
+        let individual = |xref: &str| gedcom.find_individual(xref).unwrap();
+
+        let associates = gedcom.get_associates(individual("@I1@"));
+        assert!(associates.len() == 1);
+        assert!(associates[0].individual.xref.as_deref() == Some("@I3@"));
+        assert!(associates[0].association.relation.as_deref() == Some("Godfather"));
+
+        let associated_by: Vec<_> = gedcom
+            .get_associated_by(individual("@I3@"))
+            .iter()
+            .map(|a| {
+                (
+                    a.individual.xref.clone().unwrap(),
+                    a.association.relation.clone().unwrap(),
+                )
+            })
+            .collect();
+        assert!(
+            associated_by
+                == [
+                    ("@I1@".to_string(), "Godfather".to_string()),
+                    ("@I2@".to_string(), "Witness".to_string())
+                ]
+        );
+        assert!(gedcom.get_associated_by(individual("@I1@")).is_empty());
+
+        // Associates are only followed when asked for
+        assert!(gedcom
+            .find_relationship_path(individual("@I1@"), individual("@I2@"))
+            .is_none());
+        let path = gedcom
+            .find_relationship_path_with(
+                individual("@I1@"),
+                individual("@I2@"),
+                PathOptions {
+                    associations: true,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+        assert!(xrefs(&path) == ["@I1@", "@I3@", "@I2@"]);
+    }
+
+    #[test]
+    fn witnessed_events() {
+        // @I3@ witnessed @I1@'s marriage and baptism, and officiated at
+        // @I2@'s burial
+        let text = "0 HEAD
+0 @I1@ INDI
+1 BAPM
+2 DATE 1 MAY 1801
+2 _WITN @I3@
+2 _WITN John Smith
+1 FAMS @F1@
+0 @I2@ INDI
+1 BURI
+2 _SHAR @I3@
+3 ROLE Officiant
+0 @I3@ INDI
+0 @F1@ FAM
+1 HUSB @I1@
+1 MARR
+2 DATE 1825
+2 ASSO @I3@
+3 RELA Witness
+2 ASSO @I2@
+3 RELA Best man
+0 TRLR
+";
+        let gedcom = parse_gedcom_from_str(text).unwrap();
+        let witness = gedcom.find_individual("@I3@").unwrap();
+
+        let events = gedcom.events_witnessed_by(witness);
+        let found: Vec<(EventType, Option<&str>, &str)> = events
+            .iter()
+            .map(|e| {
+                let whose = e.individual.and_then(|i| i.xref.as_deref());
+                let role = e.association.relation.as_deref().unwrap_or_default();
+                (e.event, whose, role)
+            })
+            .collect();
+        assert!(
+            found
+                == [
+                    (EventType::Baptism, Some("@I1@"), "Witness"),
+                    (EventType::Marriage, None, "Witness"),
+                    (EventType::Burial, Some("@I2@"), "Officiant"),
+                ]
+        );
+        assert!(events[1].family.map(|f| f.xref.as_str()) == Some("@F1@"));
+        assert!(gedcom.individuals[0].baptism[0].detail.associations.len() == 1);
+
+        // They're written as _ASSO before 7.0
+        let written = crate::writer::write_gedcom(&gedcom);
+        assert!(written.contains("\n2 _ASSO @I3@\n3 RELA Officiant\n"));
+        let gedcom = parse_gedcom_from_str(&written).unwrap();
+        let witness = gedcom.find_individual("@I3@").unwrap();
+        assert!(gedcom.events_witnessed_by(witness).len() == 3);
+    }
+
+    #[test]
+    fn aliases() {
+        // @I5@ and @I7@ turned out to be @I2@, and through @I5@, @I6@ is
+        // the grandfather of @I1@
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @I1@ INDI
+1 FAMC @F1@
+0 @I2@ INDI
+1 NAME John /Smith/
+1 FAMS @F1@
+0 @I5@ INDI
+1 NAME John /Smith/
+1 ALIA @I2@
+1 FAMC @F2@
+0 @I6@ INDI
+1 FAMS @F2@
+0 @I7@ INDI
+1 NAME J. /Smith/
+1 ALIA @I5@
+0 @I8@ INDI
+1 ALIA @I9@
+1 ALIA John Smith
+0 @F1@ FAM
+1 HUSB @I2@
+1 CHIL @I1@
+0 @F2@ FAM
+1 HUSB @I6@
+1 CHIL @I5@
+0 TRLR
+",
+        )
+        .unwrap();
+        let individual = |xref: &str| gedcom.find_individual(xref).unwrap();
+        assert!(individual("@I8@").aliases == ["@I9@"]);
+
+        let identities = gedcom.resolve_aliases();
+        assert!(identities.groups == [["@I2@", "@I5@", "@I7@"]]);
+        assert!(identities.group("@I7@") == Some(&identities.groups[0][..]));
+        assert!(identities.group("@I1@").is_none() && identities.group("@I8@").is_none());
+        assert!(identities.canonical("@I7@") == "@I2@");
+        assert!(identities.canonical("@I1@") == "@I1@");
+        assert!(identities.same_person("@I5@", "@I7@"));
+        assert!(!identities.same_person("@I1@", "@I2@"));
+
+        let path = |from: &str, to: &str, aliases: bool| {
+            gedcom
+                .find_relationship_path_with(
+                    individual(from),
+                    individual(to),
+                    PathOptions {
+                        aliases,
+                        ..Default::default()
+                    },
+                )
+                .map(|p| xrefs(&p))
+        };
+        assert!(path("@I1@", "@I6@", false).is_none());
+        assert!(path("@I1@", "@I6@", true).unwrap() == ["@I1@", "@I2@", "@I6@"]);
+        assert!(path("@I1@", "@I7@", true).unwrap() == ["@I1@", "@I7@"]);
+        assert!(path("@I2@", "@I7@", true).unwrap() == ["@I2@", "@I7@"]);
+
+        let query = SearchQuery::new().name("John Smith");
+        assert!(gedcom.search_individuals(&query).len() == 2);
+        let merged = gedcom.search_individuals(&query.merge_aliases(true));
+        assert!(merged.len() == 1 && merged[0].item.xref.as_deref() == Some("@I2@"));
+    }
+
+    #[test]
+    fn precompute_kinship() {
+        let mut gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @I1@ INDI
+1 SEX M
+1 FAMS @F1@
+1 FAMS @F2@
+0 @I2@ INDI
+1 SEX F
+1 FAMS @F1@
+0 @I3@ INDI
+1 SEX M
+1 FAMC @F1@
+0 @I4@ INDI
+1 SEX F
+1 FAMC @F2@
+1 FAMS @F3@
+0 @I5@ INDI
+1 SEX M
+1 FAMC @F3@
+0 @I6@ INDI
+1 SEX M
+0 @F1@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 CHIL @I3@
+0 @F2@ FAM
+1 HUSB @I1@
+1 CHIL @I4@
+0 @F3@ FAM
+1 WIFE @I4@
+1 CHIL @I5@
+0 TRLR
+",
+        )
+        .unwrap();
+        let describe = |gedcom: &Gedcom, from: &str, to: &str| {
+            let individual = |xref: &str| gedcom.find_individual(xref).unwrap();
+            gedcom
+                .find_relationship(individual(from), individual(to))
+                .map(|r| (r.description, r.common_ancestors.len()))
+        };
+        let pairs = [
+            ("@I3@", "@I4@"),
+            ("@I5@", "@I3@"),
+            ("@I5@", "@I1@"),
+            ("@I3@", "@I6@"),
+        ];
+        let before: Vec<_> = pairs.iter().map(|(a, b)| describe(&gedcom, a, b)).collect();
+        assert!(before[0] == Some(("half-sister".to_string(), 1)));
+        assert!(before[1] == Some(("half-uncle".to_string(), 1)));
+
+        assert!(gedcom.precompute_kinship("@I9@").is_err());
+        gedcom.precompute_kinship("@I5@").unwrap();
+        let kinship = gedcom.kinship.as_ref().unwrap();
+        assert!(kinship.contains("@I2@") && !kinship.contains("@I6@"));
+        let after: Vec<_> = pairs.iter().map(|(a, b)| describe(&gedcom, a, b)).collect();
+        assert!(before == after);
+
+        gedcom
+            .replace_record_from_str("@I6@", "0 @I6@ INDI\n1 SEX F\n")
+            .unwrap();
+        assert!(gedcom.kinship.is_none());
+    }
+
+    #[test]
+    fn ancestors() {
+        let mut gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @I1@ INDI
+1 FAMS @F1@
+0 @I2@ INDI
+1 FAMC @F1@
+1 FAMS @F2@
+0 @I3@ INDI
+1 FAMC @F2@
+0 @I4@ INDI
+1 FAMC @F1@
+0 @I5@ INDI
+0 @F1@ FAM
+1 HUSB @I1@
+1 CHIL @I2@
+1 CHIL @I4@
+0 @F2@ FAM
+1 WIFE @I2@
+1 CHIL @I3@
+0 TRLR
+",
+        )
+        .unwrap();
+        let check = |gedcom: &Gedcom| {
+            let individual = |xref: &str| gedcom.find_individual(xref).unwrap();
+            let is_ancestor =
+                |a: &str, d: &str| gedcom.is_ancestor_of(individual(a), individual(d));
+            assert!(is_ancestor("@I1@", "@I3@") && is_ancestor("@I2@", "@I3@"));
+            assert!(!is_ancestor("@I3@", "@I1@"));
+            assert!(!is_ancestor("@I3@", "@I3@"));
+            // An uncle isn't an ancestor
+            assert!(!is_ancestor("@I4@", "@I3@"));
+            assert!(!is_ancestor("@I5@", "@I3@"));
+            assert!(gedcom.is_descendant_of(individual("@I3@"), individual("@I1@")));
+        };
+        check(&gedcom);
+
+        gedcom.precompute_kinship("@I3@").unwrap();
+        let kinship = gedcom.kinship.as_ref().unwrap();
+        assert!(kinship.is_ancestor("@I1@", "@I3@") == Some(true));
+        assert!(kinship.is_ancestor("@I3@", "@I3@") == Some(false));
+        assert!(kinship.is_ancestor("@I5@", "@I3@").is_none());
+        check(&gedcom);
+    }
+
+    #[test]
+    fn direct_lines() {
+        // @I4@ was adopted by @I5@, but born to @I1@ and @I2@
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 FAMC @F1@
+0 @I2@ INDI
+1 NAME Ann /Smith/
+1 FAMC @F2@
+0 @I3@ INDI
+1 NAME Tom /Bloggs/
+0 @I4@ INDI
+1 NAME Sam /Bloggs/
+1 FAMC @F3@
+1 FAMC @F4@
+2 PEDI adopted
+1 FAMS @F5@
+0 @I5@ INDI
+1 NAME Bill /Jones/
+0 @I6@ INDI
+1 NAME Mary /Smith/
+0 @I7@ INDI
+1 NAME Liz /Bloggs/
+1 FAMS @F6@
+0 @I8@ INDI
+1 NAME Ben /Bloggs/
+0 @I9@ INDI
+1 NAME Kit /Brown/
+0 @I10@ INDI
+1 NAME Ada /bloggs/
+0 @F1@ FAM
+1 HUSB @I3@
+1 CHIL @I1@
+0 @F2@ FAM
+1 WIFE @I6@
+1 CHIL @I2@
+0 @F3@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 CHIL @I4@
+0 @F4@ FAM
+1 HUSB @I5@
+1 CHIL @I4@
+0 @F5@ FAM
+1 HUSB @I4@
+1 CHIL @I7@
+1 CHIL @I8@
+0 @F6@ FAM
+1 WIFE @I7@
+1 CHIL @I9@
+1 CHIL @I10@
+0 TRLR
+",
+        )
+        .unwrap();
+        let sam = gedcom.find_individual("@I4@").unwrap();
+        assert!(xrefs(&gedcom.patrilineal_ancestors(sam)) == ["@I1@", "@I3@"]);
+        assert!(xrefs(&gedcom.matrilineal_ancestors(sam)) == ["@I2@", "@I6@"]);
+        assert!(gedcom
+            .patrilineal_ancestors(gedcom.find_individual("@I5@").unwrap())
+            .is_empty());
+
+        let tom = gedcom.find_individual("@I3@").unwrap();
+        assert!(
+            xrefs(&gedcom.descendants_carrying_surname(tom, "BLOGGS"))
+                == ["@I1@", "@I4@", "@I7@", "@I8@", "@I10@"]
+        );
+        assert!(gedcom.descendants_carrying_surname(tom, "Smith").is_empty());
+    }
+
+    #[test]
+    fn parentage() {
+        // @I4@ was born to @I1@ and @I2@, adopted by @I5@, and fostered by
+        // @I7@
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @I1@ INDI
+1 FAMC @F1@
+0 @I2@ INDI
+0 @I3@ INDI
+0 @I4@ INDI
+1 FAMC @F2@
+1 FAMC @F3@
+2 PEDI adopted
+1 FAMC @F4@
+2 PEDI foster
+0 @I5@ INDI
+1 FAMC @F5@
+0 @I6@ INDI
+0 @I7@ INDI
+0 @F1@ FAM
+1 HUSB @I3@
+1 CHIL @I1@
+0 @F2@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 CHIL @I4@
+0 @F3@ FAM
+1 HUSB @I5@
+1 CHIL @I4@
+0 @F4@ FAM
+1 WIFE @I7@
+1 CHIL @I4@
+0 @F5@ FAM
+1 WIFE @I6@
+1 CHIL @I5@
+0 TRLR
+",
+        )
+        .unwrap();
+        let sam = gedcom.find_individual("@I4@").unwrap();
+
+        let families: Vec<(&str, Pedigree, Vec<String>)> = gedcom
+            .get_parents_with_pedigree(sam)
+            .into_iter()
+            .map(|f| (f.family.xref.as_str(), f.pedigree, xrefs(&f.parents)))
+            .collect();
+        assert!(
+            families
+                == [
+                    (
+                        "@F2@",
+                        Pedigree::Birth,
+                        vec!["@I1@".to_string(), "@I2@".to_string()]
+                    ),
+                    ("@F3@", Pedigree::Adopted, vec!["@I5@".to_string()]),
+                    ("@F4@", Pedigree::Foster, vec!["@I7@".to_string()]),
+                ]
+        );
+
+        let parents = |parentage| xrefs(&gedcom.parents_with(sam, parentage));
+        assert!(parents(Parentage::All) == ["@I1@", "@I2@", "@I5@", "@I7@"]);
+        assert!(parents(Parentage::Biological) == ["@I1@", "@I2@"]);
+        assert!(parents(Parentage::Adoptive) == ["@I5@"]);
+
+        let ancestors = |parentage| xrefs(&gedcom.get_ancestors_with(sam, parentage));
+        assert!(ancestors(Parentage::Biological) == ["@I1@", "@I2@", "@I3@"]);
+        // The adoptive father's own parents are by birth
+        assert!(ancestors(Parentage::Adoptive) == ["@I5@", "@I6@"]);
+        assert!(ancestors(Parentage::All).len() == 6);
+        assert!(ancestors(Parentage::All) == xrefs(&gedcom.get_ancestors(sam)));
+    }
+
+    #[test]
+    fn descent_path() {
+        // @I5@ and @I6@ are first cousins, and their daughter @I7@ descends
+        // from @I1@ twice
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @I1@ INDI
+1 FAMS @F1@
+0 @I2@ INDI
+1 FAMC @F1@
+1 FAMS @F2@
+0 @I3@ INDI
+1 FAMC @F1@
+1 FAMS @F3@
+0 @I5@ INDI
+1 FAMC @F2@
+1 FAMS @F4@
+0 @I6@ INDI
+1 FAMC @F3@
+1 FAMS @F4@
+0 @I7@ INDI
+1 FAMC @F4@
+0 @F1@ FAM
+1 HUSB @I1@
+1 CHIL @I2@
+1 CHIL @I3@
+0 @F2@ FAM
+1 HUSB @I2@
+1 CHIL @I5@
+0 @F3@ FAM
+1 WIFE @I3@
+1 CHIL @I6@
+0 @F4@ FAM
+1 HUSB @I5@
+1 WIFE @I6@
+1 CHIL @I7@
+0 TRLR
+",
+        )
+        .unwrap();
+        let individual = |xref: &str| gedcom.find_individual(xref).unwrap();
+
+        let paths: Vec<Vec<&str>> = gedcom
+            .descent_path(individual("@I7@"), individual("@I1@"))
+            .iter()
+            .map(|p| p.iter().map(|i| i.xref.as_deref().unwrap()).collect())
+            .collect();
+        assert!(
+            paths
+                == [
+                    ["@I7@", "@I5@", "@I2@", "@I1@"],
+                    ["@I7@", "@I6@", "@I3@", "@I1@"]
+                ]
+        );
+
+        assert!(gedcom
+            .descent_path(individual("@I5@"), individual("@I3@"))
+            .is_empty());
+        assert!(gedcom
+            .descent_path(individual("@I7@"), individual("@I7@"))
+            .is_empty());
+    }
+
+    #[test]
+    fn descent_path_collapse() {
+        // Forty generations of a brother and sister marrying, so everyone
+        // has 2^n lines up to their ancestors n generations back, and none
+        // to @X@
+        let mut text = "0 HEAD\n0 @X@ INDI\n".to_string();
+        for g in 0..=40 {
+            text += &format!("0 @A{g}@ INDI\n0 @B{g}@ INDI\n");
+        }
+        for g in 0..40 {
+            text += &format!("0 @F{g}@ FAM\n1 HUSB @A{g}@\n1 WIFE @B{g}@\n");
+            text += &format!("1 CHIL @A{}@\n1 CHIL @B{}@\n", g + 1, g + 1);
+        }
+        text += "0 TRLR\n";
+        let gedcom = parse_gedcom_from_str(&text).unwrap();
+        let individual = |xref: &str| gedcom.find_individual(xref).unwrap();
+
+        assert!(gedcom
+            .descent_path(individual("@A40@"), individual("@X@"))
+            .is_empty());
+        assert!(
+            gedcom
+                .descent_path(individual("@A40@"), individual("@B37@"))
+                .len()
+                == 4
+        );
+    }
+
+    #[test]
+    fn relatives() {
+        // @I2@ is linked to @F1@ only from the family's side, and @I3@ only
+        // from their own, and there are two records for @I4@
+        let text = "0 HEAD
+0 @I1@ INDI
+1 FAMS @F1@
+1 ASSO @I4@
+0 @I2@ INDI
+0 @I3@ INDI
+1 FAMC @F1@
+1 FAMC @F1@
+0 @I4@ INDI
+1 FAMS @F2@
+0 @I4@ INDI
+1 FAMC @F2@
+0 @I5@ INDI
+1 FAMS @F1@
+1 FAMS @F2@
+0 @F1@ FAM
+1 HUSB @I1@
+1 CHIL @I2@
+0 @F2@ FAM
+1 WIFE @I5@
+1 CHIL @I4@
+0 TRLR
+";
+        let gedcom = parse_gedcom_from_str(text).unwrap();
+        let complete = parse_gedcom("./data/complete.ged");
+        for gedcom in [&gedcom, &complete] {
+            let relatives = Relatives::new(gedcom);
+            let copies: Vec<Individual> = gedcom.individuals.clone();
+            for individual in gedcom.individuals.iter().chain(&copies) {
+                assert!(
+                    xrefs(&relatives.parents(individual)) == xrefs(&gedcom.parents(individual))
+                );
+                assert!(
+                    xrefs(&relatives.children(individual)) == xrefs(&gedcom.children(individual))
+                );
+                assert!(
+                    xrefs(&relatives.spouses(individual)) == xrefs(&gedcom.spouses(individual))
+                );
+                let associates: Vec<&Individual> = super::associates(gedcom, individual)
+                    .into_iter()
+                    .chain(associated_by(gedcom, individual))
+                    .map(|a| a.individual)
+                    .collect();
+                assert!(xrefs(&relatives.associates(individual)) == xrefs(&associates));
+            }
+        }
+
+        let relatives = Relatives::new(&gedcom);
+        let i5 = gedcom.find_individual("@I5@").unwrap();
+        assert!(xrefs(&relatives.children(i5)) == ["@I2@", "@I3@", "@I4@"]);
+    }
+
+    #[test]
+    fn extended_family() {
+        // @I1@ and @I2@ have @I3@ and @I4@. @I3@ and @I5@ have @I6@, the
+        // root, and @I7@; @I3@ and @I8@ have @I9@. @I4@ has @I10@, @I7@ has
+        // @I11@, and the root and @I12@, whose parents are @I13@ and who has
+        // a brother @I14@, have @I15@, who with @I16@ has @I17@.
+        let text = "0 HEAD
+0 @F1@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 CHIL @I3@
+1 CHIL @I4@
+0 @F2@ FAM
+1 HUSB @I3@
+1 WIFE @I5@
+1 CHIL @I6@
+1 CHIL @I7@
+0 @F3@ FAM
+1 HUSB @I3@
+1 WIFE @I8@
+1 CHIL @I9@
+0 @F4@ FAM
+1 HUSB @I4@
+1 CHIL @I10@
+0 @F5@ FAM
+1 WIFE @I7@
+1 HUSB @I18@
+1 CHIL @I11@
+0 @F6@ FAM
+1 HUSB @I6@
+1 WIFE @I12@
+1 CHIL @I15@
+0 @F7@ FAM
+1 HUSB @I13@
+1 CHIL @I12@
+1 CHIL @I14@
+0 @F8@ FAM
+1 HUSB @I15@
+1 WIFE @I16@
+1 CHIL @I17@
+"
+        .to_string()
+            + &(1..=18)
+                .map(|i| format!("0 @I{}@ INDI\n1 NAME Person{}\n", i, i))
+                .collect::<String>()
+            + "0 TRLR\n";
+        let gedcom = parse_gedcom_from_str(&text).unwrap();
+        let root = gedcom.find_individual("@I6@").unwrap();
+        let family = gedcom.extended_family(root);
+        let xrefs = |people: &[&Individual]| -> Vec<String> {
+            people.iter().filter_map(|p| p.xref.clone()).collect()
+        };
+
+        assert!(xrefs(&family.parents) == ["@I3@", "@I5@"]);
+        assert!(xrefs(&family.grandparents) == ["@I1@", "@I2@"]);
+        assert!(xrefs(&family.siblings) == ["@I7@", "@I9@"]);
+        assert!(xrefs(&family.aunts_uncles) == ["@I4@"]);
+        assert!(xrefs(&family.first_cousins) == ["@I10@"]);
+        assert!(xrefs(&family.nieces_nephews) == ["@I11@"]);
+        assert!(xrefs(&family.spouses) == ["@I12@"]);
+        assert!(xrefs(&family.children) == ["@I15@"]);
+        assert!(xrefs(&family.grandchildren) == ["@I17@"]);
+        assert!(xrefs(&family.parents_in_law) == ["@I13@"]);
+        assert!(xrefs(&family.siblings_in_law) == ["@I14@", "@I18@"]);
+        assert!(xrefs(&family.children_in_law) == ["@I16@"]);
+
+        // Without parents, there's no one in the groups through them
+        let top = gedcom.extended_family(gedcom.find_individual("@I1@").unwrap());
+        assert!(top.parents.is_empty() && top.siblings.is_empty());
+        assert!(xrefs(&top.spouses) == ["@I2@"]);
+        assert!(xrefs(&top.grandchildren) == ["@I6@", "@I7@", "@I9@", "@I10@"]);
+    }
+}
@@ -0,0 +1,476 @@
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::str::FromStr;
+
+use crate::sample::{family_events, individual_events, Random};
+use crate::types::{
+    EventDetail, Family, FamilyEventDetail, Gedcom, Note, Object, SourceCitation, Spouse,
+};
+
+// Give every record a new xref, and point everything that pointed at the old
+// one at the new one: before merging two files whose xrefs clash, or so that
+// files exported by different programs can be compared line by line.
+//
+// Each xref is renamed once, the first time it's seen, so that pointers to
+// records that aren't in the model (i.e. SUBN, or a program's own records) or
+// that don't exist at all are renamed consistently too, and can't end up
+// clashing with a new xref.
+
+/// How `renumber_xrefs` names records
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum XrefScheme {
+    /// Numbered in file order, with a letter for the kind of record: @I1@,
+    /// @I2@, ..., @F1@, ..., @S1@, ...
+    #[default]
+    Sequential,
+    /// A random UUID, after the same letter, i.e.
+    /// @I5F0C9A2E3B1D4C7E8A6F0B2D4E6A8C0E@, so that records from different
+    /// files never clash
+    Uuid,
+}
+
+impl FromStr for XrefScheme {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<XrefScheme, Self::Err> {
+        match input.to_lowercase().as_str() {
+            "sequential" => Ok(XrefScheme::Sequential),
+            "uuid" => Ok(XrefScheme::Uuid),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Rename every xref in the Gedcom, following `scheme`, and update every
+/// pointer to match. Returns the new xref for each old one.
+///
+/// Any kinship worked out with `Gedcom::precompute_kinship` is discarded.
+pub fn renumber_xrefs(gedcom: &mut Gedcom, scheme: XrefScheme) -> HashMap<String, String> {
+    let mut renamer = Renamer {
+        scheme,
+        xrefs: HashMap::new(),
+        counts: HashMap::new(),
+        random: Random(RandomState::new().hash_one(0)),
+    };
+
+    // The records first, so they're numbered in file order
+    for record in &gedcom.raw_records {
+        if let Some(xref) = &record.xref {
+            renamer.xref(xref, prefix(&record.tag));
+        }
+    }
+    for individual in &mut gedcom.individuals {
+        renamer.rename_option(&mut individual.xref, "I");
+    }
+    for family in &mut gedcom.families {
+        renamer.rename(&mut family.xref, "F");
+    }
+    for source in &mut gedcom.sources {
+        renamer.rename(&mut source.xref, "S");
+    }
+    for repository in &mut gedcom.repositories {
+        renamer.rename(&mut repository.xref, "R");
+    }
+    for record in &mut gedcom.multimedia {
+        renamer.rename(&mut record.xref, "M");
+    }
+    for record in &mut gedcom.notes {
+        renamer.rename(&mut record.xref, "N");
+    }
+    for submitter in &mut gedcom.submitters {
+        renamer.rename_option(&mut submitter.xref, "U");
+        for media in &mut submitter.media {
+            renamer.rename(media, "M");
+        }
+        renamer.note(submitter.note.as_mut());
+    }
+
+    // Then everything that points at them
+    let header = &mut gedcom.header;
+    if let Some(submitter) = &mut header.submitter {
+        renamer.rename_option(&mut submitter.xref, "U");
+        for media in &mut submitter.media {
+            renamer.rename(media, "M");
+        }
+        renamer.note(submitter.note.as_mut());
+    }
+    if let Some(submission) = &mut header.submission {
+        renamer.rename_option(&mut submission.xref, "SUBN");
+    }
+    renamer.rename_option(&mut header.home_person, "I");
+
+    for individual in &mut gedcom.individuals {
+        for name in &mut individual.names {
+            renamer.note(name.name.note.as_mut());
+        }
+        for event in individual_events(individual) {
+            renamer.detail(&mut event.detail);
+        }
+        for birth in &mut individual.birth {
+            renamer.family_option(birth.family.as_mut());
+        }
+        for adoption in &mut individual.adoption {
+            renamer.family_option(adoption.family.as_mut());
+        }
+        for christening in individual
+            .christening
+            .iter_mut()
+            .chain(&mut individual.christening_adult)
+        {
+            renamer.family_option(christening.family.as_mut());
+        }
+        for death in &mut individual.death {
+            if let Some(detail) = &mut death.event {
+                renamer.detail(detail);
+            }
+            renamer.family_option(death.family.as_mut());
+        }
+        for residence in &mut individual.residences {
+            if let Some(event) = &mut residence.detail {
+                renamer.family_event(event);
+            }
+        }
+        for family in individual.famc.iter_mut().chain(&mut individual.fams) {
+            renamer.family(family);
+        }
+        renamer.notes(&mut individual.notes);
+        renamer.citations(&mut individual.sources);
+        for association in &mut individual.associations {
+            renamer.rename(&mut association.xref, "I");
+            renamer.notes(&mut association.notes);
+            renamer.citations(&mut association.sources);
+        }
+        for alias in &mut individual.aliases {
+            renamer.rename(alias, "I");
+        }
+        for submitter in individual
+            .submitters
+            .iter_mut()
+            .chain(&mut individual.ancestor_interest)
+            .chain(&mut individual.descendant_interest)
+        {
+            renamer.rename(submitter, "U");
+        }
+        renamer.media(&mut individual.media);
+    }
+    for family in &mut gedcom.families {
+        renamer.family_pointers(family);
+    }
+    for source in &mut gedcom.sources {
+        for repository in &mut source.repositories {
+            renamer.rename_option(&mut repository.xref, "R");
+            renamer.notes(&mut repository.notes);
+        }
+        renamer.notes(&mut source.notes);
+        renamer.media(&mut source.media);
+    }
+    for repository in &mut gedcom.repositories {
+        renamer.notes(&mut repository.notes);
+    }
+    for record in &mut gedcom.multimedia {
+        renamer.notes(&mut record.notes);
+    }
+    for record in &mut gedcom.notes {
+        renamer.citations(&mut record.sources);
+    }
+
+    for record in &mut gedcom.raw_records {
+        if let Some(xref) = &mut record.xref {
+            *xref = renamer.xref(xref, prefix(&record.tag));
+        }
+        for line in &mut record.lines {
+            *line = renamer.line(line);
+        }
+    }
+
+    // And everything kept by xref
+    gedcom.record_positions = gedcom
+        .record_positions
+        .drain()
+        .map(|(xref, position)| (renamer.xref(&xref, "X"), position))
+        .collect();
+    for skipped in &mut gedcom.skipped_text {
+        renamer.rename_option(&mut skipped.xref, "X");
+    }
+    for record in &mut gedcom.decoding.records {
+        renamer.rename_option(&mut record.xref, "X");
+    }
+    gedcom.kinship = None;
+
+    renamer.xrefs
+}
+
+struct Renamer {
+    scheme: XrefScheme,
+    /// The new xref for each old one
+    xrefs: HashMap<String, String>,
+    /// How many of each kind of record have been numbered
+    counts: HashMap<&'static str, usize>,
+    random: Random,
+}
+
+impl Renamer {
+    /// The new xref for `old`, naming it after `prefix` if it hasn't been
+    /// seen before
+    fn xref(&mut self, old: &str, prefix: &'static str) -> String {
+        if let Some(new) = self.xrefs.get(old) {
+            return new.clone();
+        }
+        let new = match self.scheme {
+            XrefScheme::Sequential => {
+                let count = self.counts.entry(prefix).or_insert(0);
+                *count += 1;
+                format!("@{}{}@", prefix, count)
+            }
+            XrefScheme::Uuid => format!("@{}{}@", prefix, self.uuid()),
+        };
+        self.xrefs.insert(old.to_string(), new.clone());
+        new
+    }
+
+    /// A random (version 4) UUID, in upper case without hyphens, since
+    /// those aren't allowed in an xref
+    fn uuid(&mut self) -> String {
+        let mut uuid = (u128::from(self.random.next()) << 64) | u128::from(self.random.next());
+        uuid = (uuid & !(0xF << 76)) | (0x4 << 76);
+        uuid = (uuid & !(0x3 << 62)) | (0x2 << 62);
+        format!("{:032X}", uuid)
+    }
+
+    fn rename(&mut self, xref: &mut String, prefix: &'static str) {
+        if is_pointer(xref) {
+            *xref = self.xref(xref, prefix);
+        }
+    }
+
+    fn rename_option(&mut self, xref: &mut Option<String>, prefix: &'static str) {
+        if let Some(xref) = xref {
+            self.rename(xref, prefix);
+        }
+    }
+
+    /// A FAM record, or a FAMC or FAMS link to one
+    fn family(&mut self, family: &mut Family) {
+        self.rename(&mut family.xref, "F");
+        self.family_pointers(family);
+    }
+
+    fn family_option(&mut self, family: Option<&mut Family>) {
+        if let Some(family) = family {
+            self.family(family);
+        }
+    }
+
+    /// Everything a family points to, other than itself
+    fn family_pointers(&mut self, family: &mut Family) {
+        self.spouse(family.husband.as_mut());
+        self.spouse(family.wife.as_mut());
+        for child in &mut family.children {
+            self.rename(child, "I");
+        }
+        for event in family_events(family) {
+            self.family_event(event);
+        }
+        self.notes(&mut family.notes);
+        self.citations(&mut family.sources);
+        self.media(&mut family.media);
+        for submitter in &mut family.submitters {
+            self.rename(submitter, "U");
+        }
+    }
+
+    fn spouse(&mut self, spouse: Option<&mut Spouse>) {
+        if let Some(xref) = spouse.and_then(|s| s.xref.as_mut()) {
+            self.rename_option(&mut xref.xref, "I");
+        }
+    }
+
+    fn family_event(&mut self, event: &mut FamilyEventDetail) {
+        self.spouse(event.husband.as_mut());
+        self.spouse(event.wife.as_mut());
+        if let Some(detail) = &mut event.detail {
+            self.detail(detail);
+        }
+    }
+
+    fn detail(&mut self, detail: &mut EventDetail) {
+        if let Some(place) = &mut detail.place {
+            self.note(place.note.as_mut());
+        }
+        self.citations(&mut detail.sources);
+        self.media(&mut detail.media);
+    }
+
+    fn citations(&mut self, citations: &mut [SourceCitation]) {
+        for citation in citations {
+            self.rename_option(&mut citation.xref, "S");
+            self.media(&mut citation.media);
+            self.note(citation.note.as_mut());
+        }
+    }
+
+    fn media(&mut self, media: &mut [Object]) {
+        for object in media {
+            self.rename_option(&mut object.xref, "M");
+        }
+    }
+
+    fn notes(&mut self, notes: &mut [Note]) {
+        for note in notes {
+            self.note(Some(note));
+        }
+    }
+
+    /// A note, which may be a pointer to a NOTE record
+    fn note(&mut self, note: Option<&mut Note>) {
+        if let Some(note) = note {
+            self.rename_option(&mut note.note, "N");
+        }
+    }
+
+    /// A line of a record's original text, with its xref or pointer renamed
+    fn line(&mut self, line: &str) -> String {
+        let body = line.trim_end_matches(&['\r', '\n'][..]);
+        let ending = &line[body.len()..];
+        let words: Vec<&str> = body.splitn(3, ' ').collect();
+        match words[..] {
+            [level, xref, rest] if is_pointer(xref) => {
+                let tag = rest.split(' ').next().unwrap_or_default();
+                let xref = self.xref(xref, prefix(tag));
+                format!("{} {} {}{}", level, xref, rest, ending)
+            }
+            [level, tag, value] if is_pointer(value) => {
+                let value = self.xref(value, prefix(tag));
+                format!("{} {} {}{}", level, tag, value, ending)
+            }
+            _ => line.to_string(),
+        }
+    }
+}
+
+/// The letter xrefs start with for a kind of record, or the tag of a line
+/// pointing at one
+fn prefix(tag: &str) -> &'static str {
+    match tag {
+        "INDI" | "HUSB" | "WIFE" | "CHIL" | "ASSO" | "ALIA" | "_HOME" | "_ASSO" | "_SHAR"
+        | "_WITN" => "I",
+        "FAM" | "FAMC" | "FAMS" => "F",
+        "SOUR" => "S",
+        "OBJE" => "M",
+        "NOTE" | "SNOTE" => "N",
+        "REPO" => "R",
+        "SUBM" | "ANCI" | "DESI" => "U",
+        "SUBN" => "SUBN",
+        _ => "X",
+    }
+}
+
+/// Whether a value is a pointer to a record, i.e. "@I1@", rather than text
+/// or an escape like "@#DJULIAN@"
+pub(crate) fn is_pointer(value: &str) -> bool {
+    value.len() > 2
+        && value.starts_with('@')
+        && value.ends_with('@')
+        && !value.starts_with("@#")
+        && !value.contains(' ')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GedcomConfig;
+    use crate::parse::{parse_gedcom_from_reader_with_config, parse_gedcom_from_str};
+    use crate::writer::write_gedcom;
+
+    const DATA: &str = "0 HEAD
+1 SUBM @SUB9@
+1 _HOME @P7@
+0 @P7@ INDI
+1 NAME Joe /Bloggs/
+1 FAMS @FAM3@
+1 BIRT
+2 DATE 1900
+2 SOUR @SRC2@
+3 NOTE @NOTE1@
+1 NOTE @NOTE1@
+0 @P2@ INDI
+1 NAME Ann /Bloggs/
+1 FAMC @FAM3@
+1 OBJE @PHOTO@
+0 @FAM3@ FAM
+1 HUSB @P7@
+1 CHIL @P2@
+1 MARR
+2 SOUR @SRC2@
+0 @SRC2@ SOUR
+1 TITL Parish register
+1 REPO @ARCHIVE@
+0 @PHOTO@ OBJE
+1 FILE photo.jpg
+0 @NOTE1@ NOTE Checked
+0 @SUB9@ SUBM
+1 NAME Joe Bloggs
+0 TRLR
+";
+
+    #[test]
+    fn renumber() {
+        let config = GedcomConfig::new().preserve_unknown();
+        let mut gedcom = parse_gedcom_from_reader_with_config(DATA.as_bytes(), &config).unwrap();
+        let xrefs = renumber_xrefs(&mut gedcom, XrefScheme::Sequential);
+
+        let renamed = |old: &str| xrefs.get(old).map(|x| x.as_str());
+        assert!(renamed("@P7@") == Some("@I1@") && renamed("@P2@") == Some("@I2@"));
+        assert!(renamed("@FAM3@") == Some("@F1@") && renamed("@SRC2@") == Some("@S1@"));
+        assert!(renamed("@PHOTO@") == Some("@M1@") && renamed("@NOTE1@") == Some("@N1@"));
+        assert!(renamed("@SUB9@") == Some("@U1@") && renamed("@ARCHIVE@") == Some("@R1@"));
+
+        let joe = &gedcom.individuals[0];
+        assert!(joe.xref.as_deref() == Some("@I1@"));
+        assert!(joe.fams[0].xref == "@F1@");
+        assert!(joe.birth[0].event.detail.sources[0].xref.as_deref() == Some("@S1@"));
+        assert!(joe.notes[0].note.as_deref() == Some("@N1@"));
+        assert!(gedcom.individuals[1].media[0].xref.as_deref() == Some("@M1@"));
+        let family = &gedcom.families[0];
+        assert!(family.husband_xref() == Some("@I1@") && family.children == ["@I2@"]);
+        assert!(family.marriage[0].detail.as_ref().unwrap().sources[0].xref == Some("@S1@".into()));
+        assert!(gedcom.sources[0].repositories[0].xref.as_deref() == Some("@R1@"));
+        assert!(gedcom.header.home_person.as_deref() == Some("@I1@"));
+        assert!(gedcom.header.submitter.as_ref().unwrap().xref.as_deref() == Some("@U1@"));
+        assert!(gedcom.record_positions.contains_key("@F1@"));
+        assert!(!gedcom.record_positions.contains_key("@FAM3@"));
+        assert!(gedcom.raw_records[6].lines[0] == "0 @N1@ NOTE Checked\n");
+        assert!(gedcom.raw_records[3].lines[1] == "1 HUSB @I1@\n");
+
+        // The renumbered file reads back the same
+        let text = write_gedcom(&gedcom);
+        let reread = parse_gedcom_from_str(&text).unwrap();
+        assert!(reread.individuals.len() == 2 && reread.warnings.is_empty());
+        assert!(reread.find_family("@F1@").unwrap().wife_xref().is_none());
+
+        // Renumbering again changes nothing
+        let xrefs = renumber_xrefs(&mut gedcom, XrefScheme::Sequential);
+        assert!(xrefs.iter().all(|(old, new)| old == new));
+    }
+
+    #[test]
+    fn renumber_uuid() {
+        let mut gedcom = parse_gedcom_from_str(DATA).unwrap();
+        let xrefs = renumber_xrefs(&mut gedcom, XrefScheme::Uuid);
+        let joe = xrefs.get("@P7@").unwrap();
+        assert!(joe.len() == 35 && joe.starts_with("@I") && joe.ends_with('@'));
+        assert!(joe[2..34]
+            .chars()
+            .all(|c| c.is_ascii_hexdigit() && !c.is_lowercase()));
+        assert!(&joe[14..15] == "4");
+        assert!(gedcom.families[0].husband_xref() == Some(joe.as_str()));
+
+        let mut other = parse_gedcom_from_str(DATA).unwrap();
+        let other = renumber_xrefs(&mut other, XrefScheme::Uuid);
+        assert!(other.get("@P7@") != Some(joe));
+
+        assert!("UUID".parse() == Ok(XrefScheme::Uuid));
+        assert!("random".parse::<XrefScheme>().is_err());
+    }
+}
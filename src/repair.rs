@@ -0,0 +1,1274 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+
+use crate::error::GedcomError;
+use crate::parse::parse_gedcom_from_bytes;
+use crate::types::{Family, Gedcom, Gender, Spouse, Xref};
+use crate::writer::MAX_VALUE_LENGTH;
+
+// Safe, automatic fixes for the ways GEDCOM files are commonly broken by the
+// programs that write them, or by hand editing, so that a file will load in
+// programs that are less forgiving than this one.
+//
+// Repairs are made line by line, on the bytes of the file, so anything that
+// isn't broken (including text in character sets other than UTF-8) is
+// written back exactly as it was. Each repair is reported, so the changes
+// can be reviewed.
+//
+// Some defects are only visible in the model: links between individuals and
+// families that only go one way, or point to nothing. `repair_structure`
+// fixes those in a parsed Gedcom, so the two together, as the fix command
+// runs them, fix what can be fixed at either level.
+
+/// The longest a line may be, in characters, including its level and tag
+const MAX_LINE_LENGTH: usize = 255;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+/// The kind of a Repair
+pub enum RepairKind {
+    /// A byte order mark at the start of the file was removed
+    ByteOrderMark,
+    /// CRLF and CR line endings were changed to LF
+    LineEndings,
+    /// Blank lines were removed
+    BlankLines,
+    /// Whitespace before lines' levels was removed
+    Indentation,
+    /// A line that isn't a GEDCOM line, most likely because a newline was
+    /// written in a value, was made a CONT of the line before it
+    Continuation,
+    /// A line that isn't part of any record was removed
+    OrphanLine,
+    /// A line's level jumped by more than one, so was lowered to follow on
+    /// from the line it belongs to
+    LevelJump,
+    /// A line longer than the specification allows was split with CONC
+    LongLine,
+    /// A record's xref was already used by another record, so was renamed
+    DuplicateXref,
+    /// A missing TRLR was added, or one before the end of the file removed
+    Trailer,
+    /// Spaces and tabs at the end of lines were removed
+    TrailingWhitespace,
+    /// A NOTE record with the same text as another was removed, and what
+    /// pointed to it pointed to the other instead
+    DuplicateNote,
+    /// A FAMC or FAMS link, or a family's HUSB, WIFE or CHIL, was added to
+    /// match the link the other way
+    ReciprocalLink,
+    /// A link to a family, individual or source that doesn't exist was
+    /// removed
+    DanglingPointer,
+}
+
+/// Which of the optional repairs to make. By default, they all are.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RepairOptions {
+    /// Split lines longer than the specification allows with CONC
+    pub long_lines: bool,
+    /// Remove spaces and tabs from the ends of lines, except before a CONC,
+    /// where they're part of the text
+    pub trailing_whitespace: bool,
+    /// Merge NOTE records with the same text
+    pub duplicate_notes: bool,
+    /// Add the missing side of one-way links between individuals and
+    /// families. See `repair_structure`.
+    pub reciprocal_links: bool,
+    /// Remove links to records that don't exist. See `repair_structure`.
+    pub dangling_pointers: bool,
+}
+
+impl Default for RepairOptions {
+    fn default() -> RepairOptions {
+        RepairOptions {
+            long_lines: true,
+            trailing_whitespace: true,
+            duplicate_notes: true,
+            reciprocal_links: true,
+            dangling_pointers: true,
+        }
+    }
+}
+
+impl RepairOptions {
+    pub fn new() -> RepairOptions {
+        RepairOptions::default()
+    }
+
+    pub fn long_lines(mut self, repair: bool) -> RepairOptions {
+        self.long_lines = repair;
+        self
+    }
+
+    pub fn trailing_whitespace(mut self, repair: bool) -> RepairOptions {
+        self.trailing_whitespace = repair;
+        self
+    }
+
+    pub fn duplicate_notes(mut self, repair: bool) -> RepairOptions {
+        self.duplicate_notes = repair;
+        self
+    }
+
+    pub fn reciprocal_links(mut self, repair: bool) -> RepairOptions {
+        self.reciprocal_links = repair;
+        self
+    }
+
+    pub fn dangling_pointers(mut self, repair: bool) -> RepairOptions {
+        self.dangling_pointers = repair;
+        self
+    }
+}
+
+/// A change made by `repair_gedcom`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Repair {
+    /// The line of the original file, or the first line, for repairs made
+    /// to many lines
+    pub line: Option<usize>,
+    pub kind: RepairKind,
+    pub message: String,
+}
+
+impl fmt::Display for Repair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// Repair a GEDCOM file's contents. Returns the repaired file, with LF line
+/// endings, and the repairs made, in the order of the lines they were made
+/// to.
+pub fn repair_gedcom(input: &[u8]) -> (Vec<u8>, Vec<Repair>) {
+    repair_gedcom_with_options(input, &RepairOptions::default())
+}
+
+/// Repair a GEDCOM file's contents, making only the optional repairs in
+/// `options`. See `repair_gedcom`.
+pub fn repair_gedcom_with_options(input: &[u8], options: &RepairOptions) -> (Vec<u8>, Vec<Repair>) {
+    let (lines, _, repairs) = repair_lines(input, options);
+    (join(&lines), repairs)
+}
+
+/// Repair the file, returning its lines, the line of the original file each
+/// came from, and the repairs made
+fn repair_lines(input: &[u8], options: &RepairOptions) -> (Vec<Vec<u8>>, Vec<usize>, Vec<Repair>) {
+    let mut repairer = Repairer {
+        options: options.clone(),
+        ..Default::default()
+    };
+
+    let input = match input.strip_prefix(b"\xEF\xBB\xBF") {
+        Some(input) => {
+            repairer.repair(
+                Some(1),
+                RepairKind::ByteOrderMark,
+                "Removed the byte order mark",
+            );
+            input
+        }
+        None => input,
+    };
+    let (lines, endings) = split_lines(input);
+    if endings > 0 {
+        repairer.repair(
+            None,
+            RepairKind::LineEndings,
+            &format!("Changed {} CRLF or CR line endings to LF", endings),
+        );
+    }
+
+    // Every xref in the file, so a record can be renamed to one that's free
+    for line in &lines {
+        if let Some(line) = parse_line(trim_start(line)) {
+            repairer.xrefs.extend(line.xref.map(<[u8]>::to_vec));
+            if let Some(pointer) = line.pointer() {
+                repairer.xrefs.insert(pointer.to_vec());
+            }
+        }
+    }
+
+    for (i, line) in lines.iter().enumerate() {
+        repairer.line(i + 1, line);
+    }
+    repairer.finish()
+}
+
+/// Repair a GEDCOM file, writing the result to `output`. See
+/// `repair_gedcom`.
+pub fn repair_gedcom_file(input: &str, output: &str) -> Result<Vec<Repair>, GedcomError> {
+    let (repaired, repairs) = repair_gedcom(&fs::read(input)?);
+    fs::write(output, repaired)?;
+    Ok(repairs)
+}
+
+#[derive(Default)]
+struct Repairer {
+    options: RepairOptions,
+    output: Vec<Vec<u8>>,
+    /// The line of the original file each line of the output came from
+    numbers: Vec<usize>,
+    repairs: Vec<Repair>,
+    /// Every xref in the file, and those given to renamed records
+    xrefs: HashSet<Vec<u8>>,
+    /// The xrefs of the records written so far
+    defined: HashSet<Vec<u8>>,
+    /// The original and repaired levels of the lines the current line may
+    /// belong to
+    parents: Vec<(u8, u8)>,
+    /// The level and tag of the last line written, if it's in a record
+    last: Option<(u8, Vec<u8>)>,
+    /// The record being repaired
+    record: Vec<u8>,
+    /// Whether the file is GEDCOM 7, which has no limit on line length
+    version7: bool,
+    /// The line of the last TRLR, until we know whether it's the end of
+    /// the file
+    trailer: Option<usize>,
+    /// Whether the file has a TRLR anywhere
+    has_trailer: bool,
+    /// Blank and indented lines, counted rather than reported one by one,
+    /// with the first of each
+    blank: (usize, Option<usize>),
+    indented: (usize, Option<usize>),
+}
+
+impl Repairer {
+    fn repair(&mut self, line: Option<usize>, kind: RepairKind, message: &str) {
+        self.repairs.push(Repair {
+            line,
+            kind,
+            message: message.to_string(),
+        });
+    }
+
+    fn line(&mut self, number: usize, text: &[u8]) {
+        let trimmed = trim_start(text);
+        if trimmed.iter().all(u8::is_ascii_whitespace) {
+            self.blank.0 += 1;
+            self.blank.1.get_or_insert(number);
+            return;
+        }
+        if trimmed.len() < text.len() {
+            self.indented.0 += 1;
+            self.indented.1.get_or_insert(number);
+        }
+
+        let line = match parse_line(trimmed) {
+            Some(line) => line,
+            None => return self.continuation(number, trimmed),
+        };
+
+        // A TRLR is only kept if nothing follows it
+        if line.level == 0 {
+            if let Some(trailer) = self.trailer.take() {
+                self.repair(
+                    Some(trailer),
+                    RepairKind::Trailer,
+                    "Removed a TRLR before the end of the file",
+                );
+            }
+            self.parents.clear();
+            self.last = None;
+            self.record = line.tag.to_vec();
+            if line.tag == b"TRLR" {
+                self.trailer = Some(number);
+                self.has_trailer = true;
+                return;
+            }
+        }
+
+        // Each line is one level below the line it belongs to: the last one
+        // with a lower level
+        while self
+            .parents
+            .last()
+            .is_some_and(|(level, _)| *level >= line.level)
+        {
+            self.parents.pop();
+        }
+        let level = match self.parents.last() {
+            Some((_, parent)) => parent + 1,
+            None if line.level == 0 => 0,
+            None => {
+                self.repair(
+                    Some(number),
+                    RepairKind::OrphanLine,
+                    &format!(
+                        "Removed a level {} {} line outside of any record",
+                        line.level,
+                        String::from_utf8_lossy(line.tag)
+                    ),
+                );
+                return;
+            }
+        };
+        self.parents.push((line.level, level));
+        if level != line.level {
+            self.repair(
+                Some(number),
+                RepairKind::LevelJump,
+                &format!(
+                    "Moved {} from level {} to level {}",
+                    String::from_utf8_lossy(line.tag),
+                    line.level,
+                    level
+                ),
+            );
+        }
+
+        let mut xref = line.xref.map(<[u8]>::to_vec);
+        if let (0, Some(old)) = (level, &xref) {
+            if !self.defined.insert(old.clone()) {
+                let new = self.rename(old);
+                self.repair(
+                    Some(number),
+                    RepairKind::DuplicateXref,
+                    &format!(
+                        "Renamed {} {} to {}, since {} is already used",
+                        String::from_utf8_lossy(line.tag),
+                        String::from_utf8_lossy(old),
+                        String::from_utf8_lossy(&new),
+                        String::from_utf8_lossy(old),
+                    ),
+                );
+                xref = Some(new);
+            }
+        }
+
+        if self.record == b"HEAD" && level == 2 && line.tag == b"VERS" {
+            self.version7 = line.value().starts_with(b"7");
+        }
+
+        self.write(number, level, xref.as_deref(), line.tag, line.value());
+    }
+
+    /// A line that isn't a GEDCOM line, added as a CONT of the line before
+    fn continuation(&mut self, number: usize, text: &[u8]) {
+        let level = match &self.last {
+            Some((level, tag)) if tag == b"CONT" || tag == b"CONC" => *level,
+            Some((level, _)) => level + 1,
+            None => {
+                self.repair(
+                    Some(number),
+                    RepairKind::OrphanLine,
+                    "Removed text outside of any record",
+                );
+                return;
+            }
+        };
+        self.repair(
+            Some(number),
+            RepairKind::Continuation,
+            "Made a line that isn't a GEDCOM line a CONT of the line before",
+        );
+        self.write(number, level, None, b"CONT", text);
+    }
+
+    /// Write a line, splitting it with CONC if it's too long
+    fn write(&mut self, number: usize, level: u8, xref: Option<&[u8]>, tag: &[u8], value: &[u8]) {
+        let mut head = level.to_string().into_bytes();
+        if let Some(xref) = xref {
+            head.push(b' ');
+            head.extend_from_slice(xref);
+        }
+        head.push(b' ');
+        head.extend_from_slice(tag);
+
+        let length = characters(&head) + 1 + characters(value);
+        let chunks = if self.options.long_lines && !self.version7 && length > MAX_LINE_LENGTH {
+            split_value(value)
+        } else {
+            vec![value]
+        };
+        if chunks.len() > 1 {
+            self.repair(
+                Some(number),
+                RepairKind::LongLine,
+                &format!(
+                    "Split a {}-character {} line with CONC",
+                    length,
+                    String::from_utf8_lossy(tag)
+                ),
+            );
+        }
+
+        // A CONC continues the line it's under, or the CONT or CONC it
+        // follows
+        let continued = if tag == b"CONT" || tag == b"CONC" {
+            level
+        } else {
+            level + 1
+        };
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            let mut line = if i == 0 {
+                head.clone()
+            } else {
+                format!("{} CONC", continued).into_bytes()
+            };
+            if !chunk.is_empty() {
+                line.push(b' ');
+                line.extend_from_slice(chunk);
+            }
+            self.output.push(line);
+            self.numbers.push(number);
+        }
+        self.last = Some((level, tag.to_vec()));
+    }
+
+    /// Remove the spaces and tabs at the ends of lines, unless a CONC
+    /// follows, which carries on from the space
+    fn trim_ends(&mut self) {
+        let (mut count, mut first) = (0, None);
+        for i in 0..self.output.len() {
+            let continued = self
+                .output
+                .get(i + 1)
+                .and_then(|next| parse_line(next))
+                .is_some_and(|next| next.tag == b"CONC");
+            let line = &mut self.output[i];
+            let length = line
+                .iter()
+                .rposition(|b| *b != b' ' && *b != b'\t')
+                .map_or(0, |end| end + 1);
+            if !continued && length < line.len() {
+                line.truncate(length);
+                count += 1;
+                first.get_or_insert(self.numbers[i]);
+            }
+        }
+        if count > 0 {
+            self.repair(
+                first,
+                RepairKind::TrailingWhitespace,
+                &format!("Removed trailing whitespace from {} line(s)", count),
+            );
+        }
+    }
+
+    /// Remove NOTE records with the same text, and everything under it, as
+    /// one before them, pointing to that one instead
+    fn merge_notes(&mut self) {
+        let starts: Vec<usize> = (0..self.output.len())
+            .filter(|i| self.output[*i].starts_with(b"0 "))
+            .chain([self.output.len()])
+            .collect();
+
+        let mut texts: HashMap<Vec<u8>, Vec<u8>> = HashMap::new();
+        let mut merged: HashMap<Vec<u8>, Vec<u8>> = HashMap::new();
+        let mut removed = vec![false; self.output.len()];
+        for record in starts.windows(2) {
+            let (start, end) = (record[0], record[1]);
+            let (xref, text) = match parse_line(&self.output[start]) {
+                Some(Line {
+                    xref: Some(xref),
+                    tag: b"NOTE",
+                    rest,
+                    ..
+                }) => {
+                    let mut text = rest.to_vec();
+                    for line in &self.output[start + 1..end] {
+                        text.push(b'\n');
+                        text.extend_from_slice(line);
+                    }
+                    (xref.to_vec(), text)
+                }
+                _ => continue,
+            };
+            match texts.get(&text) {
+                Some(kept) => {
+                    self.repairs.push(Repair {
+                        line: Some(self.numbers[start]),
+                        kind: RepairKind::DuplicateNote,
+                        message: format!(
+                            "Merged NOTE {} into {}, which has the same text",
+                            String::from_utf8_lossy(&xref),
+                            String::from_utf8_lossy(kept)
+                        ),
+                    });
+                    merged.insert(xref, kept.clone());
+                    removed[start..end].fill(true);
+                }
+                None => {
+                    texts.insert(text, xref);
+                }
+            }
+        }
+        if merged.is_empty() {
+            return;
+        }
+
+        let mut output = vec![];
+        let mut numbers = vec![];
+        for (i, mut line) in std::mem::take(&mut self.output).into_iter().enumerate() {
+            if removed[i] {
+                continue;
+            }
+            let pointer = parse_line(&line).and_then(|l| l.pointer().map(<[u8]>::to_vec));
+            if let Some((pointer, kept)) = pointer.and_then(|p| Some((p.len(), merged.get(&p)?))) {
+                line.truncate(line.len() - pointer);
+                line.extend_from_slice(kept);
+            }
+            output.push(line);
+            numbers.push(self.numbers[i]);
+        }
+        self.output = output;
+        self.numbers = numbers;
+    }
+
+    /// A new xref for a record whose xref is taken, i.e. @I1_2@ for @I1@
+    fn rename(&mut self, xref: &[u8]) -> Vec<u8> {
+        let base = &xref[1..xref.len() - 1];
+        let mut n = 2;
+        loop {
+            let mut new = vec![b'@'];
+            new.extend_from_slice(base);
+            new.extend_from_slice(format!("_{}@", n).as_bytes());
+            if self.xrefs.insert(new.clone()) {
+                self.defined.insert(new.clone());
+                return new;
+            }
+            n += 1;
+        }
+    }
+
+    fn finish(mut self) -> (Vec<Vec<u8>>, Vec<usize>, Vec<Repair>) {
+        if !self.has_trailer {
+            self.repair(None, RepairKind::Trailer, "Added the missing TRLR");
+        }
+        self.output.push(b"0 TRLR".to_vec());
+        self.numbers.push(0);
+        if self.options.trailing_whitespace {
+            self.trim_ends();
+        }
+        if self.options.duplicate_notes {
+            self.merge_notes();
+        }
+
+        if let (count, Some(first)) = self.blank {
+            self.repair(
+                Some(first),
+                RepairKind::BlankLines,
+                &format!("Removed {} blank line(s)", count),
+            );
+        }
+        if let (count, Some(first)) = self.indented {
+            self.repair(
+                Some(first),
+                RepairKind::Indentation,
+                &format!("Removed the indentation of {} line(s)", count),
+            );
+        }
+        // Repairs to the whole file first, then by line
+        self.repairs.sort_by_key(|r| r.line.unwrap_or(0));
+
+        (self.output, self.numbers, self.repairs)
+    }
+}
+
+/// Make the links between individuals and families go both ways, and
+/// remove those to records that don't exist, as `options` says. Returns the
+/// repairs made, in the order of the records they were made to.
+///
+/// A FAMS is only matched by making the individual the family's husband or
+/// wife if their sex says which, or only one is missing.
+pub fn repair_structure(gedcom: &mut Gedcom, options: &RepairOptions) -> Vec<Repair> {
+    let edits = structural_edits(gedcom, options);
+    for edit in &edits {
+        edit.apply(gedcom);
+    }
+    if !edits.is_empty() {
+        gedcom.kinship = None;
+    }
+
+    let mut repairs: Vec<Repair> = edits
+        .into_iter()
+        .map(|edit| {
+            let line = gedcom.record_positions.get(&edit.record).map(|p| p.line);
+            edit.repair(line)
+        })
+        .collect();
+    repairs.sort_by_key(|r| r.line.unwrap_or(usize::MAX));
+    repairs
+}
+
+/// Make every repair there is to a GEDCOM file's contents: those of
+/// `repair_gedcom_with_options`, then those of `repair_structure`, made to
+/// the lines of the file rather than a model of it, so nothing the model
+/// doesn't keep is lost. Returns the repaired file and the repairs made.
+pub fn fix_gedcom(input: &[u8], options: &RepairOptions) -> (Vec<u8>, Vec<Repair>) {
+    let (mut lines, numbers, mut repairs) = repair_lines(input, options);
+    if let Ok(gedcom) = parse_gedcom_from_bytes(&join(&lines)) {
+        let edits = structural_edits(&gedcom, options);
+        lines = apply_to_lines(lines, &edits);
+        for edit in edits {
+            // Where the record was in the file we were given
+            let line = gedcom
+                .record_positions
+                .get(&edit.record)
+                .and_then(|p| numbers.get(p.line - 1))
+                .copied();
+            repairs.push(edit.repair(line));
+        }
+        repairs.sort_by_key(|r| r.line.unwrap_or(0));
+    }
+    (join(&lines), repairs)
+}
+
+/// Fix a GEDCOM file, writing the result to `output`. See `fix_gedcom`.
+pub fn fix_gedcom_file(
+    input: &str,
+    output: &str,
+    options: &RepairOptions,
+) -> Result<Vec<Repair>, GedcomError> {
+    let (fixed, repairs) = fix_gedcom(&fs::read(input)?, options);
+    fs::write(output, fixed)?;
+    Ok(repairs)
+}
+
+/// Make `xref` the home person in a GEDCOM file's contents, as
+/// `Gedcom::set_home_person` does in the model: the value of the header's
+/// `_HOME` (or `_HME`) line is replaced, or if it has none, a `_HOME` line
+/// is added to the end of the header. Every other line is left as it was.
+/// Returns None if the file has no header.
+pub fn set_home_person(input: &[u8], xref: &str) -> Option<Vec<u8>> {
+    let mut output = Vec::with_capacity(input.len() + xref.len() + 9);
+    let mut header = false;
+    let mut done = false;
+    for (i, text) in input.split_inclusive(|b| *b == b'\n').enumerate() {
+        let end = text.len()
+            - text
+                .iter()
+                .rev()
+                .take_while(|b| b"\r\n".contains(b))
+                .count();
+        let (content, ending) = text.split_at(end);
+        let content = match i {
+            0 => content.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(content),
+            _ => content,
+        };
+        match parse_line(content) {
+            Some(line) if line.level == 0 => {
+                if header && !done {
+                    output.extend_from_slice(format!("1 _HOME {}", xref).as_bytes());
+                    output.extend_from_slice(if ending.is_empty() { b"\n" } else { ending });
+                    done = true;
+                }
+                header = line.tag == b"HEAD";
+            }
+            Some(line)
+                if header
+                    && !done
+                    && line.level == 1
+                    && [&b"_HOME"[..], b"_HME"].contains(&line.tag) =>
+            {
+                output.extend_from_slice(&text[..end - line.rest.len()]);
+                output.extend_from_slice(format!(" {}", xref).as_bytes());
+                output.extend_from_slice(ending);
+                done = true;
+                continue;
+            }
+            _ => {}
+        }
+        output.extend_from_slice(text);
+    }
+    if header && !done {
+        if !output.ends_with(b"\n") {
+            output.push(b'\n');
+        }
+        output.extend_from_slice(format!("1 _HOME {}\n", xref).as_bytes());
+        done = true;
+    }
+    done.then_some(output)
+}
+
+/// A pointer to add to a record, or to remove from it, with everything
+/// under it
+struct Edit {
+    /// The xref of the record
+    record: String,
+    tag: &'static str,
+    pointer: String,
+    add: bool,
+}
+
+impl Edit {
+    fn repair(&self, line: Option<usize>) -> Repair {
+        let (kind, message) = if self.add {
+            (
+                RepairKind::ReciprocalLink,
+                format!("Added {} {} to {}", self.tag, self.pointer, self.record),
+            )
+        } else {
+            (
+                RepairKind::DanglingPointer,
+                format!(
+                    "Removed {} {} from {}, since it doesn't exist",
+                    self.tag, self.pointer, self.record
+                ),
+            )
+        };
+        Repair {
+            line,
+            kind,
+            message,
+        }
+    }
+
+    fn apply(&self, gedcom: &mut Gedcom) {
+        let pointer = self.pointer.as_str();
+        if let Some(individual) = gedcom
+            .individuals
+            .iter_mut()
+            .find(|i| i.xref.as_deref() == Some(self.record.as_str()))
+        {
+            let links = match self.tag {
+                "FAMC" => &mut individual.famc,
+                "FAMS" => &mut individual.fams,
+                _ => {
+                    individual
+                        .sources
+                        .retain(|c| c.xref.as_deref() != Some(pointer));
+                    return;
+                }
+            };
+            if self.add {
+                links.push(Family {
+                    xref: pointer.to_string(),
+                    ..Default::default()
+                });
+            } else {
+                links.retain(|l| l.xref != pointer);
+            }
+        } else if let Some(family) = gedcom.families.iter_mut().find(|f| f.xref == self.record) {
+            let spouse = Spouse {
+                xref: Some(Xref {
+                    xref: Some(pointer.to_string()),
+                }),
+                age: None,
+            };
+            match (self.tag, self.add) {
+                ("HUSB", true) => family.husband = Some(spouse),
+                ("WIFE", true) => family.wife = Some(spouse),
+                ("HUSB", false) => family.husband = None,
+                ("WIFE", false) => family.wife = None,
+                ("CHIL", true) => family.children.push(pointer.to_string()),
+                ("CHIL", false) => family.children.retain(|c| c != pointer),
+                _ => family
+                    .sources
+                    .retain(|c| c.xref.as_deref() != Some(pointer)),
+            }
+        }
+    }
+}
+
+/// What `repair_structure` would change
+fn structural_edits(gedcom: &Gedcom, options: &RepairOptions) -> Vec<Edit> {
+    let individuals: HashSet<&str> = gedcom
+        .individuals
+        .iter()
+        .filter_map(|i| i.xref.as_deref())
+        .collect();
+    let families: HashSet<&str> = gedcom.families.iter().map(|f| f.xref.as_str()).collect();
+    let sources: HashSet<&str> = gedcom.sources.iter().map(|s| s.xref.as_str()).collect();
+    let mut edits = vec![];
+    let mut edit = |record: &str, tag: &'static str, pointer: &str, add: bool| {
+        edits.push(Edit {
+            record: record.to_string(),
+            tag,
+            pointer: pointer.to_string(),
+            add,
+        })
+    };
+
+    if options.dangling_pointers {
+        for individual in &gedcom.individuals {
+            let xref = individual.xref.as_deref().unwrap_or_default();
+            for (tag, links) in [("FAMC", &individual.famc), ("FAMS", &individual.fams)] {
+                for link in links.iter().filter(|l| !families.contains(l.xref.as_str())) {
+                    edit(xref, tag, &link.xref, false);
+                }
+            }
+            for source in individual.sources.iter().filter_map(|c| c.xref.as_deref()) {
+                if !sources.contains(source) {
+                    edit(xref, "SOUR", source, false);
+                }
+            }
+        }
+        for family in &gedcom.families {
+            let spouses = [
+                ("HUSB", family.husband_xref()),
+                ("WIFE", family.wife_xref()),
+            ];
+            let members = spouses
+                .iter()
+                .copied()
+                .chain(family.children.iter().map(|c| ("CHIL", Some(c.as_str()))));
+            for (tag, member) in members {
+                if let Some(member) = member.filter(|m| !individuals.contains(m)) {
+                    edit(&family.xref, tag, member, false);
+                }
+            }
+            for source in family.sources.iter().filter_map(|c| c.xref.as_deref()) {
+                if !sources.contains(source) {
+                    edit(&family.xref, "SOUR", source, false);
+                }
+            }
+        }
+    }
+
+    if options.reciprocal_links {
+        // Families' members who don't link back to them
+        for family in &gedcom.families {
+            let spouses = family.husband_xref().into_iter().chain(family.wife_xref());
+            let members = spouses
+                .map(|s| ("FAMS", s))
+                .chain(family.children.iter().map(|c| ("FAMC", c.as_str())));
+            for (tag, member) in members {
+                let individual = match gedcom.find_individual(member) {
+                    Some(individual) => individual,
+                    None => continue,
+                };
+                let links = if tag == "FAMS" {
+                    &individual.fams
+                } else {
+                    &individual.famc
+                };
+                if !links.iter().any(|l| l.xref == family.xref) {
+                    edit(member, tag, &family.xref, true);
+                }
+            }
+        }
+
+        // Individuals' families that don't list them
+        let mut filled: HashSet<(&str, &str)> = HashSet::new();
+        for individual in &gedcom.individuals {
+            let xref = match individual.xref.as_deref() {
+                Some(xref) => xref,
+                None => continue,
+            };
+            for link in &individual.famc {
+                if let Some(family) = gedcom.find_family(&link.xref) {
+                    if !family.children.iter().any(|c| c == xref) {
+                        edit(&family.xref, "CHIL", xref, true);
+                    }
+                }
+            }
+            for link in &individual.fams {
+                let family = match gedcom.find_family(&link.xref) {
+                    Some(family) => family,
+                    None => continue,
+                };
+                if family.husband_xref() == Some(xref) || family.wife_xref() == Some(xref) {
+                    continue;
+                }
+                // A spouse who doesn't exist is about to be removed
+                let empty = |tag: &'static str, spouse: Option<&str>| {
+                    !spouse.is_some_and(|s| individuals.contains(s) || !options.dangling_pointers)
+                        && !filled.contains(&(family.xref.as_str(), tag))
+                };
+                let husband = empty("HUSB", family.husband_xref());
+                let wife = empty("WIFE", family.wife_xref());
+                let tag = match (&individual.gender, husband, wife) {
+                    (Gender::Male, true, _) => "HUSB",
+                    (Gender::Female, _, true) => "WIFE",
+                    (Gender::Male | Gender::Female, _, _) => continue,
+                    (_, true, false) => "HUSB",
+                    (_, false, true) => "WIFE",
+                    _ => continue,
+                };
+                filled.insert((family.xref.as_str(), tag));
+                edit(&family.xref, tag, xref, true);
+            }
+        }
+    }
+
+    edits
+}
+
+/// Make the edits to the lines of a file: removing lines, and those under
+/// them, and adding lines at the ends of records
+fn apply_to_lines(lines: Vec<Vec<u8>>, edits: &[Edit]) -> Vec<Vec<u8>> {
+    let mut output: Vec<Vec<u8>> = vec![];
+    let mut record: Option<Vec<u8>> = None;
+    let mut skipping = false;
+    let added = |record: &Option<Vec<u8>>, output: &mut Vec<Vec<u8>>| {
+        for edit in edits.iter().filter(|e| e.add) {
+            if record.as_deref() == Some(edit.record.as_bytes()) {
+                output.push(format!("1 {} {}", edit.tag, edit.pointer).into_bytes());
+            }
+        }
+    };
+    for text in lines {
+        if let Some(line) = parse_line(&text) {
+            if line.level == 0 {
+                added(&record, &mut output);
+                record = line.xref.map(<[u8]>::to_vec);
+                skipping = false;
+            } else if line.level == 1 {
+                skipping = edits.iter().any(|e| {
+                    !e.add
+                        && record.as_deref() == Some(e.record.as_bytes())
+                        && line.tag == e.tag.as_bytes()
+                        && line.pointer() == Some(e.pointer.as_bytes())
+                });
+            }
+        }
+        if !skipping {
+            output.push(text);
+        }
+    }
+    added(&record, &mut output);
+    output
+}
+
+/// A line, split into its parts
+struct Line<'a> {
+    level: u8,
+    xref: Option<&'a [u8]>,
+    tag: &'a [u8],
+    /// Everything after the tag, including the space before any value
+    rest: &'a [u8],
+}
+
+impl<'a> Line<'a> {
+    fn value(&self) -> &'a [u8] {
+        self.rest.strip_prefix(b" ").unwrap_or(self.rest)
+    }
+
+    /// The xref the line points to, if its value is a pointer
+    fn pointer(&self) -> Option<&'a [u8]> {
+        let value = self.value();
+        (value.len() > 2 && value.starts_with(b"@") && value.ends_with(b"@")).then_some(value)
+    }
+}
+
+/// The lines of a file, each ended with LF
+fn join(lines: &[Vec<u8>]) -> Vec<u8> {
+    let mut output = lines.join(&b'\n');
+    output.push(b'\n');
+    output
+}
+
+/// Split a line into its parts, if it's well-formed: a level, an optional
+/// xref, and a tag, separated by spaces
+fn parse_line(text: &[u8]) -> Option<Line<'_>> {
+    fn word(text: &[u8]) -> (&[u8], &[u8]) {
+        let text = trim_start(text);
+        let end = text.iter().position(|b| *b == b' ').unwrap_or(text.len());
+        (&text[..end], &text[end..])
+    }
+
+    let digits = text.iter().take_while(|b| b.is_ascii_digit()).count();
+    if digits == 0 || digits > 2 || text.get(digits) != Some(&b' ') {
+        return None;
+    }
+    let level = std::str::from_utf8(&text[..digits]).ok()?.parse().ok()?;
+
+    let (mut tag, mut rest) = word(&text[digits..]);
+    let mut xref = None;
+    if tag.starts_with(b"@") {
+        if tag.len() < 3 || !tag.ends_with(b"@") {
+            return None;
+        }
+        xref = Some(tag);
+        (tag, rest) = word(rest);
+    }
+    if tag.is_empty() || !tag.iter().all(|b| b.is_ascii_alphanumeric() || *b == b'_') {
+        return None;
+    }
+
+    Some(Line {
+        level,
+        xref,
+        tag,
+        rest,
+    })
+}
+
+/// Split the file into lines, however they're terminated. Returns the lines,
+/// and how many were terminated by CRLF or CR.
+pub(crate) fn split_lines(input: &[u8]) -> (Vec<&[u8]>, usize) {
+    let mut lines = vec![];
+    let mut endings = 0;
+    let mut start = 0;
+    let mut i = 0;
+    while i < input.len() {
+        match input[i] {
+            b'\n' => {
+                lines.push(&input[start..i]);
+                start = i + 1;
+            }
+            b'\r' => {
+                lines.push(&input[start..i]);
+                endings += 1;
+                if input.get(i + 1) == Some(&b'\n') {
+                    i += 1;
+                }
+                start = i + 1;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    if start < input.len() {
+        lines.push(&input[start..]);
+    }
+    (lines, endings)
+}
+
+/// Split a value into pieces short enough for a GEDCOM line, as the writer
+/// does, never splitting a character or next to a space
+fn split_value(value: &[u8]) -> Vec<&[u8]> {
+    let starts = |text: &[u8]| {
+        text.iter()
+            .enumerate()
+            .filter(|(_, b)| **b & 0xC0 != 0x80)
+            .map(|(i, _)| i)
+            .collect::<Vec<usize>>()
+    };
+    let mut chunks = vec![];
+    let mut rest = value;
+    loop {
+        let starts = starts(rest);
+        let mut index = MAX_VALUE_LENGTH;
+        while index > 1
+            && starts
+                .get(index)
+                .is_some_and(|split| rest[*split] == b' ' || rest[*split - 1] == b' ')
+        {
+            index -= 1;
+        }
+        match starts.get(index) {
+            Some(split) if index > 1 => {
+                chunks.push(&rest[..*split]);
+                rest = &rest[*split..];
+            }
+            _ => break,
+        }
+    }
+    chunks.push(rest);
+    chunks
+}
+
+/// How many characters a line has, counting bytes that aren't UTF-8 as
+/// characters of their own
+pub(crate) fn characters(text: &[u8]) -> usize {
+    String::from_utf8_lossy(text).chars().count()
+}
+
+fn trim_start(text: &[u8]) -> &[u8] {
+    let start = text
+        .iter()
+        .position(|b| *b != b' ' && *b != b'\t')
+        .unwrap_or(text.len());
+    &text[start..]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repair() {
+        let long = "x".repeat(300);
+        let input = format!(
+            "\u{FEFF}0 HEAD\r\n1 GEDC\r\n2 VERS 5.5.1\r\n\r\n0 @I1@ INDI\r\n  1 NAME Joe /Bloggs/\r\n1 BIRT\r\n3 DATE 1900\r\n4 _SRC x\r\n3 PLAC York\r\n1 NOTE First line\r\nsecond line\r\nthird line\r\n0 TRLR\r\n0 @I1@ INDI\r\n1 NAME Jane /Doe/\r\n1 NOTE {}\r\n"
+            , long
+        );
+        let (output, repairs) = repair_gedcom(input.as_bytes());
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(
+            output
+                == format!(
+                    "0 HEAD\n1 GEDC\n2 VERS 5.5.1\n0 @I1@ INDI\n1 NAME Joe /Bloggs/\n1 BIRT\n2 DATE 1900\n3 _SRC x\n2 PLAC York\n1 NOTE First line\n2 CONT second line\n2 CONT third line\n0 @I1_2@ INDI\n1 NAME Jane /Doe/\n1 NOTE {}\n2 CONC {}\n0 TRLR\n",
+                    &long[..200],
+                    &long[200..]
+                )
+        );
+
+        let kinds: Vec<(Option<usize>, RepairKind)> =
+            repairs.iter().map(|r| (r.line, r.kind)).collect();
+        assert!(
+            kinds
+                == [
+                    (None, RepairKind::LineEndings),
+                    (Some(1), RepairKind::ByteOrderMark),
+                    (Some(4), RepairKind::BlankLines),
+                    (Some(6), RepairKind::Indentation),
+                    (Some(8), RepairKind::LevelJump),
+                    (Some(9), RepairKind::LevelJump),
+                    (Some(10), RepairKind::LevelJump),
+                    (Some(12), RepairKind::Continuation),
+                    (Some(13), RepairKind::Continuation),
+                    (Some(14), RepairKind::Trailer),
+                    (Some(15), RepairKind::DuplicateXref),
+                    (Some(17), RepairKind::LongLine),
+                ]
+        );
+        assert!(repairs[0].message == "Changed 17 CRLF or CR line endings to LF");
+        assert!(
+            repairs[10].to_string()
+                == "line 15: Renamed INDI @I1@ to @I1_2@, since @I1@ is already used"
+        );
+
+        let gedcom = parse_gedcom_from_bytes(output.as_bytes()).unwrap();
+        assert!(gedcom.warnings.is_empty());
+        assert!(gedcom.individuals.len() == 2);
+        assert!(gedcom.individuals[1].notes[0].note == Some(long));
+    }
+
+    #[test]
+    fn repair_nothing() {
+        let input = "0 HEAD\n0 @I1@ INDI\n1 NAME Joe /Bloggs/\n0 TRLR\n";
+        let (output, repairs) = repair_gedcom(input.as_bytes());
+        assert!(output == input.as_bytes());
+        assert!(repairs.is_empty());
+
+        // Bytes that aren't UTF-8 are left alone
+        let input = b"garbage\n0 HEAD\n1 CHAR ANSI\n0 @I1@ INDI\n1 NAME Jos\xe9 /Bloggs/\n2 GIVN";
+        let (output, repairs) = repair_gedcom(input);
+        assert!(
+            output
+                == b"0 HEAD\n1 CHAR ANSI\n0 @I1@ INDI\n1 NAME Jos\xe9 /Bloggs/\n2 GIVN\n0 TRLR\n"
+        );
+        let kinds: Vec<RepairKind> = repairs.iter().map(|r| r.kind).collect();
+        assert!(kinds == [RepairKind::Trailer, RepairKind::OrphanLine]);
+    }
+
+    #[test]
+    fn whitespace_and_notes() {
+        let input = "0 HEAD\n0 @N1@ NOTE Same \n1 CONC text\n0 @N2@ NOTE Other \n0 @N3@ NOTE Same \n1 CONC text\n0 @I1@ INDI\n1 NOTE @N3@\n1 NAME Joe /Bloggs/\t\n0 TRLR\n";
+        let (output, repairs) = repair_gedcom(input.as_bytes());
+        assert!(
+            output
+                == b"0 HEAD\n0 @N1@ NOTE Same \n1 CONC text\n0 @N2@ NOTE Other\n0 @I1@ INDI\n1 NOTE @N1@\n1 NAME Joe /Bloggs/\n0 TRLR\n"
+        );
+        let kinds: Vec<(Option<usize>, RepairKind)> =
+            repairs.iter().map(|r| (r.line, r.kind)).collect();
+        assert!(
+            kinds
+                == [
+                    (Some(4), RepairKind::TrailingWhitespace),
+                    (Some(5), RepairKind::DuplicateNote),
+                ]
+        );
+        assert!(repairs[0].message == "Removed trailing whitespace from 2 line(s)");
+        assert!(repairs[1].message == "Merged NOTE @N3@ into @N1@, which has the same text");
+
+        let options = RepairOptions::new()
+            .trailing_whitespace(false)
+            .duplicate_notes(false);
+        let (output, repairs) = repair_gedcom_with_options(input.as_bytes(), &options);
+        assert!(output == input.as_bytes() && repairs.is_empty());
+    }
+
+    #[test]
+    fn structure() {
+        let input = "0 HEAD
+0 @I1@ INDI
+1 SEX M
+1 FAMS @F1@
+1 FAMC @F9@
+0 @I2@ INDI
+1 FAMS @F1@
+0 @I3@ INDI
+1 SOUR @S9@
+2 PAGE 4
+0 @I4@ INDI
+1 FAMC @F1@
+0 @F1@ FAM
+1 WIFE @I8@
+1 CHIL @I3@
+0 TRLR
+";
+        let mut gedcom = parse_gedcom_from_bytes(input.as_bytes()).unwrap();
+        let repairs = gedcom.repair(&RepairOptions::default());
+        let messages: Vec<String> = repairs.iter().map(|r| r.to_string()).collect();
+        assert!(
+            messages
+                == [
+                    "line 2: Removed FAMC @F9@ from @I1@, since it doesn't exist",
+                    "line 8: Removed SOUR @S9@ from @I3@, since it doesn't exist",
+                    "line 8: Added FAMC @F1@ to @I3@",
+                    "line 13: Removed WIFE @I8@ from @F1@, since it doesn't exist",
+                    "line 13: Added HUSB @I1@ to @F1@",
+                    "line 13: Added WIFE @I2@ to @F1@",
+                    "line 13: Added CHIL @I4@ to @F1@",
+                ]
+        );
+        let family = &gedcom.families[0];
+        assert!(family.husband_xref() == Some("@I1@") && family.wife_xref() == Some("@I2@"));
+        assert!(family.children == ["@I3@", "@I4@"]);
+        assert!(gedcom.individuals[0].famc.is_empty());
+        assert!(gedcom.individuals[2].famc[0].xref == "@F1@");
+        assert!(gedcom.repair(&RepairOptions::default()).is_empty());
+
+        // The same, made to the file
+        let (output, repairs) = fix_gedcom(input.as_bytes(), &RepairOptions::default());
+        assert!(repairs.len() == 7);
+        assert!(
+            String::from_utf8(output).unwrap()
+                == "0 HEAD
+0 @I1@ INDI
+1 SEX M
+1 FAMS @F1@
+0 @I2@ INDI
+1 FAMS @F1@
+0 @I3@ INDI
+1 FAMC @F1@
+0 @I4@ INDI
+1 FAMC @F1@
+0 @F1@ FAM
+1 CHIL @I3@
+1 HUSB @I1@
+1 WIFE @I2@
+1 CHIL @I4@
+0 TRLR
+"
+        );
+
+        let options = RepairOptions::new()
+            .reciprocal_links(false)
+            .dangling_pointers(false);
+        let mut gedcom = parse_gedcom_from_bytes(input.as_bytes()).unwrap();
+        assert!(gedcom.repair(&options).is_empty());
+    }
+
+    #[test]
+    fn split() {
+        let value = format!("{} {}", "a".repeat(199), "b".repeat(100));
+        let chunks = split_value(value.as_bytes());
+        assert!(chunks.len() == 2 && chunks[0].len() == 198);
+
+        let value = "é".repeat(250);
+        let chunks = split_value(value.as_bytes());
+        assert!(chunks.len() == 2 && chunks[0].len() == 400);
+        assert!(split_value(b"short") == [b"short"]);
+    }
+
+    #[test]
+    fn home_person() {
+        let input =
+            "\u{FEFF}0 HEAD\r\n1 GEDC\r\n2 VERS 5.5.1\r\n0 @I1@ INDI\r\n1 _CUSTOM x\r\n0 TRLR\r\n";
+        let output = set_home_person(input.as_bytes(), "@I1@").unwrap();
+        assert!(
+            String::from_utf8(output).unwrap() == input.replace("0 @I1@", "1 _HOME @I1@\r\n0 @I1@")
+        );
+
+        let input = "0 HEAD\n1 _HME @I1@\n1 CHAR UTF-8\n0 @I1@ INDI\n0 @I2@ INDI\n0 TRLR";
+        let output = set_home_person(input.as_bytes(), "@I2@").unwrap();
+        assert!(String::from_utf8(output).unwrap() == input.replace("_HME @I1@", "_HME @I2@"));
+
+        // A header and nothing else
+        let output = set_home_person(b"0 HEAD", "@I1@").unwrap();
+        assert!(output == b"0 HEAD\n1 _HOME @I1@\n");
+        assert!(set_home_person(b"0 @I1@ INDI\n0 TRLR\n", "@I1@").is_none());
+    }
+}
@@ -0,0 +1,636 @@
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crate::chart::roman;
+use crate::graph::escape;
+use crate::relationship::{birth_families, ordinal, Relatives};
+use crate::types::{
+    DateQualifier, EventDetail, EventType, Gedcom, GedcomDate, Gender, Individual, SimpleDate,
+    SourceCitation,
+};
+
+// Narrative reports, written as prose rather than drawn as charts, for
+// sharing with family or publishing:
+//
+//     **1. Joe Bloggs** was born on 12 Mar 1840 in York[^1]; he married Ann
+//     Smith on 3 Jun 1865 in Leeds; he died on 1 Jan 1900 in Leeds.
+//
+// A descendant report follows the Register style: a generation at a time,
+// each person with children of their own given a running number, and
+// their children listed under them in Roman numerals. An ahnentafel report
+// numbers ancestors the usual way: the root is 1, and the father and
+// mother of n are 2n and 2n + 1.
+//
+// Each report is built as a list of blocks, then written as Markdown or
+// HTML, with the sources cited by each event as numbered footnotes.
+
+/// How a report is written
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Markdown, with footnotes in the `[^1]` style most renderers support
+    #[default]
+    Markdown,
+    /// A complete HTML page
+    Html,
+}
+
+impl FromStr for ReportFormat {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<ReportFormat, Self::Err> {
+        match input.to_lowercase().as_str() {
+            "markdown" | "md" => Ok(ReportFormat::Markdown),
+            "html" => Ok(ReportFormat::Html),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A Register-style report of `root` and `generations` generations of their
+/// descendants
+pub fn descendant_report(
+    gedcom: &Gedcom,
+    root: &Individual,
+    generations: usize,
+    format: ReportFormat,
+) -> String {
+    let mut report = Report::new(gedcom, format!("Descendants of {}", name(root)));
+
+    // Each generation's numbered individuals, in the order they're numbered
+    let mut generation = vec![(1, root)];
+    let mut running = 1;
+    for g in 1..=generations.max(1) {
+        if generation.is_empty() {
+            break;
+        }
+        report
+            .blocks
+            .push(Block::Heading(format!("Generation {}", g)));
+        let mut next = vec![];
+        for (number, individual) in generation {
+            report.biography(&number.to_string(), individual, g == 1);
+
+            for family in report.relatives.spouse_families(individual) {
+                let children: Vec<&Individual> = family
+                    .children
+                    .iter()
+                    .filter_map(|xref| report.relatives.find_individual(xref))
+                    .collect();
+                if children.is_empty() {
+                    continue;
+                }
+                let spouse = family
+                    .husband_xref()
+                    .into_iter()
+                    .chain(family.wife_xref())
+                    .filter(|xref| Some(*xref) != individual.xref.as_deref())
+                    .find_map(|xref| report.relatives.find_individual(xref));
+                let intro = match spouse {
+                    Some(spouse) => {
+                        format!("Children of {} and {}:", name(individual), name(spouse))
+                    }
+                    None => format!("Children of {}:", name(individual)),
+                };
+
+                let mut items = vec![];
+                for (place, child) in children.into_iter().enumerate() {
+                    let continued = g < generations && !report.relatives.children(child).is_empty();
+                    let mut item = String::new();
+                    if continued {
+                        running += 1;
+                        item += &format!("{} ", running);
+                        next.push((running, child));
+                    }
+                    item += &format!("{}. {}", roman(place + 1), name(child));
+                    let lifespan = child.lifespan_string();
+                    if !lifespan.is_empty() {
+                        item += &format!(", {}", lifespan);
+                    }
+                    items.push(item);
+                }
+                report.blocks.push(Block::List(intro, items));
+            }
+        }
+        generation = next;
+    }
+
+    report.render(format)
+}
+
+/// A narrative ahnentafel report of `root` and `generations` generations of
+/// their ancestors. Someone who is an ancestor more than once, where
+/// cousins married, is written up once, and referred back to after that.
+pub fn ahnentafel_report(
+    gedcom: &Gedcom,
+    root: &Individual,
+    generations: usize,
+    format: ReportFormat,
+) -> String {
+    let mut report = Report::new(gedcom, format!("Ancestors of {}", name(root)));
+
+    let mut seen: HashMap<&str, u64> = HashMap::new();
+    let mut generation: Vec<(u64, &Individual)> = vec![(1, root)];
+    for g in 0..=generations {
+        if generation.is_empty() {
+            break;
+        }
+        report.blocks.push(Block::Heading(ancestor_heading(g)));
+        let mut next = vec![];
+        for (number, individual) in generation {
+            if let Some(first) = individual.xref.as_deref().and_then(|x| seen.get(x)) {
+                report.blocks.push(Block::Paragraph(vec![
+                    Span::Strong(format!("{}. {}", number, name(individual))),
+                    Span::Text(format!(": see number {}.", first)),
+                ]));
+                continue;
+            }
+            if let Some(xref) = individual.xref.as_deref() {
+                seen.insert(xref, number);
+            }
+            report.biography(&number.to_string(), individual, false);
+
+            if let Some(family) = birth_families(gedcom, individual).first() {
+                let father = family
+                    .husband_xref()
+                    .and_then(|x| gedcom.find_individual(x));
+                let mother = family.wife_xref().and_then(|x| gedcom.find_individual(x));
+                next.extend(father.map(|f| (2 * number, f)));
+                next.extend(mother.map(|m| (2 * number + 1, m)));
+            }
+        }
+        generation = next;
+    }
+
+    report.render(format)
+}
+
+/// "Parents", "Grandparents", "Great-grandparents", "2nd great-grandparents"...
+fn ancestor_heading(generation: usize) -> String {
+    match generation {
+        0 => "Generation 1".to_string(),
+        1 => "Parents".to_string(),
+        2 => "Grandparents".to_string(),
+        3 => "Great-grandparents".to_string(),
+        g => format!("{} great-grandparents", ordinal(g - 2)),
+    }
+}
+
+enum Block {
+    Heading(String),
+    Paragraph(Vec<Span>),
+    /// A list, with a line introducing it
+    List(String, Vec<String>),
+}
+
+enum Span {
+    Text(String),
+    Strong(String),
+    /// A reference to a footnote, counting from 1
+    Note(usize),
+}
+
+struct Report<'a> {
+    gedcom: &'a Gedcom,
+    relatives: Relatives<'a>,
+    title: String,
+    blocks: Vec<Block>,
+    /// The text of each footnote, in the order they're first cited
+    notes: Vec<String>,
+}
+
+impl<'a> Report<'a> {
+    fn new(gedcom: &'a Gedcom, title: String) -> Report<'a> {
+        Report {
+            gedcom,
+            relatives: Relatives::new(gedcom),
+            title,
+            blocks: vec![],
+            notes: vec![],
+        }
+    }
+
+    /// A paragraph about `individual`: their birth, marriages and death,
+    /// each with its date, place and sources. With `parents`, who their
+    /// parents were.
+    fn biography(&mut self, number: &str, individual: &Individual, parents: bool) {
+        let pronoun = match individual.gender {
+            Gender::Male => "he",
+            Gender::Female => "she",
+            _ => "they",
+        };
+        let mut spans = vec![Span::Strong(format!("{}. {}", number, name(individual)))];
+        if parents {
+            let parents: Vec<String> = self
+                .relatives
+                .parents(individual)
+                .iter()
+                .map(|p| name(p))
+                .collect();
+            if !parents.is_empty() {
+                let child = match individual.gender {
+                    Gender::Male => "son",
+                    Gender::Female => "daughter",
+                    _ => "child",
+                };
+                spans.push(Span::Text(format!(
+                    ", {} of {},",
+                    child,
+                    parents.join(" and ")
+                )));
+            }
+        }
+
+        let mut clauses: Vec<(String, Option<&EventDetail>)> = vec![];
+        let first = |kinds: &[EventType]| {
+            kinds.iter().find_map(|kind| {
+                individual
+                    .all_events()
+                    .find(|e| e.event_type == *kind)
+                    .map(|e| (*kind, e.detail))
+            })
+        };
+        if let Some((kind, detail)) =
+            first(&[EventType::Birth, EventType::Christening, EventType::Baptism])
+        {
+            let verb = match kind {
+                EventType::Birth => "was born",
+                EventType::Christening => "was christened",
+                _ => "was baptized",
+            };
+            clauses.push((verb.to_string(), detail));
+        }
+        for family in self.relatives.spouse_families(individual) {
+            let spouse = family
+                .husband_xref()
+                .into_iter()
+                .chain(family.wife_xref())
+                .filter(|xref| Some(*xref) != individual.xref.as_deref())
+                .find_map(|xref| self.relatives.find_individual(xref));
+            let marriage = family.marriage.first().and_then(|m| m.detail.as_ref());
+            match spouse {
+                Some(spouse) => clauses.push((format!("married {}", name(spouse)), marriage)),
+                None if marriage.is_some() => clauses.push(("married".to_string(), marriage)),
+                None => {}
+            }
+        }
+        if let Some((kind, detail)) = first(&[EventType::Death, EventType::Burial]) {
+            let verb = match kind {
+                EventType::Death => "died",
+                _ => "was buried",
+            };
+            clauses.push((verb.to_string(), detail));
+        }
+
+        if clauses.is_empty() {
+            spans.push(Span::Text(".".to_string()));
+        }
+        for (i, (clause, detail)) in clauses.iter().enumerate() {
+            let mut text = if i == 0 {
+                format!(" {}", clause)
+            } else {
+                format!("; {} {}", pronoun, clause)
+            };
+            if let Some(date) = detail.and_then(|d| d.date.as_deref()) {
+                text += &format!(" {}", when(date));
+            }
+            if let Some(place) = detail.and_then(|d| d.place.as_ref()?.name.as_deref()) {
+                text += &format!(" in {}", place);
+            }
+            if i == clauses.len() - 1 {
+                text += ".";
+            }
+            spans.push(Span::Text(text));
+            if let Some(detail) = detail {
+                self.cite(&mut spans, &detail.sources);
+            }
+        }
+        self.blocks.push(Block::Paragraph(spans));
+    }
+
+    /// Add a footnote reference for each citation, reusing the footnote of
+    /// any citing the same source and page
+    fn cite(&mut self, spans: &mut Vec<Span>, citations: &[SourceCitation]) {
+        for citation in citations {
+            let note = citation_text(self.gedcom, citation);
+            let number = match self.notes.iter().position(|n| *n == note) {
+                Some(i) => i + 1,
+                None => {
+                    self.notes.push(note);
+                    self.notes.len()
+                }
+            };
+            // A footnote goes before the punctuation ending its clause
+            let punctuation = match spans.last_mut() {
+                Some(Span::Text(text)) if text.ends_with('.') => text.pop(),
+                _ => None,
+            };
+            spans.push(Span::Note(number));
+            if let Some(punctuation) = punctuation {
+                spans.push(Span::Text(punctuation.to_string()));
+            }
+        }
+    }
+
+    fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Markdown => self.markdown(),
+            ReportFormat::Html => self.html(),
+        }
+    }
+
+    fn markdown(&self) -> String {
+        let spans = |spans: &[Span]| -> String {
+            spans
+                .iter()
+                .map(|span| match span {
+                    Span::Text(text) => text.clone(),
+                    Span::Strong(text) => format!("**{}**", text),
+                    Span::Note(n) => format!("[^{}]", n),
+                })
+                .collect()
+        };
+
+        let mut md = format!("# {}\n", self.title);
+        for block in &self.blocks {
+            md += "\n";
+            match block {
+                Block::Heading(heading) => md += &format!("## {}\n", heading),
+                Block::Paragraph(paragraph) => md += &format!("{}\n", spans(paragraph)),
+                Block::List(intro, items) => {
+                    md += &format!("{}\n\n", intro);
+                    for item in items {
+                        md += &format!("- {}\n", item);
+                    }
+                }
+            }
+        }
+        if !self.notes.is_empty() {
+            md += "\n";
+            for (i, note) in self.notes.iter().enumerate() {
+                md += &format!("[^{}]: {}\n", i + 1, note);
+            }
+        }
+        md
+    }
+
+    fn html(&self) -> String {
+        let spans = |spans: &[Span]| -> String {
+            spans
+                .iter()
+                .map(|span| match span {
+                    Span::Text(text) => escape(text),
+                    Span::Strong(text) => format!("<strong>{}</strong>", escape(text)),
+                    Span::Note(n) => format!("<sup><a href=\"#note-{0}\">{0}</a></sup>", n),
+                })
+                .collect()
+        };
+
+        let title = escape(&self.title);
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{0}</title>\n</head>\n<body>\n<h1>{0}</h1>\n",
+            title
+        );
+        for block in &self.blocks {
+            match block {
+                Block::Heading(heading) => html += &format!("<h2>{}</h2>\n", escape(heading)),
+                Block::Paragraph(paragraph) => html += &format!("<p>{}</p>\n", spans(paragraph)),
+                Block::List(intro, items) => {
+                    html += &format!("<p>{}</p>\n<ul>\n", escape(intro));
+                    for item in items {
+                        html += &format!("<li>{}</li>\n", escape(item));
+                    }
+                    html += "</ul>\n";
+                }
+            }
+        }
+        if !self.notes.is_empty() {
+            html += "<h2>Sources</h2>\n<ol>\n";
+            for (i, note) in self.notes.iter().enumerate() {
+                html += &format!("<li id=\"note-{}\">{}</li>\n", i + 1, escape(note));
+            }
+            html += "</ol>\n";
+        }
+        html += "</body>\n</html>\n";
+        html
+    }
+}
+
+/// A citation as a single line: the source's author and title, and where in
+/// it to look
+pub(crate) fn citation_text(gedcom: &Gedcom, citation: &SourceCitation) -> String {
+    let source = citation.xref.as_deref().and_then(|x| gedcom.find_source(x));
+    let mut parts: Vec<String> = vec![];
+    match source {
+        Some(source) => {
+            parts.extend(source.author.clone());
+            parts.extend(
+                source
+                    .title
+                    .clone()
+                    .or_else(|| source.abbreviation.clone())
+                    .or_else(|| Some(source.xref.clone())),
+            );
+        }
+        // A source without a record is described by its text
+        None => parts.extend(
+            citation
+                .xref
+                .clone()
+                .or_else(|| citation.note.as_ref()?.note.clone()),
+        ),
+    }
+    if let Some(page) = &citation.page {
+        parts.push(format!("page {}", page));
+    }
+    // However the source's fields run on
+    let parts: Vec<String> = parts
+        .iter()
+        .map(|p| p.split_whitespace().collect::<Vec<&str>>().join(" "))
+        .collect();
+    parts.join(", ")
+}
+
+fn name(individual: &Individual) -> String {
+    individual
+        .primary_name()
+        .unwrap_or_else(|| "(no name)".to_string())
+}
+
+/// A date as it reads in a sentence, i.e. "on 12 Mar 1840", "in 1840",
+/// "about 1840" or "between 1820 and 1830". Dates that can't be read are
+/// given as written.
+fn when(date: &str) -> String {
+    let date = match GedcomDate::from_str(date) {
+        Ok(date) => date,
+        Err(_) => return date.to_string(),
+    };
+    let on = |date: Option<SimpleDate>| match date {
+        Some(date) if date.day.is_some() => format!("on {}", day(&date)),
+        Some(date) => format!("in {}", day(&date)),
+        None => String::new(),
+    };
+    let plain = |date: Option<SimpleDate>| date.map(|d| day(&d)).unwrap_or_default();
+    match date.qualifier {
+        DateQualifier::Exact | DateQualifier::Interpreted => on(date.start),
+        DateQualifier::About => format!("about {}", plain(date.start)),
+        DateQualifier::Calculated => format!("{} (calculated)", on(date.start)),
+        DateQualifier::Estimated => format!("about {} (estimated)", plain(date.start)),
+        DateQualifier::Before => format!("before {}", plain(date.start.or(date.end))),
+        DateQualifier::After => format!("after {}", plain(date.start)),
+        DateQualifier::Between => format!("between {} and {}", plain(date.start), plain(date.end)),
+        DateQualifier::From => format!("from {}", plain(date.start)),
+        DateQualifier::To => format!("until {}", plain(date.start.or(date.end))),
+        DateQualifier::FromTo => format!("from {} to {}", plain(date.start), plain(date.end)),
+        DateQualifier::Phrase => format!("({})", date.phrase.unwrap_or_default()),
+    }
+}
+
+/// A date with the month written as in prose, i.e. "12 Mar 1840"
+fn day(date: &SimpleDate) -> String {
+    date.to_string()
+        .split(' ')
+        .map(|word| match word.len() {
+            3 if word.chars().all(|c| c.is_ascii_uppercase()) => {
+                word[..1].to_string() + &word[1..].to_lowercase()
+            }
+            _ => word.to_string(),
+        })
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_gedcom_from_str;
+
+    const DATA: &str = "0 HEAD
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 SEX M
+1 BIRT
+2 DATE 12 MAR 1840
+2 PLAC York
+2 SOUR @S1@
+3 PAGE 12
+1 DEAT
+2 DATE ABT 1900
+2 SOUR @S1@
+3 PAGE 12
+1 FAMS @F1@
+0 @I2@ INDI
+1 NAME Ann /Smith/
+1 SEX F
+1 FAMS @F1@
+1 FAMC @F2@
+0 @I3@ INDI
+1 NAME Sam /Bloggs/
+1 SEX M
+1 BIRT
+2 DATE 1866
+1 FAMC @F1@
+1 FAMS @F3@
+0 @I4@ INDI
+1 NAME Liz /Bloggs/
+1 FAMC @F1@
+0 @I5@ INDI
+1 NAME Tom /Bloggs/
+1 FAMC @F3@
+0 @I6@ INDI
+1 NAME Bill /Smith/
+1 FAMS @F2@
+0 @F1@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 CHIL @I3@
+1 CHIL @I4@
+1 MARR
+2 DATE BET 1864 AND 1865
+2 PLAC Leeds
+0 @F2@ FAM
+1 HUSB @I6@
+1 CHIL @I2@
+0 @F3@ FAM
+1 HUSB @I3@
+1 CHIL @I5@
+0 @S1@ SOUR
+1 TITL Parish
+2 CONT registers
+1 AUTH St Mary's
+0 TRLR
+";
+
+    #[test]
+    fn descendants() {
+        let gedcom = parse_gedcom_from_str(DATA).unwrap();
+        let joe = gedcom.find_individual("@I1@").unwrap();
+        let report = descendant_report(&gedcom, joe, 3, ReportFormat::Markdown);
+        assert!(report.starts_with("# Descendants of Joe Bloggs\n\n## Generation 1\n\n"));
+        assert!(report.contains(
+            "**1. Joe Bloggs** was born on 12 Mar 1840 in York[^1]; he married Ann Smith \
+             between 1864 and 1865 in Leeds; he died about 1900[^1].\n"
+        ));
+        assert!(report.contains(
+            "Children of Joe Bloggs and Ann Smith:\n\n- 2 i. Sam Bloggs, 1866–\n- ii. Liz Bloggs\n"
+        ));
+        assert!(report.contains("## Generation 2\n\n**2. Sam Bloggs** was born in 1866.\n"));
+        assert!(report.contains("- i. Tom Bloggs\n"));
+        assert!(report.ends_with("\n[^1]: St Mary's, Parish registers, page 12\n"));
+
+        // Only as many generations as asked for
+        let report = descendant_report(&gedcom, joe, 1, ReportFormat::Markdown);
+        assert!(!report.contains("Generation 2") && report.contains("- i. Sam Bloggs"));
+    }
+
+    #[test]
+    fn ahnentafel() {
+        let gedcom = parse_gedcom_from_str(DATA).unwrap();
+        let tom = gedcom.find_individual("@I5@").unwrap();
+        let report = ahnentafel_report(&gedcom, tom, 4, ReportFormat::Markdown);
+        let numbered: Vec<&str> = report
+            .lines()
+            .filter(|line| line.starts_with("**") || line.starts_with("##"))
+            .collect();
+        assert!(
+            numbered
+                == [
+                    "## Generation 1",
+                    "**1. Tom Bloggs**.",
+                    "## Parents",
+                    "**2. Sam Bloggs** was born in 1866.",
+                    "## Grandparents",
+                    "**4. Joe Bloggs** was born on 12 Mar 1840 in York[^1]; he married Ann Smith \
+                     between 1864 and 1865 in Leeds; he died about 1900[^1].",
+                    "**5. Ann Smith** married Joe Bloggs between 1864 and 1865 in Leeds.",
+                    "## Great-grandparents",
+                    "**10. Bill Smith**.",
+                ]
+        );
+    }
+
+    #[test]
+    fn html() {
+        let gedcom = parse_gedcom_from_str(DATA).unwrap();
+        let joe = gedcom.find_individual("@I1@").unwrap();
+        let report = descendant_report(&gedcom, joe, 2, ReportFormat::Html);
+        assert!(report.starts_with("<!DOCTYPE html>"));
+        assert!(report.contains("<h1>Descendants of Joe Bloggs</h1>"));
+        assert!(report.contains(
+            "<p><strong>1. Joe Bloggs</strong> was born on 12 Mar 1840 in York<sup><a href=\"#note-1\">1</a></sup>;"
+        ));
+        assert!(report.contains("<li id=\"note-1\">St Mary&apos;s, Parish registers, page 12</li>"));
+        assert!(ReportFormat::from_str("HTML") == Ok(ReportFormat::Html));
+    }
+
+    #[test]
+    fn dates() {
+        assert!(when("12 MAR 1840") == "on 12 Mar 1840");
+        assert!(when("MAR 1840") == "in Mar 1840");
+        assert!(when("ABT 1840") == "about 1840");
+        assert!(when("BEF 44 B.C.") == "before 44 B.C.");
+        assert!(when("FROM 1840 TO 1850") == "from 1840 to 1850");
+        assert!(when("(at sea)") == "(at sea)");
+        assert!(when("sometime") == "sometime");
+        assert!(ancestor_heading(4) == "2nd great-grandparents");
+    }
+}
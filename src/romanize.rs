@@ -0,0 +1,84 @@
+use crate::names;
+use crate::types::{Name, PersonalName};
+
+/// Transliterate text in any script into plain Latin letters, i.e.
+/// "Иван" into "Ivan". Text that's already ASCII is returned unchanged.
+pub fn romanize(text: &str) -> String {
+    if text.is_ascii() {
+        return text.to_string();
+    }
+    // deunicode separates each CJK character with a space, so tidy up
+    deunicode::deunicode(text)
+        .split(' ')
+        .filter(|s| !s.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+/// A transliteration of a name, for names that aren't already in Latin
+/// script. The surname keeps its slashes, so it can still be picked out.
+pub fn romanize_name(name: &Name) -> Option<Name> {
+    let value = name.value.as_deref()?;
+    if value.is_ascii() {
+        return None;
+    }
+    Some(Name {
+        value: Some(romanize(value)),
+        given: name.given.as_deref().map(romanize),
+        surname: name.surname.as_deref().map(romanize),
+        ..Default::default()
+    })
+}
+
+/// A lowercase, romanized form of a name, for indexing and comparing names
+/// across scripts
+pub fn search_key(name: &Name) -> Option<String> {
+    let value = name.value.as_deref()?;
+    let key = romanize(&names::strip_slashes(value)).to_lowercase();
+    Some(key)
+}
+
+impl PersonalName {
+    /// How to spell this name in Latin script, for reports: the ROMN
+    /// variation if there is one, otherwise a transliteration. None if the
+    /// name is already in Latin script.
+    pub fn romanized_spelling(&self) -> Option<String> {
+        let value = self.name.value.as_deref()?;
+        if value.is_ascii() {
+            return None;
+        }
+        match &self.romanized.value {
+            Some(romanized) => Some(romanized.clone()),
+            None => Some(romanize(value)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn romanize_names() {
+        assert!(romanize("Иван /Петров/") == "Ivan /Petrov/");
+        assert!(romanize("Νίκος") == "Nikos");
+        assert!(romanize("Joe /Bloggs/") == "Joe /Bloggs/");
+
+        let name = Name {
+            value: Some("Иван /Петров/".to_string()),
+            surname: Some("Петров".to_string()),
+            ..Default::default()
+        };
+        let romanized = romanize_name(&name).unwrap();
+        assert!(romanized.surname.as_deref() == Some("Petrov"));
+        assert!(search_key(&name).as_deref() == Some("ivan petrov"));
+
+        let mut personal = PersonalName {
+            name,
+            ..Default::default()
+        };
+        assert!(personal.romanized_spelling().as_deref() == Some("Ivan /Petrov/"));
+        personal.romanized.value = Some("Iwan /Petrow/".to_string());
+        assert!(personal.romanized_spelling().as_deref() == Some("Iwan /Petrow/"));
+    }
+}
@@ -0,0 +1,721 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::names;
+use crate::types::{
+    Address, EventDetail, Family, FamilyEventDetail, Gedcom, Individual, IndividualEventDetail,
+    Name, Note, Object, Place, SourceCitation, Submitter,
+};
+
+/// A small pseudo-random number generator (SplitMix64), so that the same
+/// seed always gives the same sample, and a bug report can be reproduced
+pub(crate) struct Random(pub(crate) u64);
+
+impl Random {
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A number from 0 up to, but not including, `n`
+    fn below(&mut self, n: u64) -> u64 {
+        if n == 0 {
+            return 0;
+        }
+        self.next() % n
+    }
+}
+
+/// `n` individuals chosen at random, with their immediate families: each
+/// family they're a spouse or child in, and everyone else in those
+/// families. Links to families that weren't chosen are dropped, and only
+/// the sources and multimedia records still cited are kept, so the sample
+/// is a consistent file of its own. The same seed gives the same sample.
+pub fn random_sample(gedcom: &Gedcom, n: usize, seed: u64) -> Gedcom {
+    let mut random = Random(seed);
+
+    // A partial shuffle, to choose without repeats
+    let mut order: Vec<usize> = (0..gedcom.individuals.len()).collect();
+    let n = n.min(order.len());
+    for i in 0..n {
+        let j = i + random.below((order.len() - i) as u64) as usize;
+        order.swap(i, j);
+    }
+
+    let mut families: HashSet<&str> = HashSet::new();
+    for &i in &order[..n] {
+        let individual = &gedcom.individuals[i];
+        for family in gedcom
+            .child_families(individual)
+            .into_iter()
+            .chain(gedcom.spouse_families(individual))
+        {
+            families.insert(&family.xref);
+        }
+    }
+    let mut individuals: HashSet<&str> = order[..n]
+        .iter()
+        .filter_map(|&i| gedcom.individuals[i].xref.as_deref())
+        .collect();
+    for family in gedcom
+        .families
+        .iter()
+        .filter(|f| families.contains(f.xref.as_str()))
+    {
+        individuals.extend(family.husband_xref());
+        individuals.extend(family.wife_xref());
+        individuals.extend(family.children.iter().map(|c| c.as_str()));
+        individuals.extend(
+            gedcom
+                .individuals
+                .iter()
+                .filter(|i| i.famc.iter().any(|c| c.xref == family.xref))
+                .filter_map(|i| i.xref.as_deref()),
+        );
+    }
+
+    let mut sample = Gedcom {
+        header: gedcom.header.clone(),
+        ..Default::default()
+    };
+    if let Some(home) = &sample.header.home_person {
+        if !individuals.contains(home.as_str()) {
+            sample.header.home_person = None;
+        }
+    }
+    for individual in &gedcom.individuals {
+        if individual
+            .xref
+            .as_deref()
+            .is_some_and(|x| individuals.contains(x))
+        {
+            let mut individual = individual.clone();
+            individual
+                .famc
+                .retain(|f| families.contains(f.xref.as_str()));
+            individual
+                .fams
+                .retain(|f| families.contains(f.xref.as_str()));
+            individual
+                .associations
+                .retain(|a| individuals.contains(a.xref.as_str()));
+            individual
+                .aliases
+                .retain(|a| individuals.contains(a.as_str()));
+            sample.individuals.push(individual);
+        }
+    }
+    sample.families = gedcom
+        .families
+        .iter()
+        .filter(|f| families.contains(f.xref.as_str()))
+        .cloned()
+        .collect();
+
+    // Keep the records that are still pointed to
+    let mut cited: HashSet<String> = HashSet::new();
+    let mut cite = |sources: &[SourceCitation], media: &[Object]| {
+        cited.extend(sources.iter().filter_map(|s| s.xref.clone()));
+        cited.extend(
+            sources
+                .iter()
+                .flat_map(|s| &s.media)
+                .chain(media)
+                .filter_map(|m| m.xref.clone()),
+        );
+    };
+    for individual in &sample.individuals {
+        cite(&individual.sources, &individual.media);
+        for association in &individual.associations {
+            cite(&association.sources, &[]);
+        }
+        for event in individual.all_events() {
+            if let Some(detail) = event.detail {
+                cite(&detail.sources, &detail.media);
+            }
+        }
+    }
+    for family in &sample.families {
+        cite(&family.sources, &family.media);
+        for (_, detail) in family.tagged_events() {
+            if let Some(detail) = detail {
+                cite(&detail.sources, &detail.media);
+            }
+        }
+    }
+    sample.sources = gedcom
+        .sources
+        .iter()
+        .filter(|s| cited.contains(&s.xref))
+        .cloned()
+        .collect();
+    for source in &sample.sources {
+        cited.extend(source.media.iter().filter_map(|m| m.xref.clone()));
+        cited.extend(source.repositories.iter().filter_map(|r| r.xref.clone()));
+    }
+    sample.repositories = gedcom
+        .repositories
+        .iter()
+        .filter(|r| cited.contains(&r.xref))
+        .cloned()
+        .collect();
+    sample.multimedia = gedcom
+        .multimedia
+        .iter()
+        .filter(|m| cited.contains(&m.xref))
+        .cloned()
+        .collect();
+
+    sample
+}
+
+/// Replace everything in the Gedcom that could identify a real person,
+/// keeping its structure, so it can be shared, i.e. in a bug report.
+///
+/// Given names become "Given1", "Given2" and so on, and each surname and
+/// place name is replaced by one made up for it, so names that matched
+/// still match. Dates are all moved by the same number of years, so ages
+/// and the order of events are kept, and days of the month are replaced.
+/// Other text, like notes and addresses, has its letters and digits
+/// replaced, keeping its length and line breaks. The original text of
+/// each record and any warnings are dropped. The same seed gives the same
+/// result.
+pub fn anonymize(gedcom: &mut Gedcom, seed: u64) {
+    let mut random = Random(seed);
+    // Between 5 and 15 years, either way
+    let shift = (5 + random.below(11) as i32) * if random.below(2) == 0 { -1 } else { 1 };
+    let mut anonymizer = Anonymizer {
+        random,
+        shift,
+        surnames: HashMap::new(),
+        places: HashMap::new(),
+    };
+
+    let header = &mut gedcom.header;
+    header.filename = None;
+    scrub_option(&mut header.copyright);
+    scrub_option(&mut header.note);
+    if let Some(submitter) = &mut header.submitter {
+        scrub_submitter(submitter);
+    }
+    gedcom.submitters.iter_mut().for_each(scrub_submitter);
+
+    for (i, individual) in gedcom.individuals.iter_mut().enumerate() {
+        for name in &mut individual.names {
+            anonymizer.name(&mut name.name, i + 1);
+            name.phonetic = Name::default();
+            name.romanized = Name::default();
+        }
+        for event in individual_events(individual) {
+            scrub_option(&mut event.value);
+            anonymizer.detail(&mut event.detail);
+        }
+        for death in &mut individual.death {
+            if let Some(detail) = &mut death.event {
+                anonymizer.detail(detail);
+            }
+        }
+        for residence in &mut individual.residences {
+            if let Some(event) = &mut residence.detail {
+                anonymizer.family_event(event);
+            }
+        }
+        individual.notes.iter_mut().for_each(scrub_note_text);
+        individual.sources.iter_mut().for_each(scrub_citation);
+        for association in &mut individual.associations {
+            association.notes.iter_mut().for_each(scrub_note_text);
+            association.sources.iter_mut().for_each(scrub_citation);
+        }
+        for family in individual.famc.iter_mut().chain(&mut individual.fams) {
+            family.notes.iter_mut().for_each(scrub_note_text);
+        }
+    }
+
+    for family in &mut gedcom.families {
+        for event in family_events(family) {
+            anonymizer.family_event(event);
+        }
+        family.notes.iter_mut().for_each(scrub_note_text);
+        family.sources.iter_mut().for_each(scrub_citation);
+    }
+
+    for source in &mut gedcom.sources {
+        scrub_option(&mut source.title);
+        scrub_option(&mut source.author);
+        scrub_option(&mut source.abbreviation);
+        scrub_option(&mut source.publication);
+        scrub_option(&mut source.text);
+        source.notes.iter_mut().for_each(scrub_note_text);
+    }
+
+    // A repository may be a relative holding the family papers
+    for repository in &mut gedcom.repositories {
+        scrub_option(&mut repository.name);
+        if let Some(address) = &mut repository.address {
+            scrub_address(address);
+        }
+        repository.notes.iter_mut().for_each(scrub_note_text);
+    }
+
+    for record in &mut gedcom.multimedia {
+        for (i, file) in record.files.iter_mut().enumerate() {
+            if let Some(path) = &mut file.path {
+                let extension = path.rsplit_once('.').map(|(_, e)| e).unwrap_or("bin");
+                *path = format!(
+                    "media/{}-{}.{}",
+                    record.xref.trim_matches('@'),
+                    i + 1,
+                    extension
+                );
+            }
+            scrub_option(&mut file.title);
+        }
+        scrub_option(&mut record.title);
+        record.notes.iter_mut().for_each(scrub_note_text);
+    }
+
+    for record in &mut gedcom.notes {
+        scrub_option(&mut record.text);
+        record.sources.iter_mut().for_each(scrub_citation);
+    }
+
+    gedcom.raw_records.clear();
+    gedcom.warnings.clear();
+    gedcom.skipped_text.clear();
+}
+
+struct Anonymizer {
+    random: Random,
+    /// How many years to move every date by
+    shift: i32,
+    surnames: HashMap<String, String>,
+    /// Each jurisdiction of a place name, and what it's replaced by
+    places: HashMap<String, String>,
+}
+
+impl Anonymizer {
+    fn name(&mut self, name: &mut Name, n: usize) {
+        let given = format!("Given{}", n);
+        let surname = names::surname(name).map(|s| s.to_string());
+        let surname = surname.map(|s| {
+            let count = self.surnames.len();
+            self.surnames
+                .entry(s)
+                .or_insert_with(|| format!("Surname{}", count + 1))
+                .clone()
+        });
+
+        name.value = Some(match &surname {
+            Some(surname) => format!("{} /{}/", given, surname),
+            None => given.clone(),
+        });
+        if name.given.is_some() {
+            name.given = Some(given);
+        }
+        if name.surname.is_some() {
+            name.surname = surname;
+        }
+        scrub_option(&mut name.nickname);
+        scrub_option(&mut name.prefix);
+        scrub_option(&mut name.suffix);
+        scrub_option(&mut name.surname_prefix);
+        scrub_note(&mut name.note);
+    }
+
+    fn family_event(&mut self, event: &mut FamilyEventDetail) {
+        if let Some(detail) = &mut event.detail {
+            self.detail(detail);
+        }
+    }
+
+    fn detail(&mut self, detail: &mut EventDetail) {
+        if let Some(date) = &mut detail.date {
+            *date = self.date(date);
+        }
+        if let Some(place) = &mut detail.place {
+            self.place(place);
+        }
+        if let Some(address) = &mut detail.address {
+            scrub_address(address);
+        }
+        scrub_option(&mut detail.agency);
+        scrub_option(&mut detail.cause);
+        scrub_option(&mut detail.note);
+        for values in [
+            &mut detail.phone,
+            &mut detail.email,
+            &mut detail.fax,
+            &mut detail.www,
+        ] {
+            values.iter_mut().for_each(|v| *v = scrub(v));
+        }
+        for source in &mut detail.sources {
+            scrub_citation(source);
+            if let Some(date) = source.data.as_mut().and_then(|d| d.date.as_mut()) {
+                *date = self.date(date);
+            }
+        }
+    }
+
+    /// Move a date's years by `shift`, replace its days, and drop the text
+    /// of any date phrase
+    fn date(&mut self, date: &str) -> String {
+        let date = match date.find('(') {
+            Some(i) => format!("{}(phrase)", &date[..i]),
+            None => date.to_string(),
+        };
+        let words: Vec<&str> = date.split(' ').collect();
+        let mut jittered = vec![];
+        for (i, word) in words.iter().enumerate() {
+            // A number before a month is a day, and any other a year
+            let before_month = words.get(i + 1).is_some_and(|next| {
+                next.chars().all(|c| c.is_ascii_alphabetic())
+                    && !matches!(*next, "AND" | "TO" | "BCE")
+            });
+            let (year, dual) = word.split_once('/').unwrap_or((word, ""));
+            match year.parse::<i32>() {
+                Ok(_) if before_month => {
+                    jittered.push((1 + self.random.below(28)).to_string());
+                }
+                Ok(year) => {
+                    let year = (year + self.shift).max(1);
+                    if dual.is_empty() {
+                        jittered.push(year.to_string());
+                    } else {
+                        jittered.push(format!("{}/{:02}", year, (year + 1) % 100));
+                    }
+                }
+                Err(_) => jittered.push(word.to_string()),
+            }
+        }
+        jittered.join(" ")
+    }
+
+    /// Replace each jurisdiction in a place name with a made-up one, the
+    /// same wherever it appears
+    fn place(&mut self, place: &mut Place) {
+        if let Some(name) = &place.name {
+            let parts: Vec<String> = name
+                .split(',')
+                .map(|part| {
+                    let part = part.trim();
+                    if part.is_empty() {
+                        return String::new();
+                    }
+                    let count = self.places.len();
+                    self.places
+                        .entry(part.to_string())
+                        .or_insert_with(|| format!("Place{}", count + 1))
+                        .clone()
+                })
+                .collect();
+            place.name = Some(parts.join(", "));
+        }
+        place.phonetic = None;
+        place.roman = None;
+        place.map = None;
+        scrub_note(&mut place.note);
+    }
+}
+
+/// Every event of an individual with an IndividualEventDetail
+pub(crate) fn individual_events(individual: &mut Individual) -> Vec<&mut IndividualEventDetail> {
+    let mut events: Vec<&mut IndividualEventDetail> = vec![];
+    events.extend(individual.adoption.iter_mut().map(|a| &mut a.event));
+    events.extend(individual.birth.iter_mut().map(|b| &mut b.event));
+    events.extend(
+        individual
+            .christening
+            .iter_mut()
+            .chain(&mut individual.christening_adult)
+            .map(|c| &mut c.event),
+    );
+    events.extend(individual.first_communion.iter_mut());
+    for list in [
+        &mut individual.baptism,
+        &mut individual.barmitzvah,
+        &mut individual.basmitzvah,
+        &mut individual.blessing,
+        &mut individual.burial,
+        &mut individual.census,
+        &mut individual.confirmation,
+        &mut individual.cremation,
+        &mut individual.emigration,
+        &mut individual.events,
+        &mut individual.graduation,
+        &mut individual.immigration,
+        &mut individual.naturalization,
+        &mut individual.occupations,
+        &mut individual.probate,
+        &mut individual.retirement,
+        &mut individual.will,
+        &mut individual.caste,
+        &mut individual.physical_description,
+        &mut individual.education,
+        &mut individual.id_numbers,
+        &mut individual.nationality,
+        &mut individual.children_count,
+        &mut individual.marriage_count,
+        &mut individual.property,
+        &mut individual.religion,
+        &mut individual.titles,
+    ] {
+        events.extend(list.iter_mut());
+    }
+    events
+}
+
+pub(crate) fn family_events(family: &mut Family) -> Vec<&mut FamilyEventDetail> {
+    let mut events: Vec<&mut FamilyEventDetail> = vec![];
+    for list in [
+        &mut family.annulment,
+        &mut family.census,
+        &mut family.divorce,
+        &mut family.divorce_filed,
+        &mut family.engagement,
+        &mut family.marriage,
+        &mut family.marriage_banns,
+        &mut family.marriage_contract,
+        &mut family.marriage_license,
+        &mut family.marriage_settlement,
+        &mut family.residences,
+        &mut family.events,
+    ] {
+        events.extend(list.iter_mut());
+    }
+    events
+}
+
+/// Replace letters with "x" and digits with "0", keeping everything else,
+/// and pointers to other records, like "@N1@", as they are
+fn scrub(text: &str) -> String {
+    if text.len() > 2 && text.starts_with('@') && text.ends_with('@') {
+        return text.to_string();
+    }
+    text.chars()
+        .map(|c| match c {
+            c if c.is_numeric() => '0',
+            c if c.is_alphabetic() => 'x',
+            c => c,
+        })
+        .collect()
+}
+
+fn scrub_option(text: &mut Option<String>) {
+    if let Some(text) = text {
+        *text = scrub(text);
+    }
+}
+
+fn scrub_note_text(note: &mut Note) {
+    scrub_option(&mut note.note);
+}
+
+fn scrub_note(note: &mut Option<Note>) {
+    if let Some(note) = note {
+        scrub_note_text(note);
+    }
+}
+
+fn scrub_address(address: &mut Address) {
+    for line in [
+        &mut address.addr1,
+        &mut address.addr2,
+        &mut address.addr3,
+        &mut address.city,
+        &mut address.state,
+        &mut address.postal_code,
+        &mut address.country,
+    ] {
+        scrub_option(line);
+    }
+    for values in [
+        &mut address.phone,
+        &mut address.email,
+        &mut address.fax,
+        &mut address.www,
+    ] {
+        values.iter_mut().for_each(|v| *v = scrub(v));
+    }
+}
+
+fn scrub_submitter(submitter: &mut Submitter) {
+    scrub_option(&mut submitter.name);
+    if let Some(address) = &mut submitter.address {
+        scrub_address(address);
+    }
+    scrub_note(&mut submitter.note);
+}
+
+fn scrub_citation(citation: &mut SourceCitation) {
+    scrub_note(&mut citation.note);
+    if let Some(data) = &mut citation.data {
+        scrub_note(&mut data.text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_gedcom_from_str;
+    use crate::writer::write_gedcom;
+
+    fn gedcom() -> Gedcom {
+        parse_gedcom_from_str(
+            "0 HEAD
+1 _HOME @I5@
+1 SUBM @U1@
+0 @U1@ SUBM
+1 NAME Jo Researcher
+1 ADDR 7 Pine Cone Street
+1 PHON 555-0199
+0 @U2@ SUBM
+1 NAME Ann Other
+1 NOTE Ann's family papers
+0 @N1@ NOTE Told to me by Aunt Edith
+1 SOUR @S1@
+2 DATA
+3 TEXT Edith was born in Whitby
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+2 GIVN Joe
+2 SURN Bloggs
+1 BIRT
+2 DATE ABT 12 MAR 1900
+2 PLAC York, Yorkshire, England
+2 SOUR @S1@
+1 FAMS @F1@
+0 @I2@ INDI
+1 NAME Jane /Doe/
+1 FAMS @F1@
+1 FAMC @F2@
+0 @I3@ INDI
+1 NAME Jim /Bloggs/
+1 BIRT
+2 DATE BET 1925 AND 1926
+2 PLAC Leeds, Yorkshire, England
+1 OCCU Farmer
+1 FAMC @F1@
+1 NOTE Lived at 12 Acacia Avenue
+0 @I4@ INDI
+1 NAME Ann /Doe/
+1 FAMS @F2@
+0 @I5@ INDI
+1 NAME Unrelated /Person/
+0 @F1@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 CHIL @I3@
+1 MARR
+2 DATE (during the war)
+2 NOTE Married at St Mary's, 1920
+0 @F2@ FAM
+1 WIFE @I4@
+1 CHIL @I2@
+0 @S1@ SOUR
+1 TITL York parish register
+0 @S2@ SOUR
+1 TITL Unused
+0 TRLR
+",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn sample() {
+        let gedcom = gedcom();
+        let mut found = None;
+        for seed in 0..100 {
+            let sample = random_sample(&gedcom, 1, seed);
+            let chosen: Vec<&str> = sample
+                .individuals
+                .iter()
+                .filter_map(|i| i.xref.as_deref())
+                .collect();
+            if chosen.contains(&"@I3@") {
+                found = Some(sample);
+                break;
+            }
+        }
+        // Jim, with his parents
+        let sample = found.unwrap();
+        let chosen: Vec<&str> = sample
+            .individuals
+            .iter()
+            .filter_map(|i| i.xref.as_deref())
+            .collect();
+        assert!(chosen == ["@I1@", "@I2@", "@I3@"]);
+        assert!(sample.families.len() == 1);
+        // Jane's link to her parents' family, which isn't in the sample
+        assert!(sample.individuals[1].famc.is_empty());
+        assert!(sample.sources.len() == 1 && sample.sources[0].xref == "@S1@");
+        assert!(sample.header.home_person.is_none());
+
+        // The same seed always gives the same sample
+        let first = write_gedcom(&random_sample(&gedcom, 2, 7));
+        assert!(write_gedcom(&random_sample(&gedcom, 2, 7)) == first);
+        assert!(random_sample(&gedcom, 100, 1).individuals.len() == 5);
+        assert!(random_sample(&gedcom, 0, 1).individuals.is_empty());
+    }
+
+    #[test]
+    fn anonymized() {
+        let mut gedcom = gedcom();
+        anonymize(&mut gedcom, 3);
+        let text = write_gedcom(&gedcom);
+        for secret in [
+            "Joe", "Bloggs", "Jane", "York", "Leeds", "Farmer", "Acacia", "war", "Mary", "1900",
+        ] {
+            assert!(!text.contains(secret), "{} wasn't anonymized", secret);
+        }
+        // Every submitter, not only the header's
+        for secret in ["Jo Researcher", "Pine Cone", "555", "Ann Other", "papers"] {
+            assert!(!text.contains(secret), "{} wasn't anonymized", secret);
+        }
+        assert!(gedcom.submitters.len() == 2);
+        for secret in ["Edith", "Whitby"] {
+            assert!(!text.contains(secret), "{} wasn't anonymized", secret);
+        }
+
+        let names: Vec<&str> = gedcom
+            .individuals
+            .iter()
+            .filter_map(|i| i.names[0].name.value.as_deref())
+            .collect();
+        assert!(names[0] == "Given1 /Surname1/" && names[2] == "Given3 /Surname1/");
+        assert!(names[1] == "Given2 /Surname2/" && names[3] == "Given4 /Surname2/");
+        assert!(gedcom.individuals[0].names[0].name.given.as_deref() == Some("Given1"));
+
+        let place = |i: usize| {
+            gedcom.individuals[i].birth[0]
+                .event
+                .detail
+                .place
+                .as_ref()
+                .unwrap()
+                .name
+                .clone()
+                .unwrap()
+        };
+        assert!(place(0) == "Place1, Place2, Place3");
+        assert!(place(2) == "Place4, Place2, Place3");
+
+        // Dates move together, so ages are kept
+        let birth = |i: usize| gedcom.individuals[i].birth_year().unwrap();
+        assert!(birth(2) - birth(0) == 25);
+        assert!(birth(0) != 1900);
+        let date = gedcom.individuals[2].birth[0]
+            .event
+            .detail
+            .date
+            .clone()
+            .unwrap();
+        assert!(date.starts_with("BET ") && date.contains(" AND "));
+        assert!(text.contains("2 DATE (phrase)"));
+        assert!(text.contains("xxxxx"));
+        assert!(scrub("@N1@") == "@N1@" && scrub("Room 101") == "xxxx 000");
+    }
+}
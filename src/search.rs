@@ -0,0 +1,530 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::names::{self, Particles};
+use crate::types::{GedcomDate, Individual, Name, SimpleDate};
+
+#[cfg(test)]
+use crate::types::PersonalName;
+
+/// How good a full-name match is, the best possible
+pub const EXACT_NAME_SCORE: f64 = 1.0;
+/// How good a match on the surname alone is
+pub const SURNAME_SCORE: f64 = 0.8;
+/// How good a match on the given name(s) alone is
+pub const GIVEN_NAME_SCORE: f64 = 0.6;
+/// How good a match is when every word of the query appears in the name
+pub const ALL_WORDS_SCORE: f64 = 0.5;
+/// How good a match is when the query appears somewhere within the name
+pub const SUBSTRING_SCORE: f64 = 0.3;
+
+/// How many years either side of a searched-for date we still consider a match
+pub const DEFAULT_YEAR_TOLERANCE: i32 = 5;
+
+/// The part of a record that matched a search
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MatchedField {
+    Name,
+    GivenName,
+    Surname,
+    BirthDate,
+    DeathDate,
+    Place,
+    Event,
+}
+
+impl fmt::Display for MatchedField {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            MatchedField::Name => "name",
+            MatchedField::GivenName => "given name",
+            MatchedField::Surname => "surname",
+            MatchedField::BirthDate => "birth date",
+            MatchedField::DeathDate => "death date",
+            MatchedField::Place => "place",
+            MatchedField::Event => "event",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A search hit, along with how well it matched, so callers can rank them.
+///
+/// Scores run from 0.0 (exclusive) to 1.0, where 1.0 is a perfect match on
+/// every criterion that was searched for.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchResult<T> {
+    pub item: T,
+    pub score: f64,
+    pub matched_fields: Vec<MatchedField>,
+}
+
+/// The criteria for `Gedcom::search_individuals`. Any criterion left as
+/// `None` is ignored; an individual must match every one that is set.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SearchQuery {
+    pub name: Option<String>,
+    pub birth_year: Option<i32>,
+    pub death_year: Option<i32>,
+    /// How many years away from `birth_year` or `death_year` still counts.
+    /// Closer years score higher.
+    pub year_tolerance: i32,
+    /// Somewhere an event took place. If `event` is set too, it must be
+    /// where that event took place.
+    pub place: Option<String>,
+    /// The tag of an event the individual must have, i.e. "CENS"
+    pub event: Option<String>,
+    /// Treat records linked by ALIA as one person, keeping only the best
+    /// match of each
+    pub merge_aliases: bool,
+}
+
+impl Default for SearchQuery {
+    fn default() -> SearchQuery {
+        SearchQuery {
+            name: None,
+            birth_year: None,
+            death_year: None,
+            year_tolerance: DEFAULT_YEAR_TOLERANCE,
+            place: None,
+            event: None,
+            merge_aliases: false,
+        }
+    }
+}
+
+impl SearchQuery {
+    pub fn new() -> SearchQuery {
+        SearchQuery::default()
+    }
+
+    pub fn name(mut self, name: &str) -> SearchQuery {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    pub fn birth_year(mut self, year: i32) -> SearchQuery {
+        self.birth_year = Some(year);
+        self
+    }
+
+    pub fn death_year(mut self, year: i32) -> SearchQuery {
+        self.death_year = Some(year);
+        self
+    }
+
+    pub fn year_tolerance(mut self, years: i32) -> SearchQuery {
+        self.year_tolerance = years;
+        self
+    }
+
+    pub fn place(mut self, place: &str) -> SearchQuery {
+        self.place = Some(place.to_string());
+        self
+    }
+
+    pub fn event(mut self, tag: &str) -> SearchQuery {
+        self.event = Some(tag.to_uppercase());
+        self
+    }
+
+    pub fn merge_aliases(mut self, merge: bool) -> SearchQuery {
+        self.merge_aliases = merge;
+        self
+    }
+
+    /// Score an individual against this query, or None if they don't match.
+    ///
+    /// The name carries twice the weight of each other criterion, and the
+    /// final score is the weighted average over the criteria that were set.
+    pub fn score<'a>(&self, individual: &'a Individual) -> Option<SearchResult<&'a Individual>> {
+        let mut total = 0.0;
+        let mut weight = 0.0;
+        let mut matched_fields = vec![];
+
+        if let Some(query) = &self.name {
+            let (score, field) = score_individual_name(individual, query)?;
+            total += score * 2.0;
+            weight += 2.0;
+            matched_fields.push(field);
+        }
+        if let Some(year) = self.birth_year {
+            total += score_year(individual.birth_year()?, year, self.year_tolerance)?;
+            weight += 1.0;
+            matched_fields.push(MatchedField::BirthDate);
+        }
+        if let Some(year) = self.death_year {
+            total += score_year(individual.death_year()?, year, self.year_tolerance)?;
+            weight += 1.0;
+            matched_fields.push(MatchedField::DeathDate);
+        }
+
+        let events: Vec<_> = individual
+            .tagged_events()
+            .into_iter()
+            .filter(|(tag, _)| {
+                self.event
+                    .as_deref()
+                    .is_none_or(|e| e.eq_ignore_ascii_case(tag))
+            })
+            .collect();
+        if self.event.is_some() {
+            if events.is_empty() {
+                return None;
+            }
+            total += 1.0;
+            weight += 1.0;
+            matched_fields.push(MatchedField::Event);
+        }
+        if let Some(query) = &self.place {
+            total += events
+                .iter()
+                .filter_map(|(_, detail)| (*detail)?.place.as_ref()?.name.as_deref())
+                .filter_map(|place| score_place(place, query))
+                .max_by(|a, b| a.total_cmp(b))?;
+            weight += 1.0;
+            matched_fields.push(MatchedField::Place);
+        }
+
+        if weight == 0.0 {
+            return None;
+        }
+
+        Some(SearchResult {
+            item: individual,
+            score: total / weight,
+            matched_fields,
+        })
+    }
+}
+
+/// Sort search results, best first. Ties keep their original order.
+pub fn rank<T>(results: &mut [SearchResult<T>]) {
+    results.sort_by(|a, b| b.score.total_cmp(&a.score));
+}
+
+/// Score how well `query` matches any of an individual's names, including
+/// their romanized (ROMN) and phonetic (FONE) variations and, with the
+/// `romanize` feature, a transliteration of names in non-Latin scripts.
+pub fn score_individual_name(individual: &Individual, query: &str) -> Option<(f64, MatchedField)> {
+    individual
+        .names
+        .iter()
+        .filter_map(|n| {
+            let forms = vec![&n.name, &n.romanized, &n.phonetic];
+
+            #[cfg(feature = "romanize")]
+            let transliterated = crate::romanize::romanize_name(&n.name);
+            #[cfg(feature = "romanize")]
+            let forms: Vec<&Name> = forms.into_iter().chain(transliterated.as_ref()).collect();
+
+            forms
+                .into_iter()
+                .filter(|name| name.value.is_some())
+                .filter_map(|name| score_name(name, query))
+                .max_by(|a, b| a.0.total_cmp(&b.0))
+        })
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+}
+
+/// Score how well `query` matches a name: an exact match on the whole name
+/// beats a match on the surname, which beats the given name, which beats
+/// the query's words all appearing, which beats a plain substring match.
+/// Comparisons ignore case, the slashes around the surname, and extra spaces.
+pub fn score_name(name: &Name, query: &str) -> Option<(f64, MatchedField)> {
+    let query = normalize(query);
+    if query.is_empty() {
+        return None;
+    }
+
+    let full = normalize(name.value.as_deref().unwrap_or_default());
+    let surname = names::surname(name).map(normalize);
+    let given = name.given.as_deref().map(normalize);
+
+    if full == query {
+        return Some((EXACT_NAME_SCORE, MatchedField::Name));
+    }
+    if surname.as_deref() == Some(query.as_str()) {
+        return Some((SURNAME_SCORE, MatchedField::Surname));
+    }
+    if given.as_deref() == Some(query.as_str()) {
+        return Some((GIVEN_NAME_SCORE, MatchedField::GivenName));
+    }
+
+    let words: Vec<&str> = full.split(' ').collect();
+    if query.split(' ').all(|q| words.contains(&q)) {
+        return Some((ALL_WORDS_SCORE, MatchedField::Name));
+    }
+    if full.contains(&query) {
+        return Some((SUBSTRING_SCORE, MatchedField::Name));
+    }
+    None
+}
+
+/// Score how well `query` matches a place: the whole place name beats its
+/// first, most specific, part (i.e. the city), which beats a substring.
+pub fn score_place(place: &str, query: &str) -> Option<f64> {
+    let query = normalize(query);
+    let place = normalize(place);
+    if query.is_empty() {
+        return None;
+    }
+    if place == query {
+        return Some(EXACT_NAME_SCORE);
+    }
+    if place.split(',').next().map(|p| p.trim()) == Some(query.as_str()) {
+        return Some(SURNAME_SCORE);
+    }
+    if place.contains(&query) {
+        return Some(SUBSTRING_SCORE);
+    }
+    None
+}
+
+/// Score how close `actual` is to `wanted`: 1.0 for the same year, falling
+/// off linearly to just above zero at `tolerance` years away.
+pub fn score_year(actual: i32, wanted: i32, tolerance: i32) -> Option<f64> {
+    let distance = (actual - wanted).abs();
+    if distance > tolerance {
+        return None;
+    }
+    Some(1.0 - distance as f64 / (tolerance + 1) as f64)
+}
+
+/// What `Gedcom::individuals_sorted` sorts by
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortKey {
+    /// Surname, then given names, from the first name. See
+    /// `names::sort_key`.
+    #[default]
+    Name,
+    /// The first dated birth
+    BirthDate,
+    /// The first dated death
+    DeathDate,
+    /// The xref, in numeric order, so @I2@ comes before @I10@
+    Xref,
+}
+
+impl FromStr for SortKey {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<SortKey, Self::Err> {
+        match input.to_lowercase().as_str() {
+            "name" => Ok(SortKey::Name),
+            "birth" | "birth-date" => Ok(SortKey::BirthDate),
+            "death" | "death-date" => Ok(SortKey::DeathDate),
+            "xref" => Ok(SortKey::Xref),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+impl FromStr for SortOrder {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<SortOrder, Self::Err> {
+        match input.to_lowercase().as_str() {
+            "asc" | "ascending" => Ok(SortOrder::Ascending),
+            "desc" | "descending" => Ok(SortOrder::Descending),
+            _ => Err(()),
+        }
+    }
+}
+
+/// A value to sort an individual by
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Sortable {
+    Text(String),
+    Date(SimpleDate),
+    Xref(String, u64, String),
+}
+
+impl SortKey {
+    fn of(&self, individual: &Individual) -> Option<Sortable> {
+        let date = |date: Option<&str>| {
+            let date = GedcomDate::from_str(date?).ok()?;
+            Some(Sortable::Date(date.start.or(date.end)?))
+        };
+        match self {
+            SortKey::Name => {
+                let name = &individual.names.first()?.name;
+                name.value.as_ref()?;
+                Some(Sortable::Text(names::sort_key(name, Particles::default())))
+            }
+            SortKey::BirthDate => date(individual.birth_date()),
+            SortKey::DeathDate => date(individual.death_date()),
+            SortKey::Xref => {
+                // The letters before the number, the number, and anything after
+                let xref = individual.xref.as_deref()?.trim_matches('@');
+                let digits = xref
+                    .find(|c: char| c.is_ascii_digit())
+                    .unwrap_or(xref.len());
+                let (prefix, rest) = xref.split_at(digits);
+                let end = rest
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(rest.len());
+                let number = rest[..end].parse().unwrap_or_default();
+                Some(Sortable::Xref(
+                    prefix.to_string(),
+                    number,
+                    rest[end..].to_string(),
+                ))
+            }
+        }
+    }
+}
+
+/// Sort individuals by `by`. Those without a name or date to sort by come
+/// last, whichever the order, and ties keep the order they were given in.
+pub fn sort_individuals<'a>(
+    individuals: impl IntoIterator<Item = &'a Individual>,
+    by: SortKey,
+    order: SortOrder,
+) -> Vec<&'a Individual> {
+    let mut keyed: Vec<(Option<Sortable>, &Individual)> = individuals
+        .into_iter()
+        .map(|individual| (by.of(individual), individual))
+        .collect();
+    keyed.sort_by(|(a, _), (b, _)| match (a, b) {
+        (Some(a), Some(b)) if order == SortOrder::Descending => b.cmp(a),
+        (Some(a), Some(b)) => a.cmp(b),
+        (a, b) => a.is_none().cmp(&b.is_none()),
+    });
+    keyed
+        .into_iter()
+        .map(|(_, individual)| individual)
+        .collect()
+}
+
+fn normalize(s: &str) -> String {
+    names::strip_slashes(s).to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(value: &str) -> Name {
+        Name {
+            value: Some(value.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn name_scores() {
+        let joe = name("Joe /Bloggs/");
+
+        assert!(score_name(&joe, "joe bloggs") == Some((EXACT_NAME_SCORE, MatchedField::Name)));
+        assert!(score_name(&joe, "BLOGGS") == Some((SURNAME_SCORE, MatchedField::Surname)));
+        assert!(score_name(&joe, "Blo") == Some((SUBSTRING_SCORE, MatchedField::Name)));
+        assert!(score_name(&joe, "Smith").is_none());
+        assert!(score_name(&joe, "").is_none());
+
+        let mut joe = name("Joe Fred /Bloggs/");
+        joe.given = Some("Joe Fred".to_string());
+        assert!(score_name(&joe, "joe fred") == Some((GIVEN_NAME_SCORE, MatchedField::GivenName)));
+        assert!(score_name(&joe, "bloggs joe") == Some((ALL_WORDS_SCORE, MatchedField::Name)));
+    }
+
+    #[test]
+    fn name_variations() {
+        let individual = Individual {
+            names: vec![PersonalName {
+                name: name("Иван /Петров/"),
+                phonetic: name("Eevan /Petrof/"),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert!(score_individual_name(&individual, "Иван Петров").unwrap().0 == EXACT_NAME_SCORE);
+        assert!(score_individual_name(&individual, "petrof").unwrap().0 == SURNAME_SCORE);
+
+        #[cfg(feature = "romanize")]
+        assert!(score_individual_name(&individual, "ivan petrov").unwrap().0 == EXACT_NAME_SCORE);
+        #[cfg(not(feature = "romanize"))]
+        assert!(score_individual_name(&individual, "ivan petrov").is_none());
+    }
+
+    #[test]
+    fn place_scores() {
+        assert!(
+            score_place("Salt Lake City, UT, USA", "salt lake city, ut, usa")
+                == Some(EXACT_NAME_SCORE)
+        );
+        assert!(score_place("Salt Lake City, UT, USA", "Salt Lake City") == Some(SURNAME_SCORE));
+        assert!(score_place("Salt Lake City, UT, USA", "UT") == Some(SUBSTRING_SCORE));
+        assert!(score_place("Salt Lake City, UT, USA", "Ohio").is_none());
+    }
+
+    #[test]
+    fn year_scores() {
+        assert!(score_year(1900, 1900, 5) == Some(1.0));
+        assert!(score_year(1902, 1900, 5) > score_year(1904, 1900, 5));
+        assert!(score_year(1905, 1900, 5).unwrap() > 0.0);
+        assert!(score_year(1906, 1900, 5).is_none());
+    }
+
+    #[test]
+    fn sort() {
+        let gedcom = crate::parse::parse_gedcom_from_str(
+            "0 HEAD
+0 @I10@ INDI
+1 NAME Ann /Vogel/
+1 BIRT
+2 DATE ABT 1890
+0 @I2@ INDI
+1 NAME Wernher /von Braun/
+1 BIRT
+2 DATE 23 MAR 1912
+1 DEAT
+2 DATE 16 JUN 1977
+0 @I3@ INDI
+1 BIRT
+2 DATE BET 1850 AND 1860
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 DEAT
+2 DATE (young)
+0 TRLR
+",
+        )
+        .unwrap();
+        let sorted = |by, order| -> Vec<&str> {
+            gedcom
+                .individuals_sorted(by, order)
+                .map(|i| i.xref.as_deref().unwrap())
+                .collect()
+        };
+
+        assert!(sorted(SortKey::Name, SortOrder::Ascending) == ["@I1@", "@I2@", "@I10@", "@I3@"]);
+        assert!(sorted(SortKey::Name, SortOrder::Descending) == ["@I10@", "@I2@", "@I1@", "@I3@"]);
+        assert!(
+            sorted(SortKey::BirthDate, SortOrder::Ascending) == ["@I3@", "@I10@", "@I2@", "@I1@"]
+        );
+        assert!(
+            sorted(SortKey::DeathDate, SortOrder::Descending) == ["@I2@", "@I10@", "@I3@", "@I1@"]
+        );
+        assert!(sorted(SortKey::Xref, SortOrder::Ascending) == ["@I1@", "@I2@", "@I3@", "@I10@"]);
+
+        let page: Vec<_> = gedcom
+            .individuals_sorted(SortKey::Xref, SortOrder::Descending)
+            .skip(1)
+            .take(2)
+            .map(|i| i.xref.clone().unwrap())
+            .collect();
+        assert!(page == ["@I3@", "@I2@"]);
+
+        assert!(SortKey::from_str("birth") == Ok(SortKey::BirthDate));
+        assert!(SortOrder::from_str("DESC") == Ok(SortOrder::Descending));
+        assert!(SortKey::from_str("age").is_err());
+    }
+}
@@ -0,0 +1,483 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+use tinytemplate::TinyTemplate;
+
+use crate::error::GedcomError;
+use crate::media::media_inventory;
+use crate::names::{self, Particles};
+use crate::report::citation_text;
+use crate::timeline::timeline;
+use crate::types::{Gedcom, Individual, SourceCitation};
+
+// A static website of the tree, to publish or browse offline: an index of
+// surnames at index.html, and a page for each individual in individuals/,
+// named by their slug, so links to it survive re-exporting the tree.
+//
+// Pages are rendered with TinyTemplate, from the templates in
+// `SiteTemplates`. The built-in ones are deliberately plain, to be replaced
+// by anyone who wants their site to look like their own. Values are HTML
+// escaped as they're filled in.
+
+/// The templates the pages are rendered with. See the built-in ones for the
+/// values each is given.
+#[derive(Clone, Debug)]
+pub struct SiteTemplates {
+    /// The surname index
+    pub index: String,
+    /// A page about an individual
+    pub individual: String,
+}
+
+impl Default for SiteTemplates {
+    fn default() -> SiteTemplates {
+        SiteTemplates {
+            index: INDEX_TEMPLATE.to_string(),
+            individual: INDIVIDUAL_TEMPLATE.to_string(),
+        }
+    }
+}
+
+const INDEX_TEMPLATE: &str = r##"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+</head>
+<body>
+<h1>{title}</h1>
+<p>{{ for surname in surnames }}<a href="#{surname.anchor}">{surname.surname}</a> {{ endfor }}</p>
+{{ for surname in surnames }}
+<h2 id="{surname.anchor}">{surname.surname}</h2>
+<ul>
+{{ for person in surname.individuals }}<li><a href="{person.href}">{person.name}</a> {person.lifespan}</li>
+{{ endfor }}</ul>
+{{ endfor }}
+</body>
+</html>
+"##;
+
+const INDIVIDUAL_TEMPLATE: &str = r##"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{name}</title>
+</head>
+<body>
+<p><a href="{index}">Index</a></p>
+<h1>{name}</h1>
+<p>{lifespan}</p>
+{{ if facts }}<h2>Facts</h2>
+<table>
+{{ for fact in facts }}<tr><td>{fact.event}</td><td>{fact.date}</td><td>{fact.place}</td><td>{{ for n in fact.sources }}<sup><a href="#source-{n}">{n}</a></sup>{{ endfor }}</td></tr>
+{{ endfor }}</table>
+{{ endif }}{{ if parents }}<h2>Parents</h2>
+<ul>
+{{ for person in parents }}<li><a href="{person.href}">{person.name}</a> {person.lifespan}</li>
+{{ endfor }}</ul>
+{{ endif }}{{ for family in families }}<h2>Family{{ if family.spouse }} with <a href="{family.spouse.href}">{family.spouse.name}</a>{{ endif }}</h2>
+{{ if family.children }}<ul>
+{{ for person in family.children }}<li><a href="{person.href}">{person.name}</a> {person.lifespan}</li>
+{{ endfor }}</ul>
+{{ endif }}{{ endfor }}{{ if personal_notes }}<h2>Notes</h2>
+{{ for note in personal_notes }}<p>{note}</p>
+{{ endfor }}{{ endif }}{{ if media }}<h2>Media</h2>
+<ul>
+{{ for item in media }}<li><a href="{item.path}">{{ if item.title }}{item.title}{{ else }}{item.path}{{ endif }}</a></li>
+{{ endfor }}</ul>
+{{ endif }}{{ if sources }}<h2>Sources</h2>
+<ol>
+{{ for source in sources }}<li id="source-{source.number}">{source.text}</li>
+{{ endfor }}</ol>
+{{ endif }}</body>
+</html>
+"##;
+
+/// A page of the site: where it goes, relative to the site's directory,
+/// and what's in it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Page {
+    pub path: String,
+    pub html: String,
+}
+
+#[derive(Serialize)]
+struct Index {
+    title: String,
+    surnames: Vec<Surname>,
+}
+
+#[derive(Serialize)]
+struct Surname {
+    surname: String,
+    anchor: String,
+    individuals: Vec<Link>,
+}
+
+#[derive(Serialize)]
+struct IndividualPage {
+    name: String,
+    lifespan: String,
+    index: String,
+    facts: Vec<Fact>,
+    parents: Vec<Link>,
+    families: Vec<FamilyLinks>,
+    /// Not `notes`, which TinyTemplate would read as `{{ if not es }}`
+    personal_notes: Vec<String>,
+    media: Vec<Media>,
+    sources: Vec<Source>,
+}
+
+#[derive(Serialize)]
+struct Link {
+    name: String,
+    href: String,
+    lifespan: String,
+}
+
+#[derive(Serialize)]
+struct Fact {
+    event: String,
+    date: String,
+    place: String,
+    /// The numbers of the sources cited
+    sources: Vec<usize>,
+}
+
+#[derive(Serialize)]
+struct FamilyLinks {
+    spouse: Option<Link>,
+    children: Vec<Link>,
+}
+
+#[derive(Serialize)]
+struct Media {
+    path: String,
+    title: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Source {
+    number: usize,
+    text: String,
+}
+
+/// Render every page of the site, the index first
+pub fn render_site(gedcom: &Gedcom, templates: &SiteTemplates) -> Result<Vec<Page>, GedcomError> {
+    let mut engine = TinyTemplate::new();
+    engine
+        .add_template("index", &templates.index)
+        .map_err(template_error)?;
+    engine
+        .add_template("individual", &templates.individual)
+        .map_err(template_error)?;
+
+    let mut pages = vec![Page {
+        path: "index.html".to_string(),
+        html: engine
+            .render("index", &index(gedcom))
+            .map_err(template_error)?,
+    }];
+
+    // Which files each record links to
+    let mut media: HashMap<&str, Vec<Media>> = HashMap::new();
+    for item in media_inventory(gedcom, Path::new("")) {
+        for used in &item.used_by {
+            let files = media.entry(used.xref).or_default();
+            if !files.iter().any(|f| f.path == item.path) {
+                files.push(Media {
+                    path: item.path.to_string(),
+                    title: item.title.map(|t| t.to_string()),
+                });
+            }
+        }
+    }
+
+    for individual in &gedcom.individuals {
+        let xref = individual.xref.as_deref().unwrap_or_default();
+        let page = individual_page(gedcom, individual, media.remove(xref).unwrap_or_default());
+        pages.push(Page {
+            path: format!("individuals/{}.html", individual.slug()),
+            html: engine.render("individual", &page).map_err(template_error)?,
+        });
+    }
+    Ok(pages)
+}
+
+/// Write the site into `directory`, which is created if need be, with the
+/// built-in templates, returning the paths written
+pub fn write_site(gedcom: &Gedcom, directory: &Path) -> Result<Vec<PathBuf>, GedcomError> {
+    write_site_with(gedcom, directory, &SiteTemplates::default())
+}
+
+/// `write_site`, with templates of your own
+pub fn write_site_with(
+    gedcom: &Gedcom,
+    directory: &Path,
+    templates: &SiteTemplates,
+) -> Result<Vec<PathBuf>, GedcomError> {
+    let pages = render_site(gedcom, templates)?;
+    fs::create_dir_all(directory.join("individuals"))?;
+    let mut written = vec![];
+    for page in pages {
+        let path = directory.join(&page.path);
+        fs::write(&path, page.html)?;
+        written.push(path);
+    }
+    Ok(written)
+}
+
+/// Everyone, grouped by surname, in alphabetical order
+fn index(gedcom: &Gedcom) -> Index {
+    // By the surname in lower case: the surname as first written, and
+    // everyone with it, with the key to sort them by
+    type Group<'a> = (String, Vec<(String, &'a Individual)>);
+    let mut surnames: BTreeMap<String, Group> = BTreeMap::new();
+    for individual in &gedcom.individuals {
+        let name = individual.names.first().map(|n| &n.name);
+        let surname = name
+            .and_then(names::surname)
+            .unwrap_or("(no surname)")
+            .to_string();
+        let key = name
+            .map(|n| names::sort_key(n, Particles::Ignore))
+            .unwrap_or_default();
+        surnames
+            .entry(surname.to_lowercase())
+            .or_insert_with(|| (surname, vec![]))
+            .1
+            .push((key, individual));
+    }
+
+    let surnames = surnames
+        .into_values()
+        .enumerate()
+        .map(|(i, (surname, mut individuals))| {
+            individuals.sort_by(|(a, _), (b, _)| a.cmp(b));
+            Surname {
+                surname,
+                anchor: format!("surname-{}", i + 1),
+                individuals: individuals
+                    .into_iter()
+                    .map(|(_, individual)| link(individual, "individuals/"))
+                    .collect(),
+            }
+        })
+        .collect();
+
+    Index {
+        title: "Family tree".to_string(),
+        surnames,
+    }
+}
+
+fn individual_page(gedcom: &Gedcom, individual: &Individual, media: Vec<Media>) -> IndividualPage {
+    // Each source is numbered once on the page, however often it's cited
+    let mut sources: Vec<Source> = vec![];
+    let mut cite = |citations: &[SourceCitation]| -> Vec<usize> {
+        citations
+            .iter()
+            .map(|citation| {
+                let text = citation_text(gedcom, citation);
+                match sources.iter().find(|s| s.text == text) {
+                    Some(source) => source.number,
+                    None => {
+                        sources.push(Source {
+                            number: sources.len() + 1,
+                            text,
+                        });
+                        sources.len()
+                    }
+                }
+            })
+            .collect()
+    };
+
+    let facts = timeline(gedcom, individual)
+        .iter()
+        .map(|entry| Fact {
+            event: match entry.spouse {
+                Some(spouse) => format!("{} ({})", entry.label(), name(spouse)),
+                None => entry.label(),
+            },
+            date: entry
+                .detail
+                .and_then(|d| d.date.clone())
+                .unwrap_or_default(),
+            place: entry.place.unwrap_or_default().to_string(),
+            sources: cite(
+                entry
+                    .detail
+                    .map(|d| d.sources.as_slice())
+                    .unwrap_or_default(),
+            ),
+        })
+        .collect();
+    cite(&individual.sources);
+
+    let families = gedcom
+        .spouse_families(individual)
+        .into_iter()
+        .map(|family| FamilyLinks {
+            spouse: family
+                .husband_xref()
+                .into_iter()
+                .chain(family.wife_xref())
+                .filter(|xref| Some(*xref) != individual.xref.as_deref())
+                .find_map(|xref| gedcom.find_individual(xref))
+                .map(|spouse| link(spouse, "")),
+            children: family
+                .children
+                .iter()
+                .filter_map(|xref| gedcom.find_individual(xref))
+                .map(|child| link(child, ""))
+                .collect(),
+        })
+        .collect();
+
+    IndividualPage {
+        name: name(individual),
+        lifespan: individual.lifespan_string(),
+        index: "../index.html".to_string(),
+        facts,
+        parents: gedcom
+            .parents(individual)
+            .into_iter()
+            .map(|parent| link(parent, ""))
+            .collect(),
+        families,
+        personal_notes: individual
+            .notes
+            .iter()
+            .filter_map(|n| n.note.clone())
+            .collect(),
+        media,
+        sources,
+    }
+}
+
+/// A link to an individual's page, from a page in `directory`
+fn link(individual: &Individual, directory: &str) -> Link {
+    Link {
+        name: name(individual),
+        href: format!("{}{}.html", directory, individual.slug()),
+        lifespan: individual.lifespan_string(),
+    }
+}
+
+fn name(individual: &Individual) -> String {
+    individual
+        .primary_name()
+        .unwrap_or_else(|| "(no name)".to_string())
+}
+
+fn template_error(err: tinytemplate::error::Error) -> GedcomError {
+    GedcomError::ParseError {
+        line: None,
+        offset: None,
+        tag: String::new(),
+        message: format!("Invalid site template: {}", err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_gedcom_from_str;
+
+    const DATA: &str = "0 HEAD
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 BIRT
+2 DATE 12 MAR 1900
+2 PLAC York
+2 SOUR @S1@
+3 PAGE 4
+1 NOTE Fond of <b>tags</b>
+1 OBJE
+2 FILE photos/joe.jpg
+3 TITL Joe at the seaside
+1 FAMS @F1@
+0 @I2@ INDI
+1 NAME Ann /Smith/
+1 FAMS @F1@
+0 @I3@ INDI
+1 NAME Sam /Bloggs/
+1 FAMC @F1@
+0 @F1@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 CHIL @I3@
+1 MARR
+2 DATE 1925
+2 SOUR @S1@
+3 PAGE 4
+0 @S1@ SOUR
+1 TITL Parish registers
+0 TRLR
+";
+
+    #[test]
+    fn render() {
+        let gedcom = parse_gedcom_from_str(DATA).unwrap();
+        let pages = render_site(&gedcom, &SiteTemplates::default()).unwrap();
+        assert!(pages.len() == 4);
+        let joe = gedcom.find_individual("@I1@").unwrap();
+        let sam = gedcom.find_individual("@I3@").unwrap();
+
+        let index = &pages[0];
+        assert!(index.path == "index.html");
+        let bloggs = index.html.find("<h2 id=\"surname-1\">Bloggs</h2>").unwrap();
+        let smith = index.html.find("<h2 id=\"surname-2\">Smith</h2>").unwrap();
+        assert!(bloggs < smith);
+        assert!(index.html.contains(&format!(
+            "<li><a href=\"individuals/{}.html\">Joe Bloggs</a> 1900–</li>",
+            joe.slug()
+        )));
+
+        let page = &pages[1];
+        assert!(page.path == format!("individuals/{}.html", joe.slug()));
+        assert!(page.html.contains("<h1>Joe Bloggs</h1>"));
+        assert!(page.html.contains(
+            "<tr><td>Birth</td><td>12 MAR 1900</td><td>York</td><td><sup><a href=\"#source-1\">1</a></sup></td></tr>"
+        ));
+        assert!(page
+            .html
+            .contains("<td>Marriage (Ann Smith)</td><td>1925</td>"));
+        assert!(page.html.contains(&format!(
+            "<li><a href=\"{}.html\">Sam Bloggs</a> </li>",
+            sam.slug()
+        )));
+        assert!(page.html.contains("<p>Fond of &lt;b&gt;tags&lt;/b&gt;</p>"));
+        assert!(page
+            .html
+            .contains("<li><a href=\"photos/joe.jpg\">Joe at the seaside</a></li>"));
+        // Cited twice, listed once
+        assert!(page
+            .html
+            .contains("<ol>\n<li id=\"source-1\">Parish registers, page 4</li>\n</ol>"));
+
+        let page = &pages[3];
+        assert!(page.html.contains("<h2>Parents</h2>") && !page.html.contains("<h2>Facts</h2>"));
+    }
+
+    #[test]
+    fn templates() {
+        let gedcom = parse_gedcom_from_str(DATA).unwrap();
+        let templates = SiteTemplates {
+            index: "{{ for surname in surnames }}{surname.surname};{{ endfor }}".to_string(),
+            individual: "{name}".to_string(),
+        };
+        let pages = render_site(&gedcom, &templates).unwrap();
+        assert!(pages[0].html == "Bloggs;Smith;");
+        assert!(pages[2].html == "Ann Smith");
+
+        let templates = SiteTemplates {
+            individual: "{{ if name }}".to_string(),
+            ..Default::default()
+        };
+        assert!(render_site(&gedcom, &templates).is_err());
+    }
+}
@@ -0,0 +1,282 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+
+use crate::names;
+use crate::types::{date_year, Gedcom, Gender, Individual};
+
+/// How many of the most common surnames and given names the report lists
+const REPORT_TOP: usize = 10;
+/// The longest bar in the report's histograms
+const REPORT_WIDTH: usize = 50;
+
+/// Descriptive statistics about the people in a Gedcom: their names,
+/// lifespans, and when they were born and married. See
+/// `Gedcom::statistics`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GedcomStats {
+    pub individuals: usize,
+    pub families: usize,
+    /// How many individuals have each surname, from their first name
+    pub surnames: BTreeMap<String, usize>,
+    /// The mean lifespan, in years, of those whose birth and death years are
+    /// both known
+    pub average_lifespan: Option<f64>,
+    /// The median lifespan, in years
+    pub median_lifespan: Option<f64>,
+    /// Men's first given names, most common first
+    pub male_given_names: Vec<(String, usize)>,
+    /// Women's first given names, most common first
+    pub female_given_names: Vec<(String, usize)>,
+    /// The first given names of everyone else, most common first
+    pub other_given_names: Vec<(String, usize)>,
+    /// How many individuals were born in each decade, i.e. 1900 for 1900-1909
+    pub births_per_decade: BTreeMap<i32, usize>,
+    /// How many families married in each decade
+    pub marriages_per_decade: BTreeMap<i32, usize>,
+    /// The mean number of children per family
+    pub average_children: Option<f64>,
+}
+
+impl GedcomStats {
+    pub fn new(gedcom: &Gedcom) -> GedcomStats {
+        let mut stats = GedcomStats {
+            individuals: gedcom.individuals.len(),
+            families: gedcom.families.len(),
+            ..Default::default()
+        };
+
+        let mut lifespans = vec![];
+        let mut given_names: [HashMap<String, usize>; 3] = Default::default();
+        for individual in &gedcom.individuals {
+            if let Some(surname) = surname(individual) {
+                *stats.surnames.entry(surname.to_string()).or_insert(0) += 1;
+            }
+            if let Some(given) = given_name(individual) {
+                let sex = match individual.gender {
+                    Gender::Male => 0,
+                    Gender::Female => 1,
+                    _ => 2,
+                };
+                *given_names[sex].entry(given.to_string()).or_insert(0) += 1;
+            }
+
+            let birth = individual.birth_year();
+            if let Some(birth) = birth {
+                *stats.births_per_decade.entry(decade(birth)).or_insert(0) += 1;
+            }
+            if let (Some(birth), Some(death)) = (birth, individual.death_year()) {
+                lifespans.push(death - birth);
+            }
+        }
+        let [male, female, other] = given_names;
+        stats.male_given_names = by_frequency(male);
+        stats.female_given_names = by_frequency(female);
+        stats.other_given_names = by_frequency(other);
+
+        if !lifespans.is_empty() {
+            lifespans.sort();
+            let total: i32 = lifespans.iter().sum();
+            stats.average_lifespan = Some(total as f64 / lifespans.len() as f64);
+
+            let middle = lifespans.len() / 2;
+            stats.median_lifespan = Some(if lifespans.len() % 2 == 0 {
+                (lifespans[middle - 1] + lifespans[middle]) as f64 / 2.0
+            } else {
+                lifespans[middle] as f64
+            });
+        }
+
+        let mut children = 0;
+        for family in &gedcom.families {
+            children += family.children.len();
+            let married = family
+                .marriage
+                .iter()
+                .filter_map(|m| m.detail.as_ref()?.date.as_deref())
+                .find_map(date_year);
+            if let Some(year) = married {
+                *stats.marriages_per_decade.entry(decade(year)).or_insert(0) += 1;
+            }
+        }
+        if !gedcom.families.is_empty() {
+            stats.average_children = Some(children as f64 / gedcom.families.len() as f64);
+        }
+
+        stats
+    }
+
+    /// The surnames, most common first
+    pub fn surnames_by_frequency(&self) -> Vec<(&str, usize)> {
+        let mut surnames: Vec<(&str, usize)> = self
+            .surnames
+            .iter()
+            .map(|(surname, count)| (surname.as_str(), *count))
+            .collect();
+        surnames.sort_by_key(|s| std::cmp::Reverse(s.1));
+        surnames
+    }
+}
+
+impl fmt::Display for GedcomStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Individuals: {}", self.individuals)?;
+        writeln!(f, "Families: {}", self.families)?;
+        if let (Some(average), Some(median)) = (self.average_lifespan, self.median_lifespan) {
+            writeln!(
+                f,
+                "Lifespan: {:.1} years on average, {:.1} median",
+                average, median
+            )?;
+        }
+        if let Some(average) = self.average_children {
+            writeln!(f, "Children per family: {:.1} on average", average)?;
+        }
+
+        let surnames: Vec<(String, usize)> = self
+            .surnames_by_frequency()
+            .into_iter()
+            .map(|(surname, count)| (surname.to_string(), count))
+            .collect();
+        counts(f, "Most common surnames", &surnames)?;
+        counts(f, "Most common given names (male)", &self.male_given_names)?;
+        counts(
+            f,
+            "Most common given names (female)",
+            &self.female_given_names,
+        )?;
+        counts(
+            f,
+            "Most common given names (other)",
+            &self.other_given_names,
+        )?;
+
+        decades(f, "Births by decade", &self.births_per_decade)?;
+        decades(f, "Marriages by decade", &self.marriages_per_decade)
+    }
+}
+
+/// Write up to REPORT_TOP of the counts, i.e. "Smith (3), Jones (2)"
+fn counts(f: &mut fmt::Formatter, heading: &str, counts: &[(String, usize)]) -> fmt::Result {
+    if counts.is_empty() {
+        return Ok(());
+    }
+    let top: Vec<String> = counts
+        .iter()
+        .take(REPORT_TOP)
+        .map(|(name, count)| format!("{} ({})", name, count))
+        .collect();
+    writeln!(f, "{}: {}", heading, top.join(", "))
+}
+
+/// Write a count per decade, with a bar to show their relative sizes
+fn decades(f: &mut fmt::Formatter, heading: &str, decades: &BTreeMap<i32, usize>) -> fmt::Result {
+    let most = match decades.values().max() {
+        Some(most) => *most,
+        None => return Ok(()),
+    };
+    writeln!(f, "{}:", heading)?;
+    for (decade, count) in decades {
+        let bar = if most <= REPORT_WIDTH {
+            *count
+        } else {
+            (count * REPORT_WIDTH / most).max(1)
+        };
+        writeln!(f, "  {}s {:>4} {}", decade, count, "#".repeat(bar))?;
+    }
+    Ok(())
+}
+
+/// The first surname of an individual's first name
+fn surname(individual: &Individual) -> Option<&str> {
+    names::surname(&individual.names.first()?.name)
+}
+
+/// The first given name of an individual's first name, i.e. "John" for
+/// "John Paul /Jones/"
+fn given_name(individual: &Individual) -> Option<&str> {
+    names::given(&individual.names.first()?.name)?
+        .split_whitespace()
+        .next()
+}
+
+/// The first year of the decade `year` is in, i.e. 1900 for 1907
+pub(crate) fn decade(year: i32) -> i32 {
+    year - year.rem_euclid(10)
+}
+
+/// The names and how often they appear, most common first, then by name
+fn by_frequency(names: HashMap<String, usize>) -> Vec<(String, usize)> {
+    let mut names: Vec<(String, usize)> = names.into_iter().collect();
+    names.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_gedcom_from_str;
+
+    #[test]
+    fn statistics() {
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 SEX M
+1 BIRT
+2 DATE 1900
+1 DEAT
+2 DATE 1970
+0 @I2@ INDI
+1 NAME Jane /Doe/
+1 SEX F
+1 BIRT
+2 DATE 1905
+1 DEAT
+2 DATE 1985
+0 @I3@ INDI
+1 NAME Joe Fred /Bloggs/
+1 SEX M
+1 BIRT
+2 DATE 12 MAR 1931
+1 DEAT
+2 DATE 1991
+0 @I4@ INDI
+1 NAME Sam /Bloggs/
+1 BIRT
+2 DATE 1933
+0 @F1@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 CHIL @I3@
+1 CHIL @I4@
+1 MARR
+2 DATE 1929
+0 @F2@ FAM
+1 HUSB @I3@
+0 TRLR
+",
+        )
+        .unwrap();
+        let stats = gedcom.statistics();
+
+        assert!(stats.individuals == 4 && stats.families == 2);
+        assert!(stats.surnames_by_frequency() == [("Bloggs", 3), ("Doe", 1)]);
+        assert!(stats.average_lifespan == Some((70 + 80 + 60) as f64 / 3.0));
+        assert!(stats.median_lifespan == Some(70.0));
+        assert!(stats.male_given_names == [("Joe".to_string(), 2)]);
+        assert!(stats.female_given_names == [("Jane".to_string(), 1)]);
+        assert!(stats.other_given_names == [("Sam".to_string(), 1)]);
+        assert!(stats.births_per_decade.get(&1930) == Some(&2));
+        assert!(stats.births_per_decade.get(&1900) == Some(&2));
+        assert!(stats.marriages_per_decade.get(&1920) == Some(&1));
+        assert!(stats.average_children == Some(1.0));
+
+        let report = stats.to_string();
+        assert!(report.contains("Lifespan: 70.0 years on average, 70.0 median\n"));
+        assert!(report.contains("Most common surnames: Bloggs (3), Doe (1)\n"));
+        assert!(report.contains("  1930s    2 ##\n"));
+
+        assert!(decade(1899) == 1890 && decade(-5) == -10);
+    }
+}
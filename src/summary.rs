@@ -0,0 +1,269 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::types::{EventDetail, Gedcom};
+
+/// A count of what's in a Gedcom: its records, the tags in use, and how many
+/// events are dated and sourced.
+///
+/// Comparing the summaries of two files is a quick way to check that an
+/// export and re-import didn't silently drop a whole category of data.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Summary {
+    pub individuals: usize,
+    pub families: usize,
+    pub sources: usize,
+    pub repositories: usize,
+    /// OBJE records
+    pub multimedia: usize,
+    /// NOTE records
+    pub notes: usize,
+    pub submitters: usize,
+    /// How many times each tag (NAME, BIRT, MARR, CHIL, ...) was captured
+    pub tags: BTreeMap<&'static str, usize>,
+    /// Every individual and family event
+    pub events: usize,
+    /// The events with a date
+    pub dated_events: usize,
+    /// The events with at least one source citation
+    pub sourced_events: usize,
+}
+
+impl Summary {
+    pub fn new(gedcom: &Gedcom) -> Summary {
+        let mut summary = Summary {
+            individuals: gedcom.individuals.len(),
+            families: gedcom.families.len(),
+            sources: gedcom.sources.len(),
+            repositories: gedcom.repositories.len(),
+            multimedia: gedcom.multimedia.len(),
+            notes: gedcom.notes.len(),
+            submitters: gedcom.submitters.len(),
+            tags: BTreeMap::new(),
+            events: 0,
+            dated_events: 0,
+            sourced_events: 0,
+        };
+
+        for individual in &gedcom.individuals {
+            summary.count("NAME", individual.names.len());
+            summary.count("FAMC", individual.famc.len());
+            summary.count("FAMS", individual.fams.len());
+
+            for (tag, detail) in individual.tagged_events() {
+                summary.event(tag, detail);
+            }
+        }
+
+        for family in &gedcom.families {
+            summary.count("HUSB", family.husband.iter().count());
+            summary.count("WIFE", family.wife.iter().count());
+            summary.count("CHIL", family.children.len());
+
+            for (tag, detail) in family.tagged_events() {
+                summary.event(tag, detail);
+            }
+        }
+
+        summary
+    }
+
+    fn count(&mut self, tag: &'static str, n: usize) {
+        if n > 0 {
+            *self.tags.entry(tag).or_insert(0) += n;
+        }
+    }
+
+    fn event(&mut self, tag: &'static str, detail: Option<&EventDetail>) {
+        self.count(tag, 1);
+        self.events += 1;
+        if let Some(detail) = detail {
+            if detail.date.is_some() {
+                self.dated_events += 1;
+            }
+            if !detail.sources.is_empty() {
+                self.sourced_events += 1;
+            }
+        }
+    }
+}
+
+/// One thing that differs between two summaries
+#[derive(Clone, Debug, PartialEq)]
+pub struct SummaryDelta {
+    /// What was counted, i.e. "individuals" or "tag MARR"
+    pub category: String,
+    pub before: usize,
+    pub after: usize,
+}
+
+impl SummaryDelta {
+    /// How much the count went up (or, if negative, down)
+    pub fn change(&self) -> i64 {
+        self.after as i64 - self.before as i64
+    }
+}
+
+impl fmt::Display for SummaryDelta {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} -> {} ({:+})",
+            self.category,
+            self.before,
+            self.after,
+            self.change()
+        )
+    }
+}
+
+/// The summaries of two files, side by side. See `Gedcom::compare_summary`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SummaryComparison {
+    pub before: Summary,
+    pub after: Summary,
+}
+
+impl SummaryComparison {
+    /// Everything whose count differs, records first, then tags, then the
+    /// date and source coverage of events
+    pub fn deltas(&self) -> Vec<SummaryDelta> {
+        let mut counts: Vec<(String, usize, usize)> = vec![
+            (
+                "individuals".to_string(),
+                self.before.individuals,
+                self.after.individuals,
+            ),
+            (
+                "families".to_string(),
+                self.before.families,
+                self.after.families,
+            ),
+            (
+                "sources".to_string(),
+                self.before.sources,
+                self.after.sources,
+            ),
+            (
+                "repositories".to_string(),
+                self.before.repositories,
+                self.after.repositories,
+            ),
+            (
+                "multimedia".to_string(),
+                self.before.multimedia,
+                self.after.multimedia,
+            ),
+            ("notes".to_string(), self.before.notes, self.after.notes),
+            (
+                "submitters".to_string(),
+                self.before.submitters,
+                self.after.submitters,
+            ),
+        ];
+
+        let mut tags: Vec<&&str> = self.before.tags.keys().collect();
+        tags.extend(self.after.tags.keys());
+        tags.sort();
+        tags.dedup();
+        for tag in tags {
+            counts.push((
+                format!("tag {}", tag),
+                self.before.tags.get(*tag).copied().unwrap_or(0),
+                self.after.tags.get(*tag).copied().unwrap_or(0),
+            ));
+        }
+
+        counts.push(("events".to_string(), self.before.events, self.after.events));
+        counts.push((
+            "dated events".to_string(),
+            self.before.dated_events,
+            self.after.dated_events,
+        ));
+        counts.push((
+            "sourced events".to_string(),
+            self.before.sourced_events,
+            self.after.sourced_events,
+        ));
+
+        counts
+            .into_iter()
+            .filter(|(_, before, after)| before != after)
+            .map(|(category, before, after)| SummaryDelta {
+                category,
+                before,
+                after,
+            })
+            .collect()
+    }
+
+    /// Do the two files hold the same amount of everything?
+    pub fn is_same(&self) -> bool {
+        self.before == self.after
+    }
+}
+
+impl fmt::Display for SummaryComparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let deltas = self.deltas();
+        if deltas.is_empty() {
+            return write!(f, "No differences");
+        }
+        for delta in deltas {
+            writeln!(f, "{}", delta)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::{parse_gedcom, parse_gedcom_from_str};
+    use crate::writer::write_gedcom;
+
+    #[test]
+    fn compare_summary() {
+        let before = parse_gedcom_from_str(
+            "0 HEAD\n0 @I1@ INDI\n1 NAME Joe /Bloggs/\n1 BIRT\n2 DATE 1900\n2 SOUR @S1@\n1 DEAT\n0 TRLR\n",
+        )
+        .unwrap();
+        let after = parse_gedcom_from_str(
+            "0 HEAD\n0 @I1@ INDI\n1 NAME Joe /Bloggs/\n1 BIRT\n2 DATE 1900\n0 TRLR\n",
+        )
+        .unwrap();
+
+        let summary = before.summary();
+        assert!(summary.individuals == 1);
+        assert!(summary.tags.get("BIRT") == Some(&1));
+        assert!(summary.events == 2);
+        assert!(summary.dated_events == 1);
+        assert!(summary.sourced_events == 1);
+
+        assert!(before.compare_summary(&before).is_same());
+        assert!(before.compare_summary(&before).to_string() == "No differences");
+
+        let comparison = before.compare_summary(&after);
+        assert!(!comparison.is_same());
+        let deltas: Vec<String> = comparison.deltas().iter().map(|d| d.to_string()).collect();
+        assert!(
+            deltas
+                == [
+                    "tag DEAT: 1 -> 0 (-1)",
+                    "events: 2 -> 1 (-1)",
+                    "sourced events: 1 -> 0 (-1)"
+                ]
+        );
+    }
+
+    #[test]
+    fn write_every_record() {
+        let gedcom = parse_gedcom("./data/complete.ged");
+        let summary = gedcom.summary();
+        assert!(summary.sources > 0 && summary.repositories > 0);
+        assert!(summary.multimedia > 0 && summary.notes > 0 && summary.submitters > 0);
+
+        let written = parse_gedcom_from_str(&write_gedcom(&gedcom)).unwrap();
+        let comparison = gedcom.compare_summary(&written);
+        assert!(comparison.is_same(), "{}", comparison);
+    }
+}
@@ -0,0 +1,323 @@
+use std::str::FromStr;
+
+use crate::types::{
+    DateQualifier, EventDetail, EventType, Family, Gedcom, GedcomAge, GedcomDate, Individual, Quay,
+    SimpleDate,
+};
+
+/// One event in an individual's life. See `Gedcom::timeline`.
+#[derive(Debug)]
+pub struct TimelineEntry<'a> {
+    pub event: EventType,
+    /// The job of an occupation, or the description of a generic event
+    pub value: Option<&'a str>,
+    /// The event's date, if it has one we can read
+    pub date: Option<GedcomDate>,
+    pub place: Option<&'a str>,
+    /// How old the individual was, in whole years, if their birth and the
+    /// event are both dated
+    pub age: Option<i32>,
+    /// Everything else recorded about the event, i.e. its sources
+    pub detail: Option<&'a EventDetail>,
+    /// For a family event, the family and the individual's spouse in it
+    pub family: Option<&'a Family>,
+    pub spouse: Option<&'a Individual>,
+}
+
+impl TimelineEntry<'_> {
+    /// What happened, for display: the TYPE of a generic event, or else the
+    /// kind of event, followed by its value, i.e. "Occupation: Farmer"
+    pub fn label(&self) -> String {
+        let kind = match self.detail.and_then(|d| d.r#type.as_deref()) {
+            Some(kind) if self.event == EventType::Event => kind.to_string(),
+            _ => self.event.to_string(),
+        };
+        match self.value {
+            Some(value) => format!("{}: {}", kind, value),
+            None => kind,
+        }
+    }
+
+    /// The QUAY of the most credible evidence cited for the event. See
+    /// `EventDetail::best_quay`.
+    pub fn quay(&self) -> Option<Quay> {
+        self.detail?.best_quay()
+    }
+
+    /// The date the timeline is sorted by: the first date of a range or
+    /// period
+    pub fn sort_date(&self) -> Option<SimpleDate> {
+        let date = self.date.as_ref()?;
+        date.start.or(date.end)
+    }
+}
+
+/// Every event in `individual`'s life, and those of the families they're a
+/// spouse in, in date order. Undated events come last, in the order they
+/// were recorded.
+pub fn timeline<'a>(gedcom: &'a Gedcom, individual: &'a Individual) -> Vec<TimelineEntry<'a>> {
+    let mut entries: Vec<TimelineEntry> = individual
+        .all_events()
+        .map(|e| entry(e.event_type, e.value, e.detail, None, None))
+        .collect();
+
+    for family in gedcom.spouse_families(individual) {
+        let spouse = family
+            .husband_xref()
+            .into_iter()
+            .chain(family.wife_xref())
+            .filter(|xref| Some(*xref) != individual.xref.as_deref())
+            .find_map(|xref| gedcom.find_individual(xref));
+        entries.extend(
+            family
+                .tagged_events()
+                .into_iter()
+                .filter_map(|(tag, detail)| {
+                    let event = EventType::from_str(tag).ok()?;
+                    Some(entry(event, None, detail, Some(family), spouse))
+                }),
+        );
+    }
+
+    // Sorting is stable, so events on the same date keep their order
+    entries.sort_by(|a, b| (a.date.is_none(), &a.date).cmp(&(b.date.is_none(), &b.date)));
+
+    let birth = [EventType::Birth, EventType::Christening, EventType::Baptism]
+        .iter()
+        .find_map(|kind| {
+            entries
+                .iter()
+                .filter(|e| e.event == *kind)
+                .find_map(|e| e.sort_date())
+        });
+    if let Some(birth) = birth {
+        for entry in &mut entries {
+            entry.age = entry.sort_date().and_then(|date| age(&birth, &date));
+        }
+    }
+
+    entries
+}
+
+fn entry<'a>(
+    event: EventType,
+    value: Option<&'a str>,
+    detail: Option<&'a EventDetail>,
+    family: Option<&'a Family>,
+    spouse: Option<&'a Individual>,
+) -> TimelineEntry<'a> {
+    TimelineEntry {
+        event,
+        value,
+        date: detail
+            .and_then(|d| d.date.as_deref())
+            .and_then(|d| GedcomDate::from_str(d).ok()),
+        place: detail.and_then(|d| d.place.as_ref()?.name.as_deref()),
+        age: None,
+        detail,
+        family,
+        spouse,
+    }
+}
+
+/// An event that happened on a given day of the year. See
+/// `Gedcom::events_on`.
+#[derive(Debug)]
+pub struct Anniversary<'a> {
+    /// Birth, Marriage or Death
+    pub event: EventType,
+    pub date: GedcomDate,
+    /// Who was born or died, for a birth or death
+    pub individual: Option<&'a Individual>,
+    /// The family, for a marriage
+    pub family: Option<&'a Family>,
+    pub detail: &'a EventDetail,
+}
+
+impl Anniversary<'_> {
+    /// The year the event happened
+    pub fn year(&self) -> Option<i32> {
+        self.date.year()
+    }
+}
+
+/// The births, marriages and deaths that happened on `day` of `month`, in
+/// any year, oldest first. Only dates that name a single day count, so
+/// "12 MAR 1900" and "INT 12 MAR 1900 (Lady Day)" are on 12 March, but
+/// "ABT 12 MAR 1900" and "BET 12 MAR 1900 AND 1901" aren't.
+pub fn events_on(gedcom: &Gedcom, month: u8, day: u8) -> Vec<Anniversary<'_>> {
+    let on = |detail: &EventDetail| {
+        let date = GedcomDate::from_str(detail.date.as_deref()?).ok()?;
+        let single = matches!(
+            date.qualifier,
+            DateQualifier::Exact | DateQualifier::Interpreted
+        );
+        let start = date.start?;
+        (single && start.month == Some(month) && start.day == Some(day)).then_some(date)
+    };
+
+    let mut found = vec![];
+    for individual in &gedcom.individuals {
+        for event in individual.all_events() {
+            if !matches!(event.event_type, EventType::Birth | EventType::Death) {
+                continue;
+            }
+            if let Some((detail, date)) = event.detail.and_then(|d| Some((d, on(d)?))) {
+                found.push(Anniversary {
+                    event: event.event_type,
+                    date,
+                    individual: Some(individual),
+                    family: None,
+                    detail,
+                });
+            }
+        }
+    }
+    for family in &gedcom.families {
+        for marriage in &family.marriage {
+            if let Some((detail, date)) = marriage.detail.as_ref().and_then(|d| Some((d, on(d)?))) {
+                found.push(Anniversary {
+                    event: EventType::Marriage,
+                    date,
+                    individual: None,
+                    family: Some(family),
+                    detail,
+                });
+            }
+        }
+    }
+
+    // Sorting is stable, so events in the same year keep their order
+    found.sort_by(|a, b| a.date.cmp(&b.date));
+    found
+}
+
+/// Whole years from `birth` to `date`, counting the months and days where
+/// both dates have them
+fn age(birth: &SimpleDate, date: &SimpleDate) -> Option<i32> {
+    let age = GedcomAge::between(birth, date)?;
+    age.years.map(|years| years as i32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse::parse_gedcom_from_str;
+
+    #[test]
+    fn timeline() {
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 DEAT
+2 DATE 3 MAR 1970
+2 PLAC Leeds, England
+1 BIRT
+2 DATE 12 MAR 1900
+2 PLAC York, England
+1 CENS
+1 RESI
+2 DATE ABT 1911
+1 OCCU Farmer
+2 DATE 1920
+1 EVEN
+2 TYPE Military service
+2 DATE 1918
+1 FAMS @F1@
+0 @I2@ INDI
+1 NAME Jane /Doe/
+1 FAMS @F1@
+0 @F1@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 MARR
+2 DATE 12 MAR 1925
+0 TRLR
+",
+        )
+        .unwrap();
+        let joe = gedcom.find_individual("@I1@").unwrap();
+        let timeline = gedcom.timeline(joe);
+
+        let events: Vec<(EventType, Option<i32>)> =
+            timeline.iter().map(|e| (e.event, e.age)).collect();
+        assert!(
+            events
+                == [
+                    (EventType::Birth, Some(0)),
+                    (EventType::Residence, Some(11)),
+                    (EventType::Event, Some(18)),
+                    (EventType::Occupation, Some(20)),
+                    (EventType::Marriage, Some(25)),
+                    (EventType::Death, Some(69)),
+                    (EventType::Census, None),
+                ]
+        );
+        assert!(timeline[0].place == Some("York, England"));
+        assert!(timeline[2].label() == "Military service");
+        assert!(timeline[3].label() == "Occupation: Farmer");
+        assert!(timeline[0].label() == "Birth");
+        let marriage = &timeline[4];
+        assert!(marriage.family.unwrap().xref == "@F1@");
+        assert!(marriage.spouse.unwrap().xref.as_deref() == Some("@I2@"));
+        assert!(timeline[1].date.as_ref().unwrap().year() == Some(1911));
+
+        let date = |s: &str| SimpleDate::from_str(s).unwrap();
+        assert!(age(&date("12 MAR 1900"), &date("11 MAR 1910")) == Some(9));
+        assert!(age(&date("MAR 1900"), &date("31 MAR 1910")) == Some(10));
+        assert!(age(&date("1900"), &date("1899")).is_none());
+    }
+
+    #[test]
+    fn events_on() {
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @I1@ INDI
+1 BIRT
+2 DATE 12 MAR 1900
+1 DEAT
+2 DATE 12 MAR 1970
+1 BURI
+2 DATE 12 MAR 1970
+0 @I2@ INDI
+1 BIRT
+2 DATE ABT 12 MAR 1890
+1 DEAT
+2 DATE INT 12 MAR 1850 (Lady Day)
+0 @I3@ INDI
+1 BIRT
+2 DATE MAR 1880
+1 DEAT
+2 DATE 13 MAR 1950
+0 @F1@ FAM
+1 HUSB @I1@
+1 MARR
+2 DATE 12 MAR 1925
+0 TRLR
+",
+        )
+        .unwrap();
+        let found = gedcom.events_on(3, 12);
+        let found: Vec<(EventType, Option<i32>, Option<&str>)> = found
+            .iter()
+            .map(|a| {
+                let xref = match a.family {
+                    Some(family) => Some(family.xref.as_str()),
+                    None => a.individual.and_then(|i| i.xref.as_deref()),
+                };
+                (a.event, a.year(), xref)
+            })
+            .collect();
+        assert!(
+            found
+                == [
+                    (EventType::Death, Some(1850), Some("@I2@")),
+                    (EventType::Birth, Some(1900), Some("@I1@")),
+                    (EventType::Marriage, Some(1925), Some("@F1@")),
+                    (EventType::Death, Some(1970), Some("@I1@")),
+                ]
+        );
+        assert!(gedcom.events_on(2, 30).is_empty());
+    }
+}
@@ -0,0 +1,256 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::{DateQualifier, GedcomDate, SimpleDate};
+
+// AGE_AT_EVENT:= {Size=1:12}
+// [ < | > | <NULL>]
+// [ YYy MMm DDDd | YYy | MMm | DDDd |
+//   YYy MMm | YYy DDDd | MMm DDDd |
+//   CHILD | INFANT | STILLBORN ]
+// ]
+// Where:
+// > = greater than indicated age
+// < = less than indicated age
+// CHILD = age < 8 years
+// INFANT = age < 1 year
+// STILLBORN = died just prior, at, or near birth, 0 years
+
+/// Whether an age is exact, or only a bound on it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum AgeBound {
+    #[default]
+    Exact,
+    /// <: younger than the age given
+    LessThan,
+    /// >: older than the age given
+    GreaterThan,
+}
+
+/// How old an individual was, as recorded in an AGE, i.e. "76y 3m", or as
+/// worked out from two dates. See `Individual::age_at`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct GedcomAge {
+    pub bound: AgeBound,
+    pub years: Option<u32>,
+    pub months: Option<u32>,
+    /// Days, including any weeks (GEDCOM 7's "w")
+    pub days: Option<u32>,
+    /// Whether the age was worked out from approximate dates, i.e. ABT 1900,
+    /// so may be out by a year or more
+    pub approximate: bool,
+}
+
+impl GedcomAge {
+    /// The age of someone born on `birth` at `date`, as precisely as both
+    /// dates allow: in years, months and days if both have a day, years and
+    /// months if both have a month, and otherwise in years. None if `date`
+    /// is before `birth`.
+    pub fn between(birth: &SimpleDate, date: &SimpleDate) -> Option<GedcomAge> {
+        let mut years = date.year - birth.year;
+        let mut months = None;
+        let mut days = None;
+
+        if let (Some(born), Some(month)) = (birth.month, date.month) {
+            let mut m = i32::from(month) - i32::from(born);
+            if let (Some(born_day), Some(day)) = (birth.day, date.day) {
+                let mut d = i32::from(day) - i32::from(born_day);
+                if d < 0 {
+                    // Count from the last monthly anniversary, in the month
+                    // before `date`'s, or that month's end if it's shorter
+                    m -= 1;
+                    let (year, previous) = match month {
+                        1 => (date.year - 1, 12),
+                        _ => (date.year, month - 1),
+                    };
+                    let length = days_in_month(year, previous);
+                    d = i32::from(length - born_day.min(length)) + i32::from(day);
+                }
+                days = Some(d as u32);
+            }
+            if m < 0 {
+                years -= 1;
+                m += 12;
+            }
+            months = Some(m as u32);
+        }
+
+        if years < 0 {
+            return None;
+        }
+        Some(GedcomAge {
+            years: Some(years as u32),
+            months,
+            days,
+            ..Default::default()
+        })
+    }
+
+    /// The age of someone born on `birth` at `date`, using the first date of
+    /// any range or period. A date before or after the one given gives an
+    /// upper or lower bound, and any other qualifier an approximate age.
+    pub fn between_dates(birth: &GedcomDate, date: &GedcomDate) -> Option<GedcomAge> {
+        let mut age = GedcomAge::between(&birth.start.or(birth.end)?, &date.start.or(date.end)?)?;
+
+        // Being born before a date makes someone older, and an event before
+        // a date makes them younger
+        let direction = |qualifier: DateQualifier| match qualifier {
+            DateQualifier::Before => Some(-1),
+            DateQualifier::After => Some(1),
+            DateQualifier::Exact => Some(0),
+            _ => None,
+        };
+        match (direction(birth.qualifier), direction(date.qualifier)) {
+            (Some(birth), Some(date)) => match date - birth {
+                0 if birth != 0 => age.approximate = true,
+                0 => {}
+                d if d < 0 => age.bound = AgeBound::LessThan,
+                _ => age.bound = AgeBound::GreaterThan,
+            },
+            _ => age.approximate = true,
+        }
+        Some(age)
+    }
+}
+
+impl FromStr for GedcomAge {
+    type Err = ();
+
+    /// Parse an AGE, i.e. "76y 3m", "< 8y", or "INFANT"
+    fn from_str(input: &str) -> Result<GedcomAge, Self::Err> {
+        let mut age = GedcomAge::default();
+        let mut input = input.trim();
+        if let Some(rest) = input.strip_prefix('<') {
+            age.bound = AgeBound::LessThan;
+            input = rest.trim_start();
+        } else if let Some(rest) = input.strip_prefix('>') {
+            age.bound = AgeBound::GreaterThan;
+            input = rest.trim_start();
+        }
+
+        match input.to_uppercase().as_str() {
+            "CHILD" => {
+                age.bound = AgeBound::LessThan;
+                age.years = Some(8);
+                return Ok(age);
+            }
+            "INFANT" => {
+                age.bound = AgeBound::LessThan;
+                age.years = Some(1);
+                return Ok(age);
+            }
+            "STILLBORN" => {
+                age.years = Some(0);
+                return Ok(age);
+            }
+            _ => {}
+        }
+
+        for token in input.split_whitespace() {
+            let (i, unit) = token.char_indices().last().ok_or(())?;
+            let unit = unit.to_ascii_lowercase();
+            let number: u32 = token[..i].parse().map_err(|_| ())?;
+            let field = match unit {
+                'y' => &mut age.years,
+                'm' => &mut age.months,
+                'w' | 'd' => &mut age.days,
+                _ => return Err(()),
+            };
+            let number = if unit == 'w' {
+                number.saturating_mul(7)
+            } else {
+                number
+            };
+            *field = Some(field.unwrap_or(0).saturating_add(number));
+        }
+        if age.years.is_none() && age.months.is_none() && age.days.is_none() {
+            return Err(());
+        }
+        Ok(age)
+    }
+}
+
+impl fmt::Display for GedcomAge {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = vec![];
+        match self.bound {
+            AgeBound::Exact => {}
+            AgeBound::LessThan => parts.push("<".to_string()),
+            AgeBound::GreaterThan => parts.push(">".to_string()),
+        }
+        for (value, unit) in [(self.years, 'y'), (self.months, 'm'), (self.days, 'd')] {
+            if let Some(value) = value {
+                parts.push(format!("{}{}", value, unit));
+            }
+        }
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn age(years: u32, months: Option<u32>, days: Option<u32>) -> GedcomAge {
+        GedcomAge {
+            years: Some(years),
+            months,
+            days,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn parse_age() {
+        assert!(GedcomAge::from_str("76y 3m") == Ok(age(76, Some(3), None)));
+        assert!(GedcomAge::from_str("1y 2w 3d").unwrap().days == Some(17));
+        let child = GedcomAge::from_str("CHILD").unwrap();
+        assert!(child.bound == AgeBound::LessThan && child.years == Some(8));
+        let older = GedcomAge::from_str("> 40y").unwrap();
+        assert!(older.bound == AgeBound::GreaterThan && older.years == Some(40));
+        assert!(GedcomAge::from_str("3m").unwrap().years.is_none());
+        assert!(GedcomAge::from_str("old").is_err());
+        assert!(GedcomAge::from_str("5é").is_err());
+        assert!(GedcomAge::from_str("").is_err());
+        assert!(older.to_string() == "> 40y");
+        assert!(age(76, Some(3), None).to_string() == "76y 3m");
+    }
+
+    #[test]
+    fn age_between() {
+        let date = |s: &str| SimpleDate::from_str(s).unwrap();
+        assert!(
+            GedcomAge::between(&date("12 MAR 1900"), &date("11 MAR 1910"))
+                == Some(age(9, Some(11), Some(27)))
+        );
+        assert!(
+            GedcomAge::between(&date("31 JAN 1900"), &date("1 MAR 1900"))
+                == Some(age(0, Some(1), Some(1)))
+        );
+        assert!(
+            GedcomAge::between(&date("MAR 1900"), &date("31 MAR 1910"))
+                == Some(age(10, Some(0), None))
+        );
+        assert!(GedcomAge::between(&date("1900"), &date("MAR 1950")) == Some(age(50, None, None)));
+        assert!(GedcomAge::between(&date("1900"), &date("1899")).is_none());
+
+        let date = |s: &str| GedcomDate::from_str(s).unwrap();
+        let exact = GedcomAge::between_dates(&date("1900"), &date("1950")).unwrap();
+        assert!(exact.bound == AgeBound::Exact && !exact.approximate);
+        let before = GedcomAge::between_dates(&date("1900"), &date("BEF 1950")).unwrap();
+        assert!(before.bound == AgeBound::LessThan);
+        let after = GedcomAge::between_dates(&date("BEF 1900"), &date("1950")).unwrap();
+        assert!(after.bound == AgeBound::GreaterThan);
+        let about = GedcomAge::between_dates(&date("ABT 1900"), &date("1950")).unwrap();
+        assert!(about.approximate && about.years == Some(50));
+    }
+}
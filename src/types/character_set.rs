@@ -1,6 +1,6 @@
 use super::Line;
 
-#[derive(Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct CharacterSet {
     /// The version of this Gedcom file.
     pub encoding: Option<String>,
@@ -0,0 +1,573 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+// DATE_VALUE:=
+// [ <DATE> | <DATE_PERIOD> | <DATE_RANGE> | <DATE_APPROXIMATED> |
+//   INT <DATE> (<DATE_PHRASE>) | (<DATE_PHRASE>) ]
+//
+// DATE_PERIOD:= [ FROM <DATE> | TO <DATE> | FROM <DATE> TO <DATE> ]
+// DATE_RANGE:= [ BEF <DATE> | AFT <DATE> | BET <DATE> AND <DATE> ]
+// DATE_APPROXIMATED:= [ ABT <DATE> | CAL <DATE> | EST <DATE> ]
+// DATE:= [ <DATE_CALENDAR_ESCAPE> | <NULL>] <DATE_CALENDAR>
+// DATE_GREG:= [ <YEAR_GREG>[B.C.] | <MONTH> <YEAR_GREG> | <DAY> <MONTH> <YEAR_GREG> ]
+
+/// How a GEDCOM date value qualifies its date(s)
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum DateQualifier {
+    /// A plain date, i.e. 1 JAN 1900
+    #[default]
+    Exact,
+    /// ABT: about, meaning the date is not exact
+    About,
+    /// CAL: calculated mathematically, for example, from an event date and age
+    Calculated,
+    /// EST: estimated based on an algorithm using some other event date
+    Estimated,
+    /// BEF: before the date
+    Before,
+    /// AFT: after the date
+    After,
+    /// BET ... AND ...: sometime between the two dates
+    Between,
+    /// FROM ...: the start of a period
+    From,
+    /// TO ...: the end of a period
+    To,
+    /// FROM ... TO ...: a period
+    FromTo,
+    /// INT: interpreted from knowledge about the associated date phrase
+    Interpreted,
+    /// (...): only a date phrase, that couldn't be interpreted
+    Phrase,
+}
+
+/// A single calendar date, which may be missing its day or month
+///
+/// The fields are ordered so that dates sort chronologically, with less
+/// precise dates sorting before more precise dates in the same year or month.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SimpleDate {
+    /// The year, negative for B.C.
+    pub year: i32,
+    /// The month, from 1 to 12
+    pub month: Option<u8>,
+    /// The day of the month
+    pub day: Option<u8>,
+}
+
+impl FromStr for SimpleDate {
+    type Err = ();
+
+    /// Parse a date such as "1 JAN 1900", "JAN 1900", "1900", "1750/51" or "44 B.C."
+    fn from_str(input: &str) -> Result<SimpleDate, Self::Err> {
+        let mut tokens: Vec<&str> = input.split_whitespace().collect();
+
+        // Ignore any calendar escape, i.e. @#DGREGORIAN@
+        if tokens.first().map(|t| t.starts_with("@#")).unwrap_or(false) {
+            tokens.remove(0);
+        }
+
+        let bc = matches!(tokens.last(), Some(&"B.C.") | Some(&"BC"));
+        if bc {
+            tokens.pop();
+        }
+
+        let (year, rest) = tokens.split_last().ok_or(())?;
+        // Handle dual dates, i.e. 1750/51
+        let year: i32 = year
+            .split('/')
+            .next()
+            .unwrap_or(year)
+            .parse()
+            .map_err(|_| ())?;
+
+        let mut date = SimpleDate {
+            year: if bc { -year } else { year },
+            month: None,
+            day: None,
+        };
+
+        match rest {
+            [] => {}
+            [month] => date.month = Some(month_number(month).ok_or(())?),
+            [day, month] => {
+                date.month = Some(month_number(month).ok_or(())?);
+                let day: u8 = day.parse().map_err(|_| ())?;
+                if day == 0 || day > 31 {
+                    return Err(());
+                }
+                date.day = Some(day);
+            }
+            _ => return Err(()),
+        }
+
+        Ok(date)
+    }
+}
+
+impl fmt::Display for SimpleDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(day) = self.day {
+            write!(f, "{} ", day)?;
+        }
+        if let Some(month) = self.month {
+            write!(f, "{} ", MONTHS[month as usize - 1])?;
+        }
+        if self.year < 0 {
+            write!(f, "{} B.C.", -self.year)
+        } else {
+            write!(f, "{}", self.year)
+        }
+    }
+}
+
+const MONTHS: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+
+/// The number (1-12) of a GEDCOM month, i.e. JAN
+pub fn month_number(month: &str) -> Option<u8> {
+    MONTHS
+        .iter()
+        .position(|m| m.eq_ignore_ascii_case(month))
+        .map(|i| i as u8 + 1)
+}
+
+/// A structured GEDCOM date value
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct GedcomDate {
+    pub qualifier: DateQualifier,
+    /// The date, or the first date of a range or period
+    pub start: Option<SimpleDate>,
+    /// The second date of a range or period
+    pub end: Option<SimpleDate>,
+    /// The text of a date phrase, without the parentheses
+    pub phrase: Option<String>,
+}
+
+impl GedcomDate {
+    /// The year of the (first) date, if there is one
+    pub fn year(&self) -> Option<i32> {
+        self.start.or(self.end).map(|d| d.year)
+    }
+
+    /// The first and last days the date could be, as (year, month, day),
+    /// where None is open-ended. "1900" could be any day of 1900, "BEF
+    /// 1900" any day up to the end of 1900, and "BET 1900 AND 1910" any
+    /// day of those years. ABT, CAL and EST dates are taken as written.
+    fn bounds(&self) -> Option<(Option<Day>, Option<Day>)> {
+        let first = |d: &SimpleDate| (d.year, d.month.unwrap_or(1), d.day.unwrap_or(1));
+        let last = |d: &SimpleDate| (d.year, d.month.unwrap_or(12), d.day.unwrap_or(31));
+        Some(match (self.qualifier, &self.start, &self.end) {
+            (_, None, None) => return None,
+            (DateQualifier::Before, Some(d), _) | (DateQualifier::To, None, Some(d)) => {
+                (None, Some(last(d)))
+            }
+            (DateQualifier::After, Some(d), _) | (DateQualifier::From, Some(d), None) => {
+                (Some(first(d)), None)
+            }
+            (_, Some(start), Some(end)) => (Some(first(start)), Some(last(end))),
+            (_, Some(d), None) | (_, None, Some(d)) => (Some(first(d)), Some(last(d))),
+        })
+    }
+
+    /// Whether the date certainly falls from `start` to `end`, inclusive,
+    /// where they may be partial dates, i.e. "1800" to "JUN 1850". Open
+    /// ranges, like "BEF 1850", and date phrases never do.
+    pub fn within(&self, start: &SimpleDate, end: &SimpleDate) -> bool {
+        let from = (start.year, start.month.unwrap_or(1), start.day.unwrap_or(1));
+        let to = (end.year, end.month.unwrap_or(12), end.day.unwrap_or(31));
+        match self.bounds() {
+            Some((Some(first), Some(last))) => from <= first && last <= to,
+            _ => false,
+        }
+    }
+
+    /// Where the date sorts: by its (first) date, with BEF and TO just
+    /// before and AFT just after the same date, and dates that are only a
+    /// phrase last
+    fn sort_key(&self) -> (bool, Option<SimpleDate>, i8) {
+        let date = self.start.or(self.end);
+        let nudge = match self.qualifier {
+            DateQualifier::Before | DateQualifier::To => -1,
+            DateQualifier::After => 1,
+            _ => 0,
+        };
+        (date.is_none(), date, nudge)
+    }
+}
+
+/// A day, as (year, month, day)
+type Day = (i32, u8, u8);
+
+/// Dates sort chronologically, by the date they're written with: a range
+/// or period by its first date, and an approximate date as if it were
+/// exact. A date BEF or TO a day sorts just before that day, one AFT it
+/// just after, and a date that's only a phrase sorts after every other.
+/// Dates that sort together are then ordered by their qualifier, second
+/// date and phrase, so only equal dates compare equal.
+impl Ord for GedcomDate {
+    fn cmp(&self, other: &GedcomDate) -> Ordering {
+        self.sort_key()
+            .cmp(&other.sort_key())
+            .then_with(|| self.qualifier.cmp(&other.qualifier))
+            .then_with(|| self.start.cmp(&other.start))
+            .then_with(|| self.end.cmp(&other.end))
+            .then_with(|| self.phrase.cmp(&other.phrase))
+    }
+}
+
+impl PartialOrd for GedcomDate {
+    fn partial_cmp(&self, other: &GedcomDate) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl FromStr for GedcomDate {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<GedcomDate, Self::Err> {
+        let input = input.trim();
+        let mut date = GedcomDate::default();
+
+        // Split off any date phrase
+        let (input, phrase) = match input.find('(') {
+            Some(i) => {
+                let phrase = input[i + 1..].trim_end().trim_end_matches(')');
+                (input[..i].trim(), Some(phrase.to_string()))
+            }
+            None => (input, None),
+        };
+        date.phrase = phrase;
+
+        if input.is_empty() {
+            if date.phrase.is_none() {
+                return Err(());
+            }
+            date.qualifier = DateQualifier::Phrase;
+            return Ok(date);
+        }
+
+        let (keyword, rest) = match input.split_once(' ') {
+            Some((keyword, rest)) => (keyword.to_ascii_uppercase(), rest),
+            None => (String::new(), input),
+        };
+        date.qualifier = match keyword.as_str() {
+            "ABT" => DateQualifier::About,
+            "CAL" => DateQualifier::Calculated,
+            "EST" => DateQualifier::Estimated,
+            "BEF" => DateQualifier::Before,
+            "AFT" => DateQualifier::After,
+            "BET" => DateQualifier::Between,
+            "FROM" => DateQualifier::From,
+            "TO" => DateQualifier::To,
+            "INT" => DateQualifier::Interpreted,
+            _ => DateQualifier::Exact,
+        };
+        let rest = if date.qualifier == DateQualifier::Exact {
+            input
+        } else {
+            rest
+        };
+
+        match date.qualifier {
+            DateQualifier::Between => {
+                let (start, end) = split_keyword(rest, "AND").ok_or(())?;
+                date.start = Some(start.parse()?);
+                date.end = Some(end.parse()?);
+            }
+            DateQualifier::From => match split_keyword(rest, "TO") {
+                Some((start, end)) => {
+                    date.qualifier = DateQualifier::FromTo;
+                    date.start = Some(start.parse()?);
+                    date.end = Some(end.parse()?);
+                }
+                None => date.start = Some(rest.parse()?),
+            },
+            DateQualifier::To => date.end = Some(rest.parse()?),
+            _ => date.start = Some(rest.parse()?),
+        }
+
+        Ok(date)
+    }
+}
+
+/// Split "1900 AND 1910" into ("1900", "1910")
+fn split_keyword<'a>(input: &'a str, keyword: &str) -> Option<(&'a str, &'a str)> {
+    // Uppercasing ASCII doesn't change the byte offsets
+    let upper = input.to_ascii_uppercase();
+    let i = upper.find(&format!(" {} ", keyword))?;
+    Some((input[..i].trim(), input[i + keyword.len() + 2..].trim()))
+}
+
+/// A time of day, as found in the TIME of a DATE, i.e. 13:57:24.80
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Time {
+    pub hour: u8,
+    pub minute: u8,
+    pub second: u8,
+    /// Fractions of a second, in milliseconds
+    pub millisecond: u16,
+    /// Whether the time is in UTC, as marked by a trailing Z in GEDCOM 7,
+    /// rather than an unknown local time
+    pub utc: bool,
+}
+
+impl FromStr for Time {
+    type Err = ();
+
+    /// Parse a time as the specification gives it, hh:mm:ss.fs, and as
+    /// some programs write it: with a comma before, or any number of digits
+    /// in, the fraction of a second, and with AM or PM, or a zone of Z, UTC
+    /// or GMT, after it. Anything else, such as an hour of 25, is an error.
+    fn from_str(input: &str) -> Result<Time, Self::Err> {
+        let mut input = input.trim();
+        let mut utc = false;
+        for zone in ["Z", "UTC", "GMT"] {
+            if let Some(rest) = strip_suffix_ignore_case(input, zone) {
+                input = rest.trim_end();
+                utc = true;
+                break;
+            }
+        }
+        let mut afternoon = None;
+        for (suffix, pm) in [("AM", false), ("PM", true)] {
+            if let Some(rest) = strip_suffix_ignore_case(input, suffix) {
+                input = rest.trim_end();
+                afternoon = Some(pm);
+                break;
+            }
+        }
+
+        let mut parts = input.split(':');
+        let mut hour: u8 = parts.next().ok_or(())?.parse().map_err(|_| ())?;
+        let minute: u8 = parts.next().ok_or(())?.parse().map_err(|_| ())?;
+        if let Some(pm) = afternoon {
+            if !(1..=12).contains(&hour) {
+                return Err(());
+            }
+            hour = hour % 12 + if pm { 12 } else { 0 };
+        }
+
+        let mut time = Time {
+            hour,
+            minute,
+            second: 0,
+            millisecond: 0,
+            utc,
+        };
+        if let Some(seconds) = parts.next() {
+            let (second, fraction) = seconds.split_once(['.', ',']).unwrap_or((seconds, ""));
+            time.second = second.parse().map_err(|_| ())?;
+            if !fraction.is_empty() {
+                if !fraction.chars().all(|c| c.is_ascii_digit()) {
+                    return Err(());
+                }
+                // Scale the fraction to milliseconds, i.e. .8 and .80 are both 800
+                let digits: String = fraction.chars().chain("000".chars()).take(3).collect();
+                time.millisecond = digits.parse().map_err(|_| ())?;
+            }
+        }
+        if parts.next().is_some() || time.hour > 23 || time.minute > 59 || time.second > 60 {
+            return Err(());
+        }
+
+        Ok(time)
+    }
+}
+
+fn strip_suffix_ignore_case<'a>(input: &'a str, suffix: &str) -> Option<&'a str> {
+    let split = input.len().checked_sub(suffix.len())?;
+    let (rest, end) = (input.get(..split)?, input.get(split..)?);
+    Some(rest).filter(|_| end.eq_ignore_ascii_case(suffix))
+}
+
+impl fmt::Display for Time {
+    /// The time as the specification gives it, i.e. 08:35:00 or
+    /// 13:57:24.800Z
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}:{:02}", self.hour, self.minute, self.second)?;
+        if self.millisecond > 0 {
+            write!(f, ".{:03}", self.millisecond)?;
+        }
+        if self.utc {
+            write!(f, "Z")?;
+        }
+        Ok(())
+    }
+}
+
+/// A date with an optional time, such as the transmission date of a file or
+/// the date a record was changed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp {
+    pub date: SimpleDate,
+    pub time: Option<Time>,
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.date)?;
+        if let Some(time) = self.time {
+            write!(f, " {}", time)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_simple_date() {
+        let date: SimpleDate = "1 JAN 1998".parse().unwrap();
+        assert!(
+            date == SimpleDate {
+                year: 1998,
+                month: Some(1),
+                day: Some(1)
+            }
+        );
+        assert!(date.to_string() == "1 JAN 1998");
+
+        let date: SimpleDate = "MAY 1979".parse().unwrap();
+        assert!(date.month == Some(5) && date.day.is_none());
+        assert!("1750/51".parse::<SimpleDate>().unwrap().year == 1750);
+        assert!("44 B.C.".parse::<SimpleDate>().unwrap().year == -44);
+        assert!("@#DGREGORIAN@ 1900".parse::<SimpleDate>().unwrap().year == 1900);
+
+        assert!("32 JAN 1900".parse::<SimpleDate>().is_err());
+        assert!("1 FOO 1900".parse::<SimpleDate>().is_err());
+        assert!("".parse::<SimpleDate>().is_err());
+
+        // Less precise dates sort first
+        let year: SimpleDate = "1900".parse().unwrap();
+        let month: SimpleDate = "JAN 1900".parse().unwrap();
+        let day: SimpleDate = "2 JAN 1900".parse().unwrap();
+        assert!(year < month && month < day);
+    }
+
+    #[test]
+    fn parse_gedcom_date() {
+        let date: GedcomDate = "ABT 31 DEC 1997".parse().unwrap();
+        assert!(date.qualifier == DateQualifier::About);
+        assert!(date.year() == Some(1997));
+
+        let date: GedcomDate = "BET MAY 1979 AND AUG 1979".parse().unwrap();
+        assert!(date.qualifier == DateQualifier::Between);
+        assert!(date.start.unwrap().month == Some(5));
+        assert!(date.end.unwrap().month == Some(8));
+
+        let date: GedcomDate = "FROM 1900 TO 1905".parse().unwrap();
+        assert!(date.qualifier == DateQualifier::FromTo);
+        assert!(date.end.unwrap().year == 1905);
+
+        let date: GedcomDate = "TO 1905".parse().unwrap();
+        assert!(date.qualifier == DateQualifier::To);
+        assert!(date.year() == Some(1905));
+
+        let date: GedcomDate = "INT 1995 (from estimated age)".parse().unwrap();
+        assert!(date.qualifier == DateQualifier::Interpreted);
+        assert!(date.phrase == Some("from estimated age".to_string()));
+
+        let date: GedcomDate = "(Stillborn)".parse().unwrap();
+        assert!(date.qualifier == DateQualifier::Phrase);
+        assert!(date.year().is_none());
+
+        assert!("".parse::<GedcomDate>().is_err());
+        assert!("sometime".parse::<GedcomDate>().is_err());
+    }
+
+    #[test]
+    fn order_gedcom_dates() {
+        let mut dates: Vec<GedcomDate> = [
+            "(Stillborn)",
+            "AFT 1900",
+            "1900",
+            "BET 1899 AND 1920",
+            "ABT 1900",
+            "BEF 1900",
+            "MAR 1900",
+            "TO 1900",
+            "44 B.C.",
+        ]
+        .iter()
+        .map(|d| d.parse().unwrap())
+        .collect();
+        dates.sort();
+        let dates: Vec<String> = dates
+            .iter()
+            .map(|d| match (d.qualifier, d.start.or(d.end)) {
+                (q, Some(date)) => format!("{:?} {}", q, date),
+                (q, None) => format!("{:?}", q),
+            })
+            .collect();
+        assert!(
+            dates
+                == [
+                    "Exact 44 B.C.",
+                    "Between 1899",
+                    "Before 1900",
+                    "To 1900",
+                    "Exact 1900",
+                    "About 1900",
+                    "After 1900",
+                    "Exact MAR 1900",
+                    "Phrase"
+                ]
+        );
+
+        let a: GedcomDate = "1900".parse().unwrap();
+        let b: GedcomDate = "INT 1900 (nineteen hundred)".parse().unwrap();
+        assert!(a < b && a.cmp(&a.clone()) == Ordering::Equal);
+    }
+
+    #[test]
+    fn date_within() {
+        let date = |d: &str| d.parse::<SimpleDate>().unwrap();
+        let within = |d: &str, start: &str, end: &str| {
+            d.parse::<GedcomDate>()
+                .unwrap()
+                .within(&date(start), &date(end))
+        };
+        assert!(within("1850", "1800", "1850"));
+        assert!(within("ABT 1820", "1820", "1820"));
+        assert!(within("BET 1820 AND 1830", "1800", "1850"));
+        assert!(within("FROM MAR 1900 TO 1905", "JAN 1900", "1905"));
+        assert!(!within("BET 1820 AND 1860", "1800", "1850"));
+        assert!(!within("1850", "1800", "JUN 1850"));
+        assert!(!within("BEF 1850", "1800", "1850"));
+        assert!(!within("AFT 1800", "1800", "1850"));
+        assert!(!within("(young)", "1800", "1850"));
+    }
+
+    #[test]
+    fn parse_time() {
+        let time: Time = "13:57:24.80".parse().unwrap();
+        assert!(
+            time == Time {
+                hour: 13,
+                minute: 57,
+                second: 24,
+                millisecond: 800,
+                utc: false,
+            }
+        );
+        assert!(time.to_string() == "13:57:24.800");
+        assert!("8:35".parse::<Time>().unwrap().to_string() == "08:35:00");
+        assert!("25:00".parse::<Time>().is_err());
+        assert!("12:00:00:00".parse::<Time>().is_err());
+
+        // As other programs write them
+        let time: Time = "13:57:24,8046Z".parse().unwrap();
+        assert!(time.utc && time.millisecond == 804);
+        assert!(time.to_string() == "13:57:24.804Z");
+        assert!("1:05 pm".parse::<Time>().unwrap().to_string() == "13:05:00");
+        assert!("12:30AM UTC".parse::<Time>().unwrap().to_string() == "00:30:00Z");
+        assert!("13:05 PM".parse::<Time>().is_err());
+        assert!("12:3a".parse::<Time>().is_err());
+        assert!("Z".parse::<Time>().is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use super::Line;
+use super::{Line, SimpleDate, Time, Timestamp};
 // use crate::parse;
 
 // +1 DATE <TRANSMISSION_DATE>
@@ -38,4 +38,62 @@ impl DateTime {
 
         (buffer, Some(dt))
     }
+
+    /// The date and time, parsed. Returns None if there's no date, or it
+    /// isn't a single calendar date. An invalid time is ignored.
+    pub fn timestamp(&self) -> Option<Timestamp> {
+        let date: SimpleDate = self.date.as_ref()?.parse().ok()?;
+
+        Some(Timestamp {
+            date,
+            time: self.parsed_time(),
+        })
+    }
+
+    /// The time, parsed. None if there's no time, or it isn't valid.
+    pub fn parsed_time(&self) -> Option<Time> {
+        self.time.as_ref()?.parse().ok()
+    }
+
+    /// The time, as written in the file, if there is one and it isn't valid
+    pub fn invalid_time(&self) -> Option<&str> {
+        self.time
+            .as_deref()
+            .filter(|_| self.parsed_time().is_none())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_timestamp() {
+        let data = ["1 DATE 1 JAN 1998", "2 TIME 13:57:24.80"].join("\n");
+        let (_, dt) = DateTime::parse(&data);
+        let timestamp = dt.unwrap().timestamp().unwrap();
+        assert!(timestamp.to_string() == "1 JAN 1998 13:57:24.800");
+
+        let dt = DateTime {
+            date: Some("ABT 1998".to_string()),
+            time: None,
+        };
+        assert!(dt.timestamp().is_none());
+    }
+
+    #[test]
+    fn parse_time() {
+        let mut dt = DateTime {
+            date: Some("1 JAN 1998".to_string()),
+            time: Some("1:57:24.80 PM".to_string()),
+        };
+        assert!(dt.parsed_time().unwrap().to_string() == "13:57:24.800");
+        assert!(dt.invalid_time().is_none());
+
+        // The date is still good without it
+        dt.time = Some("25:61".to_string());
+        assert!(dt.parsed_time().is_none());
+        assert!(dt.invalid_time() == Some("25:61"));
+        assert!(dt.timestamp().unwrap().to_string() == "1 JAN 1998");
+    }
 }
@@ -0,0 +1,95 @@
+/// How much of a file couldn't be decoded cleanly, so you can judge whether
+/// its text survived. See `Gedcom::decoding`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DecodingStats {
+    /// Byte sequences that weren't valid UTF-8, each replaced with U+FFFD
+    pub replaced: usize,
+    /// Characters that decoded, but are likely the result of an earlier
+    /// mistake: U+FFFD already in the file, C1 control characters, and
+    /// UTF-8 read as Latin-1 (i.e. "Ã©" for "é")
+    pub suspect: usize,
+    /// The records with replaced or suspect characters, in file order
+    pub records: Vec<RecordDecoding>,
+    /// The encoding the file was read as: the one given to
+    /// `GedcomConfig::force_encoding`, or else the one its first bytes
+    /// suggest
+    pub encoding: Encoding,
+    /// Whether the file started with a byte order mark
+    pub byte_order_mark: bool,
+}
+
+impl DecodingStats {
+    /// Whether every character was decoded without trouble
+    pub fn is_clean(&self) -> bool {
+        self.replaced == 0 && self.suspect == 0
+    }
+}
+
+/// The encodings a file can be read as. Files in other character sets, i.e.
+/// ANSEL, are read as UTF-8.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Encoding {
+    #[default]
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    /// What GEDCOM calls ANSI, a superset of Latin-1
+    Windows1252,
+}
+
+impl Encoding {
+    /// Whether a declared character set (the header's CHAR) could describe
+    /// a file in this encoding
+    pub fn matches(&self, declared: &str) -> bool {
+        let declared = declared.to_uppercase();
+        match self {
+            Encoding::Utf8 => matches!(declared.as_str(), "UTF-8" | "UTF8" | "ASCII"),
+            Encoding::Utf16Le | Encoding::Utf16Be => {
+                matches!(declared.as_str(), "UNICODE" | "UTF-16" | "UTF16")
+            }
+            Encoding::Windows1252 => matches!(
+                declared.as_str(),
+                "ANSI" | "WINDOWS-1252" | "CP1252" | "ISO-8859-1" | "LATIN1" | "ASCII"
+            ),
+        }
+    }
+}
+
+impl std::str::FromStr for Encoding {
+    type Err = ();
+
+    /// Parse an encoding's name, or a CHAR value naming one, i.e. "UTF-16BE"
+    /// or "ANSI"
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "UTF-8" | "UTF8" => Ok(Encoding::Utf8),
+            "UTF-16LE" | "UTF16LE" | "UNICODE" | "UTF-16" | "UTF16" => Ok(Encoding::Utf16Le),
+            "UTF-16BE" | "UTF16BE" => Ok(Encoding::Utf16Be),
+            "ANSI" | "WINDOWS-1252" | "CP1252" | "ISO-8859-1" | "LATIN1" => {
+                Ok(Encoding::Windows1252)
+            }
+            _ => Err(()),
+        }
+    }
+}
+
+impl std::fmt::Display for Encoding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Encoding::Utf8 => "UTF-8",
+            Encoding::Utf16Le => "UTF-16LE",
+            Encoding::Utf16Be => "UTF-16BE",
+            Encoding::Windows1252 => "Windows-1252",
+        })
+    }
+}
+
+/// The replaced and suspect characters in a single record
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RecordDecoding {
+    pub xref: Option<String>,
+    /// The line the record starts on
+    pub line: usize,
+    pub replaced: usize,
+    pub suspect: usize,
+}
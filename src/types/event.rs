@@ -1,6 +1,9 @@
 /// This is a template of a Type
 use crate::parse;
-use crate::types::{Address, Line, Object, Place, SourceCitation, Spouse};
+use crate::renumber::is_pointer;
+use crate::types::{
+    Address, Association, Line, Object, Place, Quay, Restriction, SourceCitation, Spouse,
+};
 
 use winnow::prelude::*;
 
@@ -18,8 +21,12 @@ use winnow::prelude::*;
 // n <<NOTE_STRUCTURE>> {0:M} p.37
 // n <<SOURCE_CITATION>> {0:M} p.39
 // n <<MULTIMEDIA_LINK>> {0:M} p.37, 26
+//
+// GEDCOM 7 adds n <<ASSOCIATION_STRUCTURE>> {0:M}, for the people at an
+// event other than its principals, i.e. witnesses. Before that, programs
+// wrote them as _ASSO, or as shared events, _SHAR, with a ROLE, or _WITN.
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct EventDetail {
     pub r#type: Option<String>,
     pub date: Option<String>,
@@ -32,9 +39,31 @@ pub struct EventDetail {
     pub note: Option<String>,
     pub sources: Vec<SourceCitation>,
     pub media: Vec<Object>,
+    /// Contact details recorded for the event. These are part of the
+    /// ADDRESS_STRUCTURE but sit at the same level as ADDR, and may appear
+    /// without one, so we collect them here regardless of where they were
+    /// found. Any that follow an ADDR are also kept on the address.
+    pub phone: Vec<String>,
+    pub email: Vec<String>,
+    pub fax: Vec<String>,
+    pub www: Vec<String>,
+    /// ASSO: Others who were at the event, with their role, i.e. witnesses
+    /// or an officiant. See `Gedcom::events_witnessed_by`.
+    pub associations: Vec<Association>,
 }
 
 impl EventDetail {
+    /// The restrictions in the event's RESN
+    pub fn restrictions(&self) -> Vec<Restriction> {
+        Restriction::parse_list(self.restriction_notice.as_deref().unwrap_or_default())
+    }
+
+    /// The QUAY of the most credible evidence cited for the event, or None
+    /// if no citation gives one
+    pub fn best_quay(&self) -> Option<Quay> {
+        self.sources.iter().filter_map(|s| s.quay.clone()).max()
+    }
+
     /// Parse
     pub fn parse(record: &mut &str) -> PResult<EventDetail> {
         let mut event = EventDetail {
@@ -49,6 +78,11 @@ impl EventDetail {
             note: None,
             sources: vec![],
             media: vec![],
+            phone: vec![],
+            email: vec![],
+            fax: vec![],
+            www: vec![],
+            associations: vec![],
         };
 
         let mut line = Line::peek(record).unwrap();
@@ -57,11 +91,21 @@ impl EventDetail {
         match line.tag {
             "ADOP" | "BAPM" | "BARM" | "BASM" | "BIRT" | "BLES" | "BURI" | "CENS" | "CHR"
             | "CHRA" | "CONF" | "CREM" | "DEAT" | "EMIG" | "EVEN" | "FCOM" | "GRAD" | "IMMI"
-            | "ORDN" | "PROB" | "NATU" | "RETI" | "WILL" => {
+            | "ORDN" | "PROB" | "NATU" | "RETI" | "WILL"
+            // Individual attributes share the same detail structure
+            | "CAST" | "DSCR" | "EDUC" | "IDNO" | "NATI" | "NCHI" | "NMR" | "OCCU" | "PROP"
+            | "SSN" | "TITL" | "FACT" => {
                 // Consume the current line
-                let _ = Line::parse(record);
+                let top = Line::parse(record).unwrap().level;
+                if record.is_empty() {
+                    return Ok(event);
+                }
                 // Get the next line
                 line = Line::peek(record).unwrap();
+                // An event with no detail, i.e. `1 DEAT Y`
+                if line.level <= top {
+                    return Ok(event);
+                }
             }
             _ => {}
         }
@@ -72,7 +116,12 @@ impl EventDetail {
             let mut parse = true;
             match line.tag {
                 "ADDR" => {
-                    event.address = Some(Address::parse(record).unwrap());
+                    let address = Address::parse(record).unwrap();
+                    event.phone.extend(address.phone.iter().cloned());
+                    event.email.extend(address.email.iter().cloned());
+                    event.fax.extend(address.fax.iter().cloned());
+                    event.www.extend(address.www.iter().cloned());
+                    event.address = Some(address);
                     parse = false;
                 }
                 // "AGE" => {
@@ -81,21 +130,33 @@ impl EventDetail {
                 "AGNC" => {
                     event.agency = Some(line.value.to_string());
                 }
+                // _WITN may give just a name, rather than a pointer
+                "ASSO" | "_ASSO" | "_SHAR" | "_WITN" if is_pointer(line.value) => {
+                    event.associations.push(Association::parse(record).unwrap());
+                    parse = false;
+                }
                 "CAUS" => {
                     event.cause = Some(line.value.to_string());
                 }
                 "DATE" => {
                     event.date = Some(line.value.to_string());
                 }
+                "EMAIL" => {
+                    event.email.push(line.value.to_string());
+                }
+                "FAX" => {
+                    event.fax.push(line.value.to_string());
+                }
                 "NOTE" => {
                     event.note = parse::get_tag_value(record).unwrap();
                     parse = false;
                 }
                 "OBJE" => {
-                    let obj = Object {
-                        xref: Some(line.value.to_string()),
-                    };
-                    event.media.push(obj);
+                    event.media.push(Object::parse(record).unwrap());
+                    parse = false;
+                }
+                "PHON" => {
+                    event.phone.push(line.value.to_string());
                 }
                 "PLAC" => {
                     event.place = Some(Place::parse(record).unwrap());
@@ -104,6 +165,9 @@ impl EventDetail {
                 "RELI" => {
                     event.religion = Some(line.value.to_string());
                 }
+                "RESN" => {
+                    event.restriction_notice = Some(line.value.to_string());
+                }
                 "SOUR" => {
                     let sc = SourceCitation::parse(record).unwrap();
                     event.sources.push(sc);
@@ -112,6 +176,9 @@ impl EventDetail {
                 "TYPE" => {
                     event.r#type = Some(line.value.to_string());
                 }
+                "WWW" => {
+                    event.www.push(line.value.to_string());
+                }
                 _ => {
                     // TODO: Need to collect and parse these lines. They seem to
                     // correspond to INDIVIDUAL_ATTRIBUTE_STRUCTURE.
@@ -124,6 +191,9 @@ impl EventDetail {
             if parse {
                 Line::parse(record).unwrap();
             }
+            if record.is_empty() {
+                break;
+            }
 
             line = Line::peek(record).unwrap();
             if line.level < level {
@@ -142,13 +212,16 @@ impl EventDetail {
 // +1 AGE <AGE_AT_EVENT>
 // n <<EVENT_DETAIL>>
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct FamilyEventDetail {
     // Xref of husband
     pub husband: Option<Spouse>,
     // Xref of wife
     pub wife: Option<Spouse>,
     pub detail: Option<EventDetail>,
+    /// Whether the event line was flagged `Y`, i.e. `1 MARR Y`: the event is
+    /// known to have happened, even if nothing else about it is
+    pub occurred: bool,
 }
 
 impl FamilyEventDetail {
@@ -157,27 +230,38 @@ impl FamilyEventDetail {
             husband: None,
             wife: None,
             detail: None,
+            occurred: false,
         };
 
-        // TODO: Check the first line for and see if it's a top-level event tag
+        let mut line = Line::peek(record).unwrap();
+        let mut min_level = line.level;
+
+        // Check if we've received a top-level event tag, which we want to skip over.
+        match line.tag {
+            "ANUL" | "CENS" | "DIV" | "DIVF" | "ENGA" | "EVEN" | "MARB" | "MARC" | "MARL"
+            | "MARR" | "MARS" | "RESI" => {
+                // Consume the current line
+                let _ = Line::parse(record);
+                event.occurred = line.value.trim().eq_ignore_ascii_case("Y");
+                min_level += 1;
+            }
+            _ => {}
+        }
 
         let mut events: Vec<String> = vec![];
 
         while !record.is_empty() {
-            // let mut parse = true;
-            let line = Line::peek(record).unwrap();
+            line = Line::peek(record).unwrap();
+            if line.level < min_level {
+                break;
+            }
+
             match line.tag {
-                "HUSB" => {
-                    let spouse = Spouse::parse(record);
-                    if spouse.is_ok() {
-                        event.husband = Some(spouse.unwrap());
-                    }
+                "HUSB" if line.level == min_level => {
+                    event.husband = Some(Spouse::parse(record)?);
                 }
-                "WIFE" => {
-                    let spouse = Spouse::parse(record);
-                    if spouse.is_ok() {
-                        event.wife = Some(spouse.unwrap());
-                    }
+                "WIFE" if line.level == min_level => {
+                    event.wife = Some(Spouse::parse(record)?);
                 }
                 _ => {
                     // Store the remaining lines to be parsed as EventDetail
@@ -190,6 +274,26 @@ impl FamilyEventDetail {
 
         Ok(event)
     }
+
+    /// The date of the event, if known
+    pub fn date(&self) -> Option<&str> {
+        self.detail.as_ref().and_then(|d| d.date.as_deref())
+    }
+
+    /// The place of the event, if known
+    pub fn place(&self) -> Option<&Place> {
+        self.detail.as_ref().and_then(|d| d.place.as_ref())
+    }
+
+    /// The age of the husband at the time of the event
+    pub fn husband_age(&self) -> Option<&str> {
+        self.husband.as_ref().and_then(|h| h.age.as_deref())
+    }
+
+    /// The age of the wife at the time of the event
+    pub fn wife_age(&self) -> Option<&str> {
+        self.wife.as_ref().and_then(|w| w.age.as_deref())
+    }
 }
 
 // +1 EVEN <EVENT_TYPE_CITED_FROM>
@@ -198,7 +302,7 @@ impl FamilyEventDetail {
 // "3 EVEN BIRT",
 // "4 ROLE CHIL",
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct EventTypeCitedFrom {
     pub r#type: Option<String>,
     pub role: Option<String>,
@@ -284,6 +388,37 @@ mod tests {
         // assert!(detail.age.is_some());
     }
 
+    #[test]
+    fn parse_event_contacts() {
+        let data = [
+            "1 EVEN",
+            "2 TYPE Reunion",
+            "2 PHON +1-800-555-0000",
+            "2 ADDR",
+            "3 CITY City Name",
+            "2 PHON +1-800-555-5555",
+            "2 EMAIL a@example.com",
+            "2 FAX +1-800-555-1414",
+            "2 WWW https://www.example.com",
+            "2 CAUS Family",
+        ]
+        .join("\n");
+        let mut record = data.as_str();
+        let detail = EventDetail::parse(&mut record).unwrap();
+
+        assert!(detail.phone == ["+1-800-555-0000", "+1-800-555-5555"]);
+        assert!(detail.email == ["a@example.com"]);
+        assert!(detail.fax == ["+1-800-555-1414"]);
+        assert!(detail.www == ["https://www.example.com"]);
+        assert!(detail.cause == Some("Family".to_string()));
+
+        // Contacts following the ADDR are part of the address structure, too
+        let address = detail.address.unwrap();
+        assert!(address.city == Some("City Name".to_string()));
+        assert!(address.phone == ["+1-800-555-5555"]);
+        assert!(address.email == ["a@example.com"]);
+    }
+
     #[test]
     fn parse_family_event_detail() {
         let data = vec![
@@ -336,7 +471,7 @@ mod tests {
 
     #[test]
     fn parse_event_type_cited_from() {
-        let data = vec!["3 EVEN BIRT", "4 ROLE CHIL"];
+        let data = ["3 EVEN BIRT", "4 ROLE CHIL"];
 
         let input = data.join("\n");
         let mut record = input.as_str();
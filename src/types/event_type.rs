@@ -0,0 +1,286 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// The kind of an individual or family event or attribute, whichever field
+/// it's stored in
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EventType {
+    // Individual events
+    Birth,
+    Adoption,
+    Christening,
+    AdultChristening,
+    Baptism,
+    BarMitzvah,
+    BasMitzvah,
+    Blessing,
+    Confirmation,
+    FirstCommunion,
+    Emigration,
+    Immigration,
+    Naturalization,
+    Graduation,
+    Retirement,
+    Will,
+    Probate,
+    Death,
+    Burial,
+    Cremation,
+
+    // Individual attributes
+    Occupation,
+    /// Any other individual attribute
+    Attribute(AttributeType),
+
+    // Family events
+    Engagement,
+    MarriageBanns,
+    MarriageContract,
+    MarriageLicense,
+    MarriageSettlement,
+    Marriage,
+    DivorceFiled,
+    Divorce,
+    Annulment,
+
+    // Either
+    Census,
+    Residence,
+    /// EVEN: any other event
+    Event,
+}
+
+impl EventType {
+    /// The GEDCOM tag, i.e. "BIRT"
+    pub fn tag(&self) -> &'static str {
+        match self {
+            EventType::Birth => "BIRT",
+            EventType::Adoption => "ADOP",
+            EventType::Christening => "CHR",
+            EventType::AdultChristening => "CHRA",
+            EventType::Baptism => "BAPM",
+            EventType::BarMitzvah => "BARM",
+            EventType::BasMitzvah => "BASM",
+            EventType::Blessing => "BLES",
+            EventType::Confirmation => "CONF",
+            EventType::FirstCommunion => "FCOM",
+            EventType::Emigration => "EMIG",
+            EventType::Immigration => "IMMI",
+            EventType::Naturalization => "NATU",
+            EventType::Graduation => "GRAD",
+            EventType::Retirement => "RETI",
+            EventType::Will => "WILL",
+            EventType::Probate => "PROB",
+            EventType::Death => "DEAT",
+            EventType::Burial => "BURI",
+            EventType::Cremation => "CREM",
+            EventType::Occupation => "OCCU",
+            EventType::Engagement => "ENGA",
+            EventType::MarriageBanns => "MARB",
+            EventType::MarriageContract => "MARC",
+            EventType::MarriageLicense => "MARL",
+            EventType::MarriageSettlement => "MARS",
+            EventType::Marriage => "MARR",
+            EventType::DivorceFiled => "DIVF",
+            EventType::Divorce => "DIV",
+            EventType::Annulment => "ANUL",
+            EventType::Census => "CENS",
+            EventType::Residence => "RESI",
+            EventType::Event => "EVEN",
+            EventType::Attribute(attribute) => attribute.tag(),
+        }
+    }
+}
+
+impl FromStr for EventType {
+    type Err = ();
+
+    /// Parse an event's GEDCOM tag, i.e. "BIRT"
+    fn from_str(input: &str) -> Result<EventType, Self::Err> {
+        match input {
+            "BIRT" => Ok(EventType::Birth),
+            "ADOP" => Ok(EventType::Adoption),
+            "CHR" => Ok(EventType::Christening),
+            "CHRA" => Ok(EventType::AdultChristening),
+            "BAPM" => Ok(EventType::Baptism),
+            "BARM" => Ok(EventType::BarMitzvah),
+            "BASM" => Ok(EventType::BasMitzvah),
+            "BLES" => Ok(EventType::Blessing),
+            "CONF" => Ok(EventType::Confirmation),
+            "FCOM" => Ok(EventType::FirstCommunion),
+            "EMIG" => Ok(EventType::Emigration),
+            "IMMI" => Ok(EventType::Immigration),
+            "NATU" => Ok(EventType::Naturalization),
+            "GRAD" => Ok(EventType::Graduation),
+            "RETI" => Ok(EventType::Retirement),
+            "WILL" => Ok(EventType::Will),
+            "PROB" => Ok(EventType::Probate),
+            "DEAT" => Ok(EventType::Death),
+            "BURI" => Ok(EventType::Burial),
+            "CREM" => Ok(EventType::Cremation),
+            "OCCU" => Ok(EventType::Occupation),
+            "ENGA" => Ok(EventType::Engagement),
+            "MARB" => Ok(EventType::MarriageBanns),
+            "MARC" => Ok(EventType::MarriageContract),
+            "MARL" => Ok(EventType::MarriageLicense),
+            "MARS" => Ok(EventType::MarriageSettlement),
+            "MARR" => Ok(EventType::Marriage),
+            "DIVF" => Ok(EventType::DivorceFiled),
+            "DIV" => Ok(EventType::Divorce),
+            "ANUL" => Ok(EventType::Annulment),
+            "CENS" => Ok(EventType::Census),
+            "RESI" => Ok(EventType::Residence),
+            "EVEN" => Ok(EventType::Event),
+            tag => AttributeType::from_str(tag).map(EventType::Attribute),
+        }
+    }
+}
+
+impl fmt::Display for EventType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            EventType::Birth => "Birth",
+            EventType::Adoption => "Adoption",
+            EventType::Christening => "Christening",
+            EventType::AdultChristening => "Adult christening",
+            EventType::Baptism => "Baptism",
+            EventType::BarMitzvah => "Bar mitzvah",
+            EventType::BasMitzvah => "Bas mitzvah",
+            EventType::Blessing => "Blessing",
+            EventType::Confirmation => "Confirmation",
+            EventType::FirstCommunion => "First communion",
+            EventType::Emigration => "Emigration",
+            EventType::Immigration => "Immigration",
+            EventType::Naturalization => "Naturalization",
+            EventType::Graduation => "Graduation",
+            EventType::Retirement => "Retirement",
+            EventType::Will => "Will",
+            EventType::Probate => "Probate",
+            EventType::Death => "Death",
+            EventType::Burial => "Burial",
+            EventType::Cremation => "Cremation",
+            EventType::Occupation => "Occupation",
+            EventType::Engagement => "Engagement",
+            EventType::MarriageBanns => "Marriage banns",
+            EventType::MarriageContract => "Marriage contract",
+            EventType::MarriageLicense => "Marriage license",
+            EventType::MarriageSettlement => "Marriage settlement",
+            EventType::Marriage => "Marriage",
+            EventType::DivorceFiled => "Divorce filed",
+            EventType::Divorce => "Divorce",
+            EventType::Annulment => "Annulment",
+            EventType::Census => "Census",
+            EventType::Residence => "Residence",
+            EventType::Event => "Event",
+            EventType::Attribute(attribute) => return attribute.fmt(f),
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A fact about an individual, rather than something that happened to
+/// them, i.e. their education or a title. Occupations and residences have
+/// an `EventType` of their own.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum AttributeType {
+    /// CAST: Caste
+    Caste,
+    /// DSCR: Physical description
+    Description,
+    /// EDUC: Education
+    Education,
+    /// IDNO: National ID number
+    IdNumber,
+    /// NATI: Nationality or tribal origin
+    Nationality,
+    /// NCHI: Number of children
+    ChildCount,
+    /// NMR: Number of marriages
+    MarriageCount,
+    /// PROP: Property or possessions
+    Property,
+    /// RELI: Religion
+    Religion,
+    /// TITL: Nobility or other title
+    Title,
+}
+
+impl AttributeType {
+    /// The GEDCOM tag, i.e. "EDUC"
+    pub fn tag(&self) -> &'static str {
+        match self {
+            AttributeType::Caste => "CAST",
+            AttributeType::Description => "DSCR",
+            AttributeType::Education => "EDUC",
+            AttributeType::IdNumber => "IDNO",
+            AttributeType::Nationality => "NATI",
+            AttributeType::ChildCount => "NCHI",
+            AttributeType::MarriageCount => "NMR",
+            AttributeType::Property => "PROP",
+            AttributeType::Religion => "RELI",
+            AttributeType::Title => "TITL",
+        }
+    }
+}
+
+impl FromStr for AttributeType {
+    type Err = ();
+
+    /// Parse an attribute's GEDCOM tag, i.e. "EDUC"
+    fn from_str(input: &str) -> Result<AttributeType, Self::Err> {
+        match input {
+            "CAST" => Ok(AttributeType::Caste),
+            "DSCR" => Ok(AttributeType::Description),
+            "EDUC" => Ok(AttributeType::Education),
+            "IDNO" => Ok(AttributeType::IdNumber),
+            "NATI" => Ok(AttributeType::Nationality),
+            "NCHI" => Ok(AttributeType::ChildCount),
+            "NMR" => Ok(AttributeType::MarriageCount),
+            "PROP" => Ok(AttributeType::Property),
+            "RELI" => Ok(AttributeType::Religion),
+            "TITL" => Ok(AttributeType::Title),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for AttributeType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            AttributeType::Caste => "Caste",
+            AttributeType::Description => "Physical description",
+            AttributeType::Education => "Education",
+            AttributeType::IdNumber => "ID number",
+            AttributeType::Nationality => "Nationality",
+            AttributeType::ChildCount => "Number of children",
+            AttributeType::MarriageCount => "Number of marriages",
+            AttributeType::Property => "Property",
+            AttributeType::Religion => "Religion",
+            AttributeType::Title => "Title",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AttributeType, EventType};
+    use std::str::FromStr;
+
+    #[test]
+    fn parse_event_type() {
+        assert!(EventType::from_str("BIRT") == Ok(EventType::Birth));
+        assert!(EventType::from_str("MARR") == Ok(EventType::Marriage));
+        assert!(EventType::from_str("NAME").is_err());
+        assert!(EventType::from_str(EventType::Census.tag()) == Ok(EventType::Census));
+        assert!(EventType::AdultChristening.to_string() == "Adult christening");
+
+        let title = EventType::Attribute(AttributeType::Title);
+        assert!(EventType::from_str("TITL") == Ok(title));
+        assert!(title.tag() == "TITL" && title.to_string() == "Title");
+        assert!(
+            EventType::from_str("NMR") == Ok(EventType::Attribute(AttributeType::MarriageCount))
+        );
+        assert!(AttributeType::from_str("OCCU").is_err());
+    }
+}
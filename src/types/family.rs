@@ -1,11 +1,16 @@
+use std::fmt;
 use std::str::FromStr;
 
 use crate::{
     parse,
-    types::{AdoptedBy, Line, Note, Pedigree, Spouse},
+    relationship::ordinal,
+    renumber::is_pointer,
+    types::{
+        AdoptedBy, DateTime, EventDetail, FamilyEventDetail, Gedcom, GedcomDate, Individual, Line,
+        Note, Object, Pedigree, Restriction, SourceCitation, Spouse,
+    },
 };
 
-// TODO: implement full parsing of the family record
 // TODO: Need to create a trait? to find_by_xref that can be used in these
 // types of structs, to find the type of object in a vec of the types.
 
@@ -28,17 +33,85 @@ use crate::{
 // +1 <<SOURCE_CITATION>> {0:M} p.39
 // +1 <<MULTIMEDIA_LINK>> {0:M} p.37, 26
 
-#[derive(Debug, Clone, PartialEq)]
+// FAMILY_EVENT_STRUCTURE:=
+// n [ ANUL | CENS | DIV | DIVF ] {1:1}
+// +1 <<FAMILY_EVENT_DETAIL>> {0:1} p.32
+// n [ ENGA | MARB | MARC ] {1:1}
+// +1 <<FAMILY_EVENT_DETAIL>> {0:1} p.32
+// n MARR [Y|<NULL>] {1:1}
+// +1 <<FAMILY_EVENT_DETAIL>> {0:1} p.32
+// n [ MARL | MARS ] {1:1}
+// +1 <<FAMILY_EVENT_DETAIL>> {0:1} p.32
+// n RESI
+// +1 <<FAMILY_EVENT_DETAIL>> {0:1} p.32
+// n EVEN [<EVENT_DESCRIPTOR> | <NULL>] {1:1}
+// +1 <<FAMILY_EVENT_DETAIL>> {0:1} p.32
+
+#[derive(Debug, Default, Clone, PartialEq)]
 /// The Family structure
+///
+/// This is used both for the FAM record and for the FAMC/FAMS links from an
+/// individual to a family.
 pub struct Family {
     pub adopted_by: Option<AdoptedBy>,
 
     pub husband: Option<Spouse>,
     pub wife: Option<Spouse>,
 
+    /// The xrefs of the children of this family, in the order they're
+    /// listed, which is supposed to be birth order but often isn't. See
+    /// `children_sorted_by_birth`.
+    pub children: Vec<String>,
+
+    /// NCHI: The reported number of children, which may differ from the
+    /// number of CHIL records.
+    pub number_of_children: Option<u32>,
+
+    /// ANUL: Declaring a marriage void from the beginning (never existed).
+    pub annulment: Vec<FamilyEventDetail>,
+    /// CENS: The event of the periodic count of the population.
+    pub census: Vec<FamilyEventDetail>,
+    /// DIV: An event of dissolving a marriage through civil action.
+    pub divorce: Vec<FamilyEventDetail>,
+    /// DIVF: An event of filing for a divorce by a spouse.
+    pub divorce_filed: Vec<FamilyEventDetail>,
+    /// ENGA: An event of recording or announcing an agreement between two
+    /// people to become married.
+    pub engagement: Vec<FamilyEventDetail>,
+    /// MARR: A legal, common-law, or customary event of creating a family unit.
+    pub marriage: Vec<FamilyEventDetail>,
+    /// MARB: An event of an official public notice given that two people
+    /// intend to marry.
+    pub marriage_banns: Vec<FamilyEventDetail>,
+    /// MARC: An event of recording a formal agreement of marriage.
+    pub marriage_contract: Vec<FamilyEventDetail>,
+    /// MARL: An event of obtaining a legal license to marry.
+    pub marriage_license: Vec<FamilyEventDetail>,
+    /// MARS: An event of creating an agreement between two people
+    /// contemplating marriage.
+    pub marriage_settlement: Vec<FamilyEventDetail>,
+    /// RESI: The act of dwelling at an address for a period of time.
+    pub residences: Vec<FamilyEventDetail>,
+    /// EVEN: Generic events not covered by a specific type
+    pub events: Vec<FamilyEventDetail>,
+
     pub xref: String,
+    /// RESN: Who may see or change the record. See `restrictions`.
+    pub restriction_notice: Option<String>,
     pub notes: Vec<Note>,
     pub pedigree: Option<Pedigree>,
+    pub sources: Vec<SourceCitation>,
+    pub media: Vec<Object>,
+    /// SUBM: The submitters who contributed the record. See
+    /// `Gedcom::records_submitted_by`.
+    pub submitters: Vec<String>,
+
+    /// CHAN: When the record was last changed
+    pub change_date: Option<DateTime>,
+
+    /// UID or _UID: Identifiers that stay with the record from program to
+    /// program, unlike its xref
+    pub uids: Vec<String>,
 }
 
 impl Family {
@@ -47,9 +120,29 @@ impl Family {
             adopted_by: None,
             husband: None,
             wife: None,
+            children: vec![],
+            number_of_children: None,
+            annulment: vec![],
+            census: vec![],
+            divorce: vec![],
+            divorce_filed: vec![],
+            engagement: vec![],
+            marriage: vec![],
+            marriage_banns: vec![],
+            marriage_contract: vec![],
+            marriage_license: vec![],
+            marriage_settlement: vec![],
+            residences: vec![],
+            events: vec![],
             xref: "".to_string(),
+            restriction_notice: None,
             notes: vec![],
             pedigree: None,
+            sources: vec![],
+            media: vec![],
+            submitters: vec![],
+            change_date: None,
+            uids: vec![],
         };
 
         let line = Line::peek(record).unwrap();
@@ -61,6 +154,10 @@ impl Family {
             // Capture the xref
             family.xref = line.value.to_string();
             Line::parse(record).unwrap();
+        } else if tag == "FAM" {
+            // The FAM record itself
+            family.xref = line.xref.to_string();
+            Line::parse(record).unwrap();
         }
 
         while !record.is_empty() {
@@ -73,7 +170,57 @@ impl Family {
                 break;
             }
 
+            // Sub-structures of the current tag are handled by their own parsers
+            if line.level > level + 1 {
+                Line::parse(record).unwrap();
+                continue;
+            }
+
             match line.tag {
+                "HUSB" => {
+                    family.husband = Some(Spouse::parse(record).unwrap());
+                    consume = false;
+                }
+                "WIFE" => {
+                    family.wife = Some(Spouse::parse(record).unwrap());
+                    consume = false;
+                }
+                "CHIL" if is_pointer(line.value) => {
+                    family.children.push(line.value.to_string());
+                }
+                "NCHI" => {
+                    family.number_of_children = line.value.trim().parse().ok();
+                }
+                "SUBM" if is_pointer(line.value) => {
+                    family.submitters.push(line.value.to_string());
+                }
+                "ANUL" | "CENS" | "DIV" | "DIVF" | "ENGA" | "MARB" | "MARC" | "MARR" | "MARL"
+                | "MARS" | "RESI" | "EVEN" => {
+                    let event = FamilyEventDetail::parse(record).unwrap();
+                    match line.tag {
+                        "ANUL" => family.annulment.push(event),
+                        "CENS" => family.census.push(event),
+                        "DIV" => family.divorce.push(event),
+                        "DIVF" => family.divorce_filed.push(event),
+                        "ENGA" => family.engagement.push(event),
+                        "MARB" => family.marriage_banns.push(event),
+                        "MARC" => family.marriage_contract.push(event),
+                        "MARR" => family.marriage.push(event),
+                        "MARL" => family.marriage_license.push(event),
+                        "MARS" => family.marriage_settlement.push(event),
+                        "RESI" => family.residences.push(event),
+                        _ => family.events.push(event),
+                    }
+                    consume = false;
+                }
+                "SOUR" => {
+                    family.sources.push(SourceCitation::parse(record).unwrap());
+                    consume = false;
+                }
+                "OBJE" => {
+                    family.media.push(Object::parse(record).unwrap());
+                    consume = false;
+                }
                 "NOTE" => {
                     if let Some(note) = parse::get_tag_value(record).unwrap() {
                         family.notes.push(Note { note: Some(note) });
@@ -88,6 +235,19 @@ impl Family {
                     let adopted_by = AdoptedBy::from_str(line.value).unwrap();
                     family.adopted_by = Some(adopted_by);
                 }
+                "RESN" => {
+                    family.restriction_notice = Some(line.value.to_string());
+                }
+                "UID" | "_UID" => {
+                    family.uids.push(line.value.to_string());
+                }
+                "CHAN" => {
+                    Line::parse(record).unwrap();
+                    let (buffer, change_date) = DateTime::parse(record);
+                    *record = buffer;
+                    family.change_date = change_date;
+                    consume = false;
+                }
                 _ => {}
             }
 
@@ -98,6 +258,165 @@ impl Family {
 
         family
     }
+
+    /// Every event, by its GEDCOM tag, whichever field it's stored in.
+    pub fn tagged_events(&self) -> Vec<(&'static str, Option<&EventDetail>)> {
+        let details = [
+            ("ENGA", &self.engagement),
+            ("MARB", &self.marriage_banns),
+            ("MARC", &self.marriage_contract),
+            ("MARL", &self.marriage_license),
+            ("MARS", &self.marriage_settlement),
+            ("MARR", &self.marriage),
+            ("RESI", &self.residences),
+            ("CENS", &self.census),
+            ("EVEN", &self.events),
+            ("DIVF", &self.divorce_filed),
+            ("DIV", &self.divorce),
+            ("ANUL", &self.annulment),
+        ];
+
+        let mut events: Vec<(&'static str, Option<&EventDetail>)> = vec![];
+        for (tag, details) in details {
+            events.extend(details.iter().map(|e| (tag, e.detail.as_ref())));
+        }
+        events
+    }
+
+    /// The restrictions in the record's RESN, i.e. that it's confidential
+    pub fn restrictions(&self) -> Vec<Restriction> {
+        Restriction::parse_list(self.restriction_notice.as_deref().unwrap_or_default())
+    }
+
+    /// The xref of the husband, if there is one.
+    pub fn husband_xref(&self) -> Option<&str> {
+        self.husband
+            .as_ref()
+            .and_then(|h| h.xref.as_ref())
+            .and_then(|x| x.xref.as_deref())
+    }
+
+    /// The xref of the wife, if there is one.
+    pub fn wife_xref(&self) -> Option<&str> {
+        self.wife
+            .as_ref()
+            .and_then(|w| w.xref.as_ref())
+            .and_then(|x| x.xref.as_deref())
+    }
+
+    /// The husband, if there is one and he's in `gedcom`
+    pub fn husband_individual<'a>(&self, gedcom: &'a Gedcom) -> Option<&'a Individual> {
+        gedcom.find_individual(self.husband_xref()?)
+    }
+
+    /// The wife, if there is one and she's in `gedcom`
+    pub fn wife_individual<'a>(&self, gedcom: &'a Gedcom) -> Option<&'a Individual> {
+        gedcom.find_individual(self.wife_xref()?)
+    }
+
+    /// The children, in the order they're listed. Any that aren't in
+    /// `gedcom` are skipped.
+    pub fn children_individuals<'a>(&self, gedcom: &'a Gedcom) -> Vec<&'a Individual> {
+        self.children
+            .iter()
+            .filter_map(|c| gedcom.find_individual(c))
+            .collect()
+    }
+
+    /// The children in order of birth, falling back to christening or
+    /// baptism. Those without a date we can read keep their place in the
+    /// list, and children born on the same date keep their order. Any that
+    /// aren't in `gedcom` are skipped.
+    pub fn children_sorted_by_birth<'a>(&self, gedcom: &'a Gedcom) -> Vec<&'a Individual> {
+        let mut children = self.children_individuals(gedcom);
+        let dated: Vec<(usize, GedcomDate)> = children
+            .iter()
+            .enumerate()
+            .filter_map(|(i, c)| Some((i, birth_date(c)?)))
+            .collect();
+        let mut sorted: Vec<(&GedcomDate, &Individual)> =
+            dated.iter().map(|(i, date)| (date, children[*i])).collect();
+        sorted.sort_by(|a, b| a.0.cmp(b.0));
+        // Put the dated children, in order, back where the dated ones were
+        let sorted: Vec<&Individual> = sorted.into_iter().map(|(_, c)| c).collect();
+        for ((i, _), child) in dated.iter().zip(sorted) {
+            children[*i] = child;
+        }
+        children
+    }
+
+    /// Where `xref` comes in the family, by birth, i.e. "3rd of 7 children".
+    /// None if they aren't one of its children in `gedcom`. See
+    /// `children_sorted_by_birth`.
+    pub fn birth_order(&self, gedcom: &Gedcom, xref: &str) -> Option<BirthOrder> {
+        let children = self.children_sorted_by_birth(gedcom);
+        let position = children
+            .iter()
+            .position(|c| c.xref.as_deref() == Some(xref))?;
+        let listed = self
+            .children_individuals(gedcom)
+            .iter()
+            .position(|c| c.xref.as_deref() == Some(xref))?;
+        Some(BirthOrder {
+            family: self.xref.clone(),
+            position: position + 1,
+            listed: listed + 1,
+            of: children.len(),
+        })
+    }
+
+    /// The first marriage (MARR) event, if there is one
+    pub fn marriage_event(&self) -> Option<&FamilyEventDetail> {
+        self.marriage.first()
+    }
+
+    /// The date of the first marriage, if known
+    pub fn marriage_date(&self) -> Option<&str> {
+        self.marriage_event().and_then(|m| m.date())
+    }
+}
+
+/// Where a child comes among their siblings. See `Family::birth_order`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BirthOrder {
+    /// The family's xref
+    pub family: String,
+    /// By birth, from 1
+    pub position: usize,
+    /// By where their CHIL is in the family, from 1. If it isn't
+    /// `position`, the children are listed out of order.
+    pub listed: usize,
+    /// How many children there are
+    pub of: usize,
+}
+
+impl fmt::Display for BirthOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let children = if self.of == 1 { "child" } else { "children" };
+        write!(f, "{} of {} {}", ordinal(self.position), self.of, children)
+    }
+}
+
+/// The date of the first dated birth, christening or baptism
+fn birth_date(individual: &Individual) -> Option<GedcomDate> {
+    individual
+        .birth
+        .iter()
+        .filter_map(|b| b.event.detail.date.as_deref())
+        .chain(
+            individual
+                .christening
+                .iter()
+                .filter_map(|c| c.event.detail.date.as_deref()),
+        )
+        .chain(
+            individual
+                .baptism
+                .iter()
+                .filter_map(|b| b.detail.date.as_deref()),
+        )
+        .filter_map(|d| GedcomDate::from_str(d).ok())
+        .find(|d| d.start.or(d.end).is_some())
 }
 
 #[cfg(test)]
@@ -148,4 +467,119 @@ mod tests {
                 == "Note about the link to his adoptive parents family record."
         );
     }
+
+    #[test]
+    fn parse_family_record() {
+        let data = [
+            "0 @F1@ FAM",
+            "1 HUSB @I1@",
+            "1 WIFE @I2@",
+            "1 MARR",
+            "2 DATE 31 DEC 1997",
+            "2 PLAC The place",
+            "2 HUSB",
+            "3 AGE 42y",
+            "2 WIFE",
+            "3 AGE 42y 6m",
+            "1 CHIL @I3@",
+            "1 CHIL @I4@",
+            "1 NCHI 2",
+            "1 DIV Y",
+            "1 CHAN",
+            "2 DATE 11 JAN 2001",
+            "3 TIME 16:18:40",
+        ]
+        .join("\n");
+        let mut record = data.as_str();
+
+        let family = Family::parse(&mut record);
+        assert!(family.xref == "@F1@");
+        assert!(family.marriage_date() == Some("31 DEC 1997"));
+        assert!(family.husband_xref() == Some("@I1@"));
+        assert!(family.wife_xref() == Some("@I2@"));
+        assert!(family.children.len() == 2);
+        assert!(family.number_of_children == Some(2));
+
+        let marriage = family.marriage_event().unwrap();
+        assert!(marriage.place().unwrap().name == Some("The place".to_string()));
+        assert!(marriage.husband_age() == Some("42y"));
+        assert!(marriage.wife_age() == Some("42y 6m"));
+
+        assert!(family.divorce.len() == 1);
+        assert!(family.divorce[0].date().is_none());
+        assert!(record.is_empty());
+    }
+
+    #[test]
+    fn navigate() {
+        let data = "0 HEAD
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+0 @I2@ INDI
+1 NAME Ann /Bloggs/
+0 @I3@ INDI
+1 NAME Sam /Bloggs/
+0 @F1@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 CHIL @I3@
+1 CHIL @I9@
+0 @F2@ FAM
+1 WIFE @I9@
+0 TRLR
+";
+        let gedcom = crate::parse::parse_gedcom_from_str(data).unwrap();
+        let family = &gedcom.families[0];
+        assert!(family.husband_individual(&gedcom).unwrap().xref == Some("@I1@".into()));
+        assert!(family.wife_individual(&gedcom).unwrap().xref == Some("@I2@".into()));
+        let children = family.children_individuals(&gedcom);
+        assert!(children.len() == 1 && children[0].xref == Some("@I3@".into()));
+        assert!(family.marriage_event().is_none() && family.marriage_date().is_none());
+
+        let family = &gedcom.families[1];
+        assert!(family.husband_individual(&gedcom).is_none());
+        assert!(family.wife_individual(&gedcom).is_none());
+    }
+
+    #[test]
+    fn birth_order() {
+        let data = "0 HEAD
+0 @I1@ INDI
+1 BIRT
+2 DATE 1855
+1 FAMC @F1@
+0 @I2@ INDI
+1 FAMC @F1@
+0 @I3@ INDI
+1 CHR
+2 DATE 3 MAR 1850
+1 FAMC @F1@
+0 @I4@ INDI
+1 BIRT
+2 DATE ABT 1852
+1 FAMC @F1@
+0 @F1@ FAM
+1 CHIL @I1@
+1 CHIL @I2@
+1 CHIL @I3@
+1 CHIL @I4@
+0 TRLR
+";
+        let gedcom = crate::parse::parse_gedcom_from_str(data).unwrap();
+        let family = &gedcom.families[0];
+        let sorted: Vec<&str> = family
+            .children_sorted_by_birth(&gedcom)
+            .iter()
+            .filter_map(|c| c.xref.as_deref())
+            .collect();
+        // The undated @I2@ stays second
+        assert!(sorted == ["@I3@", "@I2@", "@I4@", "@I1@"]);
+
+        let order = family.birth_order(&gedcom, "@I1@").unwrap();
+        assert!(order.position == 4 && order.listed == 1 && order.of == 4);
+        assert!(order.to_string() == "4th of 4 children");
+        let first = gedcom.find_individual("@I3@").unwrap();
+        assert!(gedcom.birth_order(first).unwrap().to_string() == "1st of 4 children");
+        assert!(family.birth_order(&gedcom, "@I9@").is_none());
+    }
 }
@@ -1,7 +1,43 @@
+use std::fmt;
+use std::str::FromStr;
+
 use super::Line;
 // use crate::parse;
 
-#[derive(Debug, Default, PartialEq)]
+/// A version of the GEDCOM specification that can be written
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum GedcomVersion {
+    V55,
+    V551,
+    V70,
+}
+
+impl FromStr for GedcomVersion {
+    type Err = ();
+
+    /// Parse a GEDC VERS, i.e. "5.5.1". Any 7.x is 7.0.
+    fn from_str(input: &str) -> Result<GedcomVersion, Self::Err> {
+        match input.trim() {
+            "5.5" => Ok(GedcomVersion::V55),
+            "5.5.1" => Ok(GedcomVersion::V551),
+            v if v == "7" || v.starts_with("7.") => Ok(GedcomVersion::V70),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for GedcomVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let version = match self {
+            GedcomVersion::V55 => "5.5",
+            GedcomVersion::V551 => "5.5.1",
+            GedcomVersion::V70 => "7.0",
+        };
+        write!(f, "{}", version)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
 // pub enum Form {
 //     LineageLinked,
 //     EventLineageLinked,
@@ -48,7 +84,7 @@ impl Form {
     }
 }
 
-#[derive(Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct Gedc {
     /// The version of this Gedcom file.
     pub version: Option<String>,
@@ -95,7 +131,17 @@ impl Gedc {
 
 #[cfg(test)]
 mod tests {
-    use super::Gedc;
+    use super::{Gedc, GedcomVersion};
+    use std::str::FromStr;
+
+    #[test]
+    fn parse_version() {
+        assert!(GedcomVersion::from_str("5.5.1") == Ok(GedcomVersion::V551));
+        assert!(GedcomVersion::from_str("7.0.14") == Ok(GedcomVersion::V70));
+        assert!(GedcomVersion::from_str("5.5.5").is_err());
+        assert!(GedcomVersion::V55 < GedcomVersion::V70);
+        assert!(GedcomVersion::V70.to_string() == "7.0");
+    }
 
     #[test]
     fn parse() {
@@ -0,0 +1,1633 @@
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use crate::access::{self, AccessLevel};
+use crate::analysis::{self, ResearchTask, SurnamePlace};
+use crate::changes::{self, RecordChange};
+use crate::chart::{self, Numbering};
+use crate::citations::{self, CitationCoverage, SourceReference};
+use crate::config::GedcomConfig;
+use crate::error::{self, GedcomError, WarningGroup};
+use crate::export::svg::{self, SvgOptions};
+use crate::export::{self, CsvOptions};
+use crate::graph::{self, FamilyGraph, GraphFormat, Island};
+use crate::marriage::{self, Marriage};
+use crate::media::{self, MediaItem};
+use crate::parse::parse_gedcom_from_reader_with_config;
+use crate::provenance::{self, SubmittedRecord, SubmitterLink};
+use crate::query::{self, Filter};
+use crate::relationship::{
+    self, Associate, BrickWall, ExtendedFamily, Identities, Kinship, Lineage, OwnedRelationship,
+    ParentFamily, Parentage, PathOptions, Relationship, WitnessedEvent,
+};
+use crate::renumber::{self, XrefScheme};
+use crate::repair::{self, Repair, RepairOptions};
+use crate::report::{self, ReportFormat};
+use crate::sample;
+use crate::search::{
+    self, rank, score_place, SearchQuery, SearchResult, SortKey, SortOrder, EXACT_NAME_SCORE,
+};
+use crate::statistics::GedcomStats;
+use crate::summary::{Summary, SummaryComparison};
+use crate::timeline::{self, Anniversary, TimelineEntry};
+use crate::validate::{self, Finding, OrphanedRecord};
+
+use super::{
+    date_year, BirthOrder, EventType, Family, Gedcom, GedcomDate, Individual, IndividualEvent,
+    MultimediaRecord, NoteRecord, Place, RepositoryHolding, RepositoryRecord, SimpleDate,
+    SourceMedia, SourceRecord, Submitter, Timestamp,
+};
+
+impl Gedcom {
+    /// When the file was created (transmitted), from the header's DATE and TIME
+    pub fn file_date(&self) -> Option<Timestamp> {
+        self.header.date.as_ref().and_then(|d| d.timestamp())
+    }
+
+    /// Surname and given name frequencies, lifespans, and births and
+    /// marriages per decade
+    pub fn statistics(&self) -> GedcomStats {
+        GedcomStats::new(self)
+    }
+
+    /// How many events of a kind, e.g. births, happened in each year. See
+    /// `analysis::events_per_year`.
+    pub fn events_per_year(&self, kind: EventType) -> BTreeMap<i32, usize> {
+        analysis::events_per_year(self, kind)
+    }
+
+    /// Which events cite a source, and how credible that source is: the
+    /// share of births, deaths, and marriages that are sourced, and the
+    /// individuals with nothing sourced at all
+    pub fn citation_coverage(&self) -> CitationCoverage<'_> {
+        CitationCoverage::new(self)
+    }
+
+    /// Every citation of the SOUR record `xref`, with the record, and event,
+    /// it's on, and its PAGE and QUAY. See `citations::records_citing_source`.
+    pub fn records_citing_source(&self, xref: &str) -> Vec<SourceReference<'_>> {
+        citations::records_citing_source(self, xref)
+    }
+
+    /// Count the records, tags, and dated and sourced events in this file
+    pub fn summary(&self) -> Summary {
+        Summary::new(self)
+    }
+
+    /// Compare the counts of what's in this file against `other`, i.e. to
+    /// check that exporting and re-importing a tree didn't lose anything.
+    pub fn compare_summary(&self, other: &Gedcom) -> SummaryComparison {
+        SummaryComparison {
+            before: self.summary(),
+            after: other.summary(),
+        }
+    }
+
+    /// Find an individual by their xref, i.e. "@I1@"
+    pub fn find_individual(&self, xref: &str) -> Option<&Individual> {
+        self.individuals
+            .iter()
+            .find(|i| i.xref.as_deref() == Some(xref))
+    }
+
+    /// Find an individual by one of their UIDs, which, unlike an xref,
+    /// stays the same when the tree is exported from one program to
+    /// another. UIDs are compared ignoring case, hyphens and braces, and
+    /// the checksum some programs add to the end of a _UID.
+    pub fn find_individual_by_uid(&self, uid: &str) -> Option<&Individual> {
+        let uid = normalize_uid(uid);
+        self.individuals
+            .iter()
+            .find(|i| i.uids.iter().any(|u| normalize_uid(u) == uid))
+    }
+
+    /// Find a family by one of its UIDs. See `find_individual_by_uid`.
+    pub fn find_family_by_uid(&self, uid: &str) -> Option<&Family> {
+        let uid = normalize_uid(uid);
+        self.families
+            .iter()
+            .find(|f| f.uids.iter().any(|u| normalize_uid(u) == uid))
+    }
+
+    /// The individual the tree is centred on, if one has been chosen
+    pub fn home_person(&self) -> Option<&Individual> {
+        self.find_individual(self.header.home_person.as_deref()?)
+    }
+
+    /// Centre the tree on an individual, by xref. This is kept in the
+    /// header's `_HOME` tag, so it survives `write_gedcom` and re-parsing.
+    pub fn set_home_person(&mut self, xref: &str) -> Result<(), GedcomError> {
+        if self.find_individual(xref).is_none() {
+            return Err(GedcomError::MissingData {
+                line: None,
+                offset: None,
+                xref: Some(xref.to_string()),
+                message: "No such individual".to_string(),
+            });
+        }
+        self.header.home_person = Some(xref.to_string());
+        Ok(())
+    }
+
+    /// Find a family by its xref, i.e. "@F1@"
+    pub fn find_family(&self, xref: &str) -> Option<&Family> {
+        self.families.iter().find(|f| f.xref == xref)
+    }
+
+    /// Find a source by its xref, i.e. "@S1@"
+    pub fn find_source(&self, xref: &str) -> Option<&SourceRecord> {
+        self.sources.iter().find(|s| s.xref == xref)
+    }
+
+    /// Find a multimedia record by its xref, i.e. "@M1@"
+    pub fn find_multimedia(&self, xref: &str) -> Option<&MultimediaRecord> {
+        self.multimedia.iter().find(|m| m.xref == xref)
+    }
+
+    /// Find a NOTE record by its xref, i.e. "@N1@"
+    pub fn find_note(&self, xref: &str) -> Option<&NoteRecord> {
+        self.notes.iter().find(|n| n.xref == xref)
+    }
+
+    /// Find a REPO record by its xref, i.e. "@R1@"
+    pub fn find_repository(&self, xref: &str) -> Option<&RepositoryRecord> {
+        self.repositories.iter().find(|r| r.xref == xref)
+    }
+
+    /// Find a SUBM record by its xref, i.e. "@U1@"
+    pub fn find_submitter(&self, xref: &str) -> Option<&Submitter> {
+        self.submitters
+            .iter()
+            .find(|s| s.xref.as_deref() == Some(xref))
+    }
+
+    /// The submitter of the file. See `provenance::primary_submitter`.
+    pub fn primary_submitter(&self) -> Option<&Submitter> {
+        provenance::primary_submitter(self)
+    }
+
+    /// The individuals and families the submitter `xref` contributed. See
+    /// `provenance::records_submitted_by`.
+    pub fn records_submitted_by(&self, xref: &str) -> Vec<SubmittedRecord<'_>> {
+        provenance::records_submitted_by(self, xref)
+    }
+
+    /// The submitters linked to `individual`, and how. See
+    /// `provenance::submitters_of`.
+    pub fn submitters_of(&self, individual: &Individual) -> Vec<(SubmitterLink, &Submitter)> {
+        provenance::submitters_of(self, individual)
+    }
+
+    /// Every record changed on or after `since`, the most recently changed
+    /// first. See `changes::records_changed_since`.
+    pub fn records_changed_since(&self, since: &Timestamp) -> Vec<RecordChange<'_>> {
+        changes::records_changed_since(self, since)
+    }
+
+    /// The NOTE records changed on or after `since`, the most recently
+    /// changed first. Notes without a CHAN date we can read are left out.
+    pub fn notes_modified_since(&self, since: &Timestamp) -> Vec<&NoteRecord> {
+        let mut notes: Vec<(Timestamp, &NoteRecord)> = self
+            .notes
+            .iter()
+            .filter_map(|n| Some((n.change_date.as_ref()?.timestamp()?, n)))
+            .filter(|(changed, _)| changed >= since)
+            .collect();
+        notes.sort_by_key(|&(changed, _)| std::cmp::Reverse(changed));
+        notes.into_iter().map(|(_, n)| n).collect()
+    }
+
+    /// Every file the Gedcom refers to, with the records that use it, and
+    /// whether it's on disk, looking for relative paths from the current
+    /// directory. See `media::media_inventory` to look elsewhere.
+    pub fn media_inventory(&self) -> Vec<MediaItem<'_>> {
+        media::media_inventory(self, Path::new(""))
+    }
+
+    /// The sources a repository holds in this medium, i.e. everything on
+    /// microfilm, to plan a research trip around
+    pub fn sources_by_media(&self, media: &SourceMedia) -> Vec<&SourceRecord> {
+        self.sources.iter().filter(|s| s.has_media(media)).collect()
+    }
+
+    /// The sources a repository, by xref, holds, in file order, with their
+    /// call numbers and media: what to pull on a visit there. A source
+    /// citing the repository twice appears twice.
+    pub fn sources_in_repository(&self, xref: &str) -> Vec<RepositoryHolding<'_>> {
+        self.sources
+            .iter()
+            .flat_map(|source| {
+                source
+                    .repositories
+                    .iter()
+                    .filter(|r| r.xref.as_deref() == Some(xref))
+                    .map(move |citation| RepositoryHolding { source, citation })
+            })
+            .collect()
+    }
+
+    /// Find the families in which these two individuals, by xref, are the
+    /// husband and wife. The order doesn't matter, so this also finds
+    /// families where the roles were recorded the other way around.
+    pub fn families_of(&self, first: &str, second: &str) -> Vec<&Family> {
+        self.families
+            .iter()
+            .filter(|f| {
+                let spouses = (f.husband_xref(), f.wife_xref());
+                spouses == (Some(first), Some(second)) || spouses == (Some(second), Some(first))
+            })
+            .collect()
+    }
+
+    /// Find the family joining `husband` and `wife`, by xref. If they were
+    /// married more than once, this is the first family in the file.
+    pub fn family_of(&self, husband: &str, wife: &str) -> Option<&Family> {
+        self.families_of(husband, wife).into_iter().next()
+    }
+
+    /// Every couple: the husband, wife, and their family, in file order.
+    /// Families without both a husband and a wife, or whose spouses aren't
+    /// in the file, are skipped.
+    pub fn couples(&self) -> impl Iterator<Item = (&Individual, &Individual, &Family)> {
+        let individuals: HashMap<&str, &Individual> = self
+            .individuals
+            .iter()
+            .filter_map(|i| Some((i.xref.as_deref()?, i)))
+            .collect();
+
+        self.families.iter().filter_map(move |f| {
+            let husband = individuals.get(f.husband_xref()?)?;
+            let wife = individuals.get(f.wife_xref()?)?;
+            Some((*husband, *wife, f))
+        })
+    }
+
+    /// The families `individual` is a child of, from either side of the link
+    pub(crate) fn child_families(&self, individual: &Individual) -> Vec<&Family> {
+        let xref = individual.xref.as_deref();
+        self.families
+            .iter()
+            .filter(|f| {
+                individual.famc.iter().any(|c| c.xref == f.xref)
+                    || f.children.iter().any(|c| Some(c.as_str()) == xref)
+            })
+            .collect()
+    }
+
+    /// The families `individual` is a spouse in, from either side of the link
+    pub(crate) fn spouse_families(&self, individual: &Individual) -> Vec<&Family> {
+        let xref = individual.xref.as_deref();
+        self.families
+            .iter()
+            .filter(|f| {
+                individual.fams.iter().any(|s| s.xref == f.xref)
+                    || (xref.is_some() && (f.husband_xref() == xref || f.wife_xref() == xref))
+            })
+            .collect()
+    }
+
+    /// The parents of `individual`, from every family they're a child of
+    pub fn parents(&self, individual: &Individual) -> Vec<&Individual> {
+        let mut parents: Vec<&Individual> = vec![];
+        for family in self.child_families(individual) {
+            for xref in family.husband_xref().into_iter().chain(family.wife_xref()) {
+                if let Some(parent) = self.find_individual(xref) {
+                    if !parents.iter().any(|p| p.xref == parent.xref) {
+                        parents.push(parent);
+                    }
+                }
+            }
+        }
+        parents
+    }
+
+    /// The children of `individual`, from every family they're a spouse in
+    pub fn children(&self, individual: &Individual) -> Vec<&Individual> {
+        let mut children: Vec<&Individual> = vec![];
+        for family in self.spouse_families(individual) {
+            let linked = self
+                .individuals
+                .iter()
+                .filter(|i| i.famc.iter().any(|c| c.xref == family.xref));
+            let listed = family
+                .children
+                .iter()
+                .filter_map(|c| self.find_individual(c));
+            for child in listed.chain(linked) {
+                if !children.iter().any(|c| c.xref == child.xref) {
+                    children.push(child);
+                }
+            }
+        }
+        children
+    }
+
+    /// The spouses of `individual`, from every family they're a spouse in
+    pub fn spouses(&self, individual: &Individual) -> Vec<&Individual> {
+        let xref = individual.xref.as_deref();
+        let mut spouses: Vec<&Individual> = vec![];
+        for family in self.spouse_families(individual) {
+            for other in family.husband_xref().into_iter().chain(family.wife_xref()) {
+                if Some(other) == xref {
+                    continue;
+                }
+                if let Some(spouse) = self.find_individual(other) {
+                    if !spouses.iter().any(|s| s.xref == spouse.xref) {
+                        spouses.push(spouse);
+                    }
+                }
+            }
+        }
+        spouses
+    }
+
+    /// Every marriage of `individual`, in date order, with the spouse, when
+    /// and where they married, and how it ended. See
+    /// `marriage::marital_history`.
+    pub fn marital_history<'a>(&'a self, individual: &'a Individual) -> Vec<Marriage<'a>> {
+        marriage::marital_history(self, individual)
+    }
+
+    /// Whether `individual` was certainly married on `date`. See
+    /// `Marriage::was_married_at`.
+    pub fn was_married_at(&self, individual: &Individual, date: &SimpleDate) -> bool {
+        marriage::was_married_at(self, individual, date)
+    }
+
+    /// Where `individual` comes among the children of the first family
+    /// they're a child of, by birth, i.e. "3rd of 7 children". See
+    /// `Family::birth_order`.
+    pub fn birth_order(&self, individual: &Individual) -> Option<BirthOrder> {
+        let xref = individual.xref.as_deref()?;
+        self.child_families(individual)
+            .iter()
+            .find_map(|f| f.birth_order(self, xref))
+    }
+
+    /// The siblings and half-siblings of `individual`: the other children
+    /// of every family they're a child of, and of each of their parents
+    pub fn siblings(&self, individual: &Individual) -> Vec<&Individual> {
+        let xref = individual.xref.as_deref();
+        let mut candidates: Vec<&Individual> = vec![];
+        for family in self.child_families(individual) {
+            candidates.extend(
+                family
+                    .children
+                    .iter()
+                    .filter_map(|c| self.find_individual(c)),
+            );
+            candidates.extend(
+                self.individuals
+                    .iter()
+                    .filter(|i| i.famc.iter().any(|c| c.xref == family.xref)),
+            );
+        }
+        for parent in self.parents(individual) {
+            candidates.extend(self.children(parent));
+        }
+
+        let mut siblings: Vec<&Individual> = vec![];
+        for sibling in candidates {
+            if sibling.xref.as_deref() != xref && !siblings.iter().any(|s| s.xref == sibling.xref) {
+                siblings.push(sibling);
+            }
+        }
+        siblings
+    }
+
+    /// The people `individual` lists as associates (ASSO), i.e. godparents
+    /// and witnesses, with what each was to them
+    pub fn get_associates<'a>(&'a self, individual: &'a Individual) -> Vec<Associate<'a>> {
+        relationship::associates(self, individual)
+    }
+
+    /// The people who list `individual` as an associate, i.e. the
+    /// godchildren of a godparent
+    pub fn get_associated_by(&self, individual: &Individual) -> Vec<Associate<'_>> {
+        relationship::associated_by(self, individual)
+    }
+
+    /// The events of others, and of families, that name `individual` in an
+    /// ASSO, i.e. as a witness to a baptism or marriage, in date order. See
+    /// `relationship::events_witnessed_by`.
+    pub fn events_witnessed_by(&self, individual: &Individual) -> Vec<WitnessedEvent<'_>> {
+        relationship::events_witnessed_by(self, individual)
+    }
+
+    /// The relatives of `individual` grouped into kin classes: grandparents,
+    /// aunts and uncles, first cousins, nieces and nephews, in-laws, and so
+    /// on, for a family view
+    pub fn extended_family(&self, individual: &Individual) -> ExtendedFamily<'_> {
+        relationship::extended_family(self, individual)
+    }
+
+    /// Every ancestor of `individual`, nearest generation first
+    pub fn get_ancestors(&self, individual: &Individual) -> Vec<&Individual> {
+        self.traverse(individual, |i| self.parents(i))
+    }
+
+    /// The ancestors of `individual` up the lines `parentage` follows, i.e.
+    /// only through birth parents, nearest generation first. See
+    /// `relationship::Parentage`.
+    pub fn get_ancestors_with(
+        &self,
+        individual: &Individual,
+        parentage: Parentage,
+    ) -> Vec<&Individual> {
+        self.traverse(individual, |i| {
+            relationship::parents_with(self, i, parentage)
+        })
+    }
+
+    /// Every family `individual` is a child of, with the PEDI of their link
+    /// to it, i.e. birth or adopted, and the parents in it
+    pub fn get_parents_with_pedigree(&self, individual: &Individual) -> Vec<ParentFamily<'_>> {
+        relationship::parents_with_pedigree(self, individual)
+    }
+
+    /// The parents of `individual` that `parentage` follows. See
+    /// `relationship::parents_with`.
+    pub fn parents_with(&self, individual: &Individual, parentage: Parentage) -> Vec<&Individual> {
+        relationship::parents_with(self, individual, parentage)
+    }
+
+    /// Whether `ancestor` is a parent, grandparent, and so on, of
+    /// `descendant`. This uses the precomputed kinship, if it covers them
+    /// both, and otherwise stops looking as soon as `ancestor` is found.
+    pub fn is_ancestor_of(&self, ancestor: &Individual, descendant: &Individual) -> bool {
+        let (ancestor, descendant) = match (ancestor.xref.as_deref(), descendant.xref.as_deref()) {
+            (Some(ancestor), Some(descendant)) => (ancestor, descendant),
+            _ => return false,
+        };
+        if let Some(known) = self
+            .kinship
+            .as_ref()
+            .and_then(|k| k.is_ancestor(ancestor, descendant))
+        {
+            return known;
+        }
+
+        let start = match self.find_individual(descendant) {
+            Some(start) => start,
+            None => return false,
+        };
+        let mut seen: HashSet<&str> = HashSet::from([descendant]);
+        let mut queue: VecDeque<&Individual> = VecDeque::from([start]);
+        while let Some(individual) = queue.pop_front() {
+            for parent in self.parents(individual) {
+                let xref = parent.xref.as_deref().unwrap_or_default();
+                if xref == ancestor {
+                    return true;
+                }
+                if seen.insert(xref) {
+                    queue.push_back(parent);
+                }
+            }
+        }
+        false
+    }
+
+    /// Whether `descendant` is a child, grandchild, and so on, of
+    /// `ancestor`. See `is_ancestor_of`.
+    pub fn is_descendant_of(&self, descendant: &Individual, ancestor: &Individual) -> bool {
+        self.is_ancestor_of(ancestor, descendant)
+    }
+
+    /// Every descendant of `individual`, nearest generation first
+    pub fn get_descendants(&self, individual: &Individual) -> Vec<&Individual> {
+        self.traverse(individual, |i| self.children(i))
+    }
+
+    /// Breadth-first, visiting each individual once
+    fn traverse<'a, F>(&'a self, individual: &Individual, next: F) -> Vec<&'a Individual>
+    where
+        F: Fn(&Individual) -> Vec<&'a Individual>,
+    {
+        let mut found: Vec<&Individual> = vec![];
+        let mut queue = next(individual);
+        while !queue.is_empty() {
+            let mut following = vec![];
+            for i in queue {
+                if i.xref != individual.xref && !found.iter().any(|f| f.xref == i.xref) {
+                    found.push(i);
+                    following.extend(next(i));
+                }
+            }
+            queue = following;
+        }
+        found
+    }
+
+    /// Every ancestor of the individual with this xref, by xref, nearest
+    /// generation first. Unlike `get_ancestors`, the result doesn't borrow
+    /// from the Gedcom.
+    pub fn get_ancestor_xrefs(&self, xref: &str) -> Vec<String> {
+        self.find_individual(xref)
+            .map(|i| relationship::xrefs(&self.get_ancestors(i)))
+            .unwrap_or_default()
+    }
+
+    /// Every descendant of the individual with this xref, by xref, nearest
+    /// generation first. See `get_ancestor_xrefs`.
+    pub fn get_descendant_xrefs(&self, xref: &str) -> Vec<String> {
+        self.find_individual(xref)
+            .map(|i| relationship::xrefs(&self.get_descendants(i)))
+            .unwrap_or_default()
+    }
+
+    /// The father, father's father, and so on, of `individual`, nearest
+    /// first, through birth families only: the line a Y-DNA test follows
+    pub fn patrilineal_ancestors(&self, individual: &Individual) -> Vec<&Individual> {
+        relationship::direct_line(self, individual, Lineage::Paternal)
+    }
+
+    /// The mother, mother's mother, and so on, of `individual`, nearest
+    /// first, through birth families only: the line an mtDNA test follows
+    pub fn matrilineal_ancestors(&self, individual: &Individual) -> Vec<&Individual> {
+        relationship::direct_line(self, individual, Lineage::Maternal)
+    }
+
+    /// The descendants of `root` who inherited `surname`, nearest generation
+    /// first, for a one-name study. See
+    /// `relationship::descendants_carrying_surname`.
+    pub fn descendants_carrying_surname(
+        &self,
+        root: &Individual,
+        surname: &str,
+    ) -> Vec<&Individual> {
+        relationship::descendants_carrying_surname(self, root, surname)
+    }
+
+    /// Every distinct line of descent from `ancestor` to `individual`, i.e.
+    /// for a lineage society application. Each runs a generation at a time
+    /// from `individual` up to `ancestor`.
+    pub fn descent_path(
+        &self,
+        individual: &Individual,
+        ancestor: &Individual,
+    ) -> Vec<Vec<&Individual>> {
+        relationship::descent_path(self, individual, ancestor)
+    }
+
+    /// `descent_path`, by xref. See `get_ancestor_xrefs`.
+    pub fn descent_path_by_xref(&self, individual: &str, ancestor: &str) -> Vec<Vec<String>> {
+        match (
+            self.find_individual(individual),
+            self.find_individual(ancestor),
+        ) {
+            (Some(individual), Some(ancestor)) => self
+                .descent_path(individual, ancestor)
+                .iter()
+                .map(|path| relationship::xrefs(path))
+                .collect(),
+            _ => vec![],
+        }
+    }
+
+    /// Every event in an individual's life, including their marriages and
+    /// other family events, in date order, with their age at each. See
+    /// `citations::QuayFilter` to keep only the well-evidenced ones.
+    pub fn timeline<'a>(&'a self, individual: &'a Individual) -> Vec<TimelineEntry<'a>> {
+        timeline::timeline(self, individual)
+    }
+
+    /// The births, marriages and deaths on a day of the year, i.e. 12 March
+    /// is `events_on(3, 12)`, in any year, oldest first: for an "on this
+    /// day" list
+    pub fn events_on(&self, month: u8, day: u8) -> Vec<Anniversary<'_>> {
+        timeline::events_on(self, month, day)
+    }
+
+    /// The ancestors of `root` with no parents recorded, the ends of each
+    /// line, with how far back they are and along which line
+    pub fn brick_walls(&self, root: &Individual) -> Vec<BrickWall<'_>> {
+        relationship::brick_walls(self, root)
+    }
+
+    /// The family graph as GraphML or GEXF, for network-analysis tools,
+    /// with generations counted from the individual with the `root` xref.
+    /// None if there's no such individual.
+    pub fn export_graph(&self, format: GraphFormat, root: Option<&str>) -> Option<String> {
+        let root = match root {
+            Some(xref) => Some(self.find_individual(xref)?),
+            None => None,
+        };
+        Some(graph::export_graph(self, format, root))
+    }
+
+    /// The family graph as nodes and typed edges, for graph algorithms. See
+    /// `graph::to_graph`.
+    pub fn to_graph(&self) -> FamilyGraph {
+        graph::to_graph(self)
+    }
+
+    /// Make the links between individuals and families go both ways, and
+    /// remove those to records that don't exist, as `options` says. See
+    /// `repair::repair_structure`, and `repair::fix_gedcom` to make the same
+    /// repairs, and those to the text, to a file.
+    pub fn repair(&mut self, options: &RepairOptions) -> Vec<Repair> {
+        repair::repair_structure(self, options)
+    }
+
+    /// Check the Gedcom against the rules it was parsed with, in
+    /// `GedcomConfig::validation`, or the default ones. See
+    /// `validate::validate_gedcom_with_config`.
+    pub fn validate(&self) -> Vec<Finding> {
+        validate::validate_gedcom_with_config(self, &self.validation)
+    }
+
+    /// The warnings found while parsing, by category. See
+    /// `GedcomError::category`.
+    pub fn warnings_by_category(&self) -> BTreeMap<&'static str, Vec<&GedcomError>> {
+        error::warnings_by_category(&self.warnings)
+    }
+
+    /// The warnings found while parsing, by category, keeping only the first
+    /// `limit` of each, and a count of the rest. See
+    /// `error::summarize_warnings`.
+    pub fn summarize_warnings(&self, limit: usize) -> Vec<WarningGroup<'_>> {
+        error::summarize_warnings(&self.warnings, limit)
+    }
+
+    /// The records nothing points to, and individuals in no family. See
+    /// `validate::orphaned_records`.
+    pub fn orphaned_records(&self) -> Vec<OrphanedRecord> {
+        validate::orphaned_records(self)
+    }
+
+    /// The islands of individuals linked to each other but not to anyone
+    /// else, largest first, to find stray fragments of the tree. See
+    /// `graph::connected_components`.
+    pub fn connected_components(&self) -> Vec<Island<'_>> {
+        graph::connected_components(self)
+    }
+
+    /// Every line of a note with one of the `markers` on it, i.e. "TODO" or
+    /// "verify", as a research to-do list. See `analysis::RESEARCH_MARKERS`.
+    pub fn research_tasks(&self, markers: &[&str]) -> Vec<ResearchTask<'_>> {
+        analysis::research_tasks(self, markers)
+    }
+
+    /// Where and when the carriers of a surname were recorded, by place and
+    /// decade, for mapping. See `analysis::surname_geography`.
+    pub fn surname_geography(&self, surname: &str) -> Vec<SurnamePlace<'_>> {
+        analysis::surname_geography(self, surname)
+    }
+
+    /// Write the individuals and families as tables, for a spreadsheet, to
+    /// individuals.csv and families.csv (or .tsv) in `directory`
+    pub fn export_csv(
+        &self,
+        options: &CsvOptions,
+        directory: &Path,
+    ) -> Result<Vec<PathBuf>, GedcomError> {
+        export::write_csv(self, options, directory)
+    }
+
+    /// Write a static website of the tree into `directory`: a surname
+    /// index, and a page for each individual. See `site::write_site_with`
+    /// to use templates of your own.
+    #[cfg(feature = "site")]
+    pub fn export_site(&self, directory: &Path) -> Result<Vec<PathBuf>, GedcomError> {
+        crate::site::write_site(self, directory)
+    }
+
+    /// `n` individuals chosen at random with their immediate families, as a
+    /// Gedcom of their own. The same seed always gives the same sample.
+    pub fn random_sample(&self, n: usize, seed: u64) -> Gedcom {
+        sample::random_sample(self, n, seed)
+    }
+
+    /// A copy with whatever the file's restrictions (RESN) hide from `view`
+    /// taken out, i.e. confidential records, for a server to hand out
+    pub fn filtered(&self, view: AccessLevel) -> Gedcom {
+        access::filtered(self, view)
+    }
+
+    /// Replace names, places, dates and other text that could identify
+    /// anyone, keeping the structure of the tree, so it can be shared
+    pub fn anonymize(&mut self, seed: u64) {
+        sample::anonymize(self, seed)
+    }
+
+    /// Rename every record's xref, i.e. to @I1@, @I2@, ... in file order,
+    /// updating every pointer to match, before merging with another file or
+    /// to get a stable diff. Returns the new xref for each old one.
+    pub fn renumber_xrefs(&mut self, scheme: XrefScheme) -> HashMap<String, String> {
+        renumber::renumber_xrefs(self, scheme)
+    }
+
+    /// Draw a text pedigree chart of the individual with this xref and
+    /// `generations` generations of their ancestors
+    pub fn render_pedigree(&self, xref: &str, generations: usize) -> Option<String> {
+        let individual = self.find_individual(xref)?;
+        Some(chart::render_pedigree(self, individual, generations))
+    }
+
+    /// List the individual with this xref and `generations` generations of
+    /// their descendants, as an indented outline
+    pub fn render_descendants(&self, xref: &str, generations: usize) -> Option<String> {
+        let individual = self.find_individual(xref)?;
+        Some(chart::render_descendants(self, individual, generations))
+    }
+
+    /// Like `render_descendants`, numbering the outline with the given system,
+    /// i.e. Henry or NGSQ numbers
+    pub fn render_numbered_descendants(
+        &self,
+        xref: &str,
+        generations: usize,
+        numbering: Numbering,
+    ) -> Option<String> {
+        let individual = self.find_individual(xref)?;
+        Some(chart::render_numbered_descendants(
+            self,
+            individual,
+            generations,
+            numbering,
+        ))
+    }
+
+    /// An SVG fan chart of the individual with this xref and their
+    /// ancestors, as many generations back as `options` says
+    pub fn fan_chart_svg(&self, xref: &str, options: &SvgOptions) -> Option<String> {
+        let individual = self.find_individual(xref)?;
+        Some(svg::fan_chart(self, individual, options))
+    }
+
+    /// An SVG box chart of the individual with this xref and their
+    /// descendants, as many generations down as `options` says
+    pub fn descendant_chart_svg(&self, xref: &str, options: &SvgOptions) -> Option<String> {
+        let individual = self.find_individual(xref)?;
+        Some(svg::descendant_chart(self, individual, options))
+    }
+
+    /// A narrative, Register-style report of the individual with this xref
+    /// and `generations` generations of their descendants, with their
+    /// sources as footnotes
+    pub fn descendant_report(
+        &self,
+        xref: &str,
+        generations: usize,
+        format: ReportFormat,
+    ) -> Option<String> {
+        let individual = self.find_individual(xref)?;
+        Some(report::descendant_report(
+            self,
+            individual,
+            generations,
+            format,
+        ))
+    }
+
+    /// A narrative ahnentafel report of the individual with this xref and
+    /// `generations` generations of their ancestors, with their sources as
+    /// footnotes
+    pub fn ahnentafel_report(
+        &self,
+        xref: &str,
+        generations: usize,
+        format: ReportFormat,
+    ) -> Option<String> {
+        let individual = self.find_individual(xref)?;
+        Some(report::ahnentafel_report(
+            self,
+            individual,
+            generations,
+            format,
+        ))
+    }
+
+    /// Work out how `other` is related to `individual`: what they are to
+    /// them, and through which common ancestors.
+    pub fn find_relationship(
+        &self,
+        individual: &Individual,
+        other: &Individual,
+    ) -> Option<Relationship<'_>> {
+        Relationship::find(self, individual, other)
+    }
+
+    /// `find_relationship`, by xref, with a result that doesn't borrow from
+    /// the Gedcom, so it can be kept or sent to another thread
+    pub fn find_relationship_by_xref(
+        &self,
+        individual: &str,
+        other: &str,
+    ) -> Option<OwnedRelationship> {
+        let relationship = self.find_relationship(
+            self.find_individual(individual)?,
+            self.find_individual(other)?,
+        )?;
+        Some(relationship.into_owned())
+    }
+
+    /// Work out the ancestors of everyone in the same tree as `root`, by
+    /// xref, so that `find_relationship` between any two of them only needs
+    /// to compare their lists of ancestors. Worthwhile before answering many
+    /// queries over a large file. Editing the records with
+    /// `replace_record_from_str` discards it; after editing them directly,
+    /// precompute it again.
+    pub fn precompute_kinship(&mut self, root: &str) -> Result<(), GedcomError> {
+        if self.find_individual(root).is_none() {
+            return Err(GedcomError::MissingData {
+                line: None,
+                offset: None,
+                xref: Some(root.to_string()),
+                message: "No such individual".to_string(),
+            });
+        }
+        self.kinship = Some(Kinship::new(self, root));
+        Ok(())
+    }
+
+    /// The shortest chain of parents, children, and spouses from
+    /// `individual` to `other`, including both of them.
+    pub fn find_relationship_path(
+        &self,
+        individual: &Individual,
+        other: &Individual,
+    ) -> Option<Vec<&Individual>> {
+        relationship::find_relationship_path(self, individual, other)
+    }
+
+    /// `find_relationship_path`, also stepping between associates (ASSO),
+    /// or treating records linked by ALIA as one person, as `options` allow
+    pub fn find_relationship_path_with(
+        &self,
+        individual: &Individual,
+        other: &Individual,
+        options: PathOptions,
+    ) -> Option<Vec<&Individual>> {
+        relationship::find_relationship_path_with(self, individual, other, options)
+    }
+
+    /// Group the records that are for the same person, linked by ALIA
+    pub fn resolve_aliases(&self) -> Identities {
+        Identities::new(self)
+    }
+
+    /// `find_relationship_path`, by xref. See `find_relationship_by_xref`.
+    pub fn find_relationship_path_by_xref(
+        &self,
+        individual: &str,
+        other: &str,
+    ) -> Option<Vec<String>> {
+        let path = self.find_relationship_path(
+            self.find_individual(individual)?,
+            self.find_individual(other)?,
+        )?;
+        Some(relationship::xrefs(&path))
+    }
+
+    /// Find the individuals with a name matching `name`, best match first.
+    pub fn find_individuals_by_name(&self, name: &str) -> Vec<&Individual> {
+        self.find_individuals_by_name_scored(name)
+            .into_iter()
+            .map(|r| r.item)
+            .collect()
+    }
+
+    /// Like `find_individuals_by_name`, but with each match's score.
+    pub fn find_individuals_by_name_scored(&self, name: &str) -> Vec<SearchResult<&Individual>> {
+        self.search_individuals(&SearchQuery::new().name(name))
+    }
+
+    /// Find the individuals born within `tolerance` years of `year`, closest first.
+    pub fn find_individuals_by_birth_year(&self, year: i32, tolerance: i32) -> Vec<&Individual> {
+        self.search_individuals(
+            &SearchQuery::new()
+                .birth_year(year)
+                .year_tolerance(tolerance),
+        )
+        .into_iter()
+        .map(|r| r.item)
+        .collect()
+    }
+
+    /// Find the individuals with an event of the given type within
+    /// `tolerance` years of `year`, closest first. Ties keep file order.
+    pub fn find_individuals_by_event_date(
+        &self,
+        event_type: EventType,
+        year: i32,
+        tolerance: i32,
+    ) -> Vec<&Individual> {
+        self.find_individuals_by(|e| e.event_type == event_type, year, tolerance)
+    }
+
+    /// Like `find_individuals_by_event_date`, for generic EVEN events of the
+    /// given TYPE, i.e. "Military service". The TYPE is matched ignoring case.
+    pub fn find_individuals_by_custom_event_date(
+        &self,
+        r#type: &str,
+        year: i32,
+        tolerance: i32,
+    ) -> Vec<&Individual> {
+        self.find_individuals_by(
+            |e| {
+                e.event_type == EventType::Event
+                    && e.detail
+                        .and_then(|d| d.r#type.as_deref())
+                        .is_some_and(|t| t.trim().eq_ignore_ascii_case(r#type.trim()))
+            },
+            year,
+            tolerance,
+        )
+    }
+
+    /// Find the individuals born from `start` to `end`, inclusive, in order
+    /// of birth. Either may be a partial date, i.e. "1800" or "JUN 1850".
+    /// See `find_individuals_by_event_between`.
+    pub fn find_individuals_born_between(
+        &self,
+        start: SimpleDate,
+        end: SimpleDate,
+    ) -> Vec<&Individual> {
+        self.find_individuals_by_event_between(EventType::Birth, start, end)
+    }
+
+    /// Find the individuals who died from `start` to `end`, inclusive, in
+    /// order of death. See `find_individuals_by_event_between`.
+    pub fn find_individuals_died_between(
+        &self,
+        start: SimpleDate,
+        end: SimpleDate,
+    ) -> Vec<&Individual> {
+        self.find_individuals_by_event_between(EventType::Death, start, end)
+    }
+
+    /// Find the individuals with an event of the given type from `start` to
+    /// `end`, inclusive, in date order; ties keep file order. An event
+    /// counts only if its date certainly falls in the range: "BET 1820 AND
+    /// 1830" is between 1800 and 1850, but "BEF 1850" isn't. See
+    /// `GedcomDate::within`.
+    pub fn find_individuals_by_event_between(
+        &self,
+        event_type: EventType,
+        start: SimpleDate,
+        end: SimpleDate,
+    ) -> Vec<&Individual> {
+        let mut found: Vec<(GedcomDate, &Individual)> = self
+            .individuals
+            .iter()
+            .filter_map(|individual| {
+                let date = individual
+                    .all_events()
+                    .filter(|e| e.event_type == event_type)
+                    .filter_map(|e| GedcomDate::from_str(e.detail?.date.as_deref()?).ok())
+                    .filter(|date| date.within(&start, &end))
+                    .min()?;
+                Some((date, individual))
+            })
+            .collect();
+        found.sort_by(|(a, _), (b, _)| a.cmp(b));
+        found.into_iter().map(|(_, i)| i).collect()
+    }
+
+    fn find_individuals_by(
+        &self,
+        matches: impl Fn(&IndividualEvent) -> bool,
+        year: i32,
+        tolerance: i32,
+    ) -> Vec<&Individual> {
+        let mut found: Vec<(i32, &Individual)> = self
+            .individuals
+            .iter()
+            .filter_map(|individual| {
+                let distance = individual
+                    .all_events()
+                    .filter(|e| matches(e))
+                    .filter_map(|e| date_year(e.detail?.date.as_deref()?))
+                    .map(|y| (y - year).abs())
+                    .filter(|d| *d <= tolerance)
+                    .min()?;
+                Some((distance, individual))
+            })
+            .collect();
+        found.sort_by_key(|(distance, _)| *distance);
+        found.into_iter().map(|(_, i)| i).collect()
+    }
+
+    /// Find the individuals with an event, or a family event as a spouse,
+    /// that took place at `place`, ignoring case. Those whose place name is
+    /// the same, or starts with it, come before those that only contain it,
+    /// i.e. in "Munich, Bavaria, Germany" for "Bavaria".
+    pub fn find_individuals_by_place(&self, place: &str) -> Vec<&Individual> {
+        self.find_individuals_at(|_, p| score_place(p.name.as_deref()?, place))
+    }
+
+    /// Like `find_individuals_by_place`, for events of the given type only
+    pub fn find_individuals_by_event_place(
+        &self,
+        event_type: EventType,
+        place: &str,
+    ) -> Vec<&Individual> {
+        self.find_individuals_at(|e, p| {
+            if e != event_type {
+                return None;
+            }
+            score_place(p.name.as_deref()?, place)
+        })
+    }
+
+    /// Find the individuals with an event in a jurisdiction, named by its
+    /// level in the PLAC FORM, i.e. everyone with an event in the "State" of
+    /// "Bavaria". Places without a FORM use the header's.
+    pub fn find_individuals_by_jurisdiction(&self, level: &str, name: &str) -> Vec<&Individual> {
+        let form = self.header.place_form();
+        self.find_individuals_at(|_, p| {
+            p.jurisdiction(level, form)
+                .filter(|j| j.eq_ignore_ascii_case(name.trim()))
+                .map(|_| EXACT_NAME_SCORE)
+        })
+    }
+
+    /// The individuals with a place scored by `score`, best first. Ties keep
+    /// file order.
+    fn find_individuals_at(
+        &self,
+        score: impl Fn(EventType, &Place) -> Option<f64>,
+    ) -> Vec<&Individual> {
+        let mut found: Vec<(f64, &Individual)> = self
+            .individuals
+            .iter()
+            .filter_map(|individual| {
+                let own = individual
+                    .all_events()
+                    .filter_map(|e| Some((e.event_type, e.detail?.place.as_ref()?)));
+                let families = self.spouse_families(individual).into_iter().flat_map(|f| {
+                    f.tagged_events().into_iter().filter_map(|(tag, detail)| {
+                        Some((EventType::from_str(tag).ok()?, detail?.place.as_ref()?))
+                    })
+                });
+                let best = own
+                    .chain(families)
+                    .filter_map(|(event_type, place)| score(event_type, place))
+                    .max_by(|a, b| a.total_cmp(b))?;
+                Some((best, individual))
+            })
+            .collect();
+        found.sort_by(|(a, _), (b, _)| b.total_cmp(a));
+        found.into_iter().map(|(_, i)| i).collect()
+    }
+
+    /// The individuals matching a query, i.e. "surname = 'Smith' AND
+    /// birth.year BETWEEN 1800 AND 1850", in file order. See `Filter` for
+    /// what a query may contain.
+    pub fn query(&self, query: &str) -> Result<Vec<&Individual>, GedcomError> {
+        Ok(query::filter(self, &Filter::parse(query)?))
+    }
+
+    /// The individuals matching `filter`, in file order
+    pub fn filter(&self, filter: &Filter) -> Vec<&Individual> {
+        query::filter(self, filter)
+    }
+
+    /// Every individual, sorted `by` a name, date or xref. Those without one
+    /// come last, and ties keep file order. Take a page of a long list with
+    /// `skip` and `take`:
+    ///
+    /// ```
+    /// use gedcom_rs::parse::parse_gedcom_from_str;
+    /// use gedcom_rs::search::{SortKey, SortOrder};
+    ///
+    /// let gedcom = parse_gedcom_from_str("0 HEAD\n0 @I1@ INDI\n1 NAME Joe /Bloggs/\n0 TRLR\n").unwrap();
+    /// let page: Vec<_> = gedcom
+    ///     .individuals_sorted(SortKey::Name, SortOrder::Ascending)
+    ///     .skip(0)
+    ///     .take(50)
+    ///     .collect();
+    /// assert!(page.len() == 1);
+    /// ```
+    pub fn individuals_sorted(
+        &self,
+        by: SortKey,
+        order: SortOrder,
+    ) -> std::vec::IntoIter<&Individual> {
+        search::sort_individuals(&self.individuals, by, order).into_iter()
+    }
+
+    /// Find the individuals matching every criterion of `query`, scored and
+    /// ranked best first.
+    pub fn search_individuals(&self, query: &SearchQuery) -> Vec<SearchResult<&Individual>> {
+        let mut results: Vec<SearchResult<&Individual>> = self
+            .individuals
+            .iter()
+            .filter_map(|i| query.score(i))
+            .collect();
+        rank(&mut results);
+        if query.merge_aliases {
+            let identities = self.resolve_aliases();
+            let mut seen = HashSet::new();
+            results.retain(|r| {
+                r.item
+                    .xref
+                    .as_deref()
+                    .is_none_or(|x| seen.insert(identities.canonical(x).to_string()))
+            });
+        }
+        results
+    }
+
+    /// Replace the individual or family record `xref` with one parsed from
+    /// `text`, the GEDCOM text of that single record, i.e. after it's been
+    /// edited by hand.
+    ///
+    /// The text must be well-formed, must be a record of the same kind with
+    /// the same xref, and every individual or family it points to must
+    /// exist. Otherwise, the model is left untouched and the problem is
+    /// returned, with line numbers relative to `text`.
+    pub fn replace_record_from_str(&mut self, xref: &str, text: &str) -> Result<(), GedcomError> {
+        let config = GedcomConfig::new().strict().preserve_unknown();
+        let mut parsed = parse_gedcom_from_reader_with_config(text.as_bytes(), &config)?;
+
+        let problem = |message: String| GedcomError::MissingData {
+            line: Some(1),
+            offset: Some(0),
+            xref: Some(xref.to_string()),
+            message,
+        };
+
+        let records: Vec<&str> = parsed
+            .raw_records
+            .iter()
+            .filter(|r| r.tag != "TRLR")
+            .map(|r| r.tag.as_str())
+            .collect();
+        if records.len() != 1 {
+            return Err(problem(format!(
+                "Expected a single record, found {}",
+                records.len()
+            )));
+        }
+        let tag = records[0];
+        if parsed.raw_records[0].xref.as_deref() != Some(xref) {
+            return Err(problem("The record's xref doesn't match".to_string()));
+        }
+
+        match tag {
+            "INDI" => {
+                let index = self
+                    .individuals
+                    .iter()
+                    .position(|i| i.xref.as_deref() == Some(xref))
+                    .ok_or_else(|| problem("No such individual".to_string()))?;
+                let individual = parsed.individuals.remove(0);
+                for family in individual.famc.iter().chain(individual.fams.iter()) {
+                    if self.find_family(&family.xref).is_none() {
+                        return Err(problem(format!("Unknown family {}", family.xref)));
+                    }
+                }
+                self.individuals[index] = individual;
+                self.kinship = None;
+            }
+            "FAM" => {
+                let index = self
+                    .families
+                    .iter()
+                    .position(|f| f.xref == xref)
+                    .ok_or_else(|| problem("No such family".to_string()))?;
+                let family = parsed.families.remove(0);
+                let members = family
+                    .husband_xref()
+                    .into_iter()
+                    .chain(family.wife_xref())
+                    .chain(family.children.iter().map(|c| c.as_str()));
+                for member in members {
+                    if self.find_individual(member).is_none() {
+                        return Err(problem(format!("Unknown individual {}", member)));
+                    }
+                }
+                self.families[index] = family;
+                self.kinship = None;
+            }
+            _ => {
+                return Err(problem(format!("Can't replace a {} record", tag)));
+            }
+        }
+
+        // The record no longer comes from the file we read
+        self.record_positions.remove(xref);
+        if let Some(raw) = self
+            .raw_records
+            .iter_mut()
+            .find(|r| r.xref.as_deref() == Some(xref))
+        {
+            *raw = parsed.raw_records.remove(0);
+            if let Some(last) = raw.lines.last_mut() {
+                if !last.ends_with('\n') {
+                    last.push('\n');
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A UID in a form that can be compared: upper case, without hyphens or
+/// braces, and without the four-digit checksum PAF and others add to a
+/// 32-digit UUID
+fn normalize_uid(uid: &str) -> String {
+    let mut uid: String = uid
+        .chars()
+        .filter(|c| !matches!(c, '-' | '{' | '}'))
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+    if uid.len() == 36 && uid.chars().all(|c| c.is_ascii_hexdigit()) {
+        uid.truncate(32);
+    }
+    uid
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse::parse_gedcom_from_str;
+    use crate::types::{AttributeType, EventType, SimpleDate, SourceMedia, Timestamp};
+    use crate::writer::write_gedcom;
+
+    const DATA: &str = "0 HEAD
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 FAMS @F1@
+0 @I2@ INDI
+1 NAME Jane /Doe/
+1 FAMS @F1@
+0 @F1@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+0 TRLR
+";
+
+    #[test]
+    fn replace_record_from_str() {
+        let mut gedcom = parse_gedcom_from_str(DATA).unwrap();
+
+        gedcom
+            .replace_record_from_str("@I1@", "0 @I1@ INDI\n1 NAME Joseph /Bloggs/\n1 FAMS @F1@")
+            .unwrap();
+        let joe = gedcom.find_individual("@I1@").unwrap();
+        assert!(joe.names[0].name.value.as_deref() == Some("Joseph /Bloggs/"));
+        assert!(gedcom.individuals.len() == 2);
+        assert!(!gedcom.record_positions.contains_key("@I1@"));
+
+        // Dangling references are refused
+        let err = gedcom
+            .replace_record_from_str("@F1@", "0 @F1@ FAM\n1 HUSB @I1@\n1 CHIL @I9@\n")
+            .unwrap_err();
+        assert!(err.to_string() == "line 1: @F1@: Unknown individual @I9@");
+        assert!(gedcom.find_family("@F1@").unwrap().children.is_empty());
+
+        // As are mismatched xrefs, malformed text, and multiple records
+        assert!(gedcom
+            .replace_record_from_str("@I2@", "0 @I1@ INDI\n")
+            .is_err());
+        assert!(gedcom
+            .replace_record_from_str("@I2@", "0 @I2@ INDI\n3 NAME Jane\n")
+            .is_err());
+        assert!(gedcom
+            .replace_record_from_str("@I2@", "0 @I2@ INDI\n0 @I3@ INDI\n")
+            .is_err());
+        assert!(
+            gedcom.find_individual("@I2@").unwrap().names[0]
+                .name
+                .value
+                .as_deref()
+                == Some("Jane /Doe/")
+        );
+    }
+
+    #[test]
+    fn home_person() {
+        let mut gedcom = parse_gedcom_from_str(DATA).unwrap();
+        assert!(gedcom.home_person().is_none());
+
+        assert!(gedcom.set_home_person("@I9@").is_err());
+        gedcom.set_home_person("@I2@").unwrap();
+        assert!(gedcom.home_person().unwrap().xref.as_deref() == Some("@I2@"));
+
+        let written = write_gedcom(&gedcom);
+        assert!(written.contains("\n1 _HOME @I2@\n"));
+        let gedcom = parse_gedcom_from_str(&written).unwrap();
+        assert!(gedcom.header.home_person.as_deref() == Some("@I2@"));
+    }
+
+    #[test]
+    fn sources_by_media() {
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @S1@ SOUR
+1 TITL Parish registers
+1 REPO @R1@
+2 CALN 920.23
+3 MEDI microfilm
+0 @S2@ SOUR
+1 TITL Churchyard survey
+1 REPO @R1@
+2 CALN 12
+3 MEDI Tombstone
+2 CALN 13
+3 MEDI Photo
+0 TRLR
+",
+        )
+        .unwrap();
+        let titles = |media| -> Vec<&str> {
+            gedcom
+                .sources_by_media(&media)
+                .iter()
+                .filter_map(|s| s.title.as_deref())
+                .collect()
+        };
+        assert!(titles(SourceMedia::Film) == ["Parish registers"]);
+        assert!(titles(SourceMedia::Photo) == ["Churchyard survey"]);
+        assert!(titles(SourceMedia::Book).is_empty());
+        assert!(
+            gedcom.find_source("@S2@").unwrap().repositories[0]
+                .call_numbers
+                .len()
+                == 2
+        );
+    }
+
+    #[test]
+    fn notes_modified_since() {
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @N1@ NOTE Old
+1 CHAN
+2 DATE 14 JAN 2001
+0 @N2@ NOTE Newest
+1 CHAN
+2 DATE 3 MAR 2024
+3 TIME 09:15
+0 @N3@ NOTE Never changed
+0 @N4@ NOTE Newer
+1 CHAN
+2 DATE 1 FEB 2024
+0 TRLR
+",
+        )
+        .unwrap();
+        assert!(gedcom.notes.len() == 4);
+        assert!(gedcom.find_note("@N3@").unwrap().text.as_deref() == Some("Never changed"));
+
+        let since = |date: &str| -> Vec<&str> {
+            let since = Timestamp {
+                date: date.parse().unwrap(),
+                time: None,
+            };
+            gedcom
+                .notes_modified_since(&since)
+                .iter()
+                .map(|n| n.xref.as_str())
+                .collect()
+        };
+        assert!(since("1 JAN 2024") == ["@N2@", "@N4@"]);
+        assert!(since("1 FEB 2024") == ["@N2@", "@N4@"]);
+        assert!(since("2000") == ["@N2@", "@N4@", "@N1@"]);
+        assert!(since("2025").is_empty());
+    }
+
+    #[test]
+    fn sources_in_repository() {
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @S1@ SOUR
+1 TITL Parish registers
+1 REPO @R1@
+2 CALN FHL 1234567
+3 MEDI microfilm
+0 @S2@ SOUR
+1 TITL Census
+1 REPO @R2@
+2 CALN HO107/1234
+0 @S3@ SOUR
+1 TITL Churchyard survey
+1 REPO @R1@
+2 CALN 12
+3 MEDI Tombstone
+2 CALN 13
+3 MEDI Photo
+0 TRLR
+",
+        )
+        .unwrap();
+        let holdings = gedcom.sources_in_repository("@R1@");
+        let sources: Vec<&str> = holdings.iter().map(|h| h.source.xref.as_str()).collect();
+        assert!(sources == ["@S1@", "@S3@"]);
+        assert!(holdings[0].call_numbers().collect::<Vec<_>>() == ["FHL 1234567"]);
+        assert!(holdings[1].call_numbers().collect::<Vec<_>>() == ["12", "13"]);
+        assert!(
+            holdings[1].media().collect::<Vec<_>>()
+                == [&SourceMedia::Tombstone, &SourceMedia::Photo]
+        );
+        assert!(gedcom.sources_in_repository("@R2@").len() == 1);
+        assert!(gedcom.sources_in_repository("@R3@").is_empty());
+    }
+
+    #[test]
+    fn find_individuals_between() {
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @I1@ INDI
+1 BIRT
+2 DATE BET 1820 AND 1830
+1 DEAT
+2 DATE 12 MAR 1850
+0 @I2@ INDI
+1 BIRT
+2 DATE BEF 1850
+0 @I3@ INDI
+1 BIRT
+2 DATE ABT 1805
+0 @I4@ INDI
+1 BIRT
+2 DATE 1850
+1 DEAT
+2 DATE (in the war)
+0 @I5@ INDI
+1 BIRT
+2 DATE JUL 1850
+0 TRLR
+",
+        )
+        .unwrap();
+        let xrefs = |found: Vec<&crate::types::Individual>| -> Vec<String> {
+            found.iter().filter_map(|i| i.xref.clone()).collect()
+        };
+        let date = |date: &str| date.parse::<SimpleDate>().unwrap();
+
+        assert!(
+            xrefs(gedcom.find_individuals_born_between(date("1800"), date("1850")))
+                == ["@I3@", "@I1@", "@I4@", "@I5@"]
+        );
+        assert!(
+            xrefs(gedcom.find_individuals_born_between(date("1800"), date("JUN 1850")))
+                == ["@I3@", "@I1@"]
+        );
+        assert!(
+            xrefs(gedcom.find_individuals_died_between(date("MAR 1850"), date("1900"))) == ["@I1@"]
+        );
+        assert!(gedcom
+            .find_individuals_by_event_between(EventType::Burial, date("1800"), date("1900"))
+            .is_empty());
+    }
+
+    #[test]
+    fn find_individuals_by_event_date() {
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 CENS
+2 DATE 1901
+1 DEAT
+2 DATE 1950
+1 OCCU Miner
+2 DATE 1921
+1 EVEN
+2 TYPE Military Service
+2 DATE 1916
+0 @I2@ INDI
+1 NAME Jane /Doe/
+1 CENS
+2 DATE 1891
+1 CENS
+2 DATE 1911
+1 EDUC Teacher training college
+2 DATE 1909
+1 TITL Lady
+0 @I3@ INDI
+1 NAME Jim /Bloggs/
+1 CENS
+0 TRLR
+",
+        )
+        .unwrap();
+
+        let xrefs = |found: Vec<&crate::types::Individual>| -> Vec<String> {
+            found.iter().filter_map(|i| i.xref.clone()).collect()
+        };
+        assert!(
+            xrefs(gedcom.find_individuals_by_event_date(EventType::Census, 1910, 10))
+                == ["@I2@", "@I1@"]
+        );
+        assert!(
+            xrefs(gedcom.find_individuals_by_event_date(EventType::Census, 1901, 0)) == ["@I1@"]
+        );
+        assert!(
+            xrefs(gedcom.find_individuals_by_event_date(EventType::Death, 1949, 1)) == ["@I1@"]
+        );
+        assert!(gedcom
+            .find_individuals_by_event_date(EventType::Burial, 1950, 5)
+            .is_empty());
+        assert!(
+            xrefs(gedcom.find_individuals_by_event_date(EventType::Occupation, 1920, 1))
+                == ["@I1@"]
+        );
+        let education = EventType::Attribute(AttributeType::Education);
+        assert!(xrefs(gedcom.find_individuals_by_event_date(education, 1910, 1)) == ["@I2@"]);
+        assert!(gedcom
+            .find_individuals_by_event_date(EventType::Attribute(AttributeType::Title), 1910, 100)
+            .is_empty());
+        assert!(
+            xrefs(gedcom.find_individuals_by_custom_event_date("military service", 1915, 2))
+                == ["@I1@"]
+        );
+        assert!(gedcom
+            .find_individuals_by_custom_event_date("Apprenticeship", 1915, 2)
+            .is_empty());
+    }
+
+    #[test]
+    fn find_individuals_by_place() {
+        let gedcom = parse_gedcom_from_str(
+            "0 HEAD
+1 PLAC
+2 FORM City, State, Country
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 BIRT
+2 PLAC Munich, Bavaria, Germany
+1 FAMS @F1@
+0 @I2@ INDI
+1 NAME Jane /Doe/
+1 DEAT
+2 PLAC bavaria
+1 FAMS @F1@
+0 @I3@ INDI
+1 NAME Jim /Bloggs/
+1 BIRT
+2 PLAC Leeds, Yorkshire, England
+0 @F1@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 MARR
+2 PLAC Nuremberg, Bavaria, Germany
+0 TRLR
+",
+        )
+        .unwrap();
+        let xrefs = |found: Vec<&crate::types::Individual>| -> Vec<String> {
+            found.iter().filter_map(|i| i.xref.clone()).collect()
+        };
+
+        assert!(xrefs(gedcom.find_individuals_by_place("Bavaria")) == ["@I2@", "@I1@"]);
+        assert!(xrefs(gedcom.find_individuals_by_place("nuremberg")) == ["@I1@", "@I2@"]);
+        assert!(xrefs(gedcom.find_individuals_by_place("leeds, yorkshire, england")) == ["@I3@"]);
+        assert!(gedcom.find_individuals_by_place("Paris").is_empty());
+
+        assert!(
+            xrefs(gedcom.find_individuals_by_event_place(EventType::Birth, "Bavaria")) == ["@I1@"]
+        );
+        assert!(
+            xrefs(gedcom.find_individuals_by_jurisdiction("state", "Bavaria")) == ["@I1@", "@I2@"]
+        );
+        assert!(xrefs(gedcom.find_individuals_by_jurisdiction("Country", "England")) == ["@I3@"]);
+    }
+
+    #[test]
+    fn uids() {
+        let text = "0 HEAD
+1 GEDC
+2 VERS 5.5.1
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 _UID 5F0C9A2E3B1D4C7E8A6F0B2D4E6A8C0E1A2B
+0 @I2@ INDI
+1 NAME Ann /Bloggs/
+0 @F1@ FAM
+1 HUSB @I1@
+1 UID 0a1b2c3d-0000-4000-8000-0123456789ab
+1 CHAN
+2 DATE 1 JAN 2000
+0 TRLR
+";
+        let gedcom = parse_gedcom_from_str(text).unwrap();
+        assert!(gedcom.individuals[0].uids == ["5F0C9A2E3B1D4C7E8A6F0B2D4E6A8C0E1A2B"]);
+        assert!(gedcom.individuals[1].uids.is_empty());
+        assert!(gedcom.families[0].change_date.is_some());
+
+        let joe = gedcom.find_individual_by_uid("5f0c9a2e-3b1d-4c7e-8a6f-0b2d4e6a8c0e");
+        assert!(joe.unwrap().xref.as_deref() == Some("@I1@"));
+        assert!(gedcom
+            .find_individual_by_uid("{5F0C9A2E3B1D4C7E8A6F0B2D4E6A8C0E1A2B}")
+            .is_some());
+        assert!(gedcom.find_individual_by_uid("5F0C9A2E").is_none());
+        let family = gedcom.find_family_by_uid("0A1B2C3D0000400080000123456789AB");
+        assert!(family.unwrap().xref == "@F1@");
+
+        // Written as _UID, or as UID in GEDCOM 7
+        let written = write_gedcom(&gedcom);
+        assert!(written.contains("1 _UID 5F0C9A2E3B1D4C7E8A6F0B2D4E6A8C0E1A2B\n"));
+        assert!(written.contains("1 _UID 0a1b2c3d-0000-4000-8000-0123456789ab\n"));
+        let (written, _) =
+            crate::writer::write_gedcom_as(&gedcom, crate::types::GedcomVersion::V70);
+        assert!(written.contains("1 UID 0a1b2c3d-0000-4000-8000-0123456789ab\n"));
+        let reread = parse_gedcom_from_str(&written).unwrap();
+        assert!(reread.families[0].uids == gedcom.families[0].uids);
+    }
+}
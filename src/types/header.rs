@@ -1,12 +1,14 @@
+use std::str::FromStr;
+
 use crate::parse;
 // use crate::types::corporation;
 // use crate::types::Copyright;
 // use crate::types::Note;
 use crate::types::{CharacterSet, Source, Submission, Submitter};
 
-use super::Gedc;
 use super::Line;
 use super::{DateTime, Place};
+use super::{Gedc, GedcomVersion};
 
 /*
 HEADER:= n HEAD
@@ -38,7 +40,7 @@ HEADER:= n HEAD
     +2 [CONC|CONT] <GEDCOM_CONTENT_DESCRIPTION>
 */
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 // #[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct Header {
     pub character_set: Option<CharacterSet>,
@@ -53,6 +55,10 @@ pub struct Header {
     pub source: Option<Source>,
     pub submitter: Option<Submitter>,
     pub submission: Option<Submission>,
+    /// The xref of the individual the tree is centred on. This isn't part of
+    /// the specification, so it's kept in a custom `_HOME` tag. We also read
+    /// the `_HME` tag some programs use for the same purpose.
+    pub home_person: Option<String>,
 }
 
 impl Header {
@@ -71,6 +77,7 @@ impl Header {
             source: None,
             submitter: None,
             submission: None,
+            home_person: None,
         };
 
         // do parser stuff here
@@ -142,6 +149,10 @@ impl Header {
                     "SUBN" => {
                         (buffer, header.submission) = Submission::parse(&record);
                     }
+                    "_HOME" | "_HME" => {
+                        header.home_person = Some(line.value.to_string());
+                        Line::parse(&mut buffer).unwrap();
+                    }
                     _ => {
                         // println!("Unhandled header tag: {}", line.tag);
                         // (buffer, _) = Line::parse(&record).unwrap();
@@ -157,6 +168,22 @@ impl Header {
         }
         header
     }
+
+    /// The version of GEDCOM the file says it follows (GEDC.VERS), if it's
+    /// one we know
+    pub fn version(&self) -> Option<GedcomVersion> {
+        let version = self.gedcom_version.as_ref()?.version.as_deref()?;
+        GedcomVersion::from_str(version).ok()
+    }
+
+    /// The default place hierarchy (PLAC.FORM) used by places in this file,
+    /// i.e. ["City", "County", "State", "Country"]. Empty if none was given.
+    pub fn place_form(&self) -> &[String] {
+        match &self.place {
+            Some(place) => &place.form,
+            None => &[],
+        }
+    }
 }
 
 #[cfg(test)]
@@ -209,7 +236,9 @@ mod tests {
             "1 LANG English",
             "1 DATE 1 JAN 2023",
             "2 TIME 12:13:14.15",
-            "1 PLAC Salt Lake City, UT, USA",
+            "1 PLAC",
+            "2 FORM City, County, State, Country",
+            "1 FILE example.ged",
             // The submitter record
             "0 @U1@ SUBM",
             "1 NAME Adam Israel",
@@ -244,7 +273,7 @@ mod tests {
 
         // Character encoding
         assert!(header.character_set.is_some());
-        if let Some(character_set) = header.character_set {
+        if let Some(character_set) = &header.character_set {
             assert!(character_set.encoding.is_some());
             assert!(character_set.version.is_some());
 
@@ -343,6 +372,11 @@ mod tests {
 
         // place
         assert!(header.place.is_some());
+        assert!(header.place.as_ref().unwrap().name.is_none());
+        assert!(header.place_form() == ["City", "County", "State", "Country"]);
+
+        // filename
+        assert!(header.filename == Some("example.ged".to_string()));
 
         // datetime
         assert!(header.date.is_some());
@@ -359,5 +393,6 @@ mod tests {
 
         // submission
         assert!(header.submission.is_some());
+        assert!(header.submission.as_ref().unwrap().xref == Some("@U1@".to_string()));
     }
 }
@@ -0,0 +1,116 @@
+use crate::parse;
+use crate::types::{Line, Note, SourceCitation};
+
+use winnow::error::ErrMode;
+use winnow::error::ErrorKind;
+use winnow::error::ParserError;
+use winnow::prelude::*;
+
+// ASSOCIATION_STRUCTURE:=
+// n ASSO @<XREF:INDI>@ {1:1}
+// +1 RELA <RELATION_IS_DESCRIPTOR> {1:1}
+// +1 <<SOURCE_CITATION>> {0:M}
+// +1 <<NOTE_STRUCTURE>> {0:M}
+//
+// GEDCOM 7 replaces RELA with a ROLE from a fixed list, i.e. GODP or WITN,
+// which may be OTHER, with a PHRASE saying what the role really was. It
+// also allows an ASSO on an event, which programs wrote before that as
+// _ASSO, _SHAR (a shared event) or _WITN (a witness, with no role).
+
+/// A link to someone who isn't family, but was involved in the individual's
+/// life, i.e. a godparent or a witness
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Association {
+    /// The xref of the associated individual
+    pub xref: String,
+    /// RELA, or ROLE: what the associate was to the individual, i.e.
+    /// "Godfather"
+    pub relation: Option<String>,
+    pub notes: Vec<Note>,
+    pub sources: Vec<SourceCitation>,
+}
+
+impl Association {
+    pub fn parse(record: &mut &str) -> PResult<Association> {
+        let line = Line::parse(record).unwrap();
+        if !matches!(line.tag, "ASSO" | "_ASSO" | "_SHAR" | "_WITN") {
+            return Err(ErrMode::from_error_kind(record, ErrorKind::Verify));
+        }
+        let level = line.level;
+        let mut association = Association {
+            xref: line.value.to_string(),
+            relation: (line.tag == "_WITN").then(|| "Witness".to_string()),
+            ..Default::default()
+        };
+
+        while !record.is_empty() {
+            let line = Line::peek(record).unwrap();
+            if line.level <= level {
+                break;
+            }
+            let mut consume = true;
+            match (line.level - level, line.tag) {
+                (1, "RELA") | (1, "ROLE") => {
+                    association.relation = Some(line.value.to_string());
+                }
+                // Only needed when the ROLE is OTHER, but kept whenever it's
+                // given, since it says more than the role does
+                (2, "PHRASE") => {
+                    association.relation = Some(line.value.to_string());
+                }
+                (1, "NOTE") => {
+                    if let Some(note) = parse::get_tag_value(record).unwrap() {
+                        association.notes.push(Note { note: Some(note) });
+                    }
+                    consume = false;
+                }
+                (1, "SOUR") => {
+                    association
+                        .sources
+                        .push(SourceCitation::parse(record).unwrap());
+                    consume = false;
+                }
+                _ => {}
+            }
+            if consume {
+                Line::parse(record).unwrap();
+            }
+        }
+
+        Ok(association)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Association;
+
+    #[test]
+    fn parse_association() {
+        let data = [
+            "1 ASSO @I2@",
+            "2 RELA Godfather",
+            "2 NOTE Stood in for his brother",
+            "2 SOUR @S1@",
+            "3 PAGE 12",
+            "1 NAME Joe /Bloggs/",
+        ]
+        .join("\n");
+        let mut record = data.as_str();
+        let association = Association::parse(&mut record).unwrap();
+        assert!(association.xref == "@I2@");
+        assert!(association.relation.as_deref() == Some("Godfather"));
+        assert!(association.notes[0].note.as_deref() == Some("Stood in for his brother"));
+        assert!(association.sources[0].xref.as_deref() == Some("@S1@"));
+        assert!(record == "1 NAME Joe /Bloggs/");
+
+        let data = ["1 ASSO @I3@", "2 ROLE OTHER", "3 PHRASE Best man"].join("\n");
+        let association = Association::parse(&mut data.as_str()).unwrap();
+        assert!(association.relation.as_deref() == Some("Best man"));
+        let association = Association::parse(&mut "1 ASSO @I4@\n2 ROLE WITN").unwrap();
+        assert!(association.relation.as_deref() == Some("WITN"));
+        let association = Association::parse(&mut "2 _WITN @I5@").unwrap();
+        assert!(association.relation.as_deref() == Some("Witness"));
+        assert!(Association::parse(&mut "1 NAME Joe").is_err());
+    }
+}
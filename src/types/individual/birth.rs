@@ -51,12 +51,8 @@ impl Birth {
             match line.tag {
                 "FAMC" => {
                     let famc = Family {
-                        adopted_by: None,
-                        husband: None,
-                        wife: None,
                         xref: line.value.to_string(),
-                        notes: vec![],
-                        pedigree: None,
+                        ..Default::default()
                     };
                     birth.family = Some(famc);
                 }
@@ -37,12 +37,8 @@ impl Christening {
             match line.tag {
                 "FAMC" => {
                     let famc = Family {
-                        adopted_by: None,
-                        husband: None,
-                        wife: None,
                         xref: line.value.to_string(),
-                        notes: vec![],
-                        pedigree: None,
+                        ..Default::default()
                     };
                     christening.family = Some(famc);
                 }
@@ -20,11 +20,15 @@ use winnow::prelude::*;
 // n <<MULTIMEDIA_LINK>> {0:M} p.37, 26
 // FAMILY
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct Death {
     pub age: Option<String>,
+    /// None if nothing was recorded about the death, i.e. `1 DEAT Y`
     pub event: Option<EventDetail>,
     pub family: Option<Family>,
+    /// Whether the DEAT line was flagged `Y`: the individual is known to
+    /// have died, even if nothing else about it is
+    pub occurred: bool,
 }
 
 impl Death {
@@ -33,14 +37,11 @@ impl Death {
             age: None,
             event: None,
             family: None,
+            occurred: false,
         };
 
         let line = Line::parse(record).unwrap();
-        // TODO: This implies a death is known but the date is not.
-        // Is this effective as-is? It'll create an empty death record, so
-        // we have Some() in place, where if there is no death tag we would
-        // have None()
-        // 1 DEAT Y
+        death.occurred = line.value.trim().eq_ignore_ascii_case("Y");
         let mut events: Vec<String> = vec![];
 
         // Add the first line so EventDetails will parse cleanly
@@ -68,8 +69,8 @@ impl Death {
             Line::parse(record).unwrap();
         }
 
-        // Now parse the events
-        if !events.is_empty() {
+        // Now parse the events, if there's more than the DEAT line
+        if events.len() > 1 {
             let event = events.join("\n");
             let mut event_str = event.as_str();
             death.event = Some(EventDetail::parse(&mut event_str).unwrap());
@@ -146,5 +147,21 @@ mod tests {
         assert!(death.age.unwrap() == "76y");
 
         assert!(death.family.is_none());
+        assert!(!death.occurred);
+    }
+
+    #[test]
+    fn parse_death_flag() {
+        let mut record = "1 DEAT Y\n1 BURI";
+        let death = Death::parse(&mut record).unwrap();
+        assert!(death.occurred);
+        assert!(death.event.is_none());
+        assert!(record == "1 BURI");
+
+        let mut record = "1 DEAT\n2 AGE 70y";
+        let death = Death::parse(&mut record).unwrap();
+        assert!(!death.occurred);
+        assert!(death.event.is_none());
+        assert!(death.age.as_deref() == Some("70y"));
     }
 }
@@ -4,7 +4,7 @@
 
 use winnow::prelude::*;
 
-use crate::types::{EventDetail, Line};
+use crate::types::{EventDetail, EventType, Line};
 
 // INDIVIDUAL_EVENT_DETAIL:=
 // n <<EVENT_DETAIL>> {1:1} p.32
@@ -13,15 +13,38 @@ use crate::types::{EventDetail, Line};
 #[derive(Clone, Debug, Default)]
 pub struct IndividualEventDetail {
     pub age: Option<String>,
+    /// The value given on the event line itself, i.e. the job in
+    /// `1 OCCU Farmer`, or a description of an EVEN
+    pub value: Option<String>,
+    /// Whether the event line was flagged `Y`, i.e. `1 DEAT Y`: the event is
+    /// known to have happened, even if nothing else about it is
+    pub occurred: bool,
 
     pub detail: EventDetail,
 }
 
+/// One of an individual's events, whichever field it's stored in. See
+/// `Individual::all_events`.
+#[derive(Clone, Copy, Debug)]
+pub struct IndividualEvent<'a> {
+    pub event_type: EventType,
+    /// AGE: How old the individual was at the time
+    pub age: Option<&'a str>,
+    /// See `IndividualEventDetail::value`
+    pub value: Option<&'a str>,
+    /// See `IndividualEventDetail::occurred`
+    pub occurred: bool,
+    /// None for an event recorded without any detail, i.e. `1 DEAT Y`
+    pub detail: Option<&'a EventDetail>,
+}
+
 impl IndividualEventDetail {
     /// Initialize an empty Individual Event Detail
     pub fn new() -> IndividualEventDetail {
         IndividualEventDetail {
             age: None,
+            value: None,
+            occurred: false,
             detail: EventDetail {
                 r#type: None,
                 date: None,
@@ -34,6 +57,11 @@ impl IndividualEventDetail {
                 note: None,
                 sources: vec![],
                 media: vec![],
+                phone: vec![],
+                email: vec![],
+                fax: vec![],
+                www: vec![],
+                associations: vec![],
             },
         }
     }
@@ -41,6 +69,8 @@ impl IndividualEventDetail {
     pub fn parse(record: &mut &str) -> PResult<IndividualEventDetail> {
         let mut event = IndividualEventDetail {
             age: None,
+            value: None,
+            occurred: false,
             detail: EventDetail {
                 r#type: None,
                 date: None,
@@ -53,6 +83,11 @@ impl IndividualEventDetail {
                 note: None,
                 sources: vec![],
                 media: vec![],
+                phone: vec![],
+                email: vec![],
+                fax: vec![],
+                www: vec![],
+                associations: vec![],
             },
         };
 
@@ -62,11 +97,28 @@ impl IndividualEventDetail {
         match line.tag {
             "ADOP" | "BAPM" | "BARM" | "BASM" | "BIRT" | "BLES" | "BURI" | "CENS" | "CHR"
             | "CHRA" | "CONF" | "CREM" | "DEAT" | "EMIG" | "EVEN" | "FCOM" | "GRAD" | "IMMI"
-            | "ORDN" | "PROB" | "NATU" | "RETI" | "WILL" => {
+            | "ORDN" | "PROB" | "NATU" | "RETI" | "WILL"
+            // Individual attributes share the same detail structure
+            | "CAST" | "DSCR" | "EDUC" | "IDNO" | "NATI" | "NCHI" | "NMR" | "OCCU" | "PROP"
+            | "RELI" | "SSN" | "TITL" | "FACT" => {
                 // Consume the line
-                let _ = Line::parse(record);
+                let top = Line::parse(record).unwrap();
+                let value = top.value.trim();
+                if value.eq_ignore_ascii_case("Y") {
+                    event.occurred = true;
+                } else if !value.is_empty() {
+                    event.value = Some(value.to_string());
+                }
+                let top = top.level;
+                if record.is_empty() {
+                    return Ok(event);
+                }
                 // Get the next line
                 line = Line::peek(record).unwrap();
+                // An event with no detail, i.e. `1 DEAT Y`
+                if line.level <= top {
+                    return Ok(event);
+                }
             }
             _ => {}
         }
@@ -74,9 +126,6 @@ impl IndividualEventDetail {
         let level = line.level;
         let mut events: Vec<String> = vec![];
 
-        // Add the first line so EventDetails will parse cleanly
-        events.push(line.to_string());
-
         while !record.is_empty() {
             match line.tag {
                 "AGE" => {
@@ -1,26 +1,32 @@
 use std::str::FromStr;
 
-#[derive(Default, Debug, PartialEq)]
+#[derive(Clone, Default, Debug, PartialEq, Eq, Hash)]
 /// The Gender of the Individual
 pub enum Gender {
     Male,
     Female,
+    /// SEX X in GEDCOM 7, and N in some 5.5.1 exports
     Nonbinary,
     #[default]
     Unknown,
+    /// Any other value, as written in the file
+    Other(String),
 }
 
 impl FromStr for Gender {
-    type Err = ();
+    type Err = std::convert::Infallible;
 
+    /// Parse a SEX value. Case is ignored, and anything unrecognised is kept
+    /// as `Other`, so this never fails.
     fn from_str(input: &str) -> Result<Gender, Self::Err> {
-        match input {
-            "M" => Ok(Gender::Male),
-            "F" => Ok(Gender::Female),
-            "N" => Ok(Gender::Nonbinary),
-            "U" => Ok(Gender::Unknown),
-            _ => Err(()),
-        }
+        let input = input.trim();
+        Ok(match input.to_uppercase().as_str() {
+            "M" | "MALE" => Gender::Male,
+            "F" | "FEMALE" => Gender::Female,
+            "X" | "N" | "NONBINARY" => Gender::Nonbinary,
+            "" | "U" | "UNKNOWN" => Gender::Unknown,
+            _ => Gender::Other(input.to_string()),
+        })
     }
 }
 
@@ -34,7 +40,12 @@ mod tests {
         assert!(Gender::from_str("M").unwrap() == Gender::Male);
         assert!(Gender::from_str("F").unwrap() == Gender::Female);
         assert!(Gender::from_str("N").unwrap() == Gender::Nonbinary);
+        assert!(Gender::from_str("X").unwrap() == Gender::Nonbinary);
         assert!(Gender::from_str("U").unwrap() == Gender::Unknown);
-        assert!(Gender::from_str("X").is_err());
+        assert!(Gender::from_str("").unwrap() == Gender::Unknown);
+        assert!(Gender::from_str("female").unwrap() == Gender::Female);
+        // It can't fail, so there's no error to handle
+        let Ok(other) = Gender::from_str(" Intersex ");
+        assert!(other == Gender::Other("Intersex".to_string()));
     }
 }
@@ -1,9 +1,18 @@
 use std::str::FromStr;
 
+use crate::names;
+use crate::parse;
+use crate::renumber::is_pointer;
 use crate::types::individual::name::*;
-use crate::types::{Family, Line};
+use crate::types::{
+    AttributeType, DateTime, EventDetail, EventType, Family, Gedcom, GedcomAge, GedcomDate, Line,
+    Note, Object, Restriction, SourceCitation,
+};
 
-use super::{Adoption, Birth, Christening, Death, IndividualEventDetail, Residence};
+use super::{
+    current_year, date_year, Adoption, Association, Birth, Christening, Death, IndividualEvent,
+    IndividualEventDetail, Residence, VitalStatus, MAX_LIFESPAN, MIN_PARENT_AGE,
+};
 
 // n @XREF:INDI@ INDI
 // +1 RESN <RESTRICTION_NOTICE>
@@ -27,7 +36,7 @@ use super::{Adoption, Birth, Christening, Death, IndividualEventDetail, Residenc
 // +1 <<CHANGE_DATE>>
 // +1 <<NOTE_STRUCTURE>>
 // +1 <<SOURCE_CITATION>> +1 <<MULTIMEDIA_LINK>>
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct Individual {
     pub adoption: Vec<Adoption>,
 
@@ -82,6 +91,40 @@ pub struct Individual {
 
     pub naturalization: Vec<IndividualEventDetail>,
 
+    /// OCCU: Occupations, each with the job as its value
+    pub occupations: Vec<IndividualEventDetail>,
+
+    // Other attributes, each with its value, i.e. the title in `1 TITL Duke`
+    /// CAST: Caste
+    pub caste: Vec<IndividualEventDetail>,
+
+    /// DSCR: Physical descriptions
+    pub physical_description: Vec<IndividualEventDetail>,
+
+    /// EDUC: Education, i.e. schools attended or degrees earned
+    pub education: Vec<IndividualEventDetail>,
+
+    /// IDNO: National ID numbers, each with its TYPE
+    pub id_numbers: Vec<IndividualEventDetail>,
+
+    /// NATI: Nationality or tribal origin
+    pub nationality: Vec<IndividualEventDetail>,
+
+    /// NCHI: How many children they had
+    pub children_count: Vec<IndividualEventDetail>,
+
+    /// NMR: How many times they married
+    pub marriage_count: Vec<IndividualEventDetail>,
+
+    /// PROP: Property or possessions
+    pub property: Vec<IndividualEventDetail>,
+
+    /// RELI: Religious affiliation
+    pub religion: Vec<IndividualEventDetail>,
+
+    /// TITL: Titles, i.e. of nobility
+    pub titles: Vec<IndividualEventDetail>,
+
     pub probate: Vec<IndividualEventDetail>,
 
     // RETI: Retirement
@@ -89,8 +132,46 @@ pub struct Individual {
 
     pub will: Vec<IndividualEventDetail>,
 
+    /// NOTE: Notes on the individual, or pointers to NOTE records
+    pub notes: Vec<Note>,
+
+    /// OBJE: Photos and other files of the individual
+    pub media: Vec<Object>,
+
+    /// SOUR: Sources for the individual as a whole, rather than any one
+    /// event
+    pub sources: Vec<SourceCitation>,
+
+    /// ASSO: Godparents, witnesses and others who weren't family
+    pub associations: Vec<Association>,
+
+    /// ALIA: Other records for the same person, i.e. where research turned
+    /// up someone who was only later found to be them. See
+    /// `Gedcom::resolve_aliases`.
+    pub aliases: Vec<String>,
+
+    /// SUBM: The submitters who contributed the record. See
+    /// `Gedcom::records_submitted_by`.
+    pub submitters: Vec<String>,
+
+    /// ANCI: Submitters interested in the individual's ancestors
+    pub ancestor_interest: Vec<String>,
+
+    /// DESI: Submitters interested in the individual's descendants
+    pub descendant_interest: Vec<String>,
+
     /// The XRef pointer associated with this individual
     pub xref: Option<String>,
+
+    /// RESN: Who may see or change the record. See `restrictions`.
+    pub restriction_notice: Option<String>,
+
+    /// CHAN: When the record was last changed
+    pub change_date: Option<DateTime>,
+
+    /// UID or _UID: Identifiers that stay with the record from program to
+    /// program, unlike its xref
+    pub uids: Vec<String>,
 }
 
 // impl<'a> Individual<'a> {
@@ -98,7 +179,6 @@ impl Individual {
     pub fn parse(record: &mut &str) -> Individual {
         // pub fn parse(mut record: String) -> Individual {
         let mut individual = Individual {
-            // sources: vec![],
             adoption: vec![],
             birth: vec![],
             burial: vec![],
@@ -123,12 +203,34 @@ impl Individual {
             names: vec![],
 
             naturalization: vec![],
+            occupations: vec![],
+            caste: vec![],
+            physical_description: vec![],
+            education: vec![],
+            id_numbers: vec![],
+            nationality: vec![],
+            children_count: vec![],
+            marriage_count: vec![],
+            property: vec![],
+            religion: vec![],
+            titles: vec![],
             probate: vec![],
             residences: vec![],
             retirement: vec![],
             will: vec![],
+            notes: vec![],
+            media: vec![],
+            sources: vec![],
+            associations: vec![],
+            aliases: vec![],
+            submitters: vec![],
+            ancestor_interest: vec![],
+            descendant_interest: vec![],
 
             xref: None,
+            restriction_notice: None,
+            change_date: None,
+            uids: vec![],
         };
 
         while !record.is_empty() {
@@ -148,8 +250,12 @@ impl Individual {
                             individual.names.push(pn);
                             parse = false;
                         }
+                        "RESN" => {
+                            individual.restriction_notice = Some(line.value.to_string());
+                        }
                         "SEX" => {
-                            individual.gender = super::Gender::from_str(line.value).unwrap();
+                            let Ok(gender) = super::Gender::from_str(line.value);
+                            individual.gender = gender;
                         }
                         "BIRT" => {
                             let birth = Birth::parse(record).unwrap();
@@ -157,19 +263,24 @@ impl Individual {
                             parse = false;
                         }
                         "DEAT" => {
-                            // TODO: Support 1 DEAT Y
                             let death = Death::parse(record).unwrap();
                             individual.death.push(death);
                             parse = false;
                         }
                         "FAMS" => {
                             let fam = Family::parse(record);
-                            individual.fams.push(fam);
+                            // Without a pointer, it links to nothing
+                            if !fam.xref.is_empty() {
+                                individual.fams.push(fam);
+                            }
                             parse = false;
                         }
                         "FAMC" => {
                             let fam = Family::parse(record);
-                            individual.famc.push(fam);
+                            // Without a pointer, it links to nothing
+                            if !fam.xref.is_empty() {
+                                individual.famc.push(fam);
+                            }
                             parse = false;
                         }
                         // baptism
@@ -295,40 +406,111 @@ impl Individual {
                         // occupation
                         "OCCU" => {
                             let occupation = IndividualEventDetail::parse(record).unwrap();
-                            individual.events.push(occupation);
+                            individual.occupations.push(occupation);
+                            parse = false;
+                        }
+                        "CAST" => {
+                            let attribute = IndividualEventDetail::parse(record).unwrap();
+                            individual.caste.push(attribute);
+                            parse = false;
+                        }
+                        "DSCR" => {
+                            let attribute = IndividualEventDetail::parse(record).unwrap();
+                            individual.physical_description.push(attribute);
+                            parse = false;
+                        }
+                        "EDUC" => {
+                            let attribute = IndividualEventDetail::parse(record).unwrap();
+                            individual.education.push(attribute);
+                            parse = false;
+                        }
+                        "IDNO" => {
+                            let attribute = IndividualEventDetail::parse(record).unwrap();
+                            individual.id_numbers.push(attribute);
+                            parse = false;
+                        }
+                        "NATI" => {
+                            let attribute = IndividualEventDetail::parse(record).unwrap();
+                            individual.nationality.push(attribute);
+                            parse = false;
+                        }
+                        "NCHI" => {
+                            let attribute = IndividualEventDetail::parse(record).unwrap();
+                            individual.children_count.push(attribute);
+                            parse = false;
+                        }
+                        "NMR" => {
+                            let attribute = IndividualEventDetail::parse(record).unwrap();
+                            individual.marriage_count.push(attribute);
+                            parse = false;
+                        }
+                        "PROP" => {
+                            let attribute = IndividualEventDetail::parse(record).unwrap();
+                            individual.property.push(attribute);
+                            parse = false;
+                        }
+                        "RELI" => {
+                            let attribute = IndividualEventDetail::parse(record).unwrap();
+                            individual.religion.push(attribute);
+                            parse = false;
+                        }
+                        "TITL" => {
+                            let attribute = IndividualEventDetail::parse(record).unwrap();
+                            individual.titles.push(attribute);
+                            parse = false;
+                        }
+                        "NOTE" => {
+                            if let Some(note) = parse::get_tag_value(record).unwrap() {
+                                individual.notes.push(Note { note: Some(note) });
+                            }
                             parse = false;
                         }
-                        "EDUC" => {}
-                        // physical description
-                        "DSCR" => {}
-                        // religion
-                        "RELI" => {}
-                        // national identification number
-                        "IDNO" => {}
-                        // property/possessions
-                        "PROP" => {}
-                        // cast(e) name?
-                        "CAST" => {}
-                        // number of children
-                        "NCHI" => {}
-                        // number of marriages
-                        "NMR" => {}
-                        // nobility title
-                        "TITL" => {}
-                        // national or tribe origin
-                        "NATI" => {}
-                        "NOTE" => {}
                         // source records
-                        "SOUR" => {}
+                        "SOUR" => {
+                            individual
+                                .sources
+                                .push(SourceCitation::parse(record).unwrap());
+                            parse = false;
+                        }
                         // multimedia links
-                        "OBJE" => {}
-                        "ASSO" => {}
+                        "OBJE" => {
+                            individual.media.push(Object::parse(record).unwrap());
+                            parse = false;
+                        }
+                        "ASSO" => {
+                            let association = Association::parse(record).unwrap();
+                            if !association.xref.is_empty() {
+                                individual.associations.push(association);
+                            }
+                            parse = false;
+                        }
+                        // Some programs put another name here, rather than a
+                        // pointer, which says nothing about other records
+                        "ALIA" if is_pointer(line.value) => {
+                            individual.aliases.push(line.value.to_string());
+                        }
+                        "SUBM" if is_pointer(line.value) => {
+                            individual.submitters.push(line.value.to_string());
+                        }
+                        "ANCI" if is_pointer(line.value) => {
+                            individual.ancestor_interest.push(line.value.to_string());
+                        }
+                        "DESI" if is_pointer(line.value) => {
+                            individual.descendant_interest.push(line.value.to_string());
+                        }
                         "REFN" => {}
                         "RIN" => {}
-                        "CHAN" => {}
-                        _ => {
-                            println!("Unknown Individual tag: {:?}", line.tag);
+                        "UID" | "_UID" => {
+                            individual.uids.push(line.value.to_string());
                         }
+                        "CHAN" => {
+                            Line::parse(record).unwrap();
+                            let (buffer, change_date) = DateTime::parse(record);
+                            *record = buffer;
+                            individual.change_date = change_date;
+                            parse = false;
+                        }
+                        _ => {}
                     }
                 }
                 _ => {}
@@ -341,9 +523,350 @@ impl Individual {
 
         individual
     }
+
+    /// The restrictions in the record's RESN, i.e. that it's confidential
+    pub fn restrictions(&self) -> Vec<Restriction> {
+        Restriction::parse_list(self.restriction_notice.as_deref().unwrap_or_default())
+    }
+
+    /// Derive whether this individual is living or deceased.
+    ///
+    /// Any death, burial, or cremation event (including a bare `1 DEAT Y`)
+    /// means the individual is deceased. Otherwise, we fall back to age
+    /// heuristics: anyone born, or active, more than `MAX_LIFESPAN` years ago
+    /// is presumed dead, as is anyone whose child was born long enough ago
+    /// that the parent would now be older than that.
+    pub fn vital_status(&self, gedcom: &Gedcom) -> VitalStatus {
+        if !self.death.is_empty() || !self.burial.is_empty() || !self.cremation.is_empty() {
+            return VitalStatus::Deceased;
+        }
+
+        let now = current_year();
+
+        if let Some(year) = self.birth_year() {
+            if now - year > MAX_LIFESPAN {
+                return VitalStatus::Deceased;
+            }
+            return VitalStatus::Living;
+        }
+
+        // Without a birth date, the earliest thing we know the individual
+        // did tells us they were alive (and at least born) by then.
+        if let Some(year) = self.event_years().into_iter().min() {
+            if now - year > MAX_LIFESPAN {
+                return VitalStatus::Deceased;
+            }
+        }
+
+        // A parent is at least MIN_PARENT_AGE years older than their children.
+        for child in gedcom.children(self) {
+            if let Some(year) = child.birth_year() {
+                if now - (year - MIN_PARENT_AGE) > MAX_LIFESPAN {
+                    return VitalStatus::Deceased;
+                }
+            }
+        }
+
+        VitalStatus::Unknown
+    }
+
+    /// The year of birth, falling back to christening or baptism.
+    pub fn birth_year(&self) -> Option<i32> {
+        self.birth
+            .iter()
+            .filter_map(|b| b.event.detail.date.as_deref())
+            .chain(
+                self.christening
+                    .iter()
+                    .filter_map(|c| c.event.detail.date.as_deref()),
+            )
+            .chain(self.baptism.iter().filter_map(|b| b.detail.date.as_deref()))
+            .find_map(date_year)
+    }
+
+    /// The year of death, falling back to burial or cremation.
+    pub fn death_year(&self) -> Option<i32> {
+        self.death
+            .iter()
+            .filter_map(|d| d.event.as_ref().and_then(|e| e.date.as_deref()))
+            .chain(self.burial.iter().filter_map(|b| b.detail.date.as_deref()))
+            .chain(
+                self.cremation
+                    .iter()
+                    .filter_map(|c| c.detail.date.as_deref()),
+            )
+            .find_map(date_year)
+    }
+
+    /// The date of the first dated birth, as written in the file
+    pub fn birth_date(&self) -> Option<&str> {
+        self.birth
+            .iter()
+            .find_map(|b| b.event.detail.date.as_deref())
+    }
+
+    /// The date of the first dated death, as written in the file
+    pub fn death_date(&self) -> Option<&str> {
+        self.death
+            .iter()
+            .find_map(|d| d.event.as_ref()?.date.as_deref())
+    }
+
+    /// The first name, without the slashes around the surname, i.e.
+    /// "Joe Bloggs"
+    pub fn primary_name(&self) -> Option<String> {
+        let name = self.names.first()?.name.value.as_deref()?;
+        Some(names::strip_slashes(name))
+    }
+
+    /// The years of birth and death, i.e. "1881–1952", "1881–" while the
+    /// death is unknown, or "–1952" while the birth is. Empty if neither is
+    /// known.
+    pub fn lifespan_string(&self) -> String {
+        match (self.birth_year(), self.death_year()) {
+            (None, None) => String::new(),
+            (birth, death) => format!(
+                "{}–{}",
+                birth.map(|y| y.to_string()).unwrap_or_default(),
+                death.map(|y| y.to_string()).unwrap_or_default()
+            ),
+        }
+    }
+
+    /// The parents, from every family this individual is a child of
+    pub fn parents<'a>(&self, gedcom: &'a Gedcom) -> Vec<&'a Individual> {
+        gedcom.parents(self)
+    }
+
+    /// The families this individual is a spouse in
+    pub fn spouse_families<'a>(&self, gedcom: &'a Gedcom) -> Vec<&'a Family> {
+        gedcom.spouse_families(self)
+    }
+
+    /// How old the individual was, or would have been, on `date`, counting
+    /// from their birth. Their christening or baptism stands in for an
+    /// undated birth, making the age approximate. None if none of these is
+    /// dated, or `date` is before it.
+    pub fn age_at(&self, date: &GedcomDate) -> Option<GedcomAge> {
+        let birth = self
+            .birth
+            .iter()
+            .filter_map(|b| b.event.detail.date.as_deref())
+            .find_map(|d| GedcomDate::from_str(d).ok());
+        let (birth, approximate) = match birth {
+            Some(birth) => (birth, false),
+            None => {
+                let baptism = self
+                    .christening
+                    .iter()
+                    .filter_map(|c| c.event.detail.date.as_deref())
+                    .chain(self.baptism.iter().filter_map(|b| b.detail.date.as_deref()))
+                    .find_map(|d| GedcomDate::from_str(d).ok())?;
+                (baptism, true)
+            }
+        };
+
+        let mut age = GedcomAge::between_dates(&birth, date)?;
+        age.approximate |= approximate;
+        Some(age)
+    }
+
+    /// How old the individual was when they died: worked out from their
+    /// birth and death dates where both are known, and otherwise the AGE
+    /// recorded with their death.
+    pub fn age_at_death(&self) -> Option<GedcomAge> {
+        let death = self
+            .death
+            .iter()
+            .filter_map(|d| d.event.as_ref()?.date.as_deref())
+            .find_map(|d| GedcomDate::from_str(d).ok());
+        death.and_then(|d| self.age_at(&d)).or_else(|| {
+            self.death
+                .iter()
+                .find_map(|d| GedcomAge::from_str(d.age.as_deref()?).ok())
+        })
+    }
+
+    /// A stable, URL-safe identifier for the individual, i.e.
+    /// "joe-bloggs-1900-977f8b": their name, year of birth, and a short hash
+    /// of their xref, so links to them survive re-exporting the tree. With
+    /// the `romanize` feature, names in other scripts are transliterated
+    /// rather than dropped.
+    pub fn slug(&self) -> String {
+        let name = self
+            .names
+            .first()
+            .and_then(|n| n.name.value.as_deref())
+            .unwrap_or_default();
+        #[cfg(feature = "romanize")]
+        let name = crate::romanize::romanize(name);
+
+        let mut slug = String::new();
+        for word in name
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|w| !w.is_empty())
+        {
+            slug += &word.to_ascii_lowercase();
+            slug.push('-');
+        }
+        if slug.is_empty() {
+            slug += "unknown-";
+        }
+        match self.birth_year() {
+            Some(year) if year < 0 => slug += &format!("{}bc-", -year),
+            Some(year) => slug += &format!("{}-", year),
+            None => {}
+        }
+        let hash = fnv1a(self.xref.as_deref().unwrap_or_default().as_bytes());
+        slug + &format!("{:06x}", hash & 0xff_ffff)
+    }
+
+    /// Every event, whichever field it's stored in, in the order of
+    /// `tagged_events`.
+    pub fn all_events<'a>(&'a self) -> impl Iterator<Item = IndividualEvent<'a>> {
+        let event = |event_type, e: &'a IndividualEventDetail| IndividualEvent {
+            event_type,
+            age: e.age.as_deref(),
+            value: e.value.as_deref(),
+            occurred: e.occurred,
+            // As with a death, nothing recorded but the event line is no detail
+            detail: (e.detail != EventDetail::default()).then_some(&e.detail),
+        };
+        let mut events: Vec<IndividualEvent> = vec![];
+
+        events.extend(self.birth.iter().map(|b| event(EventType::Birth, &b.event)));
+        events.extend(
+            self.adoption
+                .iter()
+                .map(|a| event(EventType::Adoption, &a.event)),
+        );
+        events.extend(
+            self.christening
+                .iter()
+                .map(|c| event(EventType::Christening, &c.event)),
+        );
+        events.extend(
+            self.christening_adult
+                .iter()
+                .map(|c| event(EventType::AdultChristening, &c.event)),
+        );
+
+        let details = [
+            (EventType::Baptism, &self.baptism),
+            (EventType::BarMitzvah, &self.barmitzvah),
+            (EventType::BasMitzvah, &self.basmitzvah),
+            (EventType::Blessing, &self.blessing),
+            (EventType::Census, &self.census),
+            (EventType::Confirmation, &self.confirmation),
+            (EventType::Emigration, &self.emigration),
+            (EventType::Event, &self.events),
+            (EventType::Graduation, &self.graduation),
+            (EventType::Immigration, &self.immigration),
+            (EventType::Naturalization, &self.naturalization),
+            (EventType::Occupation, &self.occupations),
+            (EventType::Probate, &self.probate),
+            (EventType::Retirement, &self.retirement),
+            (EventType::Will, &self.will),
+            (EventType::Attribute(AttributeType::Caste), &self.caste),
+            (
+                EventType::Attribute(AttributeType::Description),
+                &self.physical_description,
+            ),
+            (
+                EventType::Attribute(AttributeType::Education),
+                &self.education,
+            ),
+            (
+                EventType::Attribute(AttributeType::IdNumber),
+                &self.id_numbers,
+            ),
+            (
+                EventType::Attribute(AttributeType::Nationality),
+                &self.nationality,
+            ),
+            (
+                EventType::Attribute(AttributeType::ChildCount),
+                &self.children_count,
+            ),
+            (
+                EventType::Attribute(AttributeType::MarriageCount),
+                &self.marriage_count,
+            ),
+            (
+                EventType::Attribute(AttributeType::Property),
+                &self.property,
+            ),
+            (
+                EventType::Attribute(AttributeType::Religion),
+                &self.religion,
+            ),
+            (EventType::Attribute(AttributeType::Title), &self.titles),
+        ];
+        for (event_type, details) in details {
+            events.extend(details.iter().map(|e| event(event_type, e)));
+        }
+        events.extend(
+            self.first_communion
+                .iter()
+                .map(|e| event(EventType::FirstCommunion, e)),
+        );
+        events.extend(self.residences.iter().map(|r| IndividualEvent {
+            event_type: EventType::Residence,
+            age: None,
+            value: None,
+            occurred: r.detail.as_ref().is_some_and(|d| d.occurred),
+            detail: r.detail.as_ref().and_then(|d| d.detail.as_ref()),
+        }));
+
+        events.extend(self.death.iter().map(|d| IndividualEvent {
+            event_type: EventType::Death,
+            age: d.age.as_deref(),
+            value: None,
+            occurred: d.occurred,
+            detail: d.event.as_ref(),
+        }));
+        events.extend(self.burial.iter().map(|b| event(EventType::Burial, b)));
+        events.extend(
+            self.cremation
+                .iter()
+                .map(|c| event(EventType::Cremation, c)),
+        );
+
+        events.into_iter()
+    }
+
+    /// Every event, by its GEDCOM tag, whichever field it's stored in.
+    /// Events recorded without any detail, i.e. `1 BIRT Y` or `1 DEAT Y`,
+    /// have none.
+    pub fn tagged_events(&self) -> Vec<(&'static str, Option<&EventDetail>)> {
+        self.all_events()
+            .map(|e| (e.event_type.tag(), e.detail))
+            .collect()
+    }
+
+    /// The years of every dated event that implies the individual was alive.
+    fn event_years(&self) -> Vec<i32> {
+        self.all_events()
+            .filter(|e| {
+                !matches!(
+                    e.event_type,
+                    EventType::Birth
+                        | EventType::Christening
+                        | EventType::Baptism
+                        | EventType::Residence
+                        | EventType::Probate
+                        | EventType::Death
+                        | EventType::Burial
+                        | EventType::Cremation
+                )
+            })
+            .filter_map(|e| e.detail?.date.as_deref())
+            .filter_map(date_year)
+            .collect()
+    }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 /// The type of the name.
 ///
 /// Not sure when/where to use this yet but I wanted to capture it from the spec.
@@ -356,10 +879,185 @@ pub enum NameType {
     Other,
 }
 
+/// The 32-bit FNV-1a hash, which unlike `std`'s hasher is the same on
+/// every platform and Rust version
+fn fnv1a(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c_9dc5, |hash, byte| {
+        (hash ^ u32::from(*byte)).wrapping_mul(0x0100_0193)
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{place, AdoptedBy, Quay};
+    use crate::types::{AdoptedBy, Quay};
+
+    #[test]
+    fn all_events() {
+        let individual = Individual::parse(
+            &mut "0 @I1@ INDI\n1 NAME Joe /Bloggs/\n1 DEAT\n2 AGE 70y\n2 DATE 1970\n1 BIRT\n2 DATE 1900\n1 GRAD\n2 AGE 21y\n1 RESI\n2 DATE 1911\n",
+        );
+        let events: Vec<(EventType, Option<&str>, Option<&str>)> = individual
+            .all_events()
+            .map(|e| {
+                (
+                    e.event_type,
+                    e.age,
+                    e.detail.and_then(|d| d.date.as_deref()),
+                )
+            })
+            .collect();
+        assert!(
+            events
+                == [
+                    (EventType::Birth, None, Some("1900")),
+                    (EventType::Graduation, Some("21y"), None),
+                    (EventType::Residence, None, Some("1911")),
+                    (EventType::Death, Some("70y"), Some("1970")),
+                ]
+        );
+        assert!(individual.tagged_events()[1].0 == "GRAD");
+        assert!(individual.all_events().all(|e| !e.occurred));
+
+        // Known to have happened, with no details
+        let individual = Individual::parse(&mut "0 @I1@ INDI\n1 BIRT Y\n1 DEAT Y\n");
+        let events: Vec<(EventType, bool, bool)> = individual
+            .all_events()
+            .map(|e| (e.event_type, e.occurred, e.detail.is_some()))
+            .collect();
+        assert!(
+            events
+                == [
+                    (EventType::Birth, true, false),
+                    (EventType::Death, true, false)
+                ]
+        );
+    }
+
+    #[test]
+    fn attributes() {
+        let individual = Individual::parse(
+            &mut "0 @I1@ INDI\n1 TITL Duke\n1 EDUC Oxford\n2 DATE 1820\n1 RELI Quaker\n1 NCHI 3\n1 IDNO 123-45\n2 TYPE Passport\n1 NMR 1\n1 DSCR Tall\n1 PROP Farm\n1 CAST Brahmin\n1 NATI Welsh\n",
+        );
+        assert!(individual.titles[0].value.as_deref() == Some("Duke"));
+        assert!(individual.education[0].detail.date.as_deref() == Some("1820"));
+        assert!(individual.religion[0].value.as_deref() == Some("Quaker"));
+        assert!(individual.children_count[0].value.as_deref() == Some("3"));
+        assert!(individual.id_numbers[0].detail.r#type.as_deref() == Some("Passport"));
+        assert!(individual.marriage_count.len() == 1);
+        assert!(individual.physical_description.len() == 1);
+        assert!(individual.property.len() == 1);
+        assert!(individual.caste.len() == 1);
+        assert!(individual.nationality[0].value.as_deref() == Some("Welsh"));
+
+        let tags: Vec<&str> = individual
+            .tagged_events()
+            .into_iter()
+            .map(|(tag, _)| tag)
+            .collect();
+        assert!(
+            tags == ["CAST", "DSCR", "EDUC", "IDNO", "NATI", "NCHI", "NMR", "PROP", "RELI", "TITL"]
+        );
+        let title = individual
+            .all_events()
+            .find(|e| e.event_type == EventType::Attribute(AttributeType::Title))
+            .unwrap();
+        assert!(title.value == Some("Duke") && title.detail.is_none());
+    }
+
+    #[test]
+    fn age_at() {
+        let individual = Individual::parse(
+            &mut "0 @I1@ INDI\n1 BIRT\n2 DATE 12 MAR 1900\n1 DEAT\n2 DATE 3 JAN 1970\n2 AGE 70y\n",
+        );
+        let age = individual
+            .age_at(&GedcomDate::from_str("ABT 1950").unwrap())
+            .unwrap();
+        assert!(age.years == Some(50) && age.approximate);
+        assert!(individual.age_at_death().unwrap().to_string() == "69y 9m 22d");
+        assert!(individual
+            .age_at(&GedcomDate::from_str("1899").unwrap())
+            .is_none());
+
+        // Without a birth date, the age recorded at death
+        let individual =
+            Individual::parse(&mut "0 @I1@ INDI\n1 CHR\n2 DATE 1900\n1 DEAT\n2 AGE > 76y 3m\n");
+        assert!(individual.age_at_death().unwrap().to_string() == "> 76y 3m");
+        let age = individual
+            .age_at(&GedcomDate::from_str("1910").unwrap())
+            .unwrap();
+        assert!(age.years == Some(10) && age.approximate);
+    }
+
+    #[test]
+    fn slug() {
+        let mut individual = Individual::parse(
+            &mut "0 @I1@ INDI\n1 NAME Jean-Luc /O'Brien/\n1 BIRT\n2 DATE 1 JAN 1900\n",
+        );
+        let slug = individual.slug();
+        assert!(slug.starts_with("jean-luc-o-brien-1900-"));
+        assert!(slug.len() == "jean-luc-o-brien-1900-".len() + 6);
+        assert!(slug == individual.slug());
+
+        individual.xref = Some("@I2@".to_string());
+        assert!(individual.slug() != slug);
+
+        let nameless = Individual::parse(&mut "0 @I3@ INDI\n");
+        assert!(nameless.slug().starts_with("unknown-"));
+        assert!(fnv1a(b"") == 0x811c_9dc5 && fnv1a(b"a") == 0xe40c_292c);
+    }
+
+    #[test]
+    fn display_helpers() {
+        let individual = Individual::parse(
+            &mut "0 @I1@ INDI\n1 NAME  Joe   /Bloggs/\n1 BIRT\n2 PLAC Here\n1 BIRT\n2 DATE 5 MAY 1881\n1 DEAT\n2 DATE 1952\n",
+        );
+        assert!(individual.primary_name().as_deref() == Some("Joe Bloggs"));
+        assert!(individual.birth_date() == Some("5 MAY 1881"));
+        assert!(individual.death_date() == Some("1952"));
+        assert!(individual.lifespan_string() == "1881–1952");
+
+        // A christening stands in for the birth
+        let individual = Individual::parse(&mut "0 @I2@ INDI\n1 CHR\n2 DATE 1900\n");
+        assert!(individual.birth_date().is_none());
+        assert!(individual.lifespan_string() == "1900–");
+        assert!(individual.primary_name().is_none());
+
+        let individual = Individual::parse(&mut "0 @I3@ INDI\n1 DEAT Y\n");
+        assert!(individual.death_date().is_none());
+        assert!(individual.lifespan_string().is_empty());
+    }
+
+    #[test]
+    fn navigate() {
+        let data = "0 HEAD
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 FAMS @F1@
+0 @I2@ INDI
+1 NAME Ann /Bloggs/
+1 FAMS @F1@
+0 @I3@ INDI
+1 NAME Sam /Bloggs/
+1 FAMC @F1@
+0 @F1@ FAM
+1 HUSB @I1@
+1 WIFE @I2@
+1 CHIL @I3@
+0 TRLR
+";
+        let gedcom = crate::parse::parse_gedcom_from_str(data).unwrap();
+        let sam = &gedcom.individuals[2];
+        let parents: Vec<&str> = sam
+            .parents(&gedcom)
+            .iter()
+            .filter_map(|p| p.xref.as_deref())
+            .collect();
+        assert!(parents == ["@I1@", "@I2@"]);
+        assert!(sam.spouse_families(&gedcom).is_empty());
+        let families = gedcom.individuals[0].spouse_families(&gedcom);
+        assert!(families.len() == 1 && families[0].xref == "@F1@");
+    }
 
     #[test]
     fn parse_indi_baptism() {
@@ -1078,8 +1776,8 @@ mod tests {
         assert_eq!(Some("Torture"), indi.names[0].name.surname.as_deref());
         assert_eq!(Some("Joe"), indi.names[0].name.nickname.as_deref());
         assert_eq!(Some("Prof."), indi.names[0].name.prefix.as_deref());
-        assert_eq!(Some("Le"), indi.names[0].name.suffix.as_deref());
-        assert_eq!(Some("Jr."), indi.names[0].name.surname_prefix.as_deref());
+        assert_eq!(Some("Jr."), indi.names[0].name.suffix.as_deref());
+        assert_eq!(Some("Le"), indi.names[0].name.surname_prefix.as_deref());
         assert_eq!(Some("birth"), indi.names[0].name.r#type.as_deref());
 
         // Check the indi.names[0].romanized
@@ -1091,9 +1789,9 @@ mod tests {
         assert_eq!(Some("Torture"), indi.names[0].romanized.surname.as_deref());
         assert_eq!(Some("Joe"), indi.names[0].romanized.nickname.as_deref());
         assert_eq!(Some("Prof."), indi.names[0].romanized.prefix.as_deref());
-        assert_eq!(Some("Le"), indi.names[0].romanized.suffix.as_deref());
+        assert_eq!(Some("Jr."), indi.names[0].romanized.suffix.as_deref());
         assert_eq!(
-            Some("Jr."),
+            Some("Le"),
             indi.names[0].romanized.surname_prefix.as_deref()
         );
         assert_eq!(
@@ -1110,11 +1808,8 @@ mod tests {
         assert_eq!(Some("Torture"), indi.names[0].phonetic.surname.as_deref());
         assert_eq!(Some("Joe"), indi.names[0].phonetic.nickname.as_deref());
         assert_eq!(Some("Prof."), indi.names[0].phonetic.prefix.as_deref());
-        assert_eq!(Some("Le"), indi.names[0].phonetic.suffix.as_deref());
-        assert_eq!(
-            Some("Jr."),
-            indi.names[0].phonetic.surname_prefix.as_deref()
-        );
+        assert_eq!(Some("Jr."), indi.names[0].phonetic.suffix.as_deref());
+        assert_eq!(Some("Le"), indi.names[0].phonetic.surname_prefix.as_deref());
         assert_eq!(
             Some("user defined"),
             indi.names[0].phonetic.r#type.as_deref()
@@ -1162,7 +1857,7 @@ mod tests {
 
         let mut source = event.detail.sources.pop().unwrap();
         assert!(source.xref.unwrap() == "@S1@");
-        assert!(source.page.unwrap() == 42);
+        assert!(source.page.unwrap() == "42");
 
         let sdata = source.data.unwrap();
         assert!(sdata.date.unwrap() == "1 JAN 1900");
@@ -1226,7 +1921,7 @@ mod tests {
         assert!(source.xref.unwrap() == "@S1@");
 
         // "3 PAGE 42",
-        assert!(source.page.unwrap() == 42);
+        assert!(source.page.unwrap() == "42");
 
         // "3 DATA",
         let sdata = source.data.unwrap();
@@ -1302,10 +1997,10 @@ mod tests {
         let source = sources.pop().unwrap();
 
         // "2 SOUR @S1@",
-        assert!(source.xref.unwrap() == "@S1@".to_string());
+        assert!(source.xref.unwrap() == "@S1@");
 
         // "3 PAGE 42",
-        assert!(source.page.unwrap() == 42);
+        assert!(source.page.unwrap() == "42");
 
         // "3 DATA",
         let sdata = source.data.unwrap();
@@ -1349,7 +2044,7 @@ mod tests {
         assert!(source.xref.unwrap() == "@S1@");
 
         // "3 PAGE 42",
-        assert!(source.page.unwrap() == 42);
+        assert!(source.page.unwrap() == "42");
 
         // "3 DATA",
         let data = source.data.unwrap();
@@ -1370,7 +2065,7 @@ mod tests {
         assert!(chr.event.detail.note.unwrap() == "Christening event note (the religious event (not LDS) of baptizing and/or naming a child).");
 
         // "2 FAMC @F3@",
-        assert!(chr.family.unwrap().xref == "@F3@".to_string());
+        assert!(chr.family.unwrap().xref == "@F3@");
 
         // "1 BARM",
         let barm = indi.barmitzvah.first().unwrap().clone();
@@ -1387,7 +2082,7 @@ mod tests {
         // "2 SOUR @S1@",
         assert!(source.xref.unwrap() == "@S1@");
         // "3 PAGE 42",
-        assert!(source.page.unwrap() == 42);
+        assert!(source.page.unwrap() == "42");
 
         // "3 DATA",
         let sdata = source.data.unwrap();
@@ -1425,7 +2120,7 @@ mod tests {
         // "2 SOUR @S1@",
         assert!(source.xref.unwrap() == "@S1@");
         // "3 PAGE 42",
-        assert!(source.page.unwrap() == 42);
+        assert!(source.page.unwrap() == "42");
 
         // "3 DATA",
         let sdata = source.data.unwrap();
@@ -1463,7 +2158,7 @@ mod tests {
         assert!(source.xref.unwrap() == "@S1@");
 
         // "3 PAGE 42",
-        assert!(source.page.unwrap() == 42);
+        assert!(source.page.unwrap() == "42");
 
         // "3 DATA",
         let sdata = source.data.unwrap();
@@ -1508,7 +2203,7 @@ mod tests {
         assert!(source.xref.unwrap() == "@S1@");
 
         // "3 PAGE 42",
-        assert!(source.page.unwrap() == 42);
+        assert!(source.page.unwrap() == "42");
 
         // "3 DATA",
         let data = source.data.unwrap();
@@ -1545,7 +2240,7 @@ mod tests {
         assert!(source.xref.unwrap() == "@S1@");
 
         // "3 PAGE 42",
-        assert!(source.page.unwrap() == 42);
+        assert!(source.page.unwrap() == "42");
 
         // "3 DATA",
         let sdata = source.data.unwrap();
@@ -1568,4 +2263,56 @@ mod tests {
         // First Communion
         assert!(indi.first_communion.is_some());
     }
+
+    #[test]
+    fn vital_status() {
+        let records = [
+            vec!["0 @I1@ INDI", "1 NAME Known /Dead/", "1 DEAT Y"],
+            vec![
+                "0 @I2@ INDI",
+                "1 NAME Long /Ago/",
+                "1 BIRT",
+                "2 DATE 12 FEB 1840",
+            ],
+            vec![
+                "0 @I3@ INDI",
+                "1 NAME Quite /Young/",
+                "1 BIRT",
+                "2 DATE 1 JAN 2001",
+            ],
+            vec!["0 @I4@ INDI", "1 NAME No /Data/"],
+            vec!["0 @I5@ INDI", "1 NAME Old /Parent/", "1 FAMS @F1@"],
+            // Listed as a child of the family, without a FAMC link back
+            vec!["0 @I6@ INDI", "1 NAME Old /Child/", "1 BIRT", "2 DATE 1850"],
+            vec![
+                "0 @I7@ INDI",
+                "1 NAME Buried /Person/",
+                "1 BURI",
+                "2 PLAC Somewhere",
+            ],
+            vec!["0 @F1@ FAM", "1 HUSB @I5@", "1 CHIL @I6@"],
+        ];
+
+        let mut text = String::from("0 HEAD\n");
+        for record in records.iter() {
+            text += &record.join("\n");
+            text += "\n";
+        }
+        text += "0 TRLR\n";
+        let gedcom = crate::parse::parse_gedcom_from_str(&text).unwrap();
+
+        let status: Vec<VitalStatus> = gedcom
+            .individuals
+            .iter()
+            .map(|i| i.vital_status(&gedcom))
+            .collect();
+
+        assert!(status[0] == VitalStatus::Deceased);
+        assert!(status[1] == VitalStatus::Deceased);
+        assert!(status[2] == VitalStatus::Living);
+        assert!(status[3] == VitalStatus::Unknown);
+        assert!(status[4] == VitalStatus::Deceased);
+        assert!(status[5] == VitalStatus::Deceased);
+        assert!(status[6] == VitalStatus::Deceased);
+    }
 }
@@ -3,6 +3,7 @@
 #![allow(clippy::module_inception)]
 
 mod adoption;
+mod association;
 mod birth;
 mod christening;
 mod death;
@@ -13,15 +14,18 @@ mod name;
 mod note;
 mod residence;
 mod source;
+mod vital_status;
 
 pub use adoption::Adoption;
+pub use association::Association;
 pub use birth::Birth;
 pub use christening::Christening;
 pub use death::Death;
-pub use event::IndividualEventDetail;
+pub use event::{IndividualEvent, IndividualEventDetail};
 pub use gender::*;
 pub use individual::*;
 pub use name::*;
 pub use note::*;
 pub use residence::Residence;
+pub use vital_status::*;
 // pub use source::*;
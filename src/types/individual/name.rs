@@ -12,7 +12,7 @@ use winnow::prelude::*;
 // n NSFX <NAME_PIECE_SUFFIX>
 // n <<NOTE_STRUCTURE>>
 // n <<SOURCE_CITATION>>
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct Name {
     /// The value of the Name level
     pub value: Option<String>,
@@ -37,7 +37,7 @@ pub struct Name {
     /// In this example Lt. Cmndr. is considered as the name prefix portion.
     pub prefix: Option<String>,
 
-    /// SPFX
+    /// NSFX
     /// Non-indexing name piece that appears after the given name and surname parts.
     /// Different name suffix parts are separated by a comma.
     ///
@@ -46,7 +46,7 @@ pub struct Name {
     /// In this example jr. is considered as the name suffix portion.
     pub suffix: Option<String>,
 
-    /// NSFX
+    /// SPFX
     /// Surname prefix or article used in a family name. Different surname articles
     /// are separated by a comma, for example in the name "de la Cruz", this value
     /// would be "de, la".
@@ -117,10 +117,10 @@ impl Name {
                     name.prefix = Some(line.value.to_string());
                 }
                 "SPFX" => {
-                    name.suffix = Some(line.value.to_string());
+                    name.surname_prefix = Some(line.value.to_string());
                 }
                 "NSFX" => {
-                    name.surname_prefix = Some(line.value.to_string());
+                    name.suffix = Some(line.value.to_string());
                 }
                 _ => {
                     // println!("Unhandled name tag: {:?}", tag.unwrap());
@@ -149,9 +149,6 @@ impl Name {
             if line.level == 2 && (line.tag == "ROMN" || line.tag == "FONE") {
                 break;
             }
-            if line.tag == "BIRT" {
-                println!("DEBUG: {:?}", line);
-            }
         }
 
         // println!("Name: {:?}", name);
@@ -169,7 +166,7 @@ impl Name {
 // +1 ROMN <NAME_ROMANIZED_VARIATION>
 // +2 TYPE <ROMANIZED_TYPE>
 // +2 <<PERSONAL_NAME_PIECES>>
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct PersonalName {
     /// The surname of an individual, if known, is enclosed between two slash (/)
     /// characters. The order of the name parts should be the order that the person
@@ -275,27 +272,15 @@ impl PersonalName {
                         pn.romanized = Name::parse(record).unwrap();
                         if !line.value.is_empty() {
                             pn.romanized.value = Some(line.value.to_string());
-                        } else {
-                            println!(
-                                "Romanized value is missing; Level={}, tag={}",
-                                line.level, line.tag
-                            );
                         }
                     }
                     "FONE" => {
                         pn.phonetic = Name::parse(record).unwrap();
                         if !line.value.is_empty() {
                             pn.phonetic.value = Some(line.value.to_string());
-                        } else {
-                            println!(
-                                "Phonetic value is missing; Level={}, tag={}",
-                                line.level, line.tag
-                            );
                         }
                     }
-                    _ => {
-                        println!("skipping PersonalName tag {:?}", line.tag);
-                    }
+                    _ => {}
                 }
             }
             if line.level == level {
@@ -961,8 +946,8 @@ mod tests {
         assert_eq!(Some("Torture"), name.name.surname.as_deref());
         assert_eq!(Some("Joe"), name.name.nickname.as_deref());
         assert_eq!(Some("Prof."), name.name.prefix.as_deref());
-        assert_eq!(Some("Le"), name.name.suffix.as_deref());
-        assert_eq!(Some("Jr."), name.name.surname_prefix.as_deref());
+        assert_eq!(Some("Jr."), name.name.suffix.as_deref());
+        assert_eq!(Some("Le"), name.name.surname_prefix.as_deref());
         assert_eq!(Some("birth"), name.name.r#type.as_deref());
 
         // Check the name.romanized
@@ -974,8 +959,8 @@ mod tests {
         assert_eq!(Some("Torture"), name.romanized.surname.as_deref());
         assert_eq!(Some("Joe"), name.romanized.nickname.as_deref());
         assert_eq!(Some("Prof."), name.romanized.prefix.as_deref());
-        assert_eq!(Some("Le"), name.romanized.suffix.as_deref());
-        assert_eq!(Some("Jr."), name.romanized.surname_prefix.as_deref());
+        assert_eq!(Some("Jr."), name.romanized.suffix.as_deref());
+        assert_eq!(Some("Le"), name.romanized.surname_prefix.as_deref());
         assert_eq!(Some("user defined"), name.romanized.r#type.as_deref());
 
         // Check the name.phonetic
@@ -984,8 +969,8 @@ mod tests {
         assert_eq!(Some("Torture"), name.phonetic.surname.as_deref());
         assert_eq!(Some("Joe"), name.phonetic.nickname.as_deref());
         assert_eq!(Some("Prof."), name.phonetic.prefix.as_deref());
-        assert_eq!(Some("Le"), name.phonetic.suffix.as_deref());
-        assert_eq!(Some("Jr."), name.phonetic.surname_prefix.as_deref());
+        assert_eq!(Some("Jr."), name.phonetic.suffix.as_deref());
+        assert_eq!(Some("Le"), name.phonetic.surname_prefix.as_deref());
         assert_eq!(Some("user defined"), name.phonetic.r#type.as_deref());
     }
 }
@@ -4,7 +4,7 @@ use winnow::prelude::*;
 // n RESI
 // +1 <<FAMILY_EVENT_DETAIL>>
 
-#[derive(Debug, Default)]
+#[derive(Clone, Debug, Default)]
 pub struct Residence {
     pub detail: Option<FamilyEventDetail>,
 }
@@ -0,0 +1,122 @@
+/// The oldest age we'll assume a person could reach before presuming them dead.
+pub const MAX_LIFESPAN: i32 = 110;
+
+/// The youngest age we'll assume someone could become a parent.
+pub const MIN_PARENT_AGE: i32 = 12;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+/// Whether an individual is living or deceased, as derived from their events.
+pub enum VitalStatus {
+    /// The individual is presumed to be living.
+    Living,
+    /// The individual is known, or presumed, to be deceased.
+    Deceased,
+    #[default]
+    /// There isn't enough information to tell.
+    Unknown,
+}
+
+/// Extract the (first) year from a GEDCOM date value.
+///
+/// This is deliberately forgiving: "ABT 31 DEC 1997", "BET MAY 1979 AND AUG 1979",
+/// "INT 1995 (from estimated age)" and "1750/51" all yield a year. Anything
+/// inside parentheses is a date phrase and is ignored.
+pub fn date_year(date: &str) -> Option<i32> {
+    let mut last: Option<&str> = None;
+    for token in date.split_whitespace() {
+        if token.starts_with('(') {
+            break;
+        }
+
+        // Handle dual dates, i.e. 1750/51
+        let token = token.split('/').next().unwrap_or(token);
+
+        if !token.is_empty() && token.chars().all(|c| c.is_ascii_digit()) {
+            let is_month = last.map(is_month).unwrap_or(false);
+
+            // A 1-2 digit number is a day, unless it follows a month.
+            if token.len() > 2 || is_month {
+                return token.parse().ok();
+            }
+        }
+        last = Some(token);
+    }
+    None
+}
+
+fn is_month(token: &str) -> bool {
+    matches!(
+        token,
+        "JAN"
+            | "FEB"
+            | "MAR"
+            | "APR"
+            | "MAY"
+            | "JUN"
+            | "JUL"
+            | "AUG"
+            | "SEP"
+            | "OCT"
+            | "NOV"
+            | "DEC"
+    )
+}
+
+/// The current (UTC) year, according to the system clock.
+pub fn current_year() -> i32 {
+    let days = (now() / 86_400) as i64;
+
+    // Convert days since the epoch to a civil year.
+    // See http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+
+    let year = yoe + era * 400;
+    (if month <= 2 { year + 1 } else { year }) as i32
+}
+
+/// Seconds since the Unix epoch, according to the system clock
+#[cfg(not(all(feature = "wasm", target_arch = "wasm32", target_os = "unknown")))]
+fn now() -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Seconds since the Unix epoch. In the browser, the standard library has
+/// no clock, so we ask JavaScript.
+#[cfg(all(feature = "wasm", target_arch = "wasm32", target_os = "unknown"))]
+fn now() -> u64 {
+    (js_sys::Date::now() / 1000.0) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_date_year() {
+        assert!(date_year("31 DEC 1997") == Some(1997));
+        assert!(date_year("ABT 31 DEC 1997") == Some(1997));
+        assert!(date_year("BET MAY 1979 AND AUG 1979") == Some(1979));
+        assert!(date_year("INT 1995 (from estimated age)") == Some(1995));
+        assert!(date_year("5 MAY 0005") == Some(5));
+        assert!(date_year("MAY 5") == Some(5));
+        assert!(date_year("1750/51") == Some(1750));
+        assert!(date_year("(unknown)").is_none());
+        assert!(date_year("").is_none());
+    }
+
+    #[test]
+    fn current_year_is_sane() {
+        assert!(current_year() >= 2024);
+    }
+}
@@ -6,7 +6,7 @@ use winnow::combinator::{opt, preceded, separated_pair};
 use winnow::error::StrContext;
 use winnow::prelude::*;
 use winnow::stream::Stream;
-use winnow::token::{literal, take_till};
+use winnow::token::{literal, one_of, take_till};
 
 /// A GEDCOM line
 /// level + delim (space) + [optional_xref_ID] + tag + [optional_line_value] + terminator
@@ -71,13 +71,14 @@ impl<'b> Line<'b> {
                         let _ = Self::delim(input);
                     }
                     line.tag = Self::tag(input)?;
-                    let _ = Self::delim(input);
+                    // Only the one delimiter: any more whitespace is part of
+                    // the value, which matters for CONC and CONT
+                    let _ = Self::separator(input);
 
                     let is_eol = Self::peek_eol(input)?;
                     if is_eol {
                         Self::eol(input).unwrap();
                     } else {
-                        Self::delim(input).unwrap();
                         line.value = Self::value(input)?;
 
                         let is_eol = Self::peek_eol(input)?;
@@ -86,9 +87,7 @@ impl<'b> Line<'b> {
                         }
                     }
                 }
-                Err(e) => {
-                    println!("Err: {}", e);
-                    println!("Error parsing line: '{}'", input);
+                Err(_) => {
                     Self::eol(input).unwrap();
                     /*
                     There's a case where a line is simply the extension of the
@@ -126,10 +125,10 @@ impl<'b> Line<'b> {
     /// Peek ahead at the next line without consuming it.
     pub fn peek(input: &mut &'b str) -> PResult<Line<'b>> {
         let start = input.checkpoint();
-        let line = Line::parse(input).unwrap();
+        let line = Line::parse(input);
 
         input.reset(start);
-        Ok(line)
+        line
     }
 
     /// Parse a number from the string, but return it as an actual Rust number, not a string.
@@ -143,7 +142,7 @@ impl<'b> Line<'b> {
             .parse_next(input)
     }
 
-    /// Parse a number from the string, but return it as an actual Rust number, not a string.
+    // /// Parse a number from the string, but return it as an actual Rust number, not a string.
     // fn peek_level<'s>(input: &mut &'s str) -> PResult<u8> {
     //     let start = input.checkpoint();
 
@@ -157,6 +156,13 @@ impl<'b> Line<'b> {
         space0.context(StrContext::Label("delim")).parse_next(input)
     }
 
+    /// Parse the single space, or tab, between a tag and its value
+    fn separator(input: &mut &'b str) -> PResult<Option<char>> {
+        opt(one_of([' ', '\t']))
+            .context(StrContext::Label("separator"))
+            .parse_next(input)
+    }
+
     fn eol(input: &mut &'b str) -> PResult<&'b str> {
         // multispace0.context(StrContext::Label("eol2")).parse_next(input)
         line_ending
@@ -279,5 +285,11 @@ mod tests {
         let line = Line::parse(&mut data[7]).unwrap();
         // TODO: Update this to include the wrapping @ when I figure out how to make nom do that.
         assert!(line.level == 0 && line.tag == "SUBM" && line.value == "" && line.xref == "@U1@");
+
+        // Whitespace after the delimiter belongs to the value
+        let line = Line::parse(&mut "2 CONT   indented ").unwrap();
+        assert!(line.tag == "CONT" && line.value == "  indented ");
+        let line = Line::parse(&mut "2 CONC  \n").unwrap();
+        assert!(line.tag == "CONC" && line.value == " ");
     }
 }
@@ -52,7 +52,7 @@ impl Map {
             // If the next level matches our initial level, we're done parsing
             // this structure.
             line = Line::peek(record).unwrap();
-            if line.level == level {
+            if line.level <= level {
                 break;
             }
         }
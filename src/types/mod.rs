@@ -2,55 +2,122 @@
 // top-level record types
 mod address;
 mod adopted_by;
+mod age;
 mod character_set;
 mod corporation;
+mod date;
 mod datetime;
+mod decoding;
 mod event;
+mod event_type;
 mod family;
 mod gedc;
+mod gedcom;
 mod header;
 mod individual;
 mod line;
 mod map;
+mod multimedia_record;
 mod note;
+mod note_record;
 mod object;
 mod pedigree;
 mod place;
+mod position;
 mod quay;
+mod raw_record;
+mod repository_citation;
+mod repository_record;
+mod restriction;
+mod skipped_text;
 mod source;
 mod source_citation;
+mod source_record;
 mod sourcedata;
 mod spouse;
 mod submission;
 mod submitter;
+mod user_reference;
 mod xref;
 
 pub use address::*;
 pub use adopted_by::AdoptedBy;
+pub use age::{AgeBound, GedcomAge};
 pub use character_set::CharacterSet;
+pub use date::{month_number, DateQualifier, GedcomDate, SimpleDate, Time, Timestamp};
 pub use datetime::DateTime;
+pub use decoding::{DecodingStats, Encoding, RecordDecoding};
 pub use event::{EventDetail, EventTypeCitedFrom, FamilyEventDetail};
-pub use family::Family;
-pub use gedc::{Form, Gedc};
+pub use event_type::{AttributeType, EventType};
+pub use family::{BirthOrder, Family};
+pub use gedc::{Form, Gedc, GedcomVersion};
 pub use header::Header;
 pub use individual::*;
 pub use line::Line;
 pub use map::Map;
+pub use multimedia_record::MultimediaRecord;
 pub use note::Note;
-pub use object::Object;
+pub use note_record::NoteRecord;
+pub use object::{MediaFile, Object};
 pub use pedigree::Pedigree;
 pub use place::Place;
+pub use position::Position;
 pub use quay::Quay;
+pub use raw_record::{is_standard_tag, RawRecord};
+pub use repository_citation::{CallNumber, RepositoryCitation, RepositoryHolding, SourceMedia};
+pub use repository_record::RepositoryRecord;
+pub use restriction::Restriction;
+pub use skipped_text::SkippedText;
 pub use source::Source;
 pub use source_citation::SourceCitation;
+pub use source_record::SourceRecord;
 pub use sourcedata::SourceData;
 pub use spouse::Spouse;
 pub use submission::Submission;
 pub use submitter::Submitter;
+pub use user_reference::UserReference;
 pub use xref::Xref;
 
+use std::collections::HashMap;
+
+use crate::error::GedcomError;
+use crate::media::Archive;
+use crate::relationship::Kinship;
+use crate::validate::ValidationConfig;
+
 #[derive(Debug, Default)]
 pub struct Gedcom {
     pub header: Header,
     pub individuals: Vec<Individual>,
+    pub families: Vec<Family>,
+    pub sources: Vec<SourceRecord>,
+    /// OBJE records
+    pub multimedia: Vec<MultimediaRecord>,
+    /// NOTE records
+    pub notes: Vec<NoteRecord>,
+    /// REPO records
+    pub repositories: Vec<RepositoryRecord>,
+    /// SUBM records, including the header's submitter. See
+    /// `Gedcom::primary_submitter`.
+    pub submitters: Vec<Submitter>,
+    /// The original text of every record, in file order. Only populated when
+    /// parsing with `GedcomConfig::preserve_unknown`.
+    pub raw_records: Vec<RawRecord>,
+    /// Where each record, by xref, starts in the file
+    pub record_positions: HashMap<String, Position>,
+    /// Problems found while parsing that didn't stop us from continuing
+    pub warnings: Vec<GedcomError>,
+    /// The notes and BLOBs whose text wasn't kept. See
+    /// `GedcomConfig::max_text_length`.
+    pub skipped_text: Vec<SkippedText>,
+    /// Characters that couldn't be decoded, or look mis-decoded
+    pub decoding: DecodingStats,
+    /// Everyone's ancestors, if worked out ahead of time with
+    /// `Gedcom::precompute_kinship`
+    pub kinship: Option<Kinship>,
+    /// The GEDZIP archive the Gedcom was read from, and the media in it
+    pub archive: Option<Archive>,
+    /// The rules `Gedcom::validate` checks against, from
+    /// `GedcomConfig::validation`
+    pub validation: ValidationConfig,
 }
@@ -0,0 +1,144 @@
+use crate::parse;
+
+use super::{DateTime, Line, MediaFile, Note};
+
+// MULTIMEDIA_RECORD:=
+// n @XREF:OBJE@ OBJE {1:1}
+// +1 FILE <MULTIMEDIA_FILE_REFN> {1:M} p.54
+// +2 FORM <MULTIMEDIA_FORMAT> {1:1} p.54
+// +3 TYPE <SOURCE_MEDIA_TYPE> {0:1} p.62
+// +2 TITL <DESCRIPTIVE_TITLE> {0:1} p.48
+// +1 REFN <USER_REFERENCE_NUMBER> {0:M} p.63, 64
+// +2 TYPE <USER_REFERENCE_TYPE> {0:1} p.64
+// +1 RIN <AUTOMATED_RECORD_ID> {0:1} p.43
+// +1 <<NOTE_STRUCTURE>> {0:M} p.37
+// +1 <<SOURCE_CITATION>> {0:M} p.39
+// +1 <<CHANGE_DATE>> {0:1} p.31
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// An OBJE record, describing a photo, document or other file
+pub struct MultimediaRecord {
+    pub xref: String,
+    pub files: Vec<MediaFile>,
+    /// TITL, where given for the whole record, as in 5.5
+    pub title: Option<String>,
+    pub notes: Vec<Note>,
+
+    /// CHAN: When the record was last changed
+    pub change_date: Option<DateTime>,
+
+    /// UID or _UID: Identifiers that stay with the record from program to
+    /// program, unlike its xref
+    pub uids: Vec<String>,
+}
+
+impl MultimediaRecord {
+    pub fn parse(record: &mut &str) -> MultimediaRecord {
+        let line = Line::parse(record).unwrap();
+        let level = line.level;
+        let mut multimedia = MultimediaRecord {
+            xref: line.xref.to_string(),
+            ..Default::default()
+        };
+
+        while !record.is_empty() {
+            let mut consume = true;
+            let line = Line::peek(record).unwrap();
+            if line.level <= level {
+                break;
+            }
+
+            match line.tag {
+                "NOTE" if line.level == level + 1 => {
+                    if let Some(note) = parse::get_tag_value(record).unwrap() {
+                        multimedia.notes.push(Note { note: Some(note) });
+                    }
+                    consume = false;
+                }
+                "CHAN" if line.level == level + 1 => {
+                    Line::parse(record).unwrap();
+                    let (buffer, change_date) = DateTime::parse(record);
+                    *record = buffer;
+                    multimedia.change_date = change_date;
+                    consume = false;
+                }
+                "UID" | "_UID" if line.level == level + 1 => {
+                    multimedia.uids.push(line.value.to_string());
+                }
+                // REFN has a TYPE of its own
+                "REFN" if line.level == level + 1 => {
+                    Line::parse(record).unwrap();
+                    while !record.is_empty() && Line::peek(record).unwrap().level > level + 1 {
+                        Line::parse(record).unwrap();
+                    }
+                    consume = false;
+                }
+                _ => {
+                    MediaFile::read(&mut multimedia.files, &mut multimedia.title, &line, level);
+                }
+            }
+
+            if consume {
+                Line::parse(record).unwrap();
+            }
+        }
+
+        multimedia
+    }
+
+    /// The title of a file in the record, or else of the record
+    pub fn file_title<'a>(&'a self, file: &'a MediaFile) -> Option<&'a str> {
+        file.title.as_deref().or(self.title.as_deref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_record() {
+        let data = [
+            "0 @M1@ OBJE",
+            "1 FILE photo.jpeg",
+            "2 FORM JPEG",
+            "3 TYPE photo",
+            "2 TITL Picture of the book cover",
+            "1 FILE scan.pdf",
+            "2 FORM pdf",
+            "1 REFN 01234567890123456789",
+            "2 TYPE reference",
+            "1 RIN 1",
+            "1 NOTE Here are some notes on this multimedia object.",
+            "2 CONT If decoded it should be an image of a flower.",
+            "1 CHAN",
+            "2 DATE 14 JAN 2001",
+            "3 TIME 14:10:31",
+            "0 TRLR",
+        ]
+        .join("\n");
+        let mut record = data.as_str();
+        let multimedia = MultimediaRecord::parse(&mut record);
+
+        assert!(multimedia.xref == "@M1@");
+        assert!(multimedia.files.len() == 2);
+        let photo = &multimedia.files[0];
+        assert!(photo.path.as_deref() == Some("photo.jpeg"));
+        assert!(photo.format.as_deref() == Some("JPEG"));
+        assert!(photo.media_type.as_deref() == Some("photo"));
+        assert!(multimedia.file_title(photo) == Some("Picture of the book cover"));
+        assert!(multimedia.files[1].media_type.is_none());
+        assert!(multimedia.file_title(&multimedia.files[1]).is_none());
+        assert!(multimedia.notes.len() == 1);
+        assert!(multimedia.change_date.unwrap().date.as_deref() == Some("14 JAN 2001"));
+        assert!(record == "0 TRLR");
+
+        // 5.5 has a single file, with its FORM and TITL alongside
+        let mut record = "0 @M2@ OBJE\n1 FORM bmp\n1 TITL A map\n1 FILE map.bmp";
+        let multimedia = MultimediaRecord::parse(&mut record);
+        assert!(multimedia.files.len() == 1);
+        let map = &multimedia.files[0];
+        assert!(map.format.as_deref() == Some("bmp"));
+        assert!(multimedia.file_title(map) == Some("A map"));
+    }
+}
@@ -0,0 +1,134 @@
+use crate::parse;
+
+use super::{DateTime, Line, SourceCitation, UserReference};
+
+// NOTE_RECORD:=
+// n @<XREF:NOTE>@ NOTE <SUBMITTER_TEXT> {1:1} p.63
+// +1 [CONC|CONT] <SUBMITTER_TEXT> {0:M}
+// +1 REFN <USER_REFERENCE_NUMBER> {0:M} p.63, 64
+// +2 TYPE <USER_REFERENCE_TYPE> {0:1} p.64
+// +1 RIN <AUTOMATED_RECORD_ID> {0:1} p.43
+// +1 <<SOURCE_CITATION>> {0:M} p.39
+// +1 <<CHANGE_DATE>> {0:1} p.31
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// A NOTE record, shared by the records that point to it
+pub struct NoteRecord {
+    pub xref: String,
+    /// The text of the note. None if it's empty, or was too long to keep.
+    /// See `GedcomConfig::max_text_length`.
+    pub text: Option<String>,
+    /// REFN
+    pub references: Vec<UserReference>,
+    /// RIN: The record's id in the program that wrote the file
+    pub rin: Option<String>,
+    pub sources: Vec<SourceCitation>,
+
+    /// CHAN: When the record was last changed
+    pub change_date: Option<DateTime>,
+
+    /// UID or _UID: Identifiers that stay with the record from program to
+    /// program, unlike its xref
+    pub uids: Vec<String>,
+}
+
+impl NoteRecord {
+    pub fn parse(record: &mut &str) -> NoteRecord {
+        let line = Line::peek(record).unwrap();
+        let level = line.level;
+        let mut note = NoteRecord {
+            xref: line.xref.to_string(),
+            ..Default::default()
+        };
+        // The text is on the NOTE line, continued with CONC and CONT
+        note.text = parse::get_tag_value(record)
+            .unwrap()
+            .filter(|text| !text.is_empty());
+
+        while !record.is_empty() {
+            let mut consume = true;
+            let line = Line::peek(record).unwrap();
+            if line.level <= level {
+                break;
+            }
+
+            match line.tag {
+                "REFN" if line.level == level + 1 => {
+                    note.references.push(UserReference::parse(record));
+                    consume = false;
+                }
+                "RIN" if line.level == level + 1 => {
+                    note.rin = Some(line.value.to_string());
+                }
+                "SOUR" if line.level == level + 1 => {
+                    note.sources.push(SourceCitation::parse(record).unwrap());
+                    consume = false;
+                }
+                "CHAN" if line.level == level + 1 => {
+                    Line::parse(record).unwrap();
+                    let (buffer, change_date) = DateTime::parse(record);
+                    *record = buffer;
+                    note.change_date = change_date;
+                    consume = false;
+                }
+                "UID" | "_UID" if line.level == level + 1 => {
+                    note.uids.push(line.value.to_string());
+                }
+                _ => {}
+            }
+
+            if consume {
+                Line::parse(record).unwrap();
+            }
+        }
+
+        note
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_record() {
+        let data = [
+            "0 @N1@ NOTE Copied from the parish register,",
+            "1 CONC  which is hard to read",
+            "1 CONT in places.",
+            "1 REFN 1234",
+            "2 TYPE Card index",
+            "1 REFN 99",
+            "1 RIN 7",
+            "1 SOUR @S1@",
+            "2 PAGE Folio 12",
+            "1 CHAN",
+            "2 DATE 14 JAN 2001",
+            "3 TIME 14:10:31",
+            "0 TRLR",
+        ]
+        .join("\n");
+        let mut record = data.as_str();
+        let note = NoteRecord::parse(&mut record);
+
+        assert!(note.xref == "@N1@");
+        assert!(
+            note.text.as_deref()
+                == Some("Copied from the parish register, which is hard to read\nin places.")
+        );
+        assert!(note.references.len() == 2);
+        assert!(note.references[0].number == "1234");
+        assert!(note.references[0].r#type.as_deref() == Some("Card index"));
+        assert!(note.references[1].r#type.is_none());
+        assert!(note.rin.as_deref() == Some("7"));
+        assert!(note.sources[0].xref.as_deref() == Some("@S1@"));
+        assert!(note.sources[0].page.as_deref() == Some("Folio 12"));
+        assert!(note.change_date.unwrap().date.as_deref() == Some("14 JAN 2001"));
+        assert!(record == "0 TRLR");
+
+        // Without its text, as when it was too long to keep
+        let mut record = "0 @N2@ NOTE\n1 RIN 8";
+        let note = NoteRecord::parse(&mut record);
+        assert!(note.text.is_none() && note.rin.as_deref() == Some("8"));
+    }
+}
@@ -1,9 +1,17 @@
-// use crate::types::Line;
-use crate::parse;
+use super::Line;
 
 use winnow::prelude::*;
 
-// use nom::{bytes::complete::is_not, IResult};
+// MULTIMEDIA_LINK:=
+// [ /* embedded form */
+// n OBJE @<XREF:OBJE>@ {1:1}
+// |
+// n OBJE {1:1}
+// +1 FILE <MULTIMEDIA_FILE_REFN> {1:M} p.54
+// +2 FORM <MULTIMEDIA_FORMAT> {1:1} p.54
+// +3 MEDI <SOURCE_MEDIA_TYPE> {0:1} p.62
+// +1 TITL <DESCRIPTIVE_TITLE> {0:1} p.48
+// ]
 
 // 0 @M1@ OBJE
 // 1 FILE photo.jpeg
@@ -20,16 +28,90 @@ use winnow::prelude::*;
 // 2 DATE 14 JAN 2001
 // 3 TIME 14:10:31
 
-#[derive(Debug, Eq, PartialEq, Clone)]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+/// A file in a multimedia record or link, i.e. a photo
+pub struct MediaFile {
+    /// FILE: The path or URL of the file, as written in the GEDCOM
+    pub path: Option<String>,
+    /// FORM: i.e. "jpeg"
+    pub format: Option<String>,
+    /// MEDI, or TYPE in 5.5: i.e. "photo"
+    pub media_type: Option<String>,
+    /// TITL
+    pub title: Option<String>,
+}
+
+impl MediaFile {
+    /// Read a FILE, FORM, MEDI, TYPE or TITL line from under the OBJE at
+    /// `level`, into its file or, for a TITL of the whole object, `title`.
+    /// 5.5 puts FORM and TITL directly under OBJE, and 5.5.1 under FILE.
+    pub(crate) fn read(
+        files: &mut Vec<MediaFile>,
+        title: &mut Option<String>,
+        line: &Line,
+        level: u8,
+    ) {
+        let value = Some(line.value.trim().to_string()).filter(|v| !v.is_empty());
+        if line.tag == "FILE" && line.level == level + 1 {
+            // In 5.5, the FORM may come first
+            match files.last_mut() {
+                Some(file) if file.path.is_none() => file.path = value,
+                _ => files.push(MediaFile {
+                    path: value,
+                    ..Default::default()
+                }),
+            }
+            return;
+        }
+        if line.tag == "TITL" && line.level == level + 1 {
+            *title = value;
+            return;
+        }
+
+        if !matches!(line.tag, "FORM" | "MEDI" | "TYPE" | "TITL") {
+            return;
+        }
+        if files.is_empty() {
+            files.push(MediaFile::default());
+        }
+        let file = files.last_mut().unwrap();
+        match line.tag {
+            "FORM" => file.format = value,
+            "TITL" => file.title = value,
+            _ => file.media_type = value,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+/// OBJE: A link to a multimedia record, or a description of the files
+/// themselves
 pub struct Object {
+    /// The OBJE record linked to
     pub xref: Option<String>,
+    /// The files, where they're given here rather than in a record
+    pub files: Vec<MediaFile>,
+    /// TITL
+    pub title: Option<String>,
 }
 
 impl Object {
     pub fn parse(buffer: &mut &str) -> PResult<Object> {
-        let mut obje = Object { xref: None };
+        let line = Line::parse(buffer).unwrap();
+        let level = line.level;
+        let mut obje = Object {
+            xref: Some(line.value.trim().to_string()).filter(|v| !v.is_empty()),
+            ..Default::default()
+        };
 
-        obje.xref = parse::get_tag_value(buffer).unwrap();
+        while !buffer.is_empty() {
+            let line = Line::peek(buffer).unwrap();
+            if line.level <= level {
+                break;
+            }
+            MediaFile::read(&mut obje.files, &mut obje.title, &line, level);
+            Line::parse(buffer).unwrap();
+        }
 
         Ok(obje)
     }
@@ -37,7 +119,7 @@ impl Object {
 
 #[cfg(test)]
 mod tests {
-    use super::Object;
+    use super::{MediaFile, Object};
 
     #[test]
     fn parse_obje() {
@@ -52,4 +134,33 @@ mod tests {
 
         assert!(o == "@M7@");
     }
+
+    #[test]
+    fn parse_embedded_obje() {
+        let data = [
+            "1 OBJE",
+            "2 FILE photos/joe.jpg",
+            "3 FORM jpg",
+            "4 MEDI photo",
+            "2 TITL Joe at the beach",
+            "1 NAME Next",
+        ];
+
+        let input = data.join("\n");
+        let mut record = input.as_str();
+        let obje = Object::parse(&mut record).unwrap();
+
+        assert!(obje.xref.is_none());
+        assert!(obje.title.as_deref() == Some("Joe at the beach"));
+        assert!(
+            obje.files
+                == [MediaFile {
+                    path: Some("photos/joe.jpg".to_string()),
+                    format: Some("jpg".to_string()),
+                    media_type: Some("photo".to_string()),
+                    title: None,
+                }]
+        );
+        assert!(record == "1 NAME Next");
+    }
 }
@@ -27,8 +27,9 @@ pub enum Pedigree {
 impl FromStr for Pedigree {
     type Err = ();
 
+    /// Parse a PEDI value. 7.0 writes them in upper case, i.e. "BIRTH".
     fn from_str(input: &str) -> Result<Pedigree, Self::Err> {
-        match input {
+        match input.trim().to_lowercase().as_str() {
             "adopted" => Ok(Pedigree::Adopted),
             "birth" => Ok(Pedigree::Birth),
             "foster" => Ok(Pedigree::Foster),
@@ -49,6 +50,7 @@ mod tests {
         assert!(Pedigree::from_str("birth").unwrap() == Pedigree::Birth);
         assert!(Pedigree::from_str("foster").unwrap() == Pedigree::Foster);
         assert!(Pedigree::from_str("sealing").unwrap() == Pedigree::Sealing);
+        assert!(Pedigree::from_str("FOSTER").unwrap() == Pedigree::Foster);
         assert!(Pedigree::from_str("other").is_err());
     }
 }
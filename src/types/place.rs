@@ -47,9 +47,14 @@ impl Place {
                     // It should only be used when a system has over-structured its place-names."
 
                     // Parse the value of the line as a comma-delimited list
-                    place.form = line.value.split(',').map(|s| s.to_string()).collect();
+                    place.form = line
+                        .value
+                        .split(',')
+                        .map(|s| s.trim().to_string())
+                        .collect();
                 }
-                "PLAC" => {
+                // The header's PLAC carries only a FORM, with no name.
+                "PLAC" if !line.value.is_empty() => {
                     place.name = Some(line.value.to_string());
                 }
                 "FONE" => {
@@ -76,16 +81,44 @@ impl Place {
                 Line::parse(record).unwrap();
             }
 
-            // If the next level matches our initial level, we're done parsing
-            // this structure.
+            // If the next level is at or above our initial level, we're done
+            // parsing this structure.
             line = Line::peek(record).unwrap();
-            if line.level == level {
+            if line.level <= level {
                 break;
             }
         }
 
         Ok(place)
     }
+
+    /// The parts of the place name, most specific first, i.e. the city,
+    /// county, state, and country of "Salt Lake City, Salt Lake, UT, USA".
+    /// Parts left blank to keep their position are empty.
+    pub fn jurisdictions(&self) -> Vec<&str> {
+        match &self.name {
+            Some(name) => name.split(',').map(|s| s.trim()).collect(),
+            None => vec![],
+        }
+    }
+
+    /// The part of the place name at a level of its FORM, i.e. "County",
+    /// ignoring case. Places without a FORM of their own use `default_form`,
+    /// normally the header's; see `Header::place_form`.
+    pub fn jurisdiction<'a>(&'a self, level: &str, default_form: &[String]) -> Option<&'a str> {
+        let form = if self.form.is_empty() {
+            default_form
+        } else {
+            &self.form
+        };
+        let index = form
+            .iter()
+            .position(|f| f.trim().eq_ignore_ascii_case(level.trim()))?;
+        self.jurisdictions()
+            .get(index)
+            .copied()
+            .filter(|j| !j.is_empty())
+    }
 }
 
 #[derive(Clone, Debug, Default, PartialEq)]
@@ -119,7 +152,7 @@ impl PlaceVariation {
             // If the next level matches our initial level, we're done parsing
             // this structure.
             line = Line::peek(record).unwrap();
-            if line.level == level {
+            if line.level <= level {
                 break;
             }
         }
@@ -150,6 +183,8 @@ mod tests {
         let mut record = input.as_str();
         let place = Place::parse(&mut record).unwrap();
 
+        assert!(place.jurisdiction("County", &[]) == Some("UT"));
+        assert!(place.jurisdiction("state", &[]).is_none());
         assert!(place.name.is_some());
         assert!(place.name.unwrap() == "Salt Lake City, UT, USA");
 
@@ -169,6 +204,22 @@ mod tests {
         assert!(map.longitude == 0.0);
     }
 
+    #[test]
+    fn jurisdictions() {
+        let place = Place {
+            name: Some("Leeds, , England".to_string()),
+            ..Default::default()
+        };
+        let form: Vec<String> = ["City", "County", "Country"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert!(place.jurisdictions() == ["Leeds", "", "England"]);
+        assert!(place.jurisdiction("country", &form) == Some("England"));
+        assert!(place.jurisdiction("County", &form).is_none());
+        assert!(place.jurisdiction("City", &[]).is_none());
+    }
+
     #[test]
     fn parse_variation() {
         let data = vec!["3 FONE Salt Lake City, UT, USA", "4 TYPE user defined"];
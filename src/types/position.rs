@@ -0,0 +1,16 @@
+use std::fmt;
+
+/// Where something was found in the original file
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    /// The line number, starting from 1
+    pub line: usize,
+    /// The byte offset of the start of the line, from the start of the file
+    pub offset: usize,
+}
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {} (byte {})", self.line, self.offset)
+    }
+}
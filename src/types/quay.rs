@@ -12,9 +12,9 @@ use std::str::FromStr;
 // 2 = Secondary evidence, data officially recorded sometime after event
 // 3 = Direct and primary evidence used, or by dominance of the evidence
 
-#[derive(Clone, Default, Debug, PartialEq)]
+#[derive(Clone, Default, Debug, PartialEq, Eq, PartialOrd, Ord)]
 /// The quantitative eveluation of the credibility of a piece of information
-/// based upon its supporting evidence.
+/// based upon its supporting evidence. Ordered from least to most credible.
 pub enum Quay {
     #[default]
     Unreliable,
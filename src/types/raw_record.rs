@@ -0,0 +1,273 @@
+use crate::types::{DateTime, Line};
+
+/// The original text of a level 0 record, exactly as it appeared in the file.
+///
+/// These are only collected when parsing with `GedcomConfig::preserve_unknown`,
+/// so that anything the structured model doesn't understand (vendor tags,
+/// out-of-spec levels, unusual ordering) can be written back out unchanged.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RawRecord {
+    /// The xref of the record, if it has one
+    pub xref: Option<String>,
+    /// The tag of the record, i.e. INDI
+    pub tag: String,
+    /// The lines of the record, verbatim, including their line terminators
+    pub lines: Vec<String>,
+}
+
+impl RawRecord {
+    /// The record, as it appeared in the file
+    pub fn text(&self) -> String {
+        self.lines.concat()
+    }
+
+    /// The record's CHAN, for records the structured model doesn't keep,
+    /// i.e. SUBN
+    pub fn change_date(&self) -> Option<DateTime> {
+        let mut lines = self
+            .lines
+            .iter()
+            .map(|l| l.trim_start_matches('\u{FEFF}').trim());
+        lines.find(|l| Line::parse(&mut &**l).is_ok_and(|l| l.level == 1 && l.tag == "CHAN"))?;
+        let chan: Vec<&str> = lines
+            .take_while(|l| Line::parse(&mut &**l).is_ok_and(|l| l.level > 1))
+            .collect();
+        if chan.is_empty() {
+            return None;
+        }
+        DateTime::parse(&chan.join("\n")).1
+    }
+
+    /// The lines the structured model doesn't understand: those with a tag
+    /// that isn't part of the GEDCOM 5.5.1 specification, those whose level
+    /// jumps by more than one, and anything nested beneath either of them.
+    pub fn unknown_lines(&self) -> Vec<&str> {
+        let mut unknown = vec![];
+
+        // The level of the line that started an unknown structure, if we're in one
+        let mut skip: Option<u8> = None;
+        let mut previous: Option<u8> = None;
+
+        for raw in &self.lines {
+            let text = raw.trim_start_matches('\u{FEFF}').trim_end();
+            if text.is_empty() {
+                continue;
+            }
+
+            // Not a GEDCOM line at all
+            if !text.starts_with(|c: char| c.is_ascii_digit()) {
+                unknown.push(raw.as_str());
+                continue;
+            }
+            let line = match Line::parse(&mut text.trim_start()) {
+                Ok(line) => line,
+                Err(_) => {
+                    unknown.push(raw.as_str());
+                    continue;
+                }
+            };
+
+            if let Some(level) = skip {
+                if line.level > level {
+                    unknown.push(raw.as_str());
+                    previous = Some(line.level);
+                    continue;
+                }
+                skip = None;
+            }
+
+            let jumped = previous.map(|p| line.level > p + 1).unwrap_or(false);
+            if jumped || !is_standard_tag(line.tag) {
+                unknown.push(raw.as_str());
+                skip = Some(line.level);
+            }
+            previous = Some(line.level);
+        }
+
+        unknown
+    }
+}
+
+/// Is this one of the tags defined by the GEDCOM 5.5.1 specification?
+pub fn is_standard_tag(tag: &str) -> bool {
+    matches!(
+        tag,
+        "ABBR"
+            | "ADDR"
+            | "ADR1"
+            | "ADR2"
+            | "ADR3"
+            | "ADOP"
+            | "AFN"
+            | "AGE"
+            | "AGNC"
+            | "ALIA"
+            | "ANCE"
+            | "ANCI"
+            | "ANUL"
+            | "ASSO"
+            | "AUTH"
+            | "BAPL"
+            | "BAPM"
+            | "BARM"
+            | "BASM"
+            | "BIRT"
+            | "BLES"
+            | "BLOB"
+            | "BURI"
+            | "CALN"
+            | "CAST"
+            | "CAUS"
+            | "CENS"
+            | "CHAN"
+            | "CHAR"
+            | "CHIL"
+            | "CHR"
+            | "CHRA"
+            | "CITY"
+            | "CONC"
+            | "CONF"
+            | "CONL"
+            | "CONT"
+            | "COPR"
+            | "CORP"
+            | "CREM"
+            | "CTRY"
+            | "DATA"
+            | "DATE"
+            | "DEAT"
+            | "DESC"
+            | "DESI"
+            | "DEST"
+            | "DIV"
+            | "DIVF"
+            | "DSCR"
+            | "EDUC"
+            | "EMAIL"
+            | "EMIG"
+            | "ENDL"
+            | "ENGA"
+            | "EVEN"
+            | "FACT"
+            | "FAM"
+            | "FAMC"
+            | "FAMF"
+            | "FAMS"
+            | "FAX"
+            | "FCOM"
+            | "FILE"
+            | "FONE"
+            | "FORM"
+            | "GEDC"
+            | "GIVN"
+            | "GRAD"
+            | "HEAD"
+            | "HUSB"
+            | "IDNO"
+            | "IMMI"
+            | "INDI"
+            | "LANG"
+            | "LATI"
+            | "LONG"
+            | "MAP"
+            | "MARB"
+            | "MARC"
+            | "MARL"
+            | "MARR"
+            | "MARS"
+            | "MEDI"
+            | "NAME"
+            | "NATI"
+            | "NATU"
+            | "NCHI"
+            | "NICK"
+            | "NMR"
+            | "NOTE"
+            | "NPFX"
+            | "NSFX"
+            | "OBJE"
+            | "OCCU"
+            | "ORDI"
+            | "ORDN"
+            | "PAGE"
+            | "PEDI"
+            | "PHON"
+            | "PLAC"
+            | "POST"
+            | "PROB"
+            | "PROP"
+            | "PUBL"
+            | "QUAY"
+            | "REFN"
+            | "RELA"
+            | "RELI"
+            | "REPO"
+            | "RESI"
+            | "RESN"
+            | "RETI"
+            | "RFN"
+            | "RIN"
+            | "ROLE"
+            | "ROMN"
+            | "SEX"
+            | "SLGC"
+            | "SLGS"
+            | "SOUR"
+            | "SPFX"
+            | "SSN"
+            | "STAE"
+            | "STAT"
+            | "SUBM"
+            | "SUBN"
+            | "SURN"
+            | "TEMP"
+            | "TEXT"
+            | "TIME"
+            | "TITL"
+            | "TRLR"
+            | "TYPE"
+            | "VERS"
+            | "WIFE"
+            | "WILL"
+            | "WWW"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unknown_lines() {
+        let record = RawRecord {
+            xref: Some("@I1@".to_string()),
+            tag: "INDI".to_string(),
+            lines: [
+                "0 @I1@ INDI\r\n",
+                "1 NAME Joe /Bloggs/\r\n",
+                "1 _MILT Army\r\n",
+                "2 DATE 1917\r\n",
+                "1 BIRT\r\n",
+                "3 DATE 1 JAN 1900\r\n",
+                "2 PLAC Somewhere\r\n",
+                "a stray line\r\n",
+            ]
+            .iter()
+            .map(|l| l.to_string())
+            .collect(),
+        };
+
+        assert!(
+            record.unknown_lines()
+                == [
+                    "1 _MILT Army\r\n",
+                    "2 DATE 1917\r\n",
+                    "3 DATE 1 JAN 1900\r\n",
+                    "a stray line\r\n"
+                ]
+        );
+        assert!(record.text().starts_with("0 @I1@ INDI\r\n1 NAME"));
+        assert!(is_standard_tag("INDI"));
+        assert!(!is_standard_tag("_HME"));
+    }
+}
@@ -0,0 +1,252 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::parse;
+
+use super::{Line, Note, SourceRecord};
+
+use winnow::prelude::*;
+
+// SOURCE_REPOSITORY_CITATION:=
+// n REPO [ @XREF:REPO@ | <NULL>] {1:1} p.27
+// +1 <<NOTE_STRUCTURE>> {0:M} p.37
+// +1 CALN <SOURCE_CALL_NUMBER> {0:M} p.61
+// +2 MEDI <SOURCE_MEDIA_TYPE> {0:1} p.62
+
+// SOURCE_MEDIA_TYPE:= {Size=1:15}
+// [ audio | book | card | electronic | fiche | film | magazine |
+// manuscript | map | newspaper | photo | tombstone | video ]
+// A code, selected from one of the media classifications choices above that
+// indicates the type of material in which the referenced source is stored.
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+/// The kind of material a source is held in, i.e. a book or microfilm
+pub enum SourceMedia {
+    Audio,
+    Book,
+    Card,
+    Electronic,
+    Fiche,
+    Film,
+    Magazine,
+    Manuscript,
+    Map,
+    Newspaper,
+    Photo,
+    Tombstone,
+    Video,
+    /// Anything outside the standard list, as written in the file
+    Other(String),
+}
+
+impl FromStr for SourceMedia {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<SourceMedia, Self::Err> {
+        let input = input.trim();
+        if input.is_empty() {
+            return Err(());
+        }
+        Ok(match input.to_lowercase().as_str() {
+            "audio" => SourceMedia::Audio,
+            "book" => SourceMedia::Book,
+            "card" => SourceMedia::Card,
+            "electronic" => SourceMedia::Electronic,
+            "fiche" | "microfiche" => SourceMedia::Fiche,
+            "film" | "microfilm" => SourceMedia::Film,
+            "magazine" => SourceMedia::Magazine,
+            "manuscript" => SourceMedia::Manuscript,
+            "map" => SourceMedia::Map,
+            "newspaper" => SourceMedia::Newspaper,
+            "photo" => SourceMedia::Photo,
+            "tombstone" => SourceMedia::Tombstone,
+            "video" => SourceMedia::Video,
+            _ => SourceMedia::Other(input.to_string()),
+        })
+    }
+}
+
+impl fmt::Display for SourceMedia {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let media = match self {
+            SourceMedia::Audio => "audio",
+            SourceMedia::Book => "book",
+            SourceMedia::Card => "card",
+            SourceMedia::Electronic => "electronic",
+            SourceMedia::Fiche => "fiche",
+            SourceMedia::Film => "film",
+            SourceMedia::Magazine => "magazine",
+            SourceMedia::Manuscript => "manuscript",
+            SourceMedia::Map => "map",
+            SourceMedia::Newspaper => "newspaper",
+            SourceMedia::Photo => "photo",
+            SourceMedia::Tombstone => "tombstone",
+            SourceMedia::Video => "video",
+            SourceMedia::Other(media) => media,
+        };
+        write!(f, "{}", media)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// CALN: Where a repository files a source, and what it's held in
+pub struct CallNumber {
+    pub number: Option<String>,
+    /// MEDI
+    pub media: Option<SourceMedia>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// A link from a source to the repository that holds it
+pub struct RepositoryCitation {
+    /// The REPO record, if the repository has one
+    pub xref: Option<String>,
+    pub call_numbers: Vec<CallNumber>,
+    pub notes: Vec<Note>,
+}
+
+impl RepositoryCitation {
+    pub fn parse(record: &mut &str) -> PResult<RepositoryCitation> {
+        let mut citation = RepositoryCitation {
+            xref: None,
+            call_numbers: vec![],
+            notes: vec![],
+        };
+
+        let line = Line::parse(record).unwrap();
+        let level = line.level;
+        if !line.value.trim().is_empty() {
+            citation.xref = Some(line.value.trim().to_string());
+        }
+
+        while !record.is_empty() {
+            let mut consume = true;
+            let line = Line::peek(record).unwrap();
+            if line.level <= level {
+                break;
+            }
+
+            match line.tag {
+                "CALN" if line.level == level + 1 => {
+                    let number = line.value.trim();
+                    citation.call_numbers.push(CallNumber {
+                        number: (!number.is_empty()).then(|| number.to_string()),
+                        media: None,
+                    });
+                }
+                // 5.5 put MEDI directly under REPO, rather than under CALN
+                "MEDI" => {
+                    let media = SourceMedia::from_str(line.value).ok();
+                    match citation.call_numbers.last_mut() {
+                        Some(caln) if line.level > level + 1 => caln.media = media,
+                        _ => citation.call_numbers.push(CallNumber {
+                            number: None,
+                            media,
+                        }),
+                    }
+                }
+                "NOTE" if line.level == level + 1 => {
+                    if let Some(note) = parse::get_tag_value(record).unwrap() {
+                        citation.notes.push(Note { note: Some(note) });
+                    }
+                    consume = false;
+                }
+                _ => {}
+            }
+
+            if consume {
+                Line::parse(record).unwrap();
+            }
+        }
+
+        Ok(citation)
+    }
+
+    /// Whether any of the call numbers are held in this medium
+    pub fn has_media(&self, media: &SourceMedia) -> bool {
+        self.call_numbers
+            .iter()
+            .any(|c| c.media.as_ref() == Some(media))
+    }
+}
+
+/// A source held by a repository, with where to find it there. See
+/// `Gedcom::sources_in_repository`.
+#[derive(Clone, Copy, Debug)]
+pub struct RepositoryHolding<'a> {
+    pub source: &'a SourceRecord,
+    pub citation: &'a RepositoryCitation,
+}
+
+impl<'a> RepositoryHolding<'a> {
+    /// The call numbers the repository files the source under, if any
+    pub fn call_numbers(&self) -> impl Iterator<Item = &'a str> {
+        self.citation
+            .call_numbers
+            .iter()
+            .filter_map(|c| c.number.as_deref())
+    }
+
+    /// What the repository holds the source in, i.e. microfilm
+    pub fn media(&self) -> impl Iterator<Item = &'a SourceMedia> {
+        self.citation
+            .call_numbers
+            .iter()
+            .filter_map(|c| c.media.as_ref())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_media() {
+        assert!(SourceMedia::from_str("book") == Ok(SourceMedia::Book));
+        assert!(SourceMedia::from_str("Microfilm") == Ok(SourceMedia::Film));
+        assert!(SourceMedia::from_str("TOMBSTONE") == Ok(SourceMedia::Tombstone));
+        assert!(SourceMedia::from_str("Census") == Ok(SourceMedia::Other("Census".to_string())));
+        assert!(SourceMedia::from_str(" ").is_err());
+        assert!(SourceMedia::Other("Census".to_string()).to_string() == "Census");
+    }
+
+    #[test]
+    fn parse_citation() {
+        let data = [
+            "1 REPO @R1@",
+            "2 CALN 920.23",
+            "3 MEDI Book",
+            "2 CALN FHL 1234567",
+            "3 MEDI film",
+            "2 NOTE Ask at the desk",
+            "1 TITL Next",
+        ]
+        .join("\n");
+        let mut record = data.as_str();
+        let citation = RepositoryCitation::parse(&mut record).unwrap();
+
+        assert!(citation.xref.as_deref() == Some("@R1@"));
+        assert!(
+            citation.call_numbers
+                == [
+                    CallNumber {
+                        number: Some("920.23".to_string()),
+                        media: Some(SourceMedia::Book),
+                    },
+                    CallNumber {
+                        number: Some("FHL 1234567".to_string()),
+                        media: Some(SourceMedia::Film),
+                    },
+                ]
+        );
+        assert!(citation.notes.len() == 1);
+        assert!(citation.has_media(&SourceMedia::Film));
+        assert!(!citation.has_media(&SourceMedia::Tombstone));
+        assert!(record == "1 TITL Next");
+
+        let mut record = "1 REPO\n2 MEDI Electronic";
+        let citation = RepositoryCitation::parse(&mut record).unwrap();
+        assert!(citation.xref.is_none());
+        assert!(citation.has_media(&SourceMedia::Electronic));
+    }
+}
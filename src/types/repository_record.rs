@@ -0,0 +1,161 @@
+use crate::parse;
+
+use super::{Address, DateTime, Line, Note, UserReference};
+
+// REPOSITORY_RECORD:=
+// n @<XREF:REPO>@ REPO {1:1}
+// +1 NAME <NAME_OF_REPOSITORY> {1:1} p.54
+// +1 <<ADDRESS_STRUCTURE>> {0:1} p.31
+// +1 <<NOTE_STRUCTURE>> {0:M} p.37
+// +1 REFN <USER_REFERENCE_NUMBER> {0:M} p.63, 64
+// +2 TYPE <USER_REFERENCE_TYPE> {0:1} p.64
+// +1 RIN <AUTOMATED_RECORD_ID> {0:1} p.43
+// +1 <<CHANGE_DATE>> {0:1} p.31
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// A REPO record: an archive, library or person holding sources
+pub struct RepositoryRecord {
+    pub xref: String,
+    /// NAME
+    pub name: Option<String>,
+    /// ADDR, and any PHON, EMAIL, FAX and WWW
+    pub address: Option<Address>,
+    pub notes: Vec<Note>,
+    /// REFN
+    pub references: Vec<UserReference>,
+    /// RIN: The record's id in the program that wrote the file
+    pub rin: Option<String>,
+
+    /// CHAN: When the record was last changed
+    pub change_date: Option<DateTime>,
+
+    /// UID or _UID: Identifiers that stay with the record from program to
+    /// program, unlike its xref
+    pub uids: Vec<String>,
+}
+
+impl RepositoryRecord {
+    pub fn parse(record: &mut &str) -> RepositoryRecord {
+        let line = Line::parse(record).unwrap();
+        let level = line.level;
+        let mut repository = RepositoryRecord {
+            xref: line.xref.to_string(),
+            ..Default::default()
+        };
+
+        while !record.is_empty() {
+            let mut consume = true;
+            let line = Line::peek(record).unwrap();
+            if line.level <= level {
+                break;
+            }
+
+            match line.tag {
+                "NAME" if line.level == level + 1 => {
+                    repository.name = Some(line.value.to_string());
+                }
+                "ADDR" | "PHON" | "EMAIL" | "FAX" | "WWW" if line.level == level + 1 => {
+                    let address = Address::parse(record).unwrap();
+                    // The phone numbers may come before the address
+                    match &mut repository.address {
+                        Some(previous) => {
+                            previous.phone.extend(address.phone);
+                            previous.email.extend(address.email);
+                            previous.fax.extend(address.fax);
+                            previous.www.extend(address.www);
+                            if address.addr1.is_some() || address.city.is_some() {
+                                previous.addr1 = address.addr1;
+                                previous.addr2 = address.addr2;
+                                previous.addr3 = address.addr3;
+                                previous.city = address.city;
+                                previous.state = address.state;
+                                previous.postal_code = address.postal_code;
+                                previous.country = address.country;
+                            }
+                        }
+                        None => repository.address = Some(address),
+                    }
+                    consume = false;
+                }
+                "NOTE" if line.level == level + 1 => {
+                    if let Some(note) = parse::get_tag_value(record).unwrap() {
+                        repository.notes.push(Note { note: Some(note) });
+                    }
+                    consume = false;
+                }
+                "REFN" if line.level == level + 1 => {
+                    repository.references.push(UserReference::parse(record));
+                    consume = false;
+                }
+                "RIN" if line.level == level + 1 => {
+                    repository.rin = Some(line.value.to_string());
+                }
+                "CHAN" if line.level == level + 1 => {
+                    Line::parse(record).unwrap();
+                    let (buffer, change_date) = DateTime::parse(record);
+                    *record = buffer;
+                    repository.change_date = change_date;
+                    consume = false;
+                }
+                "UID" | "_UID" if line.level == level + 1 => {
+                    repository.uids.push(line.value.to_string());
+                }
+                _ => {}
+            }
+
+            if consume {
+                Line::parse(record).unwrap();
+            }
+        }
+
+        repository
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_record() {
+        let data = [
+            "0 @R1@ REPO",
+            "1 NAME Family History Library",
+            "1 ADDR",
+            "2 ADR1 35 North West Temple",
+            "2 CITY Salt Lake City",
+            "2 STAE UT",
+            "1 PHON +1-801-240-2331",
+            "1 PHON +1-801-240-1278",
+            "1 NOTE @N2@",
+            "1 REFN 01234567890123456789",
+            "2 TYPE reference",
+            "1 RIN 1",
+            "1 CHAN",
+            "2 DATE 12 MAR 2000",
+            "3 TIME 10:36:02",
+            "0 TRLR",
+        ]
+        .join("\n");
+        let mut record = data.as_str();
+        let repository = RepositoryRecord::parse(&mut record);
+
+        assert!(repository.xref == "@R1@");
+        assert!(repository.name.as_deref() == Some("Family History Library"));
+        let address = repository.address.unwrap();
+        assert!(address.addr1.as_deref() == Some("35 North West Temple"));
+        assert!(address.city.as_deref() == Some("Salt Lake City"));
+        assert!(address.phone == ["+1-801-240-2331", "+1-801-240-1278"]);
+        assert!(repository.notes[0].note.as_deref() == Some("@N2@"));
+        assert!(repository.references[0].r#type.as_deref() == Some("reference"));
+        assert!(repository.rin.as_deref() == Some("1"));
+        assert!(repository.change_date.unwrap().time.as_deref() == Some("10:36:02"));
+        assert!(record == "0 TRLR");
+
+        // A phone number, but no address
+        let mut record = "0 @R2@ REPO\n1 NAME Aunt Mary\n1 PHON 555-0100\n0 TRLR";
+        let repository = RepositoryRecord::parse(&mut record);
+        let address = repository.address.unwrap();
+        assert!(address.addr1.is_none() && address.phone == ["555-0100"]);
+    }
+}
@@ -0,0 +1,65 @@
+use std::str::FromStr;
+
+// RESTRICTION_NOTICE:= {Size=6:7}
+// [confidential | locked | privacy]
+// The restriction notice is defined for Ancestral File usage. Ancestral File
+// download GEDCOM files may contain this data.
+// confidential = This data was marked as confidential by the user.
+// locked = Some records in Ancestral File have been locked in their
+// current form.
+// privacy = Indicate that information concerning this record is not
+// present due to rights of or an approved request for privacy.
+//
+// GEDCOM 7 writes these in upper case, and allows more than one, separated
+// by commas, i.e. "CONFIDENTIAL, LOCKED".
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+/// A RESN restriction on a record or event
+pub enum Restriction {
+    /// Not to be shared outside of the people who keep the tree
+    Confidential,
+    /// Not to be changed
+    Locked,
+    /// Withheld, i.e. because the person is living
+    Privacy,
+}
+
+impl Restriction {
+    /// The restrictions in a RESN value. Anything that isn't a restriction
+    /// is ignored.
+    pub fn parse_list(notice: &str) -> Vec<Restriction> {
+        notice
+            .split(',')
+            .filter_map(|r| Restriction::from_str(r.trim()).ok())
+            .collect()
+    }
+}
+
+impl FromStr for Restriction {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Restriction, Self::Err> {
+        match input.to_lowercase().as_str() {
+            "confidential" => Ok(Restriction::Confidential),
+            "locked" => Ok(Restriction::Locked),
+            "privacy" => Ok(Restriction::Privacy),
+            _ => Err(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Restriction;
+
+    #[test]
+    fn parse_restrictions() {
+        assert!(Restriction::parse_list("confidential") == [Restriction::Confidential]);
+        assert!(
+            Restriction::parse_list("CONFIDENTIAL, LOCKED")
+                == [Restriction::Confidential, Restriction::Locked]
+        );
+        assert!(Restriction::parse_list("privacy,unknown") == [Restriction::Privacy]);
+        assert!(Restriction::parse_list("").is_empty());
+    }
+}
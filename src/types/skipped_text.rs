@@ -0,0 +1,15 @@
+/// The text of a NOTE or BLOB that was too long to keep, when parsing with
+/// `GedcomConfig::max_text_length`. The structure itself is still parsed,
+/// just without its value, so a note's record and position in the tree
+/// remain.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SkippedText {
+    /// The xref of the record it was in, if the record has one
+    pub xref: Option<String>,
+    /// The line of the NOTE or BLOB tag
+    pub line: usize,
+    pub tag: String,
+    /// The length of the text in bytes, once its CONC and CONT lines are
+    /// joined
+    pub length: usize,
+}
@@ -61,7 +61,7 @@ impl Source {
 
             let mut next = Line::peek(&mut buffer).unwrap();
 
-            while next.level >= line.level {
+            while next.level > line.level {
                 // We don't want to consume the line yet because we may need
                 // the original for a parser.
                 let inner_line: Line = Line::peek(&mut buffer).unwrap();
@@ -74,7 +74,6 @@ impl Source {
                         // but not a part of the GEDCOM spec.
                         // The next level (3) may contain RIN, some sort of internal id
                         // but is probably not useful for anything
-                        println!("Skipping _TREE");
                         // Consume the line
                         Line::parse(&mut buffer).unwrap();
                     }
@@ -93,8 +92,6 @@ impl Source {
                         (buffer, source.data) = SourceData::parse(buffer);
                     }
                     _ => {
-                        println!("Unknown line: {:?}", inner_line);
-
                         // consume the line so we can parse the next
                         Line::parse(&mut buffer).unwrap();
                     }
@@ -29,10 +29,12 @@ use winnow::prelude::*;
 // +1 QUAY <CERTAINTY_ASSESSMENT> {0:1} p.43
 // ]
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct SourceCitation {
     pub xref: Option<String>,
-    pub page: Option<i32>,
+    /// PAGE: Where in the source the information is, i.e. "Folio 12,
+    /// entry 3"
+    pub page: Option<String>,
     pub event: Option<EventTypeCitedFrom>,
     pub data: Option<SourceCitationData>,
     pub media: Vec<Object>,
@@ -72,17 +74,15 @@ impl SourceCitation {
                     consume = false;
                 }
                 "OBJE" => {
-                    let obj = Object {
-                        xref: Some(line.value.to_string()),
-                    };
-                    sc.media.push(obj);
+                    sc.media.push(Object::parse(record).unwrap());
+                    consume = false;
                 }
                 "PAGE" => {
-                    sc.page = Some(line.value.parse().unwrap());
+                    sc.page = Some(line.value.to_string());
                 }
                 "QUAY" => {
-                    let quay = Quay::from_str(line.value).unwrap();
-                    sc.quay = Some(quay);
+                    // Anything but 0 to 3 says nothing about the evidence
+                    sc.quay = Quay::from_str(line.value.trim()).ok();
                 }
                 "SOUR" => {
                     sc.xref = Some(line.value.to_string());
@@ -93,10 +93,13 @@ impl SourceCitation {
             if consume {
                 Line::parse(record).unwrap();
             }
+            if record.is_empty() {
+                break;
+            }
             // If the next level matches our initial level, we're done parsing
             // this structure.
             line = Line::peek(record).unwrap();
-            if line.level == level {
+            if line.level <= level {
                 break;
             }
         }
@@ -105,7 +108,7 @@ impl SourceCitation {
     }
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct SourceCitationData {
     pub date: Option<String>,
     pub text: Option<Note>,
@@ -138,10 +141,13 @@ impl SourceCitationData {
             if consume {
                 Line::parse(record).unwrap();
             }
+            if record.is_empty() {
+                break;
+            }
             // If the next level matches our initial level, we're done parsing
             // this structure.
             line = Line::peek(record).unwrap();
-            if line.level == level {
+            if line.level <= level {
                 break;
             }
         }
@@ -188,6 +194,6 @@ mod tests {
         assert!(event.role.unwrap() == "CHIL");
 
         assert!(detail.page.is_some());
-        assert!(detail.page.unwrap() == 42);
+        assert!(detail.page.unwrap() == "42");
     }
 }
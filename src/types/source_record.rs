@@ -0,0 +1,168 @@
+use crate::parse;
+
+use super::{DateTime, Line, Note, Object, RepositoryCitation, SourceMedia};
+
+// SOURCE_RECORD:=
+// n @<XREF:SOUR>@ SOUR {1:1}
+// +1 DATA {0:1}
+// +2 EVEN <EVENTS_RECORDED> {0:M} p.50
+// +3 DATE <DATE_PERIOD> {0:1} p.50
+// +3 PLAC <SOURCE_JURISDICTION_PLACE> {0:1} p.62
+// +2 AGNC <RESPONSIBLE_AGENCY> {0:1} p.60
+// +2 <<NOTE_STRUCTURE>> {0:M} p.37
+// +1 AUTH <SOURCE_ORIGINATOR> {0:1} p.62
+// +2 [CONC|CONT] <SOURCE_ORIGINATOR> {0:M} p.62
+// +1 TITL <SOURCE_DESCRIPTIVE_TITLE> {0:1} p.62
+// +2 [CONC|CONT] <SOURCE_DESCRIPTIVE_TITLE> {0:M} p.62
+// +1 ABBR <SOURCE_FILED_BY_ENTRY> {0:1} p.62
+// +1 PUBL <SOURCE_PUBLICATION_FACTS> {0:1} p.62
+// +2 [CONC|CONT] <SOURCE_PUBLICATION_FACTS> {0:M} p.62
+// +1 TEXT <TEXT_FROM_SOURCE> {0:1} p.63
+// +2 [CONC|CONT] <TEXT_FROM_SOURCE> {0:M} p.63
+// +1 <<SOURCE_REPOSITORY_CITATION>> {0:M} p.40
+// +1 REFN <USER_REFERENCE_NUMBER> {0:M} p.63, 64
+// +2 TYPE <USER_REFERENCE_TYPE> {0:1} p.64
+// +1 RIN <AUTOMATED_RECORD_ID> {0:1} p.43
+// +1 <<CHANGE_DATE>> {0:1} p.31
+// +1 <<NOTE_STRUCTURE>> {0:M} p.37
+// +1 <<MULTIMEDIA_LINK>> {0:M} p.37, 26
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// A SOUR record, describing a source of information
+pub struct SourceRecord {
+    pub xref: String,
+    /// TITL
+    pub title: Option<String>,
+    /// AUTH
+    pub author: Option<String>,
+    /// ABBR: A short title, used for sorting and finding the source
+    pub abbreviation: Option<String>,
+    /// PUBL: When and where the source was published
+    pub publication: Option<String>,
+    /// TEXT: A verbatim copy of relevant text from the source
+    pub text: Option<String>,
+    /// REPO: The repositories holding the source
+    pub repositories: Vec<RepositoryCitation>,
+    pub notes: Vec<Note>,
+    pub media: Vec<Object>,
+
+    /// CHAN: When the record was last changed
+    pub change_date: Option<DateTime>,
+
+    /// UID or _UID: Identifiers that stay with the record from program to
+    /// program, unlike its xref
+    pub uids: Vec<String>,
+}
+
+impl SourceRecord {
+    pub fn parse(record: &mut &str) -> SourceRecord {
+        let line = Line::parse(record).unwrap();
+        let level = line.level;
+        let mut source = SourceRecord {
+            xref: line.xref.to_string(),
+            ..Default::default()
+        };
+
+        while !record.is_empty() {
+            let mut consume = true;
+            let line = Line::peek(record).unwrap();
+            if line.level <= level {
+                break;
+            }
+
+            // Sub-structures of the current tag are handled by their own parsers
+            if line.level > level + 1 {
+                Line::parse(record).unwrap();
+                continue;
+            }
+
+            match line.tag {
+                "TITL" | "AUTH" | "ABBR" | "PUBL" | "TEXT" => {
+                    let value = parse::get_tag_value(record).unwrap();
+                    match line.tag {
+                        "TITL" => source.title = value,
+                        "AUTH" => source.author = value,
+                        "ABBR" => source.abbreviation = value,
+                        "PUBL" => source.publication = value,
+                        _ => source.text = value,
+                    }
+                    consume = false;
+                }
+                "REPO" => {
+                    source
+                        .repositories
+                        .push(RepositoryCitation::parse(record).unwrap());
+                    consume = false;
+                }
+                "NOTE" => {
+                    if let Some(note) = parse::get_tag_value(record).unwrap() {
+                        source.notes.push(Note { note: Some(note) });
+                    }
+                    consume = false;
+                }
+                "OBJE" => {
+                    source.media.push(Object::parse(record).unwrap());
+                    consume = false;
+                }
+                "UID" | "_UID" => {
+                    source.uids.push(line.value.to_string());
+                }
+                "CHAN" => {
+                    Line::parse(record).unwrap();
+                    let (buffer, change_date) = DateTime::parse(record);
+                    *record = buffer;
+                    source.change_date = change_date;
+                    consume = false;
+                }
+                _ => {}
+            }
+
+            if consume {
+                Line::parse(record).unwrap();
+            }
+        }
+
+        source
+    }
+
+    /// Whether any repository holds this source in the given medium
+    pub fn has_media(&self, media: &SourceMedia) -> bool {
+        self.repositories.iter().any(|r| r.has_media(media))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let data = [
+            "0 @S1@ SOUR",
+            "1 TITL Parish registers of St Mary,",
+            "2 CONC  Anytown",
+            "1 AUTH Church of England",
+            "1 REPO @R1@",
+            "2 CALN 920.23",
+            "3 MEDI Microfilm",
+            "1 DATA",
+            "2 AGNC Parish clerk",
+            "1 NOTE @N1@",
+            "1 CHAN",
+            "2 DATE 14 JAN 2001",
+            "0 TRLR",
+        ]
+        .join("\n");
+        let mut record = data.as_str();
+        let source = SourceRecord::parse(&mut record);
+
+        assert!(source.xref == "@S1@");
+        assert!(source.title.as_deref() == Some("Parish registers of St Mary, Anytown"));
+        assert!(source.author.as_deref() == Some("Church of England"));
+        assert!(source.repositories.len() == 1);
+        assert!(source.has_media(&SourceMedia::Film));
+        assert!(source.notes.len() == 1);
+        assert!(source.change_date.unwrap().date.as_deref() == Some("14 JAN 2001"));
+        assert!(record == "0 TRLR");
+    }
+}
@@ -24,6 +24,9 @@ pub struct Submitter {
     pub rin: Option<String>,
     pub note: Option<Note>,
     pub change_date: Option<DateTime>,
+    /// UID or _UID: Identifiers that stay with the record from program to
+    /// program, unlike its xref
+    pub uids: Vec<String>,
 }
 
 impl Submitter {
@@ -41,6 +44,7 @@ impl Submitter {
             rin: None,
             note: None,
             change_date: None,
+            uids: vec![],
         };
         let mut line = Line::peek(&mut buffer).unwrap();
 
@@ -87,6 +91,10 @@ impl Submitter {
                         "NOTE" => {
                             submitter.note = Some(Note::parse(&mut buffer).unwrap());
                         }
+                        "UID" | "_UID" if line.level == 1 => {
+                            submitter.uids.push(line.value.to_string());
+                            Line::parse(&mut buffer).unwrap();
+                        }
                         "RFN" => {
                             let rfn = line.value;
                             submitter.rfn = Some(rfn.to_string());
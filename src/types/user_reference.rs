@@ -0,0 +1,40 @@
+use super::Line;
+
+// n REFN <USER_REFERENCE_NUMBER> {0:M} p.63, 64
+// +1 TYPE <USER_REFERENCE_TYPE> {0:1} p.64
+//
+// USER_REFERENCE_NUMBER:= {Size=1:20}
+// A user-defined number or text that the submitter uses to identify this
+// record.
+
+#[derive(Clone, Debug, Default, PartialEq)]
+/// REFN: The submitter's own number for a record, i.e. from a card index
+pub struct UserReference {
+    pub number: String,
+    /// TYPE: What kind of number it is
+    pub r#type: Option<String>,
+}
+
+impl UserReference {
+    pub fn parse(record: &mut &str) -> UserReference {
+        let line = Line::parse(record).unwrap();
+        let level = line.level;
+        let mut reference = UserReference {
+            number: line.value.trim().to_string(),
+            r#type: None,
+        };
+
+        while !record.is_empty() {
+            let line = Line::peek(record).unwrap();
+            if line.level <= level {
+                break;
+            }
+            if line.tag == "TYPE" && line.level == level + 1 {
+                reference.r#type = Some(line.value.to_string());
+            }
+            Line::parse(record).unwrap();
+        }
+
+        reference
+    }
+}
@@ -0,0 +1,1439 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "rules-config")]
+use std::path::Path;
+
+use crate::changes;
+#[cfg(feature = "rules-config")]
+use crate::error::GedcomError;
+use crate::renumber::is_pointer;
+use crate::types::{
+    EventDetail, Gedcom, Gender, Note, Object, SourceCitation, MAX_LIFESPAN, MIN_PARENT_AGE,
+};
+
+// Sanity checks for a parsed Gedcom.
+//
+// Each check is a Rule, with a name to refer to it by, a default severity,
+// and whether it's run by default. A ValidationConfig can turn any rule on
+// or off and report it at another severity, since people disagree about
+// what's a problem: a missing name matters more to most than a missing sex,
+// and neither matters at all to some. Adding a rule means adding it to
+// Rule::ALL, naming it, and giving Rule::check something to run.
+//
+// Each problem found is reported as a Finding. Where there's an obvious way to
+// repair it, the finding carries a SuggestedFix, which can be shown to the
+// user and applied to the Gedcom with SuggestedFix::apply.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+/// The check that produced a Finding
+pub enum Rule {
+    /// An individual died before they were born
+    DeathBeforeBirth,
+    /// An individual lived longer than `ValidationConfig::max_lifespan` years
+    ImplausibleLifespan,
+    /// A parent was younger than `ValidationConfig::min_parent_age` when
+    /// their child was born
+    ParentTooYoung,
+    /// An individual's FAMC or FAMS points to a family that doesn't exist
+    DanglingFamilyLink,
+    /// A family's HUSB, WIFE or CHIL points to an individual that doesn't exist
+    DanglingFamilyMember,
+    /// A record's CHAN date is after the file's DATE, which suggests clock
+    /// problems or hand editing
+    ChangedAfterFileDate,
+    /// An individual is their own ancestor, through FAMC, FAMS, HUSB, WIFE
+    /// or CHIL links that go round in a circle
+    AncestorCycle,
+    /// Nothing points to a record, or an individual isn't in any family.
+    /// See `orphaned_records`.
+    OrphanedRecord,
+    /// An individual has no NAME. Off by default.
+    MissingName,
+    /// An individual's SEX is missing, or U. Off by default.
+    MissingSex,
+}
+
+impl Rule {
+    /// Every rule, in the order they're run
+    pub const ALL: [Rule; 10] = [
+        Rule::DeathBeforeBirth,
+        Rule::ImplausibleLifespan,
+        Rule::ParentTooYoung,
+        Rule::DanglingFamilyLink,
+        Rule::DanglingFamilyMember,
+        Rule::ChangedAfterFileDate,
+        Rule::AncestorCycle,
+        Rule::OrphanedRecord,
+        Rule::MissingName,
+        Rule::MissingSex,
+    ];
+
+    /// The name of the rule, as used in reports
+    pub fn name(&self) -> &'static str {
+        match self {
+            Rule::DeathBeforeBirth => "death-before-birth",
+            Rule::ImplausibleLifespan => "implausible-lifespan",
+            Rule::ParentTooYoung => "parent-too-young",
+            Rule::DanglingFamilyLink => "dangling-family-link",
+            Rule::DanglingFamilyMember => "dangling-family-member",
+            Rule::ChangedAfterFileDate => "changed-after-file-date",
+            Rule::AncestorCycle => "ancestor-cycle",
+            Rule::OrphanedRecord => "orphaned-record",
+            Rule::MissingName => "missing-name",
+            Rule::MissingSex => "missing-sex",
+        }
+    }
+
+    /// How serious the rule's findings are, unless configured otherwise
+    pub fn default_severity(&self) -> Severity {
+        match self {
+            Rule::DeathBeforeBirth
+            | Rule::DanglingFamilyLink
+            | Rule::DanglingFamilyMember
+            | Rule::AncestorCycle => Severity::Error,
+            Rule::ImplausibleLifespan
+            | Rule::ParentTooYoung
+            | Rule::ChangedAfterFileDate
+            | Rule::OrphanedRecord
+            | Rule::MissingName => Severity::Warning,
+            Rule::MissingSex => Severity::Info,
+        }
+    }
+
+    /// Whether the rule is run, unless configured otherwise. Rules about
+    /// what's missing are off, since plenty of trees leave things out on
+    /// purpose.
+    pub fn enabled_by_default(&self) -> bool {
+        !matches!(self, Rule::MissingName | Rule::MissingSex)
+    }
+}
+
+impl FromStr for Rule {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Rule, Self::Err> {
+        match input {
+            "death-before-birth" => Ok(Rule::DeathBeforeBirth),
+            "implausible-lifespan" => Ok(Rule::ImplausibleLifespan),
+            "parent-too-young" => Ok(Rule::ParentTooYoung),
+            "dangling-family-link" => Ok(Rule::DanglingFamilyLink),
+            "dangling-family-member" => Ok(Rule::DanglingFamilyMember),
+            "changed-after-file-date" => Ok(Rule::ChangedAfterFileDate),
+            "ancestor-cycle" => Ok(Rule::AncestorCycle),
+            "orphaned-record" => Ok(Rule::OrphanedRecord),
+            "missing-name" => Ok(Rule::MissingName),
+            "missing-sex" => Ok(Rule::MissingSex),
+            _ => Err(()),
+        }
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+/// How serious a Finding is
+pub enum Severity {
+    /// Worth knowing, but not a problem
+    Info,
+    /// Probably fine, but worth a look
+    Warning,
+    /// The data is wrong, or inconsistent
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+impl FromStr for Severity {
+    type Err = ();
+
+    fn from_str(input: &str) -> Result<Severity, Self::Err> {
+        match input {
+            "info" => Ok(Severity::Info),
+            "warning" => Ok(Severity::Warning),
+            "error" => Ok(Severity::Error),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+/// The kind of link from an individual to a family
+pub enum FamilyLink {
+    /// FAMC: the individual is a child of the family
+    Child,
+    /// FAMS: the individual is a spouse in the family
+    Spouse,
+}
+
+impl fmt::Display for FamilyLink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FamilyLink::Child => write!(f, "FAMC"),
+            FamilyLink::Spouse => write!(f, "FAMS"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+/// A machine-readable repair for a Finding
+pub enum SuggestedFix {
+    /// Swap the dates of the individual's birth and death
+    SwapBirthAndDeath { individual: String },
+    /// Remove a FAMC or FAMS link to a family that doesn't exist
+    RemoveFamilyLink {
+        individual: String,
+        family: String,
+        link: FamilyLink,
+    },
+    /// Remove the HUSB, WIFE or CHIL of a family that doesn't exist
+    RemoveFamilyMember { family: String, individual: String },
+}
+
+impl SuggestedFix {
+    /// A short, stable identifier for the kind of fix
+    pub fn action(&self) -> &'static str {
+        match self {
+            SuggestedFix::SwapBirthAndDeath { .. } => "swap-birth-death",
+            SuggestedFix::RemoveFamilyLink { .. } => "remove-family-link",
+            SuggestedFix::RemoveFamilyMember { .. } => "remove-family-member",
+        }
+    }
+
+    /// Apply the fix to the Gedcom, returning whether anything was changed.
+    pub fn apply(&self, gedcom: &mut Gedcom) -> bool {
+        match self {
+            SuggestedFix::SwapBirthAndDeath { individual } => {
+                let individual = match gedcom
+                    .individuals
+                    .iter_mut()
+                    .find(|i| i.xref.as_deref() == Some(individual.as_str()))
+                {
+                    Some(individual) => individual,
+                    None => return false,
+                };
+
+                let birth = individual.birth.first_mut();
+                let death = individual.death.first_mut().and_then(|d| d.event.as_mut());
+                match (birth, death) {
+                    (Some(birth), Some(death)) => {
+                        std::mem::swap(&mut birth.event.detail.date, &mut death.date);
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            SuggestedFix::RemoveFamilyLink {
+                individual,
+                family,
+                link,
+            } => {
+                let individual = match gedcom
+                    .individuals
+                    .iter_mut()
+                    .find(|i| i.xref.as_deref() == Some(individual.as_str()))
+                {
+                    Some(individual) => individual,
+                    None => return false,
+                };
+
+                let links = match link {
+                    FamilyLink::Child => &mut individual.famc,
+                    FamilyLink::Spouse => &mut individual.fams,
+                };
+                let count = links.len();
+                links.retain(|f| f.xref != *family);
+                links.len() != count
+            }
+            SuggestedFix::RemoveFamilyMember { family, individual } => {
+                let family = match gedcom.families.iter_mut().find(|f| f.xref == *family) {
+                    Some(family) => family,
+                    None => return false,
+                };
+
+                let mut changed = false;
+                if family.husband_xref() == Some(individual.as_str()) {
+                    family.husband = None;
+                    changed = true;
+                }
+                if family.wife_xref() == Some(individual.as_str()) {
+                    family.wife = None;
+                    changed = true;
+                }
+                let count = family.children.len();
+                family.children.retain(|c| c != individual);
+                changed || family.children.len() != count
+            }
+        }
+    }
+}
+
+impl fmt::Display for SuggestedFix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SuggestedFix::SwapBirthAndDeath { individual } => {
+                write!(f, "swap birth and death dates of {}", individual)
+            }
+            SuggestedFix::RemoveFamilyLink {
+                individual,
+                family,
+                link,
+            } => write!(f, "remove dangling {} {} from {}", link, family, individual),
+            SuggestedFix::RemoveFamilyMember { family, individual } => {
+                write!(f, "remove dangling member {} from {}", individual, family)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+/// A problem found while validating a Gedcom
+pub struct Finding {
+    pub rule: Rule,
+    pub severity: Severity,
+    /// The xref of the record the problem was found in
+    pub xref: Option<String>,
+    /// The line the record starts on, if we know where it came from
+    pub line: Option<usize>,
+    /// A human-readable description of the problem
+    pub message: String,
+    /// How the problem could be repaired, if we know
+    pub fix: Option<SuggestedFix>,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}]", self.severity, self.rule)?;
+        if let Some(xref) = &self.xref {
+            write!(f, " {}", xref)?;
+        }
+        if let Some(line) = self.line {
+            write!(f, " (line {})", line)?;
+        }
+        write!(f, ": {}", self.message)?;
+        if let Some(fix) = &self.fix {
+            write!(f, " (suggested fix: {})", fix)?;
+        }
+        Ok(())
+    }
+}
+
+/// Which rules to run, how serious their findings are, and the limits they
+/// check against. The default runs the rules that are enabled by default,
+/// at their default severities. See `GedcomConfig::validation` to parse a
+/// file with a configuration for `Gedcom::validate`.
+///
+/// With the `rules-config` feature, this can be loaded from a TOML or JSON
+/// file, so that an organization can codify its own data standards:
+///
+/// ```toml
+/// max_lifespan = 120
+/// min_parent_age = 14
+///
+/// [rules.changed-after-file-date]
+/// enabled = false
+///
+/// [rules.implausible-lifespan]
+/// severity = "error"
+///
+/// [rules.missing-name]
+/// enabled = true
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationConfig {
+    /// The rules that will be run, though they're off by default
+    pub enabled: HashSet<Rule>,
+    /// The rules that won't be run
+    pub disabled: HashSet<Rule>,
+    /// Severities to report rules' findings at, instead of their own
+    pub severities: HashMap<Rule, Severity>,
+    /// How many years anyone can plausibly live
+    pub max_lifespan: i32,
+    /// How old anyone must be to have a child
+    pub min_parent_age: i32,
+}
+
+impl Default for ValidationConfig {
+    fn default() -> ValidationConfig {
+        ValidationConfig {
+            enabled: HashSet::new(),
+            disabled: HashSet::new(),
+            severities: HashMap::new(),
+            max_lifespan: MAX_LIFESPAN,
+            min_parent_age: MIN_PARENT_AGE,
+        }
+    }
+}
+
+impl ValidationConfig {
+    pub fn new() -> ValidationConfig {
+        ValidationConfig::default()
+    }
+
+    /// Run `rule`, even if it's off by default
+    pub fn enable(mut self, rule: Rule) -> ValidationConfig {
+        self.disabled.remove(&rule);
+        self.enabled.insert(rule);
+        self
+    }
+
+    /// Don't run `rule`
+    pub fn disable(mut self, rule: Rule) -> ValidationConfig {
+        self.enabled.remove(&rule);
+        self.disabled.insert(rule);
+        self
+    }
+
+    /// Report what `rule` finds at `severity`
+    pub fn severity(mut self, rule: Rule, severity: Severity) -> ValidationConfig {
+        self.severities.insert(rule, severity);
+        self
+    }
+
+    pub fn max_lifespan(mut self, years: i32) -> ValidationConfig {
+        self.max_lifespan = years;
+        self
+    }
+
+    pub fn min_parent_age(mut self, years: i32) -> ValidationConfig {
+        self.min_parent_age = years;
+        self
+    }
+
+    /// Is `rule` going to be run?
+    pub fn is_enabled(&self, rule: Rule) -> bool {
+        if self.disabled.contains(&rule) {
+            false
+        } else {
+            self.enabled.contains(&rule) || rule.enabled_by_default()
+        }
+    }
+
+    /// The severity `rule`'s findings will be reported at
+    pub fn severity_of(&self, rule: Rule) -> Severity {
+        self.severities
+            .get(&rule)
+            .copied()
+            .unwrap_or_else(|| rule.default_severity())
+    }
+
+    /// Load the configuration from a file, as JSON if its name ends in
+    /// `.json`, or TOML otherwise
+    #[cfg(feature = "rules-config")]
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<ValidationConfig, GedcomError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)?;
+        if path
+            .extension()
+            .is_some_and(|e| e.eq_ignore_ascii_case("json"))
+        {
+            ValidationConfig::from_json(&text)
+        } else {
+            ValidationConfig::from_toml(&text)
+        }
+    }
+
+    #[cfg(feature = "rules-config")]
+    pub fn from_toml(text: &str) -> Result<ValidationConfig, GedcomError> {
+        let file: ConfigFile = toml::from_str(text).map_err(|err| config_error(err.message()))?;
+        file.into_config()
+    }
+
+    #[cfg(feature = "rules-config")]
+    pub fn from_json(text: &str) -> Result<ValidationConfig, GedcomError> {
+        let file: ConfigFile =
+            serde_json::from_str(text).map_err(|err| config_error(&err.to_string()))?;
+        file.into_config()
+    }
+}
+
+/// A ValidationConfig, as it's written in a file
+#[cfg(feature = "rules-config")]
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    max_lifespan: Option<i32>,
+    min_parent_age: Option<i32>,
+    #[serde(default)]
+    rules: HashMap<String, RuleConfig>,
+}
+
+#[cfg(feature = "rules-config")]
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RuleConfig {
+    enabled: Option<bool>,
+    severity: Option<String>,
+}
+
+#[cfg(feature = "rules-config")]
+impl ConfigFile {
+    fn into_config(self) -> Result<ValidationConfig, GedcomError> {
+        let mut config = ValidationConfig::new();
+        if let Some(years) = self.max_lifespan {
+            config = config.max_lifespan(years);
+        }
+        if let Some(years) = self.min_parent_age {
+            config = config.min_parent_age(years);
+        }
+        for (name, settings) in self.rules {
+            let rule = Rule::from_str(&name)
+                .map_err(|_| config_error(&format!("Unknown rule: {}", name)))?;
+            match settings.enabled {
+                Some(true) => config = config.enable(rule),
+                Some(false) => config = config.disable(rule),
+                None => {}
+            }
+            if let Some(severity) = settings.severity {
+                let severity = Severity::from_str(&severity).map_err(|_| {
+                    config_error(&format!("Unknown severity for {}: {}", name, severity))
+                })?;
+                config = config.severity(rule, severity);
+            }
+        }
+        Ok(config)
+    }
+}
+
+#[cfg(feature = "rules-config")]
+fn config_error(message: &str) -> GedcomError {
+    GedcomError::ParseError {
+        line: None,
+        offset: None,
+        tag: String::new(),
+        message: format!("Invalid rule configuration: {}", message.trim()),
+    }
+}
+
+/// Run every check against the Gedcom and return what was found.
+pub fn validate_gedcom(gedcom: &Gedcom) -> Vec<Finding> {
+    validate_gedcom_with_config(gedcom, &ValidationConfig::default())
+}
+
+/// Run the checks enabled in `config` against the Gedcom and return what
+/// was found.
+pub fn validate_gedcom_with_config(gedcom: &Gedcom, config: &ValidationConfig) -> Vec<Finding> {
+    let mut findings = vec![];
+    for rule in Rule::ALL.iter().copied().filter(|r| config.is_enabled(*r)) {
+        let severity = config.severity_of(rule);
+        for mut finding in rule.check(gedcom, config) {
+            finding.severity = severity;
+            // Point at where the record came from, if we know
+            finding.line = finding
+                .xref
+                .as_ref()
+                .and_then(|xref| gedcom.record_positions.get(xref))
+                .map(|position| position.line);
+            findings.push(finding);
+        }
+    }
+    findings
+}
+
+impl Rule {
+    /// Everything the rule finds in the Gedcom, at its default severity
+    fn check(&self, gedcom: &Gedcom, config: &ValidationConfig) -> Vec<Finding> {
+        match self {
+            Rule::DeathBeforeBirth => death_before_birth(gedcom),
+            Rule::ImplausibleLifespan => implausible_lifespans(gedcom, config),
+            Rule::ParentTooYoung => young_parents(gedcom, config),
+            Rule::DanglingFamilyLink => dangling_family_links(gedcom),
+            Rule::DanglingFamilyMember => dangling_family_members(gedcom),
+            Rule::ChangedAfterFileDate => changed_after_file_date(gedcom),
+            Rule::AncestorCycle => ancestor_cycles(gedcom),
+            Rule::OrphanedRecord => orphans(gedcom),
+            Rule::MissingName => missing_names(gedcom),
+            Rule::MissingSex => missing_sex(gedcom),
+        }
+    }
+}
+
+fn finding(rule: Rule, xref: &str, message: String, fix: Option<SuggestedFix>) -> Finding {
+    Finding {
+        rule,
+        severity: rule.default_severity(),
+        xref: Some(xref.to_string()),
+        line: None,
+        message,
+        fix,
+    }
+}
+
+fn death_before_birth(gedcom: &Gedcom) -> Vec<Finding> {
+    let mut findings = vec![];
+    for individual in &gedcom.individuals {
+        let xref = individual.xref.as_deref().unwrap_or_default();
+        if let (Some(birth), Some(death)) = (individual.birth_year(), individual.death_year()) {
+            if death < birth {
+                // We can only swap the dates if they came from BIRT and DEAT
+                let fix = if individual.birth.is_empty() || individual.death.is_empty() {
+                    None
+                } else {
+                    Some(SuggestedFix::SwapBirthAndDeath {
+                        individual: xref.to_string(),
+                    })
+                };
+                let message = format!("died in {}, before being born in {}", death, birth);
+                findings.push(finding(Rule::DeathBeforeBirth, xref, message, fix));
+            }
+        }
+    }
+    findings
+}
+
+fn implausible_lifespans(gedcom: &Gedcom, config: &ValidationConfig) -> Vec<Finding> {
+    let mut findings = vec![];
+    for individual in &gedcom.individuals {
+        let xref = individual.xref.as_deref().unwrap_or_default();
+        if let (Some(birth), Some(death)) = (individual.birth_year(), individual.death_year()) {
+            // A death before birth is a different problem
+            if death >= birth && death - birth > config.max_lifespan {
+                let message = format!("lived for {} years ({}-{})", death - birth, birth, death);
+                findings.push(finding(Rule::ImplausibleLifespan, xref, message, None));
+            }
+        }
+    }
+    findings
+}
+
+fn young_parents(gedcom: &Gedcom, config: &ValidationConfig) -> Vec<Finding> {
+    let mut findings = vec![];
+    for family in &gedcom.families {
+        let parents = family.husband_xref().into_iter().chain(family.wife_xref());
+        for parent in parents.filter_map(|p| find_individual(gedcom, p)) {
+            let parent_birth = match parent.birth_year() {
+                Some(year) => year,
+                None => continue,
+            };
+            for child in family
+                .children
+                .iter()
+                .filter_map(|c| find_individual(gedcom, c))
+            {
+                if let Some(child_birth) = child.birth_year() {
+                    if child_birth - parent_birth < config.min_parent_age {
+                        let message = format!(
+                            "{} was born in {}, when parent {} was {}",
+                            child.xref.as_deref().unwrap_or_default(),
+                            child_birth,
+                            parent.xref.as_deref().unwrap_or_default(),
+                            child_birth - parent_birth
+                        );
+                        findings.push(finding(Rule::ParentTooYoung, &family.xref, message, None));
+                    }
+                }
+            }
+        }
+    }
+    findings
+}
+
+fn dangling_family_links(gedcom: &Gedcom) -> Vec<Finding> {
+    let families: HashSet<&str> = gedcom.families.iter().map(|f| f.xref.as_str()).collect();
+    let mut findings = vec![];
+    for individual in &gedcom.individuals {
+        let xref = individual.xref.as_deref().unwrap_or_default();
+        for (links, link) in [
+            (&individual.famc, FamilyLink::Child),
+            (&individual.fams, FamilyLink::Spouse),
+        ] {
+            for family in links {
+                if !families.contains(family.xref.as_str()) {
+                    let fix = SuggestedFix::RemoveFamilyLink {
+                        individual: xref.to_string(),
+                        family: family.xref.clone(),
+                        link,
+                    };
+                    let message = format!("{} {} does not exist", link, family.xref);
+                    findings.push(finding(Rule::DanglingFamilyLink, xref, message, Some(fix)));
+                }
+            }
+        }
+    }
+    findings
+}
+
+fn dangling_family_members(gedcom: &Gedcom) -> Vec<Finding> {
+    let individuals: HashSet<&str> = gedcom
+        .individuals
+        .iter()
+        .filter_map(|i| i.xref.as_deref())
+        .collect();
+    let mut findings = vec![];
+    for family in &gedcom.families {
+        let members = family
+            .husband_xref()
+            .into_iter()
+            .chain(family.wife_xref())
+            .chain(family.children.iter().map(|c| c.as_str()));
+        for member in members {
+            if !individuals.contains(member) {
+                let fix = SuggestedFix::RemoveFamilyMember {
+                    family: family.xref.clone(),
+                    individual: member.to_string(),
+                };
+                let message = format!("member {} does not exist", member);
+                findings.push(finding(
+                    Rule::DanglingFamilyMember,
+                    &family.xref,
+                    message,
+                    Some(fix),
+                ));
+            }
+        }
+    }
+    findings
+}
+
+fn changed_after_file_date(gedcom: &Gedcom) -> Vec<Finding> {
+    let file_date = match gedcom.file_date() {
+        Some(date) => date,
+        None => return vec![],
+    };
+    let mut findings = vec![];
+    for change in changes::record_changes(gedcom) {
+        if change.changed > file_date {
+            let message = format!(
+                "changed {}, after the file was created {}",
+                change.changed, file_date
+            );
+            findings.push(Finding {
+                xref: Some(change.xref.to_string()),
+                ..finding(Rule::ChangedAfterFileDate, "", message, None)
+            });
+        }
+    }
+    findings
+}
+
+fn orphans(gedcom: &Gedcom) -> Vec<Finding> {
+    orphaned_records(gedcom)
+        .into_iter()
+        .map(|orphan| {
+            let message = match orphan.tag.as_str() {
+                "INDI" => "isn't linked to any family".to_string(),
+                tag => format!("nothing points to this {} record", tag),
+            };
+            finding(Rule::OrphanedRecord, &orphan.xref, message, None)
+        })
+        .collect()
+}
+
+fn missing_names(gedcom: &Gedcom) -> Vec<Finding> {
+    gedcom
+        .individuals
+        .iter()
+        .filter(|i| i.primary_name().is_none())
+        .map(|i| {
+            let xref = i.xref.as_deref().unwrap_or_default();
+            finding(Rule::MissingName, xref, "has no name".to_string(), None)
+        })
+        .collect()
+}
+
+fn missing_sex(gedcom: &Gedcom) -> Vec<Finding> {
+    gedcom
+        .individuals
+        .iter()
+        .filter(|i| i.gender == Gender::Unknown)
+        .map(|i| {
+            let xref = i.xref.as_deref().unwrap_or_default();
+            finding(Rule::MissingSex, xref, "sex is unknown".to_string(), None)
+        })
+        .collect()
+}
+
+/// A record that nothing points to, or an individual who isn't in any
+/// family. See `orphaned_records`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OrphanedRecord {
+    pub xref: String,
+    /// The kind of record, by its tag, i.e. "SOUR"
+    pub tag: String,
+}
+
+/// The records nothing points to: sources never cited, multimedia never
+/// attached, and individuals with no FAMC or FAMS links who aren't in any
+/// family, in file order. These are usually left over from editing or
+/// merging.
+///
+/// NOTE and SUBM records may be pointed to from structures the model doesn't
+/// keep, i.e. a program's own tags, so they're only checked when parsing with
+/// `GedcomConfig::preserve_unknown`, which keeps the original text.
+pub fn orphaned_records(gedcom: &Gedcom) -> Vec<OrphanedRecord> {
+    let mut pointers = Pointers::default();
+    pointers.gedcom(gedcom);
+    let pointers = pointers.0;
+
+    let members: HashSet<&str> = gedcom
+        .families
+        .iter()
+        .flat_map(|f| {
+            f.husband_xref()
+                .into_iter()
+                .chain(f.wife_xref())
+                .chain(f.children.iter().map(|c| c.as_str()))
+        })
+        .collect();
+
+    let mut orphans: Vec<OrphanedRecord> = vec![];
+    let mut orphan = |xref: &str, tag: &str| {
+        orphans.push(OrphanedRecord {
+            xref: xref.to_string(),
+            tag: tag.to_string(),
+        })
+    };
+    for individual in &gedcom.individuals {
+        if let Some(xref) = individual.xref.as_deref() {
+            if individual.famc.is_empty() && individual.fams.is_empty() && !members.contains(xref) {
+                orphan(xref, "INDI");
+            }
+        }
+    }
+    for source in &gedcom.sources {
+        if !pointers.contains(source.xref.as_str()) {
+            orphan(&source.xref, "SOUR");
+        }
+    }
+    for record in &gedcom.multimedia {
+        if !pointers.contains(record.xref.as_str()) {
+            orphan(&record.xref, "OBJE");
+        }
+    }
+    if !gedcom.raw_records.is_empty() {
+        for record in &gedcom.notes {
+            if !pointers.contains(record.xref.as_str()) {
+                orphan(&record.xref, "NOTE");
+            }
+        }
+        for submitter in gedcom.submitters.iter().filter_map(|s| s.xref.as_deref()) {
+            if !pointers.contains(submitter) {
+                orphan(submitter, "SUBM");
+            }
+        }
+    }
+
+    // Where we know where the records came from, put them in file order
+    orphans.sort_by_key(|o| {
+        gedcom
+            .record_positions
+            .get(&o.xref)
+            .map_or(usize::MAX, |p| p.line)
+    });
+    orphans
+}
+
+/// Every pointer to a record, anywhere in the Gedcom
+#[derive(Default)]
+struct Pointers(HashSet<String>);
+
+impl Pointers {
+    fn gedcom(&mut self, gedcom: &Gedcom) {
+        if let Some(submitter) = &gedcom.header.submitter {
+            self.pointer(submitter.xref.as_deref());
+            for media in &submitter.media {
+                self.pointer(Some(media));
+            }
+            self.note(submitter.note.as_ref());
+        }
+        for individual in &gedcom.individuals {
+            for name in &individual.names {
+                self.note(name.name.note.as_ref());
+            }
+            for event in individual.all_events() {
+                self.detail(event.detail);
+            }
+            self.notes(&individual.notes);
+            self.citations(&individual.sources);
+            for association in &individual.associations {
+                self.pointer(Some(&association.xref));
+                self.notes(&association.notes);
+                self.citations(&association.sources);
+            }
+            for alias in &individual.aliases {
+                self.pointer(Some(alias));
+            }
+            for submitter in individual
+                .submitters
+                .iter()
+                .chain(&individual.ancestor_interest)
+                .chain(&individual.descendant_interest)
+            {
+                self.pointer(Some(submitter));
+            }
+            self.media(&individual.media);
+        }
+        for family in &gedcom.families {
+            for (_, detail) in family.tagged_events() {
+                self.detail(detail);
+            }
+            self.notes(&family.notes);
+            self.citations(&family.sources);
+            self.media(&family.media);
+            for submitter in &family.submitters {
+                self.pointer(Some(submitter));
+            }
+        }
+        for source in &gedcom.sources {
+            for repository in &source.repositories {
+                self.pointer(repository.xref.as_deref());
+                self.notes(&repository.notes);
+            }
+            self.notes(&source.notes);
+            self.media(&source.media);
+        }
+        for repository in &gedcom.repositories {
+            self.notes(&repository.notes);
+        }
+        for record in &gedcom.multimedia {
+            self.notes(&record.notes);
+        }
+        for record in &gedcom.notes {
+            self.citations(&record.sources);
+        }
+        for submitter in &gedcom.submitters {
+            for media in &submitter.media {
+                self.pointer(Some(media));
+            }
+            self.note(submitter.note.as_ref());
+        }
+
+        // The original text has pointers the model doesn't keep
+        for record in &gedcom.raw_records {
+            for line in record.lines.iter().skip(1) {
+                let words: Vec<&str> = line.trim_end().splitn(3, ' ').collect();
+                if let [_, _, value] = words[..] {
+                    self.pointer(Some(value));
+                }
+            }
+        }
+    }
+
+    fn pointer(&mut self, value: Option<&str>) {
+        if let Some(value) = value.filter(|v| is_pointer(v)) {
+            self.0.insert(value.to_string());
+        }
+    }
+
+    fn detail(&mut self, detail: Option<&EventDetail>) {
+        if let Some(detail) = detail {
+            self.pointer(detail.note.as_deref());
+            self.note(detail.place.as_ref().and_then(|p| p.note.as_ref()));
+            self.citations(&detail.sources);
+            self.media(&detail.media);
+        }
+    }
+
+    fn citations(&mut self, citations: &[SourceCitation]) {
+        for citation in citations {
+            self.pointer(citation.xref.as_deref());
+            self.media(&citation.media);
+            self.note(citation.note.as_ref());
+        }
+    }
+
+    fn media(&mut self, media: &[Object]) {
+        for object in media {
+            self.pointer(object.xref.as_deref());
+        }
+    }
+
+    fn notes(&mut self, notes: &[Note]) {
+        for note in notes {
+            self.note(Some(note));
+        }
+    }
+
+    fn note(&mut self, note: Option<&Note>) {
+        self.pointer(note.and_then(|n| n.note.as_deref()));
+    }
+}
+
+/// Find everyone who is their own ancestor. Each cycle is reported once,
+/// against whoever in it comes first in the file, with the path around it.
+fn ancestor_cycles(gedcom: &Gedcom) -> Vec<Finding> {
+    let index: HashMap<&str, usize> = gedcom
+        .individuals
+        .iter()
+        .enumerate()
+        .filter_map(|(i, individual)| Some((individual.xref.as_deref()?, i)))
+        .collect();
+
+    // Each individual's children, by index, from either side of the link
+    let mut linked: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, individual) in gedcom.individuals.iter().enumerate() {
+        for family in &individual.famc {
+            linked.entry(family.xref.as_str()).or_default().push(i);
+        }
+    }
+    let mut children: Vec<Vec<usize>> = vec![vec![]; gedcom.individuals.len()];
+    for family in &gedcom.families {
+        let listed = family.children.iter().filter_map(|c| index.get(c.as_str()));
+        let members: Vec<usize> = listed
+            .chain(linked.get(family.xref.as_str()).into_iter().flatten())
+            .copied()
+            .collect();
+        let parents = family.husband_xref().into_iter().chain(family.wife_xref());
+        for parent in parents.filter_map(|p| index.get(p)) {
+            for child in &members {
+                if !children[*parent].contains(child) {
+                    children[*parent].push(*child);
+                }
+            }
+        }
+    }
+
+    // A depth-first search, without recursion, so a long line can't
+    // overflow the stack. A cycle is found when we come back to someone
+    // whose descendants we're still going through.
+    const UNSEEN: u8 = 0;
+    const OPEN: u8 = 1;
+    const DONE: u8 = 2;
+    let mut state = vec![UNSEEN; gedcom.individuals.len()];
+    let mut cycles: Vec<Vec<usize>> = vec![];
+    for start in 0..gedcom.individuals.len() {
+        if state[start] != UNSEEN {
+            continue;
+        }
+        let mut stack: Vec<(usize, usize)> = vec![(start, 0)];
+        state[start] = OPEN;
+        while let Some((node, next)) = stack.last_mut() {
+            let node = *node;
+            match children[node].get(*next) {
+                Some(child) => {
+                    *next += 1;
+                    match state[*child] {
+                        UNSEEN => {
+                            state[*child] = OPEN;
+                            stack.push((*child, 0));
+                        }
+                        OPEN => {
+                            let from = stack.iter().position(|(n, _)| n == child).unwrap();
+                            let mut cycle: Vec<usize> =
+                                stack[from..].iter().map(|(n, _)| *n).collect();
+                            let first = (0..cycle.len()).min_by_key(|i| cycle[*i]).unwrap();
+                            cycle.rotate_left(first);
+                            cycles.push(cycle);
+                        }
+                        _ => {}
+                    }
+                }
+                None => {
+                    state[node] = DONE;
+                    stack.pop();
+                }
+            }
+        }
+    }
+
+    cycles.sort();
+    cycles
+        .into_iter()
+        .map(|cycle| {
+            let xref = |i: &usize| gedcom.individuals[*i].xref.as_deref().unwrap_or_default();
+            let path: Vec<&str> = cycle.iter().chain(cycle.first()).map(xref).collect();
+            let message = format!(
+                "is their own ancestor: {} (each a parent of the next)",
+                path.join(" -> ")
+            );
+            finding(Rule::AncestorCycle, xref(&cycle[0]), message, None)
+        })
+        .collect()
+}
+
+fn find_individual<'a>(gedcom: &'a Gedcom, xref: &str) -> Option<&'a crate::types::Individual> {
+    gedcom
+        .individuals
+        .iter()
+        .find(|i| i.xref.as_deref() == Some(xref))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{Family, Individual};
+
+    fn gedcom(individuals: &[&[&str]], families: &[&[&str]]) -> Gedcom {
+        let mut gedcom = Gedcom::default();
+        for record in individuals {
+            let buffer = record.join("\n");
+            gedcom
+                .individuals
+                .push(Individual::parse(&mut buffer.as_str()));
+        }
+        for record in families {
+            let buffer = record.join("\n");
+            gedcom.families.push(Family::parse(&mut buffer.as_str()));
+        }
+        gedcom
+    }
+
+    #[test]
+    fn swap_birth_and_death() {
+        let mut gedcom = gedcom(
+            &[&[
+                "0 @I1@ INDI",
+                "1 BIRT",
+                "2 DATE 1 JAN 1950",
+                "1 DEAT",
+                "2 DATE 1 JAN 1900",
+                "1 FAMC @F1@",
+            ]],
+            &[&["0 @F1@ FAM", "1 CHIL @I1@"]],
+        );
+
+        gedcom.record_positions.insert(
+            "@I1@".to_string(),
+            crate::types::Position {
+                line: 12,
+                offset: 345,
+            },
+        );
+
+        let findings = validate_gedcom(&gedcom);
+        assert!(findings.len() == 1);
+        assert!(findings[0].rule == Rule::DeathBeforeBirth);
+        assert!(findings[0].line == Some(12));
+        assert!(findings[0].severity == Severity::Error);
+
+        let fix = findings[0].fix.as_ref().unwrap();
+        assert!(fix.action() == "swap-birth-death");
+        assert!(fix.to_string() == "swap birth and death dates of @I1@");
+        assert!(fix.apply(&mut gedcom));
+
+        assert!(gedcom.individuals[0].birth_year() == Some(1900));
+        assert!(gedcom.individuals[0].death_year() == Some(1950));
+        assert!(validate_gedcom(&gedcom).is_empty());
+    }
+
+    #[test]
+    fn dangling_links() {
+        let mut gedcom = gedcom(
+            &[
+                &["0 @I1@ INDI", "1 FAMS @F1@", "1 FAMC @F9@"],
+                &["0 @I2@ INDI", "1 FAMC @F1@"],
+            ],
+            &[&["0 @F1@ FAM", "1 HUSB @I1@", "1 WIFE @I8@", "1 CHIL @I2@"]],
+        );
+
+        let findings = validate_gedcom(&gedcom);
+        assert!(findings.len() == 2);
+
+        let fix = findings[0].fix.clone().unwrap();
+        assert!(
+            fix == SuggestedFix::RemoveFamilyLink {
+                individual: "@I1@".to_string(),
+                family: "@F9@".to_string(),
+                link: FamilyLink::Child,
+            }
+        );
+        assert!(fix.to_string() == "remove dangling FAMC @F9@ from @I1@");
+
+        let fix = findings[1].fix.clone().unwrap();
+        assert!(findings[1].xref == Some("@F1@".to_string()));
+        assert!(fix.action() == "remove-family-member");
+
+        for finding in &findings {
+            assert!(finding.fix.as_ref().unwrap().apply(&mut gedcom));
+        }
+        assert!(validate_gedcom(&gedcom).is_empty());
+        assert!(gedcom.individuals[0].famc.is_empty());
+        assert!(gedcom.families[0].wife.is_none());
+        assert!(gedcom.families[0].husband_xref() == Some("@I1@"));
+
+        // Applying a fix a second time changes nothing
+        assert!(!findings[0].fix.as_ref().unwrap().apply(&mut gedcom));
+    }
+
+    #[test]
+    fn ancestor_cycle() {
+        let gedcom = gedcom(
+            &[
+                &["0 @I1@ INDI", "1 FAMS @F1@", "1 FAMC @F2@"],
+                &["0 @I2@ INDI", "1 FAMC @F1@", "1 FAMS @F2@"],
+                &["0 @I3@ INDI", "1 FAMS @F3@"],
+                &["0 @I4@ INDI", "1 FAMS @F4@"],
+                &["0 @I5@ INDI", "1 FAMC @F4@", "1 FAMS @F5@"],
+                &["0 @I6@ INDI", "1 FAMC @F4@", "1 FAMS @F5@"],
+                &["0 @I7@ INDI", "1 FAMC @F5@"],
+            ],
+            &[
+                &["0 @F1@ FAM", "1 HUSB @I1@"],
+                &["0 @F2@ FAM", "1 HUSB @I2@", "1 CHIL @I1@"],
+                &["0 @F3@ FAM", "1 HUSB @I3@", "1 CHIL @I3@"],
+                // Siblings with a child: odd, but not a cycle
+                &["0 @F4@ FAM", "1 HUSB @I4@"],
+                &["0 @F5@ FAM", "1 HUSB @I5@", "1 WIFE @I6@"],
+            ],
+        );
+
+        let findings = validate_gedcom(&gedcom);
+        assert!(findings.iter().all(|f| f.rule == Rule::AncestorCycle));
+        assert!(findings.len() == 2);
+        assert!(findings[0].xref.as_deref() == Some("@I1@"));
+        assert!(
+            findings[0].message
+                == "is their own ancestor: @I1@ -> @I2@ -> @I1@ (each a parent of the next)"
+        );
+        assert!(findings[1].message.contains("@I3@ -> @I3@"));
+
+        let config = ValidationConfig::new().disable(Rule::AncestorCycle);
+        assert!(validate_gedcom_with_config(&gedcom, &config).is_empty());
+    }
+
+    #[test]
+    fn orphaned_records() {
+        let data = "0 HEAD
+1 SUBM @U1@
+0 @U1@ SUBM
+1 NAME Submitter
+0 @U2@ SUBM
+1 NAME Nobody
+0 @N1@ NOTE Cited
+0 @N2@ NOTE Never cited
+0 @S1@ SOUR
+1 TITL Cited
+1 NOTE @N1@
+0 @S2@ SOUR
+1 TITL Never cited
+0 @S3@ SOUR
+1 TITL Cited for the individual
+0 @M1@ OBJE
+1 FILE photo.jpg
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 FAMS @F1@
+1 BIRT
+2 SOUR @S1@
+1 SOUR @S3@
+0 @I2@ INDI
+1 NAME Ann /Bloggs/
+0 @I3@ INDI
+1 NAME Sam /Bloggs/
+1 ALIA @U2@
+0 @F1@ FAM
+1 HUSB @I1@
+1 CHIL @I3@
+0 TRLR
+";
+        let gedcom = crate::parse::parse_gedcom_from_str(data).unwrap();
+        let orphans = gedcom.orphaned_records();
+        let orphans: Vec<(&str, &str)> = orphans
+            .iter()
+            .map(|o| (o.xref.as_str(), o.tag.as_str()))
+            .collect();
+        assert!(orphans == [("@S2@", "SOUR"), ("@M1@", "OBJE"), ("@I2@", "INDI")]);
+
+        // NOTE and SUBM records are only checked with the original text,
+        // which also has the pointers the model leaves out
+        let config = crate::config::GedcomConfig::new().preserve_unknown();
+        let gedcom =
+            crate::parse::parse_gedcom_from_reader_with_config(data.as_bytes(), &config).unwrap();
+        let orphans: Vec<String> = gedcom
+            .orphaned_records()
+            .into_iter()
+            .map(|o| o.xref)
+            .collect();
+        assert!(orphans == ["@N2@", "@S2@", "@M1@", "@I2@"]);
+
+        let findings = validate_gedcom(&gedcom);
+        assert!(findings.iter().all(|f| f.rule == Rule::OrphanedRecord));
+        assert!(findings[0]
+            .to_string()
+            .ends_with("@N2@ (line 8): nothing points to this NOTE record"));
+        assert!(findings[3].message == "isn't linked to any family");
+    }
+
+    #[test]
+    fn plausibility() {
+        let gedcom = gedcom(
+            &[
+                &["0 @I1@ INDI", "1 BIRT", "2 DATE 1800", "1 FAMS @F1@"],
+                &["0 @I2@ INDI", "1 BIRT", "2 DATE 1805", "1 FAMC @F1@"],
+                &[
+                    "0 @I3@ INDI",
+                    "1 BIRT",
+                    "2 DATE 1820",
+                    "1 BURI",
+                    "2 DATE 1950",
+                    "1 FAMC @F1@",
+                ],
+            ],
+            &[&["0 @F1@ FAM", "1 HUSB @I1@", "1 CHIL @I2@", "1 CHIL @I3@"]],
+        );
+
+        let findings = validate_gedcom(&gedcom);
+        assert!(findings.len() == 2);
+        assert!(findings[0].rule == Rule::ImplausibleLifespan);
+        assert!(findings[0].xref == Some("@I3@".to_string()));
+        assert!(findings[0].fix.is_none());
+        assert!(findings[1].rule == Rule::ParentTooYoung);
+        assert!(
+            findings[1].to_string()
+                == "warning [parent-too-young] @F1@: @I2@ was born in 1805, when parent @I1@ was 5"
+        );
+
+        assert!(Rule::from_str("parent-too-young") == Ok(Rule::ParentTooYoung));
+        assert!(Rule::from_str("nope").is_err());
+    }
+
+    #[test]
+    fn changed_after_file_date() {
+        let mut gedcom = gedcom(
+            &[
+                &[
+                    "0 @I1@ INDI",
+                    "1 FAMS @F1@",
+                    "1 CHAN",
+                    "2 DATE 1 JAN 1998",
+                    "3 TIME 12:00",
+                ],
+                &["0 @I2@ INDI", "1 FAMS @F1@", "1 CHAN", "2 DATE 2 JAN 1998"],
+            ],
+            &[&["0 @F1@ FAM", "1 CHAN", "2 DATE 1 JAN 1998", "3 TIME 14:00"]],
+        );
+
+        // Without a file date, there's nothing to compare to
+        assert!(validate_gedcom(&gedcom).is_empty());
+
+        gedcom.header.date = Some(crate::types::DateTime {
+            date: Some("1 JAN 1998".to_string()),
+            time: Some("13:57:24.80".to_string()),
+        });
+        let findings = validate_gedcom(&gedcom);
+        assert!(findings.len() == 2);
+        assert!(findings
+            .iter()
+            .all(|f| f.rule == Rule::ChangedAfterFileDate));
+        assert!(findings[0].xref == Some("@I2@".to_string()));
+        assert!(findings[1].xref == Some("@F1@".to_string()));
+        assert!(
+            findings[1].message
+                == "changed 1 JAN 1998 14:00:00, after the file was created 1 JAN 1998 13:57:24.800"
+        );
+    }
+
+    #[test]
+    fn config() {
+        let gedcom = gedcom(
+            &[
+                &["0 @I1@ INDI", "1 BIRT", "2 DATE 1800", "1 FAMS @F1@"],
+                &["0 @I2@ INDI", "1 BIRT", "2 DATE 1815", "1 FAMC @F1@"],
+                &[
+                    "0 @I3@ INDI",
+                    "1 BIRT",
+                    "2 DATE 1820",
+                    "1 DEAT",
+                    "2 DATE 1935",
+                    "1 FAMC @F1@",
+                ],
+            ],
+            &[&["0 @F1@ FAM", "1 HUSB @I1@", "1 CHIL @I2@"]],
+        );
+        assert!(validate_gedcom(&gedcom).len() == 1);
+
+        let config = ValidationConfig::new()
+            .min_parent_age(16)
+            .max_lifespan(120)
+            .severity(Rule::ParentTooYoung, Severity::Error);
+        let findings = validate_gedcom_with_config(&gedcom, &config);
+        assert!(findings.len() == 1);
+        assert!(findings[0].rule == Rule::ParentTooYoung);
+        assert!(findings[0].severity == Severity::Error);
+
+        let config = config.disable(Rule::ParentTooYoung);
+        assert!(validate_gedcom_with_config(&gedcom, &config).is_empty());
+    }
+
+    #[test]
+    fn rules() {
+        let data = "0 HEAD
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+1 SEX M
+1 FAMS @F1@
+0 @I2@ INDI
+1 SEX U
+1 FAMC @F1@
+0 @F1@ FAM
+1 HUSB @I1@
+1 CHIL @I2@
+0 TRLR
+";
+        let gedcom = crate::parse::parse_gedcom_from_str(data).unwrap();
+        assert!(gedcom.validate().is_empty());
+        assert!(!ValidationConfig::new().is_enabled(Rule::MissingName));
+
+        let config = ValidationConfig::new()
+            .enable(Rule::MissingName)
+            .enable(Rule::MissingSex);
+        let findings = validate_gedcom_with_config(&gedcom, &config);
+        assert!(findings.len() == 2);
+        assert!(findings[0].to_string() == "warning [missing-name] @I2@ (line 6): has no name");
+        assert!(findings[1].severity == Severity::Info);
+        assert!(findings[1].message == "sex is unknown");
+
+        // Parsing with a configuration makes it the Gedcom's own
+        let config = config
+            .disable(Rule::MissingName)
+            .severity(Rule::MissingSex, Severity::Error);
+        let gedcom = crate::parse::parse_gedcom_from_reader_with_config(
+            data.as_bytes(),
+            &crate::config::GedcomConfig::new().validation(config),
+        )
+        .unwrap();
+        let findings = gedcom.validate();
+        assert!(findings.len() == 1);
+        assert!(findings[0].rule == Rule::MissingSex);
+        assert!(findings[0].severity == Severity::Error);
+
+        for rule in Rule::ALL {
+            assert!(Rule::from_str(rule.name()) == Ok(rule));
+        }
+        assert!(Severity::from_str("info") == Ok(Severity::Info));
+        assert!(Severity::Info < Severity::Warning);
+    }
+
+    #[cfg(feature = "rules-config")]
+    #[test]
+    fn config_file() {
+        let toml = ValidationConfig::from_toml(
+            "max_lifespan = 120\n\n[rules.parent-too-young]\nenabled = false\n\n[rules.implausible-lifespan]\nseverity = \"error\"\n",
+        )
+        .unwrap();
+        let json = ValidationConfig::from_json(
+            r#"{"max_lifespan": 120, "rules": {"parent-too-young": {"enabled": false}, "implausible-lifespan": {"severity": "error"}}}"#,
+        )
+        .unwrap();
+        assert!(toml == json);
+        assert!(toml.max_lifespan == 120);
+        assert!(toml.min_parent_age == MIN_PARENT_AGE);
+        assert!(!toml.is_enabled(Rule::ParentTooYoung));
+        assert!(toml.severities.get(&Rule::ImplausibleLifespan) == Some(&Severity::Error));
+        let config = ValidationConfig::from_toml("[rules.missing-sex]\nenabled = true\n").unwrap();
+        assert!(config.is_enabled(Rule::MissingSex));
+
+        let err =
+            ValidationConfig::from_toml("[rules.no-such-rule]\nenabled = false\n").unwrap_err();
+        assert!(err.to_string() == "Invalid rule configuration: Unknown rule: no-such-rule");
+        assert!(ValidationConfig::from_json(
+            r#"{"rules": {"parent-too-young": {"severity": "fatal"}}}"#
+        )
+        .is_err());
+        assert!(ValidationConfig::from_toml("max_age = 3").is_err());
+        assert!(ValidationConfig::from_file("no-such-rules.toml").is_err());
+    }
+}
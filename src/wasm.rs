@@ -0,0 +1,199 @@
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::parse::parse_gedcom_from_bytes;
+use crate::types::{EventDetail, Family, Gedcom, Gender, Individual, VitalStatus};
+
+// A small facade over the parser for JavaScript, i.e. a genealogy viewer in
+// the browser, built with wasm-bindgen:
+//
+//   const gedcom = parse_bytes(new Uint8Array(await file.arrayBuffer()));
+//   const individuals = JSON.parse(get_individuals_json(gedcom));
+//
+// Everything is parsed from memory, so nothing here needs a file system.
+// Records are handed over as JSON, a summary of each rather than the whole
+// of the structured model:
+//
+//   individual   {"xref", "name", "given", "surname", "sex", "status",
+//                 "events": [{"tag", "date", "place"}], "famc", "fams"}
+//   family       {"xref", "husband", "wife", "children", "events"}
+//
+// where "sex" is M, F, X or U (or the value in the file, if it's something
+// else), and "status" is living, deceased or unknown.
+
+/// A parsed GEDCOM file, kept on the WebAssembly side until JavaScript asks
+/// for its records
+#[wasm_bindgen]
+pub struct ParsedGedcom {
+    gedcom: Gedcom,
+}
+
+#[wasm_bindgen]
+impl ParsedGedcom {
+    /// How many individuals there are
+    #[wasm_bindgen(getter)]
+    pub fn individual_count(&self) -> usize {
+        self.gedcom.individuals.len()
+    }
+
+    /// How many families there are
+    #[wasm_bindgen(getter)]
+    pub fn family_count(&self) -> usize {
+        self.gedcom.families.len()
+    }
+
+    /// The problems found while parsing, as a JSON array of messages
+    pub fn warnings_json(&self) -> String {
+        let warnings: Vec<String> = self.gedcom.warnings.iter().map(|w| w.to_string()).collect();
+        json!(warnings).to_string()
+    }
+}
+
+impl ParsedGedcom {
+    /// The Gedcom, for Rust code sharing the WebAssembly module
+    pub fn gedcom(&self) -> &Gedcom {
+        &self.gedcom
+    }
+}
+
+/// Parse the bytes of a GEDCOM file, i.e. from a file the user picked. As
+/// with `parse_gedcom`, problems are recorded as warnings rather than
+/// failing.
+#[wasm_bindgen]
+pub fn parse_bytes(bytes: &[u8]) -> ParsedGedcom {
+    let gedcom = parse_gedcom_from_bytes(bytes).unwrap_or_else(|err| Gedcom {
+        warnings: vec![err],
+        ..Default::default()
+    });
+    ParsedGedcom { gedcom }
+}
+
+/// Every individual, as a JSON array
+#[wasm_bindgen]
+pub fn get_individuals_json(gedcom: &ParsedGedcom) -> String {
+    let individuals: Vec<Value> = gedcom
+        .gedcom
+        .individuals
+        .iter()
+        .map(|i| individual_json(&gedcom.gedcom, i))
+        .collect();
+    Value::Array(individuals).to_string()
+}
+
+/// Every family, as a JSON array
+#[wasm_bindgen]
+pub fn get_families_json(gedcom: &ParsedGedcom) -> String {
+    let families: Vec<Value> = gedcom.gedcom.families.iter().map(family_json).collect();
+    Value::Array(families).to_string()
+}
+
+fn individual_json(gedcom: &Gedcom, individual: &Individual) -> Value {
+    let name = individual.names.first().map(|n| &n.name);
+    let sex = match &individual.gender {
+        Gender::Male => "M",
+        Gender::Female => "F",
+        Gender::Nonbinary => "X",
+        Gender::Unknown => "U",
+        Gender::Other(value) => value,
+    };
+    let status = match individual.vital_status(gedcom) {
+        VitalStatus::Living => "living",
+        VitalStatus::Deceased => "deceased",
+        VitalStatus::Unknown => "unknown",
+    };
+    json!({
+        "xref": individual.xref,
+        "name": name.and_then(|n| n.value.as_deref()),
+        "given": name.and_then(|n| n.given.as_deref()),
+        "surname": name.and_then(|n| n.surname.as_deref()),
+        "sex": sex,
+        "status": status,
+        "events": events_json(individual.tagged_events()),
+        "famc": individual.famc.iter().map(|f| &f.xref).collect::<Vec<_>>(),
+        "fams": individual.fams.iter().map(|f| &f.xref).collect::<Vec<_>>(),
+    })
+}
+
+fn family_json(family: &Family) -> Value {
+    json!({
+        "xref": family.xref,
+        "husband": family.husband_xref(),
+        "wife": family.wife_xref(),
+        "children": family.children,
+        "events": events_json(family.tagged_events()),
+    })
+}
+
+fn events_json(events: Vec<(&str, Option<&EventDetail>)>) -> Value {
+    let events: Vec<Value> = events
+        .into_iter()
+        .map(|(tag, detail)| {
+            json!({
+                "tag": tag,
+                "date": detail.and_then(|d| d.date.as_deref()),
+                "place": detail.and_then(|d| d.place.as_ref()?.name.as_deref()),
+            })
+        })
+        .collect();
+    Value::Array(events)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn facade() {
+        let gedcom = parse_bytes(
+            b"0 HEAD
+0 @I1@ INDI
+1 NAME Joe /Bloggs/
+2 GIVN Joe
+2 SURN Bloggs
+1 SEX M
+1 BIRT
+2 DATE 1 JAN 1900
+2 PLAC York
+1 DEAT Y
+1 FAMS @F1@
+0 @I2@ INDI
+1 NAME Ann /Bloggs/
+1 FAMC @F1@
+0 @F1@ FAM
+1 HUSB @I1@
+1 CHIL @I2@
+1 MARR
+2 DATE 1925
+0 TRLR
+",
+        );
+        assert!(gedcom.individual_count() == 2);
+        assert!(gedcom.family_count() == 1);
+        assert!(gedcom.warnings_json() == "[]");
+
+        let individuals: Value = serde_json::from_str(&get_individuals_json(&gedcom)).unwrap();
+        let joe = &individuals[0];
+        assert!(joe["xref"] == "@I1@");
+        assert!(joe["name"] == "Joe /Bloggs/");
+        assert!(joe["surname"] == "Bloggs");
+        assert!(joe["sex"] == "M");
+        assert!(joe["status"] == "deceased");
+        assert!(joe["events"][0] == json!({"tag": "BIRT", "date": "1 JAN 1900", "place": "York"}));
+        assert!(joe["events"][1] == json!({"tag": "DEAT", "date": null, "place": null}));
+        assert!(joe["fams"] == json!(["@F1@"]));
+        assert!(individuals[1]["given"].is_null());
+        assert!(individuals[1]["famc"] == json!(["@F1@"]));
+
+        let families: Value = serde_json::from_str(&get_families_json(&gedcom)).unwrap();
+        assert!(
+            families
+                == json!([{
+                    "xref": "@F1@",
+                    "husband": "@I1@",
+                    "wife": null,
+                    "children": ["@I2@"],
+                    "events": [{"tag": "MARR", "date": "1925", "place": null}],
+                }])
+        );
+    }
+}
@@ -0,0 +1,1516 @@
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use crate::error::GedcomError;
+use crate::parse::parse_gedcom_from_str;
+use crate::types::{
+    Address, AdoptedBy, Association, DateTime, EventDetail, Family, FamilyEventDetail, Gedcom,
+    GedcomAge, GedcomVersion, Gender, Header, Individual, IndividualEventDetail, MediaFile,
+    MultimediaRecord, Name, Note, NoteRecord, Object, Pedigree, Place, Quay, RepositoryCitation,
+    RepositoryRecord, SourceCitation, SourceRecord, Submitter, UserReference,
+};
+
+/// The longest line value we'll write before splitting it with CONC. The
+/// specification allows 255 characters for the whole line, including the
+/// level and tag.
+pub(crate) const MAX_VALUE_LENGTH: usize = 200;
+
+/// The calendar escapes of GEDCOM 5.5 and 5.5.1 dates, and what 7.0 calls
+/// them
+const CALENDARS: [(&str, &str); 4] = [
+    ("@#DGREGORIAN@", "GREGORIAN"),
+    ("@#DJULIAN@", "JULIAN"),
+    ("@#DHEBREW@", "HEBREW"),
+    ("@#DFRENCH R@", "FRENCH_R"),
+];
+
+/// The roles of an ASSO in GEDCOM 7.0. Any other relation is written as
+/// OTHER, with a PHRASE.
+const ROLES: [&str; 14] = [
+    "CHIL",
+    "CLERGY",
+    "FATH",
+    "FRIEND",
+    "GODP",
+    "HUSB",
+    "MOTH",
+    "MULTIPLE",
+    "NGHBR",
+    "OFFICIATOR",
+    "PARENT",
+    "SPOU",
+    "WIFE",
+    "WITN",
+];
+
+/// Something that couldn't be written as it is in the version of GEDCOM
+/// asked for, so was changed or left out. See `write_gedcom_as`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionWarning {
+    /// The record it was in, or None for the header
+    pub xref: Option<String>,
+    pub tag: String,
+    pub message: String,
+}
+
+/// The level 0 records the structured model keeps
+const MODELLED_TAGS: [&str; 9] = [
+    "HEAD", "SUBM", "INDI", "FAM", "SOUR", "REPO", "OBJE", "NOTE", "SNOTE",
+];
+
+/// Write the Gedcom out as GEDCOM text.
+///
+/// Every record in the structured model is written: the submitters,
+/// individuals, families, sources, repositories, multimedia and notes.
+/// Anything the parser skipped is not, unless the file was parsed with
+/// `GedcomConfig::preserve_unknown`. Then every record is written as it was
+/// read, in the same order, except those changed since, which are written
+/// from the model. Records added since come before the trailer, and those
+/// removed are left out.
+pub fn write_gedcom(gedcom: &Gedcom) -> String {
+    let mut writer = Writer::default();
+    writer.gedcom(gedcom);
+    writer.text
+}
+
+/// Write the Gedcom out as GEDCOM text for a particular version of the
+/// specification, whichever version it was read from, i.e. as 5.5.1 for a
+/// program that doesn't read 7.0.
+///
+/// Dates, ages, SEX values and notes are written in the form the version
+/// uses, and the header says it's that version, in UTF-8. Anything the
+/// version has no place for is left out, with a warning saying so.
+pub fn write_gedcom_as(gedcom: &Gedcom, version: GedcomVersion) -> (String, Vec<VersionWarning>) {
+    let mut writer = Writer {
+        version: Some(version),
+        ..Default::default()
+    };
+    writer.gedcom(gedcom);
+    (writer.text, writer.warnings)
+}
+
+/// Write the Gedcom out as GEDCOM text to `output`
+pub fn write_gedcom_to<W: Write>(gedcom: &Gedcom, output: &mut W) -> io::Result<()> {
+    output.write_all(write_gedcom(gedcom).as_bytes())
+}
+
+/// Write the Gedcom out as a GEDCOM file
+pub fn write_gedcom_file(gedcom: &Gedcom, filename: &str) -> Result<(), GedcomError> {
+    let mut file = File::create(filename)?;
+    write_gedcom_to(gedcom, &mut file)?;
+    Ok(())
+}
+
+/// Write the Gedcom out as a GEDCOM file for a particular version of the
+/// specification. See `write_gedcom_as`.
+pub fn write_gedcom_file_as(
+    gedcom: &Gedcom,
+    filename: &str,
+    version: GedcomVersion,
+) -> Result<Vec<VersionWarning>, GedcomError> {
+    let (text, warnings) = write_gedcom_as(gedcom, version);
+    let mut file = File::create(filename)?;
+    file.write_all(text.as_bytes())?;
+    Ok(warnings)
+}
+
+#[derive(Default)]
+struct Writer {
+    text: String,
+    /// The version to write, or None to write the model as it is
+    version: Option<GedcomVersion>,
+    /// The version the Gedcom was read from, if it says
+    read_version: Option<GedcomVersion>,
+    /// The record being written, for warnings
+    xref: Option<String>,
+    warnings: Vec<VersionWarning>,
+}
+
+impl Writer {
+    fn gedcom(&mut self, gedcom: &Gedcom) {
+        self.read_version = gedcom.header.version();
+        if !gedcom.raw_records.is_empty() && self.version.is_none() {
+            self.preserved(gedcom);
+            return;
+        }
+        self.header(&gedcom.header);
+        for submitter in &gedcom.submitters {
+            self.submitter(submitter);
+        }
+        // A Gedcom built in code may only have the header's
+        if let Some(submitter) = &gedcom.header.submitter {
+            if !gedcom.submitters.iter().any(|s| s.xref == submitter.xref) {
+                self.submitter(submitter);
+            }
+        }
+        for individual in &gedcom.individuals {
+            self.individual(individual);
+        }
+        for family in &gedcom.families {
+            self.family(family);
+        }
+        for source in &gedcom.sources {
+            self.source_record(source);
+        }
+        for repository in &gedcom.repositories {
+            self.repository(repository);
+        }
+        for multimedia in &gedcom.multimedia {
+            self.multimedia_record(multimedia);
+        }
+        for note in &gedcom.notes {
+            self.note_record(note);
+        }
+        self.line(0, "TRLR", None);
+    }
+
+    /// Write the records kept by `GedcomConfig::preserve_unknown`. A record
+    /// is unchanged if the model writes it just as it writes the record
+    /// parsed afresh from its text.
+    fn preserved(&mut self, gedcom: &Gedcom) {
+        // The header decides how the other records are read
+        let head = gedcom
+            .raw_records
+            .iter()
+            .find(|r| r.tag == "HEAD")
+            .map(|r| r.text())
+            .unwrap_or_default();
+        let kept: HashSet<&str> = gedcom
+            .raw_records
+            .iter()
+            .filter_map(|r| r.xref.as_deref())
+            .collect();
+
+        for raw in &gedcom.raw_records {
+            if raw.tag == "TRLR" {
+                self.added(gedcom, &kept);
+            }
+            let mut text = raw.text();
+            if !MODELLED_TAGS.contains(&raw.tag.as_str())
+                || (raw.xref.is_none() && raw.tag != "HEAD")
+            {
+                self.text += &text;
+                continue;
+            }
+            // Removed since it was read
+            let current = match record_text(gedcom, &raw.tag, raw.xref.as_deref()) {
+                Some(current) => current,
+                None => continue,
+            };
+            if !text.ends_with('\n') {
+                text.push('\n');
+            }
+            let read = match raw.tag.as_str() {
+                "HEAD" => text.clone(),
+                _ => head.clone() + &text,
+            };
+            let original = parse_gedcom_from_str(&(read + "0 TRLR\n"))
+                .ok()
+                .and_then(|g| record_text(&g, &raw.tag, raw.xref.as_deref()));
+            if original.as_ref() == Some(&current) {
+                self.text += &raw.text();
+            } else {
+                self.text += &current;
+            }
+        }
+        if !gedcom.raw_records.iter().any(|r| r.tag == "TRLR") {
+            self.added(gedcom, &kept);
+        }
+    }
+
+    /// Write the records that weren't in the file read, i.e. those added
+    /// since, whose xrefs aren't `kept`
+    fn added(&mut self, gedcom: &Gedcom, kept: &HashSet<&str>) {
+        let added = |xref: Option<&str>| xref.is_some_and(|x| !kept.contains(x));
+        for submitter in &gedcom.submitters {
+            if added(submitter.xref.as_deref()) {
+                self.submitter(submitter);
+            }
+        }
+        for individual in &gedcom.individuals {
+            if added(individual.xref.as_deref()) {
+                self.individual(individual);
+            }
+        }
+        for family in &gedcom.families {
+            if added(Some(&family.xref)) {
+                self.family(family);
+            }
+        }
+        for source in &gedcom.sources {
+            if added(Some(&source.xref)) {
+                self.source_record(source);
+            }
+        }
+        for repository in &gedcom.repositories {
+            if added(Some(&repository.xref)) {
+                self.repository(repository);
+            }
+        }
+        for multimedia in &gedcom.multimedia {
+            if added(Some(&multimedia.xref)) {
+                self.multimedia_record(multimedia);
+            }
+        }
+        for note in &gedcom.notes {
+            if added(Some(&note.xref)) {
+                self.note_record(note);
+            }
+        }
+    }
+
+    fn warn(&mut self, tag: &str, message: String) {
+        self.warnings.push(VersionWarning {
+            xref: self.xref.clone(),
+            tag: tag.to_string(),
+            message,
+        });
+    }
+
+    /// Whether the version being written has a place for `tag`. If not, a
+    /// warning is given, and the caller leaves it out.
+    fn supports(&mut self, tag: &str) -> bool {
+        let version = match self.version {
+            Some(version) => version,
+            None => return true,
+        };
+        let unsupported: &[&str] = match version {
+            GedcomVersion::V55 => &["EMAIL", "FAX", "WWW", "FONE", "ROMN", "MAP", "RELI"],
+            GedcomVersion::V551 => &[],
+            GedcomVersion::V70 => &["FILE", "SUBN", "RFN", "RIN", "FONE", "ROMN", "ANCI", "DESI"],
+        };
+        if !unsupported.contains(&tag) {
+            return true;
+        }
+        self.warn(
+            tag,
+            format!("{} isn't part of GEDCOM {}, so was left out", tag, version),
+        );
+        false
+    }
+
+    /// Write a single line, as-is
+    fn line(&mut self, level: u8, tag: &str, value: Option<&str>) {
+        self.text += &level.to_string();
+        self.text += " ";
+        self.text += tag;
+        if let Some(value) = value {
+            if !value.is_empty() {
+                self.text += " ";
+                self.text += value;
+            }
+        }
+        self.text += "\n";
+    }
+
+    /// Write the first line of a level 0 record
+    fn record(&mut self, xref: &str, tag: &str) {
+        self.xref = Some(xref.to_string());
+        self.text += &format!("0 {} {}\n", xref, tag);
+    }
+
+    /// Write a line whose value may be too long, or contain newlines,
+    /// continuing it with CONC and CONT as needed
+    fn text(&mut self, level: u8, tag: &str, value: &str) {
+        for (i, paragraph) in value.split('\n').enumerate() {
+            let (tag, first) = if i == 0 {
+                (tag, level)
+            } else {
+                ("CONT", level + 1)
+            };
+
+            // GEDCOM 7 has no CONC, and no limit on line length
+            let mut chunks = match self.version {
+                Some(GedcomVersion::V70) => vec![paragraph],
+                _ => split_value(paragraph),
+            }
+            .into_iter();
+            self.line(first, tag, chunks.next());
+            // A CONC after a CONT is at the same level as it, not below it
+            for chunk in chunks {
+                self.line(level + 1, "CONC", Some(chunk));
+            }
+        }
+    }
+
+    fn optional(&mut self, level: u8, tag: &str, value: &Option<String>) {
+        if let Some(value) = value {
+            if self.supports(tag) {
+                self.text(level, tag, value);
+            }
+        }
+    }
+
+    fn all(&mut self, level: u8, tag: &str, values: &[String]) {
+        for value in values {
+            if self.supports(tag) {
+                self.line(level, tag, Some(value));
+            }
+        }
+    }
+
+    fn header(&mut self, header: &Header) {
+        self.xref = None;
+        self.line(0, "HEAD", None);
+        // Readers look at GEDC first, to know how to read the rest, and
+        // 7.0 requires it to be first
+        if let Some(version) = self.version {
+            self.line(1, "GEDC", None);
+            self.line(2, "VERS", Some(&version.to_string()));
+            if version < GedcomVersion::V70 {
+                self.line(2, "FORM", Some("LINEAGE-LINKED"));
+            }
+        }
+
+        if let Some(source) = &header.source {
+            self.line(1, "SOUR", Some(&source.source));
+            self.optional(2, "VERS", &source.version);
+            self.optional(2, "NAME", &source.name);
+            if let Some(corporation) = &source.corporation {
+                self.line(2, "CORP", corporation.name.as_deref());
+                if let Some(address) = &corporation.address {
+                    self.address(3, address);
+                }
+            }
+            if let Some(data) = &source.data {
+                self.line(2, "DATA", data.name.as_deref());
+                if let Some(date) = &data.date {
+                    self.date_time(3, date);
+                }
+                self.optional(3, "COPR", &data.copyright);
+            }
+        }
+        self.optional(1, "DEST", &header.destination);
+        if let Some(date) = &header.date {
+            self.date_time(1, date);
+        }
+        if let Some(xref) = header.submitter.as_ref().and_then(|s| s.xref.as_ref()) {
+            self.line(1, "SUBM", Some(xref));
+        }
+        if let Some(xref) = header.submission.as_ref().and_then(|s| s.xref.as_ref()) {
+            if self.supports("SUBN") {
+                self.line(1, "SUBN", Some(xref));
+            }
+        }
+        self.optional(1, "FILE", &header.filename);
+        self.optional(1, "COPR", &header.copyright);
+        if let (None, Some(gedc)) = (self.version, &header.gedcom_version) {
+            self.line(1, "GEDC", None);
+            self.optional(2, "VERS", &gedc.version);
+            if let Some(form) = &gedc.form {
+                self.line(2, "FORM", form.name.as_deref());
+                self.optional(3, "VERS", &form.version);
+            }
+        }
+        match (self.version, &header.character_set) {
+            (None, Some(charset)) => {
+                self.line(1, "CHAR", charset.encoding.as_deref());
+                self.optional(2, "VERS", &charset.version);
+            }
+            (None, None) => {}
+            // 7.0 is always UTF-8
+            (Some(GedcomVersion::V70), _) => {}
+            (Some(version), _) => {
+                if version == GedcomVersion::V55 {
+                    self.warn(
+                        "CHAR",
+                        "GEDCOM 5.5 has no UTF-8 character set, but the file is written in UTF-8"
+                            .to_string(),
+                    );
+                }
+                self.line(1, "CHAR", Some("UTF-8"));
+            }
+        }
+        self.optional(1, "LANG", &header.language);
+        if !header.place_form().is_empty() {
+            self.line(1, "PLAC", None);
+            self.line(2, "FORM", Some(&header.place_form().join(", ")));
+        }
+        if let Some(note) = &header.note {
+            self.note_text(1, note);
+        }
+        self.optional(1, "_HOME", &header.home_person);
+    }
+
+    fn submitter(&mut self, submitter: &Submitter) {
+        let xref = match &submitter.xref {
+            Some(xref) => xref,
+            None => return,
+        };
+        self.record(xref, "SUBM");
+        self.optional(1, "NAME", &submitter.name);
+        if let Some(address) = &submitter.address {
+            self.address(1, address);
+            self.contacts(1, address);
+        }
+        self.all(1, "OBJE", &submitter.media);
+        self.all(1, "LANG", &submitter.lang);
+        self.optional(1, "RFN", &submitter.rfn);
+        self.optional(1, "RIN", &submitter.rin);
+        self.note(1, &submitter.note);
+        self.uids(&submitter.uids);
+        self.change_date(1, &submitter.change_date);
+    }
+
+    fn individual(&mut self, individual: &Individual) {
+        self.record(individual.xref.as_deref().unwrap_or_default(), "INDI");
+        self.optional(1, "RESN", &individual.restriction_notice);
+
+        for name in &individual.names {
+            self.name(1, "NAME", &name.name);
+            if name.name.r#type.is_none() {
+                self.optional(2, "TYPE", &name.r#type);
+            }
+            for (tag, variation) in [("FONE", &name.phonetic), ("ROMN", &name.romanized)] {
+                if variation.value.is_some() && self.supports(tag) {
+                    self.name(2, tag, variation);
+                }
+            }
+        }
+        let sex = match (&individual.gender, self.version) {
+            (Gender::Male, _) => Some("M"),
+            (Gender::Female, _) => Some("F"),
+            (Gender::Unknown, _) => None,
+            (Gender::Nonbinary, Some(version)) if version < GedcomVersion::V70 => {
+                self.warn(
+                    "SEX",
+                    format!("GEDCOM {} has no SEX X, so U was written", version),
+                );
+                Some("U")
+            }
+            (Gender::Nonbinary, _) => Some("X"),
+            (Gender::Other(value), Some(version)) => {
+                let sex = if version < GedcomVersion::V70 {
+                    "U"
+                } else {
+                    "X"
+                };
+                self.warn(
+                    "SEX",
+                    format!(
+                        "GEDCOM {} has no SEX {}, so {} was written",
+                        version, value, sex
+                    ),
+                );
+                Some(sex)
+            }
+            (Gender::Other(value), None) => Some(value.as_str()),
+        };
+        if let Some(sex) = sex {
+            self.line(1, "SEX", Some(sex));
+        }
+
+        for birth in &individual.birth {
+            let start = self.individual_event(1, "BIRT", &birth.event);
+            if let Some(family) = &birth.family {
+                self.line(2, "FAMC", Some(&family.xref));
+            }
+            self.occurred(start, "BIRT", birth.event.occurred);
+        }
+        for (tag, christenings) in [
+            ("CHR", &individual.christening),
+            ("CHRA", &individual.christening_adult),
+        ] {
+            for christening in christenings {
+                let start = self.individual_event(1, tag, &christening.event);
+                if let Some(family) = &christening.family {
+                    self.line(2, "FAMC", Some(&family.xref));
+                }
+                self.occurred(start, tag, christening.event.occurred);
+            }
+        }
+        for adoption in &individual.adoption {
+            let start = self.individual_event(1, "ADOP", &adoption.event);
+            if let Some(family) = &adoption.family {
+                self.line(2, "FAMC", Some(&family.xref));
+                if let Some(adopted_by) = &family.adopted_by {
+                    self.line(3, "ADOP", Some(adopted_by_value(adopted_by)));
+                }
+            }
+            self.occurred(start, "ADOP", adoption.event.occurred);
+        }
+
+        let events = [
+            ("BAPM", &individual.baptism),
+            ("BARM", &individual.barmitzvah),
+            ("BASM", &individual.basmitzvah),
+            ("BLES", &individual.blessing),
+            ("CONF", &individual.confirmation),
+            ("GRAD", &individual.graduation),
+            ("EMIG", &individual.emigration),
+            ("IMMI", &individual.immigration),
+            ("NATU", &individual.naturalization),
+            ("OCCU", &individual.occupations),
+            ("CENS", &individual.census),
+            ("RETI", &individual.retirement),
+            ("EVEN", &individual.events),
+            ("CAST", &individual.caste),
+            ("DSCR", &individual.physical_description),
+            ("EDUC", &individual.education),
+            ("IDNO", &individual.id_numbers),
+            ("NATI", &individual.nationality),
+            ("NCHI", &individual.children_count),
+            ("NMR", &individual.marriage_count),
+            ("PROP", &individual.property),
+            ("RELI", &individual.religion),
+            ("TITL", &individual.titles),
+        ];
+        for (tag, events) in events {
+            for event in events {
+                let start = self.individual_event(1, tag, event);
+                self.occurred(start, tag, event.occurred);
+            }
+        }
+        if let Some(event) = &individual.first_communion {
+            let start = self.individual_event(1, "FCOM", event);
+            self.occurred(start, "FCOM", event.occurred);
+        }
+        for residence in &individual.residences {
+            match &residence.detail {
+                Some(detail) => self.family_event(1, "RESI", detail),
+                None => self.line(1, "RESI", None),
+            }
+        }
+
+        for death in &individual.death {
+            self.line(1, "DEAT", None);
+            let start = self.text.len();
+            if let Some(detail) = &death.event {
+                self.event_detail(2, detail);
+            }
+            self.age(2, &death.age);
+            if let Some(family) = &death.family {
+                self.line(2, "FAMC", Some(&family.xref));
+            }
+            self.occurred(start, "DEAT", death.occurred);
+        }
+        let events = [
+            ("BURI", &individual.burial),
+            ("CREM", &individual.cremation),
+            ("PROB", &individual.probate),
+            ("WILL", &individual.will),
+        ];
+        for (tag, events) in events {
+            for event in events {
+                let start = self.individual_event(1, tag, event);
+                self.occurred(start, tag, event.occurred);
+            }
+        }
+
+        for family in &individual.famc {
+            self.line(1, "FAMC", Some(&family.xref));
+            if let Some(pedigree) = &family.pedigree {
+                let pedigree = match self.version {
+                    Some(GedcomVersion::V70) => pedigree_value(pedigree).to_uppercase(),
+                    _ => pedigree_value(pedigree).to_string(),
+                };
+                self.line(2, "PEDI", Some(&pedigree));
+            }
+            self.notes(2, &family.notes);
+        }
+        for family in &individual.fams {
+            self.line(1, "FAMS", Some(&family.xref));
+            self.notes(2, &family.notes);
+        }
+
+        self.notes(1, &individual.notes);
+        for source in &individual.sources {
+            self.source_citation(1, source);
+        }
+        for association in &individual.associations {
+            self.association(1, association);
+        }
+        self.all(1, "SUBM", &individual.submitters);
+        for alias in &individual.aliases {
+            self.line(1, "ALIA", Some(alias));
+        }
+        self.all(1, "ANCI", &individual.ancestor_interest);
+        self.all(1, "DESI", &individual.descendant_interest);
+        for media in &individual.media {
+            self.media(1, media);
+        }
+        self.uids(&individual.uids);
+        self.change_date(1, &individual.change_date);
+    }
+
+    fn family(&mut self, family: &Family) {
+        self.record(&family.xref, "FAM");
+        self.optional(1, "RESN", &family.restriction_notice);
+
+        for (tag, spouse) in [("HUSB", &family.husband), ("WIFE", &family.wife)] {
+            let xref = spouse
+                .as_ref()
+                .and_then(|s| s.xref.as_ref())
+                .and_then(|x| x.xref.as_deref());
+            if let Some(xref) = xref {
+                self.line(1, tag, Some(xref));
+            }
+        }
+        self.all(1, "CHIL", &family.children);
+        if let Some(count) = family.number_of_children {
+            self.line(1, "NCHI", Some(&count.to_string()));
+        }
+        self.all(1, "SUBM", &family.submitters);
+
+        let events = [
+            ("ENGA", &family.engagement),
+            ("MARB", &family.marriage_banns),
+            ("MARC", &family.marriage_contract),
+            ("MARL", &family.marriage_license),
+            ("MARS", &family.marriage_settlement),
+            ("MARR", &family.marriage),
+            ("RESI", &family.residences),
+            ("CENS", &family.census),
+            ("EVEN", &family.events),
+            ("DIVF", &family.divorce_filed),
+            ("DIV", &family.divorce),
+            ("ANUL", &family.annulment),
+        ];
+        for (tag, events) in events {
+            for event in events {
+                self.family_event(1, tag, event);
+            }
+        }
+
+        self.notes(1, &family.notes);
+        for source in &family.sources {
+            self.source_citation(1, source);
+        }
+        for media in &family.media {
+            self.media(1, media);
+        }
+        self.uids(&family.uids);
+        self.change_date(1, &family.change_date);
+    }
+
+    fn source_record(&mut self, source: &SourceRecord) {
+        self.record(&source.xref, "SOUR");
+        for (tag, value) in [
+            ("TITL", &source.title),
+            ("AUTH", &source.author),
+            ("ABBR", &source.abbreviation),
+            ("PUBL", &source.publication),
+            ("TEXT", &source.text),
+        ] {
+            if let Some(value) = value {
+                self.text(1, tag, value);
+            }
+        }
+        for repository in &source.repositories {
+            self.repository_citation(1, repository);
+        }
+        self.notes(1, &source.notes);
+        for media in &source.media {
+            self.media(1, media);
+        }
+        self.uids(&source.uids);
+        self.change_date(1, &source.change_date);
+    }
+
+    fn repository_citation(&mut self, level: u8, citation: &RepositoryCitation) {
+        self.line(level, "REPO", citation.xref.as_deref());
+        self.notes(level + 1, &citation.notes);
+        for call_number in &citation.call_numbers {
+            self.line(level + 1, "CALN", call_number.number.as_deref());
+            if let Some(media) = &call_number.media {
+                self.line(level + 2, "MEDI", Some(&media.to_string()));
+            }
+        }
+    }
+
+    fn repository(&mut self, repository: &RepositoryRecord) {
+        self.record(&repository.xref, "REPO");
+        self.optional(1, "NAME", &repository.name);
+        if let Some(address) = &repository.address {
+            if address.addr1.is_some() || address.city.is_some() {
+                self.address(1, address);
+            }
+            self.contacts(1, address);
+        }
+        self.notes(1, &repository.notes);
+        self.references(&repository.references);
+        self.optional(1, "RIN", &repository.rin);
+        self.uids(&repository.uids);
+        self.change_date(1, &repository.change_date);
+    }
+
+    fn multimedia_record(&mut self, multimedia: &MultimediaRecord) {
+        self.record(&multimedia.xref, "OBJE");
+        self.media_files(1, &multimedia.files);
+        self.optional(1, "TITL", &multimedia.title);
+        self.notes(1, &multimedia.notes);
+        self.uids(&multimedia.uids);
+        self.change_date(1, &multimedia.change_date);
+    }
+
+    /// A NOTE record, which 7.0 calls SNOTE, with its text on the first line
+    fn note_record(&mut self, note: &NoteRecord) {
+        let tag = match self.version {
+            Some(GedcomVersion::V70) => "SNOTE",
+            _ => "NOTE",
+        };
+        self.xref = Some(note.xref.clone());
+        let first = format!("{} {}", note.xref, tag);
+        self.text(0, &first, note.text.as_deref().unwrap_or_default());
+        self.references(&note.references);
+        self.optional(1, "RIN", &note.rin);
+        for source in &note.sources {
+            self.source_citation(1, source);
+        }
+        self.uids(&note.uids);
+        self.change_date(1, &note.change_date);
+    }
+
+    /// A record's REFNs, each with its TYPE
+    fn references(&mut self, references: &[UserReference]) {
+        for reference in references {
+            self.line(1, "REFN", Some(&reference.number));
+            self.optional(2, "TYPE", &reference.r#type);
+        }
+    }
+
+    /// A record's UIDs: UID in GEDCOM 7, and _UID, as programs write them,
+    /// before that
+    fn uids(&mut self, uids: &[String]) {
+        let tag = match self.version.or(self.read_version) {
+            Some(GedcomVersion::V70) => "UID",
+            _ => "_UID",
+        };
+        self.all(1, tag, uids);
+    }
+
+    /// An ASSO, of an individual or, at a deeper `level`, an event. 7.0 is
+    /// the first version to allow them on events; before it, they're
+    /// written as _ASSO.
+    fn association(&mut self, level: u8, association: &Association) {
+        let tag = match (level, self.version) {
+            (1, _) | (_, Some(GedcomVersion::V70)) => "ASSO",
+            _ => "_ASSO",
+        };
+        self.line(level, tag, Some(&association.xref));
+        if let Some(relation) = &association.relation {
+            if self.version == Some(GedcomVersion::V70) {
+                // 7.0 has a fixed list of roles, and a phrase for the rest
+                let role = relation.to_uppercase();
+                if ROLES.contains(&role.as_str()) {
+                    self.line(level + 1, "ROLE", Some(&role));
+                } else {
+                    self.line(level + 1, "ROLE", Some("OTHER"));
+                    self.text(level + 2, "PHRASE", relation);
+                }
+            } else {
+                self.text(level + 1, "RELA", relation);
+            }
+        }
+        self.notes(level + 1, &association.notes);
+        for source in &association.sources {
+            self.source_citation(level + 1, source);
+        }
+    }
+
+    fn name(&mut self, level: u8, tag: &str, name: &Name) {
+        self.line(level, tag, name.value.as_deref());
+        self.optional(level + 1, "TYPE", &name.r#type);
+        self.optional(level + 1, "NPFX", &name.prefix);
+        self.optional(level + 1, "GIVN", &name.given);
+        self.optional(level + 1, "NICK", &name.nickname);
+        self.optional(level + 1, "SPFX", &name.surname_prefix);
+        self.optional(level + 1, "SURN", &name.surname);
+        self.optional(level + 1, "NSFX", &name.suffix);
+        self.note(level + 1, &name.note);
+    }
+
+    /// Write an event and its detail. Returns where the detail started, for
+    /// `occurred`, since the caller may have more to add to it.
+    fn individual_event(&mut self, level: u8, tag: &str, event: &IndividualEventDetail) -> usize {
+        self.line(level, tag, event.value.as_deref());
+        let start = self.text.len();
+        self.event_detail(level + 1, &event.detail);
+        self.age(level + 1, &event.age);
+        start
+    }
+
+    /// An event we know happened, but nothing else about, is written as
+    /// i.e. `1 DEAT Y`: where it was flagged so, or where the specification
+    /// allows it
+    fn occurred(&mut self, start: usize, tag: &str, flagged: bool) {
+        if self.text.len() == start && (flagged || matches!(tag, "BIRT" | "CHR" | "DEAT" | "MARR"))
+        {
+            self.text.pop();
+            self.text += " Y\n";
+        }
+    }
+
+    fn family_event(&mut self, level: u8, tag: &str, event: &FamilyEventDetail) {
+        self.line(level, tag, None);
+        let start = self.text.len();
+        for (tag, spouse) in [("HUSB", &event.husband), ("WIFE", &event.wife)] {
+            if let Some(spouse) = spouse.as_ref().filter(|s| s.age.is_some()) {
+                self.line(level + 1, tag, None);
+                self.age(level + 2, &spouse.age);
+            }
+        }
+        if let Some(detail) = &event.detail {
+            self.event_detail(level + 1, detail);
+        }
+        self.occurred(start, tag, event.occurred);
+    }
+
+    fn event_detail(&mut self, level: u8, detail: &EventDetail) {
+        self.optional(level, "TYPE", &detail.r#type);
+        self.date(level, &detail.date);
+        if let Some(place) = &detail.place {
+            self.place(level, place);
+        }
+        if let Some(address) = &detail.address {
+            self.address(level, address);
+        }
+        self.all(level, "PHON", &detail.phone);
+        self.all(level, "EMAIL", &detail.email);
+        self.all(level, "FAX", &detail.fax);
+        self.all(level, "WWW", &detail.www);
+        self.optional(level, "AGNC", &detail.agency);
+        self.optional(level, "RELI", &detail.religion);
+        self.optional(level, "CAUS", &detail.cause);
+        self.optional(level, "RESN", &detail.restriction_notice);
+        if let Some(note) = &detail.note {
+            self.note_text(level, note);
+        }
+        for source in &detail.sources {
+            self.source_citation(level, source);
+        }
+        for media in &detail.media {
+            self.media(level, media);
+        }
+        for association in &detail.associations {
+            self.association(level, association);
+        }
+    }
+
+    fn place(&mut self, level: u8, place: &Place) {
+        self.line(level, "PLAC", place.name.as_deref());
+        if !place.form.is_empty() {
+            self.line(level + 1, "FORM", Some(&place.form.join(", ")));
+        }
+        for (tag, variation) in [("FONE", &place.phonetic), ("ROMN", &place.roman)] {
+            if let Some(variation) = variation.as_ref().filter(|_| self.supports(tag)) {
+                self.line(level + 1, tag, variation.name.as_deref());
+                self.optional(level + 2, "TYPE", &variation.r#type);
+            }
+        }
+        if let Some(map) = place.map.as_ref().filter(|_| self.supports("MAP")) {
+            let latitude = if map.latitude < 0.0 { 'S' } else { 'N' };
+            let longitude = if map.longitude < 0.0 { 'W' } else { 'E' };
+            self.line(level + 1, "MAP", None);
+            self.line(
+                level + 2,
+                "LATI",
+                Some(&format!("{}{}", latitude, map.latitude.abs())),
+            );
+            self.line(
+                level + 2,
+                "LONG",
+                Some(&format!("{}{}", longitude, map.longitude.abs())),
+            );
+        }
+        self.note(level + 1, &place.note);
+    }
+
+    fn address(&mut self, level: u8, address: &Address) {
+        match &address.addr1 {
+            Some(addr) => self.text(level, "ADDR", addr),
+            None => self.line(level, "ADDR", None),
+        }
+        self.optional(level + 1, "ADR2", &address.addr2);
+        self.optional(level + 1, "ADR3", &address.addr3);
+        self.optional(level + 1, "CITY", &address.city);
+        self.optional(level + 1, "STAE", &address.state);
+        self.optional(level + 1, "POST", &address.postal_code);
+        self.optional(level + 1, "CTRY", &address.country);
+    }
+
+    /// The phone numbers, emails, faxes and web pages kept with an address
+    fn contacts(&mut self, level: u8, address: &Address) {
+        self.all(level, "PHON", &address.phone);
+        self.all(level, "EMAIL", &address.email);
+        self.all(level, "FAX", &address.fax);
+        self.all(level, "WWW", &address.www);
+    }
+
+    fn source_citation(&mut self, level: u8, citation: &SourceCitation) {
+        self.line(level, "SOUR", citation.xref.as_deref());
+        if let Some(page) = &citation.page {
+            self.line(level + 1, "PAGE", Some(page));
+        }
+        if let Some(event) = &citation.event {
+            self.line(level + 1, "EVEN", event.r#type.as_deref());
+            self.optional(level + 2, "ROLE", &event.role);
+        }
+        if let Some(data) = &citation.data {
+            self.line(level + 1, "DATA", None);
+            self.date(level + 2, &data.date);
+            if let Some(text) = data.text.as_ref().and_then(|t| t.note.as_ref()) {
+                self.text(level + 2, "TEXT", text);
+            }
+        }
+        for media in &citation.media {
+            self.media(level + 1, media);
+        }
+        self.note(level + 1, &citation.note);
+        if let Some(quay) = &citation.quay {
+            self.line(level + 1, "QUAY", Some(quay_value(quay)));
+        }
+    }
+
+    /// Write a multimedia link, or the files it describes
+    fn media(&mut self, level: u8, media: &Object) {
+        self.line(level, "OBJE", media.xref.as_deref());
+        self.media_files(level + 1, &media.files);
+        self.optional(level + 1, "TITL", &media.title);
+    }
+
+    fn media_files(&mut self, level: u8, files: &[MediaFile]) {
+        for file in files {
+            self.line(level, "FILE", file.path.as_deref());
+            if let Some(format) = &file.format {
+                self.line(level + 1, "FORM", Some(format));
+                self.optional(level + 2, "MEDI", &file.media_type);
+            }
+            self.optional(level + 1, "TITL", &file.title);
+        }
+    }
+
+    /// Write a DATE and its TIME, in the form the specification gives, i.e.
+    /// 13:57:24.800. A time that can't be read is kept as it is, except when
+    /// writing a particular version, where it's left out.
+    fn date_time(&mut self, level: u8, date: &DateTime) {
+        self.line(level, "DATE", date.date.as_deref());
+        match date.parsed_time() {
+            Some(mut time) => {
+                // Only 7.0 has time zones
+                if self.version.is_some_and(|v| v < GedcomVersion::V70) {
+                    time.utc = false;
+                }
+                self.line(level + 1, "TIME", Some(&time.to_string()));
+            }
+            None if self.version.is_some() => {
+                if let Some(time) = &date.time {
+                    let message = format!("\"{}\" isn't a valid time", time);
+                    self.warn("TIME", message);
+                }
+            }
+            None => self.optional(level + 1, "TIME", &date.time),
+        }
+    }
+
+    fn change_date(&mut self, level: u8, date: &Option<DateTime>) {
+        if let Some(date) = date {
+            self.line(level, "CHAN", None);
+            self.date_time(level + 1, date);
+        }
+    }
+
+    /// Write a DATE in the form the version being written uses
+    fn date(&mut self, level: u8, date: &Option<String>) {
+        let date = match date {
+            Some(date) => date,
+            None => return,
+        };
+        match self.version {
+            None => self.text(level, "DATE", date),
+            Some(GedcomVersion::V70) => {
+                let (date, phrase) = date_v7(date);
+                self.line(level, "DATE", Some(&date));
+                self.optional(level + 1, "PHRASE", &phrase);
+            }
+            Some(_) => self.line(level, "DATE", Some(&date_v5(date))),
+        }
+    }
+
+    /// Write an AGE in the form the version being written uses: 7.0 has no
+    /// CHILD, INFANT or STILLBORN, and earlier versions no weeks
+    fn age(&mut self, level: u8, age: &Option<String>) {
+        let age = match age {
+            Some(age) => age,
+            None => return,
+        };
+        let keyword = age.trim().starts_with(|c: char| c.is_ascii_alphabetic());
+        let weeks = age.contains(['w', 'W']);
+        let value = match (self.version, GedcomAge::from_str(age)) {
+            (Some(GedcomVersion::V70), Ok(parsed)) if keyword => parsed.to_string(),
+            (Some(version), Ok(parsed)) if version < GedcomVersion::V70 && weeks => {
+                parsed.to_string()
+            }
+            _ => age.to_string(),
+        };
+        self.line(level, "AGE", Some(&value));
+    }
+
+    /// Write a note, which 7.0 calls SNOTE when it points to a NOTE record
+    fn note_text(&mut self, level: u8, text: &str) {
+        let pointer = text.len() > 2 && text.starts_with('@') && text.ends_with('@');
+        match self.version {
+            Some(GedcomVersion::V70) if pointer => self.line(level, "SNOTE", Some(text)),
+            _ => self.text(level, "NOTE", text),
+        }
+    }
+
+    fn note(&mut self, level: u8, note: &Option<Note>) {
+        if let Some(text) = note.as_ref().and_then(|n| n.note.as_ref()) {
+            self.note_text(level, text);
+        }
+    }
+
+    fn notes(&mut self, level: u8, notes: &[Note]) {
+        for note in notes {
+            if let Some(text) = &note.note {
+                self.note_text(level, text);
+            }
+        }
+    }
+}
+
+/// A 5.5 or 5.5.1 date as 7.0 writes it: with its calendar named rather
+/// than escaped, BCE for B.C., and any phrase, or the text of an
+/// interpreted (INT) date, moved to a PHRASE
+fn date_v7(value: &str) -> (String, Option<String>) {
+    let mut date = value.trim().to_string();
+    let mut phrase = None;
+    if let (Some(open), true) = (date.find('('), date.ends_with(')')) {
+        phrase = Some(date[open + 1..date.len() - 1].trim().to_string());
+        date = date[..open].trim().to_string();
+        if let Some(interpreted) = date.strip_prefix("INT ") {
+            date = interpreted.to_string();
+        }
+    }
+    for (escape, calendar) in CALENDARS {
+        date = date.replace(escape, calendar);
+    }
+    (date.replace("B.C.", "BCE"), phrase)
+}
+
+/// A 7.0 date as 5.5 and 5.5.1 write it. Dates that are already in that
+/// form are unchanged.
+fn date_v5(value: &str) -> String {
+    let words: Vec<&str> = value
+        .split_whitespace()
+        .map(|word| match word {
+            "BCE" => "B.C.",
+            _ => CALENDARS
+                .iter()
+                .find(|(_, calendar)| *calendar == word)
+                .map_or(word, |(escape, _)| escape),
+        })
+        .collect();
+    words.join(" ")
+}
+
+/// Split a value into pieces short enough for a GEDCOM line, never
+/// splitting next to a space, since many readers trim them
+fn split_value(value: &str) -> Vec<&str> {
+    let mut chunks = vec![];
+    let mut rest = value;
+    while rest.chars().count() > MAX_VALUE_LENGTH {
+        let mut split = rest
+            .char_indices()
+            .nth(MAX_VALUE_LENGTH)
+            .map(|(i, _)| i)
+            .unwrap_or(rest.len());
+        while split > 1 && (rest[split..].starts_with(' ') || rest[..split].ends_with(' ')) {
+            split = rest[..split]
+                .char_indices()
+                .last()
+                .map(|(i, _)| i)
+                .unwrap_or(0);
+        }
+        if split <= 1 {
+            break;
+        }
+        chunks.push(&rest[..split]);
+        rest = &rest[split..];
+    }
+    chunks.push(rest);
+    chunks
+}
+
+fn adopted_by_value(adopted_by: &AdoptedBy) -> &'static str {
+    match adopted_by {
+        AdoptedBy::Both => "BOTH",
+        AdoptedBy::Husband => "HUSB",
+        AdoptedBy::Wife => "WIFE",
+    }
+}
+
+fn pedigree_value(pedigree: &Pedigree) -> &'static str {
+    match pedigree {
+        Pedigree::Adopted => "adopted",
+        Pedigree::Birth => "birth",
+        Pedigree::Foster => "foster",
+        Pedigree::Sealing => "sealing",
+    }
+}
+
+fn quay_value(quay: &Quay) -> &'static str {
+    match quay {
+        Quay::Unreliable => "0",
+        Quay::Questionable => "1",
+        Quay::Secondary => "2",
+        Quay::Direct => "3",
+    }
+}
+
+/// How the model writes the record with `tag` and `xref`, or None if it
+/// has no such record
+fn record_text(gedcom: &Gedcom, tag: &str, xref: Option<&str>) -> Option<String> {
+    let mut writer = Writer {
+        read_version: gedcom.header.version(),
+        ..Default::default()
+    };
+    match (tag, xref) {
+        ("HEAD", _) => writer.header(&gedcom.header),
+        ("SUBM", Some(xref)) => writer.submitter(gedcom.find_submitter(xref)?),
+        ("INDI", Some(xref)) => writer.individual(gedcom.find_individual(xref)?),
+        ("FAM", Some(xref)) => writer.family(gedcom.find_family(xref)?),
+        ("SOUR", Some(xref)) => writer.source_record(gedcom.find_source(xref)?),
+        ("REPO", Some(xref)) => writer.repository(gedcom.find_repository(xref)?),
+        ("OBJE", Some(xref)) => writer.multimedia_record(gedcom.find_multimedia(xref)?),
+        ("NOTE" | "SNOTE", Some(xref)) => writer.note_record(gedcom.find_note(xref)?),
+        _ => return None,
+    }
+    Some(writer.text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::GedcomConfig;
+    use crate::parse::{parse_gedcom_from_reader_with_config, parse_gedcom_from_str};
+    use quickcheck::{Arbitrary, Gen, QuickCheck};
+
+    #[test]
+    fn write_records() {
+        let data = [
+            "0 HEAD",
+            "1 GEDC",
+            "2 VERS 5.5.1",
+            "2 FORM LINEAGE-LINKED",
+            "1 CHAR UTF-8",
+            "1 _HOME @I1@",
+            "0 @I1@ INDI",
+            "1 NAME Joe /Bloggs/",
+            "2 GIVN Joe",
+            "2 SURN Bloggs",
+            "1 SEX M",
+            "1 BIRT",
+            "2 DATE 1 JAN 1900",
+            "2 PLAC Salt Lake City, UT, USA",
+            "2 SOUR @S1@",
+            "3 QUAY 3",
+            "1 EDUC High school",
+            "2 DATE 1918",
+            "1 TITL Sir",
+            "1 DEAT Y",
+            "1 BURI Y",
+            "1 FAMS @F1@",
+            "0 @F1@ FAM",
+            "1 HUSB @I1@",
+            "1 CHIL @I2@",
+            "1 MARR",
+            "2 DATE 1925",
+            "0 TRLR",
+            "",
+        ]
+        .join("\n");
+
+        let gedcom = parse_gedcom_from_str(&data).unwrap();
+        assert!(write_gedcom(&gedcom) == data);
+    }
+
+    #[test]
+    fn write_older_version() {
+        let data = [
+            "0 HEAD",
+            "1 GEDC",
+            "2 VERS 7.0",
+            "0 @I1@ INDI",
+            "1 NAME Joe /Bloggs/",
+            "1 SEX X",
+            "1 BIRT",
+            "2 DATE JULIAN 1 JAN 1700",
+            "2 EMAIL joe@example.com",
+            "1 DEAT",
+            "2 AGE 2y 3w",
+            "0 TRLR",
+            "",
+        ]
+        .join("\n");
+        let gedcom = parse_gedcom_from_str(&data).unwrap();
+        assert!(gedcom.header.version() == Some(GedcomVersion::V70));
+
+        let (text, warnings) = write_gedcom_as(&gedcom, GedcomVersion::V551);
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(
+            lines[..5]
+                == [
+                    "0 HEAD",
+                    "1 GEDC",
+                    "2 VERS 5.5.1",
+                    "2 FORM LINEAGE-LINKED",
+                    "1 CHAR UTF-8"
+                ]
+        );
+        assert!(lines.contains(&"1 SEX U"));
+        assert!(lines.contains(&"2 DATE @#DJULIAN@ 1 JAN 1700"));
+        assert!(lines.contains(&"2 EMAIL joe@example.com"));
+        assert!(lines.contains(&"2 AGE 2y 21d"));
+        assert!(warnings.len() == 1);
+        assert!(warnings[0].xref.as_deref() == Some("@I1@") && warnings[0].tag == "SEX");
+
+        let (text, warnings) = write_gedcom_as(&gedcom, GedcomVersion::V55);
+        assert!(!text.contains("EMAIL"));
+        let tags: Vec<&str> = warnings.iter().map(|w| w.tag.as_str()).collect();
+        assert!(tags == ["CHAR", "SEX", "EMAIL"]);
+        assert!(parse_gedcom_from_str(&text).unwrap().header.version() == Some(GedcomVersion::V55));
+    }
+
+    #[test]
+    fn write_newer_version() {
+        let data = [
+            "0 HEAD",
+            "1 GEDC",
+            "2 VERS 5.5.1",
+            "2 FORM LINEAGE-LINKED",
+            "1 CHAR UTF-8",
+            "1 FILE family.ged",
+            "0 @I1@ INDI",
+            "1 NAME Joe /Bloggs/",
+            "1 BIRT",
+            "2 DATE INT 1900 (about the turn of the century)",
+            "2 NOTE @N1@",
+            "1 DEAT",
+            "2 DATE (after the war)",
+            "2 AGE INFANT",
+            "1 ASSO @I2@",
+            "2 RELA Godfather",
+            "1 ASSO @I3@",
+            "2 RELA friend",
+            "1 ALIA @I4@",
+            "1 FAMC @F1@",
+            "2 PEDI birth",
+            "0 @N1@ NOTE Baptised privately",
+            "1 RIN 12",
+            "0 TRLR",
+            "",
+        ]
+        .join("\n");
+        let mut gedcom = parse_gedcom_from_str(&data).unwrap();
+        gedcom.header.note = Some("word ".repeat(100));
+
+        let (text, warnings) = write_gedcom_as(&gedcom, GedcomVersion::V70);
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[..3] == ["0 HEAD", "1 GEDC", "2 VERS 7.0"]);
+        assert!(!text.contains("CHAR") && !text.contains("FILE") && !text.contains("CONC"));
+        assert!(lines.contains(&"2 DATE 1900"));
+        assert!(lines.contains(&"3 PHRASE about the turn of the century"));
+        assert!(lines.contains(&"2 SNOTE @N1@"));
+        assert!(lines.contains(&"2 DATE"));
+        assert!(lines.contains(&"3 PHRASE after the war"));
+        assert!(lines.contains(&"2 AGE < 1y"));
+        assert!(lines.contains(&"2 PEDI BIRTH"));
+        assert!(text.contains("1 ASSO @I2@\n2 ROLE OTHER\n3 PHRASE Godfather\n"));
+        assert!(text.contains("1 ASSO @I3@\n2 ROLE FRIEND\n1 ALIA @I4@\n"));
+        assert!(text.contains("0 @N1@ SNOTE Baptised privately\n0 TRLR"));
+        assert!(
+            warnings
+                == [
+                    VersionWarning {
+                        xref: None,
+                        tag: "FILE".to_string(),
+                        message: "FILE isn't part of GEDCOM 7.0, so was left out".to_string(),
+                    },
+                    VersionWarning {
+                        xref: Some("@N1@".to_string()),
+                        tag: "RIN".to_string(),
+                        message: "RIN isn't part of GEDCOM 7.0, so was left out".to_string(),
+                    },
+                ]
+        );
+        let written = parse_gedcom_from_str(&text).unwrap();
+        assert!(written.find_note("@N1@").unwrap().text.as_deref() == Some("Baptised privately"));
+
+        // Writing without a version leaves the model as it is
+        assert!(write_gedcom(&gedcom).contains("2 PEDI birth"));
+        assert!(write_gedcom(&gedcom).contains("1 ASSO @I3@\n2 RELA friend\n"));
+    }
+
+    #[test]
+    fn write_times() {
+        let data = [
+            "0 HEAD",
+            "1 GEDC",
+            "2 VERS 7.0",
+            "0 @I1@ INDI",
+            "1 NAME Joe /Bloggs/",
+            "1 CHAN",
+            "2 DATE 1 JAN 1998",
+            "3 TIME 1:57:24,8 PM",
+            "0 @I2@ INDI",
+            "1 NAME Joe /Bloggs/",
+            "1 CHAN",
+            "2 DATE 2 JAN 1998",
+            "3 TIME 12:00Z",
+            "0 @I3@ INDI",
+            "1 NAME Joe /Bloggs/",
+            "1 CHAN",
+            "2 DATE 3 JAN 1998",
+            "3 TIME noon",
+            "0 TRLR",
+        ]
+        .join("\n");
+        let gedcom = parse_gedcom_from_str(&data).unwrap();
+        assert!(gedcom.warnings.len() == 1);
+        assert!(gedcom.warnings[0]
+            .to_string()
+            .contains("@I3@ has an invalid time"));
+
+        let text = write_gedcom(&gedcom);
+        assert!(text.contains("3 TIME 13:57:24.800\n"));
+        assert!(text.contains("3 TIME 12:00:00Z\n"));
+        assert!(text.contains("3 TIME noon\n"));
+
+        let (text, warnings) = write_gedcom_as(&gedcom, GedcomVersion::V551);
+        assert!(text.contains("3 TIME 12:00:00\n"));
+        assert!(!text.contains("noon"));
+        assert!(warnings.len() == 1 && warnings[0].xref.as_deref() == Some("@I3@"));
+    }
+
+    #[test]
+    fn convert_dates() {
+        assert!(date_v7("@#DFRENCH R@ 1 VEND 1") == ("FRENCH_R 1 VEND 1".to_string(), None));
+        assert!(date_v7("ABT 44 B.C.") == ("ABT 44 BCE".to_string(), None));
+        assert!(date_v5("FRENCH_R 1 VEND 1") == "@#DFRENCH R@ 1 VEND 1");
+        assert!(date_v5("BEF 44 BCE") == "BEF 44 B.C.");
+        assert!(date_v5("@#DJULIAN@ 1700") == "@#DJULIAN@ 1700");
+    }
+
+    #[test]
+    fn write_long_text() {
+        let long = "word ".repeat(100) + "end";
+        let mut writer = Writer::default();
+        writer.text(1, "NOTE", &format!("{}\nsecond line", long));
+
+        let lines: Vec<&str> = writer.text.lines().collect();
+        assert!(lines.len() == 4);
+        assert!(lines[0].starts_with("1 NOTE word"));
+        assert!(lines[1].starts_with("2 CONC "));
+        assert!(lines[2].starts_with("2 CONC "));
+        assert!(lines[3] == "2 CONT second line");
+        assert!(lines.iter().all(|l| l.len() <= 255));
+
+        // Put back together, it's the same text
+        let joined: String = split_value(&long).concat();
+        assert!(joined == long);
+        assert!(split_value(&long)
+            .iter()
+            .all(|c| !c.starts_with(' ') && !c.ends_with(' ')));
+    }
+
+    /// Note text made of words, runs of spaces, and line breaks, often long
+    /// enough to need CONC
+    #[derive(Clone, Debug)]
+    struct NoteText(String);
+
+    impl Arbitrary for NoteText {
+        fn arbitrary(g: &mut Gen) -> NoteText {
+            let pieces = ["word", "é", " ", "   ", "\n", "a-long-unbroken-word"];
+            let len = usize::arbitrary(g) % 300;
+            NoteText((0..len).map(|_| *g.choose(&pieces).unwrap()).collect())
+        }
+    }
+
+    #[test]
+    fn write_notes_exactly() {
+        fn round_trip(note: NoteText) -> bool {
+            let gedcom = Gedcom {
+                header: Header {
+                    note: Some(note.0.clone()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            };
+            let config = GedcomConfig::new().preserve_whitespace();
+            let text = write_gedcom(&gedcom);
+            let parsed = parse_gedcom_from_reader_with_config(text.as_bytes(), &config).unwrap();
+            parsed.header.note == Some(note.0)
+        }
+        QuickCheck::new()
+            .tests(500)
+            .quickcheck(round_trip as fn(NoteText) -> bool);
+    }
+
+    #[test]
+    fn write_preserved() {
+        let config = GedcomConfig::new().preserve_unknown();
+
+        // Unchanged, the file is written exactly as it was read
+        let text = String::from_utf8(std::fs::read("./data/complete.ged").unwrap()).unwrap();
+        let gedcom = parse_gedcom_from_reader_with_config(text.as_bytes(), &config).unwrap();
+        assert!(write_gedcom(&gedcom) == text);
+
+        let text = [
+            "0 HEAD",
+            "1 GEDC",
+            "2 VERS 5.5.1",
+            "1 _HOME @I1@",
+            "0 @I1@ INDI",
+            "1 NAME Joe /Bloggs/",
+            "1 EDUC Grammar school",
+            "1 _CUSTOM  spaced  ",
+            "0 @I2@ INDI",
+            "1 NAME Ann /Bloggs/",
+            "1 SEX F",
+            "1 RELI Methodist",
+            "0 @F1@ FAM",
+            "1 HUSB @I1@",
+            "1 _STAT Married",
+            "0 @F2@ FAM",
+            "1 WIFE @I2@",
+            "0 _PLAC Somewhere",
+            "1 _GOV 1234",
+            "0 TRLR",
+            "",
+        ]
+        .join("\r\n");
+        let mut gedcom = parse_gedcom_from_reader_with_config(text.as_bytes(), &config).unwrap();
+        assert!(write_gedcom(&gedcom) == text);
+
+        // Only what's been changed is written from the model
+        gedcom.individuals[1].gender = Gender::Male;
+        gedcom.families.retain(|f| f.xref != "@F2@");
+        gedcom.individuals.push(Individual {
+            xref: Some("@I3@".to_string()),
+            ..Default::default()
+        });
+        let written = write_gedcom(&gedcom);
+        let expected = text
+            .replace(
+                "0 @I2@ INDI\r\n1 NAME Ann /Bloggs/\r\n1 SEX F\r\n1 RELI Methodist\r\n",
+                "",
+            )
+            .replace("0 @F2@ FAM\r\n1 WIFE @I2@\r\n", "")
+            .replace("0 TRLR", "0 @I3@ INDI\n0 TRLR");
+        let changed = written.replace(
+            &written[written.find("0 @I2@").unwrap()..written.find("0 @F1@").unwrap()],
+            "",
+        );
+        assert!(changed == expected);
+        assert!(written.contains("0 @I2@ INDI\n1 NAME Ann /Bloggs/\n"));
+        assert!(written.contains("1 SEX M\n"));
+
+        // Converting to another version writes the model
+        let (converted, _) = write_gedcom_as(&gedcom, GedcomVersion::V70);
+        assert!(!converted.contains("_CUSTOM"));
+    }
+}
@@ -0,0 +1,111 @@
+use std::env;
+use std::fs;
+use std::process::Command;
+
+fn gedcom_rs(args: &[&str]) -> Option<i32> {
+    Command::new(env!("CARGO_BIN_EXE_gedcom-rs"))
+        .args(args)
+        .output()
+        .unwrap()
+        .status
+        .code()
+}
+
+#[test]
+fn usage_exit_code() {
+    assert!(gedcom_rs(&["--help"]) == Some(0));
+    assert!(gedcom_rs(&[]) == Some(2));
+
+    let complete = "./data/complete.ged";
+    for (flag, value) in [
+        ("--format", "xml"),
+        ("--fail-on", "bogus"),
+        ("--limit", "x"),
+    ] {
+        let args = [complete, "validate", flag, value];
+        assert!(gedcom_rs(&args) == Some(2), "{:?}", args);
+    }
+    // Failing validation isn't a usage error
+    assert!(gedcom_rs(&[complete, "validate"]) == Some(0));
+    assert!(gedcom_rs(&[complete, "validate", "--fail-on", "warning"]) == Some(1));
+}
+
+/// A file with lines the model doesn't keep, and the path to write to
+fn unmodelled(name: &str) -> (String, String) {
+    let input = env::temp_dir().join(format!("gedcom-rs-cli-{}.ged", name));
+    let output = env::temp_dir().join(format!("gedcom-rs-cli-{}-out.ged", name));
+    let text = [
+        "0 HEAD",
+        "1 GEDC",
+        "2 VERS 5.5.1",
+        "0 @P1@ INDI",
+        "1 NAME Joe /Bloggs/",
+        "1 EDUC Grammar school",
+        "1 TITL Sir",
+        "1 RELI Methodist",
+        "1 FAMS @X7@",
+        "0 @X7@ FAM",
+        "1 HUSB @P1@",
+        "1 _CUSTOM Kept",
+        "0 TRLR",
+        "",
+    ]
+    .join("\n");
+    fs::write(&input, text).unwrap();
+    let path = |p: std::path::PathBuf| p.to_str().unwrap().to_string();
+    (path(input), path(output))
+}
+
+#[test]
+fn renumber_keeps_everything() {
+    let (input, output) = unmodelled("renumber");
+    assert!(gedcom_rs(&[&input, "renumber", &output]) == Some(0));
+    let written = fs::read_to_string(&output).unwrap();
+    let expected = fs::read_to_string(&input)
+        .unwrap()
+        .replace("@P1@", "@I1@")
+        .replace("@X7@", "@F1@");
+    fs::remove_file(&input).unwrap();
+    fs::remove_file(&output).unwrap();
+    assert!(written == expected);
+
+    // An unreadable file isn't renumbered into an empty one
+    let missing = "./data/does-not-exist.ged";
+    assert!(gedcom_rs(&[missing, "renumber", &output]) == Some(1));
+    assert!(fs::metadata(&output).is_err());
+}
+
+#[test]
+fn convert_unreadable() {
+    let output = env::temp_dir().join("gedcom-rs-cli-convert-out.ged");
+    let output = output.to_str().unwrap();
+    let missing = "./data/does-not-exist.ged";
+    assert!(gedcom_rs(&[missing, "convert", "7.0", output]) == Some(1));
+    assert!(fs::metadata(output).is_err());
+}
+
+#[test]
+fn anonymize_unreadable() {
+    let output = env::temp_dir().join("gedcom-rs-cli-anonymize-out.ged");
+    let output = output.to_str().unwrap();
+    let missing = "./data/does-not-exist.ged";
+    assert!(gedcom_rs(&[missing, "anonymize", output]) == Some(1));
+    assert!(fs::metadata(output).is_err());
+}
+
+#[test]
+fn home_keeps_everything() {
+    let (input, output) = unmodelled("home");
+    assert!(gedcom_rs(&[&input, "home", "@P1@", &output]) == Some(0));
+    let written = fs::read_to_string(&output).unwrap();
+    let expected = fs::read_to_string(&input)
+        .unwrap()
+        .replace("0 @P1@ INDI", "1 _HOME @P1@\n0 @P1@ INDI");
+    fs::remove_file(&input).unwrap();
+    fs::remove_file(&output).unwrap();
+    assert!(written == expected);
+
+    let missing = "./data/does-not-exist.ged";
+    assert!(gedcom_rs(&[missing, "home", "@P1@", &output]) == Some(1));
+    assert!(fs::metadata(&output).is_err());
+}